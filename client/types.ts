@@ -0,0 +1,205 @@
+// Types of the blog API's request and response bodies.
+// Generated by `server client-types`; do not edit.
+
+/** Category a post belongs to */
+export type PostCategory =
+  | "blog"
+  | "art"
+  | "reading";
+
+/** Which posts an admin listing includes, by whether they are published */
+export type Visibility =
+  | "published"
+  | "draft"
+  | "all";
+
+/** A post as returned by reads. Timestamps are RFC 3339 strings. */
+export interface Post {
+  id: number;
+  category: PostCategory;
+  title: string;
+  slug: string;
+  content: string;
+  description: string;
+  image_url: string | null;
+  external_url: string | null;
+  published: boolean;
+  meta_title: string;
+  meta_description: string;
+  og_image_url: string | null;
+  book_author: string | null;
+  isbn: string | null;
+  rating: number | null;
+  author_id: number | null;
+  language: string;
+  translation_of: number | null;
+  published_at: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+/** A post as returned to callers who can write posts */
+export interface AttributedPost extends Post {
+  created_by: number | null;
+  updated_by: number | null;
+}
+
+/** A post together with its tags */
+export interface PostWithTags extends Post {
+  tags: Tag[];
+}
+
+/** Body of `POST /posts` */
+export interface CreatePost {
+  category: PostCategory;
+  title: string;
+  slug: string;
+  content: string;
+  description: string;
+  image_url?: string | null;
+  external_url?: string | null;
+  published: boolean;
+  meta_title?: string | null;
+  meta_description?: string | null;
+  og_image_url?: string | null;
+  book_author?: string | null;
+  isbn?: string | null;
+  rating?: number | null;
+  language?: string;
+  translation_of?: number | null;
+  published_at?: string | null;
+  tags?: string[];
+}
+
+/** Body of `PUT /posts`, replacing every field */
+export interface UpdatePost {
+  id: number;
+  category: PostCategory;
+  title: string;
+  slug: string;
+  content: string;
+  description: string;
+  image_url?: string | null;
+  external_url?: string | null;
+  published: boolean;
+  meta_title?: string | null;
+  meta_description?: string | null;
+  og_image_url?: string | null;
+  book_author?: string | null;
+  isbn?: string | null;
+  rating?: number | null;
+  language?: string;
+  translation_of?: number | null;
+  published_at?: string | null;
+}
+
+/** Body of `PATCH /posts`. Missing fields are unchanged; null clears a nullable field. */
+export interface PatchPost {
+  id: number;
+  category?: PostCategory;
+  title?: string;
+  slug?: string;
+  content?: string;
+  description?: string;
+  image_url?: string | null;
+  external_url?: string | null;
+  published?: boolean;
+  meta_title?: string | null;
+  meta_description?: string | null;
+  og_image_url?: string | null;
+  book_author?: string | null;
+  isbn?: string | null;
+  rating?: number | null;
+  language?: string;
+  translation_of?: number | null;
+  published_at?: string;
+}
+
+/** Body of `PUT /posts/{id}/draft-content` */
+export interface DraftContent {
+  title?: string;
+  content?: string;
+  description?: string;
+}
+
+/** A category with its number of published posts */
+export interface CategorySummary {
+  category: PostCategory;
+  label: string;
+  published_posts: number;
+}
+
+/** A tag as returned by reads */
+export interface Tag {
+  id: number;
+  name: string;
+  slug: string;
+  created_at: string;
+  description: string | null;
+  color: string | null;
+}
+
+/** A tag with how many posts carry it */
+export interface TagWithPostCount extends Tag {
+  post_count: number;
+}
+
+/** A tag with how many posts it shares with another tag */
+export interface RelatedTag extends Tag {
+  shared_post_count: number;
+}
+
+/** Body of `POST /tags` and `PUT /tags/{id}` */
+export interface TagRequest {
+  name: string;
+  description?: string | null;
+  color?: string | null;
+}
+
+/** Body of `PATCH /tags/{id}`. Missing fields are unchanged; null clears a nullable field. */
+export interface PatchTag {
+  name?: string;
+  description?: string | null;
+  color?: string | null;
+}
+
+/** Stable machine-readable error code */
+export type ErrorCode =
+  | "not_found"
+  | "method_not_allowed"
+  | "duplicate_entry"
+  | "validation_error"
+  | "invalid_input"
+  | "malformed_body"
+  | "invalid_body"
+  | "unsupported_media_type"
+  | "unauthorized"
+  | "forbidden"
+  | "not_author"
+  | "rate_limited"
+  | "payload_too_large"
+  | "timeout"
+  | "internal_error"
+  | "configuration_error"
+  | "transaction_error"
+  | "migration_error"
+  | "database_error"
+  | "busy"
+  | "conflict";
+
+/** One rule a request broke */
+export interface FieldError {
+  field: string | null;
+  message: string;
+}
+
+/** Body of every error response */
+export interface ErrorResponse {
+  code: ErrorCode;
+  message: string;
+  field: string | null;
+  allowed_methods?: string[];
+  did_you_mean?: string[];
+  errors?: FieldError[];
+  request_id?: string;
+}
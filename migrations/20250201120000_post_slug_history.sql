@@ -0,0 +1,9 @@
+-- Previous slugs of renamed posts, so old links can be redirected
+CREATE TABLE post_slugs (
+    slug TEXT PRIMARY KEY,
+    post_id INTEGER NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_post_slugs_post_id ON post_slugs(post_id);
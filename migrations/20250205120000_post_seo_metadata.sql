@@ -0,0 +1,4 @@
+-- Optional SEO / Open Graph overrides; responses fall back to title/description
+ALTER TABLE posts ADD COLUMN meta_title TEXT;
+ALTER TABLE posts ADD COLUMN meta_description TEXT;
+ALTER TABLE posts ADD COLUMN og_image_url TEXT;
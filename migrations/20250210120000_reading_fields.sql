@@ -0,0 +1,4 @@
+-- Structured book details, only populated for posts in the 'reading' category
+ALTER TABLE posts ADD COLUMN book_author TEXT;
+ALTER TABLE posts ADD COLUMN isbn TEXT;
+ALTER TABLE posts ADD COLUMN rating INTEGER CHECK(rating IS NULL OR rating BETWEEN 1 AND 5);
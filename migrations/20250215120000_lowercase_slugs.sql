@@ -0,0 +1,25 @@
+-- Slugs are case-insensitive: store them in lowercase and prevent
+-- case-variant duplicates.
+
+-- Posts whose lowercased slug collides with an older post keep their ID as a
+-- suffix so the lowercase pass below cannot violate the unique constraint
+UPDATE posts
+SET slug = lower(slug) || '-' || id
+WHERE EXISTS (
+    SELECT 1 FROM posts older
+    WHERE lower(older.slug) = lower(posts.slug) AND older.id < posts.id
+);
+
+UPDATE posts SET slug = lower(slug) WHERE slug != lower(slug);
+
+-- Historical slugs that now collide are dropped rather than redirected
+DELETE FROM post_slugs
+WHERE EXISTS (
+    SELECT 1 FROM post_slugs newer
+    WHERE lower(newer.slug) = lower(post_slugs.slug) AND newer.rowid > post_slugs.rowid
+)
+OR lower(slug) IN (SELECT slug FROM posts);
+
+UPDATE post_slugs SET slug = lower(slug) WHERE slug != lower(slug);
+
+CREATE UNIQUE INDEX idx_posts_slug_nocase ON posts(slug COLLATE NOCASE);
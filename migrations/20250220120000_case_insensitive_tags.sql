@@ -0,0 +1,21 @@
+-- Tag names are unique regardless of case. Case variants created before this
+-- migration are merged into the oldest tag, keeping all their posts.
+
+-- Point every association at the oldest tag of its case-insensitive group
+INSERT OR IGNORE INTO post_tags (post_id, tag_id)
+SELECT pt.post_id, canonical.id
+FROM post_tags pt
+JOIN tags t ON t.id = pt.tag_id
+JOIN tags canonical ON canonical.id = (
+    SELECT MIN(variant.id) FROM tags variant WHERE lower(variant.name) = lower(t.name)
+)
+WHERE canonical.id != t.id;
+
+-- Drop the now-merged variants and their associations
+DELETE FROM post_tags
+WHERE tag_id NOT IN (SELECT MIN(id) FROM tags GROUP BY lower(name));
+
+DELETE FROM tags
+WHERE id NOT IN (SELECT MIN(id) FROM tags GROUP BY lower(name));
+
+CREATE UNIQUE INDEX idx_tags_name_nocase ON tags(name COLLATE NOCASE);
@@ -0,0 +1,3 @@
+-- Optional presentation details for tag pages and badges
+ALTER TABLE tags ADD COLUMN description TEXT;
+ALTER TABLE tags ADD COLUMN color TEXT;
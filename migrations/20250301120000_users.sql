@@ -0,0 +1,8 @@
+-- Accounts that can sign in to manage content
+CREATE TABLE users (
+    id INTEGER PRIMARY KEY,
+    email TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    password_hash TEXT NOT NULL,
+    role TEXT NOT NULL DEFAULT 'user' CHECK(role IN ('admin', 'user')),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,4 @@
+-- The user who wrote each post; existing posts and posts created with the
+-- API key have no author and can only be changed by admins
+ALTER TABLE posts ADD COLUMN author_id INTEGER REFERENCES users(id) ON DELETE SET NULL;
+CREATE INDEX idx_posts_author_id ON posts(author_id);
@@ -0,0 +1,10 @@
+-- Long-lived credentials for scripts such as CI. Only a SHA-256 hash of each
+-- key is stored; revoked keys are kept so they still show up in listings.
+CREATE TABLE api_keys (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_used_at DATETIME,
+    revoked_at DATETIME
+);
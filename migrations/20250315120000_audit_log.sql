@@ -0,0 +1,14 @@
+-- Who changed which post or tag, and how. Entries are written in the same
+-- transaction as the change and never reference the entity by foreign key,
+-- so they outlive deletions.
+CREATE TABLE audit_log (
+    id INTEGER PRIMARY KEY,
+    actor TEXT NOT NULL,
+    action TEXT NOT NULL CHECK(action IN ('create', 'update', 'patch', 'delete')),
+    entity_type TEXT NOT NULL CHECK(entity_type IN ('post', 'tag')),
+    entity_id INTEGER NOT NULL,
+    changes TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_audit_log_entity ON audit_log(entity_type, entity_id);
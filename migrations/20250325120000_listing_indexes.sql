@@ -0,0 +1,10 @@
+-- Indexes for the filtered listings: published posts of one category,
+-- newest first, and the posts carrying a tag.
+--
+-- Every insert, and every update touching an indexed column, now writes
+-- to two more b-trees. Posts and tag links are written rarely and listed
+-- on most requests, so the extra writes and disk space are worth it.
+CREATE INDEX idx_posts_published_category_created ON posts(published, category, created_at DESC);
+
+-- post_tags' primary key (post_id, tag_id) only helps lookups by post
+CREATE INDEX idx_post_tags_tag_post ON post_tags(tag_id, post_id);
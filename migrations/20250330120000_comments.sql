@@ -0,0 +1,17 @@
+-- Reader comments on posts. Every comment starts out pending and is only
+-- shown once an admin approves it; rejected ones are kept as spam. Emails
+-- are stored as SHA-256 hashes, enough to recognize a repeat commenter.
+CREATE TABLE comments (
+    id INTEGER PRIMARY KEY,
+    post_id INTEGER NOT NULL,
+    author_name TEXT NOT NULL,
+    author_email_hash TEXT NOT NULL,
+    body TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK(status IN ('pending', 'approved', 'spam')),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE
+);
+
+-- Serves both a post's approved comments and the moderation queue
+CREATE INDEX idx_comments_post_status ON comments(post_id, status, created_at);
+CREATE INDEX idx_comments_status ON comments(status, created_at);
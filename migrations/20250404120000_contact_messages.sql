@@ -0,0 +1,10 @@
+-- Messages sent through the contact form. read_at is set once an admin has
+-- read a message, so unread ones are those where it is NULL.
+CREATE TABLE contact_messages (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    email TEXT NOT NULL,
+    message TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    read_at DATETIME
+);
@@ -0,0 +1,11 @@
+-- Visits to posts' external URLs through /posts/{id}/visit, with the page
+-- the reader came from, cut to a bounded length
+CREATE TABLE link_clicks (
+    id INTEGER PRIMARY KEY,
+    post_id INTEGER NOT NULL,
+    referer TEXT,
+    clicked_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_link_clicks_post_clicked ON link_clicks(post_id, clicked_at);
@@ -0,0 +1,8 @@
+-- The language each post is written in, and the original post a
+-- translation was made from. Translations point at the original, never
+-- at another translation, so a post and its translations form one group.
+ALTER TABLE posts ADD COLUMN language TEXT NOT NULL DEFAULT 'en';
+ALTER TABLE posts ADD COLUMN translation_of INTEGER REFERENCES posts(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_posts_translation_of ON posts(translation_of);
+CREATE INDEX idx_posts_language_published_created ON posts(language, published, created_at DESC);
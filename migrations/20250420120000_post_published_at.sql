@@ -0,0 +1,14 @@
+-- When each post first went live. Set the first time a post is published
+-- and kept when it is unpublished, so republishing doesn't move it in the
+-- feeds. Posts already published count from the day they were written.
+ALTER TABLE posts ADD COLUMN published_at TIMESTAMP;
+UPDATE posts SET published_at = created_at WHERE published;
+
+-- Listings are ordered by publication date, falling back to the creation
+-- date for posts that were never published
+DROP INDEX idx_posts_published_category_created;
+DROP INDEX idx_posts_language_published_created;
+CREATE INDEX idx_posts_published_category_date
+    ON posts(published, category, COALESCE(published_at, created_at) DESC);
+CREATE INDEX idx_posts_language_published_date
+    ON posts(language, published, COALESCE(published_at, created_at) DESC);
@@ -0,0 +1,9 @@
+-- Who created each post and who changed it last, for accountability.
+-- Unlike author_id these record every writer: callers without a user
+-- account, such as API keys, are recorded as the configured system user,
+-- or left NULL when there is none. Existing posts start out attributed
+-- to their author.
+ALTER TABLE posts ADD COLUMN created_by INTEGER REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE posts ADD COLUMN updated_by INTEGER REFERENCES users(id) ON DELETE SET NULL;
+
+UPDATE posts SET created_by = author_id, updated_by = author_id;
@@ -0,0 +1,37 @@
+-- Tag names are stored trimmed, with every run of whitespace inside them
+-- collapsed to a single space. Tags whose names only differ in whitespace
+-- or case are merged into the oldest one, keeping all their posts, like
+-- the case variants in 20250220120000_case_insensitive_tags.sql.
+
+-- Names are at most 50 characters, so six passes collapse any run of spaces
+CREATE TEMP TABLE normalized_tag_names AS
+SELECT id, trim(
+    replace(replace(replace(replace(replace(replace(
+        replace(replace(replace(replace(replace(
+            name, char(9), ' '), char(10), ' '), char(11), ' '), char(12), ' '), char(13), ' '),
+    '  ', ' '), '  ', ' '), '  ', ' '), '  ', ' '), '  ', ' '), '  ', ' ')
+) AS name
+FROM tags;
+
+-- Point every association at the oldest tag of its group
+INSERT OR IGNORE INTO post_tags (post_id, tag_id)
+SELECT pt.post_id, canonical.id
+FROM post_tags pt
+JOIN normalized_tag_names t ON t.id = pt.tag_id
+JOIN normalized_tag_names canonical ON canonical.id = (
+    SELECT MIN(variant.id) FROM normalized_tag_names variant
+    WHERE lower(variant.name) = lower(t.name)
+)
+WHERE canonical.id != t.id;
+
+-- Drop the now-merged tags and their associations
+DELETE FROM post_tags
+WHERE tag_id NOT IN (SELECT MIN(id) FROM normalized_tag_names GROUP BY lower(name));
+
+DELETE FROM tags
+WHERE id NOT IN (SELECT MIN(id) FROM normalized_tag_names GROUP BY lower(name));
+
+UPDATE tags
+SET name = (SELECT n.name FROM normalized_tag_names n WHERE n.id = tags.id);
+
+DROP TABLE normalized_tag_names;
@@ -0,0 +1,45 @@
+-- Tags get a URL-safe slug derived from their name, following `tag_slug` in
+-- src/models/tag.rs: lowercase ASCII letters and digits, `+` spelled out as
+-- `plus`, and every other run of characters turned into a single hyphen.
+ALTER TABLE tags ADD COLUMN slug TEXT NOT NULL DEFAULT '';
+
+-- Map the name one character at a time
+CREATE TEMP TABLE tag_slugs AS
+WITH RECURSIVE mapped(id, rest, slug) AS (
+    SELECT id, lower(name), '' FROM tags
+    UNION ALL
+    SELECT id, substr(rest, 2), slug || CASE
+        WHEN substr(rest, 1, 1) BETWEEN 'a' AND 'z' THEN substr(rest, 1, 1)
+        WHEN substr(rest, 1, 1) BETWEEN '0' AND '9' THEN substr(rest, 1, 1)
+        WHEN substr(rest, 1, 1) = '+' THEN '-plus-'
+        ELSE '-'
+    END
+    FROM mapped
+    WHERE rest != ''
+)
+-- Names are at most 50 characters, so six passes collapse any run of hyphens
+SELECT id, trim(
+    replace(replace(replace(replace(replace(replace(
+        slug, '--', '-'), '--', '-'), '--', '-'), '--', '-'), '--', '-'), '--', '-'),
+    '-'
+) AS slug
+FROM mapped
+WHERE rest = '';
+
+UPDATE tag_slugs SET slug = 'tag' WHERE slug = '';
+
+-- The oldest tag keeps a shared slug, the others get their id appended
+UPDATE tags
+SET slug = (
+    SELECT CASE
+        WHEN s.id = (SELECT MIN(other.id) FROM tag_slugs other WHERE other.slug = s.slug)
+        THEN s.slug
+        ELSE s.slug || '-' || s.id
+    END
+    FROM tag_slugs s
+    WHERE s.id = tags.id
+);
+
+DROP TABLE tag_slugs;
+
+CREATE UNIQUE INDEX idx_tags_slug ON tags(slug);
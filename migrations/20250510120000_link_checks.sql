@@ -0,0 +1,18 @@
+-- Outcome of the latest check of each URL a post links to, kept by the
+-- background link checker. `status` is the HTTP status the URL answered
+-- with, or NULL when no answer came, with the reason in `error`.
+CREATE TABLE link_checks (
+    post_id INTEGER NOT NULL,
+    -- Which of the post's URLs: 'external_url' or 'image_url'
+    field TEXT NOT NULL,
+    -- The URL as it was when checked; the post may have changed it since
+    url TEXT NOT NULL,
+    status INTEGER,
+    error TEXT,
+    ok BOOLEAN NOT NULL,
+    checked_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (post_id, field),
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_link_checks_ok ON link_checks(ok);
@@ -0,0 +1,10 @@
+-- HTML rendered from each post's Markdown. `content_hash` covers the
+-- content and the renderer version it was rendered with, so a row whose
+-- hash no longer matches is stale and simply rendered again.
+CREATE TABLE rendered_cache (
+    post_id INTEGER PRIMARY KEY,
+    content_hash TEXT NOT NULL,
+    html TEXT NOT NULL,
+    rendered_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (post_id) REFERENCES posts(id) ON DELETE CASCADE
+);
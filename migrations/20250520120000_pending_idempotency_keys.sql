@@ -0,0 +1,3 @@
+-- A key is reserved while its first request runs, so a concurrent retry
+-- with the same key can't run the request a second time
+ALTER TABLE idempotency_keys ADD COLUMN pending BOOLEAN NOT NULL DEFAULT FALSE;
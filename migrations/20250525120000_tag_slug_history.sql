@@ -0,0 +1,9 @@
+-- Previous slugs of renamed tags, so old links can be redirected
+CREATE TABLE tag_slugs (
+    slug TEXT PRIMARY KEY,
+    tag_id INTEGER NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_tag_slugs_tag_id ON tag_slugs(tag_id);
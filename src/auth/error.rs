@@ -0,0 +1,29 @@
+use thiserror::Error;
+
+use crate::models::user::UserRole;
+
+/// Errors raised while authenticating a request or configuring tokens
+#[derive(Debug, Error)]
+pub enum AuthError {
+    #[error("Missing bearer token")]
+    MissingToken,
+
+    #[error("Token has expired")]
+    ExpiredToken,
+
+    #[error("Invalid token")]
+    InvalidToken,
+
+    /// Deliberately the same whether the email or the password was wrong
+    #[error("Invalid email or password")]
+    InvalidCredentials,
+
+    #[error("This action requires the {required} role")]
+    InsufficientRole { required: UserRole },
+
+    #[error("Only the post's author or an admin can change this post")]
+    NotAuthor,
+
+    #[error("Token signing failed: {0}")]
+    Signing(String),
+}
@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use thiserror::Error;
+use time::{Duration, OffsetDateTime};
+
+use crate::models::to_hex;
+
+/// Forms submitted sooner than this after the token was issued were filled
+/// in by a bot
+pub const MIN_FORM_AGE: Duration = Duration::seconds(3);
+
+/// How long a form token stays valid
+pub const MAX_FORM_AGE: Duration = Duration::hours(24);
+
+/// Keeps form token signatures apart from anything else signed with the
+/// same secret
+const CONTEXT: &[u8] = b"comment-form";
+
+/// Why a form token was refused
+#[derive(Debug, Error, PartialEq)]
+pub enum FormTokenError {
+    #[error("Invalid form token")]
+    Invalid,
+
+    #[error("Form token has expired; reload the form")]
+    Expired,
+
+    #[error("Form submitted too quickly")]
+    TooFast,
+}
+
+/// A token to embed in a comment form
+#[derive(Debug, Serialize)]
+pub struct FormToken {
+    pub form_token: String,
+    /// Seconds until the token expires
+    pub expires_in: i64,
+}
+
+/// Issues and checks the signed tokens comment forms carry, which record
+/// when the form was served and for which post
+#[derive(Clone)]
+pub struct FormTokens {
+    secret: Arc<[u8]>,
+}
+
+// The secret is left out of debug output
+impl std::fmt::Debug for FormTokens {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FormTokens").finish_non_exhaustive()
+    }
+}
+
+impl FormTokens {
+    pub fn new(secret: &[u8]) -> Self {
+        Self {
+            secret: secret.into(),
+        }
+    }
+
+    /// Issues a token for a form on the post, valid from now
+    pub fn issue(&self, post_id: i64) -> FormToken {
+        self.issue_at(post_id, OffsetDateTime::now_utc())
+    }
+
+    pub(crate) fn issue_at(&self, post_id: i64, issued_at: OffsetDateTime) -> FormToken {
+        let issued_at = issued_at.unix_timestamp();
+        let signature = to_hex(&self.mac(post_id, issued_at).finalize().into_bytes());
+        FormToken {
+            form_token: format!("{}.{}", issued_at, signature),
+            expires_in: MAX_FORM_AGE.whole_seconds(),
+        }
+    }
+
+    /// Checks that the token was issued by this server for the post, at
+    /// least `MIN_FORM_AGE` and at most `MAX_FORM_AGE` ago
+    pub fn verify(&self, post_id: i64, token: &str) -> Result<(), FormTokenError> {
+        self.verify_at(post_id, token, OffsetDateTime::now_utc())
+    }
+
+    fn verify_at(
+        &self,
+        post_id: i64,
+        token: &str,
+        now: OffsetDateTime,
+    ) -> Result<(), FormTokenError> {
+        let (issued_at, signature) = token.split_once('.').ok_or(FormTokenError::Invalid)?;
+        let issued_at: i64 = issued_at.parse().map_err(|_| FormTokenError::Invalid)?;
+        let signature = from_hex(signature).ok_or(FormTokenError::Invalid)?;
+        self.mac(post_id, issued_at)
+            .verify_slice(&signature)
+            .map_err(|_| FormTokenError::Invalid)?;
+
+        let age = now.unix_timestamp() - issued_at;
+        if age < MIN_FORM_AGE.whole_seconds() {
+            Err(FormTokenError::TooFast)
+        } else if age > MAX_FORM_AGE.whole_seconds() {
+            Err(FormTokenError::Expired)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn mac(&self, post_id: i64, issued_at: i64) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(CONTEXT);
+        mac.update(format!(":{}:{}", post_id, issued_at).as_bytes());
+        mac
+    }
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens() -> FormTokens {
+        FormTokens::new(b"test-secret")
+    }
+
+    #[test]
+    fn test_form_token_timing() {
+        let tokens = tokens();
+        let issued_at = OffsetDateTime::now_utc();
+        let token = tokens.issue_at(7, issued_at).form_token;
+
+        let verify = |after: Duration| tokens.verify_at(7, &token, issued_at + after);
+        assert_eq!(verify(Duration::ZERO), Err(FormTokenError::TooFast));
+        assert_eq!(verify(Duration::seconds(2)), Err(FormTokenError::TooFast));
+        assert_eq!(verify(MIN_FORM_AGE), Ok(()));
+        assert_eq!(verify(MAX_FORM_AGE), Ok(()));
+        assert_eq!(
+            verify(MAX_FORM_AGE + Duration::seconds(1)),
+            Err(FormTokenError::Expired)
+        );
+    }
+
+    #[test]
+    fn test_forged_form_tokens_rejected() {
+        let tokens = tokens();
+        let issued_at = OffsetDateTime::now_utc() - Duration::minutes(1);
+        let token = tokens.issue_at(7, issued_at).form_token;
+        let now = OffsetDateTime::now_utc();
+
+        // Tokens are tied to their post and to the server's secret
+        assert_eq!(
+            tokens.verify_at(8, &token, now),
+            Err(FormTokenError::Invalid)
+        );
+        assert_eq!(
+            FormTokens::new(b"other-secret").verify_at(7, &token, now),
+            Err(FormTokenError::Invalid)
+        );
+
+        // Backdating the timestamp breaks the signature
+        let (_, signature) = token.split_once('.').unwrap();
+        let backdated = format!("{}.{}", issued_at.unix_timestamp() - 60, signature);
+        assert_eq!(
+            tokens.verify_at(7, &backdated, now),
+            Err(FormTokenError::Invalid)
+        );
+
+        for garbage in ["", "123", "abc.def", "123.zz", "123.abc"] {
+            assert_eq!(
+                tokens.verify_at(7, garbage, now),
+                Err(FormTokenError::Invalid)
+            );
+        }
+    }
+
+    #[test]
+    fn test_debug_hides_secret() {
+        let debug = format!("{:?}", FormTokens::new(b"super-secret"));
+        assert!(!debug.contains("super-secret"));
+    }
+}
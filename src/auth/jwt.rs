@@ -0,0 +1,183 @@
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+};
+use jsonwebtoken::{errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+
+use super::{bearer_token, AuthError};
+use crate::{
+    handlers::errors::ApiError,
+    models::user::{User, UserRole},
+};
+
+/// Token lifetime when `JWT_EXPIRY_SECONDS` is not set
+pub const DEFAULT_TOKEN_EXPIRY: Duration = Duration::hours(1);
+
+/// Claims carried by an access token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Claims {
+    /// The user's ID; JWT subjects are strings
+    pub sub: String,
+    pub role: UserRole,
+    /// Issued at, in seconds since the Unix epoch
+    pub iat: i64,
+    /// Expires at, in seconds since the Unix epoch
+    pub exp: i64,
+}
+
+impl Claims {
+    /// The ID of the user the token was issued to
+    pub fn user_id(&self) -> Result<i64, AuthError> {
+        self.sub.parse().map_err(|_| AuthError::InvalidToken)
+    }
+}
+
+/// Signs and validates access tokens
+#[derive(Clone)]
+pub struct JwtAuth {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    expiry: Duration,
+}
+
+// The keys hold the secret, so they are left out of debug output
+impl std::fmt::Debug for JwtAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtAuth")
+            .field("expiry", &self.expiry)
+            .finish_non_exhaustive()
+    }
+}
+
+impl JwtAuth {
+    pub fn new(secret: &[u8], expiry: Duration) -> Self {
+        Self {
+            encoding: EncodingKey::from_secret(secret),
+            decoding: DecodingKey::from_secret(secret),
+            expiry,
+        }
+    }
+
+    /// How long issued tokens stay valid
+    pub fn expiry(&self) -> Duration {
+        self.expiry
+    }
+
+    /// Issues a token for the user, valid from now
+    pub fn issue(&self, user: &User) -> Result<String, AuthError> {
+        self.issue_at(user, OffsetDateTime::now_utc())
+    }
+
+    fn issue_at(&self, user: &User, issued_at: OffsetDateTime) -> Result<String, AuthError> {
+        let claims = Claims {
+            sub: user.id.to_string(),
+            role: user.role,
+            iat: issued_at.unix_timestamp(),
+            exp: (issued_at + self.expiry).unix_timestamp(),
+        };
+        jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &self.encoding)
+            .map_err(|e| AuthError::Signing(e.to_string()))
+    }
+
+    /// Checks the token's signature and expiry and returns its claims
+    pub fn verify(&self, token: &str) -> Result<Claims, AuthError> {
+        let validation = Validation::new(Algorithm::HS256);
+        jsonwebtoken::decode::<Claims>(token, &self.decoding, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| match e.kind() {
+                ErrorKind::ExpiredSignature => AuthError::ExpiredToken,
+                _ => AuthError::InvalidToken,
+            })
+    }
+}
+
+/// Extracts and validates the bearer token of a request
+impl<S> FromRequestParts<S> for Claims
+where
+    JwtAuth: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(&parts.headers).ok_or(AuthError::MissingToken)?;
+        Ok(JwtAuth::from_ref(state).verify(token)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_user() -> User {
+        User {
+            id: 42,
+            email: "writer@example.com".to_string(),
+            password_hash: String::new(),
+            role: UserRole::Admin,
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    fn test_auth() -> JwtAuth {
+        JwtAuth::new(b"test-secret", Duration::hours(1))
+    }
+
+    #[test]
+    fn test_token_round_trip() {
+        let auth = test_auth();
+        let token = auth.issue(&test_user()).unwrap();
+
+        let claims = auth.verify(&token).unwrap();
+        assert_eq!(claims.user_id().unwrap(), 42);
+        assert_eq!(claims.role, UserRole::Admin);
+        assert_eq!(claims.exp - claims.iat, 3600);
+    }
+
+    #[test]
+    fn test_expired_token_rejected() {
+        let auth = test_auth();
+        let issued_at = OffsetDateTime::now_utc() - Duration::hours(2);
+        let token = auth.issue_at(&test_user(), issued_at).unwrap();
+
+        assert!(matches!(auth.verify(&token), Err(AuthError::ExpiredToken)));
+    }
+
+    #[test]
+    fn test_tampered_token_rejected() {
+        let auth = test_auth();
+        let token = auth.issue(&test_user()).unwrap();
+
+        // Signed with another secret
+        let other = JwtAuth::new(b"other-secret", Duration::hours(1));
+        assert!(matches!(other.verify(&token), Err(AuthError::InvalidToken)));
+
+        // Payload swapped for one claiming a different user
+        let mut parts: Vec<&str> = token.split('.').collect();
+        let forged = other
+            .issue(&User {
+                id: 1,
+                ..test_user()
+            })
+            .unwrap();
+        let forged_payload = forged.split('.').nth(1).unwrap();
+        parts[1] = forged_payload;
+        assert!(matches!(
+            auth.verify(&parts.join(".")),
+            Err(AuthError::InvalidToken)
+        ));
+
+        assert!(matches!(
+            auth.verify("not-a-token"),
+            Err(AuthError::InvalidToken)
+        ));
+    }
+
+    #[test]
+    fn test_debug_hides_secret() {
+        let debug = format!("{:?}", JwtAuth::new(b"super-secret", Duration::hours(1)));
+        assert!(!debug.contains("super-secret"));
+    }
+}
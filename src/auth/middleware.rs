@@ -0,0 +1,294 @@
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+
+use super::{bearer_token, AuthError, AuthUser, JwtAuth};
+use crate::{
+    db::Database,
+    handlers::errors::ApiError,
+    models::{api_key::API_KEY_PREFIX, user::UserRole},
+};
+
+/// How mutating requests are authenticated, beyond access tokens
+#[derive(Clone, Default)]
+pub struct AuthPolicy {
+    api_key: Option<String>,
+    disabled: bool,
+}
+
+// The API key is a credential, so it is left out of debug output
+impl std::fmt::Debug for AuthPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthPolicy")
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("disabled", &self.disabled)
+            .finish()
+    }
+}
+
+impl AuthPolicy {
+    pub fn new(api_key: Option<String>, disabled: bool) -> Self {
+        Self { api_key, disabled }
+    }
+
+    /// Identifies the caller presenting `token`: the configured API key, a
+    /// key created through `/api-keys`, or an access token
+    async fn authenticate(
+        &self,
+        jwt: &JwtAuth,
+        db: &Database,
+        token: &str,
+    ) -> Result<Principal, ApiError> {
+        if self
+            .api_key
+            .as_deref()
+            .is_some_and(|key| constant_time_eq(key.as_bytes(), token.as_bytes()))
+        {
+            return Ok(Principal::ApiKey);
+        }
+
+        if token.starts_with(API_KEY_PREFIX) {
+            let key = db
+                .api_keys()
+                .find_active(token)
+                .await?
+                .ok_or(AuthError::InvalidToken)?;
+            record_key_use(db.clone(), key.id);
+            return Ok(Principal::ManagedKey { id: key.id });
+        }
+
+        let claims = jwt.verify(token)?;
+        Ok(Principal::User {
+            id: claims.user_id()?,
+            role: claims.role,
+        })
+    }
+}
+
+/// The authenticated caller of a request, stored in the request extensions
+/// by `require_auth`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Principal {
+    /// A user holding a valid access token
+    User { id: i64, role: UserRole },
+    /// A script using the configured API key; it acts as an admin
+    ApiKey,
+    /// A script using a key created through `/api-keys`; it acts as an admin
+    ManagedKey { id: i64 },
+    /// Any caller while authentication is disabled; it acts as an admin
+    Development,
+}
+
+impl Principal {
+    pub fn role(&self) -> UserRole {
+        match self {
+            Principal::User { role, .. } => *role,
+            Principal::ApiKey | Principal::ManagedKey { .. } | Principal::Development => {
+                UserRole::Admin
+            }
+        }
+    }
+}
+
+/// Requires authentication on every route except reads and login.
+///
+/// Callers authenticate with `Authorization: Bearer <token>`, where the
+/// token is an access token from `POST /auth/login`, an API key, or the
+/// configured API key. User and API key administration, the audit log and
+/// database maintenance additionally require the admin role. `/graphql`
+/// and the public listings that can include drafts take anonymous callers
+/// too, leaving GraphQL mutations and drafts to require a principal.
+pub async fn require_auth(
+    State(jwt): State<JwtAuth>,
+    State(policy): State<AuthPolicy>,
+    State(db): State<Database>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let path = request.uri().path();
+    let optional = is_optional(request.method(), path);
+    if is_public(request.method(), path) && !optional {
+        return Ok(next.run(request).await);
+    }
+    let required = required_role(path);
+
+    let principal = if policy.disabled {
+        Principal::Development
+    } else {
+        match bearer_token(request.headers()) {
+            Some(token) => policy.authenticate(&jwt, &db, token).await?,
+            // GraphQL queries are reads sent with POST, so anonymous callers
+            // get through; mutations and requests for drafts check for a
+            // principal themselves
+            None if optional => return Ok(next.run(request).await),
+            None => return Err(AuthError::MissingToken.into()),
+        }
+    };
+
+    AuthUser::from(&principal).require_role(required)?;
+
+    request.extensions_mut().insert(principal);
+    Ok(next.run(request).await)
+}
+
+/// Reads are public, except for administration; logging in, leaving a
+/// comment and sending a contact message need no token
+fn is_public(method: &Method, path: &str) -> bool {
+    let is_read = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+    path == "/auth/login"
+        || (is_read && !is_administration(path))
+        || (*method == Method::POST && (path == "/contact" || is_post_route(path, "comments")))
+}
+
+/// `/posts/{id}/{name}`, one of a single post's own routes
+fn is_post_route(path: &str, name: &str) -> bool {
+    path.strip_prefix("/posts/")
+        .and_then(|rest| rest.strip_suffix(name))
+        .and_then(|rest| rest.strip_suffix('/'))
+        .is_some_and(|id| !id.is_empty() && !id.contains('/'))
+}
+
+/// Routes that authenticate callers who present credentials and let the
+/// others through anonymously. These listings are public, but only
+/// authenticated callers may ask them for drafts.
+fn is_optional(method: &Method, path: &str) -> bool {
+    let is_read = matches!(*method, Method::GET | Method::HEAD);
+    path == "/graphql"
+        || (is_read
+            && (["/posts/changes", "/posts/untagged", "/export/posts.ndjson"].contains(&path)
+                || (path.starts_with("/tags/") && path.ends_with("/posts"))))
+}
+
+fn required_role(path: &str) -> UserRole {
+    if is_administration(path) {
+        UserRole::Admin
+    } else {
+        UserRole::User
+    }
+}
+
+/// User accounts, API keys, the audit log, contact messages, link click
+/// counts and database maintenance are for admins only
+fn is_administration(path: &str) -> bool {
+    ["/users", "/api-keys", "/audit", "/contact", "/admin"]
+        .iter()
+        .any(|prefix| path == *prefix || path.starts_with(&format!("{}/", prefix)))
+        || is_post_route(path, "clicks")
+}
+
+/// Updates a key's `last_used_at` in the background, so authenticating
+/// never waits on a write
+fn record_key_use(db: Database, id: i64) {
+    tokio::spawn(async move {
+        if let Err(e) = db.api_keys().touch(id).await {
+            tracing::warn!("Failed to record use of API key {}: {}", id, e);
+        }
+    });
+}
+
+/// Compares secrets in time independent of where they first differ
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db::test_utils::create_test_db, models::api_key::CreateApiKey};
+
+    #[test]
+    fn test_public_routes() {
+        assert!(is_public(&Method::GET, "/posts"));
+        assert!(is_public(&Method::HEAD, "/tags/1"));
+        assert!(is_public(&Method::POST, "/auth/login"));
+        assert!(!is_public(&Method::POST, "/posts"));
+        assert!(!is_public(&Method::DELETE, "/tags/1"));
+        assert!(!is_public(&Method::GET, "/users"));
+        assert!(!is_public(&Method::GET, "/users/1"));
+        assert!(!is_public(&Method::GET, "/api-keys"));
+        assert!(!is_public(&Method::GET, "/audit"));
+        assert!(is_public(&Method::GET, "/usersettings"));
+        assert!(is_public(&Method::POST, "/posts/1/comments"));
+        assert!(!is_public(&Method::DELETE, "/posts/1/comments"));
+        assert!(!is_public(&Method::POST, "/posts/1/tags/comments"));
+        assert!(!is_public(&Method::GET, "/admin/comments"));
+        assert!(is_public(&Method::POST, "/contact"));
+        assert!(is_public(&Method::GET, "/posts/1/visit"));
+        assert!(!is_public(&Method::GET, "/posts/1/clicks"));
+        assert!(!is_public(&Method::GET, "/contact"));
+        assert!(!is_public(&Method::PATCH, "/contact/1"));
+    }
+
+    #[test]
+    fn test_optional_routes() {
+        assert!(is_optional(&Method::POST, "/graphql"));
+        assert!(is_optional(&Method::GET, "/posts/changes"));
+        assert!(is_optional(&Method::GET, "/posts/untagged"));
+        assert!(is_optional(&Method::GET, "/export/posts.ndjson"));
+        assert!(is_optional(&Method::GET, "/tags/1/posts"));
+        assert!(is_optional(&Method::HEAD, "/tags/by-name/rust/posts"));
+        assert!(!is_optional(&Method::GET, "/posts"));
+        assert!(!is_optional(&Method::GET, "/tags/1"));
+        assert!(!is_optional(&Method::POST, "/posts/untagged"));
+    }
+
+    #[tokio::test]
+    async fn test_api_key_authentication() {
+        let db = create_test_db().await.unwrap();
+        let jwt = JwtAuth::new(b"test-secret", time::Duration::hours(1));
+        let policy = AuthPolicy::new(Some("ci-key".to_string()), false);
+
+        assert_eq!(
+            policy.authenticate(&jwt, &db, "ci-key").await.unwrap(),
+            Principal::ApiKey
+        );
+        assert!(matches!(
+            policy.authenticate(&jwt, &db, "ci-kez").await,
+            Err(ApiError::Auth(AuthError::InvalidToken))
+        ));
+        assert!(!format!("{:?}", policy).contains("ci-key"));
+
+        // Without a configured key, nothing but access tokens is accepted
+        assert!(AuthPolicy::default()
+            .authenticate(&jwt, &db, "")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_managed_key_authentication() {
+        let db = create_test_db().await.unwrap();
+        let jwt = JwtAuth::new(b"test-secret", time::Duration::hours(1));
+        let policy = AuthPolicy::default();
+
+        let created = db
+            .api_keys()
+            .create(CreateApiKey {
+                name: "CI".to_string(),
+            })
+            .await
+            .unwrap();
+        let id = created.api_key.id;
+
+        assert_eq!(
+            policy.authenticate(&jwt, &db, &created.key).await.unwrap(),
+            Principal::ManagedKey { id }
+        );
+
+        db.api_keys().revoke(id).await.unwrap();
+        assert!(matches!(
+            policy.authenticate(&jwt, &db, &created.key).await,
+            Err(ApiError::Auth(AuthError::InvalidToken))
+        ));
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+    }
+}
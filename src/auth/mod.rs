@@ -0,0 +1,27 @@
+//! Authentication for the API: JSON Web Tokens issued at login, an optional
+//! static API key for scripts, the middleware that enforces them on
+//! mutating routes, and the `AuthUser` extractor handlers use to check roles
+//! and post ownership. Anonymous comment forms carry signed tokens instead.
+
+mod error;
+mod form_token;
+mod jwt;
+mod middleware;
+mod user;
+
+use axum::http::{header, HeaderMap};
+
+pub use error::AuthError;
+pub use form_token::{FormToken, FormTokenError, FormTokens, MAX_FORM_AGE, MIN_FORM_AGE};
+pub use jwt::{Claims, JwtAuth, DEFAULT_TOKEN_EXPIRY};
+pub use middleware::{require_auth, AuthPolicy, Principal};
+pub use user::AuthUser;
+
+/// The token of an `Authorization: Bearer <token>` header, if present
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+}
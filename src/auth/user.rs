@@ -0,0 +1,117 @@
+use axum::{extract::FromRequestParts, http::request::Parts};
+
+use super::{AuthError, Principal};
+use crate::{
+    handlers::errors::ApiError,
+    models::{audit::Actor, user::UserRole},
+};
+
+/// The authenticated caller of a protected route, as seen by handlers.
+///
+/// Built from the `Principal` that `require_auth` stores in the request
+/// extensions. Callers using an API key, or any caller while authentication
+/// is disabled, are admins without a user ID.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AuthUser {
+    pub id: Option<i64>,
+    pub role: UserRole,
+    /// How the caller appears in the audit log
+    pub actor: Actor,
+}
+
+impl AuthUser {
+    pub fn is_admin(&self) -> bool {
+        self.role == UserRole::Admin
+    }
+
+    /// Fails with `InsufficientRole` unless the caller has the required
+    /// role; admins satisfy every role
+    pub fn require_role(&self, required: UserRole) -> Result<(), AuthError> {
+        if self.is_admin() || self.role == required {
+            Ok(())
+        } else {
+            Err(AuthError::InsufficientRole { required })
+        }
+    }
+
+    /// Fails with `NotAuthor` unless the caller is an admin or the author
+    /// of a post written by `author_id`
+    pub fn require_author(&self, author_id: Option<i64>) -> Result<(), AuthError> {
+        if self.is_admin() || (self.id.is_some() && self.id == author_id) {
+            Ok(())
+        } else {
+            Err(AuthError::NotAuthor)
+        }
+    }
+}
+
+impl From<&Principal> for AuthUser {
+    fn from(principal: &Principal) -> Self {
+        let (id, actor) = match principal {
+            Principal::User { id, .. } => (Some(*id), Actor::User(*id)),
+            Principal::ApiKey => (None, Actor::ApiKey(None)),
+            Principal::ManagedKey { id } => (None, Actor::ApiKey(Some(*id))),
+            Principal::Development => (None, Actor::Development),
+        };
+        Self {
+            id,
+            role: principal.role(),
+            actor,
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for AuthUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Principal>()
+            .map(AuthUser::from)
+            .ok_or_else(|| AuthError::MissingToken.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_require_role() {
+        let admin = AuthUser::from(&Principal::ApiKey);
+        let user = AuthUser::from(&Principal::User {
+            id: 7,
+            role: UserRole::User,
+        });
+
+        assert!(admin.require_role(UserRole::Admin).is_ok());
+        assert!(admin.require_role(UserRole::User).is_ok());
+        assert!(user.require_role(UserRole::User).is_ok());
+        assert!(matches!(
+            user.require_role(UserRole::Admin),
+            Err(AuthError::InsufficientRole {
+                required: UserRole::Admin
+            })
+        ));
+    }
+
+    #[test]
+    fn test_require_author() {
+        let user = AuthUser::from(&Principal::User {
+            id: 7,
+            role: UserRole::User,
+        });
+
+        assert!(user.require_author(Some(7)).is_ok());
+        assert!(matches!(
+            user.require_author(Some(8)),
+            Err(AuthError::NotAuthor)
+        ));
+        // Posts without an author belong to the admins
+        assert!(user.require_author(None).is_err());
+        assert!(AuthUser::from(&Principal::Development)
+            .require_author(None)
+            .is_ok());
+    }
+}
@@ -0,0 +1,280 @@
+//! Command line interface of the bundled binary: serving the API plus a
+//! few one-off tasks for deploy pipelines and operators, all sharing the
+//! same configuration and database setup
+
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use time::OffsetDateTime;
+
+use crate::{
+    client_types::client_types,
+    db::{Database, DatabaseError},
+    models::{
+        post::PostWithTags,
+        tag::{TagListOptions, TagWithPostCount},
+        user::{CreateUser, User, UserRole},
+    },
+};
+
+/// Command line arguments. Settings still come from the environment and
+/// `config.toml`; the arguments only pick what to do.
+#[derive(Debug, Parser)]
+#[command(version, about = "A JSON API for a blog")]
+pub struct Cli {
+    /// Defaults to `serve`, so deployments that start the binary without
+    /// arguments keep working
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    /// The subcommand to run
+    pub fn command(&self) -> &Command {
+        self.command.as_ref().unwrap_or(&Command::Serve)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Subcommand)]
+pub enum Command {
+    /// Serve the API (the default)
+    Serve,
+    /// Apply pending migrations and exit; exits non-zero if they fail
+    Migrate,
+    /// Write every post and tag to a JSON file
+    Export {
+        /// File to write the dump to; an existing file is overwritten
+        #[arg(long, short)]
+        out: PathBuf,
+    },
+    /// Create a user with the admin role
+    CreateAdmin {
+        #[arg(long)]
+        email: String,
+        /// Prompted for when neither this nor ADMIN_PASSWORD is given
+        #[arg(long, env = "ADMIN_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+    /// Write TypeScript declarations of the API's request and response
+    /// bodies
+    ClientTypes {
+        /// File to write the declarations to; an existing file is overwritten
+        #[arg(long, short)]
+        out: PathBuf,
+    },
+}
+
+/// Everything `export` writes
+#[derive(Debug, Serialize)]
+pub struct Dump {
+    #[serde(with = "time::serde::rfc3339")]
+    pub exported_at: OffsetDateTime,
+    /// Drafts included, in id order
+    pub posts: Vec<PostWithTags>,
+    /// In name order, with how many posts carry each
+    pub tags: Vec<TagWithPostCount>,
+}
+
+/// Reads every post and tag into a `Dump`
+pub async fn dump(db: &Database) -> Result<Dump, DatabaseError> {
+    let posts = db.posts().list_all().await?;
+    let ids: Vec<i64> = posts.iter().map(|post| post.id).collect();
+    let mut tags_by_post = db.tags().list_tags_for_posts(&ids).await?;
+    let posts = posts
+        .into_iter()
+        .map(|post| PostWithTags {
+            tags: tags_by_post.remove(&post.id).unwrap_or_default(),
+            post,
+        })
+        .collect();
+
+    let mut tags = Vec::new();
+    let mut options = TagListOptions {
+        include_post_count: true,
+        limit: 200,
+        ..TagListOptions::default()
+    };
+    loop {
+        let (page, total) = db.tags().list(&options).await?;
+        let done = page.is_empty() || options.offset + (page.len() as i64) >= total;
+        options.offset += page.len() as i64;
+        tags.extend(page);
+        if done {
+            break;
+        }
+    }
+
+    Ok(Dump {
+        exported_at: OffsetDateTime::now_utc(),
+        posts,
+        tags,
+    })
+}
+
+/// Writes a `Dump` of the database to `out` as JSON, returning it
+pub async fn export(db: &Database, out: &Path) -> Result<Dump, Box<dyn std::error::Error>> {
+    let dump = dump(db).await?;
+    let json = serde_json::to_vec_pretty(&dump)?;
+    tokio::fs::write(out, json).await?;
+    Ok(dump)
+}
+
+/// Writes the TypeScript declarations served at `/client/types.ts` to `out`
+pub fn write_client_types(out: &Path) -> std::io::Result<()> {
+    std::fs::write(out, client_types())
+}
+
+/// Creates a user with the admin role. The email and password are checked
+/// like any other user's.
+pub async fn create_admin(
+    db: &Database,
+    email: &str,
+    password: String,
+) -> Result<User, DatabaseError> {
+    db.users()
+        .create(CreateUser {
+            email: email.to_string(),
+            password,
+            role: UserRole::Admin,
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::post::CreatePost;
+
+    #[test]
+    fn test_parse_subcommands() {
+        let cli = Cli::try_parse_from(["server"]).unwrap();
+        assert_eq!(cli.command, None);
+        assert_eq!(cli.command(), &Command::Serve);
+
+        let cli = Cli::try_parse_from(["server", "serve"]).unwrap();
+        assert_eq!(cli.command(), &Command::Serve);
+
+        let cli = Cli::try_parse_from(["server", "migrate"]).unwrap();
+        assert_eq!(cli.command(), &Command::Migrate);
+
+        let cli = Cli::try_parse_from(["server", "export", "--out", "dump.json"]).unwrap();
+        assert_eq!(
+            cli.command(),
+            &Command::Export {
+                out: PathBuf::from("dump.json")
+            }
+        );
+        let cli = Cli::try_parse_from(["server", "export", "-o", "dump.json"]).unwrap();
+        assert_eq!(
+            cli.command(),
+            &Command::Export {
+                out: PathBuf::from("dump.json")
+            }
+        );
+
+        let cli = Cli::try_parse_from([
+            "server",
+            "create-admin",
+            "--email",
+            "admin@example.com",
+            "--password",
+            "correct horse",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.command(),
+            &Command::CreateAdmin {
+                email: "admin@example.com".to_string(),
+                password: Some("correct horse".to_string()),
+            }
+        );
+
+        let cli = Cli::try_parse_from(["server", "client-types", "-o", "types.ts"]).unwrap();
+        assert_eq!(
+            cli.command(),
+            &Command::ClientTypes {
+                out: PathBuf::from("types.ts")
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for args in [
+            &["server", "export"][..],
+            &["server", "client-types"],
+            &["server", "create-admin"],
+            &["server", "frobnicate"],
+            &["server", "migrate", "--force"],
+        ] {
+            assert!(Cli::try_parse_from(args).is_err(), "{:?}", args);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("blog.db").display());
+        let db = Database::connect(&url).await.unwrap();
+
+        let mut ids = Vec::new();
+        for (title, published) in [("First", true), ("Draft", false)] {
+            let post = db
+                .posts()
+                .create(
+                    CreatePost::builder()
+                        .title(title)
+                        .slug(title.to_lowercase())
+                        .content("Body")
+                        .description("Description")
+                        .published(published)
+                        .build()
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            ids.push(post.id);
+        }
+        let tag = db.tags().create("rust").await.unwrap();
+        db.tags().create("unused").await.unwrap();
+        db.tags().add_tag_to_post(ids[0], tag.id).await.unwrap();
+
+        let out = dir.path().join("dump.json");
+        export(&db, &out).await.unwrap();
+
+        let dump: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&out).unwrap()).unwrap();
+        let posts = dump["posts"].as_array().unwrap();
+        assert_eq!(posts.len(), 2);
+        assert_eq!(posts[0]["id"], ids[0]);
+        assert_eq!(posts[0]["title"], "First");
+        assert_eq!(posts[0]["tags"][0]["name"], "rust");
+        assert_eq!(posts[1]["title"], "Draft");
+        assert_eq!(posts[1]["published"], false);
+        assert_eq!(posts[1]["tags"], serde_json::json!([]));
+
+        let tags = dump["tags"].as_array().unwrap();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0]["name"], "rust");
+        assert_eq!(tags[0]["post_count"], 1);
+        assert_eq!(tags[1]["name"], "unused");
+        assert_eq!(tags[1]["post_count"], 0);
+        assert!(dump["exported_at"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_create_admin() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+
+        let user = create_admin(&db, "Admin@Example.com", "a long password".to_string())
+            .await
+            .unwrap();
+        assert_eq!(user.role, UserRole::Admin);
+        assert_eq!(user.email, "admin@example.com");
+
+        assert!(create_admin(&db, "other@example.com", "short".to_string())
+            .await
+            .is_err());
+    }
+}
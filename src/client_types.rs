@@ -0,0 +1,581 @@
+//! TypeScript declarations of the request and response bodies of the post,
+//! tag and category endpoints, for frontends to import instead of keeping
+//! their own copies in sync. Served at `GET /client/types.ts` and written by
+//! `server client-types`.
+//!
+//! The declarations are written out by hand; the tests check them against
+//! what serde actually reads and writes, so a renamed or added field fails
+//! the build rather than a client.
+
+use std::fmt::Write;
+
+use crate::{
+    handlers::errors::error_codes,
+    models::post::{PostCategory, Visibility},
+};
+
+/// One exported TypeScript declaration
+#[derive(Debug)]
+enum Declaration {
+    /// `export type Name = "a" | "b";`
+    Union {
+        name: &'static str,
+        doc: &'static str,
+        variants: Vec<String>,
+    },
+    /// `export interface Name extends Base { ... }`
+    Interface {
+        name: &'static str,
+        doc: &'static str,
+        extends: Option<&'static str>,
+        fields: Vec<Field>,
+    },
+}
+
+/// A property of an interface. `optional` properties may be left out of
+/// the JSON entirely, which is not the same as being `null`: nullable
+/// properties say so in their type.
+#[derive(Debug)]
+struct Field {
+    name: &'static str,
+    ty: &'static str,
+    optional: bool,
+}
+
+/// A property that is always present
+fn required(name: &'static str, ty: &'static str) -> Field {
+    Field {
+        name,
+        ty,
+        optional: false,
+    }
+}
+
+/// A property that may be missing
+fn optional(name: &'static str, ty: &'static str) -> Field {
+    Field {
+        name,
+        ty,
+        optional: true,
+    }
+}
+
+/// Fields a post is written with, shared by `CreatePost` and `UpdatePost`
+fn post_input_fields() -> Vec<Field> {
+    vec![
+        required("category", "PostCategory"),
+        required("title", "string"),
+        required("slug", "string"),
+        required("content", "string"),
+        required("description", "string"),
+        optional("image_url", "string | null"),
+        optional("external_url", "string | null"),
+        required("published", "boolean"),
+        optional("meta_title", "string | null"),
+        optional("meta_description", "string | null"),
+        optional("og_image_url", "string | null"),
+        optional("book_author", "string | null"),
+        optional("isbn", "string | null"),
+        optional("rating", "number | null"),
+        optional("language", "string"),
+        optional("translation_of", "number | null"),
+        optional("published_at", "string | null"),
+    ]
+}
+
+/// Every declaration, in the order they are written
+fn declarations() -> Vec<Declaration> {
+    use Declaration::{Interface, Union};
+
+    let quoted = |value: &dyn std::fmt::Display| format!("\"{}\"", value);
+
+    vec![
+        Union {
+            name: "PostCategory",
+            doc: "Category a post belongs to",
+            variants: PostCategory::all().iter().map(|c| quoted(c)).collect(),
+        },
+        Union {
+            name: "Visibility",
+            doc: "Which posts an admin listing includes, by whether they are published",
+            variants: [Visibility::Published, Visibility::Draft, Visibility::All]
+                .iter()
+                .map(|v| quoted(v))
+                .collect(),
+        },
+        Interface {
+            name: "Post",
+            doc: "A post as returned by reads. Timestamps are RFC 3339 strings.",
+            extends: None,
+            fields: vec![
+                required("id", "number"),
+                required("category", "PostCategory"),
+                required("title", "string"),
+                required("slug", "string"),
+                required("content", "string"),
+                required("description", "string"),
+                required("image_url", "string | null"),
+                required("external_url", "string | null"),
+                required("published", "boolean"),
+                required("meta_title", "string"),
+                required("meta_description", "string"),
+                required("og_image_url", "string | null"),
+                required("book_author", "string | null"),
+                required("isbn", "string | null"),
+                required("rating", "number | null"),
+                required("author_id", "number | null"),
+                required("language", "string"),
+                required("translation_of", "number | null"),
+                required("published_at", "string | null"),
+                required("created_at", "string"),
+                required("updated_at", "string"),
+            ],
+        },
+        Interface {
+            name: "AttributedPost",
+            doc: "A post as returned to callers who can write posts",
+            extends: Some("Post"),
+            fields: vec![
+                required("created_by", "number | null"),
+                required("updated_by", "number | null"),
+            ],
+        },
+        Interface {
+            name: "PostWithTags",
+            doc: "A post together with its tags",
+            extends: Some("Post"),
+            fields: vec![required("tags", "Tag[]")],
+        },
+        Interface {
+            name: "CreatePost",
+            doc: "Body of `POST /posts`",
+            extends: None,
+            fields: {
+                let mut fields = post_input_fields();
+                fields.push(optional("tags", "string[]"));
+                fields
+            },
+        },
+        Interface {
+            name: "UpdatePost",
+            doc: "Body of `PUT /posts`, replacing every field",
+            extends: None,
+            fields: {
+                let mut fields = vec![required("id", "number")];
+                fields.extend(post_input_fields());
+                fields
+            },
+        },
+        Interface {
+            name: "PatchPost",
+            doc: "Body of `PATCH /posts`. Missing fields are unchanged; null clears a nullable field.",
+            extends: None,
+            fields: vec![
+                required("id", "number"),
+                optional("category", "PostCategory"),
+                optional("title", "string"),
+                optional("slug", "string"),
+                optional("content", "string"),
+                optional("description", "string"),
+                optional("image_url", "string | null"),
+                optional("external_url", "string | null"),
+                optional("published", "boolean"),
+                optional("meta_title", "string | null"),
+                optional("meta_description", "string | null"),
+                optional("og_image_url", "string | null"),
+                optional("book_author", "string | null"),
+                optional("isbn", "string | null"),
+                optional("rating", "number | null"),
+                optional("language", "string"),
+                optional("translation_of", "number | null"),
+                optional("published_at", "string"),
+            ],
+        },
+        Interface {
+            name: "DraftContent",
+            doc: "Body of `PUT /posts/{id}/draft-content`",
+            extends: None,
+            fields: vec![
+                optional("title", "string"),
+                optional("content", "string"),
+                optional("description", "string"),
+            ],
+        },
+        Interface {
+            name: "CategorySummary",
+            doc: "A category with its number of published posts",
+            extends: None,
+            fields: vec![
+                required("category", "PostCategory"),
+                required("label", "string"),
+                required("published_posts", "number"),
+            ],
+        },
+        Interface {
+            name: "Tag",
+            doc: "A tag as returned by reads",
+            extends: None,
+            fields: vec![
+                required("id", "number"),
+                required("name", "string"),
+                required("slug", "string"),
+                required("created_at", "string"),
+                required("description", "string | null"),
+                required("color", "string | null"),
+            ],
+        },
+        Interface {
+            name: "TagWithPostCount",
+            doc: "A tag with how many posts carry it",
+            extends: Some("Tag"),
+            fields: vec![required("post_count", "number")],
+        },
+        Interface {
+            name: "RelatedTag",
+            doc: "A tag with how many posts it shares with another tag",
+            extends: Some("Tag"),
+            fields: vec![required("shared_post_count", "number")],
+        },
+        Interface {
+            name: "TagRequest",
+            doc: "Body of `POST /tags` and `PUT /tags/{id}`",
+            extends: None,
+            fields: vec![
+                required("name", "string"),
+                optional("description", "string | null"),
+                optional("color", "string | null"),
+            ],
+        },
+        Interface {
+            name: "PatchTag",
+            doc: "Body of `PATCH /tags/{id}`. Missing fields are unchanged; null clears a nullable field.",
+            extends: None,
+            fields: vec![
+                optional("name", "string"),
+                optional("description", "string | null"),
+                optional("color", "string | null"),
+            ],
+        },
+        Union {
+            name: "ErrorCode",
+            doc: "Stable machine-readable error code",
+            variants: error_codes().iter().map(|(code, _)| quoted(code)).collect(),
+        },
+        Interface {
+            name: "FieldError",
+            doc: "One rule a request broke",
+            extends: None,
+            fields: vec![
+                required("field", "string | null"),
+                required("message", "string"),
+            ],
+        },
+        Interface {
+            name: "ErrorResponse",
+            doc: "Body of every error response",
+            extends: None,
+            fields: vec![
+                required("code", "ErrorCode"),
+                required("message", "string"),
+                required("field", "string | null"),
+                optional("allowed_methods", "string[]"),
+                optional("did_you_mean", "string[]"),
+                optional("errors", "FieldError[]"),
+                optional("request_id", "string"),
+            ],
+        },
+    ]
+}
+
+/// The TypeScript module clients import
+pub fn client_types() -> String {
+    let mut out = String::from(
+        "// Types of the blog API's request and response bodies.\n\
+         // Generated by `server client-types`; do not edit.\n",
+    );
+    for declaration in declarations() {
+        out.push('\n');
+        match declaration {
+            Declaration::Union {
+                name,
+                doc,
+                variants,
+            } => {
+                let _ = writeln!(out, "/** {} */", doc);
+                let _ = write!(out, "export type {} =", name);
+                for variant in &variants {
+                    let _ = write!(out, "\n  | {}", variant);
+                }
+                out.push_str(";\n");
+            }
+            Declaration::Interface {
+                name,
+                doc,
+                extends,
+                fields,
+            } => {
+                let _ = writeln!(out, "/** {} */", doc);
+                match extends {
+                    Some(base) => {
+                        let _ = writeln!(out, "export interface {} extends {} {{", name, base);
+                    }
+                    None => {
+                        let _ = writeln!(out, "export interface {} {{", name);
+                    }
+                }
+                for field in &fields {
+                    let marker = if field.optional { "?" } else { "" };
+                    let _ = writeln!(out, "  {}{}: {};", field.name, marker, field.ty);
+                }
+                out.push_str("}\n");
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{de::DeserializeOwned, Serialize};
+    use serde_json::{json, Value};
+    use time::OffsetDateTime;
+
+    use super::*;
+    use crate::{
+        handlers::errors::ErrorResponse,
+        models::{
+            errors::FieldError,
+            post::{
+                CategorySummary, CreatePost, DraftContent, PatchPost, Post, PostWithTags,
+                UpdatePost,
+            },
+            tag::{PatchTag, RelatedTag, Tag, TagRequest, TagWithPostCount},
+        },
+    };
+
+    /// Every property of an interface, inherited ones first
+    fn fields_of(name: &str) -> Vec<Field> {
+        let declaration = declarations()
+            .into_iter()
+            .find(|d| matches!(d, Declaration::Interface { name: n, .. } if *n == name));
+        let Some(Declaration::Interface {
+            extends,
+            mut fields,
+            ..
+        }) = declaration
+        else {
+            panic!("no interface {}", name);
+        };
+        let mut all = extends.map(fields_of).unwrap_or_default();
+        all.append(&mut fields);
+        all
+    }
+
+    /// Checks a serialized response against its interface: every property
+    /// is declared, every required one is present, and only nullable ones
+    /// are null
+    fn assert_writes(name: &str, value: impl Serialize) {
+        let value = serde_json::to_value(value).unwrap();
+        let object = value.as_object().unwrap();
+        let fields = fields_of(name);
+        for (key, value) in object {
+            let field = fields
+                .iter()
+                .find(|f| f.name == key)
+                .unwrap_or_else(|| panic!("{}.{} is not declared", name, key));
+            if value.is_null() {
+                assert!(field.ty.ends_with("| null"), "{}.{} is null", name, key);
+            }
+        }
+        for field in fields.iter().filter(|f| !f.optional) {
+            assert!(
+                object.contains_key(field.name),
+                "{}.{} is missing",
+                name,
+                field.name
+            );
+        }
+    }
+
+    /// A value of a property's type, for building request bodies
+    fn sample(field: &Field) -> Value {
+        match field.ty.split(" | ").next().unwrap() {
+            "number" => json!(1),
+            "boolean" => json!(true),
+            "string" if field.name.ends_with("_at") => json!("2025-01-01T00:00:00Z"),
+            "string" => json!("text"),
+            "string[]" => json!(["text"]),
+            "PostCategory" => json!("blog"),
+            other => panic!("no sample of {}", other),
+        }
+    }
+
+    /// Checks that a request body reads as its interface says: with every
+    /// property, with only the required ones, with null wherever allowed,
+    /// and not with properties it doesn't declare
+    fn assert_reads<T: DeserializeOwned>(name: &str) {
+        let fields = fields_of(name);
+        let body = |value: &dyn Fn(&Field) -> Option<Value>| -> Value {
+            fields
+                .iter()
+                .filter_map(|f| value(f).map(|v| (f.name.to_string(), v)))
+                .collect::<serde_json::Map<_, _>>()
+                .into()
+        };
+
+        let all = body(&|f| Some(sample(f)));
+        let required = body(&|f| (!f.optional).then(|| sample(f)));
+        let nulls = body(&|f| {
+            Some(if f.ty.ends_with("| null") {
+                Value::Null
+            } else {
+                sample(f)
+            })
+        });
+        for (case, body) in [("all", &all), ("required", &required), ("nulls", &nulls)] {
+            if let Err(e) = serde_json::from_value::<T>(body.clone()) {
+                panic!("{} with {} properties: {}", name, case, e);
+            }
+        }
+
+        let mut unknown = all;
+        unknown["undeclared"] = json!(1);
+        assert!(serde_json::from_value::<T>(unknown).is_err(), "{}", name);
+    }
+
+    fn tag() -> Tag {
+        Tag {
+            id: 1,
+            name: "Rust".to_string(),
+            slug: "rust".to_string(),
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            description: None,
+            color: None,
+        }
+    }
+
+    fn post() -> Post {
+        Post {
+            id: 1,
+            category: PostCategory::Blog,
+            title: "Title".to_string(),
+            slug: "title".to_string(),
+            content: "Content".to_string(),
+            description: "Description".to_string(),
+            image_url: None,
+            external_url: None,
+            published: false,
+            meta_title: None,
+            meta_description: None,
+            og_image_url: None,
+            book_author: None,
+            isbn: None,
+            rating: None,
+            author_id: None,
+            created_by: None,
+            updated_by: None,
+            language: "en".to_string(),
+            translation_of: None,
+            published_at: None,
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn test_snapshot() {
+        assert_eq!(
+            client_types(),
+            include_str!("../client/types.ts"),
+            "client/types.ts is out of date; run `cargo run -- client-types --out client/types.ts`"
+        );
+    }
+
+    #[test]
+    fn test_responses_match_declarations() {
+        assert_writes("Post", post());
+        assert_writes("AttributedPost", post().attributed());
+        assert_writes(
+            "PostWithTags",
+            PostWithTags {
+                post: post(),
+                tags: vec![tag()],
+            },
+        );
+        assert_writes(
+            "CategorySummary",
+            CategorySummary {
+                category: PostCategory::Art,
+                label: PostCategory::Art.label(),
+                published_posts: 2,
+            },
+        );
+        assert_writes("Tag", tag());
+        assert_writes(
+            "TagWithPostCount",
+            TagWithPostCount {
+                id: 1,
+                name: "Rust".to_string(),
+                slug: "rust".to_string(),
+                created_at: OffsetDateTime::UNIX_EPOCH,
+                description: None,
+                color: None,
+                post_count: 3,
+            },
+        );
+        assert_writes(
+            "RelatedTag",
+            RelatedTag {
+                id: 1,
+                name: "Rust".to_string(),
+                slug: "rust".to_string(),
+                created_at: OffsetDateTime::UNIX_EPOCH,
+                description: None,
+                color: None,
+                shared_post_count: 3,
+            },
+        );
+        let field_error = FieldError {
+            field: None,
+            message: "Title cannot be empty".to_string(),
+        };
+        assert_writes("FieldError", &field_error);
+        for errors in [None, Some(vec![field_error])] {
+            assert_writes(
+                "ErrorResponse",
+                ErrorResponse {
+                    code: "validation_error",
+                    message: "Title cannot be empty".to_string(),
+                    field: None,
+                    allowed_methods: Some(vec!["GET".to_string()]),
+                    did_you_mean: Some(vec!["title".to_string()]),
+                    errors,
+                    request_id: Some("abc".to_string()),
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn test_requests_match_declarations() {
+        assert_reads::<CreatePost>("CreatePost");
+        assert_reads::<UpdatePost>("UpdatePost");
+        assert_reads::<PatchPost>("PatchPost");
+        assert_reads::<DraftContent>("DraftContent");
+        assert_reads::<TagRequest>("TagRequest");
+        assert_reads::<PatchTag>("PatchTag");
+    }
+
+    #[test]
+    fn test_optional_and_nullable() {
+        let types = client_types();
+        // Always present, but may be null
+        assert!(types.contains("\n  image_url: string | null;\n"));
+        // May be left out, or null to clear it
+        assert!(types.contains("\n  image_url?: string | null;\n"));
+        assert!(types
+            .contains("export type PostCategory =\n  | \"blog\"\n  | \"art\"\n  | \"reading\";\n"));
+        assert!(types.contains("\n  code: ErrorCode;\n"));
+        assert!(types.contains("\n  | \"not_found\"\n"));
+    }
+}
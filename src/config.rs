@@ -0,0 +1,1284 @@
+//! Server settings, read once at startup from environment variables and an
+//! optional TOML file
+
+use std::{env, fmt, fs, io, net::SocketAddr, num::NonZeroU32, path::PathBuf};
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use time::Duration;
+use url::Url;
+
+use crate::{
+    auth::DEFAULT_TOKEN_EXPIRY,
+    db::{PoolConfig, SqliteConfig, DEFAULT_CACHE_TTL, DEFAULT_SLOW_QUERY_THRESHOLD},
+    feeds::FeedConfig,
+    middleware::{CacheControlConfig, RateLimitConfig},
+    models::post,
+    telemetry::LogFormat,
+};
+
+/// Environment variable naming the TOML file to read; `config.toml` in the
+/// working directory is used, if it exists, when this is unset
+pub const CONFIG_FILE_VAR: &str = "CONFIG_FILE";
+
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+const DEFAULT_REQUEST_TIMEOUT_SECONDS: u32 = 30;
+const DEFAULT_MAX_BODY_BYTES: u32 = 2 * 1024 * 1024;
+const DEFAULT_MAX_UPLOAD_BYTES: u32 = 10 * 1024 * 1024;
+const DEFAULT_MAX_CONTENT_BYTES: u32 = post::DEFAULT_MAX_CONTENT_BYTES as u32;
+const DEFAULT_COMPRESSION_MIN_BYTES: u16 = 1024;
+const DEFAULT_CORS_METHODS: &[Method] = &[
+    Method::GET,
+    Method::POST,
+    Method::PUT,
+    Method::PATCH,
+    Method::DELETE,
+];
+const DEFAULT_CORS_HEADERS: &[&str] = &[
+    "authorization",
+    "content-type",
+    "idempotency-key",
+    "x-request-id",
+];
+const DEFAULT_CORS_MAX_AGE_SECONDS: u64 = 3600;
+
+/// A setting's environment variable and its key in the TOML file
+struct Setting {
+    var: &'static str,
+    key: &'static str,
+}
+
+const DATABASE_URL: Setting = Setting {
+    var: "DATABASE_URL",
+    key: "database_url",
+};
+const DATABASE_MAX_CONNECTIONS: Setting = Setting {
+    var: "DATABASE_MAX_CONNECTIONS",
+    key: "database_max_connections",
+};
+const DATABASE_MIN_CONNECTIONS: Setting = Setting {
+    var: "DATABASE_MIN_CONNECTIONS",
+    key: "database_min_connections",
+};
+const DATABASE_ACQUIRE_TIMEOUT_SECONDS: Setting = Setting {
+    var: "DATABASE_ACQUIRE_TIMEOUT_SECONDS",
+    key: "database_acquire_timeout_seconds",
+};
+const DATABASE_IDLE_TIMEOUT_SECONDS: Setting = Setting {
+    var: "DATABASE_IDLE_TIMEOUT_SECONDS",
+    key: "database_idle_timeout_seconds",
+};
+const DATABASE_CONNECT_RETRY_SECONDS: Setting = Setting {
+    var: "DATABASE_CONNECT_RETRY_SECONDS",
+    key: "database_connect_retry_seconds",
+};
+const DATABASE_JOURNAL_MODE: Setting = Setting {
+    var: "DATABASE_JOURNAL_MODE",
+    key: "database_journal_mode",
+};
+const DATABASE_SYNCHRONOUS: Setting = Setting {
+    var: "DATABASE_SYNCHRONOUS",
+    key: "database_synchronous",
+};
+const DATABASE_BUSY_TIMEOUT_MS: Setting = Setting {
+    var: "DATABASE_BUSY_TIMEOUT_MS",
+    key: "database_busy_timeout_ms",
+};
+const CACHE_DISABLED: Setting = Setting {
+    var: "CACHE_DISABLED",
+    key: "cache_disabled",
+};
+const CACHE_TTL_SECONDS: Setting = Setting {
+    var: "CACHE_TTL_SECONDS",
+    key: "cache_ttl_seconds",
+};
+const SLOW_QUERY_THRESHOLD_MS: Setting = Setting {
+    var: "SLOW_QUERY_THRESHOLD_MS",
+    key: "slow_query_threshold_ms",
+};
+const BIND_ADDRESS: Setting = Setting {
+    var: "BIND_ADDRESS",
+    key: "bind_address",
+};
+const CORS_ORIGINS: Setting = Setting {
+    var: "CORS_ORIGINS",
+    key: "cors_origins",
+};
+const CORS_METHODS: Setting = Setting {
+    var: "CORS_METHODS",
+    key: "cors_methods",
+};
+const CORS_HEADERS: Setting = Setting {
+    var: "CORS_HEADERS",
+    key: "cors_headers",
+};
+const CORS_MAX_AGE_SECONDS: Setting = Setting {
+    var: "CORS_MAX_AGE_SECONDS",
+    key: "cors_max_age_seconds",
+};
+const CORS_ALLOW_CREDENTIALS: Setting = Setting {
+    var: "CORS_ALLOW_CREDENTIALS",
+    key: "cors_allow_credentials",
+};
+const REQUEST_TIMEOUT_SECONDS: Setting = Setting {
+    var: "REQUEST_TIMEOUT_SECONDS",
+    key: "request_timeout_seconds",
+};
+const MAX_BODY_BYTES: Setting = Setting {
+    var: "MAX_BODY_BYTES",
+    key: "max_body_bytes",
+};
+const MAX_UPLOAD_BYTES: Setting = Setting {
+    var: "MAX_UPLOAD_BYTES",
+    key: "max_upload_bytes",
+};
+const MAX_CONTENT_BYTES: Setting = Setting {
+    var: "MAX_CONTENT_BYTES",
+    key: "max_content_bytes",
+};
+const LOWERCASE_TAG_NAMES: Setting = Setting {
+    var: "LOWERCASE_TAG_NAMES",
+    key: "lowercase_tag_names",
+};
+const COMPRESSION: Setting = Setting {
+    var: "COMPRESSION",
+    key: "compression",
+};
+const COMPRESSION_MIN_BYTES: Setting = Setting {
+    var: "COMPRESSION_MIN_BYTES",
+    key: "compression_min_bytes",
+};
+const CACHE_CONTROL: Setting = Setting {
+    var: "CACHE_CONTROL",
+    key: "cache_control",
+};
+const CACHE_CONTROL_ROUTES: Setting = Setting {
+    var: "CACHE_CONTROL_ROUTES",
+    key: "cache_control_routes",
+};
+const STATIC_DIR: Setting = Setting {
+    var: "STATIC_DIR",
+    key: "static_dir",
+};
+const BACKUP_DIR: Setting = Setting {
+    var: "BACKUP_DIR",
+    key: "backup_dir",
+};
+const INTEGRITY_CHECK_INTERVAL_HOURS: Setting = Setting {
+    var: "INTEGRITY_CHECK_INTERVAL_HOURS",
+    key: "integrity_check_interval_hours",
+};
+const LINK_CHECK_INTERVAL_HOURS: Setting = Setting {
+    var: "LINK_CHECK_INTERVAL_HOURS",
+    key: "link_check_interval_hours",
+};
+const SITE_URL: Setting = Setting {
+    var: "SITE_URL",
+    key: "site_url",
+};
+const FEED_TITLE: Setting = Setting {
+    var: "FEED_TITLE",
+    key: "feed_title",
+};
+const ROBOTS_DISALLOW: Setting = Setting {
+    var: "ROBOTS_DISALLOW",
+    key: "robots_disallow",
+};
+const LOG_LEVEL: Setting = Setting {
+    var: "RUST_LOG",
+    key: "log_level",
+};
+const LOG_FORMAT: Setting = Setting {
+    var: "LOG_FORMAT",
+    key: "log_format",
+};
+const JWT_SECRET: Setting = Setting {
+    var: "JWT_SECRET",
+    key: "jwt_secret",
+};
+const JWT_EXPIRY_SECONDS: Setting = Setting {
+    var: "JWT_EXPIRY_SECONDS",
+    key: "jwt_expiry_seconds",
+};
+const API_KEY: Setting = Setting {
+    var: "API_KEY",
+    key: "api_key",
+};
+const AUTH_DISABLED: Setting = Setting {
+    var: "AUTH_DISABLED",
+    key: "auth_disabled",
+};
+const SYSTEM_USER_ID: Setting = Setting {
+    var: "SYSTEM_USER_ID",
+    key: "system_user_id",
+};
+const RATE_LIMIT_READS_PER_MINUTE: Setting = Setting {
+    var: "RATE_LIMIT_READS_PER_MINUTE",
+    key: "rate_limit_reads_per_minute",
+};
+const RATE_LIMIT_WRITES_PER_MINUTE: Setting = Setting {
+    var: "RATE_LIMIT_WRITES_PER_MINUTE",
+    key: "rate_limit_writes_per_minute",
+};
+const RATE_LIMIT_COMMENTS_PER_HOUR: Setting = Setting {
+    var: "RATE_LIMIT_COMMENTS_PER_HOUR",
+    key: "rate_limit_comments_per_hour",
+};
+const RATE_LIMIT_CONTACT_PER_HOUR: Setting = Setting {
+    var: "RATE_LIMIT_CONTACT_PER_HOUR",
+    key: "rate_limit_contact_per_hour",
+};
+const TRUST_PROXY: Setting = Setting {
+    var: "TRUST_PROXY",
+    key: "trust_proxy",
+};
+const NOTIFY_WEBHOOK_URL: Setting = Setting {
+    var: "NOTIFY_WEBHOOK_URL",
+    key: "notify_webhook_url",
+};
+
+const SETTINGS: &[Setting] = &[
+    DATABASE_URL,
+    DATABASE_MAX_CONNECTIONS,
+    DATABASE_MIN_CONNECTIONS,
+    DATABASE_ACQUIRE_TIMEOUT_SECONDS,
+    DATABASE_IDLE_TIMEOUT_SECONDS,
+    DATABASE_CONNECT_RETRY_SECONDS,
+    DATABASE_JOURNAL_MODE,
+    DATABASE_SYNCHRONOUS,
+    DATABASE_BUSY_TIMEOUT_MS,
+    CACHE_DISABLED,
+    CACHE_TTL_SECONDS,
+    SLOW_QUERY_THRESHOLD_MS,
+    BIND_ADDRESS,
+    CORS_ORIGINS,
+    CORS_METHODS,
+    CORS_HEADERS,
+    CORS_MAX_AGE_SECONDS,
+    CORS_ALLOW_CREDENTIALS,
+    REQUEST_TIMEOUT_SECONDS,
+    MAX_BODY_BYTES,
+    MAX_UPLOAD_BYTES,
+    MAX_CONTENT_BYTES,
+    LOWERCASE_TAG_NAMES,
+    COMPRESSION,
+    COMPRESSION_MIN_BYTES,
+    CACHE_CONTROL,
+    CACHE_CONTROL_ROUTES,
+    STATIC_DIR,
+    BACKUP_DIR,
+    INTEGRITY_CHECK_INTERVAL_HOURS,
+    LINK_CHECK_INTERVAL_HOURS,
+    SITE_URL,
+    FEED_TITLE,
+    ROBOTS_DISALLOW,
+    LOG_LEVEL,
+    LOG_FORMAT,
+    JWT_SECRET,
+    JWT_EXPIRY_SECONDS,
+    API_KEY,
+    AUTH_DISABLED,
+    SYSTEM_USER_ID,
+    RATE_LIMIT_READS_PER_MINUTE,
+    RATE_LIMIT_WRITES_PER_MINUTE,
+    RATE_LIMIT_COMMENTS_PER_HOUR,
+    RATE_LIMIT_CONTACT_PER_HOUR,
+    TRUST_PROXY,
+    NOTIFY_WEBHOOK_URL,
+];
+
+/// Every problem found while loading the configuration, so they can all be
+/// fixed at once
+#[derive(Debug)]
+pub struct ConfigError {
+    pub errors: Vec<String>,
+}
+
+impl std::error::Error for ConfigError {}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid configuration:")?;
+        for error in &self.errors {
+            write!(f, "\n  - {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Origins allowed to make cross-origin requests
+#[derive(Clone, Debug, PartialEq)]
+pub enum CorsOrigins {
+    /// Any origin; has to be opted into with `*`
+    Any,
+    /// Exactly these origins. Empty allows no cross-origin requests.
+    List(Vec<HeaderValue>),
+}
+
+/// What browsers may do in cross-origin requests
+#[derive(Clone, Debug, PartialEq)]
+pub struct CorsConfig {
+    pub origins: CorsOrigins,
+    pub methods: Vec<Method>,
+    pub headers: Vec<HeaderName>,
+    /// How long browsers may cache a preflight response
+    pub max_age: std::time::Duration,
+    /// Whether browsers may send cookies and credentials along. Cannot be
+    /// combined with `CorsOrigins::Any`.
+    pub allow_credentials: bool,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            origins: CorsOrigins::List(Vec::new()),
+            methods: DEFAULT_CORS_METHODS.to_vec(),
+            headers: DEFAULT_CORS_HEADERS
+                .iter()
+                .map(|name| HeaderName::from_static(name))
+                .collect(),
+            max_age: std::time::Duration::from_secs(DEFAULT_CORS_MAX_AGE_SECONDS),
+            allow_credentials: false,
+        }
+    }
+}
+
+/// Which response compression algorithms are offered, and for what sizes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CompressionConfig {
+    pub gzip: bool,
+    pub brotli: bool,
+    /// Responses smaller than this are sent uncompressed
+    pub min_bytes: u16,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            gzip: true,
+            brotli: true,
+            min_bytes: DEFAULT_COMPRESSION_MIN_BYTES,
+        }
+    }
+}
+
+/// Settings for the whole server
+#[derive(Clone)]
+pub struct Config {
+    pub database_url: String,
+    pub pool: PoolConfig,
+    pub sqlite: SqliteConfig,
+    /// How long post reads are cached; `None` turns the cache off
+    pub cache_ttl: Option<std::time::Duration>,
+    /// Repository calls taking at least this long are logged as warnings
+    pub slow_query_threshold: std::time::Duration,
+    pub bind_address: SocketAddr,
+    pub cors: CorsConfig,
+    /// How long a request may take before it is answered with 504
+    pub request_timeout: std::time::Duration,
+    /// Largest accepted request body; bigger ones get 413
+    pub max_body_bytes: usize,
+    /// Largest accepted image upload; it replaces `max_body_bytes` there
+    pub max_upload_bytes: usize,
+    /// Largest accepted post content; longer posts are rejected with 413
+    pub max_content_bytes: usize,
+    /// Stores tag names in lowercase, on top of collapsing their whitespace
+    pub lowercase_tag_names: bool,
+    pub compression: CompressionConfig,
+    /// `Cache-Control` for public reads; everything else gets `no-store`
+    pub cache_control: CacheControlConfig,
+    /// Directory served under `/static`; nothing is served when unset
+    pub static_dir: Option<PathBuf>,
+    /// Directory `POST /admin/backup` writes to; backups are off when unset
+    pub backup_dir: Option<PathBuf>,
+    /// How often the database's integrity is checked in the background;
+    /// never when unset
+    pub integrity_check_interval: Option<std::time::Duration>,
+    /// How often posts' external and image URLs are checked for dead
+    /// links; never when unset
+    pub link_check_interval: Option<std::time::Duration>,
+    pub feed: FeedConfig,
+    /// Asks crawlers not to index anything, e.g. on staging instances
+    pub robots_disallow: bool,
+    /// Filter directives for the log output, as in `RUST_LOG`
+    pub log_level: String,
+    pub log_format: LogFormat,
+    pub jwt_secret: String,
+    pub jwt_expiry: Duration,
+    /// Static key accepted as a bearer token, with admin rights
+    pub api_key: Option<String>,
+    /// Skips authentication entirely; for local development only
+    pub auth_disabled: bool,
+    /// User recorded as having created or changed posts when the caller
+    /// has no user account, such as an API key
+    pub system_user_id: Option<i64>,
+    pub rate_limit: RateLimitConfig,
+    /// Where notifications such as new contact messages are posted; they
+    /// are only logged when unset
+    pub notify_webhook_url: Option<Url>,
+}
+
+// The secret, API key and webhook URL are credentials, so they are left out
+// of debug output
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("database_url", &self.database_url)
+            .field("pool", &self.pool)
+            .field("sqlite", &self.sqlite)
+            .field("cache_ttl", &self.cache_ttl)
+            .field("slow_query_threshold", &self.slow_query_threshold)
+            .field("bind_address", &self.bind_address)
+            .field("cors", &self.cors)
+            .field("request_timeout", &self.request_timeout)
+            .field("max_body_bytes", &self.max_body_bytes)
+            .field("max_upload_bytes", &self.max_upload_bytes)
+            .field("max_content_bytes", &self.max_content_bytes)
+            .field("lowercase_tag_names", &self.lowercase_tag_names)
+            .field("compression", &self.compression)
+            .field("cache_control", &self.cache_control)
+            .field("static_dir", &self.static_dir)
+            .field("backup_dir", &self.backup_dir)
+            .field("integrity_check_interval", &self.integrity_check_interval)
+            .field("link_check_interval", &self.link_check_interval)
+            .field("feed", &self.feed)
+            .field("robots_disallow", &self.robots_disallow)
+            .field("log_level", &self.log_level)
+            .field("log_format", &self.log_format)
+            .field("jwt_secret", &"<redacted>")
+            .field("jwt_expiry", &self.jwt_expiry)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("auth_disabled", &self.auth_disabled)
+            .field("system_user_id", &self.system_user_id)
+            .field("rate_limit", &self.rate_limit)
+            .field(
+                "notify_webhook_url",
+                &self.notify_webhook_url.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+impl Config {
+    /// Loads the configuration from the environment (including a `.env`
+    /// file) and the TOML file, if any. Settings in the TOML file take
+    /// precedence over environment variables.
+    pub fn load() -> Result<Self, ConfigError> {
+        dotenv::dotenv().ok();
+
+        let (path, required) = match env::var(CONFIG_FILE_VAR) {
+            Ok(path) => (path, true),
+            Err(_) => (DEFAULT_CONFIG_FILE.to_string(), false),
+        };
+        let file = match fs::read_to_string(&path) {
+            Ok(contents) => Some(contents),
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !required => None,
+            Err(e) => {
+                return Err(ConfigError {
+                    errors: vec![format!("Cannot read {}: {}", path, e)],
+                })
+            }
+        };
+
+        Self::from_sources(|var| env::var(var).ok(), file.as_deref())
+    }
+
+    /// Settings for tests: an in-memory database and a fixed signing secret
+    #[cfg(test)]
+    pub fn default_for_tests() -> Self {
+        Self {
+            database_url: "sqlite::memory:".to_string(),
+            pool: PoolConfig::default(),
+            sqlite: SqliteConfig::default(),
+            cache_ttl: Some(DEFAULT_CACHE_TTL),
+            slow_query_threshold: DEFAULT_SLOW_QUERY_THRESHOLD,
+            bind_address: SocketAddr::from(([127, 0, 0, 1], 0)),
+            cors: CorsConfig::default(),
+            request_timeout: std::time::Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECONDS.into()),
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES as usize,
+            max_upload_bytes: DEFAULT_MAX_UPLOAD_BYTES as usize,
+            max_content_bytes: DEFAULT_MAX_CONTENT_BYTES as usize,
+            lowercase_tag_names: false,
+            compression: CompressionConfig::default(),
+            cache_control: CacheControlConfig::default(),
+            static_dir: None,
+            backup_dir: None,
+            integrity_check_interval: None,
+            link_check_interval: None,
+            feed: FeedConfig::default(),
+            robots_disallow: false,
+            log_level: "info".to_string(),
+            log_format: LogFormat::Pretty,
+            jwt_secret: "test-secret".to_string(),
+            jwt_expiry: DEFAULT_TOKEN_EXPIRY,
+            api_key: None,
+            auth_disabled: false,
+            system_user_id: None,
+            // High enough that tests never hit them
+            rate_limit: RateLimitConfig {
+                reads_per_minute: u32::MAX,
+                writes_per_minute: u32::MAX,
+                comments_per_hour: u32::MAX,
+                contact_per_hour: u32::MAX,
+                trust_proxy: false,
+            },
+            notify_webhook_url: None,
+        }
+    }
+
+    fn from_sources(
+        env: impl Fn(&str) -> Option<String>,
+        file: Option<&str>,
+    ) -> Result<Self, ConfigError> {
+        let mut errors = Vec::new();
+        let table = match file.map(str::parse::<toml::Table>) {
+            Some(Ok(table)) => table,
+            Some(Err(e)) => {
+                return Err(ConfigError {
+                    errors: vec![format!("Config file is not valid TOML: {}", e)],
+                })
+            }
+            None => toml::Table::new(),
+        };
+        for key in table.keys() {
+            if !SETTINGS.iter().any(|setting| setting.key == key) {
+                errors.push(format!("Unknown setting `{}` in config file", key));
+            }
+        }
+
+        let mut source = Source { env, table, errors };
+        let defaults = RateLimitConfig::default();
+
+        let database_url = source.required(&DATABASE_URL);
+        let pool = source.pool();
+        let sqlite = source.sqlite();
+        let cache_disabled = source.flag(&CACHE_DISABLED);
+        let cache_ttl_seconds =
+            source.positive(&CACHE_TTL_SECONDS, DEFAULT_CACHE_TTL.as_secs() as u32);
+        let slow_query_threshold_ms =
+            source.parsed(&SLOW_QUERY_THRESHOLD_MS, "a whole number of milliseconds");
+        let bind_address = source.parsed(&BIND_ADDRESS, "a socket address like 0.0.0.0:8080");
+        let cors = source.cors();
+        let request_timeout_seconds =
+            source.positive(&REQUEST_TIMEOUT_SECONDS, DEFAULT_REQUEST_TIMEOUT_SECONDS);
+        let max_body_bytes = source.positive(&MAX_BODY_BYTES, DEFAULT_MAX_BODY_BYTES);
+        let max_upload_bytes = source.positive(&MAX_UPLOAD_BYTES, DEFAULT_MAX_UPLOAD_BYTES);
+        let max_content_bytes = source.positive(&MAX_CONTENT_BYTES, DEFAULT_MAX_CONTENT_BYTES);
+        let lowercase_tag_names = source.flag(&LOWERCASE_TAG_NAMES);
+        let compression = source.compression();
+        let cache_control = source.cache_control();
+        let static_dir = source.directory(&STATIC_DIR);
+        let backup_dir = source.directory(&BACKUP_DIR);
+        let integrity_check_interval_hours = source.parsed::<NonZeroU32>(
+            &INTEGRITY_CHECK_INTERVAL_HOURS,
+            "a positive whole number of hours",
+        );
+        let link_check_interval_hours = source.parsed::<NonZeroU32>(
+            &LINK_CHECK_INTERVAL_HOURS,
+            "a positive whole number of hours",
+        );
+        let feed = source.feed();
+        let robots_disallow = source.flag(&ROBOTS_DISALLOW);
+        let log_level = source.get(&LOG_LEVEL);
+        let log_format = source.parsed(&LOG_FORMAT, "json or pretty");
+        let jwt_secret = source.required(&JWT_SECRET);
+        let jwt_expiry_seconds = source.positive(
+            &JWT_EXPIRY_SECONDS,
+            DEFAULT_TOKEN_EXPIRY.whole_seconds() as u32,
+        );
+        let api_key = source.get(&API_KEY);
+        let auth_disabled = source.flag(&AUTH_DISABLED);
+        let system_user_id = source.parsed(&SYSTEM_USER_ID, "a user id");
+        let reads_per_minute = source.parsed(&RATE_LIMIT_READS_PER_MINUTE, "a whole number");
+        let writes_per_minute = source.parsed(&RATE_LIMIT_WRITES_PER_MINUTE, "a whole number");
+        let comments_per_hour = source.parsed(&RATE_LIMIT_COMMENTS_PER_HOUR, "a whole number");
+        let contact_per_hour = source.parsed(&RATE_LIMIT_CONTACT_PER_HOUR, "a whole number");
+        let trust_proxy = source.flag(&TRUST_PROXY);
+        let notify_webhook_url = source.http_url(&NOTIFY_WEBHOOK_URL);
+
+        if !source.errors.is_empty() {
+            return Err(ConfigError {
+                errors: source.errors,
+            });
+        }
+
+        // Every setting without a default has reported an error if missing
+        Ok(Self {
+            database_url: database_url.unwrap_or_default(),
+            pool,
+            sqlite,
+            cache_ttl: (!cache_disabled)
+                .then(|| std::time::Duration::from_secs(cache_ttl_seconds.into())),
+            slow_query_threshold: slow_query_threshold_ms.map_or(
+                DEFAULT_SLOW_QUERY_THRESHOLD,
+                std::time::Duration::from_millis,
+            ),
+            bind_address: bind_address.unwrap_or(SocketAddr::from(([0, 0, 0, 0], 8080))),
+            cors,
+            request_timeout: std::time::Duration::from_secs(request_timeout_seconds.into()),
+            max_body_bytes: max_body_bytes as usize,
+            max_upload_bytes: max_upload_bytes as usize,
+            max_content_bytes: max_content_bytes as usize,
+            lowercase_tag_names,
+            compression,
+            cache_control,
+            static_dir,
+            backup_dir,
+            integrity_check_interval: integrity_check_interval_hours
+                .map(|hours| std::time::Duration::from_secs(u64::from(hours.get()) * 60 * 60)),
+            link_check_interval: link_check_interval_hours
+                .map(|hours| std::time::Duration::from_secs(u64::from(hours.get()) * 60 * 60)),
+            feed,
+            robots_disallow,
+            log_level: log_level.unwrap_or_else(|| "info".to_string()),
+            log_format: log_format.unwrap_or_default(),
+            jwt_secret: jwt_secret.unwrap_or_default(),
+            jwt_expiry: Duration::seconds(i64::from(jwt_expiry_seconds)),
+            api_key,
+            auth_disabled,
+            system_user_id,
+            rate_limit: RateLimitConfig {
+                reads_per_minute: reads_per_minute.unwrap_or(defaults.reads_per_minute),
+                writes_per_minute: writes_per_minute.unwrap_or(defaults.writes_per_minute),
+                comments_per_hour: comments_per_hour.unwrap_or(defaults.comments_per_hour),
+                contact_per_hour: contact_per_hour.unwrap_or(defaults.contact_per_hour),
+                trust_proxy,
+            },
+            notify_webhook_url,
+        })
+    }
+}
+
+/// Looks settings up in the TOML file, then the environment, collecting
+/// errors instead of stopping at the first
+struct Source<E> {
+    env: E,
+    table: toml::Table,
+    errors: Vec<String>,
+}
+
+impl<E: Fn(&str) -> Option<String>> Source<E> {
+    /// The raw value of a setting; empty values count as unset
+    fn get(&mut self, setting: &Setting) -> Option<String> {
+        let value = match self.table.get(setting.key) {
+            Some(toml::Value::String(value)) => Some(value.clone()),
+            Some(toml::Value::Integer(value)) => Some(value.to_string()),
+            Some(toml::Value::Boolean(value)) => Some(value.to_string()),
+            Some(toml::Value::Array(values)) => {
+                let items: Option<Vec<&str>> = values.iter().map(toml::Value::as_str).collect();
+                match items {
+                    Some(items) => Some(items.join(",")),
+                    None => {
+                        self.errors
+                            .push(format!("`{}` must be a list of strings", setting.key));
+                        None
+                    }
+                }
+            }
+            Some(_) => {
+                self.errors.push(format!(
+                    "`{}` must be a string, number or boolean",
+                    setting.key
+                ));
+                None
+            }
+            None => (self.env)(setting.var),
+        };
+        value.filter(|value| !value.trim().is_empty())
+    }
+
+    fn required(&mut self, setting: &Setting) -> Option<String> {
+        let value = self.get(setting);
+        if value.is_none() {
+            self.errors.push(format!(
+                "{} (or `{}` in the config file) must be set",
+                setting.var, setting.key
+            ));
+        }
+        value
+    }
+
+    fn parsed<T: std::str::FromStr>(&mut self, setting: &Setting, expected: &str) -> Option<T> {
+        let value = self.get(setting)?;
+        let parsed = value.trim().parse().ok();
+        if parsed.is_none() {
+            self.errors.push(format!(
+                "{} must be {}, got {:?}",
+                setting.var, expected, value
+            ));
+        }
+        parsed
+    }
+
+    fn positive(&mut self, setting: &Setting, default: u32) -> u32 {
+        match self.parsed::<u32>(setting, "a positive whole number") {
+            Some(0) => {
+                self.errors
+                    .push(format!("{} must be greater than zero", setting.var));
+                default
+            }
+            Some(value) => value,
+            None => default,
+        }
+    }
+
+    fn flag(&mut self, setting: &Setting) -> bool {
+        match self.get(setting) {
+            None => false,
+            Some(value) => match value.trim().to_ascii_lowercase().as_str() {
+                "true" | "1" => true,
+                "false" | "0" => false,
+                _ => {
+                    self.errors.push(format!(
+                        "{} must be true or false, got {:?}",
+                        setting.var, value
+                    ));
+                    false
+                }
+            },
+        }
+    }
+
+    /// Pool sizes and timeouts; an idle timeout of `0` keeps idle
+    /// connections open indefinitely
+    fn pool(&mut self) -> PoolConfig {
+        let defaults = PoolConfig::default();
+        let max_connections = self.positive(&DATABASE_MAX_CONNECTIONS, defaults.max_connections);
+        let min_connections = self
+            .parsed(&DATABASE_MIN_CONNECTIONS, "a whole number")
+            .unwrap_or(defaults.min_connections);
+        if min_connections > max_connections {
+            self.errors.push(format!(
+                "DATABASE_MIN_CONNECTIONS ({}) must not exceed DATABASE_MAX_CONNECTIONS ({})",
+                min_connections, max_connections
+            ));
+        }
+        let acquire_timeout_seconds = self.positive(
+            &DATABASE_ACQUIRE_TIMEOUT_SECONDS,
+            defaults.acquire_timeout.as_secs() as u32,
+        );
+        let idle_timeout = self
+            .parsed(&DATABASE_IDLE_TIMEOUT_SECONDS, "a whole number of seconds")
+            .map_or(defaults.idle_timeout, |seconds| {
+                (seconds > 0).then(|| std::time::Duration::from_secs(seconds))
+            });
+        let connect_retry = self
+            .parsed(&DATABASE_CONNECT_RETRY_SECONDS, "a whole number of seconds")
+            .map_or(defaults.connect_retry, std::time::Duration::from_secs);
+        PoolConfig {
+            max_connections,
+            min_connections,
+            acquire_timeout: std::time::Duration::from_secs(acquire_timeout_seconds.into()),
+            idle_timeout,
+            connect_retry,
+        }
+    }
+
+    fn sqlite(&mut self) -> SqliteConfig {
+        let defaults = SqliteConfig::default();
+        SqliteConfig {
+            journal_mode: self
+                .parsed(
+                    &DATABASE_JOURNAL_MODE,
+                    "one of wal, delete, truncate, persist, memory or off",
+                )
+                .unwrap_or(defaults.journal_mode),
+            synchronous: self
+                .parsed(&DATABASE_SYNCHRONOUS, "one of off, normal, full or extra")
+                .unwrap_or(defaults.synchronous),
+            busy_timeout: self
+                .parsed(&DATABASE_BUSY_TIMEOUT_MS, "a whole number of milliseconds")
+                .map(std::time::Duration::from_millis)
+                .unwrap_or(defaults.busy_timeout),
+        }
+    }
+
+    /// A comma-separated list of algorithms (`gzip`, `br`), or `none`
+    fn compression(&mut self) -> CompressionConfig {
+        let mut config = CompressionConfig {
+            min_bytes: self
+                .parsed(&COMPRESSION_MIN_BYTES, "a whole number up to 65535")
+                .unwrap_or(DEFAULT_COMPRESSION_MIN_BYTES),
+            ..CompressionConfig::default()
+        };
+        let Some(value) = self.get(&COMPRESSION) else {
+            return config;
+        };
+
+        config.gzip = false;
+        config.brotli = false;
+        for algorithm in value.split(',').map(str::trim).filter(|a| !a.is_empty()) {
+            match algorithm.to_ascii_lowercase().as_str() {
+                "gzip" => config.gzip = true,
+                "br" | "brotli" => config.brotli = true,
+                "none" => {}
+                _ => self.errors.push(format!(
+                    "{} entry {:?} is not one of gzip, br or none",
+                    COMPRESSION.var, algorithm
+                )),
+            }
+        }
+        config
+    }
+
+    /// The default `Cache-Control` value plus per-route overrides. In the
+    /// environment, overrides are `prefix=value` entries separated by `;`,
+    /// since values contain commas; in the file, they are a table.
+    fn cache_control(&mut self) -> CacheControlConfig {
+        let mut config = CacheControlConfig::default();
+        if let Some(value) = self.get(&CACHE_CONTROL) {
+            match HeaderValue::from_str(value.trim()) {
+                Ok(value) => config.default = value,
+                Err(_) => self.errors.push(format!(
+                    "{} must be a valid header value, got {:?}",
+                    CACHE_CONTROL.var, value
+                )),
+            }
+        }
+
+        let entries: Vec<(String, String)> = match self.table.get(CACHE_CONTROL_ROUTES.key) {
+            Some(toml::Value::Table(routes)) => routes
+                .iter()
+                .filter_map(|(prefix, value)| match value.as_str() {
+                    Some(value) => Some((prefix.clone(), value.to_string())),
+                    None => {
+                        self.errors.push(format!(
+                            "`{}.{}` must be a string",
+                            CACHE_CONTROL_ROUTES.key, prefix
+                        ));
+                        None
+                    }
+                })
+                .collect(),
+            Some(_) => {
+                self.errors.push(format!(
+                    "`{}` must be a table of path prefixes",
+                    CACHE_CONTROL_ROUTES.key
+                ));
+                Vec::new()
+            }
+            None => (self.env)(CACHE_CONTROL_ROUTES.var)
+                .unwrap_or_default()
+                .split(';')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .filter_map(|entry| match entry.split_once('=') {
+                    Some((prefix, value)) => Some((prefix.trim().to_string(), value.to_string())),
+                    None => {
+                        self.errors.push(format!(
+                            "{} entry {:?} is not like /feed=public, max-age=600",
+                            CACHE_CONTROL_ROUTES.var, entry
+                        ));
+                        None
+                    }
+                })
+                .collect(),
+        };
+
+        for (prefix, value) in entries {
+            match HeaderValue::from_str(value.trim()) {
+                Ok(value) if prefix.starts_with('/') && !value.is_empty() => {
+                    config.routes.push((prefix, value))
+                }
+                _ => self.errors.push(format!(
+                    "{} entry for {:?} needs a path starting with / and a valid header value",
+                    CACHE_CONTROL_ROUTES.var, prefix
+                )),
+            }
+        }
+        config
+    }
+
+    fn feed(&mut self) -> FeedConfig {
+        let defaults = FeedConfig::default();
+        let site_url = self.http_url(&SITE_URL);
+        FeedConfig {
+            site_url: site_url.unwrap_or(defaults.site_url),
+            title: self.get(&FEED_TITLE).unwrap_or(defaults.title),
+        }
+    }
+
+    /// An absolute `http` or `https` URL
+    fn http_url(&mut self, setting: &Setting) -> Option<Url> {
+        let value = self.get(setting)?;
+        match Url::parse(value.trim()) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => Some(url),
+            _ => {
+                self.errors.push(format!(
+                    "{} must be an http or https URL, got {:?}",
+                    setting.var, value
+                ));
+                None
+            }
+        }
+    }
+
+    /// A path that must name an existing directory
+    fn directory(&mut self, setting: &Setting) -> Option<PathBuf> {
+        let path = PathBuf::from(self.get(setting)?);
+        if !path.is_dir() {
+            self.errors.push(format!(
+                "{} must be an existing directory, got {:?}",
+                setting.var,
+                path.display().to_string()
+            ));
+        }
+        Some(path)
+    }
+
+    fn cors(&mut self) -> CorsConfig {
+        let defaults = CorsConfig::default();
+        let config = CorsConfig {
+            origins: self.cors_origins(),
+            methods: self
+                .list(&CORS_METHODS, "an HTTP method", |method| {
+                    Method::from_bytes(method.to_ascii_uppercase().as_bytes()).ok()
+                })
+                .unwrap_or(defaults.methods),
+            headers: self
+                .list(&CORS_HEADERS, "a header name", |name| name.parse().ok())
+                .unwrap_or(defaults.headers),
+            max_age: self
+                .parsed(&CORS_MAX_AGE_SECONDS, "a whole number of seconds")
+                .map(std::time::Duration::from_secs)
+                .unwrap_or(defaults.max_age),
+            allow_credentials: self.flag(&CORS_ALLOW_CREDENTIALS),
+        };
+        if config.allow_credentials && config.origins == CorsOrigins::Any {
+            self.errors.push(format!(
+                "{} cannot be combined with {} = *; list the origins instead",
+                CORS_ALLOW_CREDENTIALS.var, CORS_ORIGINS.var
+            ));
+        }
+        config
+    }
+
+    /// A comma-separated list, each entry converted by `parse`
+    fn list<T>(
+        &mut self,
+        setting: &Setting,
+        expected: &str,
+        parse: impl Fn(&str) -> Option<T>,
+    ) -> Option<Vec<T>> {
+        let value = self.get(setting)?;
+        let mut items = Vec::new();
+        for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match parse(entry) {
+                Some(item) => items.push(item),
+                None => self.errors.push(format!(
+                    "{} entry {:?} is not {}",
+                    setting.var, entry, expected
+                )),
+            }
+        }
+        Some(items)
+    }
+
+    /// A comma-separated list of origins, or `*` for any
+    fn cors_origins(&mut self) -> CorsOrigins {
+        let Some(value) = self.get(&CORS_ORIGINS) else {
+            return CorsOrigins::List(Vec::new());
+        };
+        let mut origins = Vec::new();
+        for origin in value.split(',').map(str::trim).filter(|o| !o.is_empty()) {
+            if origin == "*" {
+                return CorsOrigins::Any;
+            }
+            let valid = url::Url::parse(origin)
+                .is_ok_and(|url| url.origin().ascii_serialization() == origin);
+            match HeaderValue::from_str(origin) {
+                Ok(header) if valid => origins.push(header),
+                _ => self.errors.push(format!(
+                    "{} entry {:?} is not an origin like https://example.com",
+                    CORS_ORIGINS.var, origin
+                )),
+            }
+        }
+        CorsOrigins::List(origins)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn load(env: &[(&str, &str)], file: Option<&str>) -> Result<Config, ConfigError> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(var, value)| (var.to_string(), value.to_string()))
+            .collect();
+        Config::from_sources(|var| env.get(var).cloned(), file)
+    }
+
+    const REQUIRED: &[(&str, &str)] = &[
+        ("DATABASE_URL", "sqlite://blog.db"),
+        ("JWT_SECRET", "secret"),
+    ];
+
+    #[test]
+    fn test_defaults() {
+        let config = load(REQUIRED, None).unwrap();
+        assert_eq!(config.database_url, "sqlite://blog.db");
+        assert_eq!(config.bind_address, SocketAddr::from(([0, 0, 0, 0], 8080)));
+        assert_eq!(config.pool, PoolConfig::default());
+        assert_eq!(config.sqlite, SqliteConfig::default());
+        assert_eq!(config.cache_ttl, Some(std::time::Duration::from_secs(60)));
+        assert_eq!(
+            config.slow_query_threshold,
+            std::time::Duration::from_millis(100)
+        );
+        assert_eq!(config.cors, CorsConfig::default());
+        assert_eq!(config.cors.origins, CorsOrigins::List(Vec::new()));
+        assert_eq!(config.request_timeout, std::time::Duration::from_secs(30));
+        assert_eq!(config.max_body_bytes, 2 * 1024 * 1024);
+        assert_eq!(config.max_upload_bytes, 10 * 1024 * 1024);
+        assert_eq!(config.max_content_bytes, 1024 * 1024);
+        assert!(!config.lowercase_tag_names);
+        assert_eq!(config.compression, CompressionConfig::default());
+        assert_eq!(config.cache_control, CacheControlConfig::default());
+        assert_eq!(config.static_dir, None);
+        assert_eq!(config.backup_dir, None);
+        assert_eq!(config.integrity_check_interval, None);
+        assert_eq!(config.link_check_interval, None);
+        assert_eq!(config.feed, FeedConfig::default());
+        assert!(!config.robots_disallow);
+        assert_eq!(config.log_level, "info");
+        assert_eq!(config.log_format, LogFormat::Pretty);
+        assert_eq!(config.jwt_expiry, DEFAULT_TOKEN_EXPIRY);
+        assert_eq!(config.api_key, None);
+        assert!(!config.auth_disabled);
+        assert_eq!(config.system_user_id, None);
+        assert_eq!(config.rate_limit, RateLimitConfig::default());
+        assert_eq!(config.notify_webhook_url, None);
+    }
+
+    #[test]
+    fn test_file_overrides_env() {
+        let file = r#"
+            bind_address = "127.0.0.1:3000"
+            cors_origins = ["https://example.com", "http://localhost:5173"]
+            cors_methods = ["get", "POST"]
+            cors_allow_credentials = true
+            jwt_expiry_seconds = 600
+            integrity_check_interval_hours = 168
+            link_check_interval_hours = 24
+            trust_proxy = true
+            compression = ["gzip"]
+
+            [cache_control_routes]
+            "/feed" = "public, max-age=600"
+        "#;
+        let env = [REQUIRED, &[("BIND_ADDRESS", "0.0.0.0:9000")]].concat();
+        let config = load(&env, Some(file)).unwrap();
+
+        assert_eq!(
+            config.bind_address,
+            SocketAddr::from(([127, 0, 0, 1], 3000))
+        );
+        assert_eq!(
+            config.cors.origins,
+            CorsOrigins::List(vec![
+                HeaderValue::from_static("https://example.com"),
+                HeaderValue::from_static("http://localhost:5173"),
+            ])
+        );
+        assert_eq!(config.cors.methods, [Method::GET, Method::POST]);
+        assert!(config.cors.allow_credentials);
+        assert_eq!(config.jwt_expiry, Duration::minutes(10));
+        assert_eq!(
+            config.integrity_check_interval,
+            Some(std::time::Duration::from_secs(7 * 24 * 60 * 60))
+        );
+        assert_eq!(
+            config.link_check_interval,
+            Some(std::time::Duration::from_secs(24 * 60 * 60))
+        );
+        assert!(config.rate_limit.trust_proxy);
+        assert!(config.compression.gzip);
+        assert!(!config.compression.brotli);
+        assert_eq!(
+            config.cache_control.routes,
+            [(
+                "/feed".to_string(),
+                HeaderValue::from_static("public, max-age=600")
+            )]
+        );
+
+        let config = load(&[REQUIRED, &[("COMPRESSION", "none")]].concat(), None).unwrap();
+        assert!(!config.compression.gzip && !config.compression.brotli);
+
+        let config = load(&[REQUIRED, &[("CACHE_DISABLED", "true")]].concat(), None).unwrap();
+        assert_eq!(config.cache_ttl, None);
+
+        let config = load(
+            &[REQUIRED, &[("SLOW_QUERY_THRESHOLD_MS", "0")]].concat(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(config.slow_query_threshold, std::time::Duration::ZERO);
+
+        let env = [
+            REQUIRED,
+            &[
+                ("DATABASE_MAX_CONNECTIONS", "4"),
+                ("DATABASE_MIN_CONNECTIONS", "2"),
+                ("DATABASE_ACQUIRE_TIMEOUT_SECONDS", "3"),
+                ("DATABASE_IDLE_TIMEOUT_SECONDS", "0"),
+                ("DATABASE_CONNECT_RETRY_SECONDS", "0"),
+            ],
+        ]
+        .concat();
+        let config = load(&env, None).unwrap();
+        assert_eq!(
+            config.pool,
+            PoolConfig {
+                max_connections: 4,
+                min_connections: 2,
+                acquire_timeout: std::time::Duration::from_secs(3),
+                idle_timeout: None,
+                connect_retry: std::time::Duration::ZERO,
+            }
+        );
+
+        let env = [
+            REQUIRED,
+            &[
+                ("DATABASE_JOURNAL_MODE", "delete"),
+                ("DATABASE_SYNCHRONOUS", "FULL"),
+                ("DATABASE_BUSY_TIMEOUT_MS", "250"),
+            ],
+        ]
+        .concat();
+        let config = load(&env, None).unwrap();
+        assert_eq!(
+            config.sqlite,
+            SqliteConfig {
+                journal_mode: sqlx::sqlite::SqliteJournalMode::Delete,
+                synchronous: sqlx::sqlite::SqliteSynchronous::Full,
+                busy_timeout: std::time::Duration::from_millis(250),
+            }
+        );
+
+        let env = [
+            REQUIRED,
+            &[
+                ("CACHE_CONTROL", "public, max-age=30"),
+                (
+                    "CACHE_CONTROL_ROUTES",
+                    "/feed=public, max-age=600; /tags=no-cache",
+                ),
+            ],
+        ]
+        .concat();
+        let config = load(&env, None).unwrap();
+        assert_eq!(config.cache_control.default, "public, max-age=30");
+        assert_eq!(
+            config.cache_control.routes,
+            [
+                (
+                    "/feed".to_string(),
+                    HeaderValue::from_static("public, max-age=600")
+                ),
+                ("/tags".to_string(), HeaderValue::from_static("no-cache")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cors_wildcard() {
+        let env = [REQUIRED, &[("CORS_ORIGINS", "*")]].concat();
+        assert_eq!(load(&env, None).unwrap().cors.origins, CorsOrigins::Any);
+
+        // Browsers refuse credentials for wildcard origins
+        let env = [
+            REQUIRED,
+            &[("CORS_ORIGINS", "*"), ("CORS_ALLOW_CREDENTIALS", "true")],
+        ]
+        .concat();
+        let error = load(&env, None).unwrap_err();
+        assert_eq!(error.errors.len(), 1);
+        assert!(error.errors[0].contains("CORS_ALLOW_CREDENTIALS"));
+    }
+
+    #[test]
+    fn test_errors_are_aggregated() {
+        let env = [
+            ("DATABASE_MAX_CONNECTIONS", "0"),
+            ("DATABASE_MIN_CONNECTIONS", "20"),
+            ("DATABASE_JOURNAL_MODE", "fast"),
+            ("SLOW_QUERY_THRESHOLD_MS", "-1"),
+            ("BIND_ADDRESS", "localhost"),
+            ("CORS_ORIGINS", "https://example.com/path"),
+            ("CORS_HEADERS", "content-type,bad header"),
+            ("AUTH_DISABLED", "yes"),
+            ("LOG_FORMAT", "yaml"),
+            ("STATIC_DIR", "/nonexistent/static"),
+            ("BACKUP_DIR", "/nonexistent/backups"),
+            ("INTEGRITY_CHECK_INTERVAL_HOURS", "0"),
+            ("LINK_CHECK_INTERVAL_HOURS", "weekly"),
+            ("COMPRESSION", "gzip,lzma"),
+            ("COMPRESSION_MIN_BYTES", "100000"),
+            ("CACHE_CONTROL_ROUTES", "feed=max-age=600"),
+            ("SITE_URL", "blog.example.com"),
+            ("NOTIFY_WEBHOOK_URL", "ftp://hooks.example.com"),
+            ("JWT_SECRET", ""),
+        ];
+        let error = load(&env, Some("unknown = 1")).unwrap_err();
+
+        let message = error.to_string();
+        for expected in [
+            "`unknown`",
+            "DATABASE_URL",
+            "DATABASE_MAX_CONNECTIONS",
+            "DATABASE_MIN_CONNECTIONS",
+            "DATABASE_JOURNAL_MODE",
+            "SLOW_QUERY_THRESHOLD_MS",
+            "BIND_ADDRESS",
+            "CORS_ORIGINS",
+            "CORS_HEADERS",
+            "AUTH_DISABLED",
+            "LOG_FORMAT",
+            "STATIC_DIR",
+            "BACKUP_DIR",
+            "INTEGRITY_CHECK_INTERVAL_HOURS",
+            "LINK_CHECK_INTERVAL_HOURS",
+            "JWT_SECRET",
+            "lzma",
+            "COMPRESSION_MIN_BYTES",
+            "CACHE_CONTROL_ROUTES",
+            "SITE_URL",
+            "NOTIFY_WEBHOOK_URL",
+        ] {
+            assert!(
+                message.contains(expected),
+                "{} missing from {}",
+                expected,
+                message
+            );
+        }
+        assert_eq!(error.errors.len(), 21);
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        let error = load(REQUIRED, Some("bind_address = ")).unwrap_err();
+        assert!(error.errors[0].contains("not valid TOML"));
+    }
+
+    #[test]
+    fn test_debug_hides_secrets() {
+        let secrets = [
+            ("API_KEY", "top-secret-key"),
+            (
+                "NOTIFY_WEBHOOK_URL",
+                "https://hooks.example.com/top-secret-path",
+            ),
+        ];
+        let config = load(&[REQUIRED, &secrets].concat(), None).unwrap();
+        let debug = format!("{:?}", config);
+        assert!(!debug.contains("top-secret"));
+        assert!(!debug.contains("\"secret\""));
+    }
+}
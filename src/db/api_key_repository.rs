@@ -0,0 +1,195 @@
+/// Repository for managing API keys in the database.
+/// Keys are hashed before they reach this layer's queries; the plaintext
+/// is only returned once, when a key is created.
+#[derive(Clone, Debug)]
+pub struct ApiKeyRepository {
+    pool: SqlitePool,
+    metrics: QueryMetrics,
+}
+
+impl ApiKeyRepository {
+    /// Creates a new ApiKeyRepository instance sharing the given pool
+    pub(crate) fn new(pool: SqlitePool, metrics: QueryMetrics) -> Self {
+        Self { pool, metrics }
+    }
+
+    /// Generates and stores a new key, returning it with its plaintext
+    pub async fn create(&self, request: CreateApiKey) -> DatabaseResult<CreatedApiKey> {
+        let _timer = self.metrics.time("api_keys.create", "");
+        request.validate().map_err(DatabaseError::from)?;
+
+        let name = request.name.trim();
+        let key = generate_api_key();
+        let key_hash = hash_api_key(&key);
+
+        let api_key = sqlx::query_as!(
+            ApiKey,
+            r#"
+            INSERT INTO api_keys (name, key_hash)
+            VALUES (?, ?)
+            RETURNING id, name, created_at, last_used_at, revoked_at
+            "#,
+            name,
+            key_hash
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        Ok(CreatedApiKey { api_key, key })
+    }
+
+    /// Lists all keys, including revoked ones, oldest first
+    pub async fn list(&self) -> DatabaseResult<Vec<ApiKey>> {
+        let _timer = self.metrics.time("api_keys.list", "");
+        sqlx::query_as!(
+            ApiKey,
+            r#"
+            SELECT id, name, created_at, last_used_at, revoked_at
+            FROM api_keys
+            ORDER BY id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)
+    }
+
+    /// Finds the key matching a plaintext key, unless it has been revoked
+    pub async fn find_active(&self, key: &str) -> DatabaseResult<Option<ApiKey>> {
+        let _timer = self.metrics.time("api_keys.find_active", "");
+        let key_hash = hash_api_key(key);
+        sqlx::query_as!(
+            ApiKey,
+            r#"
+            SELECT
+                id as "id!", name as "name!",
+                created_at as "created_at!", last_used_at, revoked_at
+            FROM api_keys
+            WHERE key_hash = ? AND revoked_at IS NULL
+            "#,
+            key_hash
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)
+    }
+
+    /// Records that a key was just used
+    pub async fn touch(&self, id: i64) -> DatabaseResult<()> {
+        let _timer = self.metrics.time("api_keys.touch", format!("id={}", id));
+        sqlx::query!(
+            "UPDATE api_keys SET last_used_at = CURRENT_TIMESTAMP WHERE id = ?",
+            id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+        Ok(())
+    }
+
+    /// Revokes a key so it is no longer accepted. Revoking a key twice keeps
+    /// the original revocation time.
+    pub async fn revoke(&self, id: i64) -> DatabaseResult<ApiKey> {
+        let _timer = self.metrics.time("api_keys.revoke", format!("id={}", id));
+        sqlx::query_as!(
+            ApiKey,
+            r#"
+            UPDATE api_keys
+            SET revoked_at = COALESCE(revoked_at, CURRENT_TIMESTAMP)
+            WHERE id = ?
+            RETURNING id, name, created_at, last_used_at, revoked_at
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?
+        .ok_or_else(|| DatabaseError::not_found("API key", &id.to_string()))
+    }
+}
+
+use sqlx::SqlitePool;
+
+use crate::models::api_key::{generate_api_key, hash_api_key, ApiKey, CreateApiKey, CreatedApiKey};
+
+use super::{error::DatabaseResult, DatabaseError, QueryMetrics};
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        db::{test_utils::create_test_db, Database},
+        models::api_key::API_KEY_PREFIX,
+    };
+
+    use super::*;
+
+    async fn setup() -> (Database, ApiKeyRepository) {
+        let db = create_test_db().await.unwrap();
+        let repo = db.api_keys().clone();
+        (db, repo)
+    }
+
+    fn named(name: &str) -> CreateApiKey {
+        CreateApiKey {
+            name: name.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_api_key() {
+        let (_, repo) = setup().await;
+
+        let created = repo.create(named(" CI publishing ")).await.unwrap();
+        assert_eq!(created.api_key.name, "CI publishing");
+        assert!(created.key.starts_with(API_KEY_PREFIX));
+        assert!(created.api_key.last_used_at.is_none());
+        assert!(created.api_key.revoked_at.is_none());
+
+        assert!(matches!(
+            repo.create(named(" ")).await.unwrap_err(),
+            DatabaseError::Validation {
+                field: Some("name"),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_find_and_touch_api_key() {
+        let (_, repo) = setup().await;
+
+        let created = repo.create(named("CI")).await.unwrap();
+        let found = repo.find_active(&created.key).await.unwrap().unwrap();
+        assert_eq!(found.id, created.api_key.id);
+        assert!(repo.find_active("bk_unknown").await.unwrap().is_none());
+
+        repo.touch(found.id).await.unwrap();
+        let listed = repo.list().await.unwrap();
+        assert!(listed[0].last_used_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_api_key() {
+        let (_, repo) = setup().await;
+
+        let created = repo.create(named("CI")).await.unwrap();
+        let other = repo.create(named("Backups")).await.unwrap();
+
+        let revoked = repo.revoke(created.api_key.id).await.unwrap();
+        let revoked_at = revoked.revoked_at.unwrap();
+        assert!(repo.find_active(&created.key).await.unwrap().is_none());
+        assert!(repo.find_active(&other.key).await.unwrap().is_some());
+
+        // Revoked keys are still listed
+        assert_eq!(repo.list().await.unwrap().len(), 2);
+
+        let again = repo.revoke(created.api_key.id).await.unwrap();
+        assert_eq!(again.revoked_at, Some(revoked_at));
+
+        assert!(matches!(
+            repo.revoke(999).await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+    }
+}
@@ -0,0 +1,419 @@
+/// Repository for reading the audit log.
+/// Entries are written by the post and tag repositories, on the connection
+/// of the transaction making the change, through `record_in`.
+#[derive(Clone, Debug)]
+pub struct AuditRepository {
+    pool: SqlitePool,
+    metrics: QueryMetrics,
+}
+
+impl AuditRepository {
+    /// Creates a new AuditRepository instance sharing the given pool
+    pub(crate) fn new(pool: SqlitePool, metrics: QueryMetrics) -> Self {
+        Self { pool, metrics }
+    }
+
+    /// Writes an entry on the caller's connection, so it commits or rolls
+    /// back together with the change it describes
+    pub(crate) async fn record_in(
+        conn: &mut SqliteConnection,
+        actor: Actor,
+        event: &AuditEvent,
+    ) -> DatabaseResult<()> {
+        let actor = actor.to_string();
+        let changes = event.changes.to_string();
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_log (actor, action, entity_type, entity_id, changes)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+            actor,
+            event.action,
+            event.entity_type,
+            event.entity_id,
+            changes
+        )
+        .execute(conn)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+        Ok(())
+    }
+
+    /// Lists entries matching the query, newest first, together with the
+    /// total number of matching entries.
+    ///
+    /// `entity_id` is only meaningful together with `entity_type`; the limit
+    /// must be between 1 and 200.
+    pub async fn list(&self, query: &AuditQuery) -> DatabaseResult<(Vec<AuditEntry>, i64)> {
+        let _timer = self.metrics.time(
+            "audit.list",
+            format!(
+                "entity_id={:?} limit={} offset={}",
+                query.entity_id, query.limit, query.offset
+            ),
+        );
+        if query.entity_id.is_some() && query.entity_type.is_none() {
+            return Err(DatabaseError::invalid_field(
+                "entity_id",
+                "entity_id requires entity",
+            ));
+        }
+        if !(1..=MAX_AUDIT_LIMIT).contains(&query.limit) {
+            return Err(DatabaseError::invalid_field(
+                "limit",
+                &format!("Limit must be between 1 and {}", MAX_AUDIT_LIMIT),
+            ));
+        }
+        if query.offset < 0 {
+            return Err(DatabaseError::invalid_field(
+                "offset",
+                "Offset cannot be negative",
+            ));
+        }
+
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+
+        let entries = sqlx::query_as!(
+            AuditEntry,
+            r#"
+            SELECT
+                id, actor,
+                action as "action: AuditAction",
+                entity_type as "entity_type: AuditEntity",
+                entity_id,
+                changes as "changes: Json<Value>",
+                created_at
+            FROM audit_log
+            WHERE
+                (? IS NULL OR entity_type = ?)
+                AND (? IS NULL OR entity_id = ?)
+            ORDER BY id DESC
+            LIMIT ?
+            OFFSET ?
+            "#,
+            query.entity_type,
+            query.entity_type,
+            query.entity_id,
+            query.entity_id,
+            query.limit,
+            query.offset
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        let total = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*)
+            FROM audit_log
+            WHERE
+                (? IS NULL OR entity_type = ?)
+                AND (? IS NULL OR entity_id = ?)
+            "#,
+            query.entity_type,
+            query.entity_type,
+            query.entity_id,
+            query.entity_id
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok((entries, total))
+    }
+
+    /// Lists recent changes to content, newest first: posts created,
+    /// updated, published or deleted, and tags created or renamed.
+    ///
+    /// Only entries made at or before `before` are listed, when given. Each
+    /// entry carries the title or name the change gave the entity, or else
+    /// its current one, or its last one once deleted. The limit must be
+    /// between 1 and 200.
+    pub async fn activity(
+        &self,
+        before: Option<OffsetDateTime>,
+        limit: i64,
+    ) -> DatabaseResult<Vec<ActivityEntry>> {
+        let _timer = self.metrics.time(
+            "audit.activity",
+            format!("before={:?} limit={}", before, limit),
+        );
+        if !(1..=MAX_AUDIT_LIMIT).contains(&limit) {
+            return Err(DatabaseError::invalid_field(
+                "limit",
+                &format!("Limit must be between 1 and {}", MAX_AUDIT_LIMIT),
+            ));
+        }
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                a.id as "id!",
+                a.entity_type as "entity_type: AuditEntity",
+                a.entity_id,
+                a.action as "action: AuditAction",
+                a.changes as "changes: Json<Value>",
+                COALESCE(
+                    json_extract(a.changes, '$.title.new'),
+                    json_extract(a.changes, '$.name.new'),
+                    CASE WHEN a.action IN ('create', 'delete') THEN
+                        COALESCE(json_extract(a.changes, '$.title'), json_extract(a.changes, '$.name'))
+                    END,
+                    p.title,
+                    t.name,
+                    (
+                        SELECT COALESCE(json_extract(d.changes, '$.title'), json_extract(d.changes, '$.name'))
+                        FROM audit_log d
+                        WHERE d.entity_type = a.entity_type
+                            AND d.entity_id = a.entity_id
+                            AND d.action = 'delete'
+                    )
+                ) as "title: String",
+                a.created_at
+            FROM audit_log a
+            LEFT JOIN posts p ON a.entity_type = 'post' AND p.id = a.entity_id
+            LEFT JOIN tags t ON a.entity_type = 'tag' AND t.id = a.entity_id
+            WHERE
+                (? IS NULL OR julianday(a.created_at) <= julianday(?))
+                AND (
+                    a.entity_type = 'post'
+                    OR a.action = 'create'
+                    OR (a.action IN ('update', 'patch') AND json_extract(a.changes, '$.name') IS NOT NULL)
+                )
+            ORDER BY a.id DESC
+            LIMIT ?
+            "#,
+            before,
+            before,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ActivityEntry {
+                id: row.id,
+                entity_type: row.entity_type,
+                entity_id: row.entity_id,
+                title: row.title,
+                action: ActivityAction::of(row.entity_type, row.action, &row.changes),
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+}
+
+/// Largest page of audit entries a single request may ask for
+const MAX_AUDIT_LIMIT: i64 = 200;
+
+use serde_json::Value;
+use sqlx::{types::Json, SqliteConnection, SqlitePool};
+use time::OffsetDateTime;
+
+use crate::models::{
+    activity::{ActivityAction, ActivityEntry},
+    audit::{Actor, AuditAction, AuditEntity, AuditEntry, AuditEvent, AuditQuery},
+};
+
+use super::{error::DatabaseResult, DatabaseError, QueryMetrics};
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        db::{test_utils::create_test_db, Database},
+        models::{
+            post::{CreatePost, PatchPost},
+            tag::PatchTag,
+        },
+    };
+
+    use super::*;
+
+    async fn setup() -> (Database, AuditRepository) {
+        let db = create_test_db().await.unwrap();
+        let repo = db.audit().clone();
+        (db, repo)
+    }
+
+    fn query(entity_type: Option<AuditEntity>, entity_id: Option<i64>) -> AuditQuery {
+        AuditQuery {
+            entity_type,
+            entity_id,
+            limit: 50,
+            offset: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_list() {
+        let (db, repo) = setup().await;
+
+        let mut conn = db.pool().acquire().await.unwrap();
+        for (entity_type, entity_id) in [
+            (AuditEntity::Post, 1),
+            (AuditEntity::Tag, 1),
+            (AuditEntity::Post, 2),
+        ] {
+            let event = AuditEvent {
+                action: AuditAction::Create,
+                entity_type,
+                entity_id,
+                changes: json!({ "title": "x" }),
+            };
+            AuditRepository::record_in(&mut conn, Actor::User(7), &event)
+                .await
+                .unwrap();
+        }
+        drop(conn);
+
+        let (entries, total) = repo.list(&query(None, None)).await.unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(entries[0].entity_id, 2);
+        assert_eq!(entries[0].actor, "user:7");
+        assert_eq!(entries[0].changes.0, json!({ "title": "x" }));
+
+        let (entries, total) = repo
+            .list(&query(Some(AuditEntity::Post), Some(1)))
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(entries[0].entity_type, AuditEntity::Post);
+
+        let (_, total) = repo
+            .list(&query(Some(AuditEntity::Post), None))
+            .await
+            .unwrap();
+        assert_eq!(total, 2);
+    }
+
+    #[tokio::test]
+    async fn test_activity() {
+        let (db, repo) = setup().await;
+        let posts = db.posts();
+        let tags = db.tags();
+
+        let draft = posts
+            .create(
+                CreatePost::builder()
+                    .title("Draft")
+                    .slug("draft")
+                    .content("Content")
+                    .description("Description")
+                    .build()
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let tag = tags.create("rust").await.unwrap();
+        posts
+            .patch(PatchPost {
+                id: draft.id,
+                content: Some("More content".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        tags.patch(
+            tag.id,
+            PatchTag {
+                name: Some("Rust".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        // Neither a tag's color nor its deletion is content activity
+        tags.patch(
+            tag.id,
+            PatchTag {
+                color: Some(Some("#ff0000".to_string())),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        posts
+            .patch(PatchPost {
+                id: draft.id,
+                title: Some("Live".to_string()),
+                published: Some(true),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        tags.delete(tag.id).await.unwrap();
+        posts.delete(draft.id).await.unwrap();
+
+        let activity = repo.activity(None, 50).await.unwrap();
+        let summary: Vec<_> = activity
+            .iter()
+            .map(|entry| (entry.entity_type, entry.action, entry.title.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (AuditEntity::Post, ActivityAction::Deleted, Some("Live")),
+                (AuditEntity::Post, ActivityAction::Published, Some("Live")),
+                (AuditEntity::Tag, ActivityAction::Renamed, Some("Rust")),
+                (AuditEntity::Post, ActivityAction::Updated, Some("Live")),
+                (AuditEntity::Tag, ActivityAction::Created, Some("rust")),
+                (AuditEntity::Post, ActivityAction::Created, Some("Draft")),
+            ]
+        );
+
+        // Paging back in time from the oldest two entries
+        sqlx::query("UPDATE audit_log SET created_at = '2024-01-01 00:00:00' WHERE id <= ?")
+            .bind(activity[4].id)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let before = time::macros::datetime!(2024-01-01 00:00:00.5 UTC);
+        let older = repo.activity(Some(before), 50).await.unwrap();
+        assert_eq!(
+            older.iter().map(|entry| entry.id).collect::<Vec<_>>(),
+            [activity[4].id, activity[5].id]
+        );
+        assert_eq!(repo.activity(Some(before), 1).await.unwrap().len(), 1);
+
+        assert!(matches!(
+            repo.activity(None, 0).await.unwrap_err(),
+            DatabaseError::Validation {
+                field: Some("limit"),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_validation() {
+        let (_, repo) = setup().await;
+
+        for (invalid, field) in [
+            (query(None, Some(1)), "entity_id"),
+            (
+                AuditQuery {
+                    limit: 0,
+                    ..query(None, None)
+                },
+                "limit",
+            ),
+            (
+                AuditQuery {
+                    offset: -1,
+                    ..query(None, None)
+                },
+                "offset",
+            ),
+        ] {
+            match repo.list(&invalid).await.unwrap_err() {
+                DatabaseError::Validation { field: f, .. } => assert_eq!(f, Some(field)),
+                other => panic!("expected a validation error, got {:?}", other),
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+/// Repository for clicks on posts' external URLs
+#[derive(Clone, Debug)]
+pub struct ClickRepository {
+    pool: SqlitePool,
+    metrics: QueryMetrics,
+}
+
+impl ClickRepository {
+    /// Creates a new ClickRepository instance sharing the given pool
+    pub(crate) fn new(pool: SqlitePool, metrics: QueryMetrics) -> Self {
+        Self { pool, metrics }
+    }
+
+    /// Records a click on the post's external URL, keeping at most
+    /// `MAX_REFERER_LEN` characters of the referer
+    pub async fn record(&self, post_id: i64, referer: Option<&str>) -> DatabaseResult<()> {
+        let _timer = self
+            .metrics
+            .time("clicks.record", format!("post_id={}", post_id));
+        let referer = referer.map(truncate_referer);
+        sqlx::query!(
+            "INSERT INTO link_clicks (post_id, referer) VALUES (?, ?)",
+            post_id,
+            referer
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match constraint_violation(&e) {
+            Some(ConstraintViolation::ForeignKey) => {
+                DatabaseError::not_found("Post", &post_id.to_string())
+            }
+            _ => DatabaseError::Sqlx(e),
+        })?;
+        Ok(())
+    }
+
+    /// Counts a post's clicks per day, oldest day first. Days without
+    /// clicks are left out. Fails with `NotFound` when the post doesn't
+    /// exist.
+    pub async fn daily_counts(&self, post_id: i64) -> DatabaseResult<Vec<DailyClicks>> {
+        let _timer = self
+            .metrics
+            .time("clicks.daily_counts", format!("post_id={}", post_id));
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+
+        let post = sqlx::query_scalar!("SELECT id FROM posts WHERE id = ?", post_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+        if post.is_none() {
+            return Err(DatabaseError::not_found("Post", &post_id.to_string()));
+        }
+
+        let counts = sqlx::query_as!(
+            DailyClicks,
+            r#"
+            SELECT date(clicked_at) as "date!: String", COUNT(*) as "clicks!: i64"
+            FROM link_clicks
+            WHERE post_id = ?
+            GROUP BY date(clicked_at)
+            ORDER BY date(clicked_at)
+            "#,
+            post_id
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok(counts)
+    }
+}
+
+use sqlx::SqlitePool;
+
+use crate::models::click::{truncate_referer, DailyClicks};
+
+use super::{
+    error::{constraint_violation, ConstraintViolation, DatabaseResult},
+    DatabaseError, QueryMetrics,
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::{db::test_utils::create_test_db, models::click::MAX_REFERER_LEN};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_daily_counts() {
+        let db = create_test_db().await.unwrap();
+        sqlx::query(
+            "INSERT INTO posts (id, category, title, slug, content, description, external_url)
+             VALUES (1, 'reading', 'Book', 'book', 'Content', 'Description', 'https://example.com')",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        let repo = db.clicks();
+
+        repo.record(1, Some("https://news.example.com/"))
+            .await
+            .unwrap();
+        repo.record(1, None).await.unwrap();
+        let long = "x".repeat(MAX_REFERER_LEN * 2);
+        repo.record(1, Some(&long)).await.unwrap();
+        sqlx::query(
+            "UPDATE link_clicks SET clicked_at = '2025-04-01 10:00:00' WHERE referer IS NULL",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        let counts = repo.daily_counts(1).await.unwrap();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(
+            counts[0],
+            DailyClicks {
+                date: "2025-04-01".to_string(),
+                clicks: 1
+            }
+        );
+        assert_eq!(counts[1].clicks, 2);
+
+        let stored: i64 = sqlx::query_scalar("SELECT MAX(length(referer)) FROM link_clicks")
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(stored, MAX_REFERER_LEN as i64);
+
+        assert!(matches!(
+            repo.record(999, None).await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+        assert!(matches!(
+            repo.daily_counts(999).await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+    }
+}
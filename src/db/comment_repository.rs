@@ -0,0 +1,350 @@
+/// Repository for reader comments and their moderation.
+/// Comments are created pending; readers only ever see approved ones.
+#[derive(Clone, Debug)]
+pub struct CommentRepository {
+    pool: SqlitePool,
+    metrics: QueryMetrics,
+}
+
+impl CommentRepository {
+    /// Creates a new CommentRepository instance sharing the given pool
+    pub(crate) fn new(pool: SqlitePool, metrics: QueryMetrics) -> Self {
+        Self { pool, metrics }
+    }
+
+    /// Stores a comment on a post, pending moderation unless the caller
+    /// already knows it to be spam
+    pub async fn create(
+        &self,
+        post_id: i64,
+        comment: CreateComment,
+        status: CommentStatus,
+    ) -> DatabaseResult<Comment> {
+        let _timer = self.metrics.time(
+            "comments.create",
+            format!("post_id={} status={}", post_id, status),
+        );
+        comment.validate().map_err(DatabaseError::from)?;
+
+        let author_name = comment.author_name.trim();
+        let author_email_hash = hash_email(&comment.author_email);
+        let body = comment.body.trim();
+
+        sqlx::query_as!(
+            Comment,
+            r#"
+            INSERT INTO comments (post_id, author_name, author_email_hash, body, status)
+            VALUES (?, ?, ?, ?, ?)
+            RETURNING
+                id as "id!", post_id, author_name, author_email_hash, body,
+                status as "status: CommentStatus", created_at
+            "#,
+            post_id,
+            author_name,
+            author_email_hash,
+            body,
+            status
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match constraint_violation(&e) {
+            Some(ConstraintViolation::ForeignKey) => {
+                DatabaseError::not_found("Post", &post_id.to_string())
+            }
+            _ => DatabaseError::Sqlx(e),
+        })
+    }
+
+    /// Lists a post's approved comments, oldest first. Fails with
+    /// `NotFound` when the post doesn't exist.
+    pub async fn list_approved(&self, post_id: i64) -> DatabaseResult<Vec<Comment>> {
+        let _timer = self
+            .metrics
+            .time("comments.list_approved", format!("post_id={}", post_id));
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+
+        let post = sqlx::query_scalar!("SELECT id FROM posts WHERE id = ?", post_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+        if post.is_none() {
+            return Err(DatabaseError::not_found("Post", &post_id.to_string()));
+        }
+
+        let comments = sqlx::query_as!(
+            Comment,
+            r#"
+            SELECT
+                id as "id!", post_id, author_name, author_email_hash, body,
+                status as "status: CommentStatus", created_at
+            FROM comments
+            WHERE post_id = ? AND status = 'approved'
+            ORDER BY created_at, id
+            "#,
+            post_id
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok(comments)
+    }
+
+    /// Lists comments of every post in the given status, oldest first, with
+    /// the total number in that status
+    pub async fn list_by_status(
+        &self,
+        status: CommentStatus,
+        limit: i64,
+        offset: i64,
+    ) -> DatabaseResult<(Vec<Comment>, i64)> {
+        let _timer = self.metrics.time(
+            "comments.list_by_status",
+            format!("status={} limit={} offset={}", status, limit, offset),
+        );
+        if !(1..=MAX_COMMENT_LIMIT).contains(&limit) {
+            return Err(DatabaseError::invalid_field(
+                "limit",
+                &format!("Limit must be between 1 and {}", MAX_COMMENT_LIMIT),
+            ));
+        }
+        if offset < 0 {
+            return Err(DatabaseError::invalid_field(
+                "offset",
+                "Offset cannot be negative",
+            ));
+        }
+
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+
+        let comments = sqlx::query_as!(
+            Comment,
+            r#"
+            SELECT
+                id as "id!", post_id, author_name, author_email_hash, body,
+                status as "status: CommentStatus", created_at
+            FROM comments
+            WHERE status = ?
+            ORDER BY created_at, id
+            LIMIT ?
+            OFFSET ?
+            "#,
+            status,
+            limit,
+            offset
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        let total = sqlx::query_scalar!("SELECT COUNT(*) FROM comments WHERE status = ?", status)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok((comments, total))
+    }
+
+    /// Moves a comment to another status, e.g. approving it or marking it
+    /// as spam
+    pub async fn set_status(&self, id: i64, status: CommentStatus) -> DatabaseResult<Comment> {
+        let _timer = self.metrics.time(
+            "comments.set_status",
+            format!("id={} status={}", id, status),
+        );
+        sqlx::query_as!(
+            Comment,
+            r#"
+            UPDATE comments
+            SET status = ?
+            WHERE id = ?
+            RETURNING
+                id as "id!", post_id, author_name, author_email_hash, body,
+                status as "status: CommentStatus", created_at
+            "#,
+            status,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?
+        .ok_or_else(|| DatabaseError::not_found("Comment", &id.to_string()))
+    }
+
+    /// Deletes a comment for good
+    pub async fn delete(&self, id: i64) -> DatabaseResult<()> {
+        let _timer = self.metrics.time("comments.delete", format!("id={}", id));
+        let result = sqlx::query!("DELETE FROM comments WHERE id = ?", id)
+            .execute(&self.pool)
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::not_found("Comment", &id.to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Largest page of comments a single request may ask for
+const MAX_COMMENT_LIMIT: i64 = 200;
+
+use sqlx::SqlitePool;
+
+use crate::models::comment::{hash_email, Comment, CommentStatus, CreateComment};
+
+use super::{
+    error::{constraint_violation, ConstraintViolation, DatabaseResult},
+    DatabaseError, QueryMetrics,
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        db::{test_utils::create_test_db, Database},
+        models::post::CreatePost,
+    };
+
+    use super::*;
+
+    async fn setup() -> (Database, i64) {
+        let db = create_test_db().await.unwrap();
+        let post = db
+            .posts()
+            .create(
+                CreatePost::builder()
+                    .title("Commented")
+                    .slug("commented")
+                    .content("Content")
+                    .description("Description")
+                    .published(true)
+                    .build()
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        (db, post.id)
+    }
+
+    fn comment(body: &str) -> CreateComment {
+        CreateComment {
+            author_name: "Reader".to_string(),
+            author_email: "Reader@Example.com".to_string(),
+            body: body.to_string(),
+            form_token: String::new(),
+            website: None,
+        }
+    }
+
+    async fn create(repo: &CommentRepository, post_id: i64, body: &str) -> Comment {
+        repo.create(post_id, comment(body), CommentStatus::Pending)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_comment() {
+        let (db, post_id) = setup().await;
+        let repo = db.comments();
+
+        let created = create(repo, post_id, " First! ").await;
+        assert_eq!(created.status, CommentStatus::Pending);
+        assert_eq!(created.body, "First!");
+        assert_eq!(created.author_email_hash, hash_email("reader@example.com"));
+
+        let spam = repo
+            .create(post_id, comment("Spam"), CommentStatus::Spam)
+            .await
+            .unwrap();
+        assert_eq!(spam.status, CommentStatus::Spam);
+
+        assert!(matches!(
+            repo.create(999, comment("Lost"), CommentStatus::Pending)
+                .await
+                .unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+        assert!(matches!(
+            repo.create(post_id, comment(" "), CommentStatus::Pending)
+                .await
+                .unwrap_err(),
+            DatabaseError::Validation {
+                field: Some("body"),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_only_approved_comments_are_listed() {
+        let (db, post_id) = setup().await;
+        let repo = db.comments();
+
+        let approved = create(repo, post_id, "Approved").await;
+        let spam = create(repo, post_id, "Spam").await;
+        let pending = create(repo, post_id, "Pending").await;
+        repo.set_status(approved.id, CommentStatus::Approved)
+            .await
+            .unwrap();
+        repo.set_status(spam.id, CommentStatus::Spam).await.unwrap();
+
+        let listed = repo.list_approved(post_id).await.unwrap();
+        assert_eq!(
+            listed.iter().map(|c| c.id).collect::<Vec<_>>(),
+            [approved.id]
+        );
+        assert!(matches!(
+            repo.list_approved(999).await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+
+        let (queue, total) = repo
+            .list_by_status(CommentStatus::Pending, 50, 0)
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(queue[0].id, pending.id);
+
+        assert!(matches!(
+            repo.set_status(999, CommentStatus::Approved)
+                .await
+                .unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+        assert!(matches!(
+            repo.list_by_status(CommentStatus::Pending, 0, 0)
+                .await
+                .unwrap_err(),
+            DatabaseError::Validation {
+                field: Some("limit"),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_comments_are_deleted_with_their_post() {
+        let (db, post_id) = setup().await;
+        let repo = db.comments();
+
+        let kept = create(repo, post_id, "Kept").await;
+        repo.set_status(kept.id, CommentStatus::Approved)
+            .await
+            .unwrap();
+        let deleted = create(repo, post_id, "Deleted").await;
+        repo.delete(deleted.id).await.unwrap();
+        assert!(matches!(
+            repo.delete(deleted.id).await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+        assert_eq!(repo.list_approved(post_id).await.unwrap().len(), 1);
+
+        db.posts().delete(post_id).await.unwrap();
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM comments")
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+}
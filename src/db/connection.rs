@@ -1,8 +1,136 @@
-use dotenv::dotenv;
-use sqlx::SqlitePool;
-use std::env;
+use std::{future::Future, pin::Pin, str::FromStr, time::Duration};
 
-use super::{error::DatabaseResult, DatabaseError, PostRepository, TagRepository};
+use sqlx::{
+    migrate::Migrator,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
+    SqliteConnection, SqlitePool,
+};
+
+use super::{
+    error::DatabaseResult, ApiKeyRepository, AuditRepository, ClickRepository, CommentRepository,
+    ContactRepository, DatabaseError, IdempotencyRepository, LinkCheckRepository, PostRepository,
+    QueryMetrics, RenderCacheRepository, StatsRepository, TagRepository, UserRepository,
+};
+use crate::{config::Config, models::audit::Actor};
+
+/// How every pooled SQLite connection is set up. Foreign keys are always
+/// enforced, since deleting posts and tags relies on cascades.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SqliteConfig {
+    /// WAL lets reads proceed while a write is in progress
+    pub journal_mode: SqliteJournalMode,
+    /// `Normal` is safe with WAL and avoids a sync on every commit
+    pub synchronous: SqliteSynchronous,
+    /// How long a write waits for the lock before failing with
+    /// `database is locked`
+    pub busy_timeout: Duration,
+}
+
+impl Default for SqliteConfig {
+    fn default() -> Self {
+        Self {
+            journal_mode: SqliteJournalMode::Wal,
+            synchronous: SqliteSynchronous::Normal,
+            busy_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl SqliteConfig {
+    fn connect_options(&self, url: &str) -> DatabaseResult<SqliteConnectOptions> {
+        Ok(SqliteConnectOptions::from_str(url)
+            .map_err(DatabaseError::Sqlx)?
+            .foreign_keys(true)
+            .journal_mode(self.journal_mode)
+            .synchronous(self.synchronous)
+            .busy_timeout(self.busy_timeout))
+    }
+}
+
+/// Connection pool limits, and how long to keep retrying when the
+/// database can't be opened at startup
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    /// Connections kept open even when idle
+    pub min_connections: u32,
+    /// How long a query waits for a free connection before failing
+    pub acquire_timeout: Duration,
+    /// Idle connections above `min_connections` are closed after this long
+    pub idle_timeout: Option<Duration>,
+    /// How long the first connection is retried for, e.g. while a volume is
+    /// still being mounted; zero gives up after the first attempt
+    pub connect_retry: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            min_connections: 0,
+            acquire_timeout: Duration::from_secs(30),
+            idle_timeout: Some(Duration::from_secs(600)),
+            connect_retry: Duration::from_secs(30),
+        }
+    }
+}
+
+impl PoolConfig {
+    fn pool_options(&self) -> SqlitePoolOptions {
+        SqlitePoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .acquire_timeout(self.acquire_timeout)
+            .idle_timeout(self.idle_timeout)
+    }
+}
+
+/// The first delay between connection attempts, doubled after each one
+const FIRST_RETRY_DELAY: Duration = Duration::from_millis(100);
+/// The longest delay between connection attempts
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Opens the pool, retrying with exponential backoff until
+/// `config.connect_retry` has passed
+async fn connect_with_retry(
+    config: &PoolConfig,
+    options: SqliteConnectOptions,
+) -> DatabaseResult<SqlitePool> {
+    let started = tokio::time::Instant::now();
+    let mut delay = FIRST_RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        let error = match config.pool_options().connect_with(options.clone()).await {
+            Ok(pool) => return Ok(pool),
+            Err(error) => error,
+        };
+        let elapsed = started.elapsed();
+        if elapsed + delay > config.connect_retry {
+            return Err(DatabaseError::Configuration(format!(
+                "could not open the database after {} attempt(s) over {:.1}s: {}",
+                attempt,
+                elapsed.as_secs_f32(),
+                error
+            )));
+        }
+        tracing::warn!(
+            attempt,
+            retry_in_ms = delay.as_millis() as u64,
+            "Could not open the database: {}",
+            error
+        );
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RETRY_DELAY);
+        attempt += 1;
+    }
+}
+
+/// Migrations embedded in the binary, applied on startup
+pub(super) static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// The future a `Database::run_in_transaction` closure returns, borrowing
+/// the transaction's connection
+pub type TransactionFuture<'c, T> = Pin<Box<dyn Future<Output = DatabaseResult<T>> + Send + 'c>>;
 
 /// Main database interface that provides access to all repositories
 #[derive(Clone, Debug)]
@@ -10,34 +138,111 @@ pub struct Database {
     pool: SqlitePool,
     posts: PostRepository,
     tags: TagRepository,
+    users: UserRepository,
+    api_keys: ApiKeyRepository,
+    audit: AuditRepository,
+    idempotency: IdempotencyRepository,
+    comments: CommentRepository,
+    contact: ContactRepository,
+    clicks: ClickRepository,
+    link_checks: LinkCheckRepository,
+    render_cache: RenderCacheRepository,
+    stats: StatsRepository,
+    metrics: QueryMetrics,
 }
 
 impl Database {
-    /// Creates a new Database instance, establishing the connection pool
-    /// and running any pending migrations
-    pub async fn new() -> DatabaseResult<Self> {
-        // Load environment variables
-        dotenv().ok();
-        let db_url = env::var("DATABASE_URL")
-            .map_err(|_| DatabaseError::Configuration("DATABASE_URL must be set".to_string()))?;
-
-        // Create connection pool
-        let pool = SqlitePool::connect(&db_url)
+    /// Creates a new Database instance from the server configuration,
+    /// establishing the connection pool and running any pending migrations.
+    /// A database that can't be opened yet is retried for
+    /// `config.pool.connect_retry`.
+    pub async fn new(config: &Config) -> DatabaseResult<Self> {
+        let options = config.sqlite.connect_options(&config.database_url)?;
+        let pool = connect_with_retry(&config.pool, options).await?;
+        log_pragmas(&pool).await?;
+
+        let db = Self::open(pool, QueryMetrics::new(config.slow_query_threshold)).await?;
+        if let Some(id) = config.system_user_id {
+            db.users().find_by_id(id).await.map_err(|e| match e {
+                DatabaseError::NotFound(_) => DatabaseError::Configuration(format!(
+                    "SYSTEM_USER_ID {} is not an existing user",
+                    id
+                )),
+                e => e,
+            })?;
+        }
+        Ok(Self {
+            posts: db
+                .posts
+                .clone()
+                .with_cache(config.cache_ttl)
+                .with_max_content_bytes(config.max_content_bytes)
+                .with_system_user(config.system_user_id)
+                .with_lowercase_tag_names(config.lowercase_tag_names),
+            tags: db
+                .tags
+                .clone()
+                .with_lowercase_names(config.lowercase_tag_names),
+            ..db
+        })
+    }
+
+    /// Connects to the database at `url` with a default pool and SQLite
+    /// settings and runs any pending migrations. Post reads are cached for
+    /// `DEFAULT_CACHE_TTL`.
+    pub async fn connect(url: &str) -> DatabaseResult<Self> {
+        let options = SqliteConfig::default().connect_options(url)?;
+        let pool = SqlitePool::connect_with(options)
             .await
             .map_err(DatabaseError::Sqlx)?;
 
-        // Run migrations
-        sqlx::migrate!("./migrations")
+        Self::from_pool(pool).await
+    }
+
+    /// Wraps an existing pool, running any pending migrations on it first.
+    /// Applied migrations are recorded in the database, so wrapping the same
+    /// pool again applies nothing twice.
+    pub async fn from_pool(pool: SqlitePool) -> DatabaseResult<Self> {
+        Self::open(pool, QueryMetrics::default()).await
+    }
+
+    /// Runs pending migrations on `pool` and sets up the repositories,
+    /// which time their calls into `metrics`
+    async fn open(pool: SqlitePool, metrics: QueryMetrics) -> DatabaseResult<Self> {
+        MIGRATOR
             .run(&pool)
             .await
             .map_err(DatabaseError::Migration)?;
 
-        // Initialize repositories
-
-        let tags = TagRepository::new(pool.clone());
-        let posts = PostRepository::new(pool.clone());
+        let tags = TagRepository::new(pool.clone(), metrics.clone());
+        let posts = PostRepository::new(pool.clone(), metrics.clone());
+        let users = UserRepository::new(pool.clone(), metrics.clone());
+        let api_keys = ApiKeyRepository::new(pool.clone(), metrics.clone());
+        let audit = AuditRepository::new(pool.clone(), metrics.clone());
+        let idempotency = IdempotencyRepository::new(pool.clone(), metrics.clone());
+        let comments = CommentRepository::new(pool.clone(), metrics.clone());
+        let contact = ContactRepository::new(pool.clone(), metrics.clone());
+        let clicks = ClickRepository::new(pool.clone(), metrics.clone());
+        let link_checks = LinkCheckRepository::new(pool.clone(), metrics.clone());
+        let render_cache = RenderCacheRepository::new(pool.clone(), metrics.clone());
+        let stats = StatsRepository::new(pool.clone(), metrics.clone());
 
-        Ok(Self { pool, posts, tags })
+        Ok(Self {
+            pool,
+            posts,
+            tags,
+            users,
+            api_keys,
+            audit,
+            idempotency,
+            comments,
+            contact,
+            clicks,
+            link_checks,
+            render_cache,
+            stats,
+            metrics,
+        })
     }
 
     /// Provides access to post-related operations
@@ -50,6 +255,72 @@ impl Database {
         &self.tags
     }
 
+    /// Provides access to user account operations
+    pub fn users(&self) -> &UserRepository {
+        &self.users
+    }
+
+    /// Provides access to API key operations
+    pub fn api_keys(&self) -> &ApiKeyRepository {
+        &self.api_keys
+    }
+
+    /// Provides access to the audit log
+    pub fn audit(&self) -> &AuditRepository {
+        &self.audit
+    }
+
+    /// Provides access to responses stored under idempotency keys
+    pub fn idempotency(&self) -> &IdempotencyRepository {
+        &self.idempotency
+    }
+
+    /// Provides access to comments and their moderation
+    pub fn comments(&self) -> &CommentRepository {
+        &self.comments
+    }
+
+    /// Provides access to contact form messages
+    pub fn contact(&self) -> &ContactRepository {
+        &self.contact
+    }
+
+    /// Provides access to clicks on posts' external URLs
+    pub fn clicks(&self) -> &ClickRepository {
+        &self.clicks
+    }
+
+    /// Provides access to the results of checking posts' links
+    pub fn link_checks(&self) -> &LinkCheckRepository {
+        &self.link_checks
+    }
+
+    /// Provides access to HTML rendered from posts' Markdown
+    pub fn render_cache(&self) -> &RenderCacheRepository {
+        &self.render_cache
+    }
+
+    /// Provides access to figures summarizing the posts
+    pub fn stats(&self) -> &StatsRepository {
+        &self.stats
+    }
+
+    /// Durations of repository calls, by method
+    pub fn query_metrics(&self) -> &QueryMetrics {
+        &self.metrics
+    }
+
+    /// Returns a handle whose post and tag changes are recorded in the
+    /// audit log as made by `actor`. Changes made through any other handle
+    /// are attributed to the system.
+    pub fn acting_as(&self, actor: Actor) -> Self {
+        Self {
+            posts: self.posts.acting_as(actor),
+            tags: self.tags.acting_as(actor),
+            ..self.clone()
+        }
+    }
+
     /// Provides direct access to the connection pool if needed
     /// Note: Prefer using the repository methods instead of direct pool access
     pub fn pool(&self) -> &SqlitePool {
@@ -63,55 +334,206 @@ impl Database {
             .await
             .map_err(|e| DatabaseError::Transaction(e.to_string()))
     }
+
+    /// Runs `operation` in a transaction, committing if it succeeds and
+    /// rolling back if it fails. The operation passes the connection it is
+    /// given to the repositories' `_in` methods, and returns a boxed future
+    /// that may only borrow that connection, so it owns whatever else it
+    /// uses, e.g. clones of the repositories. Cached post reads are
+    /// forgotten once the transaction commits.
+    pub async fn run_in_transaction<T, F>(&self, operation: F) -> DatabaseResult<T>
+    where
+        F: for<'c> FnOnce(&'c mut SqliteConnection) -> TransactionFuture<'c, T>,
+    {
+        let mut tx = self.transaction().await?;
+        match operation(&mut tx).await {
+            Ok(value) => {
+                tx.commit()
+                    .await
+                    .map_err(|e| DatabaseError::Transaction(e.to_string()))?;
+                self.posts.clear_cache();
+                Ok(value)
+            }
+            Err(error) => {
+                tx.rollback()
+                    .await
+                    .map_err(|e| DatabaseError::Transaction(e.to_string()))?;
+                Err(error)
+            }
+        }
+    }
+}
+
+/// Logs the settings SQLite actually applied, which can differ from the
+/// configured ones: in-memory databases, for one, have no WAL
+async fn log_pragmas(pool: &SqlitePool) -> DatabaseResult<()> {
+    let mut conn = pool.acquire().await.map_err(DatabaseError::Sqlx)?;
+    let (journal_mode, synchronous, foreign_keys, busy_timeout_ms): (String, i64, i64, i64) =
+        sqlx::query_as(
+            r#"
+            SELECT
+                (SELECT journal_mode FROM pragma_journal_mode),
+                (SELECT synchronous FROM pragma_synchronous),
+                (SELECT foreign_keys FROM pragma_foreign_keys),
+                (SELECT timeout FROM pragma_busy_timeout)
+            "#,
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+    tracing::info!(
+        journal_mode,
+        synchronous,
+        foreign_keys,
+        busy_timeout_ms,
+        "Opened SQLite database"
+    );
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::{env, sync::Once};
+    use crate::{db::test_utils::create_test_db, models::tag::TagRequest};
+
+    #[tokio::test]
+    async fn test_new_database_connection() {
+        let db = Database::new(&Config::default_for_tests()).await;
+        assert!(db.is_ok(), "Should successfully create database connection");
+    }
+
+    #[tokio::test]
+    async fn test_connect_and_from_pool() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        assert!(matches!(
+            db.posts().find_by_id(1).await,
+            Err(DatabaseError::NotFound(_))
+        ));
 
-    pub async fn create_test_db() -> DatabaseResult<Database> {
-        // Use an in-memory database for testing
-        std::env::set_var("DATABASE_URL", "sqlite::memory:");
-        Database::new().await
+        // Wrapping the same pool again finds every migration applied
+        let again = Database::from_pool(db.pool().clone()).await.unwrap();
+        let applied: Vec<i64> = sqlx::query_scalar("SELECT version FROM _sqlx_migrations")
+            .fetch_all(again.pool())
+            .await
+            .unwrap();
+        let expected = sqlx::migrate!("./migrations").iter().count();
+        assert_eq!(applied.len(), expected);
+
+        assert!(Database::connect("not-a-url").await.is_err());
     }
 
-    // Helper function to set up test environment
-    fn setup_test_env() {
-        env::set_var("DATABASE_URL", "sqlite::memory:");
+    #[tokio::test]
+    async fn test_connections_enforce_foreign_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            database_url: format!("sqlite://{}?mode=rwc", dir.path().join("blog.db").display()),
+            ..Config::default_for_tests()
+        };
+        let db = Database::new(&config).await.unwrap();
+
+        let mut conn = db.pool().acquire().await.unwrap();
+        let foreign_keys: i64 = sqlx::query_scalar("PRAGMA foreign_keys")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(foreign_keys, 1);
+        let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(journal_mode, "wal");
+        let busy_timeout: i64 = sqlx::query_scalar("PRAGMA busy_timeout")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(busy_timeout, 5000);
+        drop(conn);
+
+        // Deleting a post removes its tag links through the cascade
+        let post = db
+            .posts()
+            .create_with_tags(
+                crate::models::post::CreatePost::builder()
+                    .title("Cascade")
+                    .slug("cascade")
+                    .content("Content")
+                    .description("Description")
+                    .published(true)
+                    .tag("rust")
+                    .build()
+                    .unwrap(),
+                None,
+            )
+            .await
+            .unwrap();
+        db.posts().delete(post.post.id).await.unwrap();
+        let links: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM post_tags")
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(links, 0);
     }
 
     #[tokio::test]
-    async fn test_new_database_connection() {
-        setup_test_env();
-        let db = Database::new().await;
-        assert!(db.is_ok(), "Should successfully create database connection");
+    async fn test_connect_retries_then_gives_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            // Without `mode=rwc` a missing file is never created
+            database_url: format!("sqlite://{}", dir.path().join("missing.db").display()),
+            pool: PoolConfig {
+                connect_retry: Duration::from_millis(250),
+                ..PoolConfig::default()
+            },
+            ..Config::default_for_tests()
+        };
+
+        let started = std::time::Instant::now();
+        let error = Database::new(&config).await.unwrap_err();
+        let DatabaseError::Configuration(message) = error else {
+            panic!("expected a configuration error, got {:?}", error);
+        };
+        assert!(message.contains("2 attempt(s)"), "{}", message);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let config = Config {
+            pool: PoolConfig {
+                connect_retry: Duration::ZERO,
+                ..config.pool
+            },
+            ..config
+        };
+        let error = Database::new(&config).await.unwrap_err().to_string();
+        assert!(error.contains("1 attempt(s)"), "{}", error);
     }
 
     #[tokio::test]
     async fn test_repository_access() {
-        setup_test_env();
-        let db = Database::new().await.unwrap();
+        let db = create_test_db().await.unwrap();
 
         // Test posts repository access
         let posts_repo = db.posts();
         assert!(
-            !std::ptr::eq(posts_repo, &PostRepository::new(db.pool().clone())),
+            !std::ptr::eq(
+                posts_repo,
+                &PostRepository::new(db.pool().clone(), QueryMetrics::default())
+            ),
             "Should return reference to existing repository"
         );
 
         // Test tags repository access
         let tags_repo = db.tags();
         assert!(
-            !std::ptr::eq(tags_repo, &TagRepository::new(db.pool().clone())),
+            !std::ptr::eq(
+                tags_repo,
+                &TagRepository::new(db.pool().clone(), QueryMetrics::default())
+            ),
             "Should return reference to existing repository"
         );
     }
 
     #[tokio::test]
     async fn test_transaction_creation() {
-        setup_test_env();
-        let db = Database::new().await.unwrap();
+        let db = create_test_db().await.unwrap();
 
         let transaction = db.transaction().await;
         assert!(
@@ -120,10 +542,74 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_run_in_transaction() {
+        let db = create_test_db().await.unwrap();
+        let post = |slug: &str| {
+            crate::models::post::CreatePost::builder()
+                .title("Atomic")
+                .slug(slug)
+                .content("Content")
+                .description("Description")
+                .published(true)
+                .tag("rust")
+                .build()
+                .unwrap()
+        };
+
+        // The second step fails on a missing tag, taking the post with it
+        let posts = db.posts().clone();
+        let created = post("rolled-back");
+        let error = db
+            .run_in_transaction(move |conn| {
+                Box::pin(async move {
+                    let created = posts.create_with_tags_in(conn, created, None).await?;
+                    TagRepository::set_tags_for_post_in(conn, created.post.id, &[999]).await
+                })
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, DatabaseError::NotFound(_)));
+        assert!(matches!(
+            db.posts().find_by_slug("rolled-back").await,
+            Err(DatabaseError::NotFound(_))
+        ));
+        assert!(matches!(
+            db.tags().find_by_name("rust").await,
+            Err(DatabaseError::NotFound(_))
+        ));
+
+        // Both steps succeed and are committed together
+        let posts = db.posts().clone();
+        let tags = db.tags().clone();
+        let created = post("committed");
+        let tagged = db
+            .run_in_transaction(move |conn| {
+                Box::pin(async move {
+                    let created = posts.create_with_tags_in(conn, created, None).await?;
+                    let tag = tags
+                        .create_with_details_in(conn, &TagRequest::named("go"))
+                        .await?;
+                    TagRepository::add_tag_to_post_in(conn, created.post.id, tag.id).await?;
+                    Ok(created.post.id)
+                })
+            })
+            .await
+            .unwrap();
+        let names: Vec<String> = db
+            .tags()
+            .list_tags_for_post(tagged)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect();
+        assert_eq!(names, ["go", "rust"]);
+    }
+
     #[tokio::test]
     async fn test_pool_access() {
-        setup_test_env();
-        let db = Database::new().await.unwrap();
+        let db = create_test_db().await.unwrap();
 
         let pool = db.pool();
         assert!(pool.acquire().await.is_ok(), "Pool should be functional");
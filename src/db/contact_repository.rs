@@ -0,0 +1,207 @@
+/// Repository for messages sent through the contact form
+#[derive(Clone, Debug)]
+pub struct ContactRepository {
+    pool: SqlitePool,
+    metrics: QueryMetrics,
+}
+
+impl ContactRepository {
+    /// Creates a new ContactRepository instance sharing the given pool
+    pub(crate) fn new(pool: SqlitePool, metrics: QueryMetrics) -> Self {
+        Self { pool, metrics }
+    }
+
+    /// Stores a new, unread message
+    pub async fn create(&self, message: CreateContactMessage) -> DatabaseResult<ContactMessage> {
+        let _timer = self.metrics.time("contact.create", "");
+        message.validate().map_err(DatabaseError::from)?;
+
+        let name = message.name.trim();
+        let email = normalize_email(&message.email);
+        let body = message.message.trim();
+
+        sqlx::query_as!(
+            ContactMessage,
+            r#"
+            INSERT INTO contact_messages (name, email, message)
+            VALUES (?, ?, ?)
+            RETURNING id, name, email, message, created_at, read_at
+            "#,
+            name,
+            email,
+            body
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)
+    }
+
+    /// Lists messages, newest first, optionally only the unread ones, with
+    /// the total number matching
+    pub async fn list(
+        &self,
+        unread_only: bool,
+        limit: i64,
+        offset: i64,
+    ) -> DatabaseResult<(Vec<ContactMessage>, i64)> {
+        let _timer = self.metrics.time(
+            "contact.list",
+            format!(
+                "unread_only={} limit={} offset={}",
+                unread_only, limit, offset
+            ),
+        );
+        if !(1..=MAX_CONTACT_LIMIT).contains(&limit) {
+            return Err(DatabaseError::invalid_field(
+                "limit",
+                &format!("Limit must be between 1 and {}", MAX_CONTACT_LIMIT),
+            ));
+        }
+        if offset < 0 {
+            return Err(DatabaseError::invalid_field(
+                "offset",
+                "Offset cannot be negative",
+            ));
+        }
+
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+
+        let messages = sqlx::query_as!(
+            ContactMessage,
+            r#"
+            SELECT id as "id!", name, email, message, created_at, read_at
+            FROM contact_messages
+            WHERE NOT ? OR read_at IS NULL
+            ORDER BY created_at DESC, id DESC
+            LIMIT ?
+            OFFSET ?
+            "#,
+            unread_only,
+            limit,
+            offset
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        let total = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM contact_messages WHERE NOT ? OR read_at IS NULL",
+            unread_only
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok((messages, total))
+    }
+
+    /// Marks a message read or unread. Marking a message read twice keeps
+    /// the time it was first read.
+    pub async fn set_read(&self, id: i64, read: bool) -> DatabaseResult<ContactMessage> {
+        let _timer = self
+            .metrics
+            .time("contact.set_read", format!("id={} read={}", id, read));
+        sqlx::query_as!(
+            ContactMessage,
+            r#"
+            UPDATE contact_messages
+            SET read_at = CASE WHEN ? THEN COALESCE(read_at, CURRENT_TIMESTAMP) END
+            WHERE id = ?
+            RETURNING id, name, email, message, created_at, read_at
+            "#,
+            read,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?
+        .ok_or_else(|| DatabaseError::not_found("Contact message", &id.to_string()))
+    }
+}
+
+/// Largest page of messages a single request may ask for
+const MAX_CONTACT_LIMIT: i64 = 200;
+
+use sqlx::SqlitePool;
+
+use crate::models::{
+    contact::{ContactMessage, CreateContactMessage},
+    user::normalize_email,
+};
+
+use super::{error::DatabaseResult, DatabaseError, QueryMetrics};
+
+#[cfg(test)]
+mod tests {
+    use crate::db::test_utils::create_test_db;
+
+    use super::*;
+
+    fn message(text: &str) -> CreateContactMessage {
+        CreateContactMessage {
+            name: " Reader ".to_string(),
+            email: "Reader@Example.com".to_string(),
+            message: text.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_contact_message() {
+        let db = create_test_db().await.unwrap();
+        let repo = db.contact();
+
+        let created = repo.create(message("Hello")).await.unwrap();
+        assert_eq!(created.name, "Reader");
+        assert_eq!(created.email, "reader@example.com");
+        assert!(created.read_at.is_none());
+
+        assert!(matches!(
+            repo.create(message("")).await.unwrap_err(),
+            DatabaseError::Validation {
+                field: Some("message"),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_and_unread_messages() {
+        let db = create_test_db().await.unwrap();
+        let repo = db.contact();
+
+        let first = repo.create(message("First")).await.unwrap();
+        let second = repo.create(message("Second")).await.unwrap();
+
+        let read = repo.set_read(first.id, true).await.unwrap();
+        let read_at = read.read_at.unwrap();
+        let again = repo.set_read(first.id, true).await.unwrap();
+        assert_eq!(again.read_at, Some(read_at));
+
+        let (unread, total) = repo.list(true, 50, 0).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(unread[0].id, second.id);
+        let (all, total) = repo.list(false, 50, 0).await.unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(
+            all.iter().map(|m| m.id).collect::<Vec<_>>(),
+            [second.id, first.id]
+        );
+
+        let unread = repo.set_read(first.id, false).await.unwrap();
+        assert!(unread.read_at.is_none());
+        assert_eq!(repo.list(true, 50, 0).await.unwrap().1, 2);
+
+        assert!(matches!(
+            repo.set_read(999, true).await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+        assert!(matches!(
+            repo.list(false, 500, 0).await.unwrap_err(),
+            DatabaseError::Validation {
+                field: Some("limit"),
+                ..
+            }
+        ));
+    }
+}
@@ -1,5 +1,11 @@
+use sqlx::error::ErrorKind;
 use thiserror::Error;
 
+use crate::models::errors::{
+    ApiKeyError, CommentError, ContactError, FieldError, PostError, TagError, UserError,
+    ValidationErrors,
+};
+
 #[derive(Debug, Error)]
 pub enum DatabaseError {
     #[error("Database error: {0}")]
@@ -11,14 +17,37 @@ pub enum DatabaseError {
     #[error("Configuration error: {0}")]
     Configuration(String),
 
-    #[error("Invalid input: {0}")]
-    Validation(String),
+    #[error("Invalid input: {message}")]
+    Validation {
+        /// Name of the offending field, when the first error concerns a
+        /// single field
+        field: Option<&'static str>,
+        /// Messages of every broken rule, separated by semicolons
+        message: String,
+        /// Every broken rule, in the order they were checked
+        errors: Vec<FieldError>,
+    },
+
+    /// A value is larger than the server is configured to store
+    #[error("Too large: {message}")]
+    TooLarge {
+        field: Option<&'static str>,
+        message: String,
+    },
 
     #[error("Resource not found: {0}")]
     NotFound(String),
 
-    #[error("Duplicate entry: {0}")]
-    DuplicateEntry(String),
+    #[error("Duplicate entry: {message}")]
+    DuplicateEntry {
+        /// Column whose unique constraint fired, when it is a single column
+        field: Option<String>,
+        message: String,
+    },
+
+    /// The resource is in a state that doesn't allow the change
+    #[error("Conflict: {0}")]
+    Conflict(String),
 
     #[error("Transaction error: {0}")]
     Transaction(String),
@@ -35,14 +64,231 @@ impl DatabaseError {
         ))
     }
 
-    pub fn duplicate(resource: &str, identifier: &str) -> Self {
-        Self::DuplicateEntry(format!(
-            "{} with identifier '{}' already exists",
-            resource, identifier
-        ))
+    pub fn duplicate(resource: &str, field: Option<&str>, identifier: &str) -> Self {
+        Self::DuplicateEntry {
+            field: field.map(str::to_string),
+            message: format!(
+                "{} with identifier '{}' already exists",
+                resource, identifier
+            ),
+        }
+    }
+
+    /// Returns a `map_err` adapter that reports unique violations as a
+    /// duplicate `resource` and passes every other error through
+    pub fn on_duplicate<'a>(
+        resource: &'a str,
+        identifier: &'a str,
+    ) -> impl FnOnce(sqlx::Error) -> Self + 'a {
+        move |error| match constraint_violation(&error) {
+            Some(ConstraintViolation::Unique { columns }) => {
+                let field = match columns.as_slice() {
+                    [column] => Some(column.as_str()),
+                    _ => None,
+                };
+                Self::duplicate(resource, field, identifier)
+            }
+            _ => Self::Sqlx(error),
+        }
+    }
+
+    pub fn invalid_field(field: &'static str, message: &str) -> Self {
+        Self::validation(vec![FieldError {
+            field: Some(field),
+            message: message.to_string(),
+        }])
+    }
+
+    /// A validation error reporting every rule in `errors`, which should
+    /// not be empty
+    pub fn validation(errors: Vec<FieldError>) -> Self {
+        let field = errors.first().and_then(|e| e.field);
+        let message = errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ");
+        Self::Validation {
+            field,
+            message,
+            errors,
+        }
+    }
+
+    /// A validation error reporting the single rule `error` broke
+    fn invalid_field_error(field: Option<&'static str>, error: &impl std::fmt::Display) -> Self {
+        Self::validation(vec![FieldError {
+            field,
+            message: error.to_string(),
+        }])
+    }
+}
+
+impl From<PostError> for DatabaseError {
+    fn from(error: PostError) -> Self {
+        Self::from(ValidationErrors::from(error))
+    }
+}
+
+impl From<ValidationErrors<PostError>> for DatabaseError {
+    fn from(errors: ValidationErrors<PostError>) -> Self {
+        // A database failure or an oversized post outranks the broken rules
+        // reported with it
+        let mut errors = errors.into_vec();
+        if let Some(i) = errors
+            .iter()
+            .position(|e| matches!(e, PostError::Database(_)))
+        {
+            if let PostError::Database(e) = errors.swap_remove(i) {
+                return Self::Sqlx(e);
+            }
+        }
+        if let Some(e) = errors
+            .iter()
+            .find(|e| matches!(e, PostError::ContentTooLarge { .. }))
+        {
+            return Self::TooLarge {
+                field: e.field(),
+                message: e.to_string(),
+            };
+        }
+        Self::validation(errors.iter().map(FieldError::from).collect())
+    }
+}
+
+impl From<TagError> for DatabaseError {
+    fn from(error: TagError) -> Self {
+        Self::validation(vec![FieldError::from(&error)])
+    }
+}
+
+impl From<ValidationErrors<TagError>> for DatabaseError {
+    fn from(errors: ValidationErrors<TagError>) -> Self {
+        Self::validation(errors.iter().map(FieldError::from).collect())
+    }
+}
+
+impl From<ApiKeyError> for DatabaseError {
+    fn from(error: ApiKeyError) -> Self {
+        Self::invalid_field_error(error.field(), &error)
+    }
+}
+
+impl From<CommentError> for DatabaseError {
+    fn from(error: CommentError) -> Self {
+        Self::invalid_field_error(error.field(), &error)
+    }
+}
+
+impl From<ContactError> for DatabaseError {
+    fn from(error: ContactError) -> Self {
+        Self::invalid_field_error(error.field(), &error)
+    }
+}
+
+impl From<UserError> for DatabaseError {
+    fn from(error: UserError) -> Self {
+        match error {
+            // Only possible with invalid Argon2 parameters
+            UserError::PasswordHash(message) => Self::Configuration(message),
+            e => Self::invalid_field_error(e.field(), &e),
+        }
+    }
+}
+
+/// A constraint violation reported by SQLite
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintViolation {
+    /// A UNIQUE or PRIMARY KEY constraint, with the columns it covers
+    Unique { columns: Vec<String> },
+    /// A FOREIGN KEY constraint
+    ForeignKey,
+}
+
+/// Classifies a constraint violation by its SQLite extended result code.
+///
+/// SQLite only reports the offending columns in the message text
+/// (`UNIQUE constraint failed: tags.name`), so those are parsed from there
+/// once the code has identified the kind of violation.
+pub fn constraint_violation(error: &sqlx::Error) -> Option<ConstraintViolation> {
+    let sqlx::Error::Database(e) = error else {
+        return None;
+    };
+
+    match e.kind() {
+        ErrorKind::UniqueViolation => {
+            let columns = e
+                .message()
+                .split_once(": ")
+                .map(|(_, columns)| {
+                    columns
+                        .split(", ")
+                        .map(|column| {
+                            let column = column.rsplit_once('.').map_or(column, |(_, c)| c);
+                            column.to_string()
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            Some(ConstraintViolation::Unique { columns })
+        }
+        ErrorKind::ForeignKeyViolation => Some(ConstraintViolation::ForeignKey),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::create_test_db;
+
+    #[tokio::test]
+    async fn test_constraint_violation_unique() {
+        let db = create_test_db().await.unwrap();
+        sqlx::query("INSERT INTO tags (name, slug) VALUES ('rust', 'rust')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        let error = sqlx::query("INSERT INTO tags (name, slug) VALUES ('rust', 'rust-2')")
+            .execute(db.pool())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            constraint_violation(&error),
+            Some(ConstraintViolation::Unique {
+                columns: vec!["name".to_string()]
+            })
+        );
+
+        match DatabaseError::on_duplicate("Tag", "rust")(error) {
+            DatabaseError::DuplicateEntry { field, .. } => {
+                assert_eq!(field.as_deref(), Some("name"))
+            }
+            e => panic!("expected duplicate entry, got {e:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_constraint_violation_foreign_key() {
+        let db = create_test_db().await.unwrap();
+
+        let error = sqlx::query("INSERT INTO post_tags (post_id, tag_id) VALUES (999, 999)")
+            .execute(db.pool())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            constraint_violation(&error),
+            Some(ConstraintViolation::ForeignKey)
+        );
+        assert!(matches!(
+            DatabaseError::on_duplicate("Tag association", "999, 999")(error),
+            DatabaseError::Sqlx(_)
+        ));
     }
 
-    pub fn validation(message: &str) -> Self {
-        Self::Validation(message.to_string())
+    #[test]
+    fn test_constraint_violation_ignores_other_errors() {
+        assert_eq!(constraint_violation(&sqlx::Error::RowNotFound), None);
     }
 }
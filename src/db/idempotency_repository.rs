@@ -0,0 +1,281 @@
+/// Repository for responses stored under idempotency keys.
+/// Stored responses expire after `IDEMPOTENCY_KEY_TTL`; expired ones are
+/// ignored by lookups and removed by `purge_expired`. A key is reserved
+/// with `reserve` while its request runs, and holds no response until then.
+#[derive(Clone, Debug)]
+pub struct IdempotencyRepository {
+    pool: SqlitePool,
+    metrics: QueryMetrics,
+}
+
+impl IdempotencyRepository {
+    /// Creates a new IdempotencyRepository instance sharing the given pool
+    pub(crate) fn new(pool: SqlitePool, metrics: QueryMetrics) -> Self {
+        Self { pool, metrics }
+    }
+
+    /// Retrieves the unexpired response stored under a key. A key that is
+    /// only reserved has no response yet.
+    pub async fn find(&self, scope: &str, key: &str) -> DatabaseResult<Option<StoredResponse>> {
+        let _timer = self.metrics.time("idempotency.find", "");
+        let cutoff = expiry_cutoff();
+        sqlx::query_as!(
+            StoredResponse,
+            r#"
+            SELECT scope, key, request_hash, status, location, body
+            FROM idempotency_keys
+            WHERE scope = ? AND key = ? AND NOT pending AND created_at > datetime('now', ?)
+            "#,
+            scope,
+            key,
+            cutoff
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)
+    }
+
+    /// Reserves a key for a request about to run, returning false if the
+    /// key already holds a response or another request has reserved it. A
+    /// reservation older than `stale_after` is taken over, since the request
+    /// that made it can't still be running.
+    pub async fn reserve(
+        &self,
+        scope: &str,
+        key: &str,
+        request_hash: &str,
+        stale_after: std::time::Duration,
+    ) -> DatabaseResult<bool> {
+        let _timer = self.metrics.time("idempotency.reserve", "");
+        let cutoff = expiry_cutoff();
+        // Timestamps only have whole seconds, so round the other way
+        let stale = format!("-{} seconds", stale_after.as_secs() + 1);
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO idempotency_keys (scope, key, request_hash, status, body, pending)
+            VALUES (?, ?, ?, 0, x'', TRUE)
+            ON CONFLICT (scope, key) DO UPDATE SET
+                request_hash = excluded.request_hash,
+                status = 0,
+                location = NULL,
+                body = x'',
+                pending = TRUE,
+                created_at = CURRENT_TIMESTAMP
+            WHERE idempotency_keys.created_at <= datetime('now', ?)
+                OR (idempotency_keys.pending AND idempotency_keys.created_at <= datetime('now', ?))
+            "#,
+            scope,
+            key,
+            request_hash,
+            cutoff,
+            stale
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Drops a reservation whose request produced nothing to store, so the
+    /// key can be used again
+    pub async fn release(&self, scope: &str, key: &str) -> DatabaseResult<()> {
+        let _timer = self.metrics.time("idempotency.release", "");
+        sqlx::query!(
+            "DELETE FROM idempotency_keys WHERE scope = ? AND key = ? AND pending",
+            scope,
+            key
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+        Ok(())
+    }
+
+    /// Stores a response under its key, completing its reservation. An
+    /// expired response under the same key is replaced; an unexpired one is
+    /// kept, so the first response stored for a key is the one replayed.
+    pub async fn save(&self, response: &StoredResponse) -> DatabaseResult<()> {
+        let _timer = self
+            .metrics
+            .time("idempotency.save", format!("status={}", response.status));
+        let cutoff = expiry_cutoff();
+        sqlx::query!(
+            r#"
+            INSERT INTO idempotency_keys (scope, key, request_hash, status, location, body)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (scope, key) DO UPDATE SET
+                request_hash = excluded.request_hash,
+                status = excluded.status,
+                location = excluded.location,
+                body = excluded.body,
+                pending = FALSE,
+                created_at = CURRENT_TIMESTAMP
+            WHERE idempotency_keys.pending OR idempotency_keys.created_at <= datetime('now', ?)
+            "#,
+            response.scope,
+            response.key,
+            response.request_hash,
+            response.status,
+            response.location,
+            response.body,
+            cutoff
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+        Ok(())
+    }
+
+    /// Deletes expired responses, returning how many were removed
+    pub async fn purge_expired(&self) -> DatabaseResult<u64> {
+        let _timer = self.metrics.time("idempotency.purge_expired", "");
+        let cutoff = expiry_cutoff();
+        let result = sqlx::query!(
+            "DELETE FROM idempotency_keys WHERE created_at <= datetime('now', ?)",
+            cutoff
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+        Ok(result.rows_affected())
+    }
+}
+
+/// SQLite datetime modifier for the oldest creation time still replayable
+fn expiry_cutoff() -> String {
+    format!("-{} seconds", IDEMPOTENCY_KEY_TTL.whole_seconds())
+}
+
+use sqlx::SqlitePool;
+
+use crate::models::idempotency::{StoredResponse, IDEMPOTENCY_KEY_TTL};
+
+use super::{error::DatabaseResult, DatabaseError, QueryMetrics};
+
+#[cfg(test)]
+mod tests {
+    use crate::db::{test_utils::create_test_db, Database};
+
+    use super::*;
+
+    async fn setup() -> (Database, IdempotencyRepository) {
+        let db = create_test_db().await.unwrap();
+        let repo = db.idempotency().clone();
+        (db, repo)
+    }
+
+    fn stored(key: &str, body: &str) -> StoredResponse {
+        StoredResponse {
+            scope: "user:1".to_string(),
+            key: key.to_string(),
+            request_hash: "hash".to_string(),
+            status: 201,
+            location: Some("/posts/by-id/1".to_string()),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    /// Moves a stored response's creation time past the expiry
+    async fn expire(db: &Database, key: &str) {
+        sqlx::query(
+            "UPDATE idempotency_keys SET created_at = datetime('now', '-25 hours') WHERE key = ?",
+        )
+        .bind(key)
+        .execute(db.pool())
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_save_and_find() {
+        let (_, repo) = setup().await;
+
+        repo.save(&stored("first", "{\"id\":1}")).await.unwrap();
+        assert_eq!(
+            repo.find("user:1", "first").await.unwrap(),
+            Some(stored("first", "{\"id\":1}"))
+        );
+
+        // Keys are scoped to whoever sent them
+        assert!(repo.find("user:2", "first").await.unwrap().is_none());
+
+        // The first response stored for a key wins
+        repo.save(&stored("first", "{\"id\":2}")).await.unwrap();
+        assert_eq!(
+            repo.find("user:1", "first").await.unwrap().unwrap().body,
+            b"{\"id\":1}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expiry() {
+        let (db, repo) = setup().await;
+
+        repo.save(&stored("old", "{\"id\":1}")).await.unwrap();
+        repo.save(&stored("new", "{\"id\":2}")).await.unwrap();
+        expire(&db, "old").await;
+
+        assert!(repo.find("user:1", "old").await.unwrap().is_none());
+        assert!(repo.find("user:1", "new").await.unwrap().is_some());
+
+        // An expired key can be used again
+        repo.save(&stored("old", "{\"id\":3}")).await.unwrap();
+        assert_eq!(
+            repo.find("user:1", "old").await.unwrap().unwrap().body,
+            b"{\"id\":3}"
+        );
+
+        expire(&db, "old").await;
+        assert_eq!(repo.purge_expired().await.unwrap(), 1);
+        assert!(repo.find("user:1", "new").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_reserve() {
+        let (db, repo) = setup().await;
+        let timeout = std::time::Duration::from_secs(30);
+
+        assert!(repo
+            .reserve("user:1", "key", "hash", timeout)
+            .await
+            .unwrap());
+        // A reserved key has no response yet, and can't be reserved again
+        assert!(repo.find("user:1", "key").await.unwrap().is_none());
+        assert!(!repo
+            .reserve("user:1", "key", "hash", timeout)
+            .await
+            .unwrap());
+        assert!(repo
+            .reserve("user:2", "key", "hash", timeout)
+            .await
+            .unwrap());
+
+        repo.save(&stored("key", "{\"id\":1}")).await.unwrap();
+        assert!(repo.find("user:1", "key").await.unwrap().is_some());
+        assert!(!repo
+            .reserve("user:1", "key", "hash", timeout)
+            .await
+            .unwrap());
+
+        // Released keys are free again; stored responses aren't released
+        repo.release("user:2", "key").await.unwrap();
+        assert!(repo
+            .reserve("user:2", "key", "hash", timeout)
+            .await
+            .unwrap());
+        repo.release("user:1", "key").await.unwrap();
+        assert!(repo.find("user:1", "key").await.unwrap().is_some());
+
+        // A reservation outliving any request is abandoned
+        sqlx::query(
+            "UPDATE idempotency_keys SET created_at = datetime('now', '-1 minute') WHERE scope = 'user:2'",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        assert!(repo
+            .reserve("user:2", "key", "hash", timeout)
+            .await
+            .unwrap());
+    }
+}
@@ -0,0 +1,250 @@
+/// Repository for the results of checking the URLs posts link to
+#[derive(Clone, Debug)]
+pub struct LinkCheckRepository {
+    pool: SqlitePool,
+    metrics: QueryMetrics,
+}
+
+impl LinkCheckRepository {
+    /// Creates a new LinkCheckRepository instance sharing the given pool
+    pub(crate) fn new(pool: SqlitePool, metrics: QueryMetrics) -> Self {
+        Self { pool, metrics }
+    }
+
+    /// Every external and image URL set on a post, drafts included, in
+    /// post id order
+    pub async fn links(&self) -> DatabaseResult<Vec<Link>> {
+        let _timer = self.metrics.time("link_checks.links", String::new());
+        let rows = sqlx::query!(
+            r#"
+            SELECT id as "id!", external_url, image_url
+            FROM posts
+            WHERE COALESCE(external_url, '') != '' OR COALESCE(image_url, '') != ''
+            ORDER BY id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        Ok(rows
+            .into_iter()
+            .flat_map(|row| {
+                [
+                    (LinkField::ExternalUrl, row.external_url),
+                    (LinkField::ImageUrl, row.image_url),
+                ]
+                .into_iter()
+                .filter_map(move |(field, url)| {
+                    Some(Link {
+                        post_id: row.id,
+                        field,
+                        url: url.filter(|url| !url.is_empty())?,
+                    })
+                })
+            })
+            .collect())
+    }
+
+    /// Records the outcome of checking a link, replacing the previous one
+    /// for the same post and field. Fails with `NotFound` when the post
+    /// has been deleted.
+    pub async fn record(&self, link: &Link, outcome: &LinkOutcome) -> DatabaseResult<()> {
+        let _timer = self.metrics.time(
+            "link_checks.record",
+            format!("post_id={} field={:?}", link.post_id, link.field),
+        );
+        let (status, error) = match outcome {
+            LinkOutcome::Status(status) => (Some(i64::from(*status)), None),
+            LinkOutcome::Failed(error) => (None, Some(error.as_str())),
+        };
+        let ok = outcome.is_ok();
+        sqlx::query!(
+            r#"
+            INSERT INTO link_checks (post_id, field, url, status, error, ok, checked_at)
+            VALUES (?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+            ON CONFLICT (post_id, field) DO UPDATE SET
+                url = excluded.url,
+                status = excluded.status,
+                error = excluded.error,
+                ok = excluded.ok,
+                checked_at = excluded.checked_at
+            "#,
+            link.post_id,
+            link.field,
+            link.url,
+            status,
+            error,
+            ok
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match constraint_violation(&e) {
+            Some(ConstraintViolation::ForeignKey) => {
+                DatabaseError::not_found("Post", &link.post_id.to_string())
+            }
+            _ => DatabaseError::Sqlx(e),
+        })?;
+        Ok(())
+    }
+
+    /// Lists links whose latest check failed, by post id, along with the
+    /// total number of them. Checks of URLs a post no longer has are left
+    /// out.
+    pub async fn list_broken(
+        &self,
+        limit: i64,
+        offset: i64,
+    ) -> DatabaseResult<(Vec<BrokenLink>, i64)> {
+        let _timer = self.metrics.time(
+            "link_checks.list_broken",
+            format!("limit={} offset={}", limit, offset),
+        );
+        if !(1..=MAX_LINK_CHECK_LIMIT).contains(&limit) {
+            return Err(DatabaseError::invalid_field(
+                "limit",
+                &format!("Limit must be between 1 and {}", MAX_LINK_CHECK_LIMIT),
+            ));
+        }
+        if offset < 0 {
+            return Err(DatabaseError::invalid_field(
+                "offset",
+                "Offset cannot be negative",
+            ));
+        }
+
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+
+        let links = sqlx::query_as!(
+            BrokenLink,
+            r#"
+            SELECT
+                lc.post_id, p.slug as post_slug, p.title as post_title,
+                lc.field as "field: LinkField", lc.url, lc.status, lc.error, lc.checked_at
+            FROM link_checks lc
+            JOIN posts p ON p.id = lc.post_id
+            WHERE NOT lc.ok
+              AND lc.url = CASE lc.field WHEN 'external_url' THEN p.external_url ELSE p.image_url END
+            ORDER BY lc.post_id, lc.field
+            LIMIT ?
+            OFFSET ?
+            "#,
+            limit,
+            offset
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        let total = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*)
+            FROM link_checks lc
+            JOIN posts p ON p.id = lc.post_id
+            WHERE NOT lc.ok
+              AND lc.url = CASE lc.field WHEN 'external_url' THEN p.external_url ELSE p.image_url END
+            "#
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok((links, total))
+    }
+}
+
+/// Most broken links returned at once
+const MAX_LINK_CHECK_LIMIT: i64 = 200;
+
+use sqlx::SqlitePool;
+
+use crate::models::link_check::{BrokenLink, Link, LinkField, LinkOutcome};
+
+use super::{
+    error::{constraint_violation, ConstraintViolation, DatabaseResult},
+    DatabaseError, QueryMetrics,
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::db::test_utils::create_test_db;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_record_and_list_broken() {
+        let db = create_test_db().await.unwrap();
+        sqlx::query(
+            "INSERT INTO posts (id, category, title, slug, content, description, external_url, image_url)
+             VALUES
+                (1, 'reading', 'Book', 'book', 'Content', 'Description', 'https://example.com/book', '/static/cover.png'),
+                (2, 'blog', 'Plain', 'plain', 'Content', 'Description', NULL, ''),
+                (3, 'art', 'Sketch', 'sketch', 'Content', 'Description', NULL, 'https://img.example.com/a.png')",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        let repo = db.link_checks();
+
+        let links = repo.links().await.unwrap();
+        let link = |post_id, field, url: &str| Link {
+            post_id,
+            field,
+            url: url.to_string(),
+        };
+        assert_eq!(
+            links,
+            [
+                link(1, LinkField::ExternalUrl, "https://example.com/book"),
+                link(1, LinkField::ImageUrl, "/static/cover.png"),
+                link(3, LinkField::ImageUrl, "https://img.example.com/a.png"),
+            ]
+        );
+
+        repo.record(&links[0], &LinkOutcome::Status(404))
+            .await
+            .unwrap();
+        repo.record(&links[1], &LinkOutcome::Status(200))
+            .await
+            .unwrap();
+        repo.record(&links[2], &LinkOutcome::Failed("timed out".to_string()))
+            .await
+            .unwrap();
+
+        let (broken, total) = repo.list_broken(50, 0).await.unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(broken[0].post_slug, "book");
+        assert_eq!(broken[0].field, LinkField::ExternalUrl);
+        assert_eq!(broken[0].status, Some(404));
+        assert_eq!(broken[0].error, None);
+        assert_eq!(broken[1].post_id, 3);
+        assert_eq!(broken[1].status, None);
+        assert_eq!(broken[1].error.as_deref(), Some("timed out"));
+
+        // A later check replaces the earlier one
+        repo.record(&links[0], &LinkOutcome::Status(200))
+            .await
+            .unwrap();
+        // A post that moved on from a dead URL no longer counts
+        sqlx::query("UPDATE posts SET image_url = 'https://img.example.com/b.png' WHERE id = 3")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let (broken, total) = repo.list_broken(50, 0).await.unwrap();
+        assert_eq!(total, 0);
+        assert!(broken.is_empty());
+
+        assert!(matches!(
+            repo.record(
+                &link(999, LinkField::ImageUrl, "/x.png"),
+                &LinkOutcome::Status(200)
+            )
+            .await
+            .unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+        assert!(repo.list_broken(0, 0).await.is_err());
+        assert!(repo.list_broken(10, -1).await.is_err());
+    }
+}
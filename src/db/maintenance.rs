@@ -0,0 +1,240 @@
+use std::path::Path;
+
+use super::{connection::MIGRATOR, error::DatabaseResult, Database, DatabaseError};
+use crate::models::maintenance::{
+    AppliedMigration, ForeignKeyViolation, IntegrityCheck, MigrationStatus, PendingMigration,
+};
+
+impl Database {
+    /// Writes a consistent copy of the database to `path`, which must not
+    /// exist yet. Other connections keep reading and writing meanwhile.
+    /// In-memory databases can't be copied this way.
+    ///
+    /// sqlx runs SQLite statements on a worker thread of its own, so the
+    /// copy never blocks the async runtime.
+    pub async fn backup_into(&self, path: &Path) -> DatabaseResult<()> {
+        let file: String =
+            sqlx::query_scalar("SELECT file FROM pragma_database_list WHERE name = 'main'")
+                .fetch_one(self.pool())
+                .await
+                .map_err(DatabaseError::Sqlx)?;
+        if file.is_empty() {
+            return Err(DatabaseError::Configuration(
+                "in-memory databases can't be backed up".to_string(),
+            ));
+        }
+
+        let path = path.to_str().ok_or_else(|| {
+            DatabaseError::Configuration(format!("backup path {:?} is not UTF-8", path))
+        })?;
+        sqlx::query("VACUUM INTO ?")
+            .bind(path)
+            .execute(self.pool())
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+        Ok(())
+    }
+
+    /// Rebuilds the database file to reclaim the space of deleted rows,
+    /// then refreshes the query planner's statistics. Returns the size of
+    /// the database in bytes before and after.
+    pub async fn vacuum(&self) -> DatabaseResult<(i64, i64)> {
+        let before = self.size().await?;
+        sqlx::query("VACUUM")
+            .execute(self.pool())
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+        sqlx::query("PRAGMA optimize")
+            .execute(self.pool())
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+        Ok((before, self.size().await?))
+    }
+
+    /// The migrations the database has applied, and those embedded in the
+    /// server that it hasn't, e.g. after a failed startup
+    pub async fn migration_status(&self) -> DatabaseResult<MigrationStatus> {
+        let applied: Vec<AppliedMigration> = sqlx::query_as(
+            r#"
+            SELECT version, description, installed_on AS applied_at
+            FROM _sqlx_migrations
+            WHERE success
+            ORDER BY version
+            "#,
+        )
+        .fetch_all(self.pool())
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        let pending = MIGRATOR
+            .iter()
+            .filter(|migration| !applied.iter().any(|a| a.version == migration.version))
+            .map(|migration| PendingMigration {
+                version: migration.version,
+                description: migration.description.to_string(),
+            })
+            .collect();
+        Ok(MigrationStatus { applied, pending })
+    }
+
+    /// Looks for corruption with `PRAGMA integrity_check` and for rows
+    /// pointing at missing rows with `PRAGMA foreign_key_check`, e.g. after
+    /// an unclean shutdown. Both run on one pooled connection; the first
+    /// reads the whole file, so it takes a while on large databases.
+    pub async fn integrity_check(&self) -> DatabaseResult<IntegrityCheck> {
+        let mut conn = self.pool().acquire().await.map_err(DatabaseError::Sqlx)?;
+
+        // A single "ok" row when nothing is wrong, otherwise one per problem
+        let problems: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+        let problems = problems
+            .into_iter()
+            .filter(|problem| problem != "ok")
+            .collect();
+
+        let violations: Vec<ForeignKeyViolation> =
+            sqlx::query_as(r#"SELECT "table", rowid, parent, fkid FROM pragma_foreign_key_check"#)
+                .fetch_all(&mut *conn)
+                .await
+                .map_err(DatabaseError::Sqlx)?;
+
+        Ok(IntegrityCheck::new(problems, violations))
+    }
+
+    /// Size of the database in bytes, not counting the write-ahead log
+    async fn size(&self) -> DatabaseResult<i64> {
+        sqlx::query_scalar("SELECT page_count * page_size FROM pragma_page_count, pragma_page_size")
+            .fetch_one(self.pool())
+            .await
+            .map_err(DatabaseError::Sqlx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::Config, db::test_utils::create_test_db, models::tag::TagRequest};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_backup_into() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            database_url: format!("sqlite://{}?mode=rwc", dir.path().join("blog.db").display()),
+            ..Config::default_for_tests()
+        };
+        let db = Database::new(&config).await.unwrap();
+        db.tags().create("rust").await.unwrap();
+
+        let backup = dir.path().join("backup.db");
+        db.backup_into(&backup).await.unwrap();
+        assert!(backup.is_file());
+
+        // The copy opens as a database of its own, with the data in it
+        let copy = Database::connect(&format!("sqlite://{}", backup.display()))
+            .await
+            .unwrap();
+        assert_eq!(copy.tags().find_by_name("rust").await.unwrap().name, "rust");
+
+        // An existing file is never overwritten
+        assert!(db.backup_into(&backup).await.is_err());
+
+        let memory = create_test_db().await.unwrap();
+        assert!(matches!(
+            memory.backup_into(&dir.path().join("memory.db")).await,
+            Err(DatabaseError::Configuration(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_migration_status() {
+        let db = create_test_db().await.unwrap();
+        let status = db.migration_status().await.unwrap();
+        assert!(status.is_current());
+        let versions: Vec<i64> = status.applied.iter().map(|m| m.version).collect();
+        let embedded: Vec<i64> = MIGRATOR.iter().map(|m| m.version).collect();
+        assert_eq!(versions, embedded);
+        assert_eq!(status.applied[0].description, "initial schema");
+
+        // Forgetting the newest migration makes it pending again
+        let newest = *embedded.last().unwrap();
+        sqlx::query("DELETE FROM _sqlx_migrations WHERE version = ?")
+            .bind(newest)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let status = db.migration_status().await.unwrap();
+        assert!(!status.is_current());
+        assert_eq!(status.pending.len(), 1);
+        assert_eq!(status.pending[0].version, newest);
+    }
+
+    #[tokio::test]
+    async fn test_vacuum() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            database_url: format!("sqlite://{}?mode=rwc", dir.path().join("blog.db").display()),
+            ..Config::default_for_tests()
+        };
+        let db = Database::new(&config).await.unwrap();
+        for i in 0..200 {
+            db.tags()
+                .create_with_details(&TagRequest {
+                    name: format!("tag-{i}"),
+                    description: Some("padding ".repeat(35)),
+                    color: None,
+                })
+                .await
+                .unwrap();
+        }
+        // Straight to the table, as deleting through the repository would
+        // fill the audit log instead
+        sqlx::query("DELETE FROM tags")
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        let (before, after) = db.vacuum().await.unwrap();
+        assert!(after > 0);
+        assert!(after < before, "{} -> {}", before, after);
+    }
+
+    #[tokio::test]
+    async fn test_integrity_check() {
+        let db = create_test_db().await.unwrap();
+        let check = db.integrity_check().await.unwrap();
+        assert!(check.ok);
+        assert!(check.problems.is_empty());
+        assert!(check.foreign_key_violations.is_empty());
+
+        // A tag link left behind while foreign keys were off
+        let tag = db.tags().create("rust").await.unwrap();
+        let mut conn = db.pool().acquire().await.unwrap();
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO post_tags (post_id, tag_id) VALUES (999, ?)")
+            .bind(tag.id)
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        drop(conn);
+
+        let check = db.integrity_check().await.unwrap();
+        assert!(!check.ok);
+        assert!(check.problems.is_empty());
+        assert_eq!(check.foreign_key_violations.len(), 1);
+        let violation = &check.foreign_key_violations[0];
+        assert_eq!(
+            (violation.table.as_str(), violation.parent.as_str()),
+            ("post_tags", "posts")
+        );
+    }
+}
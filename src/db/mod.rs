@@ -1,12 +1,37 @@
+mod api_key_repository;
+mod audit_repository;
+mod click_repository;
+mod comment_repository;
 mod connection;
+mod contact_repository;
 mod error;
+mod idempotency_repository;
+mod link_check_repository;
+mod maintenance;
+mod post_cache;
 mod post_repository;
+mod query_metrics;
+mod render_cache_repository;
+mod stats_repository;
 mod tag_repository;
+mod user_repository;
 
-pub use connection::Database;
+pub use api_key_repository::ApiKeyRepository;
+pub use audit_repository::AuditRepository;
+pub use click_repository::ClickRepository;
+pub use comment_repository::CommentRepository;
+pub use connection::{Database, PoolConfig, SqliteConfig, TransactionFuture};
+pub use contact_repository::ContactRepository;
 pub use error::DatabaseError;
+pub use idempotency_repository::IdempotencyRepository;
+pub use link_check_repository::LinkCheckRepository;
+pub use post_cache::{CacheStats, DEFAULT_CACHE_TTL};
 pub use post_repository::PostRepository;
+pub use query_metrics::{Bucket, MethodTimings, QueryMetrics, DEFAULT_SLOW_QUERY_THRESHOLD};
+pub use render_cache_repository::RenderCacheRepository;
+pub use stats_repository::StatsRepository;
 pub use tag_repository::TagRepository;
+pub use user_repository::UserRepository;
 
 // Re-export common types that callers might need
 pub use sqlx::SqlitePool;
@@ -15,12 +40,10 @@ pub use sqlx::SqlitePool;
 pub mod test_utils {
     use super::*;
     use error::DatabaseResult;
-    use sqlx::SqlitePool;
 
     /// Creates a new test database instance with an in-memory SQLite database
     pub async fn create_test_db() -> DatabaseResult<Database> {
         // Use an in-memory database for testing
-        std::env::set_var("DATABASE_URL", "sqlite::memory:");
-        Database::new().await
+        Database::connect("sqlite::memory:").await
     }
 }
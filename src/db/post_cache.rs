@@ -0,0 +1,201 @@
+use std::{
+    hash::Hash,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use serde::Serialize;
+
+use crate::models::post::{Post, PostCategory, Visibility};
+
+/// How long cached posts are trusted when no setting says otherwise. Every
+/// change made through the repository invalidates right away; this only
+/// bounds how long changes made around it go unnoticed.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Entries kept per map before it is emptied, so unusual listings (say, a
+/// crawler walking every offset) can't grow the cache without bound
+const MAX_ENTRIES: usize = 1024;
+
+/// Filters and page of a `PostRepository::list` call
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct ListKey {
+    category: Option<String>,
+    language: Option<String>,
+    visibility: Visibility,
+    limit: i64,
+    offset: i64,
+}
+
+impl ListKey {
+    pub(crate) fn new(
+        category: Option<&PostCategory>,
+        language: Option<&str>,
+        visibility: Visibility,
+        limit: i64,
+        offset: i64,
+    ) -> Self {
+        Self {
+            category: category.map(ToString::to_string),
+            language: language.map(ToString::to_string),
+            visibility,
+            limit,
+            offset,
+        }
+    }
+}
+
+/// Cache hits and misses since startup
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Value of the cache's generation when a read started. Writes bump the
+/// generation, so a read that raced a write doesn't cache what it read.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Generation(u64);
+
+/// In-memory cache of posts by slug and of post listings, shared by every
+/// clone of the repository
+#[derive(Clone, Debug)]
+pub(crate) struct PostCache {
+    inner: Option<Arc<Inner>>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    ttl: Duration,
+    by_slug: DashMap<String, (Instant, Post)>,
+    /// A page of posts and the number of posts matching the filters
+    lists: DashMap<ListKey, (Instant, (Vec<Post>, i64))>,
+    generation: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl PostCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            inner: Some(Arc::new(Inner {
+                ttl,
+                by_slug: DashMap::new(),
+                lists: DashMap::new(),
+                generation: AtomicU64::new(0),
+                hits: AtomicU64::new(0),
+                misses: AtomicU64::new(0),
+            })),
+        }
+    }
+
+    /// A cache that stores nothing
+    pub(crate) fn disabled() -> Self {
+        Self { inner: None }
+    }
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        self.inner
+            .as_ref()
+            .map(|inner| CacheStats {
+                hits: inner.hits.load(Ordering::Relaxed),
+                misses: inner.misses.load(Ordering::Relaxed),
+            })
+            .unwrap_or_default()
+    }
+
+    /// Call before reading from the database, and pass to `put_*` after
+    pub(crate) fn generation(&self) -> Generation {
+        Generation(
+            self.inner
+                .as_ref()
+                .map_or(0, |inner| inner.generation.load(Ordering::Acquire)),
+        )
+    }
+
+    pub(crate) fn get_slug(&self, slug: &str) -> Option<Post> {
+        let inner = self.inner.as_ref()?;
+        inner.get(&inner.by_slug, slug)
+    }
+
+    pub(crate) fn put_slug(&self, read_at: Generation, post: &Post) {
+        if let Some(inner) = &self.inner {
+            inner.put(&inner.by_slug, read_at, post.slug.clone(), post.clone());
+        }
+    }
+
+    pub(crate) fn get_list(&self, key: &ListKey) -> Option<(Vec<Post>, i64)> {
+        let inner = self.inner.as_ref()?;
+        inner.get(&inner.lists, key)
+    }
+
+    pub(crate) fn put_list(&self, read_at: Generation, key: ListKey, posts: &[Post], total: i64) {
+        if let Some(inner) = &self.inner {
+            inner.put(&inner.lists, read_at, key, (posts.to_vec(), total));
+        }
+    }
+
+    /// Forgets the posts with these slugs and every listing. Call after
+    /// the change is committed.
+    pub(crate) fn invalidate(&self, slugs: &[&str]) {
+        if let Some(inner) = &self.inner {
+            inner.generation.fetch_add(1, Ordering::AcqRel);
+            for slug in slugs {
+                inner.by_slug.remove(*slug);
+            }
+            // Any change can move a post in or out of any listing
+            inner.lists.clear();
+        }
+    }
+
+    /// Forgets everything, for changes whose slugs aren't known
+    pub(crate) fn clear(&self) {
+        if let Some(inner) = &self.inner {
+            inner.generation.fetch_add(1, Ordering::AcqRel);
+            inner.by_slug.clear();
+            inner.lists.clear();
+        }
+    }
+}
+
+impl Inner {
+    fn get<K, Q, V>(&self, map: &DashMap<K, (Instant, V)>, key: &Q) -> Option<V>
+    where
+        K: Eq + Hash + std::borrow::Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+        V: Clone,
+    {
+        let cached = map
+            .get(key)
+            .filter(|entry| entry.0.elapsed() < self.ttl)
+            .map(|entry| entry.1.clone());
+        let counter = if cached.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        cached
+    }
+
+    fn put<K: Eq + Hash, V>(
+        &self,
+        map: &DashMap<K, (Instant, V)>,
+        read_at: Generation,
+        key: K,
+        value: V,
+    ) {
+        if map.len() >= MAX_ENTRIES {
+            map.clear();
+        }
+        map.insert(key, (Instant::now(), value));
+        // A write finished while the value was read, so it may be stale
+        if self.generation.load(Ordering::Acquire) != read_at.0 {
+            map.clear();
+        }
+    }
+}
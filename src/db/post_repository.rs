@@ -4,29 +4,155 @@
 #[derive(Clone, Debug)]
 pub struct PostRepository {
     pool: SqlitePool,
+    /// Recorded in the audit log as the author of every change
+    actor: Actor,
+    /// Posts by slug and listings, shared with every clone
+    cache: PostCache,
+    max_content_bytes: usize,
+    /// User recorded as the editor for callers without a user account
+    system_user: Option<i64>,
+    /// Whether tags created along with a post get lowercase names
+    lowercase_tag_names: bool,
+    metrics: QueryMetrics,
 }
 
 impl PostRepository {
     /// Creates a new PostRepository instance.
     /// The repository takes ownership of a connection pool clone, allowing
     /// multiple repositories to share the same pool.
-    pub(crate) fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub(crate) fn new(pool: SqlitePool, metrics: QueryMetrics) -> Self {
+        Self {
+            pool,
+            actor: Actor::System,
+            cache: PostCache::new(DEFAULT_CACHE_TTL),
+            max_content_bytes: DEFAULT_MAX_CONTENT_BYTES,
+            system_user: None,
+            lowercase_tag_names: false,
+            metrics,
+        }
+    }
+
+    /// Returns a repository whose changes are attributed to `actor`
+    pub(crate) fn acting_as(&self, actor: Actor) -> Self {
+        Self {
+            actor,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a repository caching reads for `ttl`, or not at all
+    pub(crate) fn with_cache(self, ttl: Option<std::time::Duration>) -> Self {
+        Self {
+            cache: ttl.map_or_else(PostCache::disabled, PostCache::new),
+            ..self
+        }
+    }
+
+    /// Returns a repository that records changes by callers without a
+    /// user account, such as API keys, as made by `user_id`
+    pub(crate) fn with_system_user(self, user_id: Option<i64>) -> Self {
+        Self {
+            system_user: user_id,
+            ..self
+        }
+    }
+
+    /// The user recorded in `created_by` and `updated_by` for changes made
+    /// through this repository
+    fn editor(&self) -> Option<i64> {
+        match self.actor {
+            Actor::User(id) => Some(id),
+            _ => self.system_user,
+        }
+    }
+
+    /// Returns a repository that rejects post content over `limit` bytes
+    pub(crate) fn with_max_content_bytes(self, limit: usize) -> Self {
+        Self {
+            max_content_bytes: limit,
+            ..self
+        }
+    }
+
+    /// Returns a repository that gives tags created along with a post
+    /// lowercase names, like `TagRepository::with_lowercase_names`
+    pub(crate) fn with_lowercase_tag_names(self, lowercase: bool) -> Self {
+        Self {
+            lowercase_tag_names: lowercase,
+            ..self
+        }
+    }
+
+    /// Forgets every cached read, after changes made through the `_in`
+    /// methods are committed
+    pub(crate) fn clear_cache(&self) {
+        self.cache.clear();
+    }
+
+    /// Hits and misses of the cache in front of `find_by_slug` and `list`
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
     }
 
     /// Creates a new post in the database.
     /// This method handles validation, insertion, and returns the complete
     /// post record with generated fields like ID and timestamps.
     pub async fn create(&self, post: CreatePost) -> DatabaseResult<Post> {
-        // Validate all fields before attempting database operation
-        post.validate()
-            .map_err(|e| DatabaseError::Validation(e.to_string()))?;
+        self.create_with_tags(post, None)
+            .await
+            .map(|created| created.post)
+    }
 
-        // Start a transaction to ensure data consistency
+    /// Creates a new post and attaches the tags named in `post.tags`,
+    /// creating any that don't exist yet. Everything happens in one
+    /// transaction, so an invalid tag leaves no post behind.
+    ///
+    /// `author_id` is the user writing the post, or `None` for posts
+    /// created without a user account, e.g. with the API key.
+    pub async fn create_with_tags(
+        &self,
+        post: CreatePost,
+        author_id: Option<i64>,
+    ) -> DatabaseResult<PostWithTags> {
+        let _timer = self.metrics.time(
+            "posts.create",
+            format!("slug={:?} tags={}", post.slug, post.tags.len()),
+        );
         let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+        let created = self.create_with_tags_in(&mut tx, post, author_id).await?;
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        self.cache.invalidate(&[&created.post.slug]);
+        Ok(created)
+    }
+
+    /// Like `create_with_tags`, but runs on the caller's connection so it
+    /// can take part in a larger transaction
+    #[instrument(
+        name = "posts.create_with_tags_in",
+        skip_all,
+        fields(post.slug = %post.slug, post.id),
+        err(level = "info")
+    )]
+    pub async fn create_with_tags_in(
+        &self,
+        conn: &mut SqliteConnection,
+        mut post: CreatePost,
+        author_id: Option<i64>,
+    ) -> DatabaseResult<PostWithTags> {
+        post.slug = normalize_slug(&post.slug);
+
+        // Validate all fields before attempting database operation
+        post.validate().map_err(DatabaseError::from)?;
+        validate_content_size(&post.content, self.max_content_bytes)?;
+
+        if let Some(original) = post.translation_of {
+            Self::check_original(conn, original).await?;
+        }
 
         // Convert category to string for database storage
         let category_str = post.category.to_string();
+        let published_at = post.published_at.map(stored_timestamp);
+        let editor = self.editor();
 
         let created_post = sqlx::query_as!(
             Post,
@@ -39,13 +165,31 @@ impl PostRepository {
                 description,
                 image_url,
                 external_url,
-                published
+                published,
+                meta_title,
+                meta_description,
+                og_image_url,
+                book_author,
+                isbn,
+                rating,
+                author_id,
+                created_by,
+                updated_by,
+                language,
+                translation_of,
+                published_at
+            )
+            VALUES (
+                ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                COALESCE(datetime(?), CASE WHEN ? THEN CURRENT_TIMESTAMP END)
             )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             RETURNING 
                 id, category as "category: PostCategory", title, slug,
                 content, description, image_url, external_url, published,
-                created_at, updated_at
+                meta_title, meta_description, og_image_url,
+                book_author, isbn, rating, author_id, created_by, updated_by,
+                language, translation_of,
+                published_at, created_at, updated_at
             "#,
             category_str,
             post.title,
@@ -54,47 +198,104 @@ impl PostRepository {
             post.description,
             post.image_url,
             post.external_url,
+            post.published,
+            post.meta_title,
+            post.meta_description,
+            post.og_image_url,
+            post.book_author,
+            post.isbn,
+            post.rating,
+            author_id,
+            editor,
+            editor,
+            post.language,
+            post.translation_of,
+            published_at,
             post.published
         )
-        .fetch_one(&mut *tx)
+        .fetch_one(&mut *conn)
         .await
-        .map_err(|e| match e {
-            sqlx::Error::Database(e) if e.message().contains("UNIQUE constraint") => {
-                DatabaseError::duplicate("Post", &post.slug)
+        .map_err(DatabaseError::on_duplicate("Post", &post.slug))?;
+        Span::current().record("post.id", created_post.id);
+
+        // The slug is live again, so it can no longer redirect elsewhere
+        Self::evict_slug_history(conn, &created_post.slug).await?;
+        AuditRepository::record_in(conn, self.actor, &AuditEvent::created(&created_post)).await?;
+
+        let mut tags = Vec::with_capacity(post.tags.len());
+        for name in &post.tags {
+            let (tag, created) = TagRepository::get_or_create_in(
+                conn,
+                &TagRequest::named(name.as_str()),
+                self.lowercase_tag_names,
+            )
+            .await?;
+            if created {
+                AuditRepository::record_in(conn, self.actor, &AuditEvent::created(&tag)).await?;
             }
-            e => DatabaseError::Sqlx(e),
-        })?;
-
-        // Commit the transaction
-        tx.commit().await.map_err(DatabaseError::Sqlx)?;
-        Ok(created_post)
+            TagRepository::attach_in(conn, created_post.id, tag.id).await?;
+            if !tags.iter().any(|t: &Tag| t.id == tag.id) {
+                tags.push(tag);
+            }
+        }
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(PostWithTags {
+            post: created_post,
+            tags,
+        })
     }
 
     /// Retrieves a post by its unique identifier.
     /// Returns a NotFound error if the post doesn't exist.
+    #[instrument(name = "posts.find_by_id", skip_all, fields(post.id = id), err(level = "info"))]
     pub async fn find_by_id(&self, id: i64) -> DatabaseResult<Post> {
+        let _timer = self.metrics.time("posts.find_by_id", format!("id={}", id));
+        let mut conn = self.pool.acquire().await.map_err(DatabaseError::Sqlx)?;
+        Self::find_in(&mut conn, id).await
+    }
+
+    /// Like `find_by_id`, on the caller's connection
+    async fn find_in(conn: &mut SqliteConnection, id: i64) -> DatabaseResult<Post> {
         sqlx::query_as!(
             Post,
             r#"
             SELECT 
                 id, category as "category: PostCategory", title, slug,
                 content, description, image_url, external_url, published,
-                created_at, updated_at
+                meta_title, meta_description, og_image_url,
+                book_author, isbn, rating, author_id, created_by, updated_by,
+                language, translation_of,
+                published_at, created_at, updated_at
             FROM posts
             WHERE id = ?
             "#,
             id
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(conn)
         .await
         .map_err(DatabaseError::Sqlx)?
         .ok_or_else(|| DatabaseError::not_found("Post", &id.to_string()))
     }
 
-    /// Retrieves a post by its URL-friendly slug.
+    /// Retrieves a post by its URL-friendly slug, ignoring case.
     /// Returns a NotFound error if the post doesn't exist.
+    #[instrument(
+        name = "posts.find_by_slug",
+        skip_all,
+        fields(post.slug = %slug),
+        err(level = "info")
+    )]
     pub async fn find_by_slug(&self, slug: &str) -> DatabaseResult<Post> {
-        sqlx::query_as!(
+        let _timer = self
+            .metrics
+            .time("posts.find_by_slug", format!("slug={:?}", slug));
+        let slug = normalize_slug(slug);
+        if let Some(post) = self.cache.get_slug(&slug) {
+            return Ok(post);
+        }
+
+        let read_at = self.cache.generation();
+        let post = sqlx::query_as!(
             Post,
             r#"
         SELECT 
@@ -107,6 +308,18 @@ impl PostRepository {
             image_url, 
             external_url,
             published as "published!",
+            meta_title,
+            meta_description,
+            og_image_url,
+            book_author,
+            isbn,
+            rating,
+            author_id,
+            created_by,
+            updated_by,
+            language as "language!",
+            translation_of,
+            published_at,
             created_at as "created_at!",
             updated_at as "updated_at!"
         FROM posts
@@ -117,51 +330,550 @@ impl PostRepository {
         .fetch_optional(&self.pool)
         .await
         .map_err(DatabaseError::Sqlx)?
-        .ok_or_else(|| DatabaseError::not_found("Post", slug))
+        .ok_or_else(|| DatabaseError::not_found("Post", &slug))?;
+
+        self.cache.put_slug(read_at, &post);
+        Ok(post)
+    }
+
+    /// Retrieves a post by its slug, falling back to the slug history.
+    /// Returns `SlugLookup::Moved` with the current slug when the requested
+    /// slug belonged to a post that has since been renamed.
+    #[instrument(
+        name = "posts.resolve_slug",
+        skip_all,
+        fields(post.slug = %slug),
+        err(level = "info")
+    )]
+    pub async fn resolve_slug(&self, slug: &str) -> DatabaseResult<SlugLookup> {
+        let _timer = self
+            .metrics
+            .time("posts.resolve_slug", format!("slug={:?}", slug));
+        let slug = normalize_slug(slug);
+        match self.find_by_slug(&slug).await {
+            Ok(post) => return Ok(SlugLookup::Found(Box::new(post))),
+            Err(DatabaseError::NotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+
+        sqlx::query_scalar!(
+            r#"
+            SELECT p.slug
+            FROM post_slugs ps
+            JOIN posts p ON p.id = ps.post_id
+            WHERE ps.slug = ?
+            "#,
+            slug
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?
+        .map(SlugLookup::Moved)
+        .ok_or_else(|| DatabaseError::not_found("Post", &slug))
+    }
+
+    /// Up to `limit` published slugs close to `slug`, closest first, for
+    /// suggesting where a mistyped URL meant to go. Slugs too different to
+    /// be a plausible typo are left out.
+    #[instrument(
+        name = "posts.similar_slugs",
+        skip_all,
+        fields(post.slug = %slug, limit = limit, rows),
+        err(level = "info")
+    )]
+    pub async fn similar_slugs(&self, slug: &str, limit: usize) -> DatabaseResult<Vec<String>> {
+        let _timer = self
+            .metrics
+            .time("posts.similar_slugs", format!("slug={:?}", slug));
+        let slug = normalize_slug(slug);
+
+        // There are few enough posts to rank every slug in memory
+        let slugs = sqlx::query_scalar!("SELECT slug FROM posts WHERE published = TRUE")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+
+        let mut similar: Vec<(usize, String)> = slugs
+            .into_iter()
+            .filter(|candidate| *candidate != slug && is_similar_slug(&slug, candidate))
+            .map(|candidate| (slug_distance(&slug, &candidate), candidate))
+            .collect();
+        similar.sort();
+        let similar: Vec<String> = similar
+            .into_iter()
+            .take(limit)
+            .map(|(_, candidate)| candidate)
+            .collect();
+        record_rows(similar.len());
+        Ok(similar)
     }
 
     /// Lists posts with optional filtering and pagination.
     ///
     /// Parameters:
     /// - category: Optional filter for post category
+    /// - language: Optional filter for the language posts are written in
     /// - published_only: When true, returns only published posts
     /// - limit: Maximum number of posts to return (1-100)
     /// - offset: Number of posts to skip for pagination
     pub async fn list(
         &self,
         category: Option<PostCategory>,
-        published_only: bool,
+        language: Option<&str>,
+        visibility: Visibility,
         limit: i64,
         offset: i64,
     ) -> DatabaseResult<Vec<Post>> {
-        // Validate pagination parameters
-        if limit <= 0 || limit > 100 {
-            return Err(DatabaseError::validation("Limit must be between 1 and 100"));
+        let (posts, _) = self
+            .list_with_total(category, language, visibility, limit, offset)
+            .await?;
+        Ok(posts)
+    }
+
+    /// Like `list`, together with the number of posts matching the filters.
+    ///
+    /// The total is counted with a window function in the same query as
+    /// the page, so the two always agree even while posts are being written.
+    #[instrument(
+        name = "posts.list_with_total",
+        skip_all,
+        fields(
+            category = ?category,
+            language = ?language,
+            visibility = %visibility,
+            limit = limit,
+            offset = offset,
+            rows,
+            total,
+        ),
+        err(level = "info")
+    )]
+    pub async fn list_with_total(
+        &self,
+        category: Option<PostCategory>,
+        language: Option<&str>,
+        visibility: Visibility,
+        limit: i64,
+        offset: i64,
+    ) -> DatabaseResult<(Vec<Post>, i64)> {
+        #[derive(sqlx::FromRow)]
+        struct CountedPost {
+            total: i64,
+            #[sqlx(flatten)]
+            post: Post,
         }
-        if offset < 0 {
-            return Err(DatabaseError::validation("Offset cannot be negative"));
+
+        let _timer = self.metrics.time(
+            "posts.list",
+            format!(
+                "category={:?} language={:?} visibility={} limit={} offset={}",
+                category, language, visibility, limit, offset
+            ),
+        );
+        validate_pagination(limit, offset)?;
+        validate_language_filter(language)?;
+
+        let key = ListKey::new(category.as_ref(), language, visibility, limit, offset);
+        if let Some((posts, total)) = self.cache.get_list(&key) {
+            record_rows(posts.len());
+            Span::current().record("total", total);
+            return Ok((posts, total));
         }
 
         // Convert category to string if it exists
         let category_str = category.map(|c| c.to_string());
 
+        let filter = listing_filter(category_str.is_some(), language.is_some(), visibility);
+
+        let read_at = self.cache.generation();
+        let mut conn = self.pool.acquire().await.map_err(DatabaseError::Sqlx)?;
+        let sql = listing_query(&filter);
+        let mut query = sqlx::query_as::<_, CountedPost>(&sql);
+        if let Some(category) = &category_str {
+            query = query.bind(category);
+        }
+        if let Some(language) = language {
+            query = query.bind(language);
+        }
+        let rows = query
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+
+        let total = match rows.first() {
+            Some(row) => row.total,
+            // A page past the end has no rows to carry the count
+            None if offset > 0 => {
+                let sql = format!("SELECT COUNT(*) FROM posts {}", filter);
+                let mut query = sqlx::query_scalar(&sql);
+                if let Some(category) = &category_str {
+                    query = query.bind(category);
+                }
+                if let Some(language) = language {
+                    query = query.bind(language);
+                }
+                query
+                    .fetch_one(&mut *conn)
+                    .await
+                    .map_err(DatabaseError::Sqlx)?
+            }
+            None => 0,
+        };
+        let posts: Vec<Post> = rows.into_iter().map(|row| row.post).collect();
+
+        self.cache.put_list(read_at, key, &posts, total);
+        record_rows(posts.len());
+        Span::current().record("total", total);
+        Ok((posts, total))
+    }
+
+    /// Every post in id order, drafts included unless `published_only`, read
+    /// one row at a time as the stream is polled. Reads past the cache, so
+    /// it's meant for exports rather than requests.
+    ///
+    /// The span and timing cover the whole read, until the stream ends or is
+    /// dropped.
+    pub fn stream(&self, published_only: bool) -> BoxStream<'_, DatabaseResult<Post>> {
+        let span = tracing::info_span!(
+            "posts.stream",
+            published_only = published_only,
+            rows = tracing::field::Empty
+        );
+        let timer = self
+            .metrics
+            .time("posts.stream", format!("published_only={}", published_only));
+        let rows = sqlx::query_as(
+            r#"
+            SELECT
+                id, category, title, slug,
+                content, description, image_url, external_url, published,
+                meta_title, meta_description, og_image_url,
+                book_author, isbn, rating, author_id, created_by, updated_by,
+                language, translation_of,
+                published_at, created_at, updated_at
+            FROM posts
+            WHERE ? = FALSE OR published = TRUE
+            ORDER BY id
+            "#,
+        )
+        .bind(published_only)
+        .fetch(&self.pool)
+        .map_err(DatabaseError::Sqlx);
+
+        stream::unfold(
+            (rows, span, timer, 0),
+            |(mut rows, span, timer, count)| async move {
+                match rows.next().instrument(span.clone()).await {
+                    Some(Ok(post)) => Some((Ok(post), (rows, span, timer, count + 1))),
+                    Some(Err(e)) => {
+                        span.in_scope(|| tracing::info!(error = %e));
+                        Some((Err(e), (rows, span, timer, count)))
+                    }
+                    None => {
+                        span.record("rows", count);
+                        None
+                    }
+                }
+            },
+        )
+        .boxed()
+    }
+
+    /// Every post, drafts included, in id order. Reads past the cache and
+    /// isn't paginated, so it's meant for exports rather than requests.
+    #[instrument(name = "posts.list_all", skip_all, fields(rows), err(level = "info"))]
+    pub async fn list_all(&self) -> DatabaseResult<Vec<Post>> {
+        let _timer = self.metrics.time("posts.list_all", String::new());
         sqlx::query_as!(
             Post,
             r#"
-            SELECT 
+            SELECT
+                id, category as "category: PostCategory", title, slug,
+                content, description, image_url, external_url, published,
+                meta_title, meta_description, og_image_url,
+                book_author, isbn, rating, author_id, created_by, updated_by,
+                language, translation_of,
+                published_at, created_at, updated_at
+            FROM posts
+            ORDER BY id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)
+        .inspect(|posts| record_rows(posts.len()))
+    }
+
+    /// Lists posts carrying the given tag, newest first.
+    ///
+    /// Parameters:
+    /// - tag_id: Only posts associated with this tag are returned
+    /// - language: Optional filter for the language posts are written in
+    /// - published_only: When true, drafts are excluded from the result
+    /// - limit: Maximum number of posts to return (1-100)
+    /// - offset: Number of posts to skip for pagination
+    #[instrument(
+        name = "posts.list_by_tag",
+        skip_all,
+        fields(
+            tag.id = tag_id,
+            language = ?language,
+            published_only = published_only,
+            limit = limit,
+            offset = offset,
+            rows,
+        ),
+        err(level = "info")
+    )]
+    pub async fn list_by_tag(
+        &self,
+        tag_id: i64,
+        language: Option<&str>,
+        published_only: bool,
+        limit: i64,
+        offset: i64,
+    ) -> DatabaseResult<Vec<Post>> {
+        let _timer = self.metrics.time(
+            "posts.list_by_tag",
+            format!(
+                "tag_id={} language={:?} published_only={} limit={} offset={}",
+                tag_id, language, published_only, limit, offset
+            ),
+        );
+        validate_pagination(limit, offset)?;
+        validate_language_filter(language)?;
+
+        sqlx::query_as!(
+            Post,
+            r#"
+            SELECT
+                p.id as "id!", p.category as "category: PostCategory", p.title, p.slug,
+                p.content, p.description, p.image_url, p.external_url, p.published,
+                p.meta_title, p.meta_description, p.og_image_url,
+                p.book_author, p.isbn, p.rating, p.author_id, p.created_by, p.updated_by,
+                p.language, p.translation_of,
+                p.published_at, p.created_at, p.updated_at
+            FROM posts p
+            JOIN post_tags pt ON pt.post_id = p.id
+            WHERE
+                pt.tag_id = ?
+                AND (? IS NULL OR p.language = ?)
+                AND (? = FALSE OR p.published = TRUE)
+            ORDER BY COALESCE(p.published_at, p.created_at) DESC, p.id DESC
+            LIMIT ?
+            OFFSET ?
+            "#,
+            tag_id,
+            language,
+            language,
+            published_only,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)
+        .inspect(|posts| record_rows(posts.len()))
+    }
+
+    /// Lists the posts of several tags in one query, newest first, by tag
+    /// ID. Tags without posts, or that don't exist, are left out.
+    #[instrument(
+        name = "posts.list_by_tags",
+        skip_all,
+        fields(tags = tag_ids.len(), published_only = published_only, rows),
+        err(level = "info")
+    )]
+    pub async fn list_by_tags(
+        &self,
+        tag_ids: &[i64],
+        published_only: bool,
+    ) -> DatabaseResult<HashMap<i64, Vec<Post>>> {
+        #[derive(sqlx::FromRow)]
+        struct TaggedPost {
+            tag_id: i64,
+            #[sqlx(flatten)]
+            post: Post,
+        }
+
+        let _timer = self.metrics.time(
+            "posts.list_by_tags",
+            format!("tags={} published_only={}", tag_ids.len(), published_only),
+        );
+
+        // SQLite can't bind arrays, so the ids are passed as a JSON array
+        let ids = serde_json::to_string(tag_ids).expect("integers serialize to JSON");
+        let rows: Vec<TaggedPost> = sqlx::query_as(
+            r#"
+            SELECT
+                pt.tag_id,
+                p.id, p.category, p.title, p.slug,
+                p.content, p.description, p.image_url, p.external_url, p.published,
+                p.meta_title, p.meta_description, p.og_image_url,
+                p.book_author, p.isbn, p.rating, p.author_id, p.created_by, p.updated_by,
+                p.language, p.translation_of,
+                p.published_at, p.created_at, p.updated_at
+            FROM posts p
+            JOIN post_tags pt ON pt.post_id = p.id
+            WHERE
+                pt.tag_id IN (SELECT value FROM json_each(?))
+                AND (? = FALSE OR p.published = TRUE)
+            ORDER BY COALESCE(p.published_at, p.created_at) DESC, p.id DESC
+            "#,
+        )
+        .bind(&ids)
+        .bind(published_only)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        record_rows(rows.len());
+        let mut posts: HashMap<i64, Vec<Post>> = HashMap::new();
+        for row in rows {
+            posts.entry(row.tag_id).or_default().push(row.post);
+        }
+        Ok(posts)
+    }
+
+    /// Lists posts without any tags, newest first, together with the total
+    /// number of untagged posts.
+    ///
+    /// Parameters:
+    /// - published_only: When true, drafts are excluded from the result and the total
+    /// - limit: Maximum number of posts to return (1-100)
+    /// - offset: Number of posts to skip for pagination
+    #[instrument(
+        name = "posts.list_untagged",
+        skip_all,
+        fields(published_only = published_only, limit = limit, offset = offset, rows, total),
+        err(level = "info")
+    )]
+    pub async fn list_untagged(
+        &self,
+        published_only: bool,
+        limit: i64,
+        offset: i64,
+    ) -> DatabaseResult<(Vec<Post>, i64)> {
+        let _timer = self.metrics.time(
+            "posts.list_untagged",
+            format!(
+                "published_only={} limit={} offset={}",
+                published_only, limit, offset
+            ),
+        );
+        validate_pagination(limit, offset)?;
+
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+
+        let posts = sqlx::query_as!(
+            Post,
+            r#"
+            SELECT
+                p.id, p.category as "category: PostCategory", p.title, p.slug,
+                p.content, p.description, p.image_url, p.external_url, p.published,
+                p.meta_title, p.meta_description, p.og_image_url,
+                p.book_author, p.isbn, p.rating, p.author_id, p.created_by, p.updated_by,
+                p.language, p.translation_of,
+                p.published_at, p.created_at, p.updated_at
+            FROM posts p
+            WHERE
+                NOT EXISTS (SELECT 1 FROM post_tags pt WHERE pt.post_id = p.id)
+                AND (? = FALSE OR p.published = TRUE)
+            ORDER BY COALESCE(p.published_at, p.created_at) DESC, p.id DESC
+            LIMIT ?
+            OFFSET ?
+            "#,
+            published_only,
+            limit,
+            offset
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        let total = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*)
+            FROM posts p
+            WHERE
+                NOT EXISTS (SELECT 1 FROM post_tags pt WHERE pt.post_id = p.id)
+                AND (? = FALSE OR p.published = TRUE)
+            "#,
+            published_only
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        record_rows(posts.len());
+        Span::current().record("total", total);
+        Ok((posts, total))
+    }
+
+    /// Lists posts modified after the given instant, oldest change first.
+    ///
+    /// Timestamps are stored with second precision, so `since` is truncated to
+    /// the whole second and posts modified within that second are included.
+    /// Callers may therefore see a post again at the boundary, but never miss one.
+    ///
+    /// Parameters:
+    /// - since: Only posts whose updated_at is at or after this instant are returned
+    /// - published_only: When true, drafts are excluded from the result
+    /// - limit: Maximum number of posts to return (1-100)
+    /// - offset: Number of posts to skip for pagination
+    #[instrument(
+        name = "posts.list_modified_since",
+        skip_all,
+        fields(
+            since = %since,
+            published_only = published_only,
+            limit = limit,
+            offset = offset,
+            rows,
+        ),
+        err(level = "info")
+    )]
+    pub async fn list_modified_since(
+        &self,
+        since: OffsetDateTime,
+        published_only: bool,
+        limit: i64,
+        offset: i64,
+    ) -> DatabaseResult<Vec<Post>> {
+        let _timer = self.metrics.time(
+            "posts.list_modified_since",
+            format!(
+                "since={} published_only={} limit={} offset={}",
+                since, published_only, limit, offset
+            ),
+        );
+        validate_pagination(limit, offset)?;
+
+        // Stored timestamps are UTC with whole seconds, so normalize before comparing
+        let since = stored_timestamp(since);
+
+        sqlx::query_as!(
+            Post,
+            r#"
+            SELECT
                 id, category as "category: PostCategory", title, slug,
                 content, description, image_url, external_url, published,
-                created_at, updated_at
+                meta_title, meta_description, og_image_url,
+                book_author, isbn, rating, author_id, created_by, updated_by,
+                language, translation_of,
+                published_at, created_at, updated_at
             FROM posts
             WHERE
-                (? IS NULL OR category = ?)
+                julianday(updated_at) >= julianday(?)
                 AND (? = FALSE OR published = TRUE)
-            ORDER BY created_at DESC
+            ORDER BY updated_at ASC, id ASC
             LIMIT ?
             OFFSET ?
             "#,
-            category_str,
-            category_str,
+            since,
             published_only,
             limit,
             offset
@@ -169,19 +881,199 @@ impl PostRepository {
         .fetch_all(&self.pool)
         .await
         .map_err(DatabaseError::Sqlx)
+        .inspect(|posts| record_rows(posts.len()))
+    }
+
+    /// Counts the published posts in each category, for every category in
+    /// the order of `PostCategory::all`, including those without posts
+    #[instrument(
+        name = "posts.count_by_category",
+        skip_all,
+        fields(rows),
+        err(level = "info")
+    )]
+    pub async fn count_by_category(&self) -> DatabaseResult<Vec<CategorySummary>> {
+        let _timer = self.metrics.time("posts.count_by_category", String::new());
+        let counts = sqlx::query!(
+            r#"
+            SELECT category as "category: PostCategory", COUNT(*) as "posts!: i64"
+            FROM posts
+            WHERE published = TRUE
+            GROUP BY category
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        record_rows(counts.len());
+        Ok(PostCategory::all()
+            .into_iter()
+            .map(|category| CategorySummary {
+                label: category.label(),
+                published_posts: counts
+                    .iter()
+                    .find(|count| count.category == category)
+                    .map_or(0, |count| count.posts),
+                category,
+            })
+            .collect())
+    }
+
+    /// Lists the published posts created in the given month (UTC), oldest
+    /// first, for archive pages. A month without posts gives an empty list.
+    ///
+    /// Parameters:
+    /// - year, month: The month to list, with `month` from 1 to 12
+    /// - limit: Maximum number of posts to return (1-100)
+    /// - offset: Number of posts to skip for pagination
+    #[instrument(
+        name = "posts.list_by_month",
+        skip_all,
+        fields(year = year, month = month, limit = limit, offset = offset, rows),
+        err(level = "info")
+    )]
+    pub async fn list_by_month(
+        &self,
+        year: i32,
+        month: u8,
+        limit: i64,
+        offset: i64,
+    ) -> DatabaseResult<Vec<Post>> {
+        let _timer = self.metrics.time(
+            "posts.list_by_month",
+            format!(
+                "year={} month={} limit={} offset={}",
+                year, month, limit, offset
+            ),
+        );
+        let Some((start, end)) = month_bounds(year, month) else {
+            return Err(DatabaseError::invalid_field(
+                "month",
+                "Month must be between 1 and 12",
+            ));
+        };
+        validate_pagination(limit, offset)?;
+
+        sqlx::query_as!(
+            Post,
+            r#"
+            SELECT
+                id as "id!", category as "category: PostCategory", title, slug,
+                content, description, image_url, external_url, published,
+                meta_title, meta_description, og_image_url,
+                book_author, isbn, rating, author_id, created_by, updated_by,
+                language, translation_of,
+                published_at, created_at, updated_at
+            FROM posts
+            WHERE
+                published = TRUE
+                AND julianday(created_at) >= julianday(?)
+                AND julianday(created_at) < julianday(?)
+            ORDER BY created_at ASC, id ASC
+            LIMIT ?
+            OFFSET ?
+            "#,
+            start,
+            end,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)
+        .inspect(|posts| record_rows(posts.len()))
     }
 
     /// Updates all fields of an existing post.
     /// Returns a NotFound error if the post doesn't exist.
-    pub async fn update(&self, post: UpdatePost) -> DatabaseResult<Post> {
-        // Validate all fields before attempting database operation
-        post.validate()
-            .map_err(|e| DatabaseError::Validation(e.to_string()))?;
-
+    ///
+    /// The flag is true when the post changed. When it already holds these
+    /// values it is returned as is: nothing is written, audited or
+    /// invalidated, and `updated_at` stays put.
+    #[instrument(
+        name = "posts.update",
+        skip_all,
+        fields(post.id = post.id, post.slug = %post.slug),
+        err(level = "info")
+    )]
+    pub async fn update(&self, post: UpdatePost) -> DatabaseResult<(Post, bool)> {
+        let _timer = self.metrics.time("posts.update", format!("id={}", post.id));
         let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+        let (updated_post, previous_slug, changed) =
+            self.update_returning_slug(&mut tx, post).await?;
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        if changed {
+            self.cache.invalidate(&[&previous_slug, &updated_post.slug]);
+        }
+        Ok((updated_post, changed))
+    }
+
+    /// Like `update`, but runs on the caller's connection so it can take
+    /// part in a larger transaction
+    #[instrument(
+        name = "posts.update_in",
+        skip_all,
+        fields(post.id = post.id, post.slug = %post.slug),
+        err(level = "info")
+    )]
+    pub async fn update_in(
+        &self,
+        conn: &mut SqliteConnection,
+        post: UpdatePost,
+    ) -> DatabaseResult<(Post, bool)> {
+        let (updated_post, _, changed) = self.update_returning_slug(conn, post).await?;
+        Ok((updated_post, changed))
+    }
+
+    /// Updates the post, returning it along with the slug it had before,
+    /// which the cache has to forget too, and whether anything changed
+    async fn update_returning_slug(
+        &self,
+        conn: &mut SqliteConnection,
+        mut post: UpdatePost,
+    ) -> DatabaseResult<(Post, String, bool)> {
+        post.slug = normalize_slug(&post.slug);
+
+        // Validate all fields before attempting database operation
+        post.validate().map_err(DatabaseError::from)?;
+        validate_content_size(&post.content, self.max_content_bytes)?;
+
+        // Keep the current post to record the slug history and the audit diff
+        let current = Self::find_in(conn, post.id).await?;
+        Self::check_translation_of(conn, post.id, post.translation_of).await?;
+
+        let merged = Post {
+            category: post.category.clone(),
+            title: post.title.clone(),
+            slug: post.slug.clone(),
+            content: post.content.clone(),
+            description: post.description.clone(),
+            image_url: post.image_url.clone(),
+            external_url: post.external_url.clone(),
+            published: post.published,
+            meta_title: post.meta_title.clone(),
+            meta_description: post.meta_description.clone(),
+            og_image_url: post.og_image_url.clone(),
+            book_author: post.book_author.clone(),
+            isbn: post.isbn.clone(),
+            rating: post.rating,
+            language: post.language.clone(),
+            translation_of: post.translation_of,
+            published_at: post
+                .published_at
+                .map(stored_timestamp)
+                .or(current.published_at),
+            ..current.clone()
+        };
+        if current.same_fields(&merged) {
+            let slug = current.slug.clone();
+            return Ok((current, slug, false));
+        }
 
         // Convert category to string for database storage
         let category_str = post.category.to_string();
+        let editor = self.editor();
 
         let updated_post = sqlx::query_as!(
             Post,
@@ -196,12 +1088,25 @@ impl PostRepository {
                 image_url = ?,
                 external_url = ?,
                 published = ?,
+                meta_title = ?,
+                meta_description = ?,
+                og_image_url = ?,
+                book_author = ?,
+                isbn = ?,
+                rating = ?,
+                language = ?,
+                translation_of = ?,
+                published_at = COALESCE(datetime(?), CASE WHEN ? THEN CURRENT_TIMESTAMP END),
+                updated_by = ?,
                 updated_at = CURRENT_TIMESTAMP
             WHERE id = ?
             RETURNING 
                 id, category as "category: PostCategory", title, slug,
                 content, description, image_url, external_url, published,
-                created_at, updated_at
+                meta_title, meta_description, og_image_url,
+                book_author, isbn, rating, author_id, created_by, updated_by,
+                language, translation_of,
+                published_at, created_at, updated_at
             "#,
             category_str,
             post.title,
@@ -211,40 +1116,192 @@ impl PostRepository {
             post.image_url,
             post.external_url,
             post.published,
+            post.meta_title,
+            post.meta_description,
+            post.og_image_url,
+            post.book_author,
+            post.isbn,
+            post.rating,
+            post.language,
+            post.translation_of,
+            merged.published_at,
+            post.published,
+            editor,
             post.id
         )
-        .fetch_optional(&mut *tx)
+        .fetch_optional(&mut *conn)
         .await
-        .map_err(|e| match e {
-            sqlx::Error::Database(e) if e.message().contains("UNIQUE constraint") => {
-                DatabaseError::duplicate("Post", &post.slug)
-            }
-            e => DatabaseError::Sqlx(e),
-        })?
+        .map_err(DatabaseError::on_duplicate("Post", &post.slug))?
         .ok_or_else(|| DatabaseError::not_found("Post", &post.id.to_string()))?;
 
-        tx.commit().await.map_err(DatabaseError::Sqlx)?;
-        Ok(updated_post)
+        Self::record_slug_change(conn, post.id, &current.slug, &updated_post.slug).await?;
+        let event = AuditEvent::changed(AuditAction::Update, &current.snapshot(), &updated_post);
+        AuditRepository::record_in(conn, self.actor, &event).await?;
+
+        Ok((updated_post, current.slug, true))
     }
 
     /// Partially updates a post, only modifying provided fields.
     /// This is useful for making small changes without needing to send the entire post.
-    pub async fn patch(&self, patch: PatchPost) -> DatabaseResult<Post> {
+    ///
+    /// The flag is true when the post changed; like `update`, a patch that
+    /// leaves every field as it was writes nothing.
+    #[instrument(name = "posts.patch", skip_all, fields(post.id = patch.id), err(level = "info"))]
+    pub async fn patch(&self, patch: PatchPost) -> DatabaseResult<(Post, bool)> {
+        let _timer = self.metrics.time("posts.patch", format!("id={}", patch.id));
         let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+        let (updated_post, previous_slug, changed) =
+            self.patch_returning_slug(&mut tx, patch).await?;
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        if changed {
+            self.cache.invalidate(&[&previous_slug, &updated_post.slug]);
+        }
+        Ok((updated_post, changed))
+    }
 
-        // First fetch the existing post to merge with patch data
-        let current = self.find_by_id(patch.id).await?;
-
-        // Convert category to string if it's being updated
-        let category_str = patch.category.unwrap_or(current.category).to_string();
-
-        let title = patch.title.clone().unwrap_or(current.title);
-        let slug = patch.slug.clone().unwrap_or(current.slug);
-        let content = patch.content.unwrap_or(current.content);
-        let description = patch.description.unwrap_or(current.description);
-        let img = patch.image_url.or(current.image_url);
-        let url = patch.external_url.or(current.external_url);
-        let published = patch.published.unwrap_or(current.published);
+    /// Like `patch`, but runs on the caller's connection so it can take
+    /// part in a larger transaction
+    #[instrument(
+        name = "posts.patch_in",
+        skip_all,
+        fields(post.id = patch.id),
+        err(level = "info")
+    )]
+    pub async fn patch_in(
+        &self,
+        conn: &mut SqliteConnection,
+        patch: PatchPost,
+    ) -> DatabaseResult<(Post, bool)> {
+        let (updated_post, _, changed) = self.patch_returning_slug(conn, patch).await?;
+        Ok((updated_post, changed))
+    }
+
+    /// Patches the post, returning it along with the slug it had before
+    /// and whether anything changed
+    async fn patch_returning_slug(
+        &self,
+        conn: &mut SqliteConnection,
+        mut patch: PatchPost,
+    ) -> DatabaseResult<(Post, String, bool)> {
+        patch.slug = patch.slug.as_deref().map(normalize_slug);
+
+        // Validate the fields being changed before touching the database.
+        // Rules that depend on the stored post are checked once it has been
+        // fetched, and every broken rule is reported together.
+        let mut errors = patch
+            .validate()
+            .err()
+            .map(ValidationErrors::into_vec)
+            .unwrap_or_default();
+        if let Some(content) = &patch.content {
+            validate_content_size(content, self.max_content_bytes)?;
+        }
+
+        // First fetch the existing post to merge with patch data
+        let current = Self::find_in(conn, patch.id).await?;
+        let before = current.snapshot();
+
+        let category = patch
+            .category
+            .clone()
+            .unwrap_or_else(|| current.category.clone());
+
+        // Book details only apply to Reading posts: reject them for other
+        // categories, and drop any left over when a post moves away from Reading
+        let (book_author, isbn, rating) = if category == PostCategory::Reading {
+            (
+                patch
+                    .book_author
+                    .clone()
+                    .unwrap_or_else(|| current.book_author.clone()),
+                patch.isbn.clone().unwrap_or_else(|| current.isbn.clone()),
+                patch.rating.unwrap_or(current.rating),
+            )
+        } else {
+            if patch.sets_reading_fields() {
+                errors.push(PostError::ReadingFieldsNotAllowed(category.to_string()));
+            }
+            (None, None, None)
+        };
+        validate_reading_fields(
+            &category,
+            book_author.as_deref(),
+            isbn.as_deref(),
+            rating,
+            &mut errors,
+        );
+
+        // Convert category to string for database storage
+        let category_str = category.to_string();
+
+        let previous_slug = current.slug.clone();
+        let merged = Post {
+            category,
+            title: patch.title.clone().unwrap_or_else(|| current.title.clone()),
+            slug: patch.slug.clone().unwrap_or_else(|| current.slug.clone()),
+            content: patch.content.unwrap_or_else(|| current.content.clone()),
+            description: patch
+                .description
+                .unwrap_or_else(|| current.description.clone()),
+            image_url: patch.image_url.unwrap_or_else(|| current.image_url.clone()),
+            external_url: patch
+                .external_url
+                .unwrap_or_else(|| current.external_url.clone()),
+            published: patch.published.unwrap_or(current.published),
+            meta_title: patch
+                .meta_title
+                .unwrap_or_else(|| current.meta_title.clone()),
+            meta_description: patch
+                .meta_description
+                .unwrap_or_else(|| current.meta_description.clone()),
+            og_image_url: patch
+                .og_image_url
+                .unwrap_or_else(|| current.og_image_url.clone()),
+            book_author,
+            isbn,
+            rating,
+            language: patch.language.unwrap_or_else(|| current.language.clone()),
+            translation_of: patch.translation_of.unwrap_or(current.translation_of),
+            published_at: patch
+                .published_at
+                .map(stored_timestamp)
+                .or(current.published_at),
+            ..current.clone()
+        };
+        if merged.title.trim().is_empty() {
+            errors.push(PostError::EmptyTitle);
+        }
+        if merged.content.trim().is_empty() {
+            errors.push(PostError::EmptyContent);
+        }
+        errors
+            .extend(validate_external_url(&merged.category, merged.external_url.as_deref()).err());
+        ValidationErrors::check(errors).map_err(DatabaseError::from)?;
+        Self::check_translation_of(conn, patch.id, merged.translation_of).await?;
+        if current.same_fields(&merged) {
+            return Ok((current, previous_slug, false));
+        }
+
+        let Post {
+            title,
+            slug,
+            content,
+            description,
+            image_url: img,
+            external_url: url,
+            published,
+            meta_title,
+            meta_description,
+            og_image_url,
+            book_author,
+            isbn,
+            rating,
+            language,
+            translation_of,
+            published_at,
+            ..
+        } = merged;
+        let editor = self.editor();
         let updated_post = sqlx::query_as!(
             Post,
             r#"
@@ -258,12 +1315,25 @@ impl PostRepository {
                 image_url = ?,
                 external_url = ?,
                 published = ?,
+                meta_title = ?,
+                meta_description = ?,
+                og_image_url = ?,
+                book_author = ?,
+                isbn = ?,
+                rating = ?,
+                language = ?,
+                translation_of = ?,
+                published_at = COALESCE(datetime(?), CASE WHEN ? THEN CURRENT_TIMESTAMP END),
+                updated_by = ?,
                 updated_at = CURRENT_TIMESTAMP
             WHERE id = ?
             RETURNING 
                 id, category as "category: PostCategory", title, slug,
                 content, description, image_url, external_url, published,
-                created_at, updated_at
+                meta_title, meta_description, og_image_url,
+                book_author, isbn, rating, author_id, created_by, updated_by,
+                language, translation_of,
+                published_at, created_at, updated_at
             "#,
             category_str,
             title,
@@ -273,68 +1343,398 @@ impl PostRepository {
             img,
             url,
             published,
+            meta_title,
+            meta_description,
+            og_image_url,
+            book_author,
+            isbn,
+            rating,
+            language,
+            translation_of,
+            published_at,
+            published,
+            editor,
             patch.id
         )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(DatabaseError::on_duplicate(
+            "Post",
+            patch.slug.as_deref().unwrap_or_default(),
+        ))?;
+
+        Self::record_slug_change(conn, patch.id, &previous_slug, &updated_post.slug).await?;
+        let event = AuditEvent::changed(AuditAction::Patch, &before, &updated_post);
+        AuditRepository::record_in(conn, self.actor, &event).await?;
+
+        Ok((updated_post, previous_slug, true))
+    }
+
+    /// Writes autosaved text to a draft. Only the content size limit is
+    /// checked, so half written fields are accepted, and `updated_at` is
+    /// bumped even when nothing changed. Published posts are rejected with
+    /// a conflict; they go through `update` and `patch`.
+    #[instrument(name = "posts.save_draft", skip_all, fields(post.id = id), err(level = "info"))]
+    pub async fn save_draft(&self, id: i64, draft: DraftContent) -> DatabaseResult<Post> {
+        let _timer = self.metrics.time("posts.save_draft", format!("id={}", id));
+        if let Some(content) = &draft.content {
+            validate_content_size(content, self.max_content_bytes)?;
+        }
+
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+        let current = Self::find_in(&mut tx, id).await?;
+        if current.published {
+            return Err(DatabaseError::Conflict(format!(
+                "Post {} is published; only drafts can be autosaved",
+                id
+            )));
+        }
+
+        let title = draft.title.unwrap_or_else(|| current.title.clone());
+        let content = draft.content.unwrap_or_else(|| current.content.clone());
+        let description = draft
+            .description
+            .unwrap_or_else(|| current.description.clone());
+        let editor = self.editor();
+        let saved = sqlx::query_as!(
+            Post,
+            r#"
+            UPDATE posts
+            SET
+                title = ?,
+                content = ?,
+                description = ?,
+                updated_by = ?,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            RETURNING 
+                id, category as "category: PostCategory", title, slug,
+                content, description, image_url, external_url, published,
+                meta_title, meta_description, og_image_url,
+                book_author, isbn, rating, author_id, created_by, updated_by,
+                language, translation_of,
+                published_at, created_at, updated_at
+            "#,
+            title,
+            content,
+            description,
+            editor,
+            id
+        )
         .fetch_one(&mut *tx)
         .await
-        .map_err(|e| match e {
-            sqlx::Error::Database(e) if e.message().contains("UNIQUE constraint") => {
-                DatabaseError::duplicate("Post", &patch.slug.unwrap_or_default())
-            }
-            e => DatabaseError::Sqlx(e),
-        })?;
+        .map_err(DatabaseError::Sqlx)?;
 
+        // Autosaves arrive every few seconds, so only those that changed
+        // the text make it into the audit log
+        if !current.same_fields(&saved) {
+            let event = AuditEvent::changed(AuditAction::Patch, &current.snapshot(), &saved);
+            AuditRepository::record_in(&mut tx, self.actor, &event).await?;
+        }
         tx.commit().await.map_err(DatabaseError::Sqlx)?;
-        Ok(updated_post)
+        self.cache.invalidate(&[&saved.slug]);
+        Ok(saved)
     }
 
     /// Deletes a post by its ID.
     /// Returns a NotFound error if the post doesn't exist.
     pub async fn delete(&self, id: i64) -> DatabaseResult<()> {
+        let _timer = self.metrics.time("posts.delete", format!("id={}", id));
         let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+        let post = self.delete_in(&mut tx, id).await?;
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        self.cache.invalidate(&[&post.slug]);
+        Ok(())
+    }
+
+    /// Like `delete`, but runs on the caller's connection so it can take
+    /// part in a larger transaction. Returns the deleted post.
+    #[instrument(name = "posts.delete_in", skip_all, fields(post.id = id), err(level = "info"))]
+    pub async fn delete_in(&self, conn: &mut SqliteConnection, id: i64) -> DatabaseResult<Post> {
+        // Keep the post's last state for the audit log
+        let post = Self::find_in(conn, id).await?;
 
-        let result = sqlx::query!(
+        sqlx::query!(
             r#"
             DELETE FROM posts
             WHERE id = ?
             "#,
             id
         )
-        .execute(&mut *tx)
+        .execute(&mut *conn)
         .await
         .map_err(DatabaseError::Sqlx)?;
 
-        if result.rows_affected() == 0 {
-            return Err(DatabaseError::not_found("Post", &id.to_string()));
+        AuditRepository::record_in(conn, self.actor, &AuditEvent::deleted(&post)).await?;
+
+        Ok(post)
+    }
+
+    /// Records a slug rename in the history so the previous slug keeps resolving.
+    /// The new slug is evicted from the history since it now belongs to a live post.
+    async fn record_slug_change(
+        conn: &mut SqliteConnection,
+        post_id: i64,
+        previous_slug: &str,
+        new_slug: &str,
+    ) -> DatabaseResult<()> {
+        if previous_slug == new_slug {
+            return Ok(());
         }
 
-        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Self::evict_slug_history(conn, new_slug).await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO post_slugs (slug, post_id)
+            VALUES (?, ?)
+            ON CONFLICT(slug) DO UPDATE SET post_id = excluded.post_id
+            "#,
+            previous_slug,
+            post_id
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        Ok(())
+    }
+
+    /// The published posts in other languages that share `post`'s
+    /// original, or translate `post` itself, by language
+    #[instrument(
+        name = "posts.translations",
+        skip_all,
+        fields(post.id = post.id, rows),
+        err(level = "info")
+    )]
+    pub async fn translations(&self, post: &Post) -> DatabaseResult<Vec<Translation>> {
+        let _timer = self
+            .metrics
+            .time("posts.translations", format!("id={}", post.id));
+        let original = post.translation_of.unwrap_or(post.id);
+        sqlx::query_as!(
+            Translation,
+            r#"
+            SELECT language as lang, slug
+            FROM posts
+            WHERE
+                (id = ? OR translation_of = ?)
+                AND id != ?
+                AND published = TRUE
+            ORDER BY language, id
+            "#,
+            original,
+            original,
+            post.id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)
+        .inspect(|translations| record_rows(translations.len()))
+    }
+
+    /// Checks that post `id` may become a translation of `translation_of`:
+    /// the original must be an original itself, and `id` must not have
+    /// translations of its own, so translations never chain
+    async fn check_translation_of(
+        conn: &mut SqliteConnection,
+        id: i64,
+        translation_of: Option<i64>,
+    ) -> DatabaseResult<()> {
+        let Some(original) = translation_of else {
+            return Ok(());
+        };
+        if original == id {
+            return Err(PostError::InvalidTranslation(
+                "a post can't be a translation of itself".to_string(),
+            )
+            .into());
+        }
+        Self::check_original(conn, original).await?;
+
+        let has_translations = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM posts WHERE translation_of = ?) as "exists!: bool""#,
+            id
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+        if has_translations {
+            return Err(PostError::InvalidTranslation(format!(
+                "post {} has translations of its own, so it can't be a translation",
+                id
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Checks that `original` exists and isn't a translation itself
+    async fn check_original(conn: &mut SqliteConnection, original: i64) -> DatabaseResult<()> {
+        let translates =
+            sqlx::query_scalar!("SELECT translation_of FROM posts WHERE id = ?", original)
+                .fetch_optional(&mut *conn)
+                .await
+                .map_err(DatabaseError::Sqlx)?;
+        match translates {
+            None => Err(
+                PostError::InvalidTranslation(format!("post {} doesn't exist", original)).into(),
+            ),
+            Some(Some(_)) => Err(PostError::InvalidTranslation(format!(
+                "post {} is a translation itself; link to its original instead",
+                original
+            ))
+            .into()),
+            Some(None) => Ok(()),
+        }
+    }
+
+    /// Removes a slug from the history once it is in use by a live post
+    async fn evict_slug_history(conn: &mut SqliteConnection, slug: &str) -> DatabaseResult<()> {
+        sqlx::query!("DELETE FROM post_slugs WHERE slug = ?", slug)
+            .execute(&mut *conn)
+            .await
+            .map_err(DatabaseError::Sqlx)?;
         Ok(())
     }
 }
-use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+use futures_util::{
+    stream::{self, BoxStream},
+    StreamExt, TryStreamExt,
+};
+use sqlx::{SqliteConnection, SqlitePool};
+use time::{Duration, OffsetDateTime, UtcOffset};
+use tracing::{instrument, Instrument, Span};
+
+use crate::models::{
+    audit::{Actor, AuditAction, AuditEvent, Audited},
+    errors::{PostError, ValidationErrors},
+    post::{
+        is_similar_slug, is_supported_language, month_bounds, normalize_slug, slug_distance,
+        validate_content_size, validate_external_url, validate_reading_fields, CategorySummary,
+        CreatePost, DraftContent, PatchPost, Post, PostCategory, PostWithTags, SlugLookup,
+        Translation, UpdatePost, Visibility, DEFAULT_MAX_CONTENT_BYTES,
+    },
+    tag::{Tag, TagRequest},
+};
+
+use super::{
+    error::DatabaseResult,
+    post_cache::{CacheStats, ListKey, PostCache, DEFAULT_CACHE_TTL},
+    query_metrics::record_rows,
+    AuditRepository, DatabaseError, QueryMetrics, TagRepository,
+};
+
+/// WHERE clause of the post listing, binding the category and then the
+/// language when filtering by them. Filters that don't apply are left out
+/// rather than switched off with a bound flag, since SQLite can only pick
+/// an index for a filter it knows is there.
+fn listing_filter(by_category: bool, by_language: bool, visibility: Visibility) -> String {
+    let conditions: Vec<&str> = [
+        (visibility == Visibility::Published, "published = TRUE"),
+        (visibility == Visibility::Draft, "published = FALSE"),
+        (by_category, "category = ?"),
+        (by_language, "language = ?"),
+    ]
+    .into_iter()
+    .filter_map(|(applies, condition)| applies.then_some(condition))
+    .collect();
+    if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    }
+}
+
+/// Rejects listing filters for languages posts can't be written in
+fn validate_language_filter(language: Option<&str>) -> DatabaseResult<()> {
+    match language {
+        Some(language) if !is_supported_language(language) => Err(DatabaseError::invalid_field(
+            "lang",
+            &PostError::UnsupportedLanguage(language.to_string()).to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// A page of the post listing with the total, filtered by `filter` and
+/// bound with the limit and offset after it. Posts from the same instant
+/// are ordered by id, so pages never overlap or leave gaps.
+fn listing_query(filter: &str) -> String {
+    format!(
+        r#"
+        SELECT
+            COUNT(*) OVER () AS total,
+            id, category, title, slug,
+            content, description, image_url, external_url, published,
+            meta_title, meta_description, og_image_url,
+            book_author, isbn, rating, author_id, created_by, updated_by,
+            language, translation_of,
+            published_at, created_at, updated_at
+        FROM posts
+        {}
+        ORDER BY COALESCE(published_at, created_at) DESC, id DESC
+        LIMIT ?
+        OFFSET ?
+        "#,
+        filter
+    )
+}
 
-use crate::models::post::{CreatePost, PatchPost, Post, PostCategory, UpdatePost};
+/// The instant as timestamps are stored: in UTC, with whole seconds
+fn stored_timestamp(instant: OffsetDateTime) -> OffsetDateTime {
+    let instant = instant.to_offset(UtcOffset::UTC);
+    instant - Duration::nanoseconds(i64::from(instant.nanosecond()))
+}
 
-use super::{error::DatabaseResult, DatabaseError};
+/// Validates the limit and offset of a paginated listing
+fn validate_pagination(limit: i64, offset: i64) -> DatabaseResult<()> {
+    if limit <= 0 || limit > 100 {
+        return Err(DatabaseError::invalid_field(
+            "limit",
+            "Limit must be between 1 and 100",
+        ));
+    }
+    if offset < 0 {
+        return Err(DatabaseError::invalid_field(
+            "offset",
+            "Offset cannot be negative",
+        ));
+    }
+    Ok(())
+}
 
 #[cfg(test)]
 mod tests {
-    use crate::db::{test_utils::create_test_db, Database};
+    use crate::{
+        db::{test_utils::create_test_db, Database},
+        models::audit::{AuditEntity, AuditQuery},
+    };
 
     use super::*;
 
     fn create_test_post() -> CreatePost {
-        CreatePost {
-            category: PostCategory::Blog,
-            title: "Test Post".to_string(),
-            slug: "test-post".to_string(),
-            content: "Test content".to_string(),
-            description: "Test description".to_string(),
-            image_url: None,
-            external_url: None,
-            published: true,
-        }
+        CreatePost::builder()
+            .title("Test Post")
+            .slug("test-post")
+            .content("Test content")
+            .description("Test description")
+            .published(true)
+            .build()
+            .unwrap()
+    }
+
+    /// Adds a user with the given id, for changes made acting as them
+    async fn insert_user(db: &Database, id: i64) {
+        sqlx::query("INSERT INTO users (id, email, password_hash) VALUES (?, ?, '')")
+            .bind(id)
+            .bind(format!("user{}@example.com", id))
+            .execute(db.pool())
+            .await
+            .unwrap();
     }
 
     async fn setup() -> (Database, PostRepository) {
@@ -359,7 +1759,7 @@ mod tests {
         let duplicate = repo.create(post_data).await;
         assert!(matches!(
             duplicate.unwrap_err(),
-            DatabaseError::DuplicateEntry(_)
+            DatabaseError::DuplicateEntry { field: Some(field), .. } if field == "slug"
         ));
 
         // Test empty title
@@ -367,7 +1767,7 @@ mod tests {
         invalid.title = "".to_string();
         assert!(matches!(
             repo.create(invalid).await.unwrap_err(),
-            DatabaseError::Validation(_)
+            DatabaseError::Validation { .. }
         ));
     }
 
@@ -388,6 +1788,130 @@ mod tests {
         assert!(matches!(not_found.unwrap_err(), DatabaseError::NotFound(_)));
     }
 
+    #[tokio::test]
+    async fn test_list_by_tag() {
+        let (db, repo) = setup().await;
+        let tag = db.tags().create("rust").await.unwrap();
+        let other = db.tags().create("go").await.unwrap();
+
+        let mut ids = Vec::new();
+        for i in 0..5 {
+            let mut post = create_test_post();
+            post.slug = format!("post-{}", i);
+            post.published = i != 4;
+            let post = repo.create(post).await.unwrap();
+            let tag_id = if i == 3 { other.id } else { tag.id };
+            db.tags().add_tag_to_post(post.id, tag_id).await.unwrap();
+            ids.push(post.id);
+        }
+
+        // Posts created in the same second are ordered by ID, newest first
+        let page: Vec<_> = repo
+            .list_by_tag(tag.id, None, true, 2, 0)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(page, vec![ids[2], ids[1]]);
+
+        let page: Vec<_> = repo
+            .list_by_tag(tag.id, None, true, 2, 2)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(page, vec![ids[0]]);
+
+        // Drafts only appear when asked for
+        let all = repo.list_by_tag(tag.id, None, false, 10, 0).await.unwrap();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[0].id, ids[4]);
+
+        assert!(matches!(
+            repo.list_by_tag(tag.id, None, true, 0, 0)
+                .await
+                .unwrap_err(),
+            DatabaseError::Validation { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_untagged() {
+        let (db, repo) = setup().await;
+        let tag = db.tags().create("rust").await.unwrap();
+
+        let mut ids = Vec::new();
+        for i in 0..4 {
+            let mut post = create_test_post();
+            post.slug = format!("post-{}", i);
+            post.published = i != 3;
+            ids.push(repo.create(post).await.unwrap().id);
+        }
+        db.tags().add_tag_to_post(ids[0], tag.id).await.unwrap();
+
+        // Tagged posts are left out, and drafts only appear when asked for
+        let (posts, total) = repo.list_untagged(true, 10, 0).await.unwrap();
+        let page: Vec<_> = posts.into_iter().map(|p| p.id).collect();
+        assert_eq!(page, vec![ids[2], ids[1]]);
+        assert_eq!(total, 2);
+
+        let (posts, total) = repo.list_untagged(false, 1, 1).await.unwrap();
+        assert_eq!(posts[0].id, ids[2]);
+        assert_eq!(total, 3);
+
+        // A post that loses its last tag shows up right away
+        db.tags()
+            .remove_tag_from_post(ids[0], tag.id)
+            .await
+            .unwrap();
+        let (posts, total) = repo.list_untagged(true, 10, 0).await.unwrap();
+        assert_eq!(posts.last().unwrap().id, ids[0]);
+        assert_eq!(total, 3);
+
+        // ...and disappears again once tagged
+        db.tags().add_tag_to_post(ids[1], tag.id).await.unwrap();
+        let (_, total) = repo.list_untagged(true, 10, 0).await.unwrap();
+        assert_eq!(total, 2);
+    }
+
+    #[tokio::test]
+    async fn test_create_with_tags() {
+        let (db, repo) = setup().await;
+        let existing = db.tags().create("rust").await.unwrap();
+
+        // Existing tags are reused (ignoring case) and new ones are created
+        let mut post_data = create_test_post();
+        post_data.tags = vec!["Rust".to_string(), "axum".to_string()];
+        let created = repo.create_with_tags(post_data, None).await.unwrap();
+        let names: Vec<_> = created.tags.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["axum", "rust"]);
+        assert_eq!(created.tags[1].id, existing.id);
+        assert_eq!(
+            db.tags()
+                .list_tags_for_post(created.post.id)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        // One invalid name aborts everything
+        let mut post_data = create_test_post();
+        post_data.slug = "invalid-tags".to_string();
+        post_data.tags = vec!["sqlx".to_string(), "not/valid".to_string()];
+        assert!(matches!(
+            repo.create_with_tags(post_data, None).await.unwrap_err(),
+            DatabaseError::Validation {
+                field: Some("tags"),
+                ..
+            }
+        ));
+        assert!(repo.find_by_slug("invalid-tags").await.is_err());
+        assert!(db.tags().find_by_name("sqlx").await.is_err());
+    }
+
     #[tokio::test]
     async fn test_find_by_slug() {
         let (_, repo) = setup().await;
@@ -404,6 +1928,232 @@ mod tests {
         assert!(matches!(not_found.unwrap_err(), DatabaseError::NotFound(_)));
     }
 
+    /// A span or event seen by `Recorder`, with its fields formatted
+    #[derive(Debug, Clone)]
+    struct Recorded {
+        /// The span's id; none for events
+        id: Option<u64>,
+        name: String,
+        parent: Option<String>,
+        fields: HashMap<String, String>,
+    }
+
+    /// Collects every span and event, for asserting on what gets traced
+    #[derive(Clone, Default)]
+    struct Recorder(std::sync::Arc<std::sync::Mutex<Vec<Recorded>>>);
+
+    struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+    impl tracing::field::Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    impl<S> tracing_subscriber::Layer<S> for Recorder
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut fields = HashMap::new();
+            attrs.record(&mut FieldVisitor(&mut fields));
+            let parent = ctx
+                .span(id)
+                .and_then(|span| span.parent())
+                .map(|parent| parent.name().to_string());
+            self.0.lock().unwrap().push(Recorded {
+                id: Some(id.into_u64()),
+                name: attrs.metadata().name().to_string(),
+                parent,
+                fields,
+            });
+        }
+
+        fn on_record(
+            &self,
+            id: &tracing::span::Id,
+            values: &tracing::span::Record<'_>,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut recorded = self.0.lock().unwrap();
+            if let Some(span) = recorded.iter_mut().find(|r| r.id == Some(id.into_u64())) {
+                values.record(&mut FieldVisitor(&mut span.fields));
+            }
+        }
+
+        fn on_event(
+            &self,
+            event: &tracing::Event<'_>,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut fields = HashMap::new();
+            event.record(&mut FieldVisitor(&mut fields));
+            self.0.lock().unwrap().push(Recorded {
+                id: None,
+                name: "event".to_string(),
+                parent: ctx.event_span(event).map(|span| span.name().to_string()),
+                fields,
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repository_calls_are_traced() {
+        use tracing::Instrument;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let recorder = Recorder::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+        let (_, repo) = setup().await;
+        repo.create(create_test_post()).await.unwrap();
+
+        repo.find_by_slug("test-post")
+            .instrument(tracing::info_span!("request"))
+            .await
+            .unwrap();
+        repo.find_by_slug("missing").await.unwrap_err();
+
+        let recorded = recorder.0.lock().unwrap().clone();
+        let find = recorded
+            .iter()
+            .find(|span| span.name == "posts.find_by_slug")
+            .expect("a span for find_by_slug");
+        assert_eq!(find.fields["post.slug"], "test-post");
+        assert_eq!(find.parent.as_deref(), Some("request"));
+
+        // Failures are reported as an event inside the span
+        let error = recorded
+            .iter()
+            .find(|event| event.name == "event" && event.fields.contains_key("error"))
+            .expect("an error event");
+        assert_eq!(error.parent.as_deref(), Some("posts.find_by_slug"));
+        assert!(error.fields["error"].contains("missing"), "{:?}", error);
+
+        // Post bodies never make it into the trace
+        assert!(recorded
+            .iter()
+            .flat_map(|span| span.fields.values())
+            .all(|value| !value.contains("Test content")));
+    }
+
+    #[tokio::test]
+    async fn test_each_call_is_traced_once() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let recorder = Recorder::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+        let (db, repo) = setup().await;
+        repo.create(create_test_post()).await.unwrap();
+        repo.list(None, None, Visibility::All, 10, 0).await.unwrap();
+        let streamed: Vec<Post> = repo.stream(false).try_collect().await.unwrap();
+        assert_eq!(streamed.len(), 1);
+
+        // Wrappers leave the span to the call they delegate to
+        let recorded = recorder.0.lock().unwrap().clone();
+        let spans: Vec<&str> = recorded
+            .iter()
+            .filter(|r| r.id.is_some() && r.name.starts_with("posts."))
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                "posts.create_with_tags_in",
+                "posts.list_with_total",
+                "posts.stream"
+            ]
+        );
+        let list = recorded
+            .iter()
+            .find(|r| r.name == "posts.list_with_total")
+            .unwrap();
+        assert_eq!(list.fields["rows"], "1");
+        let stream = recorded.iter().find(|r| r.name == "posts.stream").unwrap();
+        assert_eq!(stream.fields["rows"], "1");
+
+        let timings = db.query_metrics().snapshot();
+        for method in ["posts.create", "posts.list", "posts.stream"] {
+            assert_eq!(timings[method].count, 1, "{}", method);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_similar_slugs() {
+        let (_, repo) = setup().await;
+        for (slug, published) in [
+            ("rust-async-intro", true),
+            ("rust-async-outro", true),
+            ("rust-basics", true),
+            ("rust-async-intro-draft", false),
+        ] {
+            repo.create(CreatePost {
+                slug: slug.to_string(),
+                published,
+                ..create_test_post()
+            })
+            .await
+            .unwrap();
+        }
+
+        // Near misses, closest first; drafts are never suggested
+        assert_eq!(
+            repo.similar_slugs("Rust-Asnyc-Intro", 3).await.unwrap(),
+            ["rust-async-intro", "rust-async-outro"]
+        );
+        assert_eq!(
+            repo.similar_slugs("rust-async-intr", 1).await.unwrap(),
+            ["rust-async-intro"]
+        );
+        // Far misses get no suggestions
+        assert!(repo.similar_slugs("cooking", 3).await.unwrap().is_empty());
+        assert!(repo.similar_slugs("r", 3).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_slugs_are_case_insensitive() {
+        let (_, repo) = setup().await;
+
+        // Mixed-case slugs are stored in lowercase
+        let mut post_data = create_test_post();
+        post_data.slug = "My-Post".to_string();
+        let post = repo.create(post_data.clone()).await.unwrap();
+        assert_eq!(post.slug, "my-post");
+
+        // Lookups ignore case
+        let found = repo.find_by_slug("MY-POST").await.unwrap();
+        assert_eq!(found.id, post.id);
+
+        // Case variants of an existing slug are duplicates
+        post_data.slug = "MY-post".to_string();
+        assert!(matches!(
+            repo.create(post_data).await.unwrap_err(),
+            DatabaseError::DuplicateEntry { .. }
+        ));
+
+        // Patches are normalized too
+        let (patched, _) = repo
+            .patch(PatchPost {
+                id: post.id,
+                slug: Some("Renamed-Post".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(patched.slug, "renamed-post");
+        assert!(matches!(
+            repo.resolve_slug("My-Post").await.unwrap(),
+            SlugLookup::Moved(slug) if slug == "renamed-post"
+        ));
+    }
+
     #[tokio::test]
     async fn test_list_posts() {
         let (_, repo) = setup().await;
@@ -420,85 +2170,836 @@ mod tests {
         repo.create(post2).await.unwrap();
 
         // Test listing all posts
-        let all_posts = repo.list(None, false, 10, 0).await.unwrap();
+        let all_posts = repo.list(None, None, Visibility::All, 10, 0).await.unwrap();
         assert_eq!(all_posts.len(), 2);
 
         // Test category filter
         let blog_posts = repo
-            .list(Some(PostCategory::Blog), false, 10, 0)
+            .list(Some(PostCategory::Blog), None, Visibility::All, 10, 0)
             .await
             .unwrap();
         assert_eq!(blog_posts.len(), 1);
 
         // Test published filter
-        let published = repo.list(None, true, 10, 0).await.unwrap();
+        let published = repo
+            .list(None, None, Visibility::Published, 10, 0)
+            .await
+            .unwrap();
         assert_eq!(published.len(), 1);
+        assert!(published[0].published);
+
+        let drafts = repo
+            .list(None, None, Visibility::Draft, 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts[0].slug, "post-2");
 
         // Test pagination
-        let paginated = repo.list(None, false, 1, 1).await.unwrap();
+        let paginated = repo.list(None, None, Visibility::All, 1, 1).await.unwrap();
         assert_eq!(paginated.len(), 1);
 
         // Test invalid pagination
-        assert!(repo.list(None, false, 0, 0).await.is_err());
-        assert!(repo.list(None, false, 10, -1).await.is_err());
+        assert!(repo.list(None, None, Visibility::All, 0, 0).await.is_err());
+        assert!(repo
+            .list(None, None, Visibility::All, 10, -1)
+            .await
+            .is_err());
     }
 
     #[tokio::test]
-    async fn test_update_post() {
-        let (_, repo) = setup().await;
+    async fn test_slow_queries_are_logged() {
+        /// Collects everything written to it
+        #[derive(Clone, Default)]
+        struct Sink(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+        impl std::io::Write for Sink {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
 
-        // Create initial post
-        let created = repo.create(create_test_post()).await.unwrap();
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
 
-        // Test successful update
-        let update = UpdatePost {
-            id: created.id,
-            category: PostCategory::Art,
-            title: "Updated Title".to_string(),
-            slug: "updated-slug".to_string(),
-            content: "Updated content".to_string(),
-            description: "Updated description".to_string(),
-            image_url: Some("https://example.com/image.jpg".to_string()),
-            external_url: Some("https://example.com".to_string()),
-            published: false,
+        let config = crate::config::Config {
+            slow_query_threshold: std::time::Duration::ZERO,
+            ..crate::config::Config::default_for_tests()
         };
+        let db = Database::new(&config).await.unwrap();
+        let sink = Sink::default();
+        let writer = sink.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        db.posts()
+            .create(CreatePost {
+                content: "Top secret draft".to_string(),
+                ..create_test_post()
+            })
+            .await
+            .unwrap();
+        db.posts()
+            .list(None, None, Visibility::Published, 10, 0)
+            .await
+            .unwrap();
 
-        let updated = repo.update(update.clone()).await.unwrap();
-        assert_eq!(updated.title, "Updated Title");
-        assert_eq!(updated.slug, "updated-slug");
-        assert_eq!(updated.category, PostCategory::Art);
-        assert!(!updated.published);
+        let output = String::from_utf8(sink.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("Slow query"), "{}", output);
+        assert!(output.contains(r#"method="posts.create""#), "{}", output);
+        assert!(
+            output.contains(
+                r#"method="posts.list" params="category=None language=None visibility=published limit=10 offset=0""#
+            ),
+            "{}",
+            output
+        );
+        assert!(!output.contains("Top secret"), "{}", output);
+
+        let timings = db.query_metrics().snapshot();
+        assert_eq!(timings["posts.create"].count, 1);
+        assert_eq!(timings["posts.list"].count, 1);
+    }
 
-        // Test non-existent ID
-        let mut invalid = update;
-        invalid.id = 999;
-        assert!(matches!(
-            repo.update(invalid).await.unwrap_err(),
-            DatabaseError::NotFound(_)
-        ));
+    /// Details of the steps SQLite plans for `sql`
+    async fn query_plan(db: &Database, sql: &str, binds: &[&str]) -> Vec<String> {
+        let sql = format!("EXPLAIN QUERY PLAN {}", sql);
+        let mut query = sqlx::query_as::<_, (i64, i64, i64, String)>(&sql);
+        for bind in binds {
+            query = query.bind(*bind);
+        }
+        query
+            .fetch_all(db.pool())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(_, _, _, detail)| detail)
+            .collect()
     }
 
     #[tokio::test]
-    async fn test_patch_post() {
-        let (_, repo) = setup().await;
+    async fn test_listing_uses_indexes() {
+        let db = create_test_db().await.unwrap();
 
-        // Create initial post
-        let created = repo.create(create_test_post()).await.unwrap();
+        let plan = query_plan(
+            &db,
+            &listing_query(&listing_filter(true, false, Visibility::Published)),
+            &["blog", "10", "0"],
+        )
+        .await;
+        assert!(
+            plan.iter().any(|step| step
+                .starts_with("SEARCH posts USING INDEX idx_posts_published_category_date")),
+            "{:?}",
+            plan
+        );
+        assert!(
+            !plan.iter().any(|step| step.starts_with("SCAN posts")),
+            "{:?}",
+            plan
+        );
+
+        let plan = query_plan(
+            &db,
+            &listing_query(&listing_filter(false, true, Visibility::Published)),
+            &["de", "10", "0"],
+        )
+        .await;
+        assert!(
+            plan.iter().any(|step| step
+                .starts_with("SEARCH posts USING INDEX idx_posts_language_published_date")),
+            "{:?}",
+            plan
+        );
+
+        let plan = query_plan(
+            &db,
+            r#"
+            SELECT p.id
+            FROM posts p
+            JOIN post_tags pt ON pt.post_id = p.id
+            WHERE pt.tag_id = ?
+            ORDER BY COALESCE(p.published_at, p.created_at) DESC, p.id DESC
+            "#,
+            &["1"],
+        )
+        .await;
+        assert!(
+            plan.iter().any(|step| step
+                .starts_with("SEARCH pt USING COVERING INDEX idx_post_tags_tag_post (tag_id=?)")),
+            "{:?}",
+            plan
+        );
+        assert!(
+            !plan.iter().any(|step| step.starts_with("SCAN")),
+            "{:?}",
+            plan
+        );
+    }
 
-        // Test partial update with only title
-        let patch = PatchPost {
-            id: created.id,
-            title: Some("Patched Title".to_string()),
-            category: None,
-            slug: None,
-            content: None,
-            description: None,
-            image_url: None,
-            external_url: None,
+    #[tokio::test]
+    async fn test_listing_pages_are_stable() {
+        let (db, repo) = setup().await;
+        // A bulk import gives every post the same timestamp
+        for i in 0..30 {
+            sqlx::query(
+                "INSERT INTO posts (category, title, slug, content, description, published, created_at)
+                 VALUES ('blog', 'Imported', ?, 'Content', 'Description', TRUE, '2024-05-01 12:00:00')",
+            )
+            .bind(format!("imported-{}", i))
+            .execute(db.pool())
+            .await
+            .unwrap();
+        }
+
+        for visibility in [Visibility::All, Visibility::Published] {
+            let mut ids = Vec::new();
+            for offset in (0..30).step_by(7) {
+                let page = repo.list(None, None, visibility, 7, offset).await.unwrap();
+                ids.extend(page.into_iter().map(|p| p.id));
+            }
+            // Newest id first, each post exactly once
+            let mut expected = ids.clone();
+            expected.sort_unstable_by(|a, b| b.cmp(a));
+            expected.dedup();
+            assert_eq!(ids, expected);
+            assert_eq!(ids.len(), 30);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_with_total() {
+        let (_, repo) = setup().await;
+        for i in 0..150 {
+            let mut post = create_test_post();
+            post.slug = format!("post-{i}");
+            post.published = i % 3 != 0;
+            repo.create(post).await.unwrap();
+        }
+
+        let mut seen = 0;
+        for offset in (0..150).step_by(40) {
+            let (posts, total) = repo
+                .list_with_total(None, None, Visibility::All, 40, offset)
+                .await
+                .unwrap();
+            assert_eq!(total, 150);
+            seen += posts.len();
+        }
+        assert_eq!(seen, 150);
+
+        let (posts, total) = repo
+            .list_with_total(None, None, Visibility::Published, 100, 50)
+            .await
+            .unwrap();
+        assert_eq!((posts.len(), total), (50, 100));
+
+        // A page past the end still reports the total
+        let (posts, total) = repo
+            .list_with_total(None, None, Visibility::All, 10, 500)
+            .await
+            .unwrap();
+        assert_eq!((posts.len(), total), (0, 150));
+        let (posts, total) = repo
+            .list_with_total(Some(PostCategory::Art), None, Visibility::All, 10, 0)
+            .await
+            .unwrap();
+        assert_eq!((posts.len(), total), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_translations() {
+        let (_, repo) = setup().await;
+        let original = repo.create(create_test_post()).await.unwrap();
+        assert_eq!(original.language, "en");
+        let german = repo
+            .create(CreatePost {
+                slug: "test-beitrag".to_string(),
+                language: "de".to_string(),
+                translation_of: Some(original.id),
+                published_at: None,
+                ..create_test_post()
+            })
+            .await
+            .unwrap();
+        let french = repo
+            .create(CreatePost {
+                slug: "article-test".to_string(),
+                language: "fr".to_string(),
+                translation_of: Some(original.id),
+                published_at: None,
+                published: false,
+                ..create_test_post()
+            })
+            .await
+            .unwrap();
+
+        // Every version lists the others that are published
+        let translation = |lang: &str, slug: &str| Translation {
+            lang: lang.to_string(),
+            slug: slug.to_string(),
+        };
+        assert_eq!(
+            repo.translations(&original).await.unwrap(),
+            [translation("de", "test-beitrag")]
+        );
+        assert_eq!(
+            repo.translations(&german).await.unwrap(),
+            [translation("en", "test-post")]
+        );
+        assert_eq!(
+            repo.translations(&french).await.unwrap(),
+            [
+                translation("de", "test-beitrag"),
+                translation("en", "test-post")
+            ]
+        );
+
+        // Only the language's posts are listed
+        let (posts, total) = repo
+            .list_with_total(None, Some("de"), Visibility::All, 10, 0)
+            .await
+            .unwrap();
+        assert_eq!((posts[0].id, total), (german.id, 1));
+        assert!(matches!(
+            repo.list(None, Some("xx"), Visibility::All, 10, 0)
+                .await
+                .unwrap_err(),
+            DatabaseError::Validation {
+                field: Some("lang"),
+                ..
+            }
+        ));
+
+        // Translations point at originals, never at each other
+        let invalid = |translation_of: i64| CreatePost {
+            slug: "invalid".to_string(),
+            translation_of: Some(translation_of),
+            published_at: None,
+            ..create_test_post()
+        };
+        for translation_of in [german.id, 999] {
+            assert!(matches!(
+                repo.create(invalid(translation_of)).await.unwrap_err(),
+                DatabaseError::Validation {
+                    field: Some("translation_of"),
+                    ..
+                }
+            ));
+        }
+        let patch = |id: i64, translation_of: Option<i64>| PatchPost {
+            id,
+            translation_of: Some(translation_of),
+            published_at: None,
+            ..Default::default()
+        };
+        assert!(matches!(
+            repo.patch(patch(original.id, Some(german.id)))
+                .await
+                .unwrap_err(),
+            DatabaseError::Validation {
+                field: Some("translation_of"),
+                ..
+            }
+        ));
+
+        // Unlinking leaves the post on its own, as does deleting the original
+        let (unlinked, _) = repo.patch(patch(french.id, None)).await.unwrap();
+        assert_eq!(unlinked.translation_of, None);
+        repo.delete(original.id).await.unwrap();
+        let german = repo.find_by_id(german.id).await.unwrap();
+        assert_eq!(german.translation_of, None);
+        assert!(repo.translations(&german).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_count_by_category() {
+        let (_, repo) = setup().await;
+
+        let posts = [
+            ("blog-one", PostCategory::Blog, true),
+            ("blog-two", PostCategory::Blog, true),
+            ("blog-draft", PostCategory::Blog, false),
+            ("reading-one", PostCategory::Reading, true),
+        ];
+        for (slug, category, published) in posts {
+            let mut post = create_test_post();
+            post.slug = slug.to_string();
+            post.published = published;
+            if category == PostCategory::Reading {
+                post.external_url = Some("https://example.com/book".to_string());
+            }
+            post.category = category;
+            repo.create(post).await.unwrap();
+        }
+
+        let counts = repo.count_by_category().await.unwrap();
+        let categories: Vec<_> = counts.iter().map(|c| c.category.clone()).collect();
+        assert_eq!(categories, PostCategory::all());
+        for category in PostCategory::all() {
+            assert_eq!(
+                counts.iter().filter(|c| c.category == category).count(),
+                1,
+                "{}",
+                category
+            );
+        }
+        let published: Vec<_> = counts
+            .iter()
+            .map(|c| (c.label, c.published_posts))
+            .collect();
+        assert_eq!(published, [("Blog", 2), ("Art", 0), ("Reading", 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_list_by_month() {
+        let (db, repo) = setup().await;
+
+        let created = [
+            ("feb-first", "2024-02-01 00:00:00"),
+            ("leap-day", "2024-02-29 23:59:59"),
+            ("march-first", "2024-03-01 00:00:00"),
+            ("new-years-eve", "2024-12-31 23:59:59"),
+            ("new-year", "2025-01-01 00:00:00"),
+            ("feb-draft", "2024-02-15 12:00:00"),
+        ];
+        for (slug, created_at) in created {
+            let mut post = create_test_post();
+            post.slug = slug.to_string();
+            post.published = slug != "feb-draft";
+            repo.create(post).await.unwrap();
+            sqlx::query("UPDATE posts SET created_at = ? WHERE slug = ?")
+                .bind(created_at)
+                .bind(slug)
+                .execute(db.pool())
+                .await
+                .unwrap();
+        }
+        let slugs = |posts: Vec<Post>| posts.into_iter().map(|p| p.slug).collect::<Vec<_>>();
+
+        // Oldest first, drafts left out, up to the last second of leap day
+        let february = repo.list_by_month(2024, 2, 10, 0).await.unwrap();
+        assert_eq!(slugs(february), ["feb-first", "leap-day"]);
+
+        let march = repo.list_by_month(2024, 3, 10, 0).await.unwrap();
+        assert_eq!(slugs(march), ["march-first"]);
+
+        // December ends where the next year begins
+        let december = repo.list_by_month(2024, 12, 10, 0).await.unwrap();
+        assert_eq!(slugs(december), ["new-years-eve"]);
+        let january = repo.list_by_month(2025, 1, 10, 0).await.unwrap();
+        assert_eq!(slugs(january), ["new-year"]);
+
+        assert!(repo.list_by_month(2023, 2, 10, 0).await.unwrap().is_empty());
+
+        let second = repo.list_by_month(2024, 2, 1, 1).await.unwrap();
+        assert_eq!(slugs(second), ["leap-day"]);
+
+        assert!(matches!(
+            repo.list_by_month(2024, 13, 10, 0).await.unwrap_err(),
+            DatabaseError::Validation {
+                field: Some("month"),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_modified_since() {
+        let (_, repo) = setup().await;
+
+        let mut draft = create_test_post();
+        draft.slug = "draft-post".to_string();
+        draft.published = false;
+
+        repo.create(create_test_post()).await.unwrap();
+        repo.create(draft).await.unwrap();
+
+        // Everything was modified after the epoch
+        let all = repo
+            .list_modified_since(OffsetDateTime::UNIX_EPOCH, false, 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+
+        // Drafts can be excluded
+        let published = repo
+            .list_modified_since(OffsetDateTime::UNIX_EPOCH, true, 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].slug, "test-post");
+
+        // Nothing was modified in the future
+        let future = OffsetDateTime::now_utc() + Duration::hours(1);
+        let none = repo
+            .list_modified_since(future, false, 10, 0)
+            .await
+            .unwrap();
+        assert!(none.is_empty());
+
+        // Test pagination
+        let paginated = repo
+            .list_modified_since(OffsetDateTime::UNIX_EPOCH, false, 1, 1)
+            .await
+            .unwrap();
+        assert_eq!(paginated.len(), 1);
+
+        // Test invalid pagination
+        assert!(repo
+            .list_modified_since(OffsetDateTime::UNIX_EPOCH, false, 101, 0)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_update_post() {
+        let (_, repo) = setup().await;
+
+        // Create initial post
+        let created = repo.create(create_test_post()).await.unwrap();
+
+        // Test successful update
+        let update = UpdatePost::builder(created.id)
+            .category(PostCategory::Art)
+            .title("Updated Title")
+            .slug("updated-slug")
+            .content("Updated content")
+            .description("Updated description")
+            .image_url("https://example.com/image.jpg")
+            .external_url("https://example.com")
+            .build()
+            .unwrap();
+
+        let (updated, _) = repo.update(update.clone()).await.unwrap();
+        assert_eq!(updated.title, "Updated Title");
+        assert_eq!(updated.slug, "updated-slug");
+        assert_eq!(updated.category, PostCategory::Art);
+        assert!(!updated.published);
+
+        // Test non-existent ID
+        let mut invalid = update;
+        invalid.id = 999;
+        assert!(matches!(
+            repo.update(invalid).await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_published_at() {
+        let (db, repo) = setup().await;
+        let backdate = |id: i64| {
+            let pool = db.pool().clone();
+            async move {
+                sqlx::query(
+                    "UPDATE posts SET published_at = '2024-01-01 00:00:00',
+                     created_at = '2023-06-01 00:00:00' WHERE id = ?",
+                )
+                .bind(id)
+                .execute(&pool)
+                .await
+                .unwrap();
+            }
+        };
+        let first_published = time::macros::datetime!(2024-01-01 00:00 UTC);
+
+        let draft = repo
+            .create(CreatePost {
+                published: false,
+                ..create_test_post()
+            })
+            .await
+            .unwrap();
+        assert!(draft.published_at.is_none());
+
+        // Going live for the first time sets it
+        let (live, _) = repo
+            .patch(PatchPost {
+                id: draft.id,
+                published: Some(true),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(live.published_at.is_some());
+
+        // Unpublishing and republishing keep the first publication
+        backdate(draft.id).await;
+        let (hidden, _) = repo
+            .patch(PatchPost {
+                id: draft.id,
+                published: Some(false),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(hidden.published_at, Some(first_published));
+        let update = UpdatePost::builder(draft.id)
+            .title("Test Post")
+            .slug("test-post")
+            .content("Test content")
+            .description("Test description")
+            .published(true)
+            .build()
+            .unwrap();
+        let (republished, _) = repo.update(update.clone()).await.unwrap();
+        assert_eq!(republished.published_at, Some(first_published));
+
+        // Unless a time is given, which is stored in UTC to the second
+        let (moved, changed) = repo
+            .update(UpdatePost {
+                published_at: Some(time::macros::datetime!(2024-03-01 12:00:00.5 +02:00)),
+                ..update
+            })
+            .await
+            .unwrap();
+        assert!(changed);
+        assert_eq!(
+            moved.published_at,
+            Some(time::macros::datetime!(2024-03-01 10:00 UTC))
+        );
+
+        // Posts created published are published now, or when they say
+        let published = repo
+            .create(CreatePost {
+                slug: "published".to_string(),
+                ..create_test_post()
+            })
+            .await
+            .unwrap();
+        assert!(published.published_at.is_some());
+        let long_ago = time::macros::datetime!(2020-01-01 00:00 UTC);
+        let imported = repo
+            .create(CreatePost {
+                slug: "imported".to_string(),
+                published_at: Some(long_ago),
+                ..create_test_post()
+            })
+            .await
+            .unwrap();
+        assert_eq!(imported.published_at, Some(long_ago));
+
+        // Published listings go by publication date: the post drafted
+        // first but published last comes first
+        backdate(published.id).await;
+        sqlx::query("UPDATE posts SET published_at = '2025-01-01 00:00:00' WHERE id = ?")
+            .bind(draft.id)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let listed: Vec<_> = repo
+            .list(None, None, Visibility::Published, 10, 0)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|post| post.slug)
+            .collect();
+        assert_eq!(listed, ["test-post", "published", "imported"]);
+    }
+
+    #[tokio::test]
+    async fn test_no_op_changes_are_skipped() {
+        let (db, repo) = setup().await;
+        let created = repo.create(create_test_post()).await.unwrap();
+        // Backdate the post so a rewrite would show in updated_at
+        sqlx::query("UPDATE posts SET updated_at = '2024-01-01 00:00:00' WHERE id = ?")
+            .bind(created.id)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let current = repo.find_by_id(created.id).await.unwrap();
+        let audited = || async {
+            db.audit()
+                .list(&AuditQuery {
+                    entity_type: Some(AuditEntity::Post),
+                    entity_id: Some(created.id),
+                    limit: 50,
+                    offset: 0,
+                })
+                .await
+                .unwrap()
+                .1
+        };
+
+        // Sending the same values back, with an empty string for an unset URL
+        let same = UpdatePost {
+            // The repository lowercases slugs too
+            slug: "Test-Post".to_string(),
+            ..UpdatePost::builder(created.id)
+                .title("Test Post")
+                .slug("test-post")
+                .content("Test content")
+                .description("Test description")
+                .image_url("")
+                .published(true)
+                .build()
+                .unwrap()
+        };
+        let (post, changed) = repo.update(same.clone()).await.unwrap();
+        assert!(!changed);
+        assert_eq!(post.updated_at, current.updated_at);
+        assert!(post.image_url.is_none());
+        let (post, changed) = repo
+            .patch(PatchPost {
+                id: created.id,
+                title: Some("Test Post".to_string()),
+                external_url: Some(Some(String::new())),
+                published: Some(true),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(!changed);
+        assert_eq!(post.updated_at, current.updated_at);
+        assert_eq!(audited().await, 1);
+
+        // A real change is written, bumping updated_at
+        let (post, changed) = repo
+            .update(UpdatePost {
+                content: "New content".to_string(),
+                ..same
+            })
+            .await
+            .unwrap();
+        assert!(changed);
+        assert!(post.updated_at > current.updated_at);
+        let (post, changed) = repo
+            .patch(PatchPost {
+                id: created.id,
+                external_url: Some(Some("https://example.com".to_string())),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(changed);
+        assert_eq!(post.external_url.as_deref(), Some("https://example.com"));
+        assert_eq!(audited().await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_save_draft() {
+        let (db, repo) = setup().await;
+        let draft = repo
+            .create(CreatePost {
+                published: false,
+                ..create_test_post()
+            })
+            .await
+            .unwrap();
+        sqlx::query("UPDATE posts SET updated_at = '2024-01-01 00:00:00' WHERE id = ?")
+            .bind(draft.id)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let current = repo.find_by_id(draft.id).await.unwrap();
+
+        // Empty and half written text that PATCH would reject is saved
+        let saved = repo
+            .save_draft(
+                draft.id,
+                DraftContent {
+                    title: Some(String::new()),
+                    content: Some("   ".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(saved.title, "");
+        assert_eq!(saved.content, "   ");
+        assert_eq!(saved.description, "Test description");
+        assert_eq!(saved.slug, "test-post");
+        assert!(!saved.published);
+        assert!(saved.updated_at > current.updated_at);
+
+        // Saving the same text again still counts as activity
+        sqlx::query("UPDATE posts SET updated_at = '2024-01-01 00:00:00' WHERE id = ?")
+            .bind(draft.id)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let again = repo
+            .save_draft(draft.id, DraftContent::default())
+            .await
+            .unwrap();
+        assert!(again.updated_at > current.updated_at);
+
+        // The content size limit still applies
+        let err = repo
+            .clone()
+            .with_max_content_bytes(4)
+            .save_draft(
+                draft.id,
+                DraftContent {
+                    content: Some("12345".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DatabaseError::TooLarge { .. }), "{:?}", err);
+
+        let missing = repo.save_draft(999, DraftContent::default()).await;
+        assert!(matches!(missing, Err(DatabaseError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_save_draft_rejects_published_posts() {
+        let (_, repo) = setup().await;
+        let published = repo.create(create_test_post()).await.unwrap();
+
+        let err = repo
+            .save_draft(
+                published.id,
+                DraftContent {
+                    content: Some("Rewritten".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DatabaseError::Conflict(_)), "{:?}", err);
+        let unchanged = repo.find_by_id(published.id).await.unwrap();
+        assert_eq!(unchanged.content, "Test content");
+        assert_eq!(unchanged.updated_at, published.updated_at);
+    }
+
+    #[tokio::test]
+    async fn test_patch_post() {
+        let (_, repo) = setup().await;
+
+        // Create initial post
+        let created = repo.create(create_test_post()).await.unwrap();
+
+        // Test partial update with only title
+        let patch = PatchPost {
+            id: created.id,
+            title: Some("Patched Title".to_string()),
+            category: None,
+            slug: None,
+            content: None,
+            description: None,
+            image_url: None,
+            external_url: None,
             published: None,
+            meta_title: None,
+            meta_description: None,
+            og_image_url: None,
+            book_author: None,
+            isbn: None,
+            rating: None,
+            language: None,
+            translation_of: None,
+            published_at: None,
         };
 
-        let patched = repo.patch(patch).await.unwrap();
+        let (patched, _) = repo.patch(patch).await.unwrap();
         assert_eq!(patched.title, "Patched Title");
         // Other fields should remain unchanged
         assert_eq!(patched.slug, "test-post");
@@ -513,7 +3014,7 @@ mod tests {
             ..Default::default()
         };
 
-        let multi_patched = repo.patch(multi_patch).await.unwrap();
+        let (multi_patched, _) = repo.patch(multi_patch).await.unwrap();
         assert_eq!(multi_patched.category, PostCategory::Art);
         assert!(!multi_patched.published);
         // Unpatched fields should remain unchanged
@@ -531,6 +3032,371 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn test_patch_post_clears_fields() {
+        let (_, repo) = setup().await;
+        let created = repo.create(create_test_post()).await.unwrap();
+
+        let (with_image, _) = repo
+            .patch(PatchPost {
+                id: created.id,
+                image_url: Some(Some("https://example.com/image.jpg".to_string())),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(with_image.image_url.is_some());
+
+        let (cleared, _) = repo
+            .patch(PatchPost {
+                id: created.id,
+                image_url: Some(None),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(cleared.image_url.is_none());
+        // Fields left out of the patch are kept
+        assert_eq!(cleared.title, created.title);
+    }
+
+    #[tokio::test]
+    async fn test_patch_rejects_blank_title_and_content() {
+        let (_, repo) = setup().await;
+        let created = repo.create(create_test_post()).await.unwrap();
+
+        let err = repo
+            .patch(PatchPost {
+                id: created.id,
+                title: Some("   ".to_string()),
+                content: Some(String::new()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        let DatabaseError::Validation { errors, .. } = err else {
+            panic!("expected a validation error, got {:?}", err);
+        };
+        let messages: Vec<_> = errors.iter().map(|e| e.message.clone()).collect();
+        assert_eq!(
+            messages,
+            [
+                PostError::EmptyTitle.to_string(),
+                PostError::EmptyContent.to_string()
+            ]
+        );
+
+        let unchanged = repo.find_by_id(created.id).await.unwrap();
+        assert_eq!(unchanged.title, created.title);
+        assert_eq!(unchanged.content, created.content);
+    }
+
+    #[tokio::test]
+    async fn test_slug_history() {
+        let (_, repo) = setup().await;
+
+        let mut post = create_test_post();
+        post.slug = "a".to_string();
+        let created = repo.create(post).await.unwrap();
+
+        // Rename a -> b via update, then b -> c via patch
+        let update = UpdatePost::builder(created.id)
+            .category(created.category.clone())
+            .title(&created.title)
+            .slug("b")
+            .content(&created.content)
+            .description(&created.description)
+            .published(true)
+            .build()
+            .unwrap();
+        repo.update(update).await.unwrap();
+        repo.patch(PatchPost {
+            id: created.id,
+            slug: Some("c".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        // Both old slugs redirect to the current one
+        for old_slug in ["a", "b"] {
+            match repo.resolve_slug(old_slug).await.unwrap() {
+                SlugLookup::Moved(slug) => assert_eq!(slug, "c"),
+                SlugLookup::Found(_) => panic!("expected a redirect for {}", old_slug),
+            }
+        }
+        assert!(matches!(
+            repo.resolve_slug("c").await.unwrap(),
+            SlugLookup::Found(_)
+        ));
+
+        // Old slugs no longer resolve directly
+        assert!(matches!(
+            repo.find_by_slug("a").await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+
+        // Creating a post with a historical slug evicts the history entry
+        let mut reused = create_test_post();
+        reused.slug = "a".to_string();
+        let reused = repo.create(reused).await.unwrap();
+        match repo.resolve_slug("a").await.unwrap() {
+            SlugLookup::Found(post) => assert_eq!(post.id, reused.id),
+            SlugLookup::Moved(_) => panic!("expected the new post"),
+        }
+
+        // Renaming back to a historical slug makes it live again
+        repo.patch(PatchPost {
+            id: created.id,
+            slug: Some("b".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        match repo.resolve_slug("c").await.unwrap() {
+            SlugLookup::Moved(slug) => assert_eq!(slug, "b"),
+            SlugLookup::Found(_) => panic!("expected a redirect for c"),
+        }
+
+        // Unknown slugs are still not found
+        assert!(matches!(
+            repo.resolve_slug("nonexistent").await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_seo_metadata() {
+        let (_, repo) = setup().await;
+
+        let mut post = create_test_post();
+        post.meta_title = Some("SEO Title".to_string());
+        post.og_image_url = Some("https://example.com/og.png".to_string());
+        let created = repo.create(post).await.unwrap();
+        assert_eq!(created.meta_title.as_deref(), Some("SEO Title"));
+        assert!(created.meta_description.is_none());
+
+        // Clearing the override stores NULL so responses fall back to the title
+        let (patched, _) = repo
+            .patch(PatchPost {
+                id: created.id,
+                meta_title: Some(None),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(patched.meta_title.is_none());
+        assert_eq!(
+            patched.og_image_url.as_deref(),
+            Some("https://example.com/og.png")
+        );
+        let json = serde_json::to_value(&patched).unwrap();
+        assert_eq!(json["meta_title"], "Test Post");
+
+        // Invalid values are rejected on patch
+        assert!(matches!(
+            repo.patch(PatchPost {
+                id: created.id,
+                og_image_url: Some(Some("not-a-url".to_string())),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err(),
+            DatabaseError::Validation { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_reading_fields() {
+        let (_, repo) = setup().await;
+
+        let mut reading = create_test_post();
+        reading.category = PostCategory::Reading;
+        reading.book_author = Some("Ursula K. Le Guin".to_string());
+        reading.isbn = Some("9780306406157".to_string());
+        reading.rating = Some(4);
+        reading.external_url = Some("https://example.com/books/earthsea".to_string());
+        let created = repo.create(reading).await.unwrap();
+        assert_eq!(created.book_author.as_deref(), Some("Ursula K. Le Guin"));
+        assert_eq!(created.rating, Some(4));
+
+        // Patching a Reading post keeps its book details
+        let (patched, _) = repo
+            .patch(PatchPost {
+                id: created.id,
+                rating: Some(Some(5)),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(patched.rating, Some(5));
+        assert_eq!(patched.isbn.as_deref(), Some("9780306406157"));
+
+        // Book fields cannot be set while switching away from Reading
+        assert!(matches!(
+            repo.patch(PatchPost {
+                id: created.id,
+                category: Some(PostCategory::Blog),
+                rating: Some(Some(3)),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err(),
+            DatabaseError::Validation { .. }
+        ));
+
+        // Switching the category away from Reading clears the orphaned fields
+        let (switched, _) = repo
+            .patch(PatchPost {
+                id: created.id,
+                category: Some(PostCategory::Blog),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(switched.category, PostCategory::Blog);
+        assert!(switched.book_author.is_none());
+        assert!(switched.isbn.is_none());
+        assert!(switched.rating.is_none());
+
+        // Book fields are rejected on non-Reading posts
+        let mut blog = create_test_post();
+        blog.slug = "blog-post".to_string();
+        blog.rating = Some(3);
+        assert!(matches!(
+            repo.create(blog).await.unwrap_err(),
+            DatabaseError::Validation { .. }
+        ));
+
+        // A post without an external_url cannot become a Reading post
+        let mut blog = create_test_post();
+        blog.slug = "soon-reading".to_string();
+        let blog = repo.create(blog).await.unwrap();
+        let err = repo
+            .patch(PatchPost {
+                id: blog.id,
+                category: Some(PostCategory::Reading),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            DatabaseError::Validation {
+                field: Some("external_url"),
+                ..
+            }
+        ));
+        let (switched, _) = repo
+            .patch(PatchPost {
+                id: blog.id,
+                category: Some(PostCategory::Reading),
+                external_url: Some(Some("https://example.com/book".to_string())),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(switched.category, PostCategory::Reading);
+
+        // Nor can a Reading post drop its external_url
+        assert!(matches!(
+            repo.patch(PatchPost {
+                id: blog.id,
+                external_url: Some(None),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err(),
+            DatabaseError::Validation { .. }
+        ));
+
+        // Rules on the patch and on the merged post are reported together
+        let err = repo
+            .patch(PatchPost {
+                id: blog.id,
+                slug: Some("not a slug".to_string()),
+                rating: Some(Some(9)),
+                external_url: Some(None),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        let DatabaseError::Validation { errors, .. } = err else {
+            panic!("expected a validation error, got {:?}", err);
+        };
+        let fields: Vec<_> = errors.iter().map(|e| e.field).collect();
+        assert_eq!(fields, [Some("slug"), Some("rating"), Some("external_url")]);
+    }
+
+    #[tokio::test]
+    async fn test_content_size_limit() {
+        let (_, repo) = setup().await;
+        let repo = repo.with_max_content_bytes(8);
+
+        // The limit counts bytes, not characters
+        let mut post = create_test_post();
+        post.content = "ééé12".to_string();
+        assert_eq!(post.content.len(), 8);
+        let created = repo.create(post.clone()).await.unwrap();
+
+        let mut too_large = post;
+        too_large.slug = "too-large".to_string();
+        too_large.content = "ééé123".to_string();
+        match repo.create(too_large).await.unwrap_err() {
+            DatabaseError::TooLarge { field, message } => {
+                assert_eq!(field, Some("content"));
+                assert!(message.contains("9 bytes"), "{}", message);
+                assert!(message.contains("limit of 8"), "{}", message);
+            }
+            e => panic!("expected TooLarge, got {:?}", e),
+        }
+
+        let update = UpdatePost::builder(created.id)
+            .category(created.category.clone())
+            .title(&created.title)
+            .slug(&created.slug)
+            .content("123456789")
+            .description(&created.description)
+            .published(created.published)
+            .language(&created.language)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            repo.update(update).await.unwrap_err(),
+            DatabaseError::TooLarge { .. }
+        ));
+        assert!(matches!(
+            repo.patch(PatchPost {
+                id: created.id,
+                content: Some("123456789".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err(),
+            DatabaseError::TooLarge { .. }
+        ));
+
+        // Patches leaving the content alone aren't held to the limit
+        let roomy = repo
+            .clone()
+            .with_max_content_bytes(DEFAULT_MAX_CONTENT_BYTES);
+        roomy
+            .patch(PatchPost {
+                id: created.id,
+                content: Some("123456789".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        repo.patch(PatchPost {
+            id: created.id,
+            title: Some("Retitled".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    }
+
     #[tokio::test]
     async fn test_delete_post() {
         let (_, repo) = setup().await;
@@ -553,4 +3419,292 @@ mod tests {
             DatabaseError::NotFound(_)
         ));
     }
+
+    #[tokio::test]
+    async fn test_deleted_post_frees_its_slugs() {
+        let (_, repo) = setup().await;
+        let mut post = create_test_post();
+        post.slug = "my-annual-review".to_string();
+        let post = repo.create(post).await.unwrap();
+        repo.patch(PatchPost {
+            id: post.id,
+            slug: Some("annual-review-2024".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        repo.delete(post.id).await.unwrap();
+
+        // Deleting is permanent, so both the current slug and the one it
+        // redirected from can go to new posts straight away
+        for slug in ["my-annual-review", "annual-review-2024"] {
+            assert!(matches!(
+                repo.resolve_slug(slug).await.unwrap_err(),
+                DatabaseError::NotFound(_)
+            ));
+            let mut reused = create_test_post();
+            reused.slug = slug.to_string();
+            let reused = repo.create(reused).await.unwrap();
+            assert_eq!(repo.find_by_slug(slug).await.unwrap().id, reused.id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_never_serves_stale_reads() {
+        let (db, repo) = setup().await;
+        let post = repo.create(create_test_post()).await.unwrap();
+        assert_eq!(
+            repo.find_by_slug("test-post").await.unwrap().title,
+            "Test Post"
+        );
+        assert_eq!(
+            repo.list(None, None, Visibility::Published, 10, 0)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Changes through any handle show up on the next read
+        insert_user(&db, 7).await;
+        db.acting_as(Actor::User(7))
+            .posts()
+            .patch(PatchPost {
+                id: post.id,
+                title: Some("Patched".to_string()),
+                published: Some(false),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.find_by_slug("test-post").await.unwrap().title,
+            "Patched"
+        );
+        assert!(repo
+            .list(None, None, Visibility::Published, 10, 0)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let mut other = create_test_post();
+        other.slug = "other-post".to_string();
+        repo.create(other).await.unwrap();
+        assert_eq!(
+            repo.list(None, None, Visibility::All, 10, 0)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        repo.delete(post.id).await.unwrap();
+        assert!(matches!(
+            repo.find_by_slug("test-post").await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+        assert_eq!(
+            repo.list(None, None, Visibility::All, 10, 0)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cached_reads_skip_the_pool() {
+        let (_, repo) = setup().await;
+        repo.create(create_test_post()).await.unwrap();
+        repo.find_by_slug("test-post").await.unwrap();
+        repo.list(Some(PostCategory::Blog), None, Visibility::Published, 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(repo.cache_stats(), CacheStats { hits: 0, misses: 2 });
+
+        // With the pool closed, only cached reads can succeed
+        repo.pool.close().await;
+        for _ in 0..3 {
+            assert_eq!(
+                repo.find_by_slug("Test-Post").await.unwrap().slug,
+                "test-post"
+            );
+            let posts = repo
+                .list(Some(PostCategory::Blog), None, Visibility::Published, 10, 0)
+                .await
+                .unwrap();
+            assert_eq!(posts.len(), 1);
+        }
+        assert_eq!(repo.cache_stats(), CacheStats { hits: 6, misses: 2 });
+        assert!(repo
+            .list(None, None, Visibility::Published, 10, 0)
+            .await
+            .is_err());
+
+        // Without the cache, every read goes to the database
+        let (_, repo) = setup().await;
+        let repo = repo.with_cache(None);
+        repo.create(create_test_post()).await.unwrap();
+        repo.find_by_slug("test-post").await.unwrap();
+        repo.pool.close().await;
+        assert!(repo.find_by_slug("test-post").await.is_err());
+        assert_eq!(repo.cache_stats(), CacheStats::default());
+    }
+
+    #[tokio::test]
+    async fn test_post_editors() {
+        let (db, _) = setup().await;
+        for id in [7, 8, 9] {
+            insert_user(&db, id).await;
+        }
+
+        let post = db
+            .acting_as(Actor::User(7))
+            .posts()
+            .create(create_test_post())
+            .await
+            .unwrap();
+        assert_eq!((post.created_by, post.updated_by), (Some(7), Some(7)));
+
+        // A second user's edit changes only updated_by
+        let (post, _) = db
+            .acting_as(Actor::User(8))
+            .posts()
+            .patch(PatchPost {
+                id: post.id,
+                title: Some("Edited".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!((post.created_by, post.updated_by), (Some(7), Some(8)));
+
+        // An edit that changes nothing isn't recorded
+        let (post, changed) = db
+            .acting_as(Actor::User(9))
+            .posts()
+            .patch(PatchPost {
+                id: post.id,
+                title: Some("Edited".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(!changed);
+        assert_eq!(post.updated_by, Some(8));
+
+        // Callers without an account are recorded as the system user, if any
+        let (post, _) = db
+            .acting_as(Actor::ApiKey(None))
+            .posts()
+            .patch(PatchPost {
+                id: post.id,
+                title: Some("By key".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!((post.created_by, post.updated_by), (Some(7), None));
+        let (post, _) = db
+            .posts()
+            .clone()
+            .with_system_user(Some(9))
+            .acting_as(Actor::ApiKey(Some(1)))
+            .patch(PatchPost {
+                id: post.id,
+                title: Some("By managed key".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(post.updated_by, Some(9));
+
+        // Only attributed responses show the editors
+        let public = serde_json::to_value(&post).unwrap();
+        assert!(public.get("created_by").is_none());
+        assert!(public.get("updated_by").is_none());
+        let attributed = serde_json::to_value(post.attributed()).unwrap();
+        assert_eq!(attributed["created_by"], 7);
+        assert_eq!(attributed["updated_by"], 9);
+        assert_eq!(attributed["title"], "By managed key");
+    }
+
+    #[tokio::test]
+    async fn test_changes_are_audited() {
+        let (db, _) = setup().await;
+        insert_user(&db, 7).await;
+        let repo = db.acting_as(Actor::User(7)).posts().clone();
+
+        let post = repo
+            .create_with_tags(
+                CreatePost {
+                    tags: vec!["rust".to_string()],
+                    ..create_test_post()
+                },
+                None,
+            )
+            .await
+            .unwrap()
+            .post;
+        repo.patch(PatchPost {
+            id: post.id,
+            title: Some("Renamed".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        // A failed change leaves no entry behind
+        repo.create(CreatePost {
+            slug: "other-post".to_string(),
+            ..create_test_post()
+        })
+        .await
+        .unwrap();
+        assert!(repo
+            .patch(PatchPost {
+                id: post.id,
+                slug: Some("other-post".to_string()),
+                ..Default::default()
+            })
+            .await
+            .is_err());
+
+        repo.delete(post.id).await.unwrap();
+
+        let (entries, total) = db
+            .audit()
+            .list(&AuditQuery {
+                entity_type: Some(AuditEntity::Post),
+                entity_id: Some(post.id),
+                limit: 50,
+                offset: 0,
+            })
+            .await
+            .unwrap();
+        assert_eq!(total, 3);
+        let actions: Vec<_> = entries.iter().map(|e| e.action).collect();
+        assert_eq!(
+            actions,
+            vec![AuditAction::Delete, AuditAction::Patch, AuditAction::Create]
+        );
+        assert!(entries.iter().all(|e| e.actor == "user:7"));
+        assert_eq!(
+            entries[1].changes.0,
+            serde_json::json!({ "title": { "old": "Test Post", "new": "Renamed" } })
+        );
+        assert_eq!(entries[0].changes.0["title"], "Renamed");
+
+        // The tag created along with the post is audited too
+        let (_, tags) = db
+            .audit()
+            .list(&AuditQuery {
+                entity_type: Some(AuditEntity::Tag),
+                limit: 50,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(tags, 1);
+    }
 }
@@ -0,0 +1,208 @@
+//! Timing of repository calls: a warning for every slow one and a
+//! histogram of durations per method
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use serde::Serialize;
+use tracing::Span;
+
+/// Calls taking at least this long are logged unless configured otherwise
+pub const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(100);
+
+/// Upper bounds of the histogram buckets, in milliseconds. Slower calls
+/// land in a final unbounded bucket.
+const BUCKET_BOUNDS_MS: [u64; 10] = [1, 5, 10, 25, 50, 100, 250, 500, 1000, 2500];
+
+/// Durations of repository calls by method, shared by every repository of a
+/// `Database` and its clones
+#[derive(Clone, Debug)]
+pub struct QueryMetrics {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    slow_threshold: Duration,
+    methods: DashMap<&'static str, Histogram>,
+}
+
+#[derive(Debug, Default)]
+struct Histogram {
+    count: u64,
+    sum: Duration,
+    max: Duration,
+    /// Calls per bucket, not cumulative
+    buckets: [u64; BUCKET_BOUNDS_MS.len() + 1],
+}
+
+impl QueryMetrics {
+    /// Metrics that warn about every call taking `slow_threshold` or longer
+    pub fn new(slow_threshold: Duration) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                slow_threshold,
+                methods: DashMap::new(),
+            }),
+        }
+    }
+
+    /// Starts timing a call of `method`, which is recorded when the
+    /// returned timer is dropped. `params` summarizes the arguments for the
+    /// slow query warning, so it must not contain post content.
+    pub(crate) fn time(&self, method: &'static str, params: impl Into<String>) -> QueryTimer<'_> {
+        QueryTimer {
+            metrics: self,
+            method,
+            params: params.into(),
+            started: Instant::now(),
+        }
+    }
+
+    fn record(&self, method: &'static str, params: &str, elapsed: Duration) {
+        if elapsed >= self.inner.slow_threshold {
+            tracing::warn!(
+                method,
+                params,
+                elapsed_ms = elapsed.as_secs_f64() * 1000.0,
+                "Slow query"
+            );
+        }
+
+        let mut histogram = self.inner.methods.entry(method).or_default();
+        histogram.count += 1;
+        histogram.sum += elapsed;
+        histogram.max = histogram.max.max(elapsed);
+        let bucket = BUCKET_BOUNDS_MS
+            .iter()
+            .position(|&bound| elapsed <= Duration::from_millis(bound))
+            .unwrap_or(BUCKET_BOUNDS_MS.len());
+        histogram.buckets[bucket] += 1;
+    }
+
+    /// Timings of every method called so far, by method name
+    pub fn snapshot(&self) -> BTreeMap<&'static str, MethodTimings> {
+        self.inner
+            .methods
+            .iter()
+            .map(|entry| {
+                let histogram = entry.value();
+                let mut calls = 0;
+                let buckets = histogram
+                    .buckets
+                    .iter()
+                    .enumerate()
+                    .map(|(i, count)| {
+                        calls += count;
+                        Bucket {
+                            le_ms: BUCKET_BOUNDS_MS.get(i).copied(),
+                            count: calls,
+                        }
+                    })
+                    .collect();
+                let timings = MethodTimings {
+                    count: histogram.count,
+                    sum_ms: histogram.sum.as_secs_f64() * 1000.0,
+                    max_ms: histogram.max.as_secs_f64() * 1000.0,
+                    buckets,
+                };
+                (*entry.key(), timings)
+            })
+            .collect()
+    }
+}
+
+impl Default for QueryMetrics {
+    fn default() -> Self {
+        Self::new(DEFAULT_SLOW_QUERY_THRESHOLD)
+    }
+}
+
+/// Records how long a repository call took when dropped
+pub(crate) struct QueryTimer<'a> {
+    metrics: &'a QueryMetrics,
+    method: &'static str,
+    params: String,
+    started: Instant,
+}
+
+impl Drop for QueryTimer<'_> {
+    fn drop(&mut self) {
+        self.metrics
+            .record(self.method, &self.params, self.started.elapsed());
+    }
+}
+
+/// Durations of the calls of one repository method
+#[derive(Debug, Serialize)]
+pub struct MethodTimings {
+    pub count: u64,
+    pub sum_ms: f64,
+    pub max_ms: f64,
+    /// Cumulative: each bucket counts the calls at or below its bound
+    pub buckets: Vec<Bucket>,
+}
+
+/// A histogram bucket
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Bucket {
+    /// Upper bound in milliseconds; `None` for the last bucket
+    pub le_ms: Option<u64>,
+    pub count: u64,
+}
+
+/// Records how many rows a repository call returned in the `rows` field of
+/// the span it runs in
+pub(crate) fn record_rows(rows: usize) {
+    Span::current().record("rows", rows);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram() {
+        let metrics = QueryMetrics::default();
+        for ms in [0, 3, 3, 120, 4000] {
+            metrics.record("posts.list", "", Duration::from_millis(ms));
+        }
+        metrics.record("tags.list", "", Duration::from_millis(2));
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(
+            snapshot.keys().copied().collect::<Vec<_>>(),
+            ["posts.list", "tags.list"]
+        );
+
+        let timings = &snapshot["posts.list"];
+        assert_eq!(timings.count, 5);
+        assert_eq!(timings.sum_ms, 4126.0);
+        assert_eq!(timings.max_ms, 4000.0);
+        let counts: Vec<(Option<u64>, u64)> = timings
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.le_ms, bucket.count))
+            .collect();
+        assert_eq!(
+            counts,
+            [
+                (Some(1), 1),
+                (Some(5), 3),
+                (Some(10), 3),
+                (Some(25), 3),
+                (Some(50), 3),
+                (Some(100), 3),
+                (Some(250), 4),
+                (Some(500), 4),
+                (Some(1000), 4),
+                (Some(2500), 4),
+                (None, 5),
+            ]
+        );
+    }
+}
@@ -0,0 +1,225 @@
+/// Repository for HTML rendered from posts' Markdown. Each post has at
+/// most one rendering, stored with the hash of the content and renderer
+/// version it came from; a rendering whose hash doesn't match is stale
+/// and gets replaced on the next request, so edits need no invalidation.
+#[derive(Clone, Debug)]
+pub struct RenderCacheRepository {
+    pool: SqlitePool,
+    metrics: QueryMetrics,
+    stats: Arc<Counters>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl RenderCacheRepository {
+    /// Creates a new RenderCacheRepository instance sharing the given pool
+    pub(crate) fn new(pool: SqlitePool, metrics: QueryMetrics) -> Self {
+        Self {
+            pool,
+            metrics,
+            stats: Arc::default(),
+        }
+    }
+
+    /// Lookups answered from the cache and lookups that had to render,
+    /// since startup
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.stats.hits.load(Ordering::Relaxed),
+            misses: self.stats.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// The post's content as HTML, rendered and stored unless the cache
+    /// already holds a rendering of this content by this renderer
+    pub async fn html(&self, post: &Post) -> DatabaseResult<String> {
+        self.html_as(post, RENDERER_VERSION).await
+    }
+
+    async fn html_as(&self, post: &Post, version: u32) -> DatabaseResult<String> {
+        let _timer = self
+            .metrics
+            .time("render_cache.html", format!("post_id={}", post.id));
+        let hash = content_hash(&post.content, version);
+        let cached = sqlx::query_scalar!(
+            "SELECT html FROM rendered_cache WHERE post_id = ? AND content_hash = ?",
+            post.id,
+            hash
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+        if let Some(html) = cached {
+            self.stats.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(html);
+        }
+
+        self.stats.misses.fetch_add(1, Ordering::Relaxed);
+        let html = render_markdown(&post.content);
+        self.store(post.id, &hash, &html).await?;
+        Ok(html)
+    }
+
+    /// Renders every post again and replaces what the cache holds,
+    /// returning how many posts were rendered. Run after bumping
+    /// `RENDERER_VERSION`, so readers don't each pay for a first render.
+    pub async fn rebuild(&self) -> DatabaseResult<usize> {
+        self.rebuild_as(RENDERER_VERSION).await
+    }
+
+    async fn rebuild_as(&self, version: u32) -> DatabaseResult<usize> {
+        let _timer = self.metrics.time("render_cache.rebuild", String::new());
+        let posts = sqlx::query!(r#"SELECT id as "id!", content FROM posts ORDER BY id"#)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+
+        let mut rendered = 0;
+        for post in posts {
+            let hash = content_hash(&post.content, version);
+            match self
+                .store(post.id, &hash, &render_markdown(&post.content))
+                .await
+            {
+                Ok(()) => rendered += 1,
+                // Deleted while rebuilding
+                Err(DatabaseError::NotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(rendered)
+    }
+
+    /// Stores a rendering, replacing the post's previous one. Fails with
+    /// `NotFound` when the post has been deleted.
+    async fn store(&self, post_id: i64, hash: &str, html: &str) -> DatabaseResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO rendered_cache (post_id, content_hash, html, rendered_at)
+            VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+            ON CONFLICT (post_id) DO UPDATE SET
+                content_hash = excluded.content_hash,
+                html = excluded.html,
+                rendered_at = excluded.rendered_at
+            "#,
+            post_id,
+            hash,
+            html
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match constraint_violation(&e) {
+            Some(ConstraintViolation::ForeignKey) => {
+                DatabaseError::not_found("Post", &post_id.to_string())
+            }
+            _ => DatabaseError::Sqlx(e),
+        })?;
+        Ok(())
+    }
+}
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use sqlx::SqlitePool;
+
+use crate::{
+    models::post::Post,
+    render::{content_hash, render_markdown, RENDERER_VERSION},
+};
+
+use super::{
+    error::{constraint_violation, ConstraintViolation, DatabaseResult},
+    CacheStats, DatabaseError, QueryMetrics,
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::db::{test_utils::create_test_db, Database};
+
+    use super::*;
+
+    async fn insert_post(db: &Database, id: i64, content: &str) {
+        sqlx::query(
+            "INSERT INTO posts (id, category, title, slug, content, description)
+             VALUES (?, 'blog', 'Post', ?, ?, 'Description')",
+        )
+        .bind(id)
+        .bind(format!("post-{}", id))
+        .bind(content)
+        .execute(db.pool())
+        .await
+        .unwrap();
+    }
+
+    async fn cached(db: &Database, post_id: i64) -> Option<(String, String)> {
+        sqlx::query_as("SELECT content_hash, html FROM rendered_cache WHERE post_id = ?")
+            .bind(post_id)
+            .fetch_optional(db.pool())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_html_renders_once_per_content() {
+        let db = create_test_db().await.unwrap();
+        insert_post(&db, 1, "# First").await;
+        let repo = db.render_cache();
+
+        let post = db.posts().find_by_id(1).await.unwrap();
+        assert_eq!(repo.html(&post).await.unwrap(), "<h1>First</h1>\n");
+        assert_eq!(repo.html(&post).await.unwrap(), "<h1>First</h1>\n");
+        assert_eq!(repo.stats(), CacheStats { hits: 1, misses: 1 });
+
+        // A new hash for new content, so the old rendering isn't served
+        sqlx::query("UPDATE posts SET content = '# Second' WHERE id = 1")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let post = db.posts().find_by_id(1).await.unwrap();
+        assert_eq!(repo.html(&post).await.unwrap(), "<h1>Second</h1>\n");
+        assert_eq!(repo.stats(), CacheStats { hits: 1, misses: 2 });
+        let (hash, html) = cached(&db, 1).await.unwrap();
+        assert_eq!(hash, content_hash("# Second", RENDERER_VERSION));
+        assert_eq!(html, "<h1>Second</h1>\n");
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_after_renderer_upgrade() {
+        let db = create_test_db().await.unwrap();
+        insert_post(&db, 1, "*one*").await;
+        insert_post(&db, 2, "**two**").await;
+        let repo = db.render_cache();
+        let post = db.posts().find_by_id(1).await.unwrap();
+        repo.html(&post).await.unwrap();
+
+        // Renderings by the previous version are stale
+        let next = RENDERER_VERSION + 1;
+        repo.html_as(&post, next).await.unwrap();
+        assert_eq!(repo.stats().misses, 2);
+
+        assert_eq!(repo.rebuild_as(next).await.unwrap(), 2);
+        for (id, html) in [
+            (1, "<p><em>one</em></p>\n"),
+            (2, "<p><strong>two</strong></p>\n"),
+        ] {
+            let post = db.posts().find_by_id(id).await.unwrap();
+            assert_eq!(
+                cached(&db, id).await.unwrap(),
+                (content_hash(&post.content, next), html.to_string())
+            );
+            assert_eq!(repo.html_as(&post, next).await.unwrap(), html);
+        }
+        assert_eq!(repo.stats(), CacheStats { hits: 2, misses: 2 });
+
+        // Deleting a post drops its rendering
+        db.posts().delete(2).await.unwrap();
+        assert!(cached(&db, 2).await.is_none());
+    }
+}
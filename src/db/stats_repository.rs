@@ -0,0 +1,211 @@
+/// Repository for figures summarizing the posts, such as a year in review
+#[derive(Clone, Debug)]
+pub struct StatsRepository {
+    pool: SqlitePool,
+    metrics: QueryMetrics,
+}
+
+impl StatsRepository {
+    /// Creates a new StatsRepository instance sharing the given pool
+    pub(crate) fn new(pool: SqlitePool, metrics: QueryMetrics) -> Self {
+        Self { pool, metrics }
+    }
+
+    /// Summarizes the posts published in `year`, dated by `published_at`
+    /// or, for posts without one, `created_at` (UTC). Drafts are left out.
+    /// A year without posts, including one in the future, gives zeros.
+    pub async fn year(&self, year: i32) -> DatabaseResult<YearStats> {
+        let _timer = self.metrics.time("stats.year", format!("year={}", year));
+        let mut stats = YearStats::empty(year);
+        let Some((start, end)) = year_bounds(year) else {
+            return Ok(stats);
+        };
+
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+
+        let categories = sqlx::query!(
+            r#"
+            SELECT category as "category: PostCategory", COUNT(*) as "posts!: i64"
+            FROM posts
+            WHERE
+                published = TRUE
+                AND julianday(COALESCE(published_at, created_at)) >= julianday(?)
+                AND julianday(COALESCE(published_at, created_at)) < julianday(?)
+            GROUP BY category
+            "#,
+            start,
+            end
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+        for row in categories {
+            stats.posts += row.posts;
+            if let Some(count) = stats
+                .by_category
+                .iter_mut()
+                .find(|count| count.category == row.category)
+            {
+                count.posts = row.posts;
+            }
+        }
+
+        stats.top_tags = sqlx::query_as!(
+            TagCount,
+            r#"
+            SELECT t.name, COUNT(*) as "posts!: i64"
+            FROM posts p
+            JOIN post_tags pt ON pt.post_id = p.id
+            JOIN tags t ON t.id = pt.tag_id
+            WHERE
+                p.published = TRUE
+                AND julianday(COALESCE(p.published_at, p.created_at)) >= julianday(?)
+                AND julianday(COALESCE(p.published_at, p.created_at)) < julianday(?)
+            GROUP BY t.id
+            ORDER BY COUNT(*) DESC, t.name ASC
+            LIMIT ?
+            "#,
+            start,
+            end,
+            TOP_TAGS
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        // SQLite can't split text into words, so the contents are counted here
+        let mut posts = sqlx::query!(
+            r#"
+            SELECT id as "id!", title, slug, content
+            FROM posts
+            WHERE
+                published = TRUE
+                AND julianday(COALESCE(published_at, created_at)) >= julianday(?)
+                AND julianday(COALESCE(published_at, created_at)) < julianday(?)
+            ORDER BY id
+            "#,
+            start,
+            end
+        )
+        .fetch(&mut *tx);
+        while let Some(post) = posts.try_next().await.map_err(DatabaseError::Sqlx)? {
+            let words = word_count(&post.content);
+            stats.words += words;
+            if stats
+                .longest_post
+                .as_ref()
+                .is_none_or(|longest| words > longest.words)
+            {
+                stats.longest_post = Some(LongestPost {
+                    id: post.id,
+                    title: post.title,
+                    slug: post.slug,
+                    words,
+                });
+            }
+        }
+        drop(posts);
+
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok(stats)
+    }
+}
+
+use futures_util::TryStreamExt;
+use sqlx::SqlitePool;
+
+use crate::models::{
+    post::PostCategory,
+    stats::{word_count, year_bounds, LongestPost, TagCount, YearStats, TOP_TAGS},
+};
+
+use super::{error::DatabaseResult, DatabaseError, QueryMetrics};
+
+#[cfg(test)]
+mod tests {
+    use crate::db::test_utils::create_test_db;
+    use crate::models::stats::CategoryCount;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_year_stats() {
+        let db = create_test_db().await.unwrap();
+        // Posts published in 2024, one only dated by its creation, a 2024
+        // draft, and posts from the years around it
+        sqlx::query(
+            "INSERT INTO posts (id, category, title, slug, content, description, external_url, published, published_at, created_at) VALUES
+             (1, 'blog', 'First', 'first', 'one two three', 'd', NULL, TRUE, '2024-01-01 00:00:00', '2023-12-20 00:00:00'),
+             (2, 'blog', 'Long', 'long', 'a b c d e f g', 'd', NULL, TRUE, '2024-06-01 12:00:00', '2024-05-01 00:00:00'),
+             (3, 'reading', 'Book', 'book', 'great  read', 'd', 'https://example.com', TRUE, NULL, '2024-12-31 23:59:59'),
+             (4, 'art', 'Draft', 'draft', 'this draft has many more words than the rest', 'd', NULL, FALSE, NULL, '2024-03-01 00:00:00'),
+             (5, 'art', 'Old', 'old', 'last year', 'd', NULL, TRUE, '2023-12-31 23:59:59', '2023-12-31 23:59:59'),
+             (6, 'art', 'New', 'new', 'next year', 'd', NULL, TRUE, '2025-01-01 00:00:00', '2024-12-31 00:00:00')",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO tags (id, name, slug) VALUES
+             (1, 'rust', 'rust'), (2, 'books', 'books'), (3, 'art', 'art'), (4, 'axum', 'axum');
+             INSERT INTO post_tags (post_id, tag_id) VALUES
+             (1, 1), (2, 1), (3, 2), (2, 4), (4, 3), (5, 3), (6, 3)",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        let stats = db.stats().year(2024).await.unwrap();
+        assert_eq!(stats.year, 2024);
+        assert_eq!(stats.posts, 3);
+        assert_eq!(
+            stats.by_category,
+            [
+                CategoryCount {
+                    category: PostCategory::Blog,
+                    posts: 2
+                },
+                CategoryCount {
+                    category: PostCategory::Art,
+                    posts: 0
+                },
+                CategoryCount {
+                    category: PostCategory::Reading,
+                    posts: 1
+                },
+            ]
+        );
+        assert_eq!(stats.words, 3 + 7 + 2);
+        assert_eq!(
+            stats.top_tags,
+            [
+                TagCount {
+                    name: "rust".to_string(),
+                    posts: 2
+                },
+                TagCount {
+                    name: "axum".to_string(),
+                    posts: 1
+                },
+                TagCount {
+                    name: "books".to_string(),
+                    posts: 1
+                },
+            ]
+        );
+        assert_eq!(
+            stats.longest_post,
+            Some(LongestPost {
+                id: 2,
+                title: "Long".to_string(),
+                slug: "long".to_string(),
+                words: 7
+            })
+        );
+
+        // Years without posts, however far off, are all zeros
+        for year in [2030, 1900, 9999] {
+            assert_eq!(db.stats().year(year).await.unwrap(), YearStats::empty(year));
+        }
+    }
+}
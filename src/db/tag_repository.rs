@@ -1,7 +1,20 @@
-use crate::models::tag::{Tag, TagWithPostCount};
-use sqlx::SqlitePool;
-
-use super::{error::DatabaseResult, DatabaseError};
+use std::collections::HashMap;
+
+use crate::models::{
+    audit::{Actor, AuditAction, AuditEvent, Audited},
+    tag::{
+        normalize_color, normalize_tag_name, tag_slug, PatchTag, RelatedTag, Tag, TagListOptions,
+        TagRequest, TagSlugLookup, TagWithPostCount,
+    },
+};
+use sqlx::{SqliteConnection, SqlitePool};
+use tracing::{instrument, Span};
+
+use super::{
+    error::{constraint_violation, ConstraintViolation, DatabaseResult},
+    query_metrics::record_rows,
+    AuditRepository, DatabaseError, QueryMetrics,
+};
 
 /// Repository for managing tags in the database
 /// Provides methods for creating, reading, updating, and deleting tags,
@@ -9,51 +22,224 @@ use super::{error::DatabaseResult, DatabaseError};
 #[derive(Clone, Debug)]
 pub struct TagRepository {
     pool: SqlitePool,
+    /// Recorded in the audit log as the author of every change
+    actor: Actor,
+    /// Whether names are lowercased when they are normalized
+    lowercase_names: bool,
+    metrics: QueryMetrics,
 }
 
 impl TagRepository {
     /// Creates a new TagRepository instance
-    pub(crate) fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub(crate) fn new(pool: SqlitePool, metrics: QueryMetrics) -> Self {
+        Self {
+            pool,
+            actor: Actor::System,
+            lowercase_names: false,
+            metrics,
+        }
     }
 
-    /// Creates a new tag with the given name
-    /// Returns an error if a tag with the same name already exists
-    pub async fn create(&self, name: &str) -> DatabaseResult<Tag> {
-        // Validate tag name
-        if name.trim().is_empty() {
-            return Err(DatabaseError::validation("Tag name cannot be empty"));
+    /// Returns a repository whose changes are attributed to `actor`
+    pub(crate) fn acting_as(&self, actor: Actor) -> Self {
+        Self {
+            actor,
+            ..self.clone()
         }
+    }
+
+    /// Returns a repository that stores and looks up tag names in lowercase
+    pub(crate) fn with_lowercase_names(self, lowercase: bool) -> Self {
+        Self {
+            lowercase_names: lowercase,
+            ..self
+        }
+    }
 
+    /// Creates a new tag with the given name and no description or color
+    pub async fn create(&self, name: &str) -> DatabaseResult<Tag> {
+        self.create_with_details(&TagRequest::named(name)).await
+    }
+
+    /// Creates a new tag with an optional description and color
+    /// Returns an error if a tag with the same name already exists, ignoring case
+    pub async fn create_with_details(&self, request: &TagRequest) -> DatabaseResult<Tag> {
+        let _timer = self.metrics.time("tags.create", String::new());
         let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+        let tag = self.create_with_details_in(&mut tx, request).await?;
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok(tag)
+    }
+
+    /// Like `create_with_details`, but runs on the caller's connection so it
+    /// can take part in a larger transaction
+    #[instrument(
+        name = "tags.create_with_details_in",
+        skip_all,
+        fields(tag.name = %request.name, tag.id),
+        err(level = "info")
+    )]
+    pub async fn create_with_details_in(
+        &self,
+        conn: &mut SqliteConnection,
+        request: &TagRequest,
+    ) -> DatabaseResult<Tag> {
+        let request = request.normalized(self.lowercase_names);
+        // Validate tag name
+        if request.name.is_empty() {
+            return Err(DatabaseError::invalid_field(
+                "name",
+                "Tag name cannot be empty",
+            ));
+        }
+        request.validate().map_err(DatabaseError::from)?;
 
-        let trimmed_name = name.trim();
+        let color = request.color.as_deref().map(normalize_color);
 
         // Attempt to create the tag
-        let tag = sqlx::query_as!(
+        let mut tag = sqlx::query_as!(
             Tag,
             r#"
-            INSERT INTO tags (name)
-            VALUES (?)
+            INSERT INTO tags (name, slug, description, color)
+            VALUES (?, '~' || ?, ?, ?)
             RETURNING *
             "#,
-            trimmed_name
+            request.name,
+            request.name,
+            request.description,
+            color
         )
-        .fetch_one(&mut *tx)
+        .fetch_one(&mut *conn)
         .await
-        .map_err(|e| match e {
-            sqlx::Error::Database(e) if e.message().contains("UNIQUE constraint") => {
-                DatabaseError::duplicate("Tag", name)
-            }
-            e => DatabaseError::Sqlx(e),
-        })?;
+        .map_err(DatabaseError::on_duplicate("Tag", &request.name))?;
+        Span::current().record("tag.id", tag.id);
+        Self::assign_slug(conn, &mut tag).await?;
+
+        AuditRepository::record_in(conn, self.actor, &AuditEvent::created(&tag)).await?;
 
-        tx.commit().await.map_err(DatabaseError::Sqlx)?;
         Ok(tag)
     }
 
+    /// Returns the tag with the given name, ignoring case, creating it with
+    /// the request's description and color if it doesn't exist. An existing
+    /// tag is returned unchanged. The flag is true when this call created the tag.
+    /// Safe to call concurrently for the same name: exactly one caller creates it.
+    pub async fn get_or_create(&self, request: &TagRequest) -> DatabaseResult<(Tag, bool)> {
+        let _timer = self.metrics.time("tags.get_or_create", String::new());
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+        let (tag, created) = Self::get_or_create_in(&mut tx, request, self.lowercase_names).await?;
+        if created {
+            AuditRepository::record_in(&mut tx, self.actor, &AuditEvent::created(&tag)).await?;
+        }
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok((tag, created))
+    }
+
+    /// Like `get_or_create`, but runs on the caller's connection so it can
+    /// take part in a larger transaction. `lowercase_names` is the
+    /// repository's setting for normalizing the name.
+    #[instrument(
+        name = "tags.get_or_create_in",
+        skip_all,
+        fields(tag.name = %request.name, tag.id, created),
+        err(level = "info")
+    )]
+    pub async fn get_or_create_in(
+        conn: &mut SqliteConnection,
+        request: &TagRequest,
+        lowercase_names: bool,
+    ) -> DatabaseResult<(Tag, bool)> {
+        let request = request.normalized(lowercase_names);
+        if request.name.is_empty() {
+            return Err(DatabaseError::invalid_field(
+                "name",
+                "Tag name cannot be empty",
+            ));
+        }
+        request.validate().map_err(DatabaseError::from)?;
+
+        let color = request.color.as_deref().map(normalize_color);
+
+        // No conflict target, so case variants caught by the NOCASE index
+        // are ignored too
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO tags (name, slug, description, color)
+            VALUES (?, '~' || ?, ?, ?)
+            ON CONFLICT DO NOTHING
+            "#,
+            request.name,
+            request.name,
+            request.description,
+            color
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        let mut tag = sqlx::query_as!(
+            Tag,
+            r#"
+            SELECT
+                id as "id!",
+                name as "name!",
+                slug as "slug!",
+                created_at as "created_at!",
+                description,
+                color
+            FROM tags
+            WHERE name = ? COLLATE NOCASE
+            "#,
+            request.name
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        let created = inserted.rows_affected() > 0;
+        if created {
+            Self::assign_slug(conn, &mut tag).await?;
+        }
+        Span::current()
+            .record("tag.id", tag.id)
+            .record("created", created);
+        Ok((tag, created))
+    }
+
+    /// Associates a tag with a post on the caller's connection, ignoring
+    /// associations that already exist
+    #[instrument(
+        name = "tags.attach_in",
+        skip_all,
+        fields(post.id = post_id, tag.id = tag_id),
+        err(level = "info")
+    )]
+    pub async fn attach_in(
+        conn: &mut SqliteConnection,
+        post_id: i64,
+        tag_id: i64,
+    ) -> DatabaseResult<()> {
+        sqlx::query!(
+            "INSERT OR IGNORE INTO post_tags (post_id, tag_id) VALUES (?, ?)",
+            post_id,
+            tag_id
+        )
+        .execute(conn)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+        Ok(())
+    }
+
     /// Retrieves a tag by its ID
+    #[instrument(name = "tags.find_by_id", skip_all, fields(tag.id = id), err(level = "info"))]
     pub async fn find_by_id(&self, id: i64) -> DatabaseResult<Tag> {
+        let _timer = self.metrics.time("tags.find_by_id", format!("id={}", id));
+        let mut conn = self.pool.acquire().await.map_err(DatabaseError::Sqlx)?;
+        Self::find_in(&mut conn, id).await
+    }
+
+    /// Like `find_by_id`, on the caller's connection
+    async fn find_in(conn: &mut SqliteConnection, id: i64) -> DatabaseResult<Tag> {
         sqlx::query_as!(
             Tag,
             r#"
@@ -63,25 +249,39 @@ impl TagRepository {
             "#,
             id
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(conn)
         .await
         .map_err(DatabaseError::Sqlx)?
         .ok_or_else(|| DatabaseError::not_found("Tag", &id.to_string()))
     }
 
-    /// Retrieves a tag by its name
+    /// Retrieves a tag by its name, ignoring case. The name is normalized
+    /// the way names are stored, so `Front  End` finds `front end`.
+    #[instrument(
+        name = "tags.find_by_name",
+        skip_all,
+        fields(tag.name = %name),
+        err(level = "info")
+    )]
     pub async fn find_by_name(&self, name: &str) -> DatabaseResult<Tag> {
+        let _timer = self
+            .metrics
+            .time("tags.find_by_name", format!("name={:?}", name));
+        let normalized = normalize_tag_name(name, self.lowercase_names);
         sqlx::query_as!(
             Tag,
             r#"
         SELECT 
             id as "id!",
             name as "name!",
-            created_at as "created_at!"
+            slug as "slug!",
+            created_at as "created_at!",
+            description,
+            color
         FROM tags
-        WHERE name = ?
+        WHERE name = ? COLLATE NOCASE
         "#,
-            name
+            normalized
         )
         .fetch_optional(&self.pool)
         .await
@@ -89,67 +289,524 @@ impl TagRepository {
         .ok_or_else(|| DatabaseError::not_found("Tag", name))
     }
 
-    /// Lists all tags, optionally including the count of posts for each tag
-    pub async fn list(&self, include_post_count: bool) -> DatabaseResult<Vec<TagWithPostCount>> {
-        let query = if include_post_count {
+    /// Retrieves a tag by its slug. Slugs are lowercase, so the lookup
+    /// ignores case.
+    #[instrument(
+        name = "tags.find_by_slug",
+        skip_all,
+        fields(tag.slug = %slug),
+        err(level = "info")
+    )]
+    pub async fn find_by_slug(&self, slug: &str) -> DatabaseResult<Tag> {
+        let _timer = self
+            .metrics
+            .time("tags.find_by_slug", format!("slug={:?}", slug));
+        let slug_lower = slug.to_ascii_lowercase();
+        sqlx::query_as!(
+            Tag,
+            r#"
+            SELECT
+                id as "id!",
+                name as "name!",
+                slug as "slug!",
+                created_at as "created_at!",
+                description,
+                color
+            FROM tags
+            WHERE slug = ?
+            "#,
+            slug_lower
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?
+        .ok_or_else(|| DatabaseError::not_found("Tag", slug))
+    }
+
+    /// Retrieves a tag by its slug, falling back to the slug history.
+    /// Returns `TagSlugLookup::Moved` with the current slug when the
+    /// requested slug belonged to a tag that has since been renamed.
+    #[instrument(
+        name = "tags.resolve_slug",
+        skip_all,
+        fields(tag.slug = %slug),
+        err(level = "info")
+    )]
+    pub async fn resolve_slug(&self, slug: &str) -> DatabaseResult<TagSlugLookup> {
+        let _timer = self
+            .metrics
+            .time("tags.resolve_slug", format!("slug={:?}", slug));
+        match self.find_by_slug(slug).await {
+            Ok(tag) => return Ok(TagSlugLookup::Found(tag)),
+            Err(DatabaseError::NotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+
+        let slug_lower = slug.to_ascii_lowercase();
+        sqlx::query_scalar!(
+            r#"
+            SELECT t.slug
+            FROM tag_slugs ts
+            JOIN tags t ON t.id = ts.tag_id
+            WHERE ts.slug = ?
+            "#,
+            slug_lower
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?
+        .map(TagSlugLookup::Moved)
+        .ok_or_else(|| DatabaseError::not_found("Tag", slug))
+    }
+
+    /// Records a slug rename in the history so the previous slug keeps
+    /// resolving. The new slug is evicted from the history since it now
+    /// belongs to a live tag.
+    async fn record_slug_change(
+        conn: &mut SqliteConnection,
+        tag_id: i64,
+        previous_slug: &str,
+        new_slug: &str,
+    ) -> DatabaseResult<()> {
+        if previous_slug == new_slug {
+            return Ok(());
+        }
+
+        Self::evict_slug_history(conn, new_slug).await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO tag_slugs (slug, tag_id)
+            VALUES (?, ?)
+            ON CONFLICT(slug) DO UPDATE SET tag_id = excluded.tag_id
+            "#,
+            previous_slug,
+            tag_id
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        Ok(())
+    }
+
+    /// Removes a slug from the history once it is in use by a live tag
+    async fn evict_slug_history(conn: &mut SqliteConnection, slug: &str) -> DatabaseResult<()> {
+        sqlx::query!("DELETE FROM tag_slugs WHERE slug = ?", slug)
+            .execute(&mut *conn)
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+        Ok(())
+    }
+
+    /// Replaces the placeholder slug a tag is inserted with by its real one.
+    ///
+    /// New tags get `~` and their name as slug, which can't clash because
+    /// names are unique and real slugs never contain `~`. Picking the slug
+    /// before inserting would read before writing, and concurrent
+    /// transactions doing that deadlock on SQLite's write lock.
+    async fn assign_slug(conn: &mut SqliteConnection, tag: &mut Tag) -> DatabaseResult<()> {
+        let slug = Self::available_slug(conn, &tag.name, Some(tag.id)).await?;
+        sqlx::query!("UPDATE tags SET slug = ? WHERE id = ?", slug, tag.id)
+            .execute(&mut *conn)
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+        Self::evict_slug_history(conn, &slug).await?;
+        tag.slug = slug;
+        Ok(())
+    }
+
+    /// Picks the slug for a tag named `name`: `tag_slug(name)` if no other
+    /// tag has it, otherwise the first free one of `-2`, `-3`, ... appended
+    /// to it. `except_id` is the tag being renamed, whose own slug counts
+    /// as free.
+    async fn available_slug(
+        conn: &mut SqliteConnection,
+        name: &str,
+        except_id: Option<i64>,
+    ) -> DatabaseResult<String> {
+        let base = tag_slug(name);
+        // Slugs only contain letters, digits and hyphens, none of which are
+        // special in LIKE
+        let pattern = format!("{}-%", base);
+        let taken: Vec<String> = sqlx::query_scalar!(
+            r#"
+            SELECT slug
+            FROM tags
+            WHERE (slug = ? OR slug LIKE ?) AND id IS NOT ?
+            "#,
+            base,
+            pattern,
+            except_id
+        )
+        .fetch_all(conn)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        if !taken.contains(&base) {
+            return Ok(base);
+        }
+        Ok((2..)
+            .map(|n| format!("{}-{}", base, n))
+            .find(|candidate| !taken.contains(candidate))
+            .expect("a free suffix exists"))
+    }
+
+    /// Lists a page of tags, returning it together with the total number of
+    /// tags matching the search
+    #[instrument(
+        name = "tags.list",
+        skip_all,
+        fields(
+            search = ?options.search,
+            sort = options.sort.as_str(),
+            limit = options.limit,
+            offset = options.offset,
+            rows,
+            total,
+        ),
+        err(level = "info")
+    )]
+    pub async fn list(
+        &self,
+        options: &TagListOptions,
+    ) -> DatabaseResult<(Vec<TagWithPostCount>, i64)> {
+        let _timer = self.metrics.time(
+            "tags.list",
+            format!(
+                "search={:?} sort={} limit={} offset={}",
+                options.search,
+                options.sort.as_str(),
+                options.limit,
+                options.offset
+            ),
+        );
+        if options.limit <= 0 || options.limit > 200 {
+            return Err(DatabaseError::invalid_field(
+                "limit",
+                "Limit must be between 1 and 200",
+            ));
+        }
+        if options.offset < 0 {
+            return Err(DatabaseError::invalid_field(
+                "offset",
+                "Offset cannot be negative",
+            ));
+        }
+
+        let pattern = options
+            .search
+            .as_deref()
+            .map(|search| format!("%{}%", escape_like(search)));
+
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+
+        let tags = sqlx::query_as(
+            r#"
+            SELECT
+                t.*,
+                CASE WHEN ? THEN COUNT(pt.post_id) ELSE 0 END as post_count
+            FROM tags t
+            LEFT JOIN post_tags pt ON t.id = pt.tag_id
+            WHERE (? IS NULL OR t.name LIKE ? ESCAPE '\')
+            GROUP BY t.id
+            ORDER BY
+                CASE WHEN ? = 'post_count' THEN COUNT(pt.post_id) END DESC,
+                CASE WHEN ? = 'created_at' THEN t.created_at END DESC,
+                t.name, t.id
+            LIMIT ?
+            OFFSET ?
+            "#,
+        )
+        .bind(options.include_post_count)
+        .bind(&pattern)
+        .bind(&pattern)
+        .bind(options.sort.as_str())
+        .bind(options.sort.as_str())
+        .bind(options.limit)
+        .bind(options.offset)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        let total = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM tags t
+            WHERE (? IS NULL OR t.name LIKE ? ESCAPE '\')
+            "#,
+        )
+        .bind(&pattern)
+        .bind(&pattern)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        record_rows(tags.len());
+        Span::current().record("total", total);
+        Ok((tags, total))
+    }
+
+    /// Suggests tags whose name starts with `prefix`, ignoring case.
+    /// The most used tags come first, then tags are ordered by name.
+    #[instrument(
+        name = "tags.suggest",
+        skip_all,
+        fields(prefix = %prefix, limit = limit, rows),
+        err(level = "info")
+    )]
+    pub async fn suggest(&self, prefix: &str, limit: i64) -> DatabaseResult<Vec<TagWithPostCount>> {
+        let _timer = self.metrics.time(
+            "tags.suggest",
+            format!("prefix={:?} limit={}", prefix, limit),
+        );
+        if limit <= 0 || limit > 100 {
+            return Err(DatabaseError::invalid_field(
+                "limit",
+                "Limit must be between 1 and 100",
+            ));
+        }
+
+        sqlx::query_as(
             r#"
-            SELECT 
+            SELECT
                 t.*,
                 COUNT(pt.post_id) as post_count
             FROM tags t
             LEFT JOIN post_tags pt ON t.id = pt.tag_id
+            WHERE t.name LIKE ? ESCAPE '\'
             GROUP BY t.id
-            ORDER BY t.name
-            "#
-        } else {
+            ORDER BY post_count DESC, t.name, t.id
+            LIMIT ?
+            "#,
+        )
+        .bind(format!("{}%", escape_like(prefix)))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)
+        .inspect(|tags| record_rows(tags.len()))
+    }
+
+    /// Lists the most used tags, most posts first and ties broken by name.
+    /// With `published_only`, drafts don't count towards a tag's usage.
+    /// Tags without any counted posts are left out.
+    #[instrument(
+        name = "tags.popular",
+        skip_all,
+        fields(published_only = published_only, limit = limit, rows),
+        err(level = "info")
+    )]
+    pub async fn popular(
+        &self,
+        published_only: bool,
+        limit: i64,
+    ) -> DatabaseResult<Vec<TagWithPostCount>> {
+        let _timer = self.metrics.time(
+            "tags.popular",
+            format!("published_only={} limit={}", published_only, limit),
+        );
+        if limit <= 0 || limit > 100 {
+            return Err(DatabaseError::invalid_field(
+                "limit",
+                "Limit must be between 1 and 100",
+            ));
+        }
+
+        sqlx::query_as(
             r#"
-            SELECT 
+            SELECT
                 t.*,
-                0 as post_count
+                COUNT(p.id) as post_count
             FROM tags t
-            ORDER BY t.name
-            "#
-        };
+            JOIN post_tags pt ON t.id = pt.tag_id
+            JOIN posts p ON p.id = pt.post_id
+            WHERE (? = FALSE OR p.published = TRUE)
+            GROUP BY t.id
+            ORDER BY post_count DESC, t.name, t.id
+            LIMIT ?
+            "#,
+        )
+        .bind(published_only)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)
+        .inspect(|tags| record_rows(tags.len()))
+    }
 
-        sqlx::query_as(query)
-            .fetch_all(&self.pool)
-            .await
-            .map_err(DatabaseError::Sqlx)
+    /// Lists the tags that appear on the same posts as the given tag, most
+    /// shared posts first and ties broken by name. Tags without any shared
+    /// post are left out.
+    #[instrument(
+        name = "tags.related",
+        skip_all,
+        fields(tag.id = tag_id, limit = limit, rows),
+        err(level = "info")
+    )]
+    pub async fn related(&self, tag_id: i64, limit: i64) -> DatabaseResult<Vec<RelatedTag>> {
+        let _timer = self
+            .metrics
+            .time("tags.related", format!("tag_id={} limit={}", tag_id, limit));
+        if limit <= 0 || limit > 100 {
+            return Err(DatabaseError::invalid_field(
+                "limit",
+                "Limit must be between 1 and 100",
+            ));
+        }
+
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+
+        if !Self::tag_exists(&mut tx, tag_id).await? {
+            return Err(DatabaseError::not_found("Tag", &tag_id.to_string()));
+        }
+
+        let tags = sqlx::query_as(
+            r#"
+            SELECT
+                t.*,
+                COUNT(*) as shared_post_count
+            FROM post_tags source
+            JOIN post_tags other
+                ON other.post_id = source.post_id AND other.tag_id != source.tag_id
+            JOIN tags t ON t.id = other.tag_id
+            WHERE source.tag_id = ?
+            GROUP BY t.id
+            ORDER BY shared_post_count DESC, t.name, t.id
+            LIMIT ?
+            "#,
+        )
+        .bind(tag_id)
+        .bind(limit)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        record_rows(tags.len());
+        Ok(tags)
+    }
+
+    /// Replaces a tag's name, description and color
+    pub async fn update(&self, id: i64, request: &TagRequest) -> DatabaseResult<Tag> {
+        let _timer = self.metrics.time("tags.update", format!("id={}", id));
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+        let tag = self.update_in(&mut tx, id, request).await?;
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok(tag)
     }
 
-    /// Updates a tag's name
-    pub async fn update(&self, id: i64, new_name: &str) -> DatabaseResult<Tag> {
+    /// Like `update`, but runs on the caller's connection so it can take
+    /// part in a larger transaction
+    #[instrument(
+        name = "tags.update_in",
+        skip_all,
+        fields(tag.id = id, tag.name = %request.name),
+        err(level = "info")
+    )]
+    pub async fn update_in(
+        &self,
+        conn: &mut SqliteConnection,
+        id: i64,
+        request: &TagRequest,
+    ) -> DatabaseResult<Tag> {
+        let request = request.normalized(self.lowercase_names);
         // Validate tag name
-        if new_name.trim().is_empty() {
-            return Err(DatabaseError::validation("Tag name cannot be empty"));
+        if request.name.is_empty() {
+            return Err(DatabaseError::invalid_field(
+                "name",
+                "Tag name cannot be empty",
+            ));
         }
+        request.validate().map_err(DatabaseError::from)?;
 
-        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+        let color = request.color.as_deref().map(normalize_color);
 
-        let trimmed_new_name = new_name.trim();
+        let current = Self::find_in(conn, id).await?;
+        let slug = Self::available_slug(conn, &request.name, Some(id)).await?;
 
         let updated_tag = sqlx::query_as!(
             Tag,
             r#"
             UPDATE tags
-            SET name = ?
+            SET name = ?, slug = ?, description = ?, color = ?
             WHERE id = ?
             RETURNING *
             "#,
-            trimmed_new_name,
+            request.name,
+            slug,
+            request.description,
+            color,
             id
         )
-        .fetch_optional(&mut *tx)
+        .fetch_one(&mut *conn)
         .await
-        .map_err(|e| match e {
-            sqlx::Error::Database(e) if e.message().contains("UNIQUE constraint") => {
-                DatabaseError::duplicate("Tag", new_name)
-            }
-            e => DatabaseError::Sqlx(e),
-        })?
-        .ok_or_else(|| DatabaseError::not_found("Tag", &id.to_string()))?;
+        .map_err(DatabaseError::on_duplicate("Tag", &request.name))?;
+        Self::record_slug_change(conn, id, &current.slug, &updated_tag.slug).await?;
+
+        let event = AuditEvent::changed(AuditAction::Update, &current.snapshot(), &updated_tag);
+        AuditRepository::record_in(conn, self.actor, &event).await?;
+
+        Ok(updated_tag)
+    }
 
+    /// Partially updates a tag, only modifying provided fields
+    pub async fn patch(&self, id: i64, patch: PatchTag) -> DatabaseResult<Tag> {
+        let _timer = self.metrics.time("tags.patch", format!("id={}", id));
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+        let tag = self.patch_in(&mut tx, id, patch).await?;
         tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok(tag)
+    }
+
+    /// Like `patch`, but runs on the caller's connection so it can take
+    /// part in a larger transaction
+    #[instrument(name = "tags.patch_in", skip_all, fields(tag.id = id), err(level = "info"))]
+    pub async fn patch_in(
+        &self,
+        conn: &mut SqliteConnection,
+        id: i64,
+        mut patch: PatchTag,
+    ) -> DatabaseResult<Tag> {
+        patch.name = patch
+            .name
+            .map(|name| normalize_tag_name(&name, self.lowercase_names));
+        patch.validate().map_err(DatabaseError::from)?;
+
+        let current = Self::find_in(conn, id).await?;
+        let before = current.snapshot();
+
+        let name = patch.name.as_deref().unwrap_or(&current.name);
+        let description = patch.description.unwrap_or(current.description);
+        let color = patch
+            .color
+            .map(|color| color.as_deref().map(normalize_color))
+            .unwrap_or(current.color);
+        let slug = Self::available_slug(conn, name, Some(id)).await?;
+
+        let updated_tag = sqlx::query_as!(
+            Tag,
+            r#"
+            UPDATE tags
+            SET name = ?, slug = ?, description = ?, color = ?
+            WHERE id = ?
+            RETURNING *
+            "#,
+            name,
+            slug,
+            description,
+            color,
+            id
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(DatabaseError::on_duplicate("Tag", name))?;
+        Self::record_slug_change(conn, id, &current.slug, &updated_tag.slug).await?;
+
+        let event = AuditEvent::changed(AuditAction::Patch, &before, &updated_tag);
+        AuditRepository::record_in(conn, self.actor, &event).await?;
+
         Ok(updated_tag)
     }
 
@@ -157,30 +814,129 @@ impl TagRepository {
     /// This will also remove all associations between this tag and any posts
     /// due to the ON DELETE CASCADE constraint
     pub async fn delete(&self, id: i64) -> DatabaseResult<()> {
+        let _timer = self.metrics.time("tags.delete", format!("id={}", id));
         let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+        self.delete_in(&mut tx, id).await?;
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok(())
+    }
 
-        let result = sqlx::query!(
+    /// Like `delete`, but runs on the caller's connection so it can take
+    /// part in a larger transaction
+    #[instrument(name = "tags.delete_in", skip_all, fields(tag.id = id), err(level = "info"))]
+    pub async fn delete_in(&self, conn: &mut SqliteConnection, id: i64) -> DatabaseResult<()> {
+        // Keep the tag's last state for the audit log
+        let tag = Self::find_in(conn, id).await?;
+
+        sqlx::query!(
             r#"
             DELETE FROM tags
             WHERE id = ?
             "#,
             id
         )
-        .execute(&mut *tx)
+        .execute(&mut *conn)
         .await
         .map_err(DatabaseError::Sqlx)?;
 
-        if result.rows_affected() == 0 {
-            return Err(DatabaseError::not_found("Tag", &id.to_string()));
-        }
+        AuditRepository::record_in(conn, self.actor, &AuditEvent::deleted(&tag)).await?;
 
-        tx.commit().await.map_err(DatabaseError::Sqlx)?;
         Ok(())
     }
 
+    /// Deletes every tag that isn't attached to any post, returning the
+    /// names of the deleted tags. With `dry_run`, nothing is deleted and the
+    /// names of the tags that would be deleted are returned instead.
+    #[instrument(
+        name = "tags.delete_unused",
+        skip_all,
+        fields(dry_run = dry_run, rows),
+        err(level = "info")
+    )]
+    pub async fn delete_unused(&self, dry_run: bool) -> DatabaseResult<Vec<String>> {
+        let _timer = self
+            .metrics
+            .time("tags.delete_unused", format!("dry_run={}", dry_run));
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+
+        let names = if dry_run {
+            sqlx::query_scalar!(
+                r#"
+                SELECT name
+                FROM tags t
+                WHERE NOT EXISTS (SELECT 1 FROM post_tags pt WHERE pt.tag_id = t.id)
+                ORDER BY name
+                "#
+            )
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(DatabaseError::Sqlx)?
+        } else {
+            let deleted = sqlx::query_as!(
+                Tag,
+                r#"
+                DELETE FROM tags
+                WHERE NOT EXISTS (SELECT 1 FROM post_tags pt WHERE pt.tag_id = tags.id)
+                RETURNING *
+                "#
+            )
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+
+            let mut names = Vec::with_capacity(deleted.len());
+            for tag in deleted {
+                AuditRepository::record_in(&mut tx, self.actor, &AuditEvent::deleted(&tag)).await?;
+                names.push(tag.name);
+            }
+            names.sort();
+            names
+        };
+
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        record_rows(names.len());
+        Ok(names)
+    }
+
     /// Associates a tag with a post
+    /// Returns a NotFound error naming whichever of the post and tag is missing
     pub async fn add_tag_to_post(&self, post_id: i64, tag_id: i64) -> DatabaseResult<()> {
+        let _timer = self.metrics.time(
+            "tags.add_tag_to_post",
+            format!("post_id={} tag_id={}", post_id, tag_id),
+        );
         let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+        Self::add_tag_to_post_in(&mut tx, post_id, tag_id).await?;
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok(())
+    }
+
+    /// Like `add_tag_to_post`, but runs on the caller's connection so it
+    /// can take part in a larger transaction
+    #[instrument(
+        name = "tags.add_tag_to_post_in",
+        skip_all,
+        fields(post.id = post_id, tag.id = tag_id),
+        err(level = "info")
+    )]
+    pub async fn add_tag_to_post_in(
+        conn: &mut SqliteConnection,
+        post_id: i64,
+        tag_id: i64,
+    ) -> DatabaseResult<()> {
+        match (
+            Self::post_exists(conn, post_id).await?,
+            Self::tag_exists(conn, tag_id).await?,
+        ) {
+            (true, true) => {}
+            (false, true) => return Err(DatabaseError::not_found("Post", &post_id.to_string())),
+            (true, false) => return Err(DatabaseError::not_found("Tag", &tag_id.to_string())),
+            (false, false) => {
+                return Err(DatabaseError::NotFound(format!(
+                    "Post with identifier '{post_id}' and Tag with identifier '{tag_id}' not found"
+                )))
+            }
+        }
 
         sqlx::query!(
             r#"
@@ -190,26 +946,44 @@ impl TagRepository {
             post_id,
             tag_id
         )
-        .execute(&mut *tx)
+        .execute(&mut *conn)
         .await
-        .map_err(|e| match e {
-            sqlx::Error::Database(e) if e.message().contains("FOREIGN KEY constraint") => {
+        .map_err(|e| match constraint_violation(&e) {
+            // Safety net in case a row was deleted after the checks above
+            Some(ConstraintViolation::ForeignKey) => {
                 DatabaseError::not_found("Post or Tag", &format!("{post_id}, {tag_id}"))
             }
-            sqlx::Error::Database(e) if e.message().contains("UNIQUE constraint") => {
-                DatabaseError::duplicate("Tag association", &format!("{post_id}, {tag_id}"))
-            }
-            e => DatabaseError::Sqlx(e),
+            _ => DatabaseError::on_duplicate("Tag association", &format!("{post_id}, {tag_id}"))(e),
         })?;
 
-        tx.commit().await.map_err(DatabaseError::Sqlx)?;
         Ok(())
     }
 
     /// Removes a tag association from a post
     pub async fn remove_tag_from_post(&self, post_id: i64, tag_id: i64) -> DatabaseResult<()> {
+        let _timer = self.metrics.time(
+            "tags.remove_tag_from_post",
+            format!("post_id={} tag_id={}", post_id, tag_id),
+        );
         let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+        Self::remove_tag_from_post_in(&mut tx, post_id, tag_id).await?;
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok(())
+    }
 
+    /// Like `remove_tag_from_post`, but runs on the caller's connection so
+    /// it can take part in a larger transaction
+    #[instrument(
+        name = "tags.remove_tag_from_post_in",
+        skip_all,
+        fields(post.id = post_id, tag.id = tag_id),
+        err(level = "info")
+    )]
+    pub async fn remove_tag_from_post_in(
+        conn: &mut SqliteConnection,
+        post_id: i64,
+        tag_id: i64,
+    ) -> DatabaseResult<()> {
         let result = sqlx::query!(
             r#"
             DELETE FROM post_tags
@@ -218,7 +992,7 @@ impl TagRepository {
             post_id,
             tag_id
         )
-        .execute(&mut *tx)
+        .execute(&mut *conn)
         .await
         .map_err(DatabaseError::Sqlx)?;
 
@@ -229,30 +1003,284 @@ impl TagRepository {
             ));
         }
 
-        tx.commit().await.map_err(DatabaseError::Sqlx)?;
         Ok(())
     }
 
     /// Lists all tags for a specific post
+    /// Returns NotFound if the post doesn't exist, so an empty list always
+    /// means the post has no tags
+    #[instrument(
+        name = "tags.list_tags_for_post",
+        skip_all,
+        fields(post.id = post_id, rows),
+        err(level = "info")
+    )]
     pub async fn list_tags_for_post(&self, post_id: i64) -> DatabaseResult<Vec<Tag>> {
-        sqlx::query_as!(
-            Tag,
-            r#"
-        SELECT 
-            t.id as "id!",
-            t.name as "name!",
-            t.created_at as "created_at!"
-        FROM tags t
-        JOIN post_tags pt ON t.id = pt.tag_id
-        WHERE pt.post_id = ?
+        let _timer = self
+            .metrics
+            .time("tags.list_tags_for_post", format!("post_id={}", post_id));
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+
+        Self::ensure_post_exists(&mut tx, post_id).await?;
+        let tags = Self::tags_for_post(&mut tx, post_id).await?;
+
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        record_rows(tags.len());
+        Ok(tags)
+    }
+
+    /// Lists the tags of several posts in one query, by post ID. Posts
+    /// without tags, or that don't exist, are left out.
+    #[instrument(
+        name = "tags.list_tags_for_posts",
+        skip_all,
+        fields(posts = post_ids.len(), rows),
+        err(level = "info")
+    )]
+    pub async fn list_tags_for_posts(
+        &self,
+        post_ids: &[i64],
+    ) -> DatabaseResult<HashMap<i64, Vec<Tag>>> {
+        let _timer = self.metrics.time(
+            "tags.list_tags_for_posts",
+            format!("posts={}", post_ids.len()),
+        );
+        #[derive(sqlx::FromRow)]
+        struct PostTag {
+            post_id: i64,
+            #[sqlx(flatten)]
+            tag: Tag,
+        }
+
+        // SQLite can't bind arrays, so the ids are passed as a JSON array
+        let ids = serde_json::to_string(post_ids).expect("integers serialize to JSON");
+        let rows: Vec<PostTag> = sqlx::query_as(
+            r#"
+            SELECT pt.post_id, t.*
+            FROM tags t
+            JOIN post_tags pt ON t.id = pt.tag_id
+            WHERE pt.post_id IN (SELECT value FROM json_each(?))
+            ORDER BY t.name
+            "#,
+        )
+        .bind(&ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        record_rows(rows.len());
+        let mut tags: HashMap<i64, Vec<Tag>> = HashMap::new();
+        for row in rows {
+            tags.entry(row.post_id).or_default().push(row.tag);
+        }
+        Ok(tags)
+    }
+
+    /// Replaces the tags on a post with exactly `tag_ids`, returning the
+    /// resulting tags. An empty list removes all tags from the post.
+    /// Fails without changing anything if the post or any tag doesn't exist.
+    pub async fn set_tags_for_post(
+        &self,
+        post_id: i64,
+        tag_ids: &[i64],
+    ) -> DatabaseResult<Vec<Tag>> {
+        let _timer = self.metrics.time(
+            "tags.set_tags_for_post",
+            format!("post_id={} tags={}", post_id, tag_ids.len()),
+        );
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+        let tags = Self::set_tags_for_post_in(&mut tx, post_id, tag_ids).await?;
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok(tags)
+    }
+
+    /// Like `set_tags_for_post`, but runs on the caller's connection so it
+    /// can take part in a larger transaction
+    #[instrument(
+        name = "tags.set_tags_for_post_in",
+        skip_all,
+        fields(post.id = post_id, tags = tag_ids.len(), rows),
+        err(level = "info")
+    )]
+    pub async fn set_tags_for_post_in(
+        conn: &mut SqliteConnection,
+        post_id: i64,
+        tag_ids: &[i64],
+    ) -> DatabaseResult<Vec<Tag>> {
+        Self::ensure_post_exists(conn, post_id).await?;
+
+        // SQLite can't bind arrays, so the ids are passed as a JSON array
+        // and expanded with json_each
+        let ids = serde_json::to_string(tag_ids).expect("integers serialize to JSON");
+
+        Self::ensure_tags_exist(conn, &ids).await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM post_tags
+            WHERE post_id = ? AND tag_id NOT IN (SELECT value FROM json_each(?))
+            "#,
+        )
+        .bind(post_id)
+        .bind(&ids)
+        .execute(&mut *conn)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO post_tags (post_id, tag_id)
+            SELECT ?, value FROM json_each(?)
+            "#,
+        )
+        .bind(post_id)
+        .bind(&ids)
+        .execute(&mut *conn)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        let tags = Self::tags_for_post(conn, post_id).await?;
+        record_rows(tags.len());
+        Ok(tags)
+    }
+
+    /// Attaches every tag in `tag_ids` to a post with a single statement,
+    /// returning the IDs of the tags that were already attached. Fails
+    /// without changing anything if the post or any tag doesn't exist.
+    pub async fn add_tags_to_post(
+        &self,
+        post_id: i64,
+        tag_ids: &[i64],
+    ) -> DatabaseResult<Vec<i64>> {
+        let _timer = self.metrics.time(
+            "tags.add_tags_to_post",
+            format!("post_id={} tags={}", post_id, tag_ids.len()),
+        );
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+        let already_attached = Self::add_tags_to_post_in(&mut tx, post_id, tag_ids).await?;
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok(already_attached)
+    }
+
+    /// Like `add_tags_to_post`, but runs on the caller's connection so it
+    /// can take part in a larger transaction
+    #[instrument(
+        name = "tags.add_tags_to_post_in",
+        skip_all,
+        fields(post.id = post_id, tags = tag_ids.len(), rows),
+        err(level = "info")
+    )]
+    pub async fn add_tags_to_post_in(
+        conn: &mut SqliteConnection,
+        post_id: i64,
+        tag_ids: &[i64],
+    ) -> DatabaseResult<Vec<i64>> {
+        Self::ensure_post_exists(conn, post_id).await?;
+
+        let mut requested = tag_ids.to_vec();
+        requested.sort_unstable();
+        requested.dedup();
+        let ids = serde_json::to_string(&requested).expect("integers serialize to JSON");
+        Self::ensure_tags_exist(conn, &ids).await?;
+
+        // Rows skipped by OR IGNORE aren't returned, so whatever is missing
+        // from the result was attached before
+        let inserted: Vec<i64> = sqlx::query_scalar(
+            r#"
+            INSERT OR IGNORE INTO post_tags (post_id, tag_id)
+            SELECT ?, value FROM json_each(?)
+            RETURNING tag_id
+            "#,
+        )
+        .bind(post_id)
+        .bind(&ids)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+
+        record_rows(inserted.len());
+        Ok(requested
+            .into_iter()
+            .filter(|id| !inserted.contains(id))
+            .collect())
+    }
+
+    /// Checks that every tag in `ids`, a JSON array, exists, naming the
+    /// first one that doesn't
+    async fn ensure_tags_exist(conn: &mut SqliteConnection, ids: &str) -> DatabaseResult<()> {
+        let missing = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT ids.value
+            FROM json_each(?) ids
+            WHERE NOT EXISTS (SELECT 1 FROM tags t WHERE t.id = ids.value)
+            LIMIT 1
+            "#,
+        )
+        .bind(ids)
+        .fetch_optional(conn)
+        .await
+        .map_err(DatabaseError::Sqlx)?;
+        match missing {
+            Some(tag_id) => Err(DatabaseError::not_found("Tag", &tag_id.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    async fn tags_for_post(conn: &mut SqliteConnection, post_id: i64) -> DatabaseResult<Vec<Tag>> {
+        sqlx::query_as!(
+            Tag,
+            r#"
+        SELECT 
+            t.id as "id!",
+            t.name as "name!",
+            t.slug as "slug!",
+            t.created_at as "created_at!",
+            t.description,
+            t.color
+        FROM tags t
+        JOIN post_tags pt ON t.id = pt.tag_id
+        WHERE pt.post_id = ?
         ORDER BY t.name
         "#,
             post_id
         )
-        .fetch_all(&self.pool)
+        .fetch_all(conn)
         .await
         .map_err(DatabaseError::Sqlx)
     }
+
+    /// Checks that a post exists, for operations on its subresources
+    async fn ensure_post_exists(conn: &mut SqliteConnection, post_id: i64) -> DatabaseResult<()> {
+        if Self::post_exists(conn, post_id).await? {
+            Ok(())
+        } else {
+            Err(DatabaseError::not_found("Post", &post_id.to_string()))
+        }
+    }
+
+    async fn post_exists(conn: &mut SqliteConnection, post_id: i64) -> DatabaseResult<bool> {
+        let row = sqlx::query_scalar!("SELECT 1 FROM posts WHERE id = ?", post_id)
+            .fetch_optional(conn)
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+        Ok(row.is_some())
+    }
+
+    async fn tag_exists(conn: &mut SqliteConnection, tag_id: i64) -> DatabaseResult<bool> {
+        let row = sqlx::query_scalar!("SELECT 1 FROM tags WHERE id = ?", tag_id)
+            .fetch_optional(conn)
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+        Ok(row.is_some())
+    }
+}
+
+/// Escapes LIKE wildcards so user input only matches literally
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
 }
 
 #[cfg(test)]
@@ -260,7 +1288,7 @@ mod tests {
     use super::*;
     use crate::{
         db::{test_utils::create_test_db, Database},
-        models::post::{CreatePost, PostCategory},
+        models::{post::CreatePost, tag::TagSort},
     };
 
     async fn setup() -> (Database, TagRepository) {
@@ -283,12 +1311,22 @@ mod tests {
         let duplicate = repo.create("rust").await;
         assert!(matches!(
             duplicate.unwrap_err(),
-            DatabaseError::DuplicateEntry(_)
+            DatabaseError::DuplicateEntry { .. }
         ));
 
+        // Test duplicate differing only in case
+        assert!(matches!(
+            repo.create("Rust").await.unwrap_err(),
+            DatabaseError::DuplicateEntry { .. }
+        ));
+        assert_eq!(repo.find_by_name("RUST").await.unwrap().id, tag.id);
+
         // Test empty tag name
         let empty = repo.create("").await;
-        assert!(matches!(empty.unwrap_err(), DatabaseError::Validation(_)));
+        assert!(matches!(
+            empty.unwrap_err(),
+            DatabaseError::Validation { .. }
+        ));
 
         // Test whitespace handling
         let trimmed = repo.create("  python  ").await.unwrap();
@@ -328,6 +1366,160 @@ mod tests {
         assert!(matches!(not_found.unwrap_err(), DatabaseError::NotFound(_)));
     }
 
+    #[tokio::test]
+    async fn test_tag_slugs() {
+        let (_, repo) = setup().await;
+
+        let cpp = repo.create("C++").await.unwrap();
+        assert_eq!(cpp.slug, "c-plus-plus");
+        let front_end = repo.create("Front End").await.unwrap();
+        assert_eq!(front_end.slug, "front-end");
+
+        // Names sharing a slug get numbered ones, including via get-or-create
+        let spelled = repo.create("c plus plus").await.unwrap();
+        assert_eq!(spelled.slug, "c-plus-plus-2");
+        let (dashed, created) = repo
+            .get_or_create(&TagRequest::named("C-Plus-Plus"))
+            .await
+            .unwrap();
+        assert!(created);
+        assert_eq!(dashed.slug, "c-plus-plus-3");
+
+        for tag in [&cpp, &front_end, &spelled] {
+            assert_eq!(repo.find_by_slug(&tag.slug).await.unwrap().id, tag.id);
+        }
+        assert_eq!(
+            repo.find_by_slug("Front-End").await.unwrap().id,
+            front_end.id
+        );
+        assert!(matches!(
+            repo.find_by_slug("c++").await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+
+        // Renaming regenerates the slug, and a tag keeps its own slug when
+        // the new name maps to it
+        let renamed = repo
+            .update(front_end.id, &TagRequest::named("Frontend"))
+            .await
+            .unwrap();
+        assert_eq!(renamed.slug, "frontend");
+        assert!(repo.find_by_slug("front-end").await.is_err());
+        let patched = repo
+            .patch(
+                cpp.id,
+                PatchTag {
+                    name: Some("c++".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(patched.slug, "c-plus-plus");
+
+        // The freed slug can be taken again
+        assert_eq!(repo.create("front_end").await.unwrap().slug, "front-end");
+    }
+
+    #[tokio::test]
+    async fn test_tag_slug_history() {
+        let (_, repo) = setup().await;
+
+        let tag = repo.create("Front End").await.unwrap();
+        repo.update(tag.id, &TagRequest::named("Frontend"))
+            .await
+            .unwrap();
+        repo.patch(
+            tag.id,
+            PatchTag {
+                name: Some("UI".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        // Every earlier slug points at the current one
+        for slug in ["front-end", "Frontend"] {
+            assert!(matches!(
+                repo.resolve_slug(slug).await.unwrap(),
+                TagSlugLookup::Moved(current) if current == "ui"
+            ));
+        }
+        assert!(matches!(
+            repo.resolve_slug("ui").await.unwrap(),
+            TagSlugLookup::Found(found) if found.id == tag.id
+        ));
+
+        // A tag taking an old slug takes it over from the history
+        let other = repo.create("front-end").await.unwrap();
+        assert!(matches!(
+            repo.resolve_slug("front-end").await.unwrap(),
+            TagSlugLookup::Found(found) if found.id == other.id
+        ));
+
+        // Deleting the tag forgets its old slugs
+        repo.delete(tag.id).await.unwrap();
+        assert!(matches!(
+            repo.resolve_slug("frontend").await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tag_names_are_normalized() {
+        let (_, repo) = setup().await;
+
+        let tag = repo.create("  Front   End ").await.unwrap();
+        assert_eq!(tag.name, "Front End");
+
+        // Lookups normalize their input the same way
+        for name in ["Front End", "front  end", "\tFRONT\nEND "] {
+            assert_eq!(
+                repo.find_by_name(name).await.unwrap().id,
+                tag.id,
+                "{:?}",
+                name
+            );
+        }
+
+        // Whitespace variants are duplicates, and get_or_create finds the tag
+        assert!(matches!(
+            repo.create("front  end").await.unwrap_err(),
+            DatabaseError::DuplicateEntry { .. }
+        ));
+        let (found, created) = repo
+            .get_or_create(&TagRequest::named("Front    End"))
+            .await
+            .unwrap();
+        assert_eq!((found.id, created), (tag.id, false));
+
+        // Updates and patches store the normalized name too
+        let updated = repo
+            .update(tag.id, &TagRequest::named(" Web  Dev"))
+            .await
+            .unwrap();
+        assert_eq!(updated.name, "Web Dev");
+        let patched = repo
+            .patch(
+                tag.id,
+                PatchTag {
+                    name: Some("Web   Development ".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(patched.name, "Web Development");
+
+        // Lowercasing is up to the configuration
+        let lowercase = repo.clone().with_lowercase_names(true);
+        let tag = lowercase.create("Back  End").await.unwrap();
+        assert_eq!(tag.name, "back end");
+        assert_eq!(lowercase.find_by_name("BACK END").await.unwrap().id, tag.id);
+        assert_eq!(repo.find_by_name("Back End").await.unwrap().id, tag.id);
+    }
+
     #[tokio::test]
     async fn test_list_tags() {
         let (_, repo) = setup().await;
@@ -337,13 +1529,116 @@ mod tests {
         repo.create("tag2").await.unwrap();
 
         // Test listing without post count
-        let tags = repo.list(false).await.unwrap();
+        let (tags, total) = repo.list(&TagListOptions::default()).await.unwrap();
         assert_eq!(tags.len(), 2);
+        assert_eq!(total, 2);
         assert_eq!(tags[0].post_count, 0);
 
         // Test listing with post count
-        let tags_with_count = repo.list(true).await.unwrap();
+        let (tags_with_count, _) = repo
+            .list(&TagListOptions {
+                include_post_count: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
         assert_eq!(tags_with_count.len(), 2);
+
+        // Test limit validation
+        assert!(matches!(
+            repo.list(&TagListOptions {
+                limit: 201,
+                ..Default::default()
+            })
+            .await
+            .unwrap_err(),
+            DatabaseError::Validation { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_tags_sorting_and_search() {
+        let (db, repo) = setup().await;
+
+        let post = db
+            .posts()
+            .create(
+                CreatePost::builder()
+                    .title("Test Post")
+                    .slug("test-post")
+                    .content("Test content")
+                    .description("Test description")
+                    .published(true)
+                    .tag("web-dev")
+                    .build()
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let backend = repo.create("backend-dev").await.unwrap();
+        repo.create("gamedev").await.unwrap();
+        repo.create("rust").await.unwrap();
+        repo.add_tag_to_post(post.id, backend.id).await.unwrap();
+
+        // Give one tag a later creation time so the sort is deterministic
+        sqlx::query("UPDATE tags SET created_at = datetime('now', '+1 day') WHERE name = 'rust'")
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        let names = |options: TagListOptions| {
+            let repo = repo.clone();
+            async move {
+                let (tags, total) = repo.list(&options).await.unwrap();
+                let names: Vec<_> = tags.into_iter().map(|t| t.name).collect();
+                (names, total)
+            }
+        };
+
+        assert_eq!(
+            names(TagListOptions::default()).await,
+            (
+                vec![
+                    "backend-dev".into(),
+                    "gamedev".into(),
+                    "rust".into(),
+                    "web-dev".into()
+                ],
+                4
+            )
+        );
+        assert_eq!(
+            names(TagListOptions {
+                sort: TagSort::PostCount,
+                ..Default::default()
+            })
+            .await
+            .0,
+            vec!["backend-dev", "web-dev", "gamedev", "rust"]
+        );
+        assert_eq!(
+            names(TagListOptions {
+                sort: TagSort::CreatedAt,
+                limit: 1,
+                ..Default::default()
+            })
+            .await
+            .0,
+            vec!["rust"]
+        );
+
+        // Search is case-insensitive and the total ignores pagination
+        assert_eq!(
+            names(TagListOptions {
+                search: Some("DEV".to_string()),
+                limit: 2,
+                offset: 1,
+                ..Default::default()
+            })
+            .await,
+            (vec!["gamedev".into(), "web-dev".into()], 3)
+        );
     }
 
     #[tokio::test]
@@ -354,21 +1649,216 @@ mod tests {
         let tag = repo.create("initial").await.unwrap();
 
         // Test successful update
-        let updated = repo.update(tag.id, "updated").await;
-        assert!(updated.is_ok());
-        assert_eq!(updated.unwrap().name, "updated");
+        let updated = repo
+            .update(
+                tag.id,
+                &TagRequest {
+                    description: Some("Updated description".to_string()),
+                    color: Some("#00ff00".to_string()),
+                    ..TagRequest::named("updated")
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.name, "updated");
+        assert_eq!(updated.description.as_deref(), Some("Updated description"));
+        assert_eq!(updated.color.as_deref(), Some("#00ff00"));
+
+        // Replacing without details clears them
+        let cleared = repo
+            .update(tag.id, &TagRequest::named("updated"))
+            .await
+            .unwrap();
+        assert_eq!(cleared.description, None);
+        assert_eq!(cleared.color, None);
+
+        // Test invalid color
+        let invalid = repo
+            .update(
+                tag.id,
+                &TagRequest {
+                    color: Some("blue".to_string()),
+                    ..TagRequest::named("updated")
+                },
+            )
+            .await;
+        assert!(matches!(
+            invalid.unwrap_err(),
+            DatabaseError::Validation {
+                field: Some("color"),
+                ..
+            }
+        ));
 
         // Test non-existent ID
-        let not_found = repo.update(999, "test").await;
+        let not_found = repo.update(999, &TagRequest::named("test")).await;
         assert!(matches!(not_found.unwrap_err(), DatabaseError::NotFound(_)));
 
         // Test duplicate name
         repo.create("existing").await.unwrap();
-        let duplicate = repo.update(tag.id, "existing").await;
+        let duplicate = repo.update(tag.id, &TagRequest::named("existing")).await;
+        assert!(matches!(
+            duplicate.unwrap_err(),
+            DatabaseError::DuplicateEntry { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_related_tags() {
+        let (db, repo) = setup().await;
+
+        // rust appears with axum on three posts, with tokio on two and with
+        // sqlx on one; go never appears alongside rust
+        let graph: [&[&str]; 5] = [
+            &["rust", "axum", "tokio"],
+            &["rust", "axum", "tokio", "sqlx"],
+            &["rust", "axum"],
+            &["axum", "sqlx"],
+            &["go"],
+        ];
+        for (i, names) in graph.iter().enumerate() {
+            db.posts()
+                .create(
+                    CreatePost::builder()
+                        .title("Test Post")
+                        .slug(format!("post-{}", i))
+                        .content("Test content")
+                        .description("Test description")
+                        .published(true)
+                        .tags(names.iter().copied())
+                        .build()
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let rust = repo.find_by_name("rust").await.unwrap();
+        let related = repo.related(rust.id, 5).await.unwrap();
+        let ranking: Vec<_> = related
+            .iter()
+            .map(|t| (t.name.as_str(), t.shared_post_count))
+            .collect();
+        assert_eq!(ranking, vec![("axum", 3), ("tokio", 2), ("sqlx", 1)]);
+
+        let top = repo.related(rust.id, 1).await.unwrap();
+        assert_eq!(top[0].name, "axum");
+
+        // A tag without posts has no related tags
+        let lonely = repo.create("lonely").await.unwrap();
+        assert!(repo.related(lonely.id, 5).await.unwrap().is_empty());
+
+        assert!(matches!(
+            repo.related(999, 5).await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+        assert!(matches!(
+            repo.related(rust.id, 0).await.unwrap_err(),
+            DatabaseError::Validation { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_patch_tag() {
+        let (_, repo) = setup().await;
+
+        let tag = repo
+            .create_with_details(&TagRequest {
+                description: Some("Posts about systems".to_string()),
+                color: Some("#1A2B3C".to_string()),
+                ..TagRequest::named("initial")
+            })
+            .await
+            .unwrap();
+        assert_eq!(tag.color.as_deref(), Some("#1a2b3c"));
+
+        // An empty patch leaves the tag untouched
+        let unchanged = repo.patch(tag.id, PatchTag::default()).await.unwrap();
+        assert_eq!(unchanged.name, "initial");
+        assert_eq!(unchanged.created_at, tag.created_at);
+        assert_eq!(unchanged.description, tag.description);
+        assert_eq!(unchanged.color, tag.color);
+
+        // Changing one field keeps the others, and null clears a field
+        let recolored = repo
+            .patch(
+                tag.id,
+                PatchTag {
+                    color: Some(Some("#FFFFFF".to_string())),
+                    description: Some(None),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(recolored.name, "initial");
+        assert_eq!(recolored.description, None);
+        assert_eq!(recolored.color.as_deref(), Some("#ffffff"));
+
+        let invalid_color = repo
+            .patch(
+                tag.id,
+                PatchTag {
+                    color: Some(Some("#fff".to_string())),
+                    ..Default::default()
+                },
+            )
+            .await;
+        assert!(matches!(
+            invalid_color.unwrap_err(),
+            DatabaseError::Validation {
+                field: Some("color"),
+                ..
+            }
+        ));
+
+        let renamed = repo
+            .patch(
+                tag.id,
+                PatchTag {
+                    name: Some(" renamed ".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(renamed.name, "renamed");
+
+        // Invalid names are rejected before touching the database
+        let invalid = repo
+            .patch(
+                tag.id,
+                PatchTag {
+                    name: Some("bad/name".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await;
+        assert!(matches!(
+            invalid.unwrap_err(),
+            DatabaseError::Validation {
+                field: Some("name"),
+                ..
+            }
+        ));
+
+        repo.create("existing").await.unwrap();
+        let duplicate = repo
+            .patch(
+                tag.id,
+                PatchTag {
+                    name: Some("existing".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await;
         assert!(matches!(
             duplicate.unwrap_err(),
-            DatabaseError::DuplicateEntry(_)
+            DatabaseError::DuplicateEntry { .. }
         ));
+
+        let not_found = repo.patch(999, PatchTag::default()).await;
+        assert!(matches!(not_found.unwrap_err(), DatabaseError::NotFound(_)));
     }
 
     #[tokio::test]
@@ -402,16 +1892,16 @@ mod tests {
         let tag = repo.create("test-tag").await.unwrap();
         let post = db
             .posts()
-            .create(CreatePost {
-                category: PostCategory::Blog,
-                title: "Test Post".to_string(),
-                slug: "test-post".to_string(),
-                content: "Test content".to_string(),
-                description: "Test description".to_string(),
-                image_url: None,
-                external_url: None,
-                published: true,
-            })
+            .create(
+                CreatePost::builder()
+                    .title("Test Post")
+                    .slug("test-post")
+                    .content("Test content")
+                    .description("Test description")
+                    .published(true)
+                    .build()
+                    .unwrap(),
+            )
             .await
             .unwrap();
 
@@ -421,9 +1911,21 @@ mod tests {
         // Test duplicate association
         assert!(matches!(
             repo.add_tag_to_post(post.id, tag.id).await.unwrap_err(),
-            DatabaseError::DuplicateEntry(_)
+            DatabaseError::DuplicateEntry { field: None, .. }
         ));
 
+        // Test missing post, missing tag, and both missing
+        let missing = |result: DatabaseResult<()>| match result.unwrap_err() {
+            DatabaseError::NotFound(message) => message,
+            e => panic!("expected not found, got {e:?}"),
+        };
+        let message = missing(repo.add_tag_to_post(post.id + 1000, tag.id).await);
+        assert!(message.starts_with("Post") && !message.contains("Tag"));
+        let message = missing(repo.add_tag_to_post(post.id, tag.id + 1000).await);
+        assert!(message.starts_with("Tag") && !message.contains("Post"));
+        let message = missing(repo.add_tag_to_post(post.id + 1000, tag.id + 1000).await);
+        assert!(message.contains("Post") && message.contains("Tag"));
+
         // Test listing tags for post
         let post_tags = repo.list_tags_for_post(post.id).await.unwrap();
         assert_eq!(post_tags.len(), 1);
@@ -441,5 +1943,494 @@ mod tests {
             repo.remove_tag_from_post(post.id, 999).await.unwrap_err(),
             DatabaseError::NotFound(_)
         ));
+
+        // Test listing tags for a missing post
+        assert!(matches!(
+            repo.list_tags_for_post(post.id + 1000).await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_delete_unused() {
+        let (db, repo) = setup().await;
+
+        let post = db
+            .posts()
+            .create(
+                CreatePost::builder()
+                    .title("Test Post")
+                    .slug("test-post")
+                    .content("Test content")
+                    .description("Test description")
+                    .published(true)
+                    .build()
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let used = repo.create("rust").await.unwrap();
+        repo.create("go").await.unwrap();
+        repo.create("zig").await.unwrap();
+        repo.add_tag_to_post(post.id, used.id).await.unwrap();
+
+        // Dry runs report without deleting
+        let names = repo.delete_unused(true).await.unwrap();
+        assert_eq!(names, vec!["go", "zig"]);
+        assert_eq!(
+            repo.list(&TagListOptions::default()).await.unwrap().0.len(),
+            3
+        );
+
+        let mut names = repo.delete_unused(false).await.unwrap();
+        names.sort();
+        assert_eq!(names, vec!["go", "zig"]);
+
+        let (remaining, _) = repo.list(&TagListOptions::default()).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, used.id);
+    }
+
+    #[tokio::test]
+    async fn test_suggest() {
+        let (db, repo) = setup().await;
+
+        let post = db
+            .posts()
+            .create(
+                CreatePost::builder()
+                    .title("Test Post")
+                    .slug("test-post")
+                    .content("Test content")
+                    .description("Test description")
+                    .published(true)
+                    .build()
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        repo.create("rust").await.unwrap();
+        let react = repo.create("react").await.unwrap();
+        repo.create("go").await.unwrap();
+        repo.create("r_lang").await.unwrap();
+        repo.add_tag_to_post(post.id, react.id).await.unwrap();
+
+        // Post counts come first, then names
+        let names: Vec<_> = repo
+            .suggest("R", 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|tag| (tag.name, tag.post_count))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("react".to_string(), 1),
+                ("r_lang".to_string(), 0),
+                ("rust".to_string(), 0)
+            ]
+        );
+
+        // Wildcards in the prefix match literally
+        let matches = repo.suggest("r_", 10).await.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].name, "r_lang");
+
+        assert_eq!(repo.suggest("r", 1).await.unwrap().len(), 1);
+        assert!(matches!(
+            repo.suggest("r", 0).await.unwrap_err(),
+            DatabaseError::Validation { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_popular() {
+        let (db, repo) = setup().await;
+
+        let mut post_ids = Vec::new();
+        for (slug, published) in [("one", true), ("two", true), ("draft", false)] {
+            let post = db
+                .posts()
+                .create(
+                    CreatePost::builder()
+                        .title("Test Post")
+                        .slug(slug)
+                        .content("Test content")
+                        .description("Test description")
+                        .published(published)
+                        .build()
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            post_ids.push(post.id);
+        }
+
+        let rust = repo.create("rust").await.unwrap();
+        let go = repo.create("go").await.unwrap();
+        let zig = repo.create("zig").await.unwrap();
+        repo.create("unused").await.unwrap();
+
+        // rust: one published post; go: one published post and the draft;
+        // zig: only the draft
+        repo.add_tag_to_post(post_ids[0], rust.id).await.unwrap();
+        repo.add_tag_to_post(post_ids[1], go.id).await.unwrap();
+        repo.add_tag_to_post(post_ids[2], go.id).await.unwrap();
+        repo.add_tag_to_post(post_ids[2], zig.id).await.unwrap();
+
+        let counts = |tags: Vec<TagWithPostCount>| -> Vec<(String, i64)> {
+            tags.into_iter().map(|t| (t.name, t.post_count)).collect()
+        };
+
+        assert_eq!(
+            counts(repo.popular(false, 10).await.unwrap()),
+            vec![
+                ("go".to_string(), 2),
+                ("rust".to_string(), 1),
+                ("zig".to_string(), 1)
+            ]
+        );
+
+        // The draft doesn't inflate counts, and ties break alphabetically
+        assert_eq!(
+            counts(repo.popular(true, 10).await.unwrap()),
+            vec![("go".to_string(), 1), ("rust".to_string(), 1)]
+        );
+
+        assert_eq!(repo.popular(true, 1).await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("100%_a\\b"), "100\\%\\_a\\\\b");
+    }
+
+    #[tokio::test]
+    async fn test_set_tags_for_post() {
+        let (db, repo) = setup().await;
+
+        let post = db
+            .posts()
+            .create(
+                CreatePost::builder()
+                    .title("Test Post")
+                    .slug("test-post")
+                    .content("Test content")
+                    .description("Test description")
+                    .published(true)
+                    .build()
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let a = repo.create("a").await.unwrap().id;
+        let b = repo.create("b").await.unwrap().id;
+        let c = repo.create("c").await.unwrap().id;
+
+        let ids = |tags: Vec<Tag>| tags.into_iter().map(|t| t.id).collect::<Vec<_>>();
+
+        // Add only
+        assert_eq!(
+            ids(repo.set_tags_for_post(post.id, &[a, b]).await.unwrap()),
+            vec![a, b]
+        );
+
+        // Remove only
+        assert_eq!(
+            ids(repo.set_tags_for_post(post.id, &[b]).await.unwrap()),
+            vec![b]
+        );
+
+        // Mixed: add a and c, remove b
+        assert_eq!(
+            ids(repo.set_tags_for_post(post.id, &[c, a]).await.unwrap()),
+            vec![a, c]
+        );
+
+        // Unknown tags fail the whole request
+        match repo
+            .set_tags_for_post(post.id, &[b, 999])
+            .await
+            .unwrap_err()
+        {
+            DatabaseError::NotFound(message) => assert!(message.contains("999")),
+            e => panic!("expected not found, got {e:?}"),
+        }
+        assert_eq!(
+            ids(repo.list_tags_for_post(post.id).await.unwrap()),
+            vec![a, c]
+        );
+
+        // Empty list clears everything
+        assert!(repo
+            .set_tags_for_post(post.id, &[])
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert!(matches!(
+            repo.set_tags_for_post(post.id + 1000, &[a])
+                .await
+                .unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_add_tags_to_post() {
+        let (db, repo) = setup().await;
+        let post = db
+            .posts()
+            .create(
+                crate::models::post::CreatePost::builder()
+                    .title("Tagged")
+                    .slug("tagged")
+                    .content("Content")
+                    .description("Description")
+                    .published(true)
+                    .build()
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let mut ids = Vec::new();
+        for name in ["a", "b", "c", "d"] {
+            ids.push(repo.create(name).await.unwrap().id);
+        }
+
+        let already = repo.add_tags_to_post(post.id, &ids[..2]).await.unwrap();
+        assert!(already.is_empty());
+
+        // Partly attached already, with a duplicate in the request
+        let already = repo
+            .add_tags_to_post(post.id, &[ids[3], ids[1], ids[2], ids[1]])
+            .await
+            .unwrap();
+        assert_eq!(already, [ids[1]]);
+        let attached: Vec<i64> = repo
+            .list_tags_for_post(post.id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|tag| tag.id)
+            .collect();
+        assert_eq!(attached, ids);
+
+        // A missing tag or post changes nothing
+        assert!(matches!(
+            repo.add_tags_to_post(post.id, &[ids[0], 999]).await,
+            Err(DatabaseError::NotFound(message)) if message.contains("999")
+        ));
+        assert!(matches!(
+            repo.add_tags_to_post(999, &ids).await,
+            Err(DatabaseError::NotFound(message)) if message.contains("Post")
+        ));
+        assert_eq!(repo.list_tags_for_post(post.id).await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_get_or_create() {
+        let (_, repo) = setup().await;
+
+        let (tag, created) = repo
+            .get_or_create(&TagRequest::named("rust"))
+            .await
+            .unwrap();
+        assert!(created);
+
+        let (again, created) = repo
+            .get_or_create(&TagRequest::named(" Rust "))
+            .await
+            .unwrap();
+        assert!(!created);
+        assert_eq!(again.id, tag.id);
+
+        assert!(matches!(
+            repo.get_or_create(&TagRequest::named("  "))
+                .await
+                .unwrap_err(),
+            DatabaseError::Validation { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_or_create_concurrently() {
+        let (_, repo) = setup().await;
+
+        let calls: Vec<_> = (0..8)
+            .map(|_| {
+                let repo = repo.clone();
+                tokio::spawn(async move { repo.get_or_create(&TagRequest::named("rust")).await })
+            })
+            .collect();
+
+        let mut created = 0;
+        let mut ids = Vec::new();
+        for call in calls {
+            let (tag, was_created) = call.await.unwrap().unwrap();
+            created += usize::from(was_created);
+            ids.push(tag.id);
+        }
+
+        assert_eq!(created, 1);
+        assert!(ids.iter().all(|id| *id == ids[0]));
+        assert_eq!(
+            repo.list(&TagListOptions::default()).await.unwrap().0.len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_case_variant_tags_are_merged_by_migration() {
+        use sqlx::{Connection, Executor};
+
+        const MERGE_VERSION: i64 = 20250220120000;
+
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        let migrator = sqlx::migrate!("./migrations");
+        let (before, after): (Vec<_>, Vec<_>) = migrator
+            .iter()
+            .partition(|migration| migration.version < MERGE_VERSION);
+
+        for migration in before {
+            conn.execute(&*migration.sql).await.unwrap();
+        }
+
+        // Posts 1 and 2 share "Rust"/"rust"; post 2 is tagged with both
+        conn.execute(
+            r#"
+            INSERT INTO posts (id, category, title, slug, content, description)
+            VALUES (1, 'blog', 'One', 'one', 'c', 'd'), (2, 'blog', 'Two', 'two', 'c', 'd');
+            INSERT INTO tags (id, name) VALUES (1, 'Rust'), (2, 'rust'), (3, 'RUST'), (4, 'go');
+            INSERT INTO post_tags (post_id, tag_id) VALUES (1, 2), (2, 1), (2, 3), (2, 4);
+            "#,
+        )
+        .await
+        .unwrap();
+
+        for migration in after {
+            conn.execute(&*migration.sql).await.unwrap();
+        }
+
+        let tags: Vec<(i64, String)> = sqlx::query_as("SELECT id, name FROM tags ORDER BY id")
+            .fetch_all(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(tags, vec![(1, "Rust".to_string()), (4, "go".to_string())]);
+
+        let post_tags: Vec<(i64, i64)> =
+            sqlx::query_as("SELECT post_id, tag_id FROM post_tags ORDER BY post_id, tag_id")
+                .fetch_all(&mut conn)
+                .await
+                .unwrap();
+        assert_eq!(post_tags, vec![(1, 1), (2, 1), (2, 4)]);
+
+        // New case variants are rejected by the index
+        assert!(conn
+            .execute("INSERT INTO tags (name) VALUES ('GO')")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_whitespace_variant_tags_are_merged_by_migration() {
+        use sqlx::{Connection, Executor};
+
+        const NORMALIZE_VERSION: i64 = 20250430120000;
+
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        let migrator = sqlx::migrate!("./migrations");
+        let (before, after): (Vec<_>, Vec<_>) = migrator
+            .iter()
+            .partition(|migration| migration.version < NORMALIZE_VERSION);
+
+        for migration in before {
+            conn.execute(&*migration.sql).await.unwrap();
+        }
+
+        // "Front  End", " front end" and "FRONT\tEND" are one tag
+        conn.execute(
+            "INSERT INTO posts (id, category, title, slug, content, description)
+             VALUES (1, 'blog', 'One', 'one', 'c', 'd'), (2, 'blog', 'Two', 'two', 'c', 'd');
+             INSERT INTO tags (id, name) VALUES
+             (1, 'Front  End'), (2, ' front end'), (3, 'FRONT' || char(9) || 'END'),
+             (4, 'Back      End'), (5, 'go');
+             INSERT INTO post_tags (post_id, tag_id) VALUES (1, 2), (2, 1), (2, 3), (2, 5)",
+        )
+        .await
+        .unwrap();
+
+        for migration in after {
+            conn.execute(&*migration.sql).await.unwrap();
+        }
+
+        let tags: Vec<(i64, String)> = sqlx::query_as("SELECT id, name FROM tags ORDER BY id")
+            .fetch_all(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(
+            tags,
+            vec![
+                (1, "Front End".to_string()),
+                (4, "Back End".to_string()),
+                (5, "go".to_string())
+            ]
+        );
+
+        let post_tags: Vec<(i64, i64)> =
+            sqlx::query_as("SELECT post_id, tag_id FROM post_tags ORDER BY post_id, tag_id")
+                .fetch_all(&mut conn)
+                .await
+                .unwrap();
+        assert_eq!(post_tags, vec![(1, 1), (2, 1), (2, 5)]);
+    }
+
+    #[tokio::test]
+    async fn test_tag_slugs_are_backfilled_by_migration() {
+        use sqlx::{Connection, Executor};
+
+        const SLUGS_VERSION: i64 = 20250505120000;
+
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        let migrator = sqlx::migrate!("./migrations");
+        let (before, after): (Vec<_>, Vec<_>) = migrator
+            .iter()
+            .partition(|migration| migration.version < SLUGS_VERSION);
+
+        for migration in before {
+            conn.execute(&*migration.sql).await.unwrap();
+        }
+
+        conn.execute(
+            "INSERT INTO tags (id, name) VALUES
+             (1, 'C++'), (2, 'Front End'), (3, 'c plus plus'), (4, 'ruby_on_rails'),
+             (5, 'Web -- Dev'), (6, '+')",
+        )
+        .await
+        .unwrap();
+
+        for migration in after {
+            conn.execute(&*migration.sql).await.unwrap();
+        }
+
+        let tags: Vec<(i64, String)> = sqlx::query_as("SELECT id, slug FROM tags ORDER BY id")
+            .fetch_all(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(
+            tags,
+            vec![
+                (1, "c-plus-plus".to_string()),
+                (2, "front-end".to_string()),
+                (3, "c-plus-plus-3".to_string()),
+                (4, "ruby-on-rails".to_string()),
+                (5, "web-dev".to_string()),
+                (6, "plus".to_string()),
+            ]
+        );
     }
 }
@@ -0,0 +1,375 @@
+/// Repository for managing user accounts in the database.
+/// Passwords are hashed before they reach this layer's queries; the
+/// plaintext is never stored.
+#[derive(Clone, Debug)]
+pub struct UserRepository {
+    pool: SqlitePool,
+    metrics: QueryMetrics,
+}
+
+impl UserRepository {
+    /// Creates a new UserRepository instance sharing the given pool
+    pub(crate) fn new(pool: SqlitePool, metrics: QueryMetrics) -> Self {
+        Self { pool, metrics }
+    }
+
+    /// Creates a new user, hashing the password with Argon2.
+    /// Returns an error if the email is already taken, ignoring case.
+    pub async fn create(&self, user: CreateUser) -> DatabaseResult<User> {
+        user.validate().map_err(DatabaseError::from)?;
+
+        let email = normalize_email(&user.email);
+        let password_hash = user.password_hash().await.map_err(DatabaseError::from)?;
+        // Hashing is slow on purpose, so only the queries are timed
+        let _timer = self.metrics.time("users.create", "");
+
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+
+        let created_user = sqlx::query_as!(
+            User,
+            r#"
+            INSERT INTO users (email, password_hash, role)
+            VALUES (?, ?, ?)
+            RETURNING
+                id, email, password_hash, role as "role: UserRole", created_at
+            "#,
+            email,
+            password_hash,
+            user.role
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(DatabaseError::on_duplicate("User", &email))?;
+
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok(created_user)
+    }
+
+    /// Retrieves a user by ID
+    pub async fn find_by_id(&self, id: i64) -> DatabaseResult<User> {
+        let _timer = self.metrics.time("users.find_by_id", format!("id={}", id));
+        sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, email, password_hash, role as "role: UserRole", created_at
+            FROM users
+            WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?
+        .ok_or_else(|| DatabaseError::not_found("User", &id.to_string()))
+    }
+
+    /// Retrieves a user by email address, ignoring case
+    pub async fn find_by_email(&self, email: &str) -> DatabaseResult<User> {
+        let _timer = self.metrics.time("users.find_by_email", "");
+        let email = normalize_email(email);
+        sqlx::query_as!(
+            User,
+            r#"
+            SELECT
+                id as "id!", email as "email!", password_hash as "password_hash!",
+                role as "role!: UserRole", created_at as "created_at!"
+            FROM users
+            WHERE email = ?
+            "#,
+            email
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)?
+        .ok_or_else(|| DatabaseError::not_found("User", &email))
+    }
+
+    /// Lists all users, oldest account first
+    pub async fn list(&self) -> DatabaseResult<Vec<User>> {
+        let _timer = self.metrics.time("users.list", "");
+        sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, email, password_hash, role as "role: UserRole", created_at
+            FROM users
+            ORDER BY id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DatabaseError::Sqlx)
+    }
+
+    /// Updates the provided fields of a user, rehashing a new password
+    pub async fn update(&self, id: i64, update: UpdateUser) -> DatabaseResult<User> {
+        update.validate().map_err(DatabaseError::from)?;
+
+        let password_hash = match update.password.as_deref() {
+            Some(password) => Some(hash_password(password).await.map_err(DatabaseError::from)?),
+            None => None,
+        };
+        // Hashing is slow on purpose, so only the queries are timed
+        let _timer = self.metrics.time("users.update", format!("id={}", id));
+
+        let mut tx = self.pool.begin().await.map_err(DatabaseError::Sqlx)?;
+
+        let current = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, email, password_hash, role as "role: UserRole", created_at
+            FROM users
+            WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(DatabaseError::Sqlx)?
+        .ok_or_else(|| DatabaseError::not_found("User", &id.to_string()))?;
+
+        let email = update
+            .email
+            .as_deref()
+            .map(normalize_email)
+            .unwrap_or(current.email);
+        let password_hash = password_hash.unwrap_or(current.password_hash);
+        let role = update.role.unwrap_or(current.role);
+
+        let updated_user = sqlx::query_as!(
+            User,
+            r#"
+            UPDATE users
+            SET email = ?, password_hash = ?, role = ?
+            WHERE id = ?
+            RETURNING
+                id, email, password_hash, role as "role: UserRole", created_at
+            "#,
+            email,
+            password_hash,
+            role,
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(DatabaseError::on_duplicate("User", &email))?;
+
+        tx.commit().await.map_err(DatabaseError::Sqlx)?;
+        Ok(updated_user)
+    }
+
+    /// Deletes a user by ID
+    pub async fn delete(&self, id: i64) -> DatabaseResult<()> {
+        let _timer = self.metrics.time("users.delete", format!("id={}", id));
+        let result = sqlx::query!("DELETE FROM users WHERE id = ?", id)
+            .execute(&self.pool)
+            .await
+            .map_err(DatabaseError::Sqlx)?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::not_found("User", &id.to_string()));
+        }
+        Ok(())
+    }
+}
+
+use sqlx::SqlitePool;
+
+use crate::models::user::{hash_password, normalize_email, CreateUser, UpdateUser, User, UserRole};
+
+use super::{error::DatabaseResult, DatabaseError, QueryMetrics};
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        db::{test_utils::create_test_db, Database},
+        models::user::verify_password,
+    };
+
+    use super::*;
+
+    fn create_test_user() -> CreateUser {
+        CreateUser {
+            email: "writer@example.com".to_string(),
+            password: "correct horse".to_string(),
+            role: UserRole::User,
+        }
+    }
+
+    async fn setup() -> (Database, UserRepository) {
+        let db = create_test_db().await.unwrap();
+        let repo = db.users().clone();
+        (db, repo)
+    }
+
+    async fn verifies(user: &User, password: &str) -> bool {
+        verify_password(password, &user.password_hash).await
+    }
+
+    #[tokio::test]
+    async fn test_create_user() {
+        let (_, repo) = setup().await;
+
+        let user = repo
+            .create(CreateUser {
+                email: " Writer@Example.com ".to_string(),
+                ..create_test_user()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(user.email, "writer@example.com");
+        assert_eq!(user.role, UserRole::User);
+        assert_ne!(user.password_hash, "correct horse");
+        assert!(verifies(&user, "correct horse").await);
+
+        // Invalid input never reaches the database
+        let invalid = repo
+            .create(CreateUser {
+                password: "short".to_string(),
+                ..create_test_user()
+            })
+            .await;
+        assert!(matches!(
+            invalid.unwrap_err(),
+            DatabaseError::Validation {
+                field: Some("password"),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_create_duplicate_email() {
+        let (_, repo) = setup().await;
+
+        repo.create(create_test_user()).await.unwrap();
+
+        let duplicate = repo
+            .create(CreateUser {
+                email: "WRITER@example.com".to_string(),
+                ..create_test_user()
+            })
+            .await;
+        match duplicate.unwrap_err() {
+            DatabaseError::DuplicateEntry { field, .. } => {
+                assert_eq!(field.as_deref(), Some("email"))
+            }
+            other => panic!("expected a duplicate entry, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_user() {
+        let (_, repo) = setup().await;
+
+        let user = repo.create(create_test_user()).await.unwrap();
+
+        assert_eq!(repo.find_by_id(user.id).await.unwrap().email, user.email);
+        assert_eq!(
+            repo.find_by_email("WRITER@example.com").await.unwrap().id,
+            user.id
+        );
+
+        assert!(matches!(
+            repo.find_by_id(999).await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+        assert!(matches!(
+            repo.find_by_email("nobody@example.com").await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_users() {
+        let (_, repo) = setup().await;
+
+        repo.create(create_test_user()).await.unwrap();
+        repo.create(CreateUser {
+            email: "admin@example.com".to_string(),
+            role: UserRole::Admin,
+            ..create_test_user()
+        })
+        .await
+        .unwrap();
+
+        let users = repo.list().await.unwrap();
+        let emails: Vec<_> = users.iter().map(|u| u.email.as_str()).collect();
+        assert_eq!(emails, vec!["writer@example.com", "admin@example.com"]);
+    }
+
+    #[tokio::test]
+    async fn test_update_user() {
+        let (_, repo) = setup().await;
+
+        let user = repo.create(create_test_user()).await.unwrap();
+
+        // Unspecified fields are left untouched
+        let promoted = repo
+            .update(
+                user.id,
+                UpdateUser {
+                    role: Some(UserRole::Admin),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(promoted.role, UserRole::Admin);
+        assert_eq!(promoted.email, user.email);
+        assert_eq!(promoted.password_hash, user.password_hash);
+
+        let updated = repo
+            .update(
+                user.id,
+                UpdateUser {
+                    email: Some("Editor@Example.com".to_string()),
+                    password: Some("battery staple".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.email, "editor@example.com");
+        assert!(verifies(&updated, "battery staple").await);
+        assert!(!verifies(&updated, "correct horse").await);
+
+        // Taking another user's email is a duplicate
+        repo.create(create_test_user()).await.unwrap();
+        let duplicate = repo
+            .update(
+                user.id,
+                UpdateUser {
+                    email: Some("writer@example.com".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await;
+        assert!(matches!(
+            duplicate.unwrap_err(),
+            DatabaseError::DuplicateEntry { .. }
+        ));
+
+        assert!(matches!(
+            repo.update(999, UpdateUser::default()).await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_delete_user() {
+        let (_, repo) = setup().await;
+
+        let user = repo.create(create_test_user()).await.unwrap();
+        repo.delete(user.id).await.unwrap();
+
+        assert!(matches!(
+            repo.find_by_id(user.id).await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+        assert!(matches!(
+            repo.delete(user.id).await.unwrap_err(),
+            DatabaseError::NotFound(_)
+        ));
+    }
+}
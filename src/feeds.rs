@@ -0,0 +1,374 @@
+//! Syndication feeds of published posts in RSS 2.0, Atom (RFC 4287) and
+//! JSON Feed 1.1. All three render the same `FeedEntry` list, so they
+//! always agree on what a post looks like.
+
+use std::fmt::Write;
+
+use serde_json::json;
+use time::{
+    format_description::well_known::{Rfc2822, Rfc3339},
+    macros::format_description,
+    OffsetDateTime,
+};
+use url::Url;
+
+use crate::models::post::Post;
+
+/// Number of posts a feed carries, newest first
+pub const FEED_LENGTH: i64 = 20;
+
+/// Site details shared by every feed
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeedConfig {
+    /// Public address of the site; post links are `<site_url>/posts/<slug>`
+    pub site_url: Url,
+    /// Feed title shown by feed readers
+    pub title: String,
+}
+
+impl Default for FeedConfig {
+    fn default() -> Self {
+        Self {
+            site_url: Url::parse("http://localhost:8080/").expect("valid default URL"),
+            title: "Blog".to_string(),
+        }
+    }
+}
+
+impl FeedConfig {
+    /// Absolute URL of `path` on the site
+    pub(crate) fn link(&self, path: &str) -> String {
+        format!("{}{}", self.site_url.as_str().trim_end_matches('/'), path)
+    }
+}
+
+/// A post as it appears in a feed
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeedEntry {
+    /// Permanent identifier: a `tag:` URI (RFC 4151) built from the post's
+    /// id, so it survives changes to the slug or the site's address
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub summary: String,
+    /// The post's content, as HTML
+    pub content_html: String,
+    pub category: String,
+    pub image_url: Option<String>,
+    pub published: OffsetDateTime,
+    pub updated: OffsetDateTime,
+}
+
+impl FeedEntry {
+    pub fn from_post(post: &Post, config: &FeedConfig) -> Self {
+        let date = post
+            .created_at
+            .format(format_description!("[year]-[month]-[day]"))
+            .unwrap_or_default();
+        let host = config.site_url.host_str().unwrap_or("localhost");
+        Self {
+            id: format!("tag:{},{}:posts/{}", host, date, post.id),
+            title: post.title.clone(),
+            url: config.link(&format!("/posts/{}", post.slug)),
+            summary: post.description.clone(),
+            content_html: post.content.clone(),
+            category: post.category.to_string(),
+            image_url: post.image_url.clone(),
+            published: post.published_at.unwrap_or(post.created_at),
+            updated: post.updated_at,
+        }
+    }
+}
+
+/// A feed ready to render in any of the formats
+#[derive(Clone, Debug)]
+pub struct Feed {
+    pub title: String,
+    /// Absolute URL of the page the feed follows
+    pub home_page_url: String,
+    /// Absolute URL the feed itself is served from
+    pub feed_url: String,
+    /// Language every entry is written in, for feeds of a single language
+    pub language: Option<String>,
+    pub entries: Vec<FeedEntry>,
+}
+
+impl Feed {
+    /// A feed of `posts`, served from `path`. The feed of a single tag or
+    /// category has its name, the `topic`, in its title.
+    pub fn new(config: &FeedConfig, path: &str, topic: Option<&str>, posts: &[Post]) -> Self {
+        Self {
+            title: match topic {
+                Some(topic) => format!("{}: {}", config.title, topic),
+                None => config.title.clone(),
+            },
+            home_page_url: config.link("/"),
+            feed_url: config.link(path),
+            language: None,
+            entries: posts
+                .iter()
+                .map(|post| FeedEntry::from_post(post, config))
+                .collect(),
+        }
+    }
+
+    /// When anything in the feed last changed; an empty feed is as new as
+    /// the request
+    fn updated(&self) -> OffsetDateTime {
+        self.entries
+            .iter()
+            .map(|entry| entry.updated)
+            .max()
+            .unwrap_or_else(OffsetDateTime::now_utc)
+    }
+
+    pub fn to_rss(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        xml.push_str("<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\"><channel>");
+        let _ = write!(
+            xml,
+            "<title>{}</title><link>{}</link><description>{}</description>\
+             <atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\"/>\
+             <lastBuildDate>{}</lastBuildDate>",
+            escape(&self.title),
+            escape(&self.home_page_url),
+            escape(&self.title),
+            escape(&self.feed_url),
+            rfc2822(self.updated()),
+        );
+        if let Some(language) = &self.language {
+            let _ = write!(xml, "<language>{}</language>", escape(language));
+        }
+        for entry in &self.entries {
+            let _ = write!(
+                xml,
+                "<item><title>{}</title><link>{}</link>\
+                 <guid isPermaLink=\"false\">{}</guid><pubDate>{}</pubDate>\
+                 <category>{}</category><description>{}</description></item>",
+                escape(&entry.title),
+                escape(&entry.url),
+                escape(&entry.id),
+                rfc2822(entry.published),
+                escape(&entry.category),
+                escape(&entry.content_html),
+            );
+        }
+        xml.push_str("</channel></rss>\n");
+        xml
+    }
+
+    pub fn to_atom(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        match &self.language {
+            Some(language) => {
+                let _ = write!(
+                    xml,
+                    "<feed xmlns=\"http://www.w3.org/2005/Atom\" xml:lang=\"{}\">",
+                    escape(language)
+                );
+            }
+            None => xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">"),
+        }
+        let _ = write!(
+            xml,
+            "<id>{}</id><title>{}</title><updated>{}</updated>\
+             <link rel=\"self\" href=\"{}\"/><link rel=\"alternate\" href=\"{}\"/>",
+            escape(&self.feed_url),
+            escape(&self.title),
+            rfc3339(self.updated()),
+            escape(&self.feed_url),
+            escape(&self.home_page_url),
+        );
+        for entry in &self.entries {
+            // Content goes in escaped rather than in a CDATA section, which
+            // a `]]>` in the post would end early
+            let _ = write!(
+                xml,
+                "<entry><id>{}</id><title>{}</title>\
+                 <link rel=\"alternate\" href=\"{}\"/>\
+                 <published>{}</published><updated>{}</updated>\
+                 <category term=\"{}\"/><summary>{}</summary>\
+                 <content type=\"html\">{}</content></entry>",
+                escape(&entry.id),
+                escape(&entry.title),
+                escape(&entry.url),
+                rfc3339(entry.published),
+                rfc3339(entry.updated),
+                escape(&entry.category),
+                escape(&entry.summary),
+                escape(&entry.content_html),
+            );
+        }
+        xml.push_str("</feed>\n");
+        xml
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let items: Vec<_> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let mut item = json!({
+                    "id": entry.id,
+                    "url": entry.url,
+                    "title": entry.title,
+                    "summary": entry.summary,
+                    "content_html": entry.content_html,
+                    "date_published": rfc3339(entry.published),
+                    "date_modified": rfc3339(entry.updated),
+                    "tags": [entry.category],
+                });
+                if let Some(image) = &entry.image_url {
+                    item["image"] = json!(image);
+                }
+                item
+            })
+            .collect();
+        let mut feed = json!({
+            "version": "https://jsonfeed.org/version/1.1",
+            "title": self.title,
+            "home_page_url": self.home_page_url,
+            "feed_url": self.feed_url,
+            "items": items,
+        });
+        if let Some(language) = &self.language {
+            feed["language"] = json!(language);
+        }
+        feed
+    }
+}
+
+/// Escapes text for use in XML content and attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than whitespace aren't allowed in XML
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn rfc3339(at: OffsetDateTime) -> String {
+    at.format(&Rfc3339).unwrap_or_default()
+}
+
+fn rfc2822(at: OffsetDateTime) -> String {
+    at.format(&Rfc2822).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::models::post::PostCategory;
+
+    fn post(content: &str) -> Post {
+        Post {
+            id: 7,
+            category: PostCategory::Blog,
+            title: "Fish & <Chips>".to_string(),
+            slug: "fish-and-chips".to_string(),
+            content: content.to_string(),
+            description: "A \"tasty\" post".to_string(),
+            image_url: None,
+            external_url: None,
+            published: true,
+            meta_title: None,
+            meta_description: None,
+            og_image_url: None,
+            book_author: None,
+            isbn: None,
+            rating: None,
+            author_id: None,
+            created_by: None,
+            updated_by: None,
+            language: "en".to_string(),
+            translation_of: None,
+            published_at: None,
+            created_at: datetime!(2024-05-01 12:00 UTC),
+            updated_at: datetime!(2024-05-02 08:30 UTC),
+        }
+    }
+
+    fn feed(content: &str) -> Feed {
+        let config = FeedConfig {
+            site_url: Url::parse("https://blog.example.com").unwrap(),
+            title: "Example".to_string(),
+        };
+        Feed::new(&config, "/atom.xml", None, &[post(content)])
+    }
+
+    #[test]
+    fn test_entry_from_post() {
+        let entry = &feed("Hello").entries[0];
+        assert_eq!(entry.id, "tag:blog.example.com,2024-05-01:posts/7");
+        assert_eq!(entry.url, "https://blog.example.com/posts/fish-and-chips");
+        assert_eq!(entry.category, "blog");
+        assert_eq!(entry.published, datetime!(2024-05-01 12:00 UTC));
+
+        // A post published long after it was drafted is dated by its
+        // publication, while its id keeps the drafting date
+        let config = FeedConfig {
+            site_url: Url::parse("https://blog.example.com").unwrap(),
+            title: "Example".to_string(),
+        };
+        let drafted = Post {
+            published_at: Some(datetime!(2024-06-10 09:00 UTC)),
+            ..post("Hello")
+        };
+        let entry = FeedEntry::from_post(&drafted, &config);
+        assert_eq!(entry.published, datetime!(2024-06-10 09:00 UTC));
+        assert_eq!(entry.id, "tag:blog.example.com,2024-05-01:posts/7");
+    }
+
+    #[test]
+    fn test_atom_escapes_content() {
+        let content = "<p>1 < 2 && x</p><![CDATA[ ]]> ]]>";
+        let atom = feed(content).to_atom();
+
+        assert!(atom.contains(
+            "<content type=\"html\">&lt;p&gt;1 &lt; 2 &amp;&amp; x&lt;/p&gt;\
+             &lt;![CDATA[ ]]&gt; ]]&gt;</content>"
+        ));
+        assert!(atom.contains("<title>Fish &amp; &lt;Chips&gt;</title>"));
+        assert!(atom.contains("<summary>A &quot;tasty&quot; post</summary>"));
+        assert!(atom.contains("<updated>2024-05-02T08:30:00Z</updated>"));
+        assert!(atom.contains("<published>2024-05-01T12:00:00Z</published>"));
+        assert!(!atom.contains("CDATA[ ]]>"));
+        assert!(!atom.contains("<p>"));
+    }
+
+    #[test]
+    fn test_rss_escapes_content() {
+        let rss = feed("<b>bold</b> & ]]>").to_rss();
+        assert!(rss.contains("<description>&lt;b&gt;bold&lt;/b&gt; &amp; ]]&gt;</description>"));
+        assert!(rss.contains("<pubDate>Wed, 01 May 2024 12:00:00 +0000</pubDate>"));
+        assert!(rss.contains(
+            "<guid isPermaLink=\"false\">tag:blog.example.com,2024-05-01:posts/7</guid>"
+        ));
+    }
+
+    #[test]
+    fn test_json_feed() {
+        let json = feed("<p>Hi & bye</p>").to_json();
+        assert_eq!(json["version"], "https://jsonfeed.org/version/1.1");
+        assert_eq!(json["feed_url"], "https://blog.example.com/atom.xml");
+        assert_eq!(json["items"][0]["content_html"], "<p>Hi & bye</p>");
+        assert_eq!(json["items"][0]["date_modified"], "2024-05-02T08:30:00Z");
+        assert!(json["items"][0].get("image").is_none());
+    }
+
+    #[test]
+    fn test_escape_drops_control_characters() {
+        assert_eq!(escape("a\u{0}b\tc\u{1b}"), "ab\tc");
+    }
+}
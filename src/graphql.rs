@@ -0,0 +1,733 @@
+//! `POST /graphql`: posts and tags in whatever shape the client asks for.
+//!
+//! Resolvers go through the same repositories as the REST handlers. Nested
+//! `Post.tags` and `Tag.posts` fields are batched by dataloaders, so a page
+//! of posts with their tags costs two queries rather than one per post.
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    ComplexObject, Context, EmptySubscription, Enum, ErrorExtensions, InputObject, Object, Schema,
+    SimpleObject,
+};
+use axum::{extract::State, Extension};
+use time::format_description::well_known::Rfc3339;
+
+use crate::{
+    auth::{AuthError, AuthUser, Principal},
+    db::{Database, DatabaseError},
+    handlers::{
+        errors::{codes, ApiError},
+        extract::Json,
+    },
+    models::{
+        post::{
+            non_blank, CreatePost, Post, PostCategory, UpdatePost, Visibility, DEFAULT_LANGUAGE,
+        },
+        tag::{Tag, TagListOptions},
+        user::UserRole,
+    },
+};
+
+pub type BlogSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+/// The schema, without per-request data
+pub fn schema() -> BlogSchema {
+    Schema::build(QueryRoot, MutationRoot, EmptySubscription).finish()
+}
+
+/// What the GraphQL handler needs: the schema, and the database for the
+/// per-request dataloaders
+#[derive(Clone)]
+pub struct GraphQlState {
+    schema: BlogSchema,
+    db: Database,
+}
+
+impl GraphQlState {
+    pub fn new(db: Database) -> Self {
+        Self {
+            schema: schema(),
+            db,
+        }
+    }
+}
+
+/// Runs a GraphQL request. Each request gets its own dataloaders, so
+/// batches never mix data read for different callers.
+pub async fn graphql_handler(
+    State(state): State<GraphQlState>,
+    principal: Option<Extension<Principal>>,
+    Json(request): Json<async_graphql::Request>,
+) -> axum::Json<async_graphql::Response> {
+    let mut request = request.data(Loaders::new(&state.db)).data(state.db);
+    if let Some(Extension(principal)) = principal {
+        request = request.data(AuthUser::from(&principal));
+    }
+    axum::Json(state.schema.execute(request).await)
+}
+
+/// Dataloaders for the nested fields
+pub(crate) struct Loaders {
+    tags: DataLoader<TagsByPost>,
+    posts: DataLoader<PostsByTag>,
+}
+
+impl Loaders {
+    pub(crate) fn new(db: &Database) -> Self {
+        Self {
+            tags: DataLoader::new(TagsByPost::new(db.clone()), tokio::spawn),
+            posts: DataLoader::new(PostsByTag::new(db.clone()), tokio::spawn),
+        }
+    }
+}
+
+/// Loads the tags of many posts with one query
+pub(crate) struct TagsByPost {
+    db: Database,
+    /// Queries run, so tests can check that lookups are batched
+    #[cfg(test)]
+    queries: Arc<std::sync::atomic::AtomicUsize>,
+}
+
+impl TagsByPost {
+    fn new(db: Database) -> Self {
+        Self {
+            db,
+            #[cfg(test)]
+            queries: Default::default(),
+        }
+    }
+}
+
+impl Loader<i64> for TagsByPost {
+    type Value = Vec<Tag>;
+    type Error = Arc<DatabaseError>;
+
+    async fn load(&self, post_ids: &[i64]) -> Result<HashMap<i64, Vec<Tag>>, Self::Error> {
+        #[cfg(test)]
+        self.queries
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.db
+            .tags()
+            .list_tags_for_posts(post_ids)
+            .await
+            .map_err(Arc::new)
+    }
+}
+
+/// Loads the posts of many tags with one query, drafts included
+pub(crate) struct PostsByTag {
+    db: Database,
+}
+
+impl PostsByTag {
+    fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+impl Loader<i64> for PostsByTag {
+    type Value = Vec<Post>;
+    type Error = Arc<DatabaseError>;
+
+    async fn load(&self, tag_ids: &[i64]) -> Result<HashMap<i64, Vec<Post>>, Self::Error> {
+        self.db
+            .posts()
+            .list_by_tags(tag_ids, false)
+            .await
+            .map_err(Arc::new)
+    }
+}
+
+/// Reports an error the way the REST API would: the same message, with its
+/// code, field and any validation errors as extensions
+fn graphql_error(error: impl Into<ApiError>) -> async_graphql::Error {
+    let error = error.into();
+    let code = error.code();
+    if error.status().is_server_error() {
+        tracing::error!(error = %error, code, "GraphQL request failed");
+    }
+    let errors = match &error {
+        ApiError::Validation { errors, .. }
+        | ApiError::Database(DatabaseError::Validation { errors, .. }) => Some(errors.clone()),
+        _ => None,
+    };
+    let (message, field) = error.client_message();
+    async_graphql::Error::new(message).extend_with(|_, extensions| {
+        extensions.set("code", code);
+        if let Some(field) = field {
+            extensions.set("field", field);
+        }
+        if let Some(errors) = errors
+            .as_ref()
+            .and_then(|e| async_graphql::to_value(e).ok())
+        {
+            extensions.set("errors", errors);
+        }
+    })
+}
+
+/// Errors from a dataloader, which shares them between the fields waiting
+/// on the batch. Loads only fail when the database does.
+fn loader_error(error: Arc<DatabaseError>) -> async_graphql::Error {
+    tracing::error!(error = %error, "GraphQL batch load failed");
+    async_graphql::Error::new("Internal server error")
+        .extend_with(|_, extensions| extensions.set("code", codes::INTERNAL_ERROR))
+}
+
+/// Whether the caller presented credentials; drafts are hidden otherwise
+fn signed_in(ctx: &Context<'_>) -> bool {
+    ctx.data_opt::<AuthUser>().is_some()
+}
+
+/// The caller, who must be allowed to write posts
+fn writer(ctx: &Context<'_>) -> async_graphql::Result<AuthUser> {
+    let user = ctx
+        .data_opt::<AuthUser>()
+        .copied()
+        .ok_or_else(|| graphql_error(AuthError::MissingToken))?;
+    user.require_role(UserRole::User).map_err(graphql_error)?;
+    Ok(user)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Enum)]
+#[graphql(name = "PostCategory")]
+pub enum Category {
+    Blog,
+    Art,
+    Reading,
+}
+
+impl From<&PostCategory> for Category {
+    fn from(category: &PostCategory) -> Self {
+        match category {
+            PostCategory::Blog => Category::Blog,
+            PostCategory::Art => Category::Art,
+            PostCategory::Reading => Category::Reading,
+        }
+    }
+}
+
+impl From<Category> for PostCategory {
+    fn from(category: Category) -> Self {
+        match category {
+            Category::Blog => PostCategory::Blog,
+            Category::Art => PostCategory::Art,
+            Category::Reading => PostCategory::Reading,
+        }
+    }
+}
+
+/// A post, with the same SEO fallbacks as the REST API
+#[derive(SimpleObject)]
+#[graphql(name = "Post", complex)]
+pub struct PostNode {
+    id: i64,
+    category: Category,
+    title: String,
+    slug: String,
+    content: String,
+    description: String,
+    image_url: Option<String>,
+    external_url: Option<String>,
+    published: bool,
+    meta_title: String,
+    meta_description: String,
+    og_image_url: Option<String>,
+    book_author: Option<String>,
+    isbn: Option<String>,
+    rating: Option<i64>,
+    author_id: Option<i64>,
+    language: String,
+    translation_of: Option<i64>,
+    /// RFC 3339 timestamp of when the post was first published
+    published_at: Option<String>,
+    /// RFC 3339 timestamp
+    created_at: String,
+    /// RFC 3339 timestamp
+    updated_at: String,
+}
+
+impl From<Post> for PostNode {
+    fn from(post: Post) -> Self {
+        Self {
+            id: post.id,
+            category: Category::from(&post.category),
+            // Blank SEO fields fall back like they do in REST responses
+            meta_title: non_blank(&post.meta_title)
+                .unwrap_or(&post.title)
+                .to_string(),
+            meta_description: non_blank(&post.meta_description)
+                .unwrap_or(&post.description)
+                .to_string(),
+            title: post.title,
+            slug: post.slug,
+            content: post.content,
+            description: post.description,
+            image_url: post.image_url,
+            external_url: post.external_url,
+            published: post.published,
+            og_image_url: post.og_image_url,
+            book_author: post.book_author,
+            isbn: post.isbn,
+            rating: post.rating,
+            author_id: post.author_id,
+            language: post.language,
+            translation_of: post.translation_of,
+            published_at: post.published_at.and_then(|at| at.format(&Rfc3339).ok()),
+            created_at: post.created_at.format(&Rfc3339).unwrap_or_default(),
+            updated_at: post.updated_at.format(&Rfc3339).unwrap_or_default(),
+        }
+    }
+}
+
+#[ComplexObject]
+impl PostNode {
+    /// The post's tags, by name
+    async fn tags(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<TagNode>> {
+        let tags = ctx
+            .data_unchecked::<Loaders>()
+            .tags
+            .load_one(self.id)
+            .await
+            .map_err(loader_error)?;
+        Ok(tags.unwrap_or_default().into_iter().map(TagNode).collect())
+    }
+}
+
+/// A tag
+pub struct TagNode(Tag);
+
+#[Object(name = "Tag")]
+impl TagNode {
+    async fn id(&self) -> i64 {
+        self.0.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// URL-safe form of the name
+    async fn slug(&self) -> &str {
+        &self.0.slug
+    }
+
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+
+    async fn color(&self) -> Option<&str> {
+        self.0.color.as_deref()
+    }
+
+    /// RFC 3339 timestamp
+    async fn created_at(&self) -> String {
+        self.0.created_at.format(&Rfc3339).unwrap_or_default()
+    }
+
+    /// Posts with the tag, newest first. Anonymous callers only see
+    /// published posts.
+    async fn posts(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] published_only: bool,
+    ) -> async_graphql::Result<Vec<PostNode>> {
+        let published_only = published_only || !signed_in(ctx);
+        let posts = ctx
+            .data_unchecked::<Loaders>()
+            .posts
+            .load_one(self.0.id)
+            .await
+            .map_err(loader_error)?;
+        Ok(posts
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|post| post.published || !published_only)
+            .map(PostNode::from)
+            .collect())
+    }
+}
+
+/// Which posts to list
+#[derive(Debug, Default, InputObject)]
+pub struct PostFilter {
+    category: Option<Category>,
+    language: Option<String>,
+    /// Ignored for anonymous callers, who only see published posts
+    #[graphql(default)]
+    published_only: bool,
+}
+
+/// Which page of a listing to return
+#[derive(Debug, InputObject)]
+pub struct Pagination {
+    #[graphql(default = 20)]
+    limit: i64,
+    #[graphql(default)]
+    offset: i64,
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Self {
+            limit: 20,
+            offset: 0,
+        }
+    }
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Posts, newest first. Anonymous callers only see published posts.
+    async fn posts(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: PostFilter,
+        #[graphql(default)] pagination: Pagination,
+    ) -> async_graphql::Result<Vec<PostNode>> {
+        let published_only = filter.published_only || !signed_in(ctx);
+        let posts = ctx
+            .data_unchecked::<Database>()
+            .posts()
+            .list(
+                filter.category.map(PostCategory::from),
+                filter.language.as_deref(),
+                Visibility::from_published_only(published_only),
+                pagination.limit,
+                pagination.offset,
+            )
+            .await
+            .map_err(graphql_error)?;
+        Ok(posts.into_iter().map(PostNode::from).collect())
+    }
+
+    /// The post with this slug, if any
+    async fn post(
+        &self,
+        ctx: &Context<'_>,
+        slug: String,
+    ) -> async_graphql::Result<Option<PostNode>> {
+        match ctx
+            .data_unchecked::<Database>()
+            .posts()
+            .find_by_slug(&slug)
+            .await
+        {
+            Ok(post) => Ok(Some(post.into())),
+            Err(DatabaseError::NotFound(_)) => Ok(None),
+            Err(e) => Err(graphql_error(e)),
+        }
+    }
+
+    /// Tags, by name
+    async fn tags(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] pagination: Pagination,
+    ) -> async_graphql::Result<Vec<TagNode>> {
+        let options = TagListOptions {
+            limit: pagination.limit,
+            offset: pagination.offset,
+            ..TagListOptions::default()
+        };
+        let (tags, _) = ctx
+            .data_unchecked::<Database>()
+            .tags()
+            .list(&options)
+            .await
+            .map_err(graphql_error)?;
+        Ok(tags
+            .into_iter()
+            .map(|tag| {
+                TagNode(Tag {
+                    id: tag.id,
+                    name: tag.name,
+                    slug: tag.slug,
+                    created_at: tag.created_at,
+                    description: tag.description,
+                    color: tag.color,
+                })
+            })
+            .collect())
+    }
+}
+
+/// Fields of a new post
+#[derive(Debug, InputObject)]
+pub struct PostInput {
+    category: Category,
+    title: String,
+    slug: String,
+    content: String,
+    description: String,
+    image_url: Option<String>,
+    external_url: Option<String>,
+    #[graphql(default)]
+    published: bool,
+    meta_title: Option<String>,
+    meta_description: Option<String>,
+    og_image_url: Option<String>,
+    book_author: Option<String>,
+    isbn: Option<String>,
+    rating: Option<i64>,
+    #[graphql(default_with = "DEFAULT_LANGUAGE.to_string()")]
+    language: String,
+    translation_of: Option<i64>,
+    /// Names of tags to attach; missing tags are created
+    #[graphql(default)]
+    tags: Vec<String>,
+}
+
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    /// Creates a post written by the caller
+    async fn create_post(
+        &self,
+        ctx: &Context<'_>,
+        input: PostInput,
+    ) -> async_graphql::Result<PostNode> {
+        let user = writer(ctx)?;
+        let create = CreatePost {
+            category: input.category.into(),
+            title: input.title,
+            slug: input.slug,
+            content: input.content,
+            description: input.description,
+            image_url: input.image_url,
+            external_url: input.external_url,
+            published: input.published,
+            meta_title: input.meta_title,
+            meta_description: input.meta_description,
+            og_image_url: input.og_image_url,
+            book_author: input.book_author,
+            isbn: input.isbn,
+            rating: input.rating,
+            language: input.language,
+            translation_of: input.translation_of,
+            published_at: None,
+            tags: input.tags,
+        };
+        let created = ctx
+            .data_unchecked::<Database>()
+            .acting_as(user.actor)
+            .posts()
+            .create_with_tags(create, user.id)
+            .await
+            .map_err(graphql_error)?;
+        Ok(created.post.into())
+    }
+
+    /// Replaces every field of a post. Only its author or an admin may.
+    /// Tags are left as they are.
+    async fn update_post(
+        &self,
+        ctx: &Context<'_>,
+        id: i64,
+        input: PostInput,
+    ) -> async_graphql::Result<PostNode> {
+        let user = writer(ctx)?;
+        let db = ctx.data_unchecked::<Database>();
+        if !user.is_admin() {
+            let post = db.posts().find_by_id(id).await.map_err(graphql_error)?;
+            user.require_author(post.author_id).map_err(graphql_error)?;
+        }
+        let update = UpdatePost {
+            id,
+            category: input.category.into(),
+            title: input.title,
+            slug: input.slug,
+            content: input.content,
+            description: input.description,
+            image_url: input.image_url,
+            external_url: input.external_url,
+            published: input.published,
+            meta_title: input.meta_title,
+            meta_description: input.meta_description,
+            og_image_url: input.og_image_url,
+            book_author: input.book_author,
+            isbn: input.isbn,
+            rating: input.rating,
+            language: input.language,
+            translation_of: input.translation_of,
+            published_at: None,
+        };
+        let (post, _) = db
+            .acting_as(user.actor)
+            .posts()
+            .update(update)
+            .await
+            .map_err(graphql_error)?;
+        Ok(post.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::db::test_utils::create_test_db;
+
+    async fn create_post(db: &Database, slug: &str, tags: &[&str]) {
+        db.posts()
+            .create_with_tags(
+                CreatePost::builder()
+                    .title(slug)
+                    .slug(slug)
+                    .content("Content")
+                    .description("Description")
+                    .published(true)
+                    .tags(tags.iter().copied())
+                    .build()
+                    .unwrap(),
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_nested_query_batches_tag_lookups() {
+        let db = create_test_db().await.unwrap();
+        for i in 0..5 {
+            create_post(&db, &format!("post-{}", i), &["rust", "web"]).await;
+        }
+
+        let loaders = Loaders::new(&db);
+        let queries = loaders.tags.loader().queries.clone();
+        let request = async_graphql::Request::new(
+            r#"{
+                posts(filter: { category: BLOG }, pagination: { limit: 10 }) {
+                    slug
+                    tags { name posts { slug } }
+                }
+            }"#,
+        )
+        .data(loaders)
+        .data(db);
+        let response = schema().execute(request).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+        let data = response.data.into_json().unwrap();
+        let posts = data["posts"].as_array().unwrap();
+        assert_eq!(posts.len(), 5);
+        for post in posts {
+            let tags = post["tags"].as_array().unwrap();
+            let names: Vec<_> = tags.iter().map(|tag| &tag["name"]).collect();
+            assert_eq!(names, [&json!("rust"), &json!("web")]);
+            assert_eq!(tags[0]["posts"].as_array().unwrap().len(), 5);
+        }
+        // One query for all five posts' tags, rather than one per post
+        assert_eq!(queries.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_drafts_require_a_caller() {
+        let db = create_test_db().await.unwrap();
+        create_post(&db, "public", &["rust"]).await;
+        create_post(&db, "draft", &["rust"]).await;
+        sqlx::query("UPDATE posts SET published = 0 WHERE slug = 'draft'")
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        let query = |user: Option<AuthUser>| {
+            let mut request = async_graphql::Request::new(
+                r#"{
+                    posts(filter: { publishedOnly: false }) {
+                        slug
+                        tags { posts(publishedOnly: false) { slug } }
+                    }
+                }"#,
+            )
+            .data(Loaders::new(&db))
+            .data(db.clone());
+            if let Some(user) = user {
+                request = request.data(user);
+            }
+            request
+        };
+
+        let response = schema().execute(query(None)).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            json!({ "posts": [{ "slug": "public", "tags": [{ "posts": [{ "slug": "public" }] }] }] })
+        );
+
+        let response = schema()
+            .execute(query(Some(AuthUser::from(&Principal::ApiKey))))
+            .await;
+        let data = response.data.into_json().unwrap();
+        assert_eq!(data["posts"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            data["posts"][0]["tags"][0]["posts"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_post_by_slug() {
+        let db = create_test_db().await.unwrap();
+        create_post(&db, "hello", &[]).await;
+
+        let query = |slug: &str| {
+            async_graphql::Request::new(format!(
+                r#"{{ post(slug: "{}") {{ title metaTitle tags {{ name }} }} }}"#,
+                slug
+            ))
+            .data(Loaders::new(&db))
+            .data(db.clone())
+        };
+        let data = schema()
+            .execute(query("hello"))
+            .await
+            .data
+            .into_json()
+            .unwrap();
+        assert_eq!(
+            data,
+            json!({ "post": { "title": "hello", "metaTitle": "hello", "tags": [] } })
+        );
+        let data = schema()
+            .execute(query("missing"))
+            .await
+            .data
+            .into_json()
+            .unwrap();
+        assert_eq!(data, json!({ "post": null }));
+    }
+
+    #[tokio::test]
+    async fn test_blank_seo_fields_fall_back() {
+        let db = create_test_db().await.unwrap();
+        create_post(&db, "blank-seo", &[]).await;
+        sqlx::query("UPDATE posts SET meta_title = '   ', meta_description = ''")
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        let request = async_graphql::Request::new(
+            r#"{ post(slug: "blank-seo") { metaTitle metaDescription } }"#,
+        )
+        .data(Loaders::new(&db))
+        .data(db.clone());
+        let data = schema().execute(request).await.data.into_json().unwrap();
+        assert_eq!(
+            data,
+            json!({ "post": { "metaTitle": "blank-seo", "metaDescription": "Description" } })
+        );
+    }
+}
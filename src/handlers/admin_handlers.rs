@@ -0,0 +1,205 @@
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+
+use axum::extract::State;
+use serde::Serialize;
+use time::{macros::format_description, OffsetDateTime};
+use tokio::sync::Mutex;
+
+use crate::{
+    db::{CacheStats, Database, MethodTimings},
+    models::maintenance::{Backup, IntegrityCheck, MigrationStatus, RenderCacheRebuild, Vacuum},
+    render::RENDERER_VERSION,
+};
+
+use super::{errors::ApiError, extract::Json};
+
+/// What the maintenance routes share: the database, where backups go,
+/// and a lock so only one of them runs at a time
+#[derive(Clone, Debug)]
+pub struct Maintenance {
+    db: Database,
+    backup_dir: Option<Arc<PathBuf>>,
+    running: Arc<Mutex<()>>,
+}
+
+impl Maintenance {
+    pub fn new(db: Database, backup_dir: Option<PathBuf>) -> Self {
+        Self {
+            db,
+            backup_dir: backup_dir.map(Arc::new),
+            running: Arc::new(Mutex::new(())),
+        }
+    }
+}
+
+/// Back up the database
+///
+/// Copies the database into a new timestamped file in the backup
+/// directory while it stays in use, and returns the file's path and size.
+/// Returns 409 Conflict while another maintenance operation is running.
+pub async fn backup(State(maintenance): State<Maintenance>) -> Result<Json<Backup>, ApiError> {
+    let _running = maintenance.running.try_lock().map_err(|_| busy())?;
+    let dir = maintenance
+        .backup_dir
+        .as_deref()
+        .expect("the backup route is only mounted with a backup directory");
+
+    let created_at = OffsetDateTime::now_utc();
+    let name = created_at
+        .format(format_description!(
+            "blog-[year][month][day]T[hour][minute][second][subsecond digits:3]Z.db"
+        ))
+        .expect("the timestamp format is valid");
+    let path = dir.join(name);
+    maintenance.db.backup_into(&path).await?;
+
+    let size_bytes = tokio::fs::metadata(&path)
+        .await
+        .map_err(ApiError::Storage)?
+        .len();
+    tracing::info!(path = %path.display(), size_bytes, "Backed up the database");
+    Ok(Json(Backup {
+        path: path.display().to_string(),
+        size_bytes,
+        created_at,
+    }))
+}
+
+/// Vacuum the database
+///
+/// Rebuilds the database file to reclaim the space of deleted rows and
+/// refreshes the query planner's statistics. Writes wait until it is done.
+/// Returns 409 Conflict while another maintenance operation is running.
+pub async fn vacuum(State(maintenance): State<Maintenance>) -> Result<Json<Vacuum>, ApiError> {
+    let _running = maintenance.running.try_lock().map_err(|_| busy())?;
+    let (size_bytes_before, size_bytes_after) = maintenance.db.vacuum().await?;
+    tracing::info!(size_bytes_before, size_bytes_after, "Vacuumed the database");
+    Ok(Json(Vacuum {
+        size_bytes_before,
+        size_bytes_after,
+    }))
+}
+
+/// Check the database's integrity
+///
+/// Runs SQLite's integrity and foreign key checks and reports what they
+/// found; `ok` is true when the database is sound. Returns 409 Conflict
+/// while another maintenance operation is running.
+pub async fn integrity_check(
+    State(maintenance): State<Maintenance>,
+) -> Result<Json<IntegrityCheck>, ApiError> {
+    let _running = maintenance.running.try_lock().map_err(|_| busy())?;
+    let check = maintenance.db.integrity_check().await?;
+    log_integrity_check(&check);
+    Ok(Json(check))
+}
+
+/// Render every post again
+///
+/// Replaces the cached HTML of every post with a rendering by the current
+/// renderer, so the first reader of each post after an upgrade doesn't
+/// wait for it. Returns 409 Conflict while another maintenance operation
+/// is running.
+pub async fn rebuild_render_cache(
+    State(maintenance): State<Maintenance>,
+) -> Result<Json<RenderCacheRebuild>, ApiError> {
+    let _running = maintenance.running.try_lock().map_err(|_| busy())?;
+    let rendered = maintenance.db.render_cache().rebuild().await?;
+    tracing::info!(
+        rendered,
+        renderer_version = RENDERER_VERSION,
+        "Rebuilt the render cache"
+    );
+    Ok(Json(RenderCacheRebuild {
+        rendered,
+        renderer_version: RENDERER_VERSION,
+    }))
+}
+
+/// Logs the outcome of an integrity check, as an error when it found
+/// problems
+pub fn log_integrity_check(check: &IntegrityCheck) {
+    if check.ok {
+        tracing::info!("Database integrity check passed");
+    } else {
+        tracing::error!(
+            problems = ?check.problems,
+            foreign_key_violations = ?check.foreign_key_violations,
+            "Database integrity check found problems"
+        );
+    }
+}
+
+/// List applied and pending migrations
+///
+/// Pending migrations are those embedded in the running server that the
+/// database hasn't applied.
+pub async fn list_migrations(
+    State(db): State<Database>,
+) -> Result<Json<MigrationStatus>, ApiError> {
+    Ok(Json(db.migration_status().await?))
+}
+
+/// What `GET /admin/metrics` reports
+#[derive(Debug, Serialize)]
+pub struct Metrics {
+    /// Durations of repository calls since startup, by method
+    pub queries: BTreeMap<&'static str, MethodTimings>,
+    pub post_cache: CacheStats,
+    /// Lookups of posts' rendered HTML
+    pub render_cache: CacheStats,
+}
+
+/// Report query timings
+///
+/// Histograms of how long each repository method has taken since the
+/// server started, and the hit rates of the post and render caches.
+pub async fn metrics(State(db): State<Database>) -> Json<Metrics> {
+    Json(Metrics {
+        queries: db.query_metrics().snapshot(),
+        post_cache: db.posts().cache_stats(),
+        render_cache: db.render_cache().stats(),
+    })
+}
+
+fn busy() -> ApiError {
+    ApiError::Busy("Another maintenance operation is already running".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+
+    use super::*;
+    use crate::config::Config;
+
+    #[tokio::test]
+    async fn test_operations_run_one_at_a_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            database_url: format!("sqlite://{}?mode=rwc", dir.path().join("blog.db").display()),
+            ..Config::default_for_tests()
+        };
+        let maintenance = Maintenance::new(
+            Database::new(&config).await.unwrap(),
+            Some(dir.path().to_path_buf()),
+        );
+
+        let held = maintenance.running.clone();
+        let guard = held.lock().await;
+        for error in [
+            backup(State(maintenance.clone())).await.unwrap_err(),
+            vacuum(State(maintenance.clone())).await.unwrap_err(),
+            integrity_check(State(maintenance.clone()))
+                .await
+                .unwrap_err(),
+        ] {
+            assert_eq!(error.status(), StatusCode::CONFLICT);
+        }
+        drop(guard);
+
+        backup(State(maintenance.clone())).await.unwrap();
+        vacuum(State(maintenance.clone())).await.unwrap();
+        assert!(integrity_check(State(maintenance)).await.unwrap().0.ok);
+    }
+}
@@ -0,0 +1,45 @@
+use axum::{extract::State, http::StatusCode};
+
+use crate::{
+    db::Database,
+    models::api_key::{ApiKey, CreateApiKey, CreatedApiKey},
+};
+
+use super::{
+    errors::ApiError,
+    extract::{Json, Path},
+    responses::Created,
+};
+
+/// Create an API key
+///
+/// Returns 201 Created with the key's plaintext in `key`. Only a hash is
+/// stored, so this is the one time the key can be read.
+pub async fn create_api_key(
+    State(db): State<Database>,
+    Json(request): Json<CreateApiKey>,
+) -> Result<Created<CreatedApiKey>, ApiError> {
+    let created = db.api_keys().create(request).await?;
+    Ok(Created::new(
+        format!("/api-keys/{}", created.api_key.id),
+        created,
+    ))
+}
+
+/// List all API keys, including revoked ones, oldest first
+pub async fn list_api_keys(State(db): State<Database>) -> Result<Json<Vec<ApiKey>>, ApiError> {
+    let keys = db.api_keys().list().await?;
+    Ok(Json(keys))
+}
+
+/// Revoke an API key
+///
+/// The key stops being accepted immediately but stays in the listing with
+/// its `revoked_at` time.
+pub async fn revoke_api_key(
+    State(db): State<Database>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    db.api_keys().revoke(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
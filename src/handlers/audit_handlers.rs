@@ -0,0 +1,80 @@
+use axum::extract::State;
+use serde::Deserialize;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{
+    db::Database,
+    models::{
+        activity::ActivityEntry,
+        audit::{AuditEntity, AuditEntry, AuditQuery},
+    },
+};
+
+use super::{
+    errors::ApiError,
+    extract::{Json, Query},
+    responses::Page,
+};
+
+/// Query parameters for reading the audit log
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    /// Only entries about this type of entity: `post` or `tag`
+    pub entity: Option<AuditEntity>,
+    /// Only entries about the entity with this ID; requires `entity`
+    pub entity_id: Option<i64>,
+    #[serde(default = "default_audit_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_audit_limit() -> i64 {
+    50
+}
+
+/// List audit log entries, newest first
+///
+/// Paginated with limit/offset, with the number of matching entries in the
+/// `X-Total-Count` header.
+pub async fn list_audit_log(
+    State(db): State<Database>,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Page<AuditEntry>, ApiError> {
+    let (entries, total) = db
+        .audit()
+        .list(&AuditQuery {
+            entity_type: query.entity,
+            entity_id: query.entity_id,
+            limit: query.limit,
+            offset: query.offset,
+        })
+        .await?;
+    Ok(Page::new(entries, total))
+}
+
+/// Query parameters for the activity feed
+#[derive(Debug, Deserialize)]
+pub struct ActivityQuery {
+    /// RFC 3339 timestamp; only changes made at or before it are listed
+    pub before: Option<String>,
+    #[serde(default = "default_audit_limit")]
+    pub limit: i64,
+}
+
+/// List recent changes to posts and tags, newest first
+///
+/// To page back in time, send the oldest entry's `created_at` as `before`.
+/// Changes made within that same second are listed again, so skip entries
+/// whose `id` was already seen.
+pub async fn list_activity(
+    State(db): State<Database>,
+    Query(query): Query<ActivityQuery>,
+) -> Result<Json<Vec<ActivityEntry>>, ApiError> {
+    let before = query
+        .before
+        .map(|before| OffsetDateTime::parse(&before, &Rfc3339))
+        .transpose()
+        .map_err(|e| ApiError::InvalidInput(format!("Invalid before timestamp: {}", e)))?;
+    Ok(Json(db.audit().activity(before, query.limit).await?))
+}
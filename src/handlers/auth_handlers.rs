@@ -0,0 +1,86 @@
+use axum::extract::State;
+use serde::{Deserialize, Serialize};
+use tokio::sync::OnceCell;
+
+use crate::{
+    auth::{AuthError, Claims, JwtAuth},
+    db::{Database, DatabaseError},
+    models::user::{hash_password, verify_password, User},
+};
+
+use super::{errors::ApiError, extract::Json};
+
+/// Request body for logging in
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: String,
+}
+
+/// A freshly issued access token
+#[derive(Debug, Serialize)]
+pub struct LoginResponse {
+    pub access_token: String,
+    pub token_type: &'static str,
+    /// Seconds until the token expires
+    pub expires_in: i64,
+}
+
+/// Hash checked when the email is unknown, so that a failed login takes
+/// as long whether or not the account exists. Hashed the first time it's
+/// needed.
+async fn dummy_password_hash() -> &'static str {
+    static HASH: OnceCell<String> = OnceCell::const_new();
+    HASH.get_or_init(|| async {
+        hash_password("not a real password")
+            .await
+            // An empty hash would fail instantly and reveal unknown emails
+            .expect("hashing a constant password can't fail")
+    })
+    .await
+}
+
+/// Log in with email and password
+///
+/// Returns a signed access token for use as `Authorization: Bearer <token>`.
+/// Unknown emails and wrong passwords get the same 401 response.
+pub async fn login(
+    State(db): State<Database>,
+    State(auth): State<JwtAuth>,
+    Json(request): Json<LoginRequest>,
+) -> Result<Json<LoginResponse>, ApiError> {
+    let user = match db.users().find_by_email(&request.email).await {
+        Ok(user) => Some(user),
+        Err(DatabaseError::NotFound(_)) => None,
+        Err(e) => return Err(e.into()),
+    };
+
+    let password_hash = match &user {
+        Some(user) => &user.password_hash,
+        None => dummy_password_hash().await,
+    };
+    let verified = verify_password(&request.password, password_hash).await;
+
+    let user = user
+        .filter(|_| verified)
+        .ok_or(AuthError::InvalidCredentials)?;
+
+    Ok(Json(LoginResponse {
+        access_token: auth.issue(&user)?,
+        token_type: "Bearer",
+        expires_in: auth.expiry().whole_seconds(),
+    }))
+}
+
+/// Get the user the request's access token was issued to
+///
+/// Returns 401 when the token is missing, invalid or expired, or when the
+/// user has since been deleted.
+pub async fn me(State(db): State<Database>, claims: Claims) -> Result<Json<User>, ApiError> {
+    match db.users().find_by_id(claims.user_id()?).await {
+        Ok(user) => Ok(Json(user)),
+        Err(DatabaseError::NotFound(_)) => Err(AuthError::InvalidToken.into()),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,51 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap},
+    response::Redirect,
+};
+
+use crate::{
+    db::{Database, DatabaseError},
+    models::click::DailyClicks,
+};
+
+use super::{
+    errors::ApiError,
+    extract::{Json, Path},
+};
+
+/// Follow a post's external link
+///
+/// Records the click, with the page the reader came from, and answers with
+/// 307 Temporary Redirect to the post's `external_url`; 404 when the post
+/// has none. A click that can't be recorded is logged and the reader is
+/// redirected all the same.
+pub async fn visit_post(
+    State(db): State<Database>,
+    Path(post_id): Path<i64>,
+    headers: HeaderMap,
+) -> Result<Redirect, ApiError> {
+    let post = db.posts().find_by_id(post_id).await?;
+    let Some(external_url) = post.external_url else {
+        return Err(DatabaseError::not_found("External URL of post", &post_id.to_string()).into());
+    };
+
+    let referer = headers
+        .get(header::REFERER)
+        .and_then(|value| value.to_str().ok());
+    if let Err(e) = db.clicks().record(post_id, referer).await {
+        tracing::warn!(post_id, "Failed to record link click: {}", e);
+    }
+
+    Ok(Redirect::temporary(&external_url))
+}
+
+/// Count clicks on a post's external link per day, oldest day first
+///
+/// Days without clicks are left out.
+pub async fn list_post_clicks(
+    State(db): State<Database>,
+    Path(post_id): Path<i64>,
+) -> Result<Json<Vec<DailyClicks>>, ApiError> {
+    Ok(Json(db.clicks().daily_counts(post_id).await?))
+}
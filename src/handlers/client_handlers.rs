@@ -0,0 +1,17 @@
+//! TypeScript declarations for API clients
+
+use axum::{http::header, response::IntoResponse};
+
+use crate::client_types::client_types as render;
+
+/// Serve the TypeScript declarations of the API's request and response
+/// bodies
+pub async fn client_types() -> impl IntoResponse {
+    (
+        [(
+            header::CONTENT_TYPE,
+            "application/typescript; charset=utf-8",
+        )],
+        render(),
+    )
+}
@@ -0,0 +1,135 @@
+use axum::{extract::State, http::StatusCode};
+use serde::Deserialize;
+
+use crate::{
+    auth::{FormToken, FormTokenError, FormTokens},
+    db::Database,
+    models::comment::{Comment, CommentStatus, CreateComment},
+};
+
+use super::{
+    errors::ApiError,
+    extract::{Json, Path, Query},
+    responses::Page,
+};
+
+/// Issue a token for a comment form on a post
+///
+/// The form must send it back as `form_token`; it records when the form
+/// was served, so submissions that come too fast or too late can be told
+/// apart.
+pub async fn comment_form_token(
+    State(db): State<Database>,
+    State(tokens): State<FormTokens>,
+    Path(post_id): Path<i64>,
+) -> Result<Json<FormToken>, ApiError> {
+    db.posts().find_by_id(post_id).await?;
+    Ok(Json(tokens.issue(post_id)))
+}
+
+/// Submit a comment on a post
+///
+/// Open to anyone, within the per-client comment rate limit. Returns 202
+/// Accepted with the stored comment: it stays hidden until an admin
+/// approves it.
+///
+/// Comments that fill in the `website` honeypot or come sooner than
+/// `MIN_FORM_AGE` after their form token was issued are stored as spam,
+/// but answered exactly like any other, so bots can't tell they were
+/// caught. Tokens that are forged or expired are refused outright, since
+/// people need to know to reload the form.
+pub async fn create_comment(
+    State(db): State<Database>,
+    State(tokens): State<FormTokens>,
+    Path(post_id): Path<i64>,
+    Json(comment): Json<CreateComment>,
+) -> Result<(StatusCode, Json<Comment>), ApiError> {
+    let spam_reason = match tokens.verify(post_id, &comment.form_token) {
+        Ok(()) if comment.fills_honeypot() => Some("honeypot"),
+        Ok(()) => None,
+        Err(FormTokenError::TooFast) => Some("too fast"),
+        Err(e) => return Err(ApiError::validation("form_token", &e.to_string())),
+    };
+    let status = match spam_reason {
+        Some(_) => CommentStatus::Spam,
+        None => CommentStatus::Pending,
+    };
+
+    let mut comment = db.comments().create(post_id, comment, status).await?;
+    if let Some(reason) = spam_reason {
+        tracing::info!(comment_id = comment.id, reason, "Comment marked as spam");
+        // Answer as if the comment were waiting for moderation
+        comment.status = CommentStatus::Pending;
+    }
+    Ok((StatusCode::ACCEPTED, Json(comment)))
+}
+
+/// List a post's approved comments, oldest first
+pub async fn list_comments(
+    State(db): State<Database>,
+    Path(post_id): Path<i64>,
+) -> Result<Json<Vec<Comment>>, ApiError> {
+    let comments = db.comments().list_approved(post_id).await?;
+    Ok(Json(comments))
+}
+
+/// Query parameters for the moderation queue
+#[derive(Debug, Deserialize)]
+pub struct ModerationQuery {
+    /// Which comments to list; pending ones by default
+    #[serde(default)]
+    pub status: CommentStatus,
+    #[serde(default = "default_moderation_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_moderation_limit() -> i64 {
+    50
+}
+
+/// List comments awaiting moderation, or in another status, oldest first
+///
+/// Paginated with limit/offset, with the number of comments in the status
+/// in the `X-Total-Count` header.
+pub async fn list_comments_for_moderation(
+    State(db): State<Database>,
+    Query(query): Query<ModerationQuery>,
+) -> Result<Page<Comment>, ApiError> {
+    let (comments, total) = db
+        .comments()
+        .list_by_status(query.status, query.limit, query.offset)
+        .await?;
+    Ok(Page::new(comments, total))
+}
+
+/// Approve a comment, showing it under its post
+pub async fn approve_comment(
+    State(db): State<Database>,
+    Path(id): Path<i64>,
+) -> Result<Json<Comment>, ApiError> {
+    let comment = db
+        .comments()
+        .set_status(id, CommentStatus::Approved)
+        .await?;
+    Ok(Json(comment))
+}
+
+/// Reject a comment as spam, hiding it from readers
+pub async fn reject_comment(
+    State(db): State<Database>,
+    Path(id): Path<i64>,
+) -> Result<Json<Comment>, ApiError> {
+    let comment = db.comments().set_status(id, CommentStatus::Spam).await?;
+    Ok(Json(comment))
+}
+
+/// Delete a comment for good
+pub async fn delete_comment(
+    State(db): State<Database>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    db.comments().delete(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
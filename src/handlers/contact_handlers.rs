@@ -0,0 +1,70 @@
+use axum::{extract::State, http::StatusCode};
+use serde::Deserialize;
+
+use crate::{
+    db::Database,
+    models::contact::{ContactMessage, CreateContactMessage, UpdateContactMessage},
+    notify::Notifier,
+};
+
+use super::{
+    errors::ApiError,
+    extract::{Json, Path, Query},
+    responses::Page,
+};
+
+/// Send a message through the contact form
+///
+/// Open to anyone, within the per-client contact rate limit. The message
+/// is stored and announced through the notification webhook; returns 202
+/// Accepted with the stored message.
+pub async fn create_contact_message(
+    State(db): State<Database>,
+    State(notifier): State<Notifier>,
+    Json(message): Json<CreateContactMessage>,
+) -> Result<(StatusCode, Json<ContactMessage>), ApiError> {
+    let message = db.contact().create(message).await?;
+    notifier.contact_message(&message);
+    Ok((StatusCode::ACCEPTED, Json(message)))
+}
+
+/// Query parameters for listing contact messages
+#[derive(Debug, Deserialize)]
+pub struct ContactQuery {
+    /// Only list messages nobody has marked read
+    #[serde(default)]
+    pub unread: bool,
+    #[serde(default = "default_contact_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_contact_limit() -> i64 {
+    50
+}
+
+/// List contact messages, newest first
+///
+/// Paginated with limit/offset, with the number of matching messages in
+/// the `X-Total-Count` header.
+pub async fn list_contact_messages(
+    State(db): State<Database>,
+    Query(query): Query<ContactQuery>,
+) -> Result<Page<ContactMessage>, ApiError> {
+    let (messages, total) = db
+        .contact()
+        .list(query.unread, query.limit, query.offset)
+        .await?;
+    Ok(Page::new(messages, total))
+}
+
+/// Mark a contact message read or unread
+pub async fn update_contact_message(
+    State(db): State<Database>,
+    Path(id): Path<i64>,
+    Json(update): Json<UpdateContactMessage>,
+) -> Result<Json<ContactMessage>, ApiError> {
+    let message = db.contact().set_read(id, update.read).await?;
+    Ok(Json(message))
+}
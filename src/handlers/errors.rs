@@ -0,0 +1,683 @@
+use axum::{
+    extract::{
+        multipart::{MultipartError, MultipartRejection},
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+    },
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use crate::{
+    auth::AuthError, db::DatabaseError, middleware::RequestId, models::errors::FieldError,
+    storage::StorageError,
+};
+
+/// Stable, machine-readable error codes returned in `ErrorResponse::code`
+pub mod codes {
+    pub const NOT_FOUND: &str = "not_found";
+    pub const METHOD_NOT_ALLOWED: &str = "method_not_allowed";
+    pub const DUPLICATE_ENTRY: &str = "duplicate_entry";
+    pub const VALIDATION_ERROR: &str = "validation_error";
+    pub const INVALID_INPUT: &str = "invalid_input";
+    pub const MALFORMED_BODY: &str = "malformed_body";
+    pub const INVALID_BODY: &str = "invalid_body";
+    pub const UNSUPPORTED_MEDIA_TYPE: &str = "unsupported_media_type";
+    pub const UNAUTHORIZED: &str = "unauthorized";
+    pub const FORBIDDEN: &str = "forbidden";
+    pub const NOT_AUTHOR: &str = "not_author";
+    pub const RATE_LIMITED: &str = "rate_limited";
+    pub const PAYLOAD_TOO_LARGE: &str = "payload_too_large";
+    pub const TIMEOUT: &str = "timeout";
+    pub const INTERNAL_ERROR: &str = "internal_error";
+    pub const CONFIGURATION_ERROR: &str = "configuration_error";
+    pub const TRANSACTION_ERROR: &str = "transaction_error";
+    pub const MIGRATION_ERROR: &str = "migration_error";
+    pub const DATABASE_ERROR: &str = "database_error";
+    pub const BUSY: &str = "busy";
+    pub const CONFLICT: &str = "conflict";
+}
+
+/// Every error code the API can return, paired with a short description.
+/// Clients can rely on these codes staying stable across releases.
+pub const fn error_codes() -> &'static [(&'static str, &'static str)] {
+    &[
+        (codes::NOT_FOUND, "The requested resource does not exist"),
+        (
+            codes::METHOD_NOT_ALLOWED,
+            "The route exists but does not accept this method; see `allowed_methods`",
+        ),
+        (
+            codes::DUPLICATE_ENTRY,
+            "A resource with the same unique value already exists",
+        ),
+        (
+            codes::VALIDATION_ERROR,
+            "The request failed validation; `errors` lists every broken rule with its field",
+        ),
+        (
+            codes::INVALID_INPUT,
+            "The request could not be interpreted, e.g. a malformed parameter",
+        ),
+        (codes::MALFORMED_BODY, "The request body is not valid JSON"),
+        (
+            codes::INVALID_BODY,
+            "The request body is valid JSON but has missing, unknown or mistyped fields",
+        ),
+        (
+            codes::UNSUPPORTED_MEDIA_TYPE,
+            "The request body content type is not accepted",
+        ),
+        (
+            codes::UNAUTHORIZED,
+            "The request lacks a valid access token, or the login credentials are wrong",
+        ),
+        (
+            codes::FORBIDDEN,
+            "The caller is authenticated but its role does not allow this action",
+        ),
+        (
+            codes::NOT_AUTHOR,
+            "Only the post's author or an admin can change this post",
+        ),
+        (
+            codes::RATE_LIMITED,
+            "Too many requests from this client; retry after `Retry-After` seconds",
+        ),
+        (
+            codes::PAYLOAD_TOO_LARGE,
+            "The request body, or post content within it, exceeds the server's size limit",
+        ),
+        (
+            codes::TIMEOUT,
+            "The server took too long to handle the request",
+        ),
+        (
+            codes::INTERNAL_ERROR,
+            "The server hit a bug while handling the request",
+        ),
+        (codes::CONFIGURATION_ERROR, "The server is misconfigured"),
+        (
+            codes::TRANSACTION_ERROR,
+            "The database could not start or complete a transaction",
+        ),
+        (codes::MIGRATION_ERROR, "A database migration failed"),
+        (
+            codes::DATABASE_ERROR,
+            "An unexpected database error occurred",
+        ),
+        (
+            codes::BUSY,
+            "Another maintenance operation is running; retry once it finishes",
+        ),
+        (
+            codes::CONFLICT,
+            "The resource's current state doesn't allow the change",
+        ),
+    ]
+}
+
+/// Custom error type for our API endpoints that maps both database
+/// and validation errors to appropriate HTTP responses
+#[derive(thiserror::Error, Debug)]
+pub enum ApiError {
+    #[error("Database error: {0}")]
+    Database(DatabaseError),
+
+    /// A well-formed request that fails business validation; `errors`
+    /// holds every broken rule, `field` and `message` sum them up
+    #[error("Validation error: {message}")]
+    Validation {
+        field: Option<&'static str>,
+        message: String,
+        errors: Vec<FieldError>,
+    },
+
+    #[error("Authentication error: {0}")]
+    Auth(#[from] AuthError),
+
+    #[error("Route not found")]
+    RouteNotFound,
+
+    #[error("File not found")]
+    FileNotFound,
+
+    #[error("Method not allowed")]
+    MethodNotAllowed(Vec<String>),
+
+    /// No post has the requested slug; holds published slugs close to it
+    #[error("Post not found: {message}")]
+    SlugNotFound {
+        message: String,
+        did_you_mean: Vec<String>,
+    },
+
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("Malformed request body: {0}")]
+    MalformedBody(String),
+
+    #[error("Invalid request body: {0}")]
+    InvalidBody(String),
+
+    #[error("Unsupported media type: {0}")]
+    UnsupportedMediaType(String),
+
+    /// The client is over its rate limit and may retry after this many seconds
+    #[error("Rate limited for {retry_after}s")]
+    RateLimited { retry_after: u64 },
+
+    #[error("Request body too large")]
+    PayloadTooLarge,
+
+    #[error("Request timed out")]
+    Timeout,
+
+    /// Writing an uploaded file failed
+    #[error("Storage error: {0}")]
+    Storage(std::io::Error),
+
+    /// A handler panicked; holds the panic message
+    #[error("Handler panicked: {0}")]
+    Panic(String),
+
+    /// An operation that must run alone found another one running
+    #[error("Busy: {0}")]
+    Busy(String),
+}
+
+impl From<DatabaseError> for ApiError {
+    fn from(error: DatabaseError) -> Self {
+        match error {
+            DatabaseError::Validation {
+                field,
+                message,
+                errors,
+            } => ApiError::Validation {
+                field,
+                message,
+                errors,
+            },
+            e => ApiError::Database(e),
+        }
+    }
+}
+
+impl ApiError {
+    pub fn validation(field: &'static str, message: &str) -> Self {
+        DatabaseError::invalid_field(field, message).into()
+    }
+
+    /// Classifies a serde_json error: syntax problems are malformed bodies,
+    /// while well-formed JSON with the wrong shape is an invalid body
+    pub fn from_json_error(error: serde_json::Error) -> Self {
+        match error.classify() {
+            serde_json::error::Category::Data => ApiError::InvalidBody(error.to_string()),
+            _ => ApiError::MalformedBody(error.to_string()),
+        }
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection {
+            JsonRejection::JsonDataError(e) => ApiError::InvalidBody(e.body_text()),
+            JsonRejection::MissingJsonContentType(e) => {
+                ApiError::UnsupportedMediaType(e.body_text())
+            }
+            e if e.status() == StatusCode::PAYLOAD_TOO_LARGE => ApiError::PayloadTooLarge,
+            e => ApiError::MalformedBody(e.body_text()),
+        }
+    }
+}
+
+impl From<MultipartRejection> for ApiError {
+    fn from(rejection: MultipartRejection) -> Self {
+        ApiError::UnsupportedMediaType(rejection.body_text())
+    }
+}
+
+impl From<MultipartError> for ApiError {
+    fn from(error: MultipartError) -> Self {
+        if error.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            ApiError::PayloadTooLarge
+        } else {
+            ApiError::MalformedBody(error.body_text())
+        }
+    }
+}
+
+impl From<StorageError> for ApiError {
+    fn from(error: StorageError) -> Self {
+        match error {
+            StorageError::UnsupportedType => ApiError::UnsupportedMediaType(error.to_string()),
+            StorageError::Io(e) => ApiError::Storage(e),
+        }
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        ApiError::InvalidInput(rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        ApiError::InvalidInput(rejection.body_text())
+    }
+}
+
+impl ApiError {
+    /// The stable error code reported to clients
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::Database(DatabaseError::NotFound(_))
+            | ApiError::SlugNotFound { .. }
+            | ApiError::RouteNotFound
+            | ApiError::FileNotFound => codes::NOT_FOUND,
+            ApiError::MethodNotAllowed(_) => codes::METHOD_NOT_ALLOWED,
+            ApiError::Database(DatabaseError::DuplicateEntry { .. }) => codes::DUPLICATE_ENTRY,
+            ApiError::Validation { .. } | ApiError::Database(DatabaseError::Validation { .. }) => {
+                codes::VALIDATION_ERROR
+            }
+            ApiError::Auth(AuthError::Signing(_))
+            | ApiError::Database(DatabaseError::Configuration(_)) => codes::CONFIGURATION_ERROR,
+            ApiError::Auth(AuthError::InsufficientRole { .. }) => codes::FORBIDDEN,
+            ApiError::Auth(AuthError::NotAuthor) => codes::NOT_AUTHOR,
+            ApiError::Auth(_) => codes::UNAUTHORIZED,
+            ApiError::Database(DatabaseError::Transaction(_)) => codes::TRANSACTION_ERROR,
+            ApiError::Database(DatabaseError::Migration(_)) => codes::MIGRATION_ERROR,
+            ApiError::Database(DatabaseError::Sqlx(_)) => codes::DATABASE_ERROR,
+            ApiError::InvalidInput(_) => codes::INVALID_INPUT,
+            ApiError::MalformedBody(_) => codes::MALFORMED_BODY,
+            ApiError::InvalidBody(_) => codes::INVALID_BODY,
+            ApiError::UnsupportedMediaType(_) => codes::UNSUPPORTED_MEDIA_TYPE,
+            ApiError::RateLimited { .. } => codes::RATE_LIMITED,
+            ApiError::PayloadTooLarge | ApiError::Database(DatabaseError::TooLarge { .. }) => {
+                codes::PAYLOAD_TOO_LARGE
+            }
+            ApiError::Timeout => codes::TIMEOUT,
+            ApiError::Storage(_) | ApiError::Panic(_) => codes::INTERNAL_ERROR,
+            ApiError::Busy(_) => codes::BUSY,
+            ApiError::Database(DatabaseError::Conflict(_)) => codes::CONFLICT,
+        }
+    }
+
+    /// The HTTP status this error is reported with
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::Database(DatabaseError::NotFound(_))
+            | ApiError::SlugNotFound { .. }
+            | ApiError::RouteNotFound
+            | ApiError::FileNotFound => StatusCode::NOT_FOUND,
+            ApiError::MethodNotAllowed(_) => StatusCode::METHOD_NOT_ALLOWED,
+            ApiError::Database(
+                DatabaseError::DuplicateEntry { .. } | DatabaseError::Conflict(_),
+            ) => StatusCode::CONFLICT,
+            ApiError::Validation { .. } | ApiError::Database(DatabaseError::Validation { .. }) => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            ApiError::Database(DatabaseError::Transaction(_)) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Auth(AuthError::Signing(_)) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::Auth(AuthError::InsufficientRole { .. } | AuthError::NotAuthor) => {
+                StatusCode::FORBIDDEN
+            }
+            ApiError::Auth(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Database(
+                DatabaseError::Configuration(_)
+                | DatabaseError::Migration(_)
+                | DatabaseError::Sqlx(_),
+            ) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::InvalidInput(_) | ApiError::MalformedBody(_) | ApiError::InvalidBody(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            ApiError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ApiError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::PayloadTooLarge | ApiError::Database(DatabaseError::TooLarge { .. }) => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            ApiError::Timeout => StatusCode::GATEWAY_TIMEOUT,
+            ApiError::Storage(_) | ApiError::Panic(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::Busy(_) => StatusCode::CONFLICT,
+        }
+    }
+
+    /// The message and offending field shown to clients. Server errors get
+    /// a generic message, so internal details never leak.
+    pub(crate) fn client_message(self) -> (String, Option<String>) {
+        match self {
+            ApiError::RouteNotFound => ("route not found".to_string(), None),
+            ApiError::FileNotFound => ("file not found".to_string(), None),
+            ApiError::MethodNotAllowed(methods) => (
+                format!("method not allowed; allowed: {}", methods.join(", ")),
+                None,
+            ),
+            ApiError::Database(DatabaseError::DuplicateEntry { field, message }) => {
+                (message, field)
+            }
+            ApiError::Database(DatabaseError::NotFound(msg) | DatabaseError::Conflict(msg))
+            | ApiError::SlugNotFound { message: msg, .. }
+            | ApiError::InvalidInput(msg)
+            | ApiError::MalformedBody(msg)
+            | ApiError::InvalidBody(msg)
+            | ApiError::UnsupportedMediaType(msg)
+            | ApiError::Busy(msg) => (msg, None),
+            ApiError::Validation { field, message, .. }
+            | ApiError::Database(
+                DatabaseError::Validation { field, message, .. }
+                | DatabaseError::TooLarge { field, message },
+            ) => (message, field.map(str::to_string)),
+            ApiError::Auth(AuthError::Signing(_))
+            | ApiError::Database(DatabaseError::Configuration(_)) => {
+                ("Server is misconfigured".to_string(), None)
+            }
+            ApiError::Auth(error) => (error.to_string(), None),
+            ApiError::Database(DatabaseError::Transaction(_)) => {
+                ("Database is temporarily unavailable".to_string(), None)
+            }
+            ApiError::Database(DatabaseError::Migration(_) | DatabaseError::Sqlx(_))
+            | ApiError::Storage(_)
+            | ApiError::Panic(_) => ("Internal server error".to_string(), None),
+            ApiError::RateLimited { retry_after } => (
+                format!("Too many requests; retry in {} seconds", retry_after),
+                None,
+            ),
+            ApiError::PayloadTooLarge => ("Request body is too large".to_string(), None),
+            ApiError::Timeout => ("Request took too long to handle".to_string(), None),
+        }
+    }
+}
+
+/// Convert our ApiError into appropriate HTTP responses
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let code = self.code();
+
+        if status.is_server_error() {
+            tracing::error!(error = %self, code, "request failed");
+        }
+
+        let retry_after = match &self {
+            ApiError::RateLimited { retry_after } => Some(*retry_after),
+            _ => None,
+        };
+
+        let allowed_methods = match &self {
+            ApiError::MethodNotAllowed(methods) => Some(methods.clone()),
+            _ => None,
+        };
+
+        let did_you_mean = match &self {
+            ApiError::SlugNotFound { did_you_mean, .. } => Some(did_you_mean.clone()),
+            _ => None,
+        };
+
+        let errors = match &self {
+            ApiError::Validation { errors, .. }
+            | ApiError::Database(DatabaseError::Validation { errors, .. }) => Some(errors.clone()),
+            _ => None,
+        };
+
+        // Internal details are logged above but never sent to the client
+        let (message, field) = self.client_message();
+
+        // Server errors carry the request id, so users can report them
+        let request_id = if status.is_server_error() {
+            RequestId::current().map(|id| id.0)
+        } else {
+            None
+        };
+
+        let body = Json(ErrorResponse {
+            code,
+            message,
+            field,
+            allowed_methods,
+            did_you_mean,
+            errors,
+            request_id,
+        });
+        let mut response = (status, body).into_response();
+        if status == StatusCode::UNAUTHORIZED {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+        if let Some(retry_after) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        response
+    }
+}
+
+/// Consistent error response structure for all API errors
+#[derive(Debug, Serialize)]
+pub struct ErrorResponse {
+    /// Stable machine-readable code, see `error_codes()`
+    pub code: &'static str,
+    /// Human-readable description of the problem
+    pub message: String,
+    /// Offending request field for validation and duplicate errors,
+    /// otherwise null
+    pub field: Option<String>,
+    /// Methods the route accepts, only present on 405 responses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_methods: Option<Vec<String>>,
+    /// Published slugs close to a missing one, only present on 404
+    /// responses for post slugs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub did_you_mean: Option<Vec<String>>,
+    /// Every broken rule with its field, only present on validation
+    /// errors. `field` and `message` describe the first of them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<FieldError>>,
+    /// Id of the failed request, only present on 5xx responses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn error_body(error: ApiError) -> (StatusCode, serde_json::Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_error_codes_per_variant() {
+        let cases = vec![
+            (
+                ApiError::Database(DatabaseError::not_found("Post", "1")),
+                StatusCode::NOT_FOUND,
+                codes::NOT_FOUND,
+            ),
+            (
+                ApiError::Database(DatabaseError::duplicate("Post", Some("slug"), "hello")),
+                StatusCode::CONFLICT,
+                codes::DUPLICATE_ENTRY,
+            ),
+            (
+                ApiError::from(DatabaseError::invalid_field("slug", "Invalid slug format")),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                codes::VALIDATION_ERROR,
+            ),
+            (
+                ApiError::validation("name", "Invalid tag name format"),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                codes::VALIDATION_ERROR,
+            ),
+            (
+                ApiError::Database(DatabaseError::Configuration("missing".to_string())),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                codes::CONFIGURATION_ERROR,
+            ),
+            (
+                ApiError::Database(DatabaseError::Transaction("busy".to_string())),
+                StatusCode::SERVICE_UNAVAILABLE,
+                codes::TRANSACTION_ERROR,
+            ),
+            (
+                ApiError::Database(DatabaseError::Migration(
+                    sqlx::migrate::MigrateError::VersionMissing(1),
+                )),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                codes::MIGRATION_ERROR,
+            ),
+            (
+                ApiError::Database(DatabaseError::Sqlx(sqlx::Error::RowNotFound)),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                codes::DATABASE_ERROR,
+            ),
+            (
+                ApiError::Auth(AuthError::ExpiredToken),
+                StatusCode::UNAUTHORIZED,
+                codes::UNAUTHORIZED,
+            ),
+            (
+                ApiError::Auth(AuthError::InsufficientRole {
+                    required: crate::models::user::UserRole::Admin,
+                }),
+                StatusCode::FORBIDDEN,
+                codes::FORBIDDEN,
+            ),
+            (
+                ApiError::Auth(AuthError::NotAuthor),
+                StatusCode::FORBIDDEN,
+                codes::NOT_AUTHOR,
+            ),
+            (
+                ApiError::Auth(AuthError::Signing("bad key".to_string())),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                codes::CONFIGURATION_ERROR,
+            ),
+            (
+                ApiError::RouteNotFound,
+                StatusCode::NOT_FOUND,
+                codes::NOT_FOUND,
+            ),
+            (
+                ApiError::FileNotFound,
+                StatusCode::NOT_FOUND,
+                codes::NOT_FOUND,
+            ),
+            (
+                ApiError::MethodNotAllowed(vec!["GET".to_string()]),
+                StatusCode::METHOD_NOT_ALLOWED,
+                codes::METHOD_NOT_ALLOWED,
+            ),
+            (
+                ApiError::InvalidInput("bad".to_string()),
+                StatusCode::BAD_REQUEST,
+                codes::INVALID_INPUT,
+            ),
+            (
+                ApiError::MalformedBody("EOF".to_string()),
+                StatusCode::BAD_REQUEST,
+                codes::MALFORMED_BODY,
+            ),
+            (
+                ApiError::InvalidBody("missing field".to_string()),
+                StatusCode::BAD_REQUEST,
+                codes::INVALID_BODY,
+            ),
+            (
+                ApiError::UnsupportedMediaType("text/plain".to_string()),
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                codes::UNSUPPORTED_MEDIA_TYPE,
+            ),
+            (
+                ApiError::RateLimited { retry_after: 3 },
+                StatusCode::TOO_MANY_REQUESTS,
+                codes::RATE_LIMITED,
+            ),
+            (
+                ApiError::PayloadTooLarge,
+                StatusCode::PAYLOAD_TOO_LARGE,
+                codes::PAYLOAD_TOO_LARGE,
+            ),
+            (
+                ApiError::Timeout,
+                StatusCode::GATEWAY_TIMEOUT,
+                codes::TIMEOUT,
+            ),
+            (
+                ApiError::from(StorageError::UnsupportedType),
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                codes::UNSUPPORTED_MEDIA_TYPE,
+            ),
+            (
+                ApiError::from(StorageError::Io(std::io::Error::other("disk full"))),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                codes::INTERNAL_ERROR,
+            ),
+            (
+                ApiError::Busy("a backup is running".to_string()),
+                StatusCode::CONFLICT,
+                codes::BUSY,
+            ),
+            (
+                ApiError::Database(DatabaseError::Conflict("Post 1 is published".to_string())),
+                StatusCode::CONFLICT,
+                codes::CONFLICT,
+            ),
+            (
+                ApiError::Panic("oops".to_string()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                codes::INTERNAL_ERROR,
+            ),
+        ];
+
+        for (error, expected_status, expected_code) in cases {
+            let (status, body) = error_body(error).await;
+            assert_eq!(status, expected_status);
+            assert_eq!(body["code"], expected_code);
+            assert!(body["message"].is_string());
+            assert!(
+                error_codes().iter().any(|(code, _)| *code == expected_code),
+                "{} should be documented",
+                expected_code
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validation_error_includes_field() {
+        let (_, body) = error_body(ApiError::from(DatabaseError::from(
+            crate::models::errors::PostError::InvalidSlug,
+        )))
+        .await;
+        assert_eq!(body["field"], "slug");
+
+        let (_, body) = error_body(ApiError::Database(DatabaseError::duplicate(
+            "Tag",
+            Some("name"),
+            "rust",
+        )))
+        .await;
+        assert_eq!(body["field"], "name");
+
+        let (_, body) = error_body(ApiError::InvalidInput("bad".to_string())).await;
+        assert!(body["field"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_internal_errors_hide_details() {
+        let (_, body) = error_body(ApiError::Database(DatabaseError::Configuration(
+            "DATABASE_URL must be set".to_string(),
+        )))
+        .await;
+        assert!(!body["message"].as_str().unwrap().contains("DATABASE_URL"));
+    }
+}
@@ -0,0 +1,79 @@
+//! JSON responses for requests that never reach a handler, or whose
+//! handler never returns
+
+use std::any::Any;
+
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use super::errors::ApiError;
+
+/// Router fallback for paths that match no route
+pub async fn route_not_found() -> ApiError {
+    ApiError::RouteNotFound
+}
+
+/// Response middleware that replaces the router's empty 405 body with an
+/// `ErrorResponse` listing the methods from the `Allow` header.
+///
+/// The router only knows the allowed methods once it has matched a path, so
+/// this runs on the response rather than as a method fallback handler.
+pub async fn method_not_allowed_as_json(response: Response) -> Response {
+    if response.status() != StatusCode::METHOD_NOT_ALLOWED {
+        return response;
+    }
+
+    let allow = response.headers().get(header::ALLOW).cloned();
+    let methods = allow
+        .as_ref()
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|method| !method.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut json = ApiError::MethodNotAllowed(methods).into_response();
+    if let Some(allow) = allow {
+        json.headers_mut().insert(header::ALLOW, allow);
+    }
+    json
+}
+
+/// Response middleware that replaces the plain-text responses of the body
+/// size limit and timeout layers with an `ErrorResponse`
+pub async fn limit_errors_as_json(response: Response) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with(mime::APPLICATION_JSON.as_ref()));
+    if is_json {
+        return response;
+    }
+
+    match response.status() {
+        StatusCode::PAYLOAD_TOO_LARGE => ApiError::PayloadTooLarge.into_response(),
+        StatusCode::GATEWAY_TIMEOUT => ApiError::Timeout.into_response(),
+        _ => response,
+    }
+}
+
+/// Responder for `CatchPanicLayer`: a 500 `ErrorResponse`, with the panic
+/// message logged in the request's span
+pub fn panic_as_json(payload: Box<dyn Any + Send + 'static>) -> Response {
+    let message = if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "non-string panic payload".to_string()
+    };
+    ApiError::Panic(message).into_response()
+}
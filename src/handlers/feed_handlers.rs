@@ -0,0 +1,149 @@
+use std::{collections::HashMap, str::FromStr};
+
+use axum::{
+    extract::{OriginalUri, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use url::Url;
+
+use super::{
+    errors::ApiError,
+    extract::{Path, Query},
+};
+use crate::{
+    db::{Database, DatabaseError},
+    feeds::{Feed, FeedConfig, FEED_LENGTH},
+    models::post::{PostCategory, Visibility},
+};
+
+/// Query parameters accepted by every feed
+#[derive(Debug, Deserialize)]
+pub struct FeedQuery {
+    /// Only posts in this language, e.g. `de`
+    pub lang: Option<String>,
+}
+
+/// Which published posts a feed carries
+#[derive(Clone, Copy, Debug)]
+enum FeedFilter<'a> {
+    All,
+    /// Posts with the tag of this name
+    Tag(&'a str),
+    /// Posts in the category of this name, e.g. `art`
+    Category(&'a str),
+}
+
+impl<'a> FeedFilter<'a> {
+    /// The filter of a feed route, told apart by its path parameter:
+    /// `/tags/{name}/…`, `/categories/{category}/…` or none at all
+    fn from_params(params: &'a HashMap<String, String>) -> Self {
+        if let Some(name) = params.get("name") {
+            FeedFilter::Tag(name)
+        } else if let Some(category) = params.get("category") {
+            FeedFilter::Category(category)
+        } else {
+            FeedFilter::All
+        }
+    }
+}
+
+/// Formats a feed can be served in
+#[derive(Clone, Copy, Debug)]
+enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    /// The format of a feed route, from its file name
+    fn from_path(path: &str) -> Option<Self> {
+        match path.rsplit('/').next()? {
+            "feed.xml" => Some(FeedFormat::Rss),
+            "atom.xml" => Some(FeedFormat::Atom),
+            "feed.json" => Some(FeedFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// The newest published posts, as RSS 2.0 (`feed.xml`), Atom (`atom.xml`)
+/// or JSON Feed (`feed.json`); of every post, a tag's by tag name, or a
+/// category's
+///
+/// Every route and format goes through the same `Feed`, so the feeds
+/// never disagree.
+pub async fn feed(
+    State(db): State<Database>,
+    State(config): State<FeedConfig>,
+    OriginalUri(uri): OriginalUri,
+    Path(params): Path<HashMap<String, String>>,
+    Query(query): Query<FeedQuery>,
+) -> Result<Response, ApiError> {
+    let format = FeedFormat::from_path(uri.path())
+        .ok_or_else(|| DatabaseError::not_found("Feed", uri.path()))?;
+    let filter = FeedFilter::from_params(&params);
+    let language = query.lang.as_deref();
+    let (topic, posts) = match filter {
+        FeedFilter::All => {
+            let posts = db
+                .posts()
+                .list(None, language, Visibility::Published, FEED_LENGTH, 0)
+                .await?;
+            (None, posts)
+        }
+        FeedFilter::Tag(name) => {
+            let tag = db.tags().find_by_name(name).await?;
+            let posts = db
+                .posts()
+                .list_by_tag(tag.id, language, true, FEED_LENGTH, 0)
+                .await?;
+            (Some(name), posts)
+        }
+        FeedFilter::Category(name) => {
+            // Unknown categories are missing feeds, not malformed requests
+            let category = PostCategory::from_str(name)
+                .map_err(|_| DatabaseError::not_found("Category", name))?;
+            let posts = db
+                .posts()
+                .list(
+                    Some(category.clone()),
+                    language,
+                    Visibility::Published,
+                    FEED_LENGTH,
+                    0,
+                )
+                .await?;
+            (Some(category.label()), posts)
+        }
+    };
+    let mut feed = Feed::new(&config, uri.path(), topic, &posts);
+    if let Some(language) = language {
+        // A language's feed is a feed of its own, so it links to itself as such
+        if let Ok(mut url) = Url::parse(&feed.feed_url) {
+            url.query_pairs_mut().append_pair("lang", language);
+            feed.feed_url = url.into();
+        }
+        feed.language = Some(language.to_string());
+    }
+
+    Ok(match format {
+        FeedFormat::Rss => (
+            [(header::CONTENT_TYPE, "application/rss+xml; charset=utf-8")],
+            feed.to_rss(),
+        )
+            .into_response(),
+        FeedFormat::Atom => (
+            [(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")],
+            feed.to_atom(),
+        )
+            .into_response(),
+        FeedFormat::Json => (
+            [(header::CONTENT_TYPE, "application/feed+json")],
+            feed.to_json().to_string(),
+        )
+            .into_response(),
+    })
+}
@@ -0,0 +1,35 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+
+use crate::db::Database;
+
+/// Readiness probe
+///
+/// 200 OK once the database answers and its schema is current, otherwise
+/// 503 Service Unavailable, e.g. while migrations are pending.
+pub async fn readyz(State(db): State<Database>) -> Response {
+    match db.migration_status().await {
+        Ok(status) if status.is_current() => Json(json!({ "status": "ready" })).into_response(),
+        Ok(status) => {
+            let pending: Vec<i64> = status.pending.iter().map(|m| m.version).collect();
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({ "status": "not_ready", "pending_migrations": pending })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            tracing::warn!("Readiness check failed: {}", e);
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({ "status": "not_ready" })),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use axum::extract::State;
+use serde::Deserialize;
+
+use super::{errors::ApiError, extract::Query, responses::Page};
+use crate::{db::Database, models::link_check::BrokenLink};
+
+/// Query parameters for listing broken links
+#[derive(Debug, Deserialize)]
+pub struct BrokenLinksQuery {
+    #[serde(default = "default_broken_links_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_broken_links_limit() -> i64 {
+    50
+}
+
+/// List posts' external and image URLs whose latest check failed, by post
+///
+/// Paginated with limit/offset, with the number of broken links in the
+/// `X-Total-Count` header. Empty until the link checker has run.
+pub async fn list_broken_links(
+    State(db): State<Database>,
+    Query(query): Query<BrokenLinksQuery>,
+) -> Result<Page<BrokenLink>, ApiError> {
+    let (links, total) = db
+        .link_checks()
+        .list_broken(query.limit, query.offset)
+        .await?;
+    Ok(Page::new(links, total))
+}
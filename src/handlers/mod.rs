@@ -1,2 +1,23 @@
+pub mod admin_handlers;
+pub mod api_key_handlers;
+pub mod audit_handlers;
+pub mod auth_handlers;
+pub mod click_handlers;
+pub mod client_handlers;
+pub mod comment_handlers;
+pub mod contact_handlers;
+pub mod errors;
+pub mod extract;
+pub mod fallback;
+pub mod feed_handlers;
+pub mod health_handlers;
+pub mod link_check_handlers;
+pub mod negotiation;
 pub mod post_handlers;
+pub mod responses;
+pub mod robots;
+pub mod static_files;
+pub mod stats_handlers;
 pub mod tag_handlers;
+pub mod upload_handlers;
+pub mod user_handlers;
@@ -0,0 +1,110 @@
+//! Choosing a response format from the `Accept` header
+
+use axum::http::{header, HeaderMap};
+
+/// The media type in `offered` the client prefers, by the quality values
+/// in its `Accept` header. The most specific matching range decides a
+/// type's quality, ties go to the type offered first, and a client that
+/// sends no `Accept` header gets the first type. Returns `None` when the
+/// client accepts none of them.
+pub fn preferred<'a>(headers: &HeaderMap, offered: &[&'a str]) -> Option<&'a str> {
+    let ranges: Vec<(String, f32)> = headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(parse_range)
+        .collect();
+    if ranges.is_empty() {
+        return offered.first().copied();
+    }
+
+    let mut best: Option<(&str, f32)> = None;
+    for &media_type in offered {
+        let quality = ranges
+            .iter()
+            .filter_map(|(range, quality)| {
+                specificity(range, media_type).map(|specificity| (specificity, *quality))
+            })
+            .max_by_key(|(specificity, _)| *specificity)
+            .map_or(0.0, |(_, quality)| quality);
+        if quality > 0.0 && best.is_none_or(|(_, best)| quality > best) {
+            best = Some((media_type, quality));
+        }
+    }
+    best.map(|(media_type, _)| media_type)
+}
+
+/// A media range and its quality, e.g. `text/*;q=0.5`, lowercased and
+/// without other parameters
+fn parse_range(range: &str) -> Option<(String, f32)> {
+    let mut parts = range.split(';').map(str::trim);
+    let media_range = parts.next().filter(|range| !range.is_empty())?;
+    let mut quality = 1.0;
+    for parameter in parts {
+        if let Some((name, value)) = parameter.split_once('=') {
+            if name.trim().eq_ignore_ascii_case("q") {
+                quality = value
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|q| (0.0..=1.0).contains(q))?;
+            }
+        }
+    }
+    Some((media_range.to_ascii_lowercase(), quality))
+}
+
+/// How closely `range` matches `media_type`: 2 for the exact type, 1 for
+/// `type/*` and 0 for `*/*`, or `None` when it doesn't match
+fn specificity(range: &str, media_type: &str) -> Option<u8> {
+    if range == media_type {
+        return Some(2);
+    }
+    match range.split_once('/') {
+        Some(("*", "*")) => Some(0),
+        Some((main, "*")) if media_type.split('/').next() == Some(main) => Some(1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    const OFFERED: &[&str] = &["application/json", "text/markdown"];
+
+    fn accept(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn test_preferred() {
+        assert_eq!(
+            preferred(&HeaderMap::new(), OFFERED),
+            Some("application/json")
+        );
+        for (value, expected) in [
+            ("*/*", Some("application/json")),
+            ("text/markdown", Some("text/markdown")),
+            ("Text/Markdown; charset=utf-8", Some("text/markdown")),
+            ("text/*", Some("text/markdown")),
+            ("application/json, text/markdown", Some("application/json")),
+            ("text/markdown, */*;q=0.8", Some("text/markdown")),
+            (
+                "application/json;q=0.5, text/markdown",
+                Some("text/markdown"),
+            ),
+            ("text/markdown;q=0, */*", Some("application/json")),
+            ("*/*;q=0.1, application/json;q=0", Some("text/markdown")),
+            ("text/html", None),
+            ("text/markdown;q=2", Some("application/json")),
+        ] {
+            assert_eq!(preferred(&accept(value), OFFERED), expected, "{}", value);
+        }
+    }
+}
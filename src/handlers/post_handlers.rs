@@ -1,21 +1,66 @@
 use std::str::FromStr;
 
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    Json,
+    body::{Body, Bytes},
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    BoxError, Extension,
+};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use time::{
+    format_description::{well_known::Rfc3339, FormatItem},
+    macros::format_description,
+    OffsetDateTime, PrimitiveDateTime, UtcOffset,
 };
-use serde::Deserialize;
 
+use super::{
+    errors::ApiError,
+    extract::{Json, Path, Query},
+    negotiation::preferred,
+    responses::{Created, Page},
+};
 use crate::{
+    auth::{AuthError, AuthUser, Principal},
     db::{Database, DatabaseError},
-    models::post::{CreatePost, PatchPost, Post, PostCategory, UpdatePost},
+    models::{
+        post::{
+            month_bounds, Attributed, CategorySummary, CreatePost, DraftContent, PatchPost, Post,
+            PostCategory, PostRef, PostWithTags, PostWithTranslations, SlugLookup, UpdatePost,
+            Visibility,
+        },
+        sha256_hex,
+        user::UserRole,
+    },
+    render::RENDERER_VERSION,
 };
 
-/// Query parameters for listing posts with pagination and filtering options
+/// Query parameters for the public post listing
+///
+/// There is deliberately no way to ask for drafts: parameters such as
+/// `visibility` or `published_only` are ignored like any other unknown one.
+#[derive(Debug, Deserialize)]
+pub struct PublicListPostsQuery {
+    pub category: Option<String>,
+    /// Only posts in this language, e.g. `de`
+    pub lang: Option<String>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+/// Query parameters for listing posts in the admin UI, with the full set
+/// of filters
 #[derive(Debug, Deserialize)]
 pub struct ListPostsQuery {
     pub category: Option<String>,
+    /// Only posts in this language, e.g. `de`
+    pub lang: Option<String>,
+    /// `published`, `draft` or `all`; all posts when unset
+    pub visibility: Option<Visibility>,
+    /// Older spelling of `visibility=published`
     #[serde(default)]
     pub published_only: bool,
     #[serde(default = "default_limit")]
@@ -29,133 +74,645 @@ fn default_limit() -> i64 {
     20
 }
 
-/// Custom error type for our API endpoints that maps both database
-/// and validation errors to appropriate HTTP responses
-#[derive(thiserror::Error, Debug)]
-pub enum ApiError {
-    #[error("Database error: {0}")]
-    Database(#[from] DatabaseError),
-
-    #[error("Invalid input: {0}")]
-    InvalidInput(String),
-}
-
-/// Convert our ApiError into appropriate HTTP responses
-impl axum::response::IntoResponse for ApiError {
-    fn into_response(self) -> axum::response::Response {
-        let (status, message) = match self {
-            ApiError::Database(DatabaseError::NotFound(msg)) => (StatusCode::NOT_FOUND, msg),
-            ApiError::Database(DatabaseError::DuplicateEntry(msg)) => (StatusCode::CONFLICT, msg),
-            ApiError::Database(DatabaseError::Validation(msg)) => (StatusCode::BAD_REQUEST, msg),
-            ApiError::InvalidInput(msg) => (StatusCode::BAD_REQUEST, msg),
-            _ => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Internal server error".to_string(),
-            ),
-        };
-
-        let body = Json(ErrorResponse { message });
-        (status, body).into_response()
-    }
+/// Query parameters for fetching posts modified since a given instant
+#[derive(Debug, Deserialize)]
+pub struct PostChangesQuery {
+    /// RFC 3339 timestamp, e.g. `2024-05-01T00:00:00Z`
+    pub since: String,
+    #[serde(default)]
+    pub include_drafts: bool,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
 }
 
-/// Consistent error response structure for all API errors
-#[derive(serde::Serialize)]
-struct ErrorResponse {
-    message: String,
+/// Response body for the post changes endpoint
+///
+/// `server_time` is captured before the query runs, so clients can send it
+/// back as the next `since` without relying on their own clock.
+#[derive(Debug, Serialize)]
+pub struct PostChanges {
+    #[serde(with = "time::serde::rfc3339")]
+    pub server_time: OffsetDateTime,
+    pub posts: Vec<Post>,
 }
 
 /// Create a new post
 ///
-/// This handler validates the input and creates a new post in the database.
-/// Returns the created post with its ID and timestamps on success.
+/// This handler validates the input and creates a new post in the database,
+/// along with any tags named in `tags`. Returns 201 Created with the post's
+/// ID, timestamps and attached tags on success, and a Location header
+/// pointing at the new post. The caller becomes the post's author.
+///
+/// Responses to writes, unlike public reads, include `created_by` and
+/// `updated_by`.
 pub async fn create_post(
     State(db): State<Database>,
+    user: AuthUser,
     Json(create_post): Json<CreatePost>,
-) -> Result<Json<Post>, ApiError> {
-    let post = db.posts().create(create_post).await?;
-    Ok(Json(post))
+) -> Result<Created<Attributed<PostWithTags>>, ApiError> {
+    let created = db
+        .acting_as(user.actor)
+        .posts()
+        .create_with_tags(create_post, user.id)
+        .await?;
+    Ok(Created::new(
+        format!("/posts/by-id/{}", created.post.id),
+        created.attributed(),
+    ))
+}
+
+/// HTTP-date format used by Last-Modified and If-Modified-Since (RFC 7231)
+const HTTP_DATE_FORMAT: &[FormatItem<'static>] = format_description!(
+    "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT"
+);
+
+/// How a single post is represented in a response
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PostFormat {
+    /// The whole post as JSON
+    Json,
+    /// The raw Markdown content, with the title and timestamps in headers
+    Markdown,
+    /// The content rendered to an HTML fragment, with the title in a header
+    Html,
+}
+
+const MARKDOWN: &str = "text/markdown";
+const HTML: &str = "text/html";
+
+/// Content-Security-Policy sent with posts rendered to HTML
+const HTML_CONTENT_SECURITY_POLICY: &str = "default-src 'none'; sandbox";
+
+/// Header carrying the post's title in Markdown and HTML responses
+pub const POST_TITLE_HEADER: &str = "x-post-title";
+
+impl PostFormat {
+    /// The format the client asks for with `Accept`; JSON unless it
+    /// prefers Markdown or HTML
+    fn negotiate(headers: &HeaderMap) -> Self {
+        match preferred(headers, &[mime::APPLICATION_JSON.as_ref(), MARKDOWN, HTML]) {
+            Some(MARKDOWN) => PostFormat::Markdown,
+            Some(HTML) => PostFormat::Html,
+            _ => PostFormat::Json,
+        }
+    }
+}
+
+/// Builds a weak ETag for a post from a SHA-256 digest of its JSON
+/// representation, which carries everything the other formats are built
+/// from. Keying on content rather than `updated_at`, which only has second
+/// precision, means two edits within the same second still get distinct tags.
+/// `None` if the post doesn't serialize, in which case it's sent without one.
+fn post_etag(post: &PostWithTranslations, format: PostFormat) -> Option<String> {
+    let mut key = serde_json::to_vec(post).ok()?;
+    key.extend_from_slice(format!("\n{:?}", format).as_bytes());
+    // A new renderer may render unchanged content differently
+    if format == PostFormat::Html {
+        key.extend_from_slice(format!("\n{}", RENDERER_VERSION).as_bytes());
+    }
+    Some(format!("W/\"{}\"", &sha256_hex(&key)[..16]))
+}
+
+/// Formats a timestamp as an HTTP-date, always in GMT
+fn http_date(timestamp: OffsetDateTime) -> Option<String> {
+    timestamp
+        .to_offset(UtcOffset::UTC)
+        .format(HTTP_DATE_FORMAT)
+        .ok()
+}
+
+/// Checks the request's conditional headers against the current post state.
+///
+/// If-None-Match takes precedence over If-Modified-Since, as required by
+/// RFC 7232. ETags are compared weakly, so `W/` prefixes are ignored.
+fn is_not_modified(headers: &HeaderMap, etag: Option<&str>, updated_at: OffsetDateTime) -> bool {
+    if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH) {
+        let Ok(if_none_match) = if_none_match.to_str() else {
+            return false;
+        };
+        let current = etag.map(|etag| etag.trim_start_matches("W/"));
+        return if_none_match.split(',').map(str::trim).any(|candidate| {
+            candidate == "*" || Some(candidate.trim_start_matches("W/")) == current
+        });
+    }
+
+    headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| PrimitiveDateTime::parse(value, HTTP_DATE_FORMAT).ok())
+        .is_some_and(|since| updated_at.unix_timestamp() <= since.assume_utc().unix_timestamp())
+}
+
+/// Builds a post response carrying ETag and Last-Modified validators,
+/// answering 304 Not Modified with an empty body when the client's copy is current.
+/// HTML comes from the render cache, and isn't looked up for a 304.
+async fn conditional_post_response(
+    db: &Database,
+    headers: &HeaderMap,
+    post: PostWithTranslations,
+    format: PostFormat,
+) -> Result<Response, ApiError> {
+    let etag = post_etag(&post, format);
+    let not_modified = is_not_modified(headers, etag.as_deref(), post.post.updated_at);
+
+    let mut validators = HeaderMap::new();
+    if let Some(value) = etag.and_then(|etag| HeaderValue::from_str(&etag).ok()) {
+        validators.insert(header::ETAG, value);
+    }
+    if let Some(value) =
+        http_date(post.post.updated_at).and_then(|d| HeaderValue::from_str(&d).ok())
+    {
+        validators.insert(header::LAST_MODIFIED, value);
+    }
+
+    if not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, validators).into_response());
+    }
+    let (content_type, body) = match format {
+        PostFormat::Json => return Ok((validators, Json(post)).into_response()),
+        PostFormat::Markdown => ("text/markdown; charset=utf-8", post.post.content),
+        PostFormat::Html => (
+            "text/html; charset=utf-8",
+            db.render_cache().html(&post.post).await?,
+        ),
+    };
+    validators.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    if format == PostFormat::Html {
+        // Rendered posts are documents on the API's origin: forbid scripts
+        // and any other subresource in case something slips past escaping
+        validators.insert(
+            header::CONTENT_SECURITY_POLICY,
+            HeaderValue::from_static(HTML_CONTENT_SECURITY_POLICY),
+        );
+    }
+    // Titles with characters headers can't carry go without
+    if let Ok(title) = HeaderValue::from_str(&post.post.title) {
+        validators.insert(POST_TITLE_HEADER, title);
+    }
+    Ok((validators, body).into_response())
+}
+
+/// The post with its published translations, as single-post responses
+/// show it
+async fn with_translations(db: &Database, post: Post) -> Result<PostWithTranslations, ApiError> {
+    let translations = db.posts().translations(&post).await?;
+    Ok(PostWithTranslations { post, translations })
+}
+
+/// Retrieve a post by its database ID or its slug
+///
+/// An all-digit segment is taken as the ID, anything else as the slug (see
+/// `PostRef::parse`); each then behaves like its dedicated route.
+pub async fn get_post(
+    State(db): State<Database>,
+    Path(id_or_slug): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    match PostRef::parse(&id_or_slug) {
+        PostRef::Id(id) => post_by_id(&db, id, &headers).await,
+        PostRef::Slug(slug) => post_by_slug(&db, slug, &headers).await,
+    }
 }
 
 /// Retrieve a post by its database ID
+///
+/// Supports conditional requests via If-None-Match and If-Modified-Since.
+/// The post's published translations are listed in `translations`.
 pub async fn get_post_by_id(
     State(db): State<Database>,
     Path(id): Path<i64>,
-) -> Result<Json<Post>, ApiError> {
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    post_by_id(&db, id, &headers).await
+}
+
+async fn post_by_id(db: &Database, id: i64, headers: &HeaderMap) -> Result<Response, ApiError> {
     let post = db.posts().find_by_id(id).await?;
-    Ok(Json(post))
+    let post = with_translations(db, post).await?;
+    conditional_post_response(db, headers, post, PostFormat::Json).await
 }
 
+/// Body returned when a requested slug has been renamed
+#[derive(Debug, Serialize)]
+pub struct SlugRedirect {
+    pub redirect_to: String,
+}
+
+/// Most slugs suggested when a post's slug isn't found
+const MAX_SLUG_SUGGESTIONS: usize = 3;
+
 /// Retrieve a post by its URL-friendly slug
+///
+/// Supports conditional requests via If-None-Match and If-Modified-Since.
+/// Clients preferring `text/markdown` in `Accept` get the raw content, and
+/// those preferring `text/html` the rendered content, with the title in
+/// `X-Post-Title`.
+/// When the slug belonged to a post that has since been renamed, responds with
+/// 301 Moved Permanently, a Location header, and `{ "redirect_to": "new-slug" }`.
+/// When no post has the slug, the 404 lists close published slugs in
+/// `did_you_mean`.
 pub async fn get_post_by_slug(
     State(db): State<Database>,
     Path(slug): Path<String>,
-) -> Result<Json<Post>, ApiError> {
-    let post = db.posts().find_by_slug(&slug).await?;
-    Ok(Json(post))
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    post_by_slug(&db, &slug, &headers).await
+}
+
+async fn post_by_slug(
+    db: &Database,
+    slug: &str,
+    headers: &HeaderMap,
+) -> Result<Response, ApiError> {
+    let lookup = match db.posts().resolve_slug(slug).await {
+        Err(DatabaseError::NotFound(message)) => {
+            let did_you_mean = db.posts().similar_slugs(slug, MAX_SLUG_SUGGESTIONS).await?;
+            return Err(ApiError::SlugNotFound {
+                message,
+                did_you_mean,
+            });
+        }
+        lookup => lookup?,
+    };
+    match lookup {
+        SlugLookup::Found(post) => {
+            let format = PostFormat::negotiate(headers);
+            let post = with_translations(db, *post).await?;
+            let mut response = conditional_post_response(db, headers, post, format).await?;
+            response
+                .headers_mut()
+                .append(header::VARY, HeaderValue::from_static("accept"));
+            Ok(response)
+        }
+        SlugLookup::Moved(redirect_to) => {
+            let location = HeaderValue::from_str(&format!("/posts/by-slug/{}", redirect_to))
+                .map_err(|e| ApiError::InvalidInput(e.to_string()))?;
+            Ok((
+                StatusCode::MOVED_PERMANENTLY,
+                [(header::LOCATION, location)],
+                Json(SlugRedirect { redirect_to }),
+            )
+                .into_response())
+        }
+    }
 }
 
-/// List posts with optional filtering and pagination
+/// List published posts with optional filtering and pagination
 ///
-/// Supports filtering by:
+/// This is the listing for the public site, so it only ever returns
+/// published posts, whatever the query says. Supports filtering by:
 /// - Category (blog, art, reading)
-/// - Publication status (draft/published)
+/// - Language (`lang`, e.g. de)
 ///
 /// And pagination using:
 /// - limit (max number of posts to return)
 /// - offset (number of posts to skip)
+///
+/// The number of matching posts is sent in the `X-Total-Count` header.
 pub async fn list_posts(
+    State(db): State<Database>,
+    Query(query): Query<PublicListPostsQuery>,
+) -> Result<Page<Post>, ApiError> {
+    let (posts, total) = db
+        .posts()
+        .list_with_total(
+            parse_category(query.category)?,
+            query.lang.as_deref(),
+            Visibility::Published,
+            query.limit,
+            query.offset,
+        )
+        .await?;
+    Ok(Page::new(posts, total))
+}
+
+/// List posts for the admin UI, drafts included
+///
+/// Takes the same filters and pagination as `list_posts`, plus:
+/// - Visibility (`published`, `draft` or `all`, the default);
+///   `published_only=true` is an alias for `visibility=published`
+///
+/// The number of matching posts is sent in the `X-Total-Count` header.
+pub async fn list_posts_for_admin(
     State(db): State<Database>,
     Query(query): Query<ListPostsQuery>,
-) -> Result<Json<Vec<Post>>, ApiError> {
-    let category = match query.category {
-        Some(cat_str) => Some(
-            PostCategory::from_str(&cat_str)
-                .map_err(|e| ApiError::InvalidInput(format!("Invalid category: {}", e)))?,
-        ),
-        None => None,
+) -> Result<Page<Post>, ApiError> {
+    let visibility = match (query.visibility, query.published_only) {
+        (None, published_only) => Visibility::from_published_only(published_only),
+        (Some(visibility), false) => visibility,
+        (Some(Visibility::Published), true) => Visibility::Published,
+        (Some(_), true) => {
+            return Err(ApiError::InvalidInput(
+                "published_only=true conflicts with visibility".to_string(),
+            ))
+        }
     };
 
+    let (posts, total) = db
+        .posts()
+        .list_with_total(
+            parse_category(query.category)?,
+            query.lang.as_deref(),
+            visibility,
+            query.limit,
+            query.offset,
+        )
+        .await?;
+    Ok(Page::new(posts, total))
+}
+
+/// Parses the optional `category` query parameter of the listings
+fn parse_category(category: Option<String>) -> Result<Option<PostCategory>, ApiError> {
+    category
+        .map(|category| {
+            PostCategory::from_str(&category)
+                .map_err(|e| ApiError::InvalidInput(format!("Invalid category: {}", e)))
+        })
+        .transpose()
+}
+
+/// List posts modified since a given instant
+///
+/// Intended for incremental rebuilds: returns posts whose `updated_at` is at
+/// or after `since`, oldest change first, paginated with limit/offset.
+/// Drafts are excluded unless `include_drafts` is set, which requires
+/// authentication.
+pub async fn list_post_changes(
+    State(db): State<Database>,
+    principal: Option<Extension<Principal>>,
+    Query(query): Query<PostChangesQuery>,
+) -> Result<Json<PostChanges>, ApiError> {
+    if query.include_drafts && principal.is_none() {
+        return Err(AuthError::MissingToken.into());
+    }
+    let since = OffsetDateTime::parse(&query.since, &Rfc3339)
+        .map_err(|e| ApiError::InvalidInput(format!("Invalid since timestamp: {}", e)))?;
+
+    let server_time = OffsetDateTime::now_utc();
     let posts = db
         .posts()
-        .list(category, query.published_only, query.limit, query.offset)
+        .list_modified_since(since, !query.include_drafts, query.limit, query.offset)
+        .await?;
+
+    Ok(Json(PostChanges { server_time, posts }))
+}
+
+/// Query parameters for exporting posts
+#[derive(Debug, Deserialize)]
+pub struct ExportPostsQuery {
+    #[serde(default)]
+    pub include_drafts: bool,
+}
+
+/// Lines an export reads ahead of a client that isn't keeping up
+const EXPORT_BUFFER_LINES: usize = 64;
+
+/// Export posts as newline-delimited JSON
+///
+/// Streams every post, oldest first, one JSON object per line. Rows are
+/// read only as fast as the client takes them, so memory use stays flat
+/// however many posts there are, and reading stops when the client
+/// disconnects. Drafts are excluded unless `include_drafts` is set, which
+/// requires authentication. An error reading or serializing a post aborts
+/// the response, so a truncated export can't be mistaken for a complete
+/// one.
+pub async fn export_posts(
+    State(db): State<Database>,
+    principal: Option<Extension<Principal>>,
+    Query(query): Query<ExportPostsQuery>,
+) -> Result<Response, ApiError> {
+    if query.include_drafts && principal.is_none() {
+        return Err(AuthError::MissingToken.into());
+    }
+    let (lines, mut receiver) = tokio::sync::mpsc::channel(EXPORT_BUFFER_LINES);
+    tokio::spawn(async move {
+        let mut posts = db.posts().stream(!query.include_drafts);
+        while let Some(post) = posts.next().await {
+            let line = post.map_err(BoxError::from).and_then(|post| {
+                let mut line = serde_json::to_vec(&post)?;
+                line.push(b'\n');
+                Ok(Bytes::from(line))
+            });
+            if let Err(e) = &line {
+                tracing::error!("Post export failed: {}", e);
+            }
+            let failed = line.is_err();
+            // A failed send means the client is gone
+            if lines.send(line).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    let body = futures_util::stream::poll_fn(move |cx| receiver.poll_recv(cx));
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(body),
+    )
+        .into_response())
+}
+
+/// Query parameters for listing posts without tags
+#[derive(Debug, Deserialize)]
+pub struct UntaggedPostsQuery {
+    #[serde(default)]
+    pub include_drafts: bool,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+/// List posts that have no tags
+///
+/// Newest first and paginated like `list_posts`, with the number of untagged
+/// posts in the `X-Total-Count` header. Drafts are excluded unless
+/// `include_drafts` is set, which requires authentication.
+pub async fn list_untagged_posts(
+    State(db): State<Database>,
+    principal: Option<Extension<Principal>>,
+    Query(query): Query<UntaggedPostsQuery>,
+) -> Result<Page<Post>, ApiError> {
+    if query.include_drafts && principal.is_none() {
+        return Err(AuthError::MissingToken.into());
+    }
+    let (posts, total) = db
+        .posts()
+        .list_untagged(!query.include_drafts, query.limit, query.offset)
+        .await?;
+    Ok(Page::new(posts, total))
+}
+
+/// List the post categories with their labels and published post counts
+///
+/// Every category is listed, in a fixed order, so clients don't need to
+/// know them in advance.
+pub async fn list_categories(
+    State(db): State<Database>,
+) -> Result<Json<Vec<CategorySummary>>, ApiError> {
+    Ok(Json(db.posts().count_by_category().await?))
+}
+
+/// Query parameters for listing the posts of a month
+#[derive(Debug, Deserialize)]
+pub struct MonthPostsQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+/// List the published posts created in a month, e.g. `/posts/2024/03`
+///
+/// Oldest first, for archive pages. A month without posts gives an empty
+/// array; a month outside 1-12 is rejected with 400.
+pub async fn list_posts_by_month(
+    State(db): State<Database>,
+    Path((year, month)): Path<(i32, u8)>,
+    Query(query): Query<MonthPostsQuery>,
+) -> Result<Json<Vec<Post>>, ApiError> {
+    if month_bounds(year, month).is_none() {
+        return Err(ApiError::InvalidInput(format!(
+            "Invalid month: {}-{:02}",
+            year, month
+        )));
+    }
+    let posts = db
+        .posts()
+        .list_by_month(year, month, query.limit, query.offset)
         .await?;
     Ok(Json(posts))
 }
 
+/// Checks that `user` may change the post with the given ID.
+///
+/// Admins may change any post; other users only the posts they wrote.
+/// Returns 404 if the post doesn't exist, so a missing post is never
+/// reported as forbidden.
+pub(super) async fn authorize_post_change(
+    db: &Database,
+    user: &AuthUser,
+    post_id: i64,
+) -> Result<(), ApiError> {
+    if user.is_admin() {
+        return Ok(());
+    }
+    let post = db.posts().find_by_id(post_id).await?;
+    Ok(user.require_author(post.author_id)?)
+}
+
+/// Header set on updates that left the post as it was
+const NOT_MODIFIED_HEADER: &str = "x-not-modified";
+
+/// Responds with the post after an update or patch, flagging with
+/// `X-Not-Modified: true` an update that changed nothing. Like `create_post`
+/// it includes `created_by` and `updated_by`.
+fn updated_post_response(post: Post, changed: bool) -> Response {
+    let post = Json(post.attributed());
+    if changed {
+        post.into_response()
+    } else {
+        ([(NOT_MODIFIED_HEADER, "true")], post).into_response()
+    }
+}
+
 /// Update all fields of an existing post
 ///
 /// This is a full update that requires all fields to be provided.
 /// For partial updates, use the patch_post handler instead.
+/// Only the post's author or an admin may update it.
 pub async fn update_post(
     State(db): State<Database>,
+    user: AuthUser,
     Json(update_post): Json<UpdatePost>,
-) -> Result<Json<Post>, ApiError> {
-    let post = db.posts().update(update_post).await?;
-    Ok(Json(post))
+) -> Result<Response, ApiError> {
+    authorize_post_change(&db, &user, update_post.id).await?;
+    let (post, changed) = db.acting_as(user.actor).posts().update(update_post).await?;
+    Ok(updated_post_response(post, changed))
 }
 
+/// Content type for RFC 7396 JSON merge patches
+const MERGE_PATCH_JSON: &str = "application/merge-patch+json";
+
 /// Partially update a post
 ///
 /// Allows updating only specific fields of a post while leaving others unchanged.
 /// This is useful for small updates like toggling publication status or updating
 /// the title without having to provide all other fields.
+///
+/// Accepts two content types:
+/// - `application/json`: explicit nulls are treated as "unchanged"
+/// - `application/merge-patch+json`: explicit nulls clear nullable fields (RFC 7396)
+///
+/// Only the post's author or an admin may patch it.
 pub async fn patch_post(
     State(db): State<Database>,
-    Json(patch_post): Json<PatchPost>,
-) -> Result<Json<Post>, ApiError> {
-    let post = db.posts().patch(patch_post).await?;
-    Ok(Json(post))
+    user: AuthUser,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ApiError> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<mime::Mime>().ok());
+
+    let patch_post = match content_type.as_ref().map(|m| m.essence_str()) {
+        Some("application/json") => serde_json::from_slice::<PatchPost>(&body)
+            .map_err(ApiError::from_json_error)?
+            .ignoring_nulls(),
+        Some(MERGE_PATCH_JSON) => {
+            let document = serde_json::from_slice(&body).map_err(ApiError::from_json_error)?;
+            PatchPost::from_merge_patch(document)
+                .map_err(|e| ApiError::InvalidBody(e.to_string()))?
+        }
+        _ => {
+            return Err(ApiError::UnsupportedMediaType(format!(
+                "Expected application/json or {}",
+                MERGE_PATCH_JSON
+            )))
+        }
+    };
+
+    authorize_post_change(&db, &user, patch_post.id).await?;
+    let (post, changed) = db.acting_as(user.actor).posts().patch(patch_post).await?;
+    Ok(updated_post_response(post, changed))
+}
+
+/// Autosave the text of a draft
+///
+/// Writes whichever of `title`, `content` and `description` are given,
+/// accepting empty or half written values so an editor can save while the
+/// author types. Only the content size limit applies, and slug, category
+/// and publication status are never touched. Published posts return
+/// `409 Conflict`. Only the post's author or an admin may save it.
+pub async fn save_draft_content(
+    State(db): State<Database>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+    Json(draft): Json<DraftContent>,
+) -> Result<Json<Attributed<Post>>, ApiError> {
+    authorize_post_change(&db, &user, id).await?;
+    let post = db
+        .acting_as(user.actor)
+        .posts()
+        .save_draft(id, draft)
+        .await?;
+    Ok(Json(post.attributed()))
 }
 
 /// Delete a post by its ID
 ///
 /// If the post has any tags, the associations will be automatically removed
 /// thanks to the ON DELETE CASCADE constraint in our database schema.
+/// Only admins may delete posts.
 pub async fn delete_post(
     State(db): State<Database>,
+    user: AuthUser,
     Path(id): Path<i64>,
 ) -> Result<StatusCode, ApiError> {
-    db.posts().delete(id).await?;
+    user.require_role(UserRole::Admin)?;
+    db.acting_as(user.actor).posts().delete(id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
@@ -0,0 +1,96 @@
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+/// Response for endpoints that create a resource.
+///
+/// Produces `201 Created` with a `Location` header pointing at the new
+/// resource and the created entity serialized as the JSON body.
+#[derive(Debug)]
+pub struct Created<T> {
+    pub location: String,
+    pub body: T,
+}
+
+impl<T> Created<T> {
+    /// Creates a new response for the resource available at `location`
+    pub fn new(location: impl Into<String>, body: T) -> Self {
+        Self {
+            location: location.into(),
+            body,
+        }
+    }
+}
+
+impl<T: Serialize> IntoResponse for Created<T> {
+    fn into_response(self) -> Response {
+        match HeaderValue::from_str(&self.location) {
+            Ok(location) => (
+                StatusCode::CREATED,
+                [(header::LOCATION, location)],
+                Json(self.body),
+            )
+                .into_response(),
+            // Locations are built from IDs, so this only happens on a programming error
+            Err(_) => (StatusCode::CREATED, Json(self.body)).into_response(),
+        }
+    }
+}
+
+/// Header carrying the total number of items matched by a paginated listing
+pub const TOTAL_COUNT_HEADER: &str = "x-total-count";
+
+/// Response for endpoints that return one page of a larger listing.
+///
+/// Produces `200 OK` with the page serialized as a JSON array and the total
+/// number of matching items in the `X-Total-Count` header.
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, total: i64) -> Self {
+        Self { items, total }
+    }
+}
+
+impl<T: Serialize> IntoResponse for Page<T> {
+    fn into_response(self) -> Response {
+        (
+            [(TOTAL_COUNT_HEADER, HeaderValue::from(self.total))],
+            Json(self.items),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_created_response() {
+        let response = Created::new("/things/1", json!({ "id": 1 })).into_response();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()[header::LOCATION], "/things/1");
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            mime::APPLICATION_JSON.as_ref()
+        );
+    }
+
+    #[test]
+    fn test_page_response() {
+        let response = Page::new(vec![json!({ "id": 1 })], 7).into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[TOTAL_COUNT_HEADER], "7");
+    }
+}
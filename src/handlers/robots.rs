@@ -0,0 +1,54 @@
+//! `robots.txt`, generated from the configuration so the sitemap address
+//! matches the environment the server runs in
+
+use std::sync::Arc;
+
+use axum::{extract::State, http::header, response::IntoResponse};
+
+use crate::config::Config;
+
+/// The `robots.txt` body, rendered once at startup
+#[derive(Clone, Debug)]
+pub struct RobotsTxt(Arc<str>);
+
+impl RobotsTxt {
+    /// Allows every crawler and points them at the sitemap, or turns them
+    /// all away when `robots_disallow` is set, e.g. on staging instances
+    pub fn new(config: &Config) -> Self {
+        let body = if config.robots_disallow {
+            "User-agent: *\nDisallow: /\n".to_string()
+        } else {
+            format!(
+                "User-agent: *\nAllow: /\n\nSitemap: {}\n",
+                config.feed.link("/sitemap.xml")
+            )
+        };
+        Self(body.into())
+    }
+}
+
+/// Serve `robots.txt`
+pub async fn robots_txt(State(robots): State<RobotsTxt>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        robots.0.to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modes() {
+        let mut config = Config::default_for_tests();
+        config.feed.site_url = "https://blog.example.com/".parse().unwrap();
+        assert_eq!(
+            &*RobotsTxt::new(&config).0,
+            "User-agent: *\nAllow: /\n\nSitemap: https://blog.example.com/sitemap.xml\n"
+        );
+
+        config.robots_disallow = true;
+        assert_eq!(&*RobotsTxt::new(&config).0, "User-agent: *\nDisallow: /\n");
+    }
+}
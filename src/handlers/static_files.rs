@@ -0,0 +1,43 @@
+//! Files served from the configured static directory, such as post images
+
+use std::path::Path;
+
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    middleware::map_response,
+    response::{IntoResponse, Response},
+    Router,
+};
+use tower_http::services::ServeDir;
+
+use super::errors::ApiError;
+
+/// How long clients and caches may reuse a static file
+const CACHE_CONTROL: HeaderValue = HeaderValue::from_static("public, max-age=86400");
+
+/// Router serving the files under `dir`. `ServeDir` sets the content type
+/// from the extension and refuses paths that climb out of `dir`.
+pub fn static_files(dir: &Path) -> Router {
+    Router::new()
+        .fallback_service(ServeDir::new(dir).append_index_html_on_directories(false))
+        .layer(map_response(cache_or_not_found))
+}
+
+/// Marks files as cacheable, and turns the empty 404 for missing ones into
+/// an `ErrorResponse`
+async fn cache_or_not_found(mut response: Response) -> Response {
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND {
+        return ApiError::FileNotFound.into_response();
+    }
+    if status.is_success() || status == StatusCode::NOT_MODIFIED {
+        let headers = response.headers_mut();
+        headers.insert(header::CACHE_CONTROL, CACHE_CONTROL);
+        // Never let browsers guess a different type than the extension's
+        headers.insert(
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        );
+    }
+    response
+}
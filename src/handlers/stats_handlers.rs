@@ -0,0 +1,19 @@
+use axum::extract::State;
+
+use super::{
+    errors::ApiError,
+    extract::{Json, Path},
+};
+use crate::{db::Database, models::stats::YearStats};
+
+/// Summarize a year of posts for a year in review
+///
+/// Counts the posts published that year, in total and per category, the
+/// words written, the most used tags and the longest post. Years without
+/// posts, including future ones, give zeros rather than an error.
+pub async fn year_stats(
+    State(db): State<Database>,
+    Path(year): Path<i32>,
+) -> Result<Json<YearStats>, ApiError> {
+    Ok(Json(db.stats().year(year).await?))
+}
@@ -1,48 +1,188 @@
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    Json,
+    extract::State,
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    auth::{AuthError, AuthUser, Principal},
     db::Database,
-    models::tag::{Tag, TagWithPostCount},
+    models::{
+        post::Post,
+        tag::{
+            PatchTag, RelatedTag, Tag, TagListOptions, TagRequest, TagSlugLookup, TagSort,
+            TagWithPostCount,
+        },
+        user::UserRole,
+    },
 };
 
-// We'll reuse the ApiError from post_handlers.rs, so let's import it
-use super::post_handlers::ApiError;
-
-/// Request body for creating or updating a tag
-#[derive(Debug, Deserialize)]
-pub struct TagRequest {
-    pub name: String,
-}
+use super::{
+    errors::ApiError,
+    extract::{Json, Path, Query},
+    post_handlers::{authorize_post_change, SlugRedirect},
+    responses::{Created, Page},
+};
 
 /// Query parameters for listing tags
 #[derive(Debug, Deserialize)]
 pub struct ListTagsQuery {
     #[serde(default)]
     pub include_post_count: bool,
+    /// Case-insensitive substring filter on the tag name
+    pub q: Option<String>,
+    #[serde(default)]
+    pub sort: TagSort,
+    #[serde(default = "default_tags_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_tags_limit() -> i64 {
+    50
 }
 
 /// Create a new tag
 ///
-/// This handler accepts a JSON payload containing the tag name and creates
-/// a new tag in the database. It ensures the tag name is unique.
+/// This handler accepts a JSON payload containing the tag name, and optionally
+/// a description and color, and creates a new tag in the database. It ensures the tag name is unique and returns
+/// 201 Created with a Location header pointing at the new tag.
 pub async fn create_tag(
     State(db): State<Database>,
+    user: AuthUser,
     Json(tag_request): Json<TagRequest>,
-) -> Result<Json<Tag>, ApiError> {
+) -> Result<Created<Tag>, ApiError> {
     // Validate tag name format before attempting database operation
     if !Tag::is_valid_name(&tag_request.name) {
+        return Err(ApiError::validation("name", "Invalid tag name format"));
+    }
+
+    let tag = db
+        .acting_as(user.actor)
+        .tags()
+        .create_with_details(&tag_request)
+        .await?;
+    Ok(Created::new(format!("/tags/{}", tag.id), tag))
+}
+
+/// Query parameters for tag suggestions
+#[derive(Debug, Deserialize)]
+pub struct SuggestTagsQuery {
+    pub prefix: String,
+    #[serde(default = "default_suggest_limit")]
+    pub limit: i64,
+}
+
+fn default_suggest_limit() -> i64 {
+    10
+}
+
+/// Suggest tags for autocomplete
+///
+/// Returns tags whose name starts with the prefix, ignoring case, with the
+/// most used tags first. Prefixes must be between 1 and 50 characters.
+pub async fn suggest_tags(
+    State(db): State<Database>,
+    Query(query): Query<SuggestTagsQuery>,
+) -> Result<Json<Vec<TagWithPostCount>>, ApiError> {
+    let length = query.prefix.chars().count();
+    if !(1..=50).contains(&length) {
         return Err(ApiError::InvalidInput(
-            "Invalid tag name format".to_string(),
+            "prefix must be between 1 and 50 characters".to_string(),
         ));
     }
 
-    let tag = db.tags().create(&tag_request.name).await?;
-    Ok(Json(tag))
+    let tags = db.tags().suggest(&query.prefix, query.limit).await?;
+    Ok(Json(tags))
+}
+
+/// Query parameters for listing popular tags
+#[derive(Debug, Deserialize)]
+pub struct PopularTagsQuery {
+    #[serde(default = "default_popular_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub published_only: bool,
+}
+
+fn default_popular_limit() -> i64 {
+    20
+}
+
+/// List the most used tags
+///
+/// Tags are ordered by the number of posts carrying them, with ties broken
+/// alphabetically. With `published_only`, drafts aren't counted.
+pub async fn popular_tags(
+    State(db): State<Database>,
+    Query(query): Query<PopularTagsQuery>,
+) -> Result<Json<Vec<TagWithPostCount>>, ApiError> {
+    let tags = db.tags().popular(query.published_only, query.limit).await?;
+    Ok(Json(tags))
+}
+
+/// Query parameters for listing related tags
+#[derive(Debug, Deserialize)]
+pub struct RelatedTagsQuery {
+    #[serde(default = "default_related_limit")]
+    pub limit: i64,
+}
+
+fn default_related_limit() -> i64 {
+    5
+}
+
+/// List the tags that most often appear on the same posts as a tag
+///
+/// Tags are ordered by the number of shared posts, with ties broken
+/// alphabetically. Returns 404 if the tag doesn't exist.
+pub async fn related_tags(
+    State(db): State<Database>,
+    Path(id): Path<i64>,
+    Query(query): Query<RelatedTagsQuery>,
+) -> Result<Json<Vec<RelatedTag>>, ApiError> {
+    let tags = db.tags().related(id, query.limit).await?;
+    Ok(Json(tags))
+}
+
+/// A tag returned by get-or-create, flagged with whether it was just created
+#[derive(Debug, Serialize)]
+pub struct TagLookup {
+    #[serde(flatten)]
+    pub tag: Tag,
+    pub created: bool,
+}
+
+/// Get a tag by name, creating it if it doesn't exist
+///
+/// Names are matched case-insensitively and validated like `create_tag`.
+/// The description and color are only used when the tag is created.
+/// Returns 201 Created with a Location header when the tag was created,
+/// and 200 OK with the existing tag otherwise.
+pub async fn get_or_create_tag(
+    State(db): State<Database>,
+    user: AuthUser,
+    Json(tag_request): Json<TagRequest>,
+) -> Result<Response, ApiError> {
+    if !Tag::is_valid_name(&tag_request.name) {
+        return Err(ApiError::validation("name", "Invalid tag name format"));
+    }
+
+    let (tag, created) = db
+        .acting_as(user.actor)
+        .tags()
+        .get_or_create(&tag_request)
+        .await?;
+    let location = format!("/tags/{}", tag.id);
+    let lookup = TagLookup { tag, created };
+    if created {
+        Ok(Created::new(location, lookup).into_response())
+    } else {
+        Ok(Json(lookup).into_response())
+    }
 }
 
 /// Get a tag by its ID
@@ -69,35 +209,89 @@ pub async fn get_tag_by_name(
     Ok(Json(tag))
 }
 
+/// Get a tag by its slug
+///
+/// This handler retrieves a single tag by the URL-safe slug derived from
+/// its name. When the slug belonged to a tag that has since been renamed,
+/// it responds with 301 Moved Permanently, a Location header, and
+/// `{ "redirect_to": "new-slug" }`. It returns a 404 error if no tag ever
+/// had the slug.
+pub async fn get_tag_by_slug(
+    State(db): State<Database>,
+    Path(slug): Path<String>,
+) -> Result<Response, ApiError> {
+    match db.tags().resolve_slug(&slug).await? {
+        TagSlugLookup::Found(tag) => Ok(Json(tag).into_response()),
+        TagSlugLookup::Moved(redirect_to) => {
+            let location = HeaderValue::from_str(&format!("/tags/by-slug/{}", redirect_to))
+                .map_err(|e| ApiError::InvalidInput(e.to_string()))?;
+            Ok((
+                StatusCode::MOVED_PERMANENTLY,
+                [(header::LOCATION, location)],
+                Json(SlugRedirect { redirect_to }),
+            )
+                .into_response())
+        }
+    }
+}
+
 /// List all tags
 ///
-/// This handler returns a list of all tags, optionally including the count
-/// of posts associated with each tag.
+/// This handler returns a page of tags, optionally filtered by name and
+/// including the count of posts associated with each tag. The total number
+/// of matching tags is returned in the `X-Total-Count` header.
 pub async fn list_tags(
     State(db): State<Database>,
     Query(query): Query<ListTagsQuery>,
-) -> Result<Json<Vec<TagWithPostCount>>, ApiError> {
-    let tags = db.tags().list(query.include_post_count).await?;
-    Ok(Json(tags))
+) -> Result<Page<TagWithPostCount>, ApiError> {
+    let (tags, total) = db
+        .tags()
+        .list(&TagListOptions {
+            include_post_count: query.include_post_count,
+            search: query.q,
+            sort: query.sort,
+            limit: query.limit,
+            offset: query.offset,
+        })
+        .await?;
+    Ok(Page::new(tags, total))
 }
 
-/// Update a tag's name
+/// Replace a tag
 ///
 /// This handler accepts a JSON payload containing the new tag name and updates
-/// the tag with the specified ID.
+/// the tag with the specified ID. A description or color left out of the
+/// payload is cleared.
 pub async fn update_tag(
     State(db): State<Database>,
+    user: AuthUser,
     Path(id): Path<i64>,
     Json(tag_request): Json<TagRequest>,
 ) -> Result<Json<Tag>, ApiError> {
     // Validate tag name format before attempting database operation
     if !Tag::is_valid_name(&tag_request.name) {
-        return Err(ApiError::InvalidInput(
-            "Invalid tag name format".to_string(),
-        ));
+        return Err(ApiError::validation("name", "Invalid tag name format"));
     }
 
-    let tag = db.tags().update(id, &tag_request.name).await?;
+    let tag = db
+        .acting_as(user.actor)
+        .tags()
+        .update(id, &tag_request)
+        .await?;
+    Ok(Json(tag))
+}
+
+/// Partially update a tag
+///
+/// Only the fields present in the payload are changed, so clients can edit a
+/// tag without knowing its current values.
+pub async fn patch_tag(
+    State(db): State<Database>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+    Json(patch): Json<PatchTag>,
+) -> Result<Json<Tag>, ApiError> {
+    let tag = db.acting_as(user.actor).tags().patch(id, patch).await?;
     Ok(Json(tag))
 }
 
@@ -106,23 +300,63 @@ pub async fn update_tag(
 /// This handler deletes the tag with the specified ID. It returns a 404
 /// error if the tag is not found. Due to the database's foreign key
 /// constraints, this will also remove all associations between this tag
-/// and any posts.
+/// and any posts. Only admins may delete tags.
 pub async fn delete_tag(
     State(db): State<Database>,
+    user: AuthUser,
     Path(id): Path<i64>,
 ) -> Result<StatusCode, ApiError> {
-    db.tags().delete(id).await?;
+    user.require_role(UserRole::Admin)?;
+    db.acting_as(user.actor).tags().delete(id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Query parameters for deleting unused tags
+#[derive(Debug, Deserialize)]
+pub struct DeleteUnusedQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Result of deleting unused tags
+#[derive(Debug, Serialize)]
+pub struct DeletedTags {
+    pub deleted: usize,
+    pub names: Vec<String>,
+}
+
+/// Delete all tags that aren't attached to any post
+///
+/// With `?dry_run=true` nothing is deleted; the response lists the tags
+/// that would have been removed. Only admins may delete tags.
+pub async fn delete_unused_tags(
+    State(db): State<Database>,
+    user: AuthUser,
+    Query(query): Query<DeleteUnusedQuery>,
+) -> Result<Json<DeletedTags>, ApiError> {
+    user.require_role(UserRole::Admin)?;
+    let names = db
+        .acting_as(user.actor)
+        .tags()
+        .delete_unused(query.dry_run)
+        .await?;
+    Ok(Json(DeletedTags {
+        deleted: names.len(),
+        names,
+    }))
+}
+
 /// Add a tag to a post
 ///
 /// This handler creates an association between a post and a tag. Both the
-/// post and tag must exist.
+/// post and tag must exist, and only the post's author or an admin may
+/// change its tags.
 pub async fn add_tag_to_post(
     State(db): State<Database>,
+    user: AuthUser,
     Path((post_id, tag_id)): Path<(i64, i64)>,
 ) -> Result<StatusCode, ApiError> {
+    authorize_post_change(&db, &user, post_id).await?;
     db.tags().add_tag_to_post(post_id, tag_id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
@@ -131,15 +365,89 @@ pub async fn add_tag_to_post(
 ///
 /// This handler removes the association between a post and a tag. Returns
 /// a 404 error if either the post or tag doesn't exist, or if they're not
-/// associated.
+/// associated. Only the post's author or an admin may change its tags.
 pub async fn remove_tag_from_post(
     State(db): State<Database>,
+    user: AuthUser,
     Path((post_id, tag_id)): Path<(i64, i64)>,
 ) -> Result<StatusCode, ApiError> {
+    authorize_post_change(&db, &user, post_id).await?;
     db.tags().remove_tag_from_post(post_id, tag_id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Query parameters for listing the posts carrying a tag
+#[derive(Debug, Deserialize)]
+pub struct TagPostsQuery {
+    /// Only posts in this language, e.g. `de`
+    pub lang: Option<String>,
+    #[serde(default = "default_published_only")]
+    pub published_only: bool,
+    #[serde(default = "default_tag_posts_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+/// Tag post listings are public, so drafts are hidden unless asked for
+fn default_published_only() -> bool {
+    true
+}
+
+fn default_tag_posts_limit() -> i64 {
+    20
+}
+
+/// List the posts carrying a tag
+///
+/// Returns published posts newest first; authenticated callers may pass
+/// `published_only=false` to include drafts. Returns a 404 error if the tag
+/// doesn't exist.
+pub async fn list_tag_posts(
+    State(db): State<Database>,
+    principal: Option<Extension<Principal>>,
+    Path(id): Path<i64>,
+    Query(query): Query<TagPostsQuery>,
+) -> Result<Json<Vec<Post>>, ApiError> {
+    let tag = db.tags().find_by_id(id).await?;
+    tag_posts(&db, principal, &tag, &query).await
+}
+
+/// List the posts carrying a tag, looked up by name
+///
+/// Behaves like `list_tag_posts`; the name is matched case-insensitively.
+pub async fn list_tag_posts_by_name(
+    State(db): State<Database>,
+    principal: Option<Extension<Principal>>,
+    Path(name): Path<String>,
+    Query(query): Query<TagPostsQuery>,
+) -> Result<Json<Vec<Post>>, ApiError> {
+    let tag = db.tags().find_by_name(&name).await?;
+    tag_posts(&db, principal, &tag, &query).await
+}
+
+async fn tag_posts(
+    db: &Database,
+    principal: Option<Extension<Principal>>,
+    tag: &Tag,
+    query: &TagPostsQuery,
+) -> Result<Json<Vec<Post>>, ApiError> {
+    if !query.published_only && principal.is_none() {
+        return Err(AuthError::MissingToken.into());
+    }
+    let posts = db
+        .posts()
+        .list_by_tag(
+            tag.id,
+            query.lang.as_deref(),
+            query.published_only,
+            query.limit,
+            query.offset,
+        )
+        .await?;
+    Ok(Json(posts))
+}
+
 /// Get all tags for a post
 ///
 /// This handler returns a list of all tags associated with the specified post.
@@ -151,15 +459,43 @@ pub async fn get_post_tags(
     Ok(Json(tags))
 }
 
+/// Request body for replacing the tags on a post
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetPostTagsRequest {
+    pub tag_ids: Vec<i64>,
+}
+
+/// Replace all tags on a post
+///
+/// This handler makes the post's tags exactly the given list in a single
+/// transaction and returns the resulting tags. An empty list removes all
+/// tags. Returns a 404 error naming the first unknown tag, if any.
+/// Only the post's author or an admin may change its tags.
+pub async fn set_post_tags(
+    State(db): State<Database>,
+    user: AuthUser,
+    Path(post_id): Path<i64>,
+    Json(request): Json<SetPostTagsRequest>,
+) -> Result<Json<Vec<Tag>>, ApiError> {
+    authorize_post_change(&db, &user, post_id).await?;
+    let tags = db
+        .tags()
+        .set_tags_for_post(post_id, &request.tag_ids)
+        .await?;
+    Ok(Json(tags))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         db::{test_utils::create_test_db, DatabaseError},
-        models::post::{CreatePost, PostCategory},
+        models::{audit::Actor, post::CreatePost},
     };
     use axum::{
         body::Body,
+        extract::FromRequest,
         http::{self, Request, StatusCode},
     };
     use serde_json::json;
@@ -168,6 +504,14 @@ mod tests {
         create_test_db().await.unwrap()
     }
 
+    fn admin() -> AuthUser {
+        AuthUser {
+            id: None,
+            role: UserRole::Admin,
+            actor: Actor::System,
+        }
+    }
+
     #[tokio::test]
     async fn test_create_tag() {
         let db = setup().await;
@@ -181,28 +525,24 @@ mod tests {
                 serde_json::to_vec(&json!({ "name": "test-tag" })).unwrap(),
             ))
             .unwrap();
+        let body = Json::<TagRequest>::from_request(req, &()).await.unwrap();
 
-        let response = create_tag(
-            State(db.clone()),
-            Json(TagRequest {
-                name: "test-tag".to_string(),
-            }),
-        )
-        .await;
+        let response = create_tag(State(db.clone()), admin(), body).await;
         assert!(response.is_ok());
-        let tag = response.unwrap().0;
-        assert_eq!(tag.name, "test-tag");
+        let created = response.unwrap();
+        assert_eq!(created.location, format!("/tags/{}", created.body.id));
+        assert_eq!(created.body.name, "test-tag");
 
         // Test invalid tag name
-        let response = create_tag(
-            State(db.clone()),
-            Json(TagRequest {
-                name: "".to_string(),
-            }),
-        )
-        .await;
+        let response = create_tag(State(db.clone()), admin(), Json(TagRequest::named(""))).await;
         assert!(response.is_err());
-        assert!(matches!(response.unwrap_err(), ApiError::InvalidInput(_)));
+        assert!(matches!(
+            response.unwrap_err(),
+            ApiError::Validation {
+                field: Some("name"),
+                ..
+            }
+        ));
     }
 
     #[tokio::test]
@@ -239,23 +579,32 @@ mod tests {
             State(db.clone()),
             Query(ListTagsQuery {
                 include_post_count: false,
+                q: None,
+                sort: TagSort::Name,
+                limit: 50,
+                offset: 0,
             }),
         )
         .await;
         assert!(response.is_ok());
-        let tags = response.unwrap().0;
-        assert_eq!(tags.len(), 2);
+        let page = response.unwrap();
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(page.total, 2);
 
         // Test listing with post count
         let response = list_tags(
             State(db),
             Query(ListTagsQuery {
                 include_post_count: true,
+                q: None,
+                sort: TagSort::Name,
+                limit: 50,
+                offset: 0,
             }),
         )
         .await;
         assert!(response.is_ok());
-        let tags = response.unwrap().0;
+        let tags = response.unwrap().items;
         assert_eq!(tags.len(), 2);
         assert_eq!(tags[0].post_count, 0);
     }
@@ -270,10 +619,9 @@ mod tests {
         // Test successful update
         let response = update_tag(
             State(db.clone()),
+            admin(),
             Path(tag.id),
-            Json(TagRequest {
-                name: "updated".to_string(),
-            }),
+            Json(TagRequest::named("updated")),
         )
         .await;
         assert!(response.is_ok());
@@ -282,22 +630,26 @@ mod tests {
         // Test invalid tag name
         let response = update_tag(
             State(db.clone()),
+            admin(),
             Path(tag.id),
-            Json(TagRequest {
-                name: "".to_string(),
-            }),
+            Json(TagRequest::named("")),
         )
         .await;
         assert!(response.is_err());
-        assert!(matches!(response.unwrap_err(), ApiError::InvalidInput(_)));
+        assert!(matches!(
+            response.unwrap_err(),
+            ApiError::Validation {
+                field: Some("name"),
+                ..
+            }
+        ));
 
         // Test non-existent tag
         let response = update_tag(
             State(db),
+            admin(),
             Path(999),
-            Json(TagRequest {
-                name: "test".to_string(),
-            }),
+            Json(TagRequest::named("test")),
         )
         .await;
         assert!(response.is_err());
@@ -315,12 +667,12 @@ mod tests {
         let tag = db.tags().create("delete-me").await.unwrap();
 
         // Test successful deletion
-        let response = delete_tag(State(db.clone()), Path(tag.id)).await;
+        let response = delete_tag(State(db.clone()), admin(), Path(tag.id)).await;
         assert!(response.is_ok());
         assert_eq!(response.unwrap(), StatusCode::NO_CONTENT);
 
         // Test deleting non-existent tag
-        let response = delete_tag(State(db), Path(999)).await;
+        let response = delete_tag(State(db), admin(), Path(999)).await;
         assert!(response.is_err());
         assert!(matches!(
             response.unwrap_err(),
@@ -336,21 +688,21 @@ mod tests {
         let tag = db.tags().create("test-tag").await.unwrap();
         let post = db
             .posts()
-            .create(CreatePost {
-                category: PostCategory::Blog,
-                title: "Test Post".to_string(),
-                slug: "test-post".to_string(),
-                content: "Test content".to_string(),
-                description: "Test description".to_string(),
-                image_url: None,
-                external_url: None,
-                published: true,
-            })
+            .create(
+                CreatePost::builder()
+                    .title("Test Post")
+                    .slug("test-post")
+                    .content("Test content")
+                    .description("Test description")
+                    .published(true)
+                    .build()
+                    .unwrap(),
+            )
             .await
             .unwrap();
 
         // Test adding tag to post
-        let response = add_tag_to_post(State(db.clone()), Path((post.id, tag.id))).await;
+        let response = add_tag_to_post(State(db.clone()), admin(), Path((post.id, tag.id))).await;
         assert!(response.is_ok());
 
         // Test getting post tags
@@ -361,7 +713,8 @@ mod tests {
         assert_eq!(tags[0].id, tag.id);
 
         // Test removing tag from post
-        let response = remove_tag_from_post(State(db.clone()), Path((post.id, tag.id))).await;
+        let response =
+            remove_tag_from_post(State(db.clone()), admin(), Path((post.id, tag.id))).await;
         assert!(response.is_ok());
         assert_eq!(response.unwrap(), StatusCode::NO_CONTENT);
 
@@ -0,0 +1,42 @@
+use axum::{
+    extract::State,
+    response::{IntoResponse, Response},
+};
+
+use crate::{models::upload::Upload, storage::ImageStore};
+
+use super::{
+    errors::ApiError,
+    extract::{Json, Multipart},
+    responses::Created,
+};
+
+/// Upload an image
+///
+/// Takes `multipart/form-data` with the image in a field named `file`. The
+/// image type is read from the content, whatever the file name or declared
+/// type say. Returns 201 Created with the image's URL, or 200 OK with the
+/// same URL when the image had already been uploaded.
+pub async fn upload_image(
+    State(store): State<ImageStore>,
+    Multipart(mut form): Multipart,
+) -> Result<Response, ApiError> {
+    while let Some(field) = form.next_field().await? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let data = field.bytes().await?;
+        let stored = store.store(&data).await?;
+
+        let upload = Upload {
+            url: format!("/static/{}", stored.file_name),
+        };
+        return Ok(if stored.existed {
+            Json(upload).into_response()
+        } else {
+            Created::new(upload.url.clone(), upload).into_response()
+        });
+    }
+
+    Err(ApiError::validation("file", "The form has no file field"))
+}
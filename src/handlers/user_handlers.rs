@@ -0,0 +1,61 @@
+use axum::{extract::State, http::StatusCode};
+
+use crate::{
+    db::Database,
+    models::user::{CreateUser, UpdateUser, User},
+};
+
+use super::{
+    errors::ApiError,
+    extract::{Json, Path},
+    responses::Created,
+};
+
+/// Create a user account
+///
+/// The password is hashed with Argon2 before it is stored. Returns 201
+/// Created with a Location header, or 409 if the email is already taken.
+pub async fn create_user(
+    State(db): State<Database>,
+    Json(user): Json<CreateUser>,
+) -> Result<Created<User>, ApiError> {
+    let user = db.users().create(user).await?;
+    Ok(Created::new(format!("/users/{}", user.id), user))
+}
+
+/// List all user accounts, oldest first
+pub async fn list_users(State(db): State<Database>) -> Result<Json<Vec<User>>, ApiError> {
+    let users = db.users().list().await?;
+    Ok(Json(users))
+}
+
+/// Get a user account by ID
+pub async fn get_user(
+    State(db): State<Database>,
+    Path(id): Path<i64>,
+) -> Result<Json<User>, ApiError> {
+    let user = db.users().find_by_id(id).await?;
+    Ok(Json(user))
+}
+
+/// Update a user account
+///
+/// Only the fields present in the payload are changed; a new password is
+/// hashed before it is stored.
+pub async fn update_user(
+    State(db): State<Database>,
+    Path(id): Path<i64>,
+    Json(update): Json<UpdateUser>,
+) -> Result<Json<User>, ApiError> {
+    let user = db.users().update(id, update).await?;
+    Ok(Json(user))
+}
+
+/// Delete a user account
+pub async fn delete_user(
+    State(db): State<Database>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    db.users().delete(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,5472 @@
+//! A JSON API for a blog: posts, tags, users and the machinery around
+//! them. `app` builds the complete router, so the API can be served by the
+//! bundled binary or nested inside another axum application.
+
+use axum::{
+    extract::DefaultBodyLimit,
+    http::{header, HeaderName, StatusCode},
+    middleware::{from_fn, from_fn_with_state, map_response},
+    routing::{delete, get, patch, post, put},
+    Router,
+};
+use tower_http::{
+    catch_panic::CatchPanicLayer,
+    compression::{
+        predicate::{NotForContentType, Predicate, SizeAbove},
+        CompressionLayer,
+    },
+    cors::{AllowOrigin, CorsLayer},
+    limit::RequestBodyLimitLayer,
+    normalize_path::NormalizePath,
+    timeout::TimeoutLayer,
+    trace::TraceLayer,
+};
+
+use crate::{
+    auth::{require_auth, AuthPolicy, FormTokens, JwtAuth},
+    config::{Config, CorsConfig, CorsOrigins},
+    db::Database,
+    handlers::{
+        admin_handlers::{
+            backup, integrity_check, list_migrations, metrics, rebuild_render_cache, vacuum,
+            Maintenance,
+        },
+        api_key_handlers::{create_api_key, list_api_keys, revoke_api_key},
+        audit_handlers::{list_activity, list_audit_log},
+        auth_handlers::{login, me},
+        click_handlers::{list_post_clicks, visit_post},
+        client_handlers::client_types,
+        comment_handlers::{
+            approve_comment, comment_form_token, create_comment, delete_comment, list_comments,
+            list_comments_for_moderation, reject_comment,
+        },
+        contact_handlers::{create_contact_message, list_contact_messages, update_contact_message},
+        fallback::{
+            limit_errors_as_json, method_not_allowed_as_json, panic_as_json, route_not_found,
+        },
+        feed_handlers::feed,
+        health_handlers::readyz,
+        link_check_handlers::list_broken_links,
+        post_handlers::{
+            create_post, delete_post, export_posts, get_post, get_post_by_id, get_post_by_slug,
+            list_categories, list_post_changes, list_posts, list_posts_by_month,
+            list_posts_for_admin, list_untagged_posts, patch_post, save_draft_content, update_post,
+            POST_TITLE_HEADER,
+        },
+        responses::TOTAL_COUNT_HEADER,
+        robots::{robots_txt, RobotsTxt},
+        static_files::static_files,
+        stats_handlers::year_stats,
+        tag_handlers::{
+            add_tag_to_post, create_tag, delete_tag, delete_unused_tags, get_or_create_tag,
+            get_post_tags, get_tag_by_id, get_tag_by_name, get_tag_by_slug, list_tag_posts,
+            list_tag_posts_by_name, list_tags, patch_tag, popular_tags, related_tags,
+            remove_tag_from_post, set_post_tags, suggest_tags, update_tag,
+        },
+        upload_handlers::upload_image,
+        user_handlers::{create_user, delete_user, get_user, list_users, update_user},
+    },
+    middleware::{
+        cache_control, idempotent, record_route, request_id, request_span, Idempotency,
+        RateLimitLayer, RateLimiter, IDEMPOTENT_REPLAYED_HEADER, REQUEST_ID_HEADER,
+    },
+    notify::Notifier,
+    state::AppState,
+    storage::ImageStore,
+};
+
+pub mod auth;
+pub mod cli;
+pub mod client_types;
+pub mod config;
+pub mod db;
+pub mod feeds;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+pub mod handlers;
+pub mod link_checker;
+pub mod middleware;
+pub mod models;
+pub mod notify;
+pub mod render;
+pub mod startup;
+pub mod state;
+pub mod storage;
+pub mod telemetry;
+
+/// Builds the API router with every route and layer, backed by `db` and
+/// configured by `config`
+pub fn app(db: Database, config: &Config) -> Router {
+    let state = AppState {
+        db,
+        auth: JwtAuth::new(config.jwt_secret.as_bytes(), config.jwt_expiry),
+        auth_policy: AuthPolicy::new(config.api_key.clone(), config.auth_disabled),
+        form_tokens: FormTokens::new(config.jwt_secret.as_bytes()),
+        feed: config.feed.clone(),
+        notifier: Notifier::new(config.notify_webhook_url.clone()),
+    };
+
+    // Per-client rate limits
+    let limiter = RateLimiter::new(config.rate_limit);
+
+    // Retry-safe creates
+    let idempotency = Idempotency::new(
+        state.db.clone(),
+        config.max_body_bytes,
+        config.request_timeout,
+    );
+
+    // Build routes
+    let routes = Router::new()
+        // Post routes
+        .route("/posts", get(list_posts))
+        .route(
+            "/posts",
+            post(create_post).route_layer(from_fn_with_state(idempotency.clone(), idempotent)),
+        )
+        .route("/posts/changes", get(list_post_changes))
+        .route("/posts/untagged", get(list_untagged_posts))
+        .route("/export/posts.ndjson", get(export_posts))
+        .route("/posts/by-id/{id}", get(get_post_by_id))
+        .route("/posts/by-slug/{slug}", get(get_post_by_slug))
+        // An ID or a slug; static segments such as `/posts/changes` win over
+        // it, so posts with those slugs need `/posts/by-slug/{slug}`
+        .route("/posts/{id}", get(get_post))
+        .route("/posts/{year}/{month}", get(list_posts_by_month))
+        .route("/categories", get(list_categories))
+        .route("/posts", put(update_post))
+        .route("/posts", patch(patch_post))
+        .route("/posts/{id}/draft-content", put(save_draft_content))
+        .route("/posts/{id}", delete(delete_post))
+        // Every post, drafts included, for the admin UI
+        .route("/admin/posts", get(list_posts_for_admin))
+        // Tag routes
+        .route("/tags", get(list_tags))
+        .route(
+            "/tags",
+            post(create_tag).route_layer(from_fn_with_state(idempotency.clone(), idempotent)),
+        )
+        .route("/tags/{id}", get(get_tag_by_id))
+        .route("/tags/by-name/{name}", get(get_tag_by_name))
+        .route("/tags/by-slug/{slug}", get(get_tag_by_slug))
+        .route("/tags/unused", delete(delete_unused_tags))
+        .route("/tags/suggest", get(suggest_tags))
+        .route("/tags/popular", get(popular_tags))
+        .route("/tags/get-or-create", post(get_or_create_tag))
+        .route("/tags/{id}/posts", get(list_tag_posts))
+        .route("/tags/{id}/related", get(related_tags))
+        .route("/tags/by-name/{name}/posts", get(list_tag_posts_by_name))
+        .route("/tags/{id}", put(update_tag))
+        .route("/tags/{id}", patch(patch_tag))
+        .route("/tags/{id}", delete(delete_tag))
+        // Feeds of published posts, in full or for one tag or category
+        .route("/feed.xml", get(feed))
+        .route("/atom.xml", get(feed))
+        .route("/feed.json", get(feed))
+        .route("/tags/{name}/feed.xml", get(feed))
+        .route("/tags/{name}/atom.xml", get(feed))
+        .route("/tags/{name}/feed.json", get(feed))
+        .route("/categories/{category}/feed.xml", get(feed))
+        .route("/categories/{category}/atom.xml", get(feed))
+        .route("/categories/{category}/feed.json", get(feed))
+        .route(
+            "/robots.txt",
+            get(robots_txt).with_state(RobotsTxt::new(config)),
+        )
+        // Types for frontends written in TypeScript
+        .route("/client/types.ts", get(client_types))
+        // Post-Tag relationship routes
+        .route("/posts/{post_id}/tags", get(get_post_tags))
+        .route("/posts/{post_id}/tags", put(set_post_tags))
+        .route("/posts/{post_id}/tags/{tag_id}", put(add_tag_to_post))
+        .route(
+            "/posts/{post_id}/tags/{tag_id}",
+            delete(remove_tag_from_post),
+        )
+        // Comments: anyone may submit one, within a limit of their own, and
+        // readers see those an admin has approved
+        .route("/posts/{post_id}/comments", get(list_comments))
+        .route("/posts/{post_id}/comments/token", get(comment_form_token))
+        .route(
+            "/posts/{post_id}/comments",
+            post(create_comment).route_layer(RateLimitLayer::comments(limiter.clone())),
+        )
+        .route("/admin/comments", get(list_comments_for_moderation))
+        .route("/admin/comments/{id}/approve", post(approve_comment))
+        .route("/admin/comments/{id}/reject", post(reject_comment))
+        .route("/admin/comments/{id}", delete(delete_comment))
+        // Links out to posts' external URLs, counted for admins
+        .route("/posts/{post_id}/visit", get(visit_post))
+        .route("/posts/{post_id}/clicks", get(list_post_clicks))
+        // Figures for a year in review
+        .route("/stats/year/{year}", get(year_stats))
+        // Contact form, and the messages it collects for admins
+        .route(
+            "/contact",
+            post(create_contact_message).route_layer(RateLimitLayer::contact(limiter.clone())),
+        )
+        .route("/contact", get(list_contact_messages))
+        .route("/contact/{id}", patch(update_contact_message))
+        // Authentication
+        .route("/auth/login", post(login))
+        .route("/auth/me", get(me))
+        // User administration
+        .route("/users", get(list_users))
+        .route("/users", post(create_user))
+        .route("/users/{id}", get(get_user))
+        .route("/users/{id}", patch(update_user))
+        .route("/users/{id}", delete(delete_user))
+        // API key administration
+        .route("/api-keys", get(list_api_keys))
+        .route("/api-keys", post(create_api_key))
+        .route("/api-keys/{id}", delete(revoke_api_key))
+        // Audit log, and the recent activity read from it
+        .route("/audit", get(list_audit_log))
+        .route("/admin/activity", get(list_activity))
+        // Schema, query timings and readiness
+        .route("/admin/migrations", get(list_migrations))
+        .route("/admin/metrics", get(metrics))
+        .route("/admin/broken-links", get(list_broken_links))
+        .route("/readyz", get(readyz))
+        // Bigger bodies are refused with 413 before a handler reads them
+        .route_layer(RequestBodyLimitLayer::new(config.max_body_bytes));
+
+    // Posts and tags in whatever shape the client asks for
+    #[cfg(feature = "graphql")]
+    let routes = routes.route(
+        "/graphql",
+        post(graphql::graphql_handler)
+            .with_state(graphql::GraphQlState::new(state.db.clone()))
+            .layer(RequestBodyLimitLayer::new(config.max_body_bytes)),
+    );
+
+    // Image uploads, stored in the static directory, have a limit of their own
+    let routes = match &config.static_dir {
+        Some(dir) => routes.route(
+            "/uploads",
+            post(upload_image)
+                .with_state(ImageStore::new(dir))
+                .layer(RequestBodyLimitLayer::new(config.max_upload_bytes)),
+        ),
+        None => routes,
+    };
+
+    // Database maintenance; backups only once there is somewhere to put them
+    let maintenance = Maintenance::new(state.db.clone(), config.backup_dir.clone());
+    let routes = routes
+        .route(
+            "/admin/vacuum",
+            post(vacuum).with_state(maintenance.clone()),
+        )
+        .route(
+            "/admin/integrity-check",
+            post(integrity_check).with_state(maintenance.clone()),
+        )
+        .route(
+            "/admin/render-cache/rebuild",
+            post(rebuild_render_cache).with_state(maintenance.clone()),
+        );
+    let routes = match &config.backup_dir {
+        Some(_) => routes.route("/admin/backup", post(backup).with_state(maintenance)),
+        None => routes,
+    };
+
+    let routes = routes
+        // Everything but reads and login needs credentials
+        .route_layer(from_fn_with_state(state.clone(), require_auth))
+        // Log lines name the route as well as the requested path
+        .route_layer(from_fn(record_route))
+        // JSON body for unknown routes
+        .fallback(route_not_found)
+        // Add shared state
+        .with_state(state);
+
+    // Files such as post images, when a directory is configured
+    let routes = match &config.static_dir {
+        Some(dir) => routes.nest("/static", static_files(dir)),
+        None => routes,
+    };
+
+    // Lets tests check how a panicking handler is answered
+    #[cfg(test)]
+    let routes = routes.route("/test/panic", get(panicking_handler));
+
+    // The router adds the Allow header after its own layers have run, so the
+    // JSON 405 mapping has to wrap the whole router rather than its routes.
+    // Trailing slashes are trimmed before the routes are matched, so
+    // `/posts/` is `/posts`; a layer on the router itself would only run
+    // once a route had matched.
+    let app = Router::new()
+        .fallback_service(NormalizePath::trim_trailing_slash(routes))
+        .layer(map_response(method_not_allowed_as_json));
+
+    with_request_limits(app, config)
+        .layer(RateLimitLayer::new(limiter))
+        // Inside the trace layer, so the panic is logged in the request's
+        // span with its id, and inside CORS, so browsers can read the error
+        .layer(CatchPanicLayer::custom(panic_as_json))
+        // Outside everything that can answer with an error, so errors are
+        // never cached
+        .layer(from_fn_with_state(
+            config.cache_control.clone(),
+            cache_control,
+        ))
+        .layer(compression(config))
+        .layer(cors(&config.cors))
+        // Every log line for a request carries its id
+        .layer(TraceLayer::new_for_http().make_span_with(request_span))
+        .layer(from_fn(request_id))
+}
+
+/// Tells browsers which cross-origin requests to allow
+fn cors(config: &CorsConfig) -> CorsLayer {
+    CorsLayer::new()
+        .allow_origin(match &config.origins {
+            CorsOrigins::Any => AllowOrigin::any(),
+            CorsOrigins::List(origins) => AllowOrigin::list(origins.clone()),
+        })
+        .allow_methods(config.methods.clone())
+        .allow_headers(config.headers.clone())
+        .max_age(config.max_age)
+        .allow_credentials(config.allow_credentials)
+        .expose_headers([
+            REQUEST_ID_HEADER,
+            HeaderName::from_static(TOTAL_COUNT_HEADER),
+            HeaderName::from_static(IDEMPOTENT_REPLAYED_HEADER),
+            HeaderName::from_static(POST_TITLE_HEADER),
+            header::LOCATION,
+            header::ETAG,
+            header::LAST_MODIFIED,
+            header::RETRY_AFTER,
+        ])
+}
+
+/// Compresses responses for clients that accept it. Event streams are left
+/// alone, since compression would hold events back until a block fills up.
+fn compression(config: &Config) -> CompressionLayer<impl Predicate> {
+    CompressionLayer::new()
+        .gzip(config.compression.gzip)
+        .br(config.compression.brotli)
+        .compress_when(
+            SizeAbove::new(config.compression.min_bytes)
+                .and(NotForContentType::GRPC)
+                .and(NotForContentType::IMAGES)
+                .and(NotForContentType::SSE),
+        )
+}
+
+/// Caps how long a request may take, and answers it with a JSON 504 error
+/// when it takes longer, or a JSON 413 error when the routes' body limits
+/// refuse its body
+fn with_request_limits(router: Router, config: &Config) -> Router {
+    router
+        // The routes' limit layers cover every body, so the extractors' own
+        // limit would only get in the way of a larger configured one
+        .layer(DefaultBodyLimit::disable())
+        .layer(TimeoutLayer::with_status_code(
+            StatusCode::GATEWAY_TIMEOUT,
+            config.request_timeout,
+        ))
+        .layer(map_response(limit_errors_as_json))
+}
+
+#[cfg(test)]
+async fn panicking_handler() {
+    panic!("test panic")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::{CacheControlConfig, RateLimitConfig};
+    use crate::models::{
+        comment::{CommentStatus, MAX_COMMENT_BODY_LEN},
+        link_check::LinkOutcome,
+        user::{User, UserRole},
+    };
+    use axum::{
+        body::Body,
+        http::{header, HeaderValue, Method, Request, StatusCode},
+        middleware::map_request,
+        response::Response,
+    };
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    const TEST_JWT_SECRET: &[u8] = b"test-secret";
+
+    /// Issues an access token the test apps accept, for a user that need
+    /// not exist in the database
+    fn test_token(role: UserRole) -> String {
+        test_token_for(1, role)
+    }
+
+    fn test_token_for(id: i64, role: UserRole) -> String {
+        JwtAuth::new(TEST_JWT_SECRET, time::Duration::hours(1))
+            .issue(&User {
+                id,
+                email: "admin@example.com".to_string(),
+                password_hash: String::new(),
+                role,
+                created_at: time::OffsetDateTime::now_utc(),
+            })
+            .unwrap()
+    }
+
+    const TEST_API_KEY: &str = "test-api-key";
+
+    // Helper function to create a test app with a database connection.
+    // Requests without credentials are sent with the API key, which acts
+    // as an admin without a user account.
+    async fn create_test_app() -> Router {
+        create_test_app_with(
+            Config {
+                api_key: Some(TEST_API_KEY.to_string()),
+                ..Config::default_for_tests()
+            },
+            Some(TEST_API_KEY.to_string()),
+        )
+        .await
+    }
+
+    // Like create_test_app, with the given configuration and a token added
+    // to requests that don't carry an Authorization header themselves
+    async fn create_test_app_with(config: Config, default_token: Option<String>) -> Router {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+
+        app(db, &config).layer(map_request(move |mut request: Request<Body>| {
+            if let Some(token) = &default_token {
+                if !request.headers().contains_key(header::AUTHORIZATION) {
+                    let value = format!("Bearer {}", token).parse().unwrap();
+                    request.headers_mut().insert(header::AUTHORIZATION, value);
+                }
+            }
+            async move { request }
+        }))
+    }
+
+    // Helper to get response body as a Value
+    async fn response_json(response: Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cors_configuration() {
+        let config = Config {
+            cors: CorsConfig {
+                origins: CorsOrigins::List(vec!["https://example.com".parse().unwrap()]),
+                allow_credentials: true,
+                ..CorsConfig::default()
+            },
+            ..Config::default_for_tests()
+        };
+        let app = create_test_app_with(config, Some(TEST_API_KEY.to_string())).await;
+
+        let preflight = |origin: &'static str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method(Method::OPTIONS)
+                    .uri("/posts")
+                    .header(header::ORIGIN, origin)
+                    .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+                    .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "authorization")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let response = preflight("https://example.com").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers();
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://example.com"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "3600");
+        let methods = headers[header::ACCESS_CONTROL_ALLOW_METHODS]
+            .to_str()
+            .unwrap();
+        assert!(methods.contains("POST"));
+
+        // Other origins aren't allowed, so browsers block the request
+        let response = preflight("https://evil.example").await.unwrap();
+        assert!(response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none());
+
+        // Simple requests are answered the same way
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/posts")
+                    .header(header::ORIGIN, "https://example.com")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://example.com"
+        );
+        // Browsers only let scripts read the response headers listed here
+        let exposed = response.headers()[header::ACCESS_CONTROL_EXPOSE_HEADERS]
+            .to_str()
+            .unwrap();
+        let exposed: Vec<&str> = exposed.split(',').map(str::trim).collect();
+        for name in [
+            "x-request-id",
+            "x-total-count",
+            "location",
+            "etag",
+            "retry-after",
+            "idempotent-replayed",
+        ] {
+            assert!(exposed.contains(&name), "{} not in {:?}", name, exposed);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_post_crud_operations() {
+        let app = create_test_app().await;
+
+        // Create post
+        let create_response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/posts")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_string(&json!({
+                            "category": "blog",
+                            "title": "Test Post",
+                            "slug": "test-post",
+                            "content": "Test content",
+                            "description": "Test description",
+                            "published": true
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(create_response.status(), StatusCode::CREATED);
+        let location = create_response.headers()[header::LOCATION].clone();
+        let post = response_json(create_response).await;
+        let post_id = post["id"].as_i64().unwrap();
+        assert_eq!(location, format!("/posts/by-id/{}", post_id).as_str());
+
+        // Read post
+        let get_response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri(format!("/posts/by-id/{}", post_id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(get_response.status(), StatusCode::OK);
+
+        // List posts, with the total in a header
+        let list_response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/posts?limit=1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(list_response.status(), StatusCode::OK);
+        assert_eq!(list_response.headers()["x-total-count"], "1");
+
+        // Update post
+        let update_response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::PATCH)
+                    .uri("/posts")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_string(&json!({
+                            "id": post_id,
+                            "title": "Updated Title"
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(update_response.status(), StatusCode::OK);
+
+        // Delete post
+        let delete_response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::DELETE)
+                    .uri(format!("/posts/{}", post_id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(delete_response.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn test_tag_operations() {
+        let app = create_test_app().await;
+
+        // Create tag
+        let create_response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/tags")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_string(&json!({
+                            "name": "test-tag"
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(create_response.status(), StatusCode::CREATED);
+        let location = create_response.headers()[header::LOCATION].clone();
+        let tag = response_json(create_response).await;
+        let tag_id = tag["id"].as_i64().unwrap();
+        assert_eq!(location, format!("/tags/{}", tag_id).as_str());
+
+        // List tags
+        let list_response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri("/tags")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(list_response.status(), StatusCode::OK);
+        let tags = response_json(list_response).await;
+        assert!(!tags.as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tag_colors() {
+        let app = create_test_app().await;
+
+        let tag = response_json(
+            app.clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/tags")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(json!({ "name": "test-tag" }).to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap(),
+        )
+        .await;
+        let tag_id = tag["id"].as_i64().unwrap();
+
+        // An empty patch keeps the name, unknown fields are rejected and
+        // colors must be full hex colors, stored in lowercase
+        for (body, status, color) in [
+            (json!({}), StatusCode::OK, Value::Null),
+            (
+                json!({ "colour": "red" }),
+                StatusCode::BAD_REQUEST,
+                Value::Null,
+            ),
+            (
+                json!({ "color": "#abc" }),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Value::Null,
+            ),
+            (
+                json!({ "color": "#1A2B3C" }),
+                StatusCode::OK,
+                json!("#1a2b3c"),
+            ),
+        ] {
+            let patch_response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::PATCH)
+                        .uri(format!("/tags/{}", tag_id))
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(body.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(patch_response.status(), status, "{}", body);
+            if status == StatusCode::OK {
+                let patched = response_json(patch_response).await;
+                assert_eq!(patched["name"], "test-tag");
+                assert_eq!(patched["color"], color);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tag_sorting() {
+        let app = create_test_app().await;
+
+        let create = |uri: &str, body: Value| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri(uri)
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+        let response = create(
+            "/posts",
+            json!({
+                "category": "blog",
+                "title": "Tagged",
+                "slug": "tagged",
+                "content": "Test content",
+                "description": "Test description",
+                "published": true,
+                "tags": ["beta"]
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let response = create("/tags", json!({ "name": "alpha" })).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let list = |query: &str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .uri(format!("/tags?{}", query))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+        for (query, names) in [
+            ("", ["alpha", "beta"]),
+            ("sort=name", ["alpha", "beta"]),
+            ("sort=post_count", ["beta", "alpha"]),
+        ] {
+            let response = list(query).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{}", query);
+            assert_eq!(response.headers()["x-total-count"], "2");
+            let tags = response_json(response).await;
+            let listed: Vec<&str> = tags
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|tag| tag["name"].as_str().unwrap())
+                .collect();
+            assert_eq!(listed, names, "{}", query);
+        }
+
+        // Sorting by an unknown key is rejected
+        let bad_sort = list("sort=popularity").await.unwrap();
+        assert_eq!(bad_sort.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_delete_unused_tags_dry_run() {
+        let app = create_test_app().await;
+
+        let tag = response_json(
+            app.clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/tags")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(json!({ "name": "test-tag" }).to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap(),
+        )
+        .await;
+
+        // The new tag has no posts, so a dry run reports it as unused
+        let unused_response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::DELETE)
+                    .uri("/tags/unused?dry_run=true")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(unused_response.status(), StatusCode::OK);
+        let unused = response_json(unused_response).await;
+        assert_eq!(unused["deleted"], 1);
+        assert_eq!(unused["names"], json!(["test-tag"]));
+
+        // ...without deleting it
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/tags/{}", tag["id"]))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_tag_posts() {
+        let app = create_test_app().await;
+
+        let post = response_json(
+            app.clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/posts")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(
+                            json!({
+                                "category": "blog",
+                                "title": "Tagged",
+                                "slug": "tagged",
+                                "content": "Test content",
+                                "description": "Test description",
+                                "published": true
+                            })
+                            .to_string(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap(),
+        )
+        .await;
+        let tag = response_json(
+            app.clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/tags")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(json!({ "name": "rust" }).to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap(),
+        )
+        .await;
+
+        // The post counts as untagged until the tag is attached
+        let untagged_total = |app: Router| async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri("/posts/untagged")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            response.headers()["x-total-count"].clone()
+        };
+        assert_eq!(untagged_total(app.clone()).await, "1");
+
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri(format!("/posts/{}/tags/{}", post["id"], tag["id"]))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(untagged_total(app.clone()).await, "0");
+
+        // The only tag on the post has nothing related, and unknown tags are 404
+        for (uri, status) in [
+            (format!("/tags/{}/related", tag["id"]), StatusCode::OK),
+            ("/tags/999/related".to_string(), StatusCode::NOT_FOUND),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(Request::builder().uri(&uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status);
+            if status == StatusCode::OK {
+                assert_eq!(response_json(response).await, json!([]));
+            }
+        }
+
+        for uri in [
+            format!("/tags/{}/posts", tag["id"]),
+            "/tags/by-name/Rust/posts".to_string(),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(Request::builder().uri(&uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+            let posts = response_json(response).await;
+            assert_eq!(posts[0]["id"], post["id"]);
+        }
+
+        // Unknown tags are a 404 rather than an empty list
+        for uri in ["/tags/99999/posts", "/tags/by-name/missing/posts"] {
+            let response = app
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_or_create_tag() {
+        let app = create_test_app().await;
+
+        let request = |name: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/tags/get-or-create")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(json!({ "name": name }).to_string()))
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(request("rust")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert!(response.headers().contains_key(header::LOCATION));
+        let created = response_json(response).await;
+        assert_eq!(created["created"], true);
+
+        let response = app.clone().oneshot(request("Rust")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let existing = response_json(response).await;
+        assert_eq!(existing["created"], false);
+        assert_eq!(existing["id"], created["id"]);
+        assert_eq!(existing["name"], "rust");
+
+        let response = app.oneshot(request("not/valid")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_get_tag_by_slug() {
+        let app = create_test_app().await;
+
+        for (name, slug) in [("C++", "c-plus-plus"), ("Front End", "front-end")] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/tags")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(json!({ "name": name }).to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+            let created = response_json(response).await;
+            assert_eq!(created["slug"], slug);
+
+            // The slug from the response finds the tag again without escaping
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri(format!("/tags/by-slug/{}", slug))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{}", slug);
+            let found = response_json(response).await;
+            assert_eq!(found["id"], created["id"]);
+            assert_eq!(found["name"], name);
+        }
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/tags/by-slug/missing")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_renamed_tag_slug_redirects() {
+        let app = create_test_app().await;
+        let send = |method: Method, uri: String, body: Option<Value>| {
+            let request = Request::builder().method(method).uri(uri);
+            let request = match body {
+                Some(body) => request
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string())),
+                None => request.body(Body::empty()),
+            };
+            app.clone().oneshot(request.unwrap())
+        };
+
+        let response = send(
+            Method::POST,
+            "/tags".into(),
+            Some(json!({ "name": "Front End" })),
+        )
+        .await
+        .unwrap();
+        let tag = response_json(response).await;
+        let response = send(
+            Method::PATCH,
+            format!("/tags/{}", tag["id"]),
+            Some(json!({ "name": "Frontend" })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Links to the old slug lead to the tag's new one
+        let response = send(Method::GET, "/tags/by-slug/front-end".into(), None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "/tags/by-slug/frontend"
+        );
+        assert_eq!(
+            response_json(response).await,
+            json!({ "redirect_to": "frontend" })
+        );
+        let response = send(Method::GET, "/tags/by-slug/frontend".into(), None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response_json(response).await["id"], tag["id"]);
+    }
+
+    #[tokio::test]
+    async fn test_suggest_tags() {
+        let app = create_test_app().await;
+
+        for name in ["rust", "react", "go"] {
+            app.clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/tags")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(json!({ "name": name }).to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/tags/suggest?prefix=r&limit=10")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let tags = response_json(response).await;
+        let names: Vec<_> = tags
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tag| tag["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["react", "rust"]);
+
+        // Empty and overlong prefixes are rejected
+        let long_prefix = format!("/tags/suggest?prefix={}", "a".repeat(51));
+        for uri in ["/tags/suggest?prefix=", long_prefix.as_str()] {
+            let response = app
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_post_tag_relationships() {
+        let app = create_test_app().await;
+
+        // Create post and tag
+        let post = response_json(
+            app.clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/posts")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(
+                            serde_json::to_string(&json!({
+                                "category": "blog",
+                                "title": "Test Post",
+                                "slug": "test-post",
+                                "content": "Test content",
+                                "description": "Test description",
+                                "published": true
+                            }))
+                            .unwrap(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap(),
+        )
+        .await;
+        let post_id = post["id"].as_i64().unwrap();
+
+        let tag = response_json(
+            app.clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/tags")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(
+                            serde_json::to_string(&json!({
+                                "name": "test-tag"
+                            }))
+                            .unwrap(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap(),
+        )
+        .await;
+        let tag_id = tag["id"].as_i64().unwrap();
+
+        // Add tag to post
+        let add_response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri(format!("/posts/{}/tags/{}", post_id, tag_id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(add_response.status(), StatusCode::NO_CONTENT);
+
+        // Get post tags
+        let tags_response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri(format!("/posts/{}/tags", post_id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(tags_response.status(), StatusCode::OK);
+        let tags = response_json(tags_response).await;
+        assert_eq!(tags.as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_post_tags_of_untagged_and_missing_posts() {
+        let app = create_test_app().await;
+
+        let post = response_json(
+            app.clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/posts")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(
+                            json!({
+                                "category": "blog",
+                                "title": "Untagged",
+                                "slug": "untagged-post",
+                                "content": "Test content",
+                                "description": "Test description",
+                                "published": true
+                            })
+                            .to_string(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap(),
+        )
+        .await;
+
+        // An existing post without tags returns an empty list
+        let empty_response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/posts/{}/tags", post["id"]))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(empty_response.status(), StatusCode::OK);
+        assert_eq!(response_json(empty_response).await, json!([]));
+
+        // A missing post is a 404 rather than an empty list
+        let missing_response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/posts/99999/tags")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(missing_response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response_json(missing_response).await["code"], "not_found");
+    }
+
+    #[tokio::test]
+    async fn test_user_administration() {
+        let app = create_test_app().await;
+
+        let create = |body: serde_json::Value| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/users")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let user = json!({ "email": "writer@example.com", "password": "correct horse" });
+
+        let response = app.clone().oneshot(create(user.clone())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let location = response.headers()[header::LOCATION].clone();
+        let created = response_json(response).await;
+        assert_eq!(created["role"], "user");
+        assert!(created.get("password_hash").is_none());
+        assert!(created.get("password").is_none());
+
+        let response = app.clone().oneshot(create(user)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(response_json(response).await["field"], "email");
+
+        let response = app
+            .clone()
+            .oneshot(create(
+                json!({ "email": "not-an-email", "password": "correct horse" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::PATCH)
+                    .uri(location.to_str().unwrap())
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(json!({ "role": "admin" }).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let updated = response_json(response).await;
+        assert_eq!(updated["role"], "admin");
+        assert_eq!(updated["email"], "writer@example.com");
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::DELETE)
+                    .uri(location.to_str().unwrap())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(location.to_str().unwrap())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_login_and_me() {
+        let app = create_test_app_with(Config::default_for_tests(), None).await;
+
+        let json_request = |method: Method, uri: &str, body: serde_json::Value| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(
+                    header::AUTHORIZATION,
+                    format!("Bearer {}", test_token(UserRole::Admin)),
+                )
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(json_request(
+                Method::POST,
+                "/users",
+                json!({ "email": "writer@example.com", "password": "correct horse" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        // A wrong password and an unknown email are indistinguishable
+        let mut failures = Vec::new();
+        for (email, password) in [
+            ("writer@example.com", "wrong password"),
+            ("nobody@example.com", "correct horse"),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(json_request(
+                    Method::POST,
+                    "/auth/login",
+                    json!({ "email": email, "password": password }),
+                ))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            failures.push(response_json(response).await);
+        }
+        assert_eq!(failures[0], failures[1]);
+        assert_eq!(failures[0]["code"], "unauthorized");
+
+        let response = app
+            .clone()
+            .oneshot(json_request(
+                Method::POST,
+                "/auth/login",
+                json!({ "email": "Writer@Example.com", "password": "correct horse" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let login = response_json(response).await;
+        assert_eq!(login["token_type"], "Bearer");
+        assert_eq!(login["expires_in"], 3600);
+        let token = login["access_token"].as_str().unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/auth/me")
+                    .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let me = response_json(response).await;
+        assert_eq!(me["email"], "writer@example.com");
+        assert!(me.get("password_hash").is_none());
+
+        // No header, a garbage token, and a token without the Bearer scheme
+        for authorization in [None, Some("Bearer not-a-token"), Some(token)] {
+            let mut request = Request::builder().uri("/auth/me");
+            if let Some(value) = authorization {
+                request = request.header(header::AUTHORIZATION, value);
+            }
+            let response = app
+                .clone()
+                .oneshot(request.body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(response.headers()[header::WWW_AUTHENTICATE], "Bearer");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_protected_routes() {
+        let app = create_test_app_with(Config::default_for_tests(), None).await;
+
+        let request = |method: Method, uri: &str, token: Option<&str>| {
+            let mut builder = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json");
+            if let Some(token) = token {
+                builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+            }
+            builder.body(Body::from("{}")).unwrap()
+        };
+
+        let protected = [
+            (Method::POST, "/posts"),
+            (Method::PUT, "/posts"),
+            (Method::PATCH, "/posts"),
+            (Method::DELETE, "/posts/1"),
+            (Method::POST, "/tags"),
+            (Method::POST, "/tags/get-or-create"),
+            (Method::PUT, "/tags/1"),
+            (Method::PATCH, "/tags/1"),
+            (Method::DELETE, "/tags/1"),
+            (Method::DELETE, "/tags/unused"),
+            (Method::PUT, "/posts/1/tags"),
+            (Method::PUT, "/posts/1/tags/1"),
+            (Method::DELETE, "/posts/1/tags/1"),
+            (Method::GET, "/users"),
+            (Method::POST, "/users"),
+            (Method::GET, "/users/1"),
+            (Method::PATCH, "/users/1"),
+            (Method::DELETE, "/users/1"),
+            (Method::GET, "/api-keys"),
+            (Method::POST, "/api-keys"),
+            (Method::DELETE, "/api-keys/1"),
+            (Method::GET, "/audit"),
+            (Method::GET, "/admin/posts"),
+            (Method::GET, "/admin/activity"),
+            (Method::GET, "/admin/broken-links"),
+            (Method::POST, "/admin/render-cache/rebuild"),
+        ];
+
+        let admin = test_token(UserRole::Admin);
+        for (method, uri) in &protected {
+            let response = app
+                .clone()
+                .oneshot(request(method.clone(), uri, None))
+                .await
+                .unwrap();
+            assert_eq!(
+                response.status(),
+                StatusCode::UNAUTHORIZED,
+                "{} {} without credentials",
+                method,
+                uri
+            );
+            assert_eq!(response.headers()[header::WWW_AUTHENTICATE], "Bearer");
+            assert_eq!(response_json(response).await["code"], "unauthorized");
+
+            let response = app
+                .clone()
+                .oneshot(request(method.clone(), uri, Some(&admin)))
+                .await
+                .unwrap();
+            assert!(
+                response.status() != StatusCode::UNAUTHORIZED
+                    && response.status() != StatusCode::FORBIDDEN,
+                "{} {} with an admin token returned {}",
+                method,
+                uri,
+                response.status()
+            );
+        }
+
+        // Read endpoints stay public
+        for uri in ["/posts", "/tags", "/tags/popular", "/posts/untagged"] {
+            let response = app
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "GET {}", uri);
+        }
+
+        // A regular user may write content but not administer users
+        let user = test_token(UserRole::User);
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/users", Some(&user)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(response_json(response).await["code"], "forbidden");
+
+        // The static API key is accepted as a bearer credential
+        let config = Config {
+            api_key: Some("ci-key".to_string()),
+            ..Config::default_for_tests()
+        };
+        let app = create_test_app_with(config, None).await;
+        let response = app
+            .clone()
+            .oneshot(request(Method::DELETE, "/tags/unused", Some("ci-key")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .oneshot(request(Method::DELETE, "/tags/unused", Some("wrong-key")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // AUTH_DISABLED lets everything through
+        let config = Config {
+            auth_disabled: true,
+            ..Config::default_for_tests()
+        };
+        let app = create_test_app_with(config, None).await;
+        let response = app
+            .oneshot(request(Method::DELETE, "/tags/unused", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_post_ownership() {
+        let app = create_test_app().await;
+
+        let request = |method: Method, uri: &str, token: &str, body: Value| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let mut tokens = Vec::new();
+        for email in ["alice@example.com", "bob@example.com"] {
+            let response = app
+                .clone()
+                .oneshot(request(
+                    Method::POST,
+                    "/users",
+                    TEST_API_KEY,
+                    json!({ "email": email, "password": "correct horse" }),
+                ))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+            let id = response_json(response).await["id"].as_i64().unwrap();
+            tokens.push((id, test_token_for(id, UserRole::User)));
+        }
+        let (alice_id, alice) = &tokens[0];
+        let (bob_id, bob) = &tokens[1];
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                Method::POST,
+                "/posts",
+                alice,
+                json!({
+                    "category": "blog",
+                    "title": "Alice's Post",
+                    "slug": "alices-post",
+                    "content": "Content",
+                    "description": "Description",
+                    "published": true
+                }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let post = response_json(response).await;
+        assert_eq!(post["author_id"], *alice_id);
+        assert_eq!(post["created_by"], *alice_id);
+        assert_eq!(post["updated_by"], *alice_id);
+        let post_id = post["id"].as_i64().unwrap();
+        let patch = json!({ "id": post_id, "title": "Edited" });
+
+        // Someone else's post is forbidden, a missing one is not found
+        for (method, uri, body) in [
+            (Method::PATCH, "/posts".to_string(), patch.clone()),
+            (
+                Method::PUT,
+                format!("/posts/{}/tags", post_id),
+                json!({ "tag_ids": [] }),
+            ),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(request(method, &uri, bob, body))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+            assert_eq!(response_json(response).await["code"], "not_author");
+        }
+        let response = app
+            .clone()
+            .oneshot(request(
+                Method::PATCH,
+                "/posts",
+                bob,
+                json!({ "id": 9999, "title": "Edited" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // The author may edit their post but not delete it
+        let response = app
+            .clone()
+            .oneshot(request(Method::PATCH, "/posts", alice, patch.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let uri = format!("/posts/{}", post_id);
+        let response = app
+            .clone()
+            .oneshot(request(Method::DELETE, &uri, alice, Value::Null))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(response_json(response).await["code"], "forbidden");
+
+        // Admins may do both; the edit is recorded as theirs
+        let admin = test_token_for(*bob_id, UserRole::Admin);
+        let response = app
+            .clone()
+            .oneshot(request(
+                Method::PATCH,
+                "/posts",
+                &admin,
+                json!({ "id": post_id, "title": "Edited by an admin" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let post = response_json(response).await;
+        assert_eq!(post["created_by"], *alice_id);
+        assert_eq!(post["updated_by"], *bob_id);
+
+        // Public reads leave the editors out
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/posts/by-id/{}", post_id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let post = response_json(response).await;
+        assert_eq!(post["title"], "Edited by an admin");
+        assert!(post.get("created_by").is_none());
+        assert!(post.get("updated_by").is_none());
+        let response = app
+            .clone()
+            .oneshot(request(Method::DELETE, &uri, &admin, Value::Null))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn test_api_keys() {
+        let app = create_test_app().await;
+
+        let request = |method: Method, uri: &str, token: &str, body: Value| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                Method::POST,
+                "/api-keys",
+                TEST_API_KEY,
+                json!({ "name": "CI publishing" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let created = response_json(response).await;
+        let id = created["id"].as_i64().unwrap();
+        let key = created["key"].as_str().unwrap().to_string();
+        assert!(key.starts_with("bk_"));
+
+        // The plaintext is never shown again
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/api-keys", TEST_API_KEY, Value::Null))
+            .await
+            .unwrap();
+        let keys = response_json(response).await;
+        assert_eq!(keys[0]["name"], "CI publishing");
+        assert!(keys[0].get("key").is_none());
+        assert!(keys[0].get("key_hash").is_none());
+        assert!(keys[0]["revoked_at"].is_null());
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                Method::POST,
+                "/tags",
+                &key,
+                json!({ "name": "from-ci" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        // Only admins manage keys
+        let response = app
+            .clone()
+            .oneshot(request(
+                Method::GET,
+                "/api-keys",
+                &test_token(UserRole::User),
+                Value::Null,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Revoked and unknown keys are rejected
+        let uri = format!("/api-keys/{}", id);
+        let response = app
+            .clone()
+            .oneshot(request(Method::DELETE, &uri, TEST_API_KEY, Value::Null))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        for token in [key.as_str(), "bk_unknown"] {
+            let response = app
+                .clone()
+                .oneshot(request(
+                    Method::POST,
+                    "/tags",
+                    token,
+                    json!({ "name": "rejected" }),
+                ))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/api-keys", TEST_API_KEY, Value::Null))
+            .await
+            .unwrap();
+        assert!(response_json(response).await[0]["revoked_at"].is_string());
+
+        let response = app
+            .oneshot(request(
+                Method::DELETE,
+                "/api-keys/999",
+                TEST_API_KEY,
+                Value::Null,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_audit_log() {
+        let app = create_test_app().await;
+
+        let request = |method: Method, uri: &str, body: Value| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                Method::POST,
+                "/posts",
+                json!({
+                    "category": "blog",
+                    "title": "Audited",
+                    "slug": "audited",
+                    "content": "Content",
+                    "description": "Description",
+                    "published": false
+                }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let post_id = response_json(response).await["id"].as_i64().unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                Method::PATCH,
+                "/posts",
+                json!({ "id": post_id, "title": "Audited again" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::POST, "/tags", json!({ "name": "audit" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let uri = format!("/audit?entity=post&entity_id={}&limit=50", post_id);
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, &uri, Value::Null))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-total-count"], "2");
+        let entries = response_json(response).await;
+        assert_eq!(entries[0]["action"], "patch");
+        assert_eq!(entries[0]["entity_type"], "post");
+        assert_eq!(entries[0]["actor"], "api_key");
+        assert_eq!(
+            entries[0]["changes"],
+            json!({ "title": { "old": "Audited", "new": "Audited again" } })
+        );
+        assert_eq!(entries[1]["action"], "create");
+        assert_eq!(entries[1]["changes"]["slug"], "audited");
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/audit", Value::Null))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["x-total-count"], "3");
+
+        for uri in ["/audit?entity_id=1", "/audit?limit=0"] {
+            let response = app
+                .clone()
+                .oneshot(request(Method::GET, uri, Value::Null))
+                .await
+                .unwrap();
+            assert_eq!(
+                response.status(),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "{}",
+                uri
+            );
+        }
+        let response = app
+            .oneshot(request(Method::GET, "/audit?entity=user", Value::Null))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_activity_feed() {
+        let app = create_test_app().await;
+
+        let request = |method: Method, uri: &str, body: Value| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                Method::POST,
+                "/posts",
+                json!({
+                    "category": "blog",
+                    "title": "Fresh",
+                    "slug": "fresh",
+                    "content": "Content",
+                    "description": "Description",
+                    "published": true
+                }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let response = app
+            .clone()
+            .oneshot(request(Method::POST, "/tags", json!({ "name": "news" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/admin/activity", Value::Null))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let activity = response_json(response).await;
+        assert_eq!(activity.as_array().unwrap().len(), 2);
+        assert_eq!(activity[0]["entity_type"], "tag");
+        assert_eq!(activity[0]["title"], "news");
+        assert_eq!(activity[0]["action"], "created");
+        assert_eq!(activity[1]["entity_type"], "post");
+        assert_eq!(activity[1]["title"], "Fresh");
+
+        // The oldest entry's timestamp pages further back, inclusively
+        let uri = format!(
+            "/admin/activity?limit=1&before={}",
+            activity[1]["created_at"].as_str().unwrap()
+        );
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, &uri, Value::Null))
+            .await
+            .unwrap();
+        let page = response_json(response).await;
+        assert_eq!(page.as_array().unwrap().len(), 1);
+
+        let response = app
+            .oneshot(request(
+                Method::GET,
+                "/admin/activity?before=yesterday",
+                Value::Null,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_keys() {
+        let app = create_test_app().await;
+
+        let request = |uri: &str, key: Option<&str>, body: Value| {
+            let mut builder = Request::builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json");
+            if let Some(key) = key {
+                builder = builder.header("Idempotency-Key", key);
+            }
+            builder.body(Body::from(body.to_string())).unwrap()
+        };
+        let new_post = |slug: &str| {
+            json!({
+                "category": "blog",
+                "title": "Retried",
+                "slug": slug,
+                "content": "Content",
+                "description": "Description",
+                "published": false
+            })
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request("/posts", Some("post-1"), new_post("retried")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert!(response.headers().get("idempotent-replayed").is_none());
+        let location = response.headers()[header::LOCATION].clone();
+        let created = response_json(response).await;
+
+        // A retry gets the original response instead of a duplicate slug error
+        let response = app
+            .clone()
+            .oneshot(request("/posts", Some("post-1"), new_post("retried")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()["idempotent-replayed"], "true");
+        assert_eq!(response.headers()[header::LOCATION], location);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(response_json(response).await, created);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/posts/by-slug/retried")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response_json(response).await["id"], created["id"]);
+
+        // Reusing the key for a different request is an error
+        let response = app
+            .clone()
+            .oneshot(request("/posts", Some("post-1"), new_post("other")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let error = response_json(response).await;
+        assert_eq!(error["code"], "validation_error");
+        assert_eq!(error["field"], "Idempotency-Key");
+
+        // Failures aren't stored, so the key can be retried once fixed
+        let response = app
+            .clone()
+            .oneshot(request("/tags", Some("tag-1"), json!({ "name": "" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        for replayed in [None, Some("true")] {
+            let response = app
+                .clone()
+                .oneshot(request("/tags", Some("tag-1"), json!({ "name": "retry" })))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+            assert_eq!(
+                response
+                    .headers()
+                    .get("idempotent-replayed")
+                    .map(|v| v.to_str().unwrap()),
+                replayed
+            );
+        }
+
+        // The request's path is part of its fingerprint, so a key can't be
+        // reused on another route either
+        let response = app
+            .clone()
+            .oneshot(request("/tags", Some("post-1"), json!({ "name": "other" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let response = app
+            .clone()
+            .oneshot(request("/tags", Some("not valid"), json!({ "name": "x" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_in_flight() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        let config = Config {
+            api_key: Some(TEST_API_KEY.to_string()),
+            ..Config::default_for_tests()
+        };
+        let app = app(db.clone(), &config);
+        let request = || {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/tags")
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::AUTHORIZATION, format!("Bearer {}", TEST_API_KEY))
+                .header("Idempotency-Key", "tag-1")
+                .body(Body::from(json!({ "name": "retry" }).to_string()))
+                .unwrap()
+        };
+
+        // Another request holds the key, so this one must not run
+        let scope = crate::auth::AuthUser::from(&crate::auth::Principal::ApiKey)
+            .actor
+            .to_string();
+        assert!(db
+            .idempotency()
+            .reserve(&scope, "tag-1", "hash", config.request_timeout)
+            .await
+            .unwrap());
+        let response = app.clone().oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(response_json(response).await["code"], "conflict");
+        assert!(db.tags().find_by_name("retry").await.is_err());
+
+        // Once the key is free, the request runs
+        db.idempotency().release(&scope, "tag-1").await.unwrap();
+        let response = app.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiting() {
+        let config = Config {
+            api_key: Some(TEST_API_KEY.to_string()),
+            rate_limit: RateLimitConfig {
+                reads_per_minute: 5,
+                writes_per_minute: 2,
+                comments_per_hour: 1,
+                contact_per_hour: 1,
+                trust_proxy: true,
+            },
+            ..Config::default_for_tests()
+        };
+        let app = create_test_app_with(config, Some(TEST_API_KEY.to_string())).await;
+
+        let request = |method: Method, client: &str| {
+            Request::builder()
+                .method(method)
+                .uri("/tags")
+                .header("X-Forwarded-For", client)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(json!({ "name": "limited" }).to_string()))
+                .unwrap()
+        };
+
+        for _ in 0..5 {
+            let response = app
+                .clone()
+                .oneshot(request(Method::GET, "203.0.113.1"))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "203.0.113.1"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = response.headers()[header::RETRY_AFTER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((1..=12).contains(&retry_after));
+        let error = response_json(response).await;
+        assert_eq!(error["code"], "rate_limited");
+        assert!(error["message"].is_string());
+
+        // Writes are limited separately, and rejected even when they'd fail
+        let statuses = [StatusCode::CREATED, StatusCode::CONFLICT];
+        for status in statuses {
+            let response = app
+                .clone()
+                .oneshot(request(Method::POST, "203.0.113.1"))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status);
+        }
+        let response = app
+            .clone()
+            .oneshot(request(Method::POST, "203.0.113.1"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // Other clients have limits of their own
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "203.0.113.2"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_request_id() {
+        let app = create_test_app().await;
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/posts")
+                    .header("X-Request-Id", "client-chosen-id")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["x-request-id"], "client-chosen-id");
+
+        // Without one, or with an unusable one, the server makes one up
+        for header in [None, Some("has spaces")] {
+            let mut request = Request::builder().uri("/no-such-route");
+            if let Some(header) = header {
+                request = request.header("X-Request-Id", header);
+            }
+            let response = app
+                .clone()
+                .oneshot(request.body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+            let id = response.headers()["x-request-id"].to_str().unwrap();
+            assert!(uuid::Uuid::parse_str(id).is_ok(), "{} is not a UUID", id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_body_size_limit() {
+        let config = Config {
+            api_key: Some(TEST_API_KEY.to_string()),
+            max_body_bytes: 1024,
+            ..Config::default_for_tests()
+        };
+        let app = create_test_app_with(config, Some(TEST_API_KEY.to_string())).await;
+
+        let body = |content: &str| {
+            json!({
+                "category": "blog",
+                "title": "Big",
+                "slug": "big",
+                "content": content,
+                "description": "Description",
+                "published": false
+            })
+            .to_string()
+        };
+
+        // Rejected up front when the length is declared, and while reading
+        // the body when it isn't
+        let oversized = body(&"x".repeat(2000));
+        for declare_length in [true, false] {
+            let mut request = Request::builder()
+                .method(Method::POST)
+                .uri("/posts")
+                .header(header::CONTENT_TYPE, "application/json");
+            if declare_length {
+                request = request.header(header::CONTENT_LENGTH, oversized.len());
+            }
+            let response = app
+                .clone()
+                .oneshot(request.body(Body::from(oversized.clone())).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+            assert_eq!(response_json(response).await["code"], "payload_too_large");
+        }
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/posts")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body("Small enough")))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        let config = Config {
+            request_timeout: std::time::Duration::from_millis(50),
+            ..Config::default_for_tests()
+        };
+        let slow = Router::new().route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                "done"
+            }),
+        );
+        let app = with_request_limits(slow, &config);
+
+        let response = app
+            .oneshot(Request::builder().uri("/slow").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        let error = response_json(response).await;
+        assert_eq!(error["code"], "timeout");
+        assert!(error["message"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_response_compression() {
+        use std::io::Read;
+
+        let app = create_test_app().await;
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/posts")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        json!({
+                            "category": "blog",
+                            "title": "Long Post",
+                            "slug": "long-post",
+                            "content": "All work and no play. ".repeat(200),
+                            "description": "Description",
+                            "published": true
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let get_posts = |encoding: &'static str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .uri("/posts")
+                    .header(header::ACCEPT_ENCODING, encoding)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let plain = get_posts("identity").await.unwrap();
+        assert!(plain.headers().get(header::CONTENT_ENCODING).is_none());
+        assert_eq!(plain.headers()[header::VARY], "accept-encoding");
+        let expected = response_json(plain).await;
+
+        let response = get_posts("gzip").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(response.headers()[header::VARY], "accept-encoding");
+
+        let compressed = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(&compressed[..])
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert!(compressed.len() < decompressed.len());
+        assert_eq!(
+            serde_json::from_slice::<Value>(&decompressed).unwrap(),
+            expected
+        );
+
+        // Small responses aren't worth compressing
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/posts/1/tags")
+                    .header(header::ACCEPT_ENCODING, "gzip")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_panic_returns_json() {
+        let app = create_test_app().await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/test/panic")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let request_id = response.headers()[&REQUEST_ID_HEADER].clone();
+        let error = response_json(response).await;
+        assert_eq!(error["code"], "internal_error");
+        assert_eq!(error["message"], "Internal server error");
+        assert_eq!(error["request_id"], request_id.to_str().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_static_files() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("static");
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(dir.join("hello.txt"), "Hello, static").unwrap();
+        std::fs::write(root.path().join("secret.txt"), "Outside").unwrap();
+
+        let config = Config {
+            static_dir: Some(dir),
+            ..Config::default_for_tests()
+        };
+        let app = create_test_app_with(config, None).await;
+        let get = |uri: &'static str| {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+
+        let response = get("/static/hello.txt").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain"));
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "public, max-age=86400"
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"Hello, static");
+
+        let response = get("/static/missing.png").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+        let error = response_json(response).await;
+        assert_eq!(error["code"], "not_found");
+        assert_eq!(error["message"], "file not found");
+
+        // Paths can't climb out of the directory
+        for uri in ["/static/../secret.txt", "/static/%2e%2e/secret.txt"] {
+            let response = get(uri).await.unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND, "{}", uri);
+        }
+
+        // Without a directory, nothing is served
+        let app = create_test_app_with(Config::default_for_tests(), None).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/static/hello.txt")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response_json(response).await["message"], "route not found");
+    }
+
+    #[tokio::test]
+    async fn test_image_uploads() {
+        use crate::storage::TINY_PNG;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            api_key: Some(TEST_API_KEY.to_string()),
+            static_dir: Some(dir.path().to_path_buf()),
+            max_upload_bytes: 1024,
+            ..Config::default_for_tests()
+        };
+        let app = create_test_app_with(config.clone(), Some(TEST_API_KEY.to_string())).await;
+
+        let upload = |file_name: &str, content_type: &str, data: &[u8]| {
+            let mut body = format!(
+                "--BOUNDARY\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+                file_name, content_type
+            )
+            .into_bytes();
+            body.extend_from_slice(data);
+            body.extend_from_slice(b"\r\n--BOUNDARY--\r\n");
+            Request::builder()
+                .method(Method::POST)
+                .uri("/uploads")
+                .header(
+                    header::CONTENT_TYPE,
+                    "multipart/form-data; boundary=BOUNDARY",
+                )
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(upload("photo.jpg", "image/jpeg", TINY_PNG))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let url = response_json(response).await["url"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            url,
+            format!("/static/{}.png", crate::models::sha256_hex(TINY_PNG))
+        );
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(&url).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], TINY_PNG);
+
+        // The same image again is not stored twice
+        let response = app
+            .clone()
+            .oneshot(upload("copy.png", "image/png", TINY_PNG))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response_json(response).await["url"], url.as_str());
+
+        // A name and type claiming an image don't make one
+        let response = app
+            .clone()
+            .oneshot(upload(
+                "evil.png",
+                "image/png",
+                b"<script>alert(1)</script>",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(
+            response_json(response).await["code"],
+            "unsupported_media_type"
+        );
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let mut big = TINY_PNG.to_vec();
+        big.resize(2048, 0);
+        let response = app
+            .clone()
+            .oneshot(upload("big.png", "image/png", &big))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(response_json(response).await["code"], "payload_too_large");
+
+        // Uploading needs credentials
+        let app = create_test_app_with(config, None).await;
+        let response = app
+            .oneshot(upload("photo.png", "image/png", TINY_PNG))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_cache_control() {
+        let config = Config {
+            api_key: Some(TEST_API_KEY.to_string()),
+            cache_control: CacheControlConfig {
+                routes: vec![(
+                    "/tags/popular".to_string(),
+                    HeaderValue::from_static("public, max-age=600"),
+                )],
+                ..CacheControlConfig::default()
+            },
+            ..Config::default_for_tests()
+        };
+        let app = create_test_app_with(config, None).await;
+        let send = |method: Method, uri: &'static str, token: Option<&'static str>| {
+            let mut request = Request::builder().method(method).uri(uri);
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let response = send(Method::GET, "/posts", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "public, max-age=60, stale-while-revalidate=300"
+        );
+
+        let response = send(Method::GET, "/tags/popular", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "public, max-age=600"
+        );
+
+        // Writes, errors and reads made with credentials are never cached
+        let response = send(Method::POST, "/tags", Some(TEST_API_KEY))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+        let response = send(Method::GET, "/posts/by-id/99", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+        let response = send(Method::GET, "/posts", Some(TEST_API_KEY))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+        let response = send(Method::GET, "/users", Some(TEST_API_KEY))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+    }
+
+    #[tokio::test]
+    async fn test_feeds() {
+        let app = create_test_app().await;
+        for (slug, published, tags) in [
+            ("first", true, json!(["rust"])),
+            ("second", true, json!([])),
+            ("draft", false, json!(["rust"])),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/posts")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(
+                            json!({
+                                "category": "blog",
+                                "title": format!("Post {}", slug),
+                                "slug": slug,
+                                "content": "<p>Fish & chips</p>",
+                                "description": "Test description",
+                                "published": published,
+                                "tags": tags
+                            })
+                            .to_string(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+        let get = |uri: &'static str| {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+        let body = |response: Response| async move {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            String::from_utf8(bytes.to_vec()).unwrap()
+        };
+
+        let response = get("/feed.json").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/feed+json"
+        );
+        let feed = response_json(response).await;
+        assert_eq!(feed["feed_url"], "http://localhost:8080/feed.json");
+        let mut urls: Vec<&str> = feed["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["url"].as_str().unwrap())
+            .collect();
+        // Both posts were created within the same second
+        urls.sort();
+        assert_eq!(
+            urls,
+            [
+                "http://localhost:8080/posts/first",
+                "http://localhost:8080/posts/second"
+            ]
+        );
+
+        // The XML feeds carry the same posts, drafts left out
+        for (uri, content_type) in [
+            ("/feed.xml", "application/rss+xml; charset=utf-8"),
+            ("/atom.xml", "application/atom+xml; charset=utf-8"),
+        ] {
+            let response = get(uri).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[header::CONTENT_TYPE], content_type);
+            let xml = body(response).await;
+            assert!(xml.contains("http://localhost:8080/posts/first"), "{}", uri);
+            assert!(
+                xml.contains("http://localhost:8080/posts/second"),
+                "{}",
+                uri
+            );
+            assert!(!xml.contains("/posts/draft"), "{}", uri);
+            assert!(
+                xml.contains("&lt;p&gt;Fish &amp; chips&lt;/p&gt;"),
+                "{}",
+                uri
+            );
+        }
+
+        // A tag's feeds only carry its posts
+        let response = get("/tags/rust/atom.xml").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let xml = body(response).await;
+        assert!(xml.contains("<title>Blog: rust</title>"));
+        assert!(xml.contains("/posts/first"));
+        assert!(!xml.contains("/posts/second"));
+        let feed = response_json(get("/tags/rust/feed.json").await.unwrap()).await;
+        assert_eq!(feed["items"].as_array().unwrap().len(), 1);
+        let response = get("/tags/rust/feed.xml").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = get("/tags/missing/feed.xml").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_category_feeds() {
+        let app = create_test_app().await;
+        for (slug, category, published) in [
+            ("sketch", "art", true),
+            ("essay", "blog", true),
+            ("unfinished", "art", false),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/posts")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(
+                            json!({
+                                "category": category,
+                                "title": format!("Post {}", slug),
+                                "slug": slug,
+                                "content": "Test content",
+                                "description": "Test description",
+                                "published": published
+                            })
+                            .to_string(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+        let get = |uri: &'static str| {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+        // Links of the feed's items, in order
+        let item_links = |response: Response| async move {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let xml = String::from_utf8(bytes.to_vec()).unwrap();
+            let channel_title = xml
+                .split("<title>")
+                .nth(1)
+                .and_then(|rest| rest.split("</title>").next())
+                .unwrap()
+                .to_string();
+            let links: Vec<String> = xml
+                .split("<item>")
+                .skip(1)
+                .map(|item| {
+                    let link = item.split("<link>").nth(1).unwrap();
+                    link.split("</link>").next().unwrap().to_string()
+                })
+                .collect();
+            (channel_title, links)
+        };
+
+        let response = get("/categories/art/feed.xml").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/rss+xml; charset=utf-8"
+        );
+        let (title, links) = item_links(response).await;
+        assert_eq!(title, "Blog: Art");
+        assert_eq!(links, ["http://localhost:8080/posts/sketch"]);
+
+        let (_, links) = item_links(get("/categories/blog/feed.xml").await.unwrap()).await;
+        assert_eq!(links, ["http://localhost:8080/posts/essay"]);
+
+        // Every format carries the same posts
+        let feed = response_json(get("/categories/art/feed.json").await.unwrap()).await;
+        assert_eq!(feed["title"], "Blog: Art");
+        assert_eq!(feed["items"].as_array().unwrap().len(), 1);
+        let response = get("/categories/art/atom.xml").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // A category without published posts still has a valid feed
+        let response = get("/categories/reading/feed.xml").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let (title, links) = item_links(response).await;
+        assert_eq!(title, "Blog: Reading");
+        assert!(links.is_empty());
+
+        let response = get("/categories/music/feed.xml").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response_json(response).await["code"], "not_found");
+    }
+
+    #[tokio::test]
+    async fn test_get_post_by_id_or_slug() {
+        let app = create_test_app().await;
+        let mut ids = Vec::new();
+        for slug in ["hello-world", "2024", "changes"] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/posts")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(
+                            json!({
+                                "category": "blog",
+                                "title": format!("Post {}", slug),
+                                "slug": slug,
+                                "content": "Test content",
+                                "description": "Test description",
+                                "published": true
+                            })
+                            .to_string(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+            ids.push(response_json(response).await["id"].clone());
+        }
+        let get = |uri: String| {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+
+        let response = get(format!("/posts/{}", ids[0])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response_json(response).await["slug"], "hello-world");
+
+        let response = get("/posts/hello-world".to_string()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response_json(response).await["id"], ids[0]);
+
+        // All digits is an ID, so numeric slugs go through `/posts/by-slug`
+        let response = get("/posts/2024".to_string()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = get("/posts/by-slug/2024".to_string()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response_json(response).await["id"], ids[1]);
+
+        // As do slugs shadowed by fixed routes
+        let response = get("/posts/changes?since=1970-01-01T00:00:00Z".to_string())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response_json(response).await["posts"].is_array());
+        let response = get("/posts/by-slug/changes".to_string()).await.unwrap();
+        assert_eq!(response_json(response).await["id"], ids[2]);
+
+        // Unknown slugs get suggestions, as on `/posts/by-slug`
+        let response = get("/posts/hello-wrold".to_string()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            response_json(response).await["did_you_mean"],
+            json!(["hello-world"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_robots_txt() {
+        for (robots_disallow, expected) in [
+            (false, "Sitemap: http://localhost:8080/sitemap.xml"),
+            (true, "Disallow: /"),
+        ] {
+            let config = Config {
+                robots_disallow,
+                ..Config::default_for_tests()
+            };
+            let response = create_test_app_with(config, None)
+                .await
+                .oneshot(
+                    Request::builder()
+                        .uri("/robots.txt")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(
+                response.headers()[header::CONTENT_TYPE],
+                "text/plain; charset=utf-8"
+            );
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body = std::str::from_utf8(&body).unwrap();
+            assert!(body.starts_with("User-agent: *\n"));
+            assert!(body.contains(expected), "{}", body);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_types() {
+        let response = create_test_app()
+            .await
+            .oneshot(
+                Request::builder()
+                    .uri("/client/types.ts")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/typescript; charset=utf-8"
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, client_types::client_types());
+    }
+
+    #[cfg(feature = "graphql")]
+    #[tokio::test]
+    async fn test_graphql() {
+        let app = create_test_app_with(
+            Config {
+                api_key: Some(TEST_API_KEY.to_string()),
+                ..Config::default_for_tests()
+            },
+            None,
+        )
+        .await;
+        let graphql = |query: &str, token: Option<&str>| {
+            let mut request = Request::builder()
+                .method(Method::POST)
+                .uri("/graphql")
+                .header(header::CONTENT_TYPE, "application/json");
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+            }
+            app.clone().oneshot(
+                request
+                    .body(Body::from(json!({ "query": query }).to_string()))
+                    .unwrap(),
+            )
+        };
+        let create = r#"mutation {
+            createPost(input: {
+                category: BLOG, title: "Hello", slug: "hello", content: "Content",
+                description: "Description", published: true, tags: ["rust"]
+            }) { id slug }
+        }"#;
+
+        // Mutations need the same credentials as the REST API
+        let response = graphql(create, None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_json(response).await;
+        assert_eq!(body["errors"][0]["extensions"]["code"], "unauthorized");
+        let response = graphql(create, Some("not-a-token")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let body = response_json(graphql(create, Some(TEST_API_KEY)).await.unwrap()).await;
+        assert_eq!(body["data"]["createPost"]["slug"], "hello");
+        let body = response_json(graphql(create, Some(TEST_API_KEY)).await.unwrap()).await;
+        assert_eq!(body["errors"][0]["extensions"]["code"], "duplicate_entry");
+
+        // Queries are public
+        let body = response_json(
+            graphql("{ post(slug: \"hello\") { title tags { name } } }", None)
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(
+            body["data"],
+            json!({ "post": { "title": "Hello", "tags": [{ "name": "rust" }] } })
+        );
+
+        // Drafts only reach callers who authenticate, whatever the filter says
+        let draft = create
+            .replace("\"hello\"", "\"draft\"")
+            .replace("published: true", "published: false");
+        let body = response_json(graphql(&draft, Some(TEST_API_KEY)).await.unwrap()).await;
+        assert_eq!(body["data"]["createPost"]["slug"], "draft");
+        let list = "{ posts(filter: { publishedOnly: false }) { slug tags { posts { slug } } } }";
+        let body = response_json(graphql(list, None).await.unwrap()).await;
+        assert_eq!(
+            body["data"],
+            json!({ "posts": [{ "slug": "hello", "tags": [{ "posts": [{ "slug": "hello" }] }] }] })
+        );
+        let body = response_json(graphql(list, Some(TEST_API_KEY)).await.unwrap()).await;
+        assert_eq!(body["data"]["posts"].as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_post_as_markdown() {
+        let app = create_test_app().await;
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/posts")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        json!({
+                            "category": "blog",
+                            "title": "Markdown post",
+                            "slug": "markdown-post",
+                            "content": "# Heading\n\nSome *text*.",
+                            "description": "Test description",
+                            "published": true
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let get = |accept: &'static str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .uri("/posts/by-slug/markdown-post")
+                    .header(header::ACCEPT, accept)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let response = get("text/markdown").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/markdown; charset=utf-8"
+        );
+        assert_eq!(response.headers()["x-post-title"], "Markdown post");
+        assert!(response.headers().contains_key(header::LAST_MODIFIED));
+        assert_eq!(response.headers()[header::VARY], "accept");
+        let markdown_etag = response.headers()[header::ETAG].clone();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"# Heading\n\nSome *text*.");
+
+        // JSON stays the default, including for clients accepting anything
+        for accept in ["application/json", "*/*", "text/markdown;q=0.5, */*"] {
+            let response = get(accept).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(
+                response.headers()[header::CONTENT_TYPE],
+                "application/json",
+                "{}",
+                accept
+            );
+            assert_ne!(response.headers()[header::ETAG], markdown_etag);
+            assert_eq!(response_json(response).await["slug"], "markdown-post");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_post_as_html() {
+        let app = create_test_app().await;
+        let send = |method: Method, uri: &str, accept: &str, body: Value| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::ACCEPT, accept)
+                .header(header::CONTENT_TYPE, "application/json");
+            let body = match body {
+                Value::Null => Body::empty(),
+                body => Body::from(body.to_string()),
+            };
+            app.clone().oneshot(request.body(body).unwrap())
+        };
+        let html = |response: Response| async move {
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(
+                response.headers()[header::CONTENT_TYPE],
+                "text/html; charset=utf-8"
+            );
+            assert_eq!(
+                response.headers()[header::CONTENT_SECURITY_POLICY],
+                "default-src 'none'; sandbox"
+            );
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            String::from_utf8(bytes.to_vec()).unwrap()
+        };
+        // As a browser asks
+        let browser = "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8";
+
+        let response = send(
+            Method::POST,
+            "/posts",
+            "application/json",
+            json!({
+                "category": "blog",
+                "title": "Rendered post",
+                "slug": "rendered-post",
+                "content": "# Heading\n\nSome *text*.",
+                "description": "Test description",
+                "published": true
+            }),
+        )
+        .await
+        .unwrap();
+        let id = response_json(response).await["id"].clone();
+
+        let response = send(Method::GET, "/posts/rendered-post", browser, Value::Null)
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["x-post-title"], "Rendered post");
+        let etag = response.headers()[header::ETAG].clone();
+        assert_eq!(
+            html(response).await,
+            "<h1>Heading</h1>\n<p>Some <em>text</em>.</p>\n"
+        );
+        let response = send(
+            Method::GET,
+            "/posts/rendered-post",
+            "text/html",
+            Value::Null,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.headers()[header::ETAG], etag);
+        html(response).await;
+
+        // Edited content is rendered again
+        let response = send(
+            Method::PATCH,
+            "/posts",
+            "application/json",
+            json!({ "id": id, "content": "Now **bold**." }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send(
+            Method::GET,
+            "/posts/rendered-post",
+            "text/html",
+            Value::Null,
+        )
+        .await
+        .unwrap();
+        assert_eq!(html(response).await, "<p>Now <strong>bold</strong>.</p>\n");
+
+        let response = send(
+            Method::POST,
+            "/admin/render-cache/rebuild",
+            "application/json",
+            Value::Null,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response_json(response).await,
+            json!({ "rendered": 1, "renderer_version": render::RENDERER_VERSION })
+        );
+
+        let response = send(
+            Method::GET,
+            "/admin/metrics",
+            "application/json",
+            Value::Null,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            response_json(response).await["render_cache"],
+            json!({ "hits": 1, "misses": 2 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_post_as_html_escapes_raw_html() {
+        let app = create_test_app().await;
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/posts")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        json!({
+                            "category": "blog",
+                            "title": "Scripted post",
+                            "slug": "scripted-post",
+                            "content": "Hello <script>alert(document.cookie)</script>",
+                            "description": "Test description",
+                            "published": true
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/posts/scripted-post")
+                    .header(header::ACCEPT, "text/html")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_SECURITY_POLICY],
+            "default-src 'none'; sandbox"
+        );
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let html = String::from_utf8(bytes.to_vec()).unwrap();
+        assert!(!html.contains("<script>"), "{}", html);
+        assert!(html.contains("&lt;script&gt;"), "{}", html);
+    }
+
+    #[tokio::test]
+    async fn test_maintenance() {
+        // Backups need a database file to copy
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            database_url: format!("sqlite://{}?mode=rwc", dir.path().join("blog.db").display()),
+            api_key: Some(TEST_API_KEY.to_string()),
+            backup_dir: Some(dir.path().to_path_buf()),
+            ..Config::default_for_tests()
+        };
+        let app = app(Database::new(&config).await.unwrap(), &config);
+        let admin_post = |uri: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header(header::AUTHORIZATION, format!("Bearer {}", TEST_API_KEY))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(admin_post("/admin/backup"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let backup = response_json(response).await;
+        let path = std::path::Path::new(backup["path"].as_str().unwrap());
+        assert!(path.starts_with(dir.path()));
+        assert_eq!(
+            std::fs::metadata(path).unwrap().len(),
+            backup["size_bytes"].as_u64().unwrap()
+        );
+        let copy = Database::connect(&format!("sqlite://{}", path.display())).await;
+        assert!(copy.is_ok());
+
+        let response = app
+            .clone()
+            .oneshot(admin_post("/admin/vacuum"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let vacuum = response_json(response).await;
+        assert!(vacuum["size_bytes_after"].as_i64().unwrap() > 0);
+
+        let response = app
+            .clone()
+            .oneshot(admin_post("/admin/integrity-check"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response_json(response).await,
+            json!({ "ok": true, "problems": [], "foreign_key_violations": [] })
+        );
+
+        // Checking is for admins only
+        let mut request = admin_post("/admin/integrity-check");
+        request.headers_mut().insert(
+            header::AUTHORIZATION,
+            format!("Bearer {}", test_token(UserRole::User))
+                .parse()
+                .unwrap(),
+        );
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let mut request = admin_post("/admin/integrity-check");
+        request.headers_mut().remove(header::AUTHORIZATION);
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // Without credentials, or without a backup directory
+        let mut request = admin_post("/admin/vacuum");
+        request
+            .headers_mut()
+            .insert(header::AUTHORIZATION, "Bearer wrong".parse().unwrap());
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let app = create_test_app().await;
+        let response = app.oneshot(admin_post("/admin/backup")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_migrations_and_readiness() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        let config = Config {
+            api_key: Some(TEST_API_KEY.to_string()),
+            ..Config::default_for_tests()
+        };
+        let app = app(db.clone(), &config);
+        let get = |uri: &str, token: Option<&str>| {
+            let mut request = Request::builder().uri(uri);
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+            }
+            request.body(Body::empty()).unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(get("/admin/migrations", Some(TEST_API_KEY)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_json(response).await;
+        assert_eq!(
+            body["applied"].as_array().unwrap().len(),
+            sqlx::migrate!("./migrations").iter().count()
+        );
+        assert_eq!(body["pending"], json!([]));
+
+        let response = app
+            .clone()
+            .oneshot(get("/admin/migrations", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app.clone().oneshot(get("/readyz", None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response_json(response).await["status"], "ready");
+
+        // A migration the database doesn't know about makes it not ready
+        sqlx::query("DELETE FROM _sqlx_migrations WHERE version = 20250110134802")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        let response = app.oneshot(get("/readyz", None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            response_json(response).await["pending_migrations"],
+            json!([20250110134802i64])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_posts() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        sqlx::query(
+            r#"
+            WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 1000)
+            INSERT INTO posts (category, title, slug, content, description, published)
+            SELECT 'blog', 'Post ' || i, 'post-' || i, 'Content', 'Description', i % 2
+            FROM n
+            "#,
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        let config = Config {
+            api_key: Some(TEST_API_KEY.to_string()),
+            ..Config::default_for_tests()
+        };
+        let app = app(db, &config);
+
+        let export = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .uri(uri)
+                    .header(header::AUTHORIZATION, format!("Bearer {}", TEST_API_KEY))
+                    .body(Body::empty())
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                assert_eq!(
+                    response.headers()[header::CONTENT_TYPE],
+                    "application/x-ndjson"
+                );
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                String::from_utf8(body.to_vec()).unwrap()
+            }
+        };
+
+        let body = export("/export/posts.ndjson?include_drafts=true").await;
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 1000);
+        assert!(body.ends_with('\n'));
+        let first: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["slug"], "post-1");
+        let last: Value = serde_json::from_str(lines[999]).unwrap();
+        assert_eq!(last["slug"], "post-1000");
+        assert_eq!(last["published"], false);
+
+        let body = export("/export/posts.ndjson").await;
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 500);
+        let last: Value = serde_json::from_str(lines[499]).unwrap();
+        assert_eq!(last["slug"], "post-999");
+    }
+
+    /// A token for a comment form on the post, served long enough ago to
+    /// have been filled in by a person
+    fn aged_form_token(post_id: i64) -> String {
+        FormTokens::new(TEST_JWT_SECRET)
+            .issue_at(
+                post_id,
+                time::OffsetDateTime::now_utc() - time::Duration::minutes(1),
+            )
+            .form_token
+    }
+
+    #[tokio::test]
+    async fn test_comments() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        sqlx::query(
+            "INSERT INTO posts (id, category, title, slug, content, description, published)
+             VALUES (1, 'blog', 'Post', 'post', 'Content', 'Description', 1)",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        let config = Config {
+            api_key: Some(TEST_API_KEY.to_string()),
+            rate_limit: RateLimitConfig {
+                comments_per_hour: 3,
+                trust_proxy: true,
+                ..Config::default_for_tests().rate_limit
+            },
+            ..Config::default_for_tests()
+        };
+        let app = app(db, &config);
+        let form_token = aged_form_token(1);
+        let submit = |body: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/posts/1/comments")
+                .header("X-Forwarded-For", "203.0.113.1")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    json!({
+                        "author_name": "Reader",
+                        "author_email": "reader@example.com",
+                        "body": body,
+                        "form_token": form_token,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+        let request = |method: Method, uri: &str, token: Option<&str>| {
+            let mut request = Request::builder().method(method).uri(uri);
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+            }
+            request.body(Body::empty()).unwrap()
+        };
+        let approved_bodies = |app: Router| async move {
+            let response = app
+                .oneshot(request(Method::GET, "/posts/1/comments", None))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let comments = response_json(response).await;
+            comments
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|comment| comment["body"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // Anyone may comment, but comments wait for moderation
+        let response = app.clone().oneshot(submit("First!")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let first = response_json(response).await;
+        assert_eq!(first["status"], "pending");
+        assert!(first.get("author_email").is_none());
+        assert!(first.get("author_email_hash").is_none());
+        let response = app.clone().oneshot(submit("Buy now")).await.unwrap();
+        let spam = response_json(response).await;
+        assert!(approved_bodies(app.clone()).await.is_empty());
+
+        // The moderation queue is for admins
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/admin/comments", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/admin/comments", Some(TEST_API_KEY)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-total-count"], "2");
+
+        let approve = format!("/admin/comments/{}/approve", first["id"]);
+        let response = app
+            .clone()
+            .oneshot(request(Method::POST, &approve, Some(TEST_API_KEY)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let reject = format!("/admin/comments/{}/reject", spam["id"]);
+        let response = app
+            .clone()
+            .oneshot(request(Method::POST, &reject, Some(TEST_API_KEY)))
+            .await
+            .unwrap();
+        assert_eq!(response_json(response).await["status"], "spam");
+        assert_eq!(approved_bodies(app.clone()).await, ["First!"]);
+
+        // Over-long bodies are refused, and count against the limit
+        let response = app
+            .clone()
+            .oneshot(submit(&"x".repeat(MAX_COMMENT_BODY_LEN + 1)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response_json(response).await["field"], "body");
+        let response = app.clone().oneshot(submit("Again")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/posts/2/comments", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let delete = format!("/admin/comments/{}", first["id"]);
+        let response = app
+            .clone()
+            .oneshot(request(Method::DELETE, &delete, Some(TEST_API_KEY)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(approved_bodies(app).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_comment_spam_is_answered_like_any_comment() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        sqlx::query(
+            "INSERT INTO posts (id, category, title, slug, content, description, published)
+             VALUES (1, 'blog', 'Post', 'post', 'Content', 'Description', 1)",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        let app = app(db.clone(), &Config::default_for_tests());
+        let submit = |form_token: &str, website: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/posts/1/comments")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    json!({
+                        "author_name": "Reader",
+                        "author_email": "reader@example.com",
+                        "body": "Hello",
+                        "form_token": form_token,
+                        "website": website,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+        // Everything but the fields that differ between any two comments
+        let answer = |response: Response| async move {
+            let status = response.status();
+            let mut body = response_json(response).await;
+            let body = body.as_object_mut().unwrap();
+            body.remove("id");
+            body.remove("created_at");
+            (status, Value::Object(body.clone()))
+        };
+
+        // Forms are served with a fresh token
+        let response = app
+            .clone()
+            .oneshot(
+                Request::get("/posts/1/comments/token")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let token = response_json(response).await;
+        assert_eq!(token["expires_in"], 86400);
+        let fresh = token["form_token"].as_str().unwrap().to_string();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::get("/posts/2/comments/token")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let aged = aged_form_token(1);
+        let genuine = answer(app.clone().oneshot(submit(&aged, "")).await.unwrap()).await;
+        let honeypot = app
+            .clone()
+            .oneshot(submit(&aged, "https://spam.example"))
+            .await
+            .unwrap();
+        let too_fast = app.clone().oneshot(submit(&fresh, "")).await.unwrap();
+        assert_eq!(genuine.0, StatusCode::ACCEPTED);
+        assert_eq!(genuine.1["status"], "pending");
+        assert_eq!(answer(honeypot).await, genuine);
+        assert_eq!(answer(too_fast).await, genuine);
+
+        // Only the moderation queue tells them apart
+        let (pending, _) = db
+            .comments()
+            .list_by_status(CommentStatus::Pending, 50, 0)
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        let (spam, _) = db
+            .comments()
+            .list_by_status(CommentStatus::Spam, 50, 0)
+            .await
+            .unwrap();
+        assert_eq!(spam.len(), 2);
+
+        // Forged and expired tokens are refused, so people know to reload
+        let expired = FormTokens::new(TEST_JWT_SECRET)
+            .issue_at(1, time::OffsetDateTime::now_utc() - time::Duration::days(2))
+            .form_token;
+        let other_post = aged_form_token(2);
+        for form_token in [expired.as_str(), other_post.as_str(), "forged"] {
+            let response = app.clone().oneshot(submit(form_token, "")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+            assert_eq!(response_json(response).await["field"], "form_token");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_contact_form() {
+        // A webhook that passes on whatever it receives
+        let (sender, mut received) = tokio::sync::mpsc::unbounded_channel();
+        let hook = Router::new().route(
+            "/hook",
+            post(move |axum::Json(body): axum::Json<Value>| async move {
+                sender.send(body).unwrap();
+                StatusCode::NO_CONTENT
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let webhook = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, hook).await });
+
+        let config = Config {
+            api_key: Some(TEST_API_KEY.to_string()),
+            rate_limit: RateLimitConfig {
+                contact_per_hour: 2,
+                trust_proxy: true,
+                ..Config::default_for_tests().rate_limit
+            },
+            notify_webhook_url: Some(webhook.parse().unwrap()),
+            ..Config::default_for_tests()
+        };
+        let app = create_test_app_with(config, None).await;
+        let send = |message: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/contact")
+                .header("X-Forwarded-For", "203.0.113.1")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    json!({
+                        "name": "Reader",
+                        "email": "reader@example.com",
+                        "message": message,
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+        let request = |method: Method, uri: &str, body: Option<Value>| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::AUTHORIZATION, format!("Bearer {}", TEST_API_KEY))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+                .unwrap()
+        };
+
+        // Anyone may write, and the webhook hears about it
+        let response = app.clone().oneshot(send("Hello")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let first = response_json(response).await;
+        assert_eq!(first["read_at"], Value::Null);
+        let notification = tokio::time::timeout(std::time::Duration::from_secs(5), received.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(notification["event"], "contact_message");
+        assert_eq!(notification["data"]["message"], "Hello");
+
+        let response = app.clone().oneshot(send("")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response_json(response).await["field"], "message");
+
+        // Even refused messages count against the strict limit
+        let response = app.clone().oneshot(send("Hello again")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response_json(response).await["code"], "rate_limited");
+
+        // Only admins read messages
+        let response = app
+            .clone()
+            .oneshot(Request::get("/contact").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/contact?unread=true", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-total-count"], "1");
+
+        let uri = format!("/contact/{}", first["id"]);
+        let response = app
+            .clone()
+            .oneshot(request(Method::PATCH, &uri, Some(json!({ "read": true }))))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response_json(response).await["read_at"].is_string());
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/contact?unread=true", None))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["x-total-count"], "0");
+    }
+
+    #[tokio::test]
+    async fn test_post_translations() {
+        let app = create_test_app().await;
+        let create = |body: Value| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/posts")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let post = |slug: &str| {
+            json!({
+                "category": "blog",
+                "title": "Hello",
+                "slug": slug,
+                "content": "Content",
+                "description": "Description",
+                "published": true,
+            })
+        };
+
+        let response = app.clone().oneshot(create(post("hello"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let original = response_json(response).await;
+        assert_eq!(original["language"], "en");
+        let created_at = original["created_at"].as_str().unwrap();
+        assert!(time::OffsetDateTime::parse(
+            created_at,
+            &time::format_description::well_known::Rfc3339
+        )
+        .is_ok());
+        let mut german = post("hallo");
+        german["language"] = json!("de");
+        german["translation_of"] = original["id"].clone();
+        let response = app.clone().oneshot(create(german)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        // Each version links to the other
+        let response = app
+            .clone()
+            .oneshot(get("/posts/by-slug/hallo"))
+            .await
+            .unwrap();
+        let german = response_json(response).await;
+        assert_eq!(german["translation_of"], original["id"]);
+        assert_eq!(
+            german["translations"],
+            json!([{ "lang": "en", "slug": "hello" }])
+        );
+        let response = app
+            .clone()
+            .oneshot(get(&format!("/posts/by-id/{}", original["id"])))
+            .await
+            .unwrap();
+        assert_eq!(
+            response_json(response).await["translations"],
+            json!([{ "lang": "de", "slug": "hallo" }])
+        );
+
+        // Listings and feeds filter by language
+        let response = app.clone().oneshot(get("/posts?lang=de")).await.unwrap();
+        assert_eq!(response.headers()["x-total-count"], "1");
+        assert_eq!(response_json(response).await[0]["slug"], "hallo");
+        let response = app
+            .clone()
+            .oneshot(get("/feed.json?lang=de"))
+            .await
+            .unwrap();
+        let feed = response_json(response).await;
+        assert_eq!(feed["language"], "de");
+        assert_eq!(feed["items"].as_array().unwrap().len(), 1);
+        assert!(feed["feed_url"]
+            .as_str()
+            .unwrap()
+            .ends_with("/feed.json?lang=de"));
+
+        let response = app.clone().oneshot(get("/posts?lang=xx")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let mut klingon = post("nuqneh");
+        klingon["language"] = json!("tlh");
+        let response = app.clone().oneshot(create(klingon)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response_json(response).await["field"], "language");
+    }
+
+    #[tokio::test]
+    async fn test_external_link_clicks() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        sqlx::query(
+            "INSERT INTO posts (id, category, title, slug, content, description, external_url, published)
+             VALUES (1, 'reading', 'Book', 'book', 'Content', 'Description', 'https://example.com/book', 1),
+                    (2, 'blog', 'Post', 'post', 'Content', 'Description', NULL, 1)",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        let config = Config {
+            api_key: Some(TEST_API_KEY.to_string()),
+            ..Config::default_for_tests()
+        };
+        let app = app(db, &config);
+        let request = |uri: &str, token: Option<&str>| {
+            let mut request = Request::builder()
+                .uri(uri)
+                .header(header::REFERER, "https://news.example.com/");
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+            }
+            request.body(Body::empty()).unwrap()
+        };
+
+        // Visiting redirects to the external URL
+        for _ in 0..2 {
+            let response = app
+                .clone()
+                .oneshot(request("/posts/1/visit", None))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+            assert_eq!(
+                response.headers()[header::LOCATION],
+                "https://example.com/book"
+            );
+        }
+
+        // Posts without an external URL, or missing ones, have nowhere to go
+        for uri in ["/posts/2/visit", "/posts/999/visit"] {
+            let response = app.clone().oneshot(request(uri, None)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+
+        // Only admins see the counts
+        let response = app
+            .clone()
+            .oneshot(request("/posts/1/clicks", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app
+            .clone()
+            .oneshot(request("/posts/1/clicks", Some(TEST_API_KEY)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let counts = response_json(response).await;
+        assert_eq!(counts.as_array().unwrap().len(), 1);
+        assert_eq!(counts[0]["clicks"], 2);
+
+        let response = app
+            .clone()
+            .oneshot(request("/posts/2/clicks", Some(TEST_API_KEY)))
+            .await
+            .unwrap();
+        assert_eq!(response_json(response).await, json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_broken_links() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        sqlx::query(
+            "INSERT INTO posts (id, category, title, slug, content, description, external_url, image_url)
+             VALUES (1, 'reading', 'Book', 'book', 'Content', 'Description', 'https://example.com/gone', '/static/cover.png')",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        let links = db.link_checks().links().await.unwrap();
+        db.link_checks()
+            .record(&links[0], &LinkOutcome::Status(404))
+            .await
+            .unwrap();
+        db.link_checks()
+            .record(&links[1], &LinkOutcome::Status(200))
+            .await
+            .unwrap();
+        let config = Config {
+            api_key: Some(TEST_API_KEY.to_string()),
+            ..Config::default_for_tests()
+        };
+        let app = app(db, &config);
+        let request = |uri: &str| {
+            Request::builder()
+                .uri(uri)
+                .header(header::AUTHORIZATION, format!("Bearer {}", TEST_API_KEY))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request("/admin/broken-links"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-total-count"], "1");
+        let body = response_json(response).await;
+        assert_eq!(body.as_array().unwrap().len(), 1);
+        assert_eq!(body[0]["post_slug"], "book");
+        assert_eq!(body[0]["field"], "external_url");
+        assert_eq!(body[0]["url"], "https://example.com/gone");
+        assert_eq!(body[0]["status"], 404);
+        assert_eq!(body[0]["error"], Value::Null);
+        assert!(body[0]["checked_at"].is_string());
+
+        let response = app
+            .oneshot(request("/admin/broken-links?limit=0"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_list_categories() {
+        let app = create_test_app().await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/categories")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response_json(response).await,
+            json!([
+                { "category": "blog", "label": "Blog", "published_posts": 0 },
+                { "category": "art", "label": "Art", "published_posts": 0 },
+                { "category": "reading", "label": "Reading", "published_posts": 0 }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_year_stats() {
+        let app = create_test_app().await;
+
+        let mut published_at = String::new();
+        for (slug, content, tags) in [
+            ("short", "Two words", json!(["rust"])),
+            ("long", "Three whole words", json!(["rust", "axum"])),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/posts")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(
+                            json!({
+                                "category": "blog",
+                                "title": slug,
+                                "slug": slug,
+                                "content": content,
+                                "description": "Description",
+                                "published": true,
+                                "tags": tags
+                            })
+                            .to_string(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+            published_at = response_json(response).await["published_at"]
+                .as_str()
+                .unwrap()
+                .to_string();
+        }
+        let year = &published_at[..4];
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/stats/year/{}", year))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let stats = response_json(response).await;
+        assert_eq!(stats["year"], year.parse::<i64>().unwrap());
+        assert_eq!(stats["posts"], 2);
+        assert_eq!(
+            stats["by_category"][0],
+            json!({ "category": "blog", "posts": 2 })
+        );
+        assert_eq!(stats["words"], 5);
+        assert_eq!(
+            stats["top_tags"],
+            json!([{ "name": "rust", "posts": 2 }, { "name": "axum", "posts": 1 }])
+        );
+        assert_eq!(stats["longest_post"]["slug"], "long");
+        assert_eq!(stats["longest_post"]["words"], 3);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/stats/year/1999")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let stats = response_json(response).await;
+        assert_eq!(stats["posts"], 0);
+        assert_eq!(stats["words"], 0);
+        assert_eq!(stats["top_tags"], json!([]));
+        assert_eq!(stats["longest_post"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_query_metrics() {
+        let app = create_test_app().await;
+
+        for _ in 0..2 {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri("/posts")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/admin/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_json(response).await;
+        let list = &body["queries"]["posts.list"];
+        assert_eq!(list["count"], 2);
+        let buckets = list["buckets"].as_array().unwrap();
+        assert_eq!(
+            buckets.last().unwrap(),
+            &json!({ "le_ms": null, "count": 2 })
+        );
+        assert_eq!(body["post_cache"], json!({ "hits": 1, "misses": 1 }));
+    }
+
+    #[tokio::test]
+    async fn test_post_changes() {
+        let app = create_test_app().await;
+
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/posts")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_string(&json!({
+                            "category": "blog",
+                            "title": "Test Post",
+                            "slug": "test-post",
+                            "content": "Test content",
+                            "description": "Test description",
+                            "published": true
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // Changes since the epoch include the new post and the server time
+        let changes_response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/posts/changes?since=1970-01-01T00:00:00Z")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(changes_response.status(), StatusCode::OK);
+        let changes = response_json(changes_response).await;
+        assert_eq!(changes["posts"].as_array().unwrap().len(), 1);
+        assert!(changes["server_time"].is_string());
+
+        // Malformed timestamps are rejected
+        let bad_response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/posts/changes?since=yesterday")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(bad_response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_posts_by_month() {
+        use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+        let app = create_test_app().await;
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/posts")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        json!({
+                            "category": "blog",
+                            "title": "Test Post",
+                            "slug": "test-post",
+                            "content": "Test content",
+                            "description": "Test description",
+                            "published": true
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let post = response_json(response).await;
+        let created_at =
+            OffsetDateTime::parse(post["created_at"].as_str().unwrap(), &Rfc3339).unwrap();
+
+        let get = |uri: String| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                (response.status(), response_json(response).await)
+            }
+        };
+
+        let (status, posts) = get(format!(
+            "/posts/{}/{:02}",
+            created_at.year(),
+            u8::from(created_at.month())
+        ))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(posts.as_array().unwrap().len(), 1);
+        assert_eq!(posts[0]["slug"], "test-post");
+
+        let (status, posts) = get("/posts/1999/02".to_string()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(posts, json!([]));
+
+        for uri in ["/posts/2024/13", "/posts/2024/00", "/posts/2024/march"] {
+            let (status, _) = get(uri.to_string()).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+        }
+
+        // Two-segment post routes still reach their own handlers
+        let (status, found) = get(format!("/posts/by-id/{}", post["id"])).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(found["slug"], "test-post");
+        let (status, tags) = get(format!("/posts/{}/tags", post["id"])).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(tags, json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_conditional_get_post() {
+        let app = create_test_app().await;
+
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/posts")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_string(&json!({
+                            "category": "blog",
+                            "title": "Test Post",
+                            "slug": "test-post",
+                            "content": "Test content",
+                            "description": "Test description",
+                            "published": true
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // First fetch returns the post with validators
+        let first = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/posts/by-slug/test-post")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(first.status(), StatusCode::OK);
+        let etag = first.headers()[header::ETAG].clone();
+        let last_modified = first.headers()[header::LAST_MODIFIED].clone();
+        let post = response_json(first).await;
+        let post_id = post["id"].as_i64().unwrap();
+
+        // Revalidating with the ETag yields 304 and an empty body
+        let revalidated = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/posts/by-slug/test-post")
+                    .header(header::IF_NONE_MATCH, etag.clone())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(revalidated.status(), StatusCode::NOT_MODIFIED);
+        let body = axum::body::to_bytes(revalidated.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.is_empty());
+
+        // Revalidating with the modification date yields 304 as well
+        let revalidated = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/posts/by-id/{}", post_id))
+                    .header(header::IF_MODIFIED_SINCE, last_modified)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(revalidated.status(), StatusCode::NOT_MODIFIED);
+
+        // An edit within the same second as the first fetch still
+        // invalidates the ETag
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::PATCH)
+                    .uri("/posts")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_string(&json!({
+                            "id": post_id,
+                            "title": "Updated Title"
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // The stale ETag no longer matches, so the full post is returned
+        let refreshed = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/posts/by-slug/test-post")
+                    .header(header::IF_NONE_MATCH, etag.clone())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(refreshed.status(), StatusCode::OK);
+        assert_ne!(refreshed.headers()[header::ETAG], etag);
+        let post = response_json(refreshed).await;
+        assert_eq!(post["title"], "Updated Title");
+
+        // Error responses are unaffected by conditional headers
+        let missing = app
+            .oneshot(
+                Request::builder()
+                    .uri("/posts/by-slug/missing")
+                    .header(header::IF_NONE_MATCH, "*")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        assert!(missing.headers().get(header::ETAG).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_posts_by_visibility() {
+        let config = Config {
+            api_key: Some(TEST_API_KEY.to_string()),
+            ..Config::default_for_tests()
+        };
+        // No default token, so requests are anonymous unless they say otherwise
+        let app = create_test_app_with(config, None).await;
+
+        for (slug, published) in [("live", true), ("draft", false)] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/posts")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .header(header::AUTHORIZATION, format!("Bearer {}", TEST_API_KEY))
+                        .body(Body::from(
+                            json!({
+                                "category": "blog",
+                                "title": slug,
+                                "slug": slug,
+                                "content": "Content",
+                                "description": "Description",
+                                "published": published
+                            })
+                            .to_string(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+
+        let list = |uri: String, authenticated: bool| {
+            let mut request = Request::builder().uri(uri);
+            if authenticated {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {}", TEST_API_KEY));
+            }
+            let request = request.body(Body::empty()).unwrap();
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = response_json(response).await;
+                let slugs: Vec<String> = body
+                    .as_array()
+                    .map(|posts| {
+                        posts
+                            .iter()
+                            .map(|post| post["slug"].as_str().unwrap().to_string())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                (status, slugs)
+            }
+        };
+        let live = (StatusCode::OK, vec!["live".to_string()]);
+
+        // The public listing has no way to reach drafts, whoever asks
+        for query in [
+            "",
+            "visibility=all",
+            "visibility=draft",
+            "visibility=hidden",
+            "published_only=false",
+            "published_only=true&visibility=draft",
+            "visibility=draft&visibility=all",
+            "published=false",
+            "include_drafts=true",
+            "category=blog&lang=en&visibility=draft&published_only=false",
+        ] {
+            for authenticated in [false, true] {
+                assert_eq!(
+                    list(format!("/posts?{}", query), authenticated).await,
+                    live,
+                    "{:?} authenticated={}",
+                    query,
+                    authenticated
+                );
+            }
+        }
+
+        // The admin listing has every post, filtered by visibility
+        let admin = |query: &str| list(format!("/admin/posts?{}", query), true);
+        assert_eq!(admin("").await.1.len(), 2);
+        assert_eq!(admin("visibility=all").await.1.len(), 2);
+        assert_eq!(admin("visibility=published").await, live);
+        assert_eq!(
+            admin("visibility=draft").await,
+            (StatusCode::OK, vec!["draft".to_string()])
+        );
+        // The older flag still means published only
+        assert_eq!(admin("published_only=true").await, live);
+        assert_eq!(
+            admin("published_only=true&visibility=published").await.0,
+            StatusCode::OK
+        );
+        for query in ["visibility=hidden", "published_only=true&visibility=draft"] {
+            assert_eq!(admin(query).await.0, StatusCode::BAD_REQUEST);
+        }
+
+        assert_eq!(
+            list("/admin/posts".to_string(), false).await.0,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_draft_flags_require_authentication() {
+        let config = Config {
+            api_key: Some(TEST_API_KEY.to_string()),
+            ..Config::default_for_tests()
+        };
+        let app = create_test_app_with(config, None).await;
+
+        let send = |uri: &str, body: Option<serde_json::Value>, authenticated: bool| {
+            let mut request = Request::builder().uri(uri);
+            if authenticated {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {}", TEST_API_KEY));
+            }
+            let request = match body {
+                Some(body) => request
+                    .method(Method::POST)
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string())),
+                None => request.body(Body::empty()),
+            };
+            app.clone().oneshot(request.unwrap())
+        };
+        let response = send(
+            "/posts",
+            Some(json!({
+                "category": "blog",
+                "title": "Draft",
+                "slug": "secret-draft",
+                "content": "Content",
+                "description": "Description",
+                "published": false,
+                "tags": ["rust"]
+            })),
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let tag = response_json(send("/tags/by-name/rust", None, false).await.unwrap()).await;
+        let tag_posts = format!("/tags/{}/posts?published_only=false", tag["id"]);
+
+        for uri in [
+            "/posts/changes?since=1970-01-01T00:00:00Z&include_drafts=true",
+            "/posts/untagged?include_drafts=true",
+            "/export/posts.ndjson?include_drafts=true",
+            &tag_posts,
+            "/tags/by-name/rust/posts?published_only=false",
+        ] {
+            let response = send(uri, None, false).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{}", uri);
+
+            let response = send(uri, None, true).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            // The draft is tagged, so the untagged listing leaves it out
+            assert_eq!(
+                body.contains("secret-draft"),
+                !uri.starts_with("/posts/untagged"),
+                "{}",
+                uri
+            );
+        }
+
+        // Without the flags, the listings stay public and hide the draft
+        for uri in [
+            "/posts/changes?since=1970-01-01T00:00:00Z",
+            "/posts/untagged",
+            "/export/posts.ndjson",
+            "/tags/by-name/rust/posts",
+        ] {
+            let response = send(uri, None, false).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            assert!(
+                !String::from_utf8_lossy(&body).contains("secret-draft"),
+                "{}",
+                uri
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oversized_content_is_rejected() {
+        let app = create_test_app().await;
+
+        let post = |slug: &str, content: String| {
+            let body = json!({
+                "category": "blog",
+                "title": "Big Post",
+                "slug": slug,
+                "content": content,
+                "description": "Test description",
+                "published": true
+            });
+            Request::builder()
+                .method(Method::POST)
+                .uri("/posts")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_string(&body).unwrap()))
+                .unwrap()
+        };
+
+        let limit = crate::models::post::DEFAULT_MAX_CONTENT_BYTES;
+        let response = app
+            .clone()
+            .oneshot(post("at-limit", "a".repeat(limit)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let response = app
+            .oneshot(post("over-limit", "a".repeat(limit + 1)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body = response_json(response).await;
+        assert_eq!(body["code"], "payload_too_large");
+        assert_eq!(body["field"], "content");
+    }
+
+    #[tokio::test]
+    async fn test_no_op_updates_are_flagged() {
+        let app = create_test_app().await;
+
+        let post = json!({
+            "category": "blog",
+            "title": "Test Post",
+            "slug": "test-post",
+            "content": "Test content",
+            "description": "Test description",
+            "published": true
+        });
+        let send = |method: Method, body: serde_json::Value| {
+            Request::builder()
+                .method(method)
+                .uri("/posts")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_string(&body).unwrap()))
+                .unwrap()
+        };
+        let created = response_json(
+            app.clone()
+                .oneshot(send(Method::POST, post.clone()))
+                .await
+                .unwrap(),
+        )
+        .await;
+
+        let mut update = post;
+        update["id"] = created["id"].clone();
+        update["image_url"] = json!("");
+        let response = app
+            .clone()
+            .oneshot(send(Method::PUT, update.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-not-modified"], "true");
+        assert_eq!(
+            response_json(response).await["updated_at"],
+            created["updated_at"]
+        );
+
+        let response = app
+            .clone()
+            .oneshot(send(
+                Method::PATCH,
+                json!({ "id": created["id"], "title": "Test Post" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["x-not-modified"], "true");
+
+        update["title"] = json!("Renamed");
+        let response = app.oneshot(send(Method::PUT, update)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("x-not-modified").is_none());
+        assert_eq!(response_json(response).await["title"], "Renamed");
+    }
+
+    #[tokio::test]
+    async fn test_autosave_draft_content() {
+        let app = create_test_app().await;
+
+        let create = |slug: &str, published: bool| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/posts")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    json!({
+                        "category": "blog",
+                        "title": "Test Post",
+                        "slug": slug,
+                        "content": "Test content",
+                        "description": "Test description",
+                        "published": published
+                    })
+                    .to_string(),
+                ))
+                .unwrap()
+        };
+        let save = |id: &serde_json::Value, body: serde_json::Value| {
+            Request::builder()
+                .method(Method::PUT)
+                .uri(format!("/posts/{}/draft-content", id))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let draft = response_json(app.clone().oneshot(create("draft", false)).await.unwrap()).await;
+        let published =
+            response_json(app.clone().oneshot(create("live", true)).await.unwrap()).await;
+
+        // An empty title and blank content are fine while typing
+        let response = app
+            .clone()
+            .oneshot(save(
+                &draft["id"],
+                json!({ "title": "", "content": " ", "description": "" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let saved = response_json(response).await;
+        assert_eq!(saved["title"], "");
+        assert_eq!(saved["content"], " ");
+        assert_eq!(saved["slug"], "draft");
+        assert_eq!(saved["published"], false);
+
+        // Only the text fields can be autosaved
+        let response = app
+            .clone()
+            .oneshot(save(&draft["id"], json!({ "slug": "renamed" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .oneshot(save(&published["id"], json!({ "content": "Rewritten" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(response_json(response).await["code"], "conflict");
+    }
+
+    #[tokio::test]
+    async fn test_patch_content_types() {
+        let app = create_test_app().await;
+
+        let mut post_ids = Vec::new();
+        for slug in ["json-post", "merge-post"] {
+            let post = response_json(
+                app.clone()
+                    .oneshot(
+                        Request::builder()
+                            .method(Method::POST)
+                            .uri("/posts")
+                            .header(header::CONTENT_TYPE, "application/json")
+                            .body(Body::from(
+                                serde_json::to_string(&json!({
+                                    "category": "blog",
+                                    "title": "Test Post",
+                                    "slug": slug,
+                                    "content": "Test content",
+                                    "description": "Test description",
+                                    "image_url": "https://example.com/image.jpg",
+                                    "published": true
+                                }))
+                                .unwrap(),
+                            ))
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap(),
+            )
+            .await;
+            post_ids.push(post["id"].as_i64().unwrap());
+        }
+
+        let patch = |content_type: &str, body: serde_json::Value| {
+            Request::builder()
+                .method(Method::PATCH)
+                .uri("/posts")
+                .header(header::CONTENT_TYPE, content_type)
+                .body(Body::from(serde_json::to_string(&body).unwrap()))
+                .unwrap()
+        };
+
+        // The same logical change via both content types yields identical rows
+        let json_response = app
+            .clone()
+            .oneshot(patch(
+                "application/json",
+                json!({ "id": post_ids[0], "title": "Patched", "published": false }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(json_response.status(), StatusCode::OK);
+        let json_post = response_json(json_response).await;
+
+        let merge_response = app
+            .clone()
+            .oneshot(patch(
+                "application/merge-patch+json",
+                json!({ "id": post_ids[1], "title": "Patched", "published": false }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(merge_response.status(), StatusCode::OK);
+        let merge_post = response_json(merge_response).await;
+
+        for field in [
+            "category",
+            "title",
+            "content",
+            "description",
+            "image_url",
+            "published",
+        ] {
+            assert_eq!(json_post[field], merge_post[field], "field {}", field);
+        }
+
+        // An explicit null leaves the value unchanged for plain JSON...
+        let json_post = response_json(
+            app.clone()
+                .oneshot(patch(
+                    "application/json",
+                    json!({ "id": post_ids[0], "image_url": null }),
+                ))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(json_post["image_url"], "https://example.com/image.jpg");
+
+        // ...but clears it for a merge patch
+        let merge_post = response_json(
+            app.clone()
+                .oneshot(patch(
+                    "application/merge-patch+json",
+                    json!({ "id": post_ids[1], "image_url": null }),
+                ))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert!(merge_post["image_url"].is_null());
+
+        // Unknown content types are rejected
+        let unsupported = app
+            .oneshot(patch(
+                "text/plain",
+                json!({ "id": post_ids[0], "title": "Nope" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(unsupported.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_renamed_slug_redirect() {
+        let app = create_test_app().await;
+
+        let post = response_json(
+            app.clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/posts")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(
+                            serde_json::to_string(&json!({
+                                "category": "blog",
+                                "title": "Test Post",
+                                "slug": "old-slug",
+                                "content": "Test content",
+                                "description": "Test description",
+                                "published": true
+                            }))
+                            .unwrap(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap(),
+        )
+        .await;
+
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::PATCH)
+                    .uri("/posts")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_string(&json!({
+                            "id": post["id"],
+                            "slug": "new-slug"
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/posts/by-slug/old-slug")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "/posts/by-slug/new-slug"
+        );
+        let body = response_json(response).await;
+        assert_eq!(body["redirect_to"], "new-slug");
+    }
+
+    #[tokio::test]
+    async fn test_missing_slug_suggestions() {
+        let app = create_test_app().await;
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/posts")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        json!({
+                            "category": "blog",
+                            "title": "Async Rust",
+                            "slug": "rust-async-intro",
+                            "content": "Content",
+                            "description": "Description",
+                            "published": true
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let get = |slug: &str| {
+            Request::builder()
+                .uri(format!("/posts/by-slug/{}", slug))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(get("rust-asnyc-intro")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = response_json(response).await;
+        assert_eq!(body["code"], "not_found");
+        assert_eq!(body["did_you_mean"], json!(["rust-async-intro"]));
+
+        let response = app.clone().oneshot(get("gardening")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response_json(response).await["did_you_mean"], json!([]));
+
+        // Other lookups don't suggest anything
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/posts/by-id/999")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(response_json(response).await.get("did_you_mean").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_malformed_requests() {
+        let app = create_test_app().await;
+
+        let post = |body: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/posts")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        // Truncated JSON is a syntax error
+        let response = app
+            .clone()
+            .oneshot(post(r#"{"category": "blog", "title": "#))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response_json(response).await;
+        assert_eq!(body["code"], "malformed_body");
+        assert!(body["message"].as_str().unwrap().contains("line 1"));
+
+        // Well-formed JSON with a wrong field type names the field
+        let response = app
+            .clone()
+            .oneshot(post(
+                r#"{"category": "blog", "title": 42, "slug": "s", "content": "c", "description": "d", "published": true}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response_json(response).await;
+        assert_eq!(body["code"], "invalid_body");
+        assert!(body["message"].as_str().unwrap().contains("title"));
+
+        // Unknown category values are rejected with the expected variants
+        let response = app
+            .clone()
+            .oneshot(post(
+                r#"{"category": "poetry", "title": "t", "slug": "s", "content": "c", "description": "d", "published": true}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response_json(response).await;
+        assert_eq!(body["code"], "invalid_body");
+        let message = body["message"].as_str().unwrap();
+        assert!(message.contains("category") && message.contains("poetry"));
+
+        // Bad query strings and path parameters use the same error shape
+        for uri in ["/posts?limit=abc", "/posts/by-id/abc"] {
+            let response = app
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", uri);
+            let body = response_json(response).await;
+            assert_eq!(body["code"], "invalid_input");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validation_errors_are_unprocessable() {
+        let app = create_test_app().await;
+
+        // A well-formed body that fails business validation
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/posts")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_string(&json!({
+                            "category": "blog",
+                            "title": "",
+                            "slug": "empty-title",
+                            "content": "Test content",
+                            "description": "Test description",
+                            "published": true
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = response_json(response).await;
+        assert_eq!(body["code"], "validation_error");
+        assert_eq!(body["field"], "title");
+        assert_eq!(
+            body["errors"],
+            json!([{ "field": "title", "message": "Post title cannot be empty" }])
+        );
+
+        // Every broken rule is reported at once
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/posts")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::to_string(&json!({
+                            "category": "reading",
+                            "title": "",
+                            "slug": "not a slug",
+                            "content": "",
+                            "description": "Test description",
+                            "published": false,
+                            "rating": 9
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = response_json(response).await;
+        let fields: Vec<_> = body["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["field"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            fields,
+            ["title", "content", "slug", "rating", "external_url"]
+        );
+        assert_eq!(body["field"], "title");
+        assert!(body["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Post title cannot be empty; "));
+
+        // A well-formed query with an out of range value
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/posts?limit=500")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = response_json(response).await;
+        assert_eq!(body["field"], "limit");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_fields_rejected() {
+        let app = create_test_app().await;
+
+        let post_fields = json!({
+            "category": "blog",
+            "title": "Test Post",
+            "slug": "test-post",
+            "content": "Test content",
+            "description": "Test description",
+            "publsihed": true
+        });
+        let mut update_fields = post_fields.clone();
+        update_fields["id"] = json!(1);
+
+        let cases = [
+            (Method::POST, "/posts", "application/json", post_fields),
+            (Method::PUT, "/posts", "application/json", update_fields),
+            (
+                Method::PATCH,
+                "/posts",
+                "application/json",
+                json!({ "id": 1, "publsihed": true }),
+            ),
+            (
+                Method::PATCH,
+                "/posts",
+                "application/merge-patch+json",
+                json!({ "id": 1, "publsihed": true }),
+            ),
+            (
+                Method::POST,
+                "/tags",
+                "application/json",
+                json!({ "name": "rust", "colour": "red" }),
+            ),
+            (
+                Method::PUT,
+                "/tags/1",
+                "application/json",
+                json!({ "name": "rust", "colour": "red" }),
+            ),
+        ];
+
+        for (method, uri, content_type, body) in cases {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(method.clone())
+                        .uri(uri)
+                        .header(header::CONTENT_TYPE, content_type)
+                        .body(Body::from(serde_json::to_string(&body).unwrap()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                response.status(),
+                StatusCode::BAD_REQUEST,
+                "{} {}",
+                method,
+                uri
+            );
+            let body = response_json(response).await;
+            assert_eq!(body["code"], "invalid_body");
+            let message = body["message"].as_str().unwrap();
+            assert!(
+                message.contains("publsihed") || message.contains("colour"),
+                "{}",
+                message
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_error_handling() {
+        let app = create_test_app().await;
+
+        // Test 404
+        let not_found = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/nonexistent")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(not_found.status(), StatusCode::NOT_FOUND);
+        let body = response_json(not_found).await;
+        assert_eq!(body["code"], "not_found");
+        assert_eq!(body["message"], "route not found");
+
+        // Test method not allowed
+        let method_not_allowed = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::PUT)
+                    .uri("/posts/by-slug/test")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(method_not_allowed.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(method_not_allowed.headers()[header::ALLOW], "GET,HEAD");
+        let body = response_json(method_not_allowed).await;
+        assert_eq!(body["code"], "method_not_allowed");
+        assert_eq!(body["allowed_methods"], json!(["GET", "HEAD"]));
+
+        // The header lists every method registered for the path, across
+        // the separate `route` calls that added them
+        let method_not_allowed = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/tags/1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(method_not_allowed.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            method_not_allowed.headers()[header::ALLOW],
+            "GET,HEAD,PUT,PATCH,DELETE"
+        );
+        assert_eq!(
+            method_not_allowed.headers()[header::CONTENT_TYPE],
+            "application/json"
+        );
+        let body = response_json(method_not_allowed).await;
+        assert_eq!(
+            body["allowed_methods"],
+            json!(["GET", "HEAD", "PUT", "PATCH", "DELETE"])
+        );
+
+        // CORS preflight still succeeds on routes without an OPTIONS handler
+        let preflight = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::OPTIONS)
+                    .uri("/posts/by-slug/test")
+                    .header(header::ORIGIN, "http://example.com")
+                    .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(preflight.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_trailing_slashes_are_ignored() {
+        let app = create_test_app().await;
+        let request = |method: Method, uri: &str, body: Option<serde_json::Value>| {
+            let builder = Request::builder().method(method).uri(uri);
+            match body {
+                Some(body) => builder
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+                None => builder.body(Body::empty()).unwrap(),
+            }
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                Method::POST,
+                "/posts/",
+                Some(json!({
+                    "category": "blog",
+                    "title": "Slashes",
+                    "slug": "slashes",
+                    "content": "Content",
+                    "description": "Description",
+                    "published": true
+                })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let id = response_json(response).await["id"].as_i64().unwrap();
+
+        // Both forms give the same answer, parameterized routes included
+        for uri in [
+            "/posts".to_string(),
+            "/categories".to_string(),
+            format!("/posts/by-id/{}", id),
+            "/posts/by-slug/slashes".to_string(),
+            format!("/posts/{}/tags", id),
+        ] {
+            let without = app
+                .clone()
+                .oneshot(request(Method::GET, &uri, None))
+                .await
+                .unwrap();
+            let with = app
+                .clone()
+                .oneshot(request(Method::GET, &format!("{}/", uri), None))
+                .await
+                .unwrap();
+            assert_eq!(without.status(), StatusCode::OK, "{}", uri);
+            assert_eq!(with.status(), StatusCode::OK, "{}/", uri);
+            assert_eq!(
+                response_json(without).await,
+                response_json(with).await,
+                "{}",
+                uri
+            );
+        }
+
+        // Unknown routes and methods are reported the same way as well
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/nonexistent/", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response_json(response).await["message"], "route not found");
+        let response = app
+            .clone()
+            .oneshot(request(Method::PUT, "/posts/by-slug/slashes/", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers()[header::ALLOW], "GET,HEAD");
+
+        // CORS preflight is answered before routing, with or without a slash
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::OPTIONS)
+                    .uri("/posts/")
+                    .header(header::ORIGIN, "http://example.com")
+                    .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_METHODS));
+    }
+}
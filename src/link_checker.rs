@@ -0,0 +1,369 @@
+//! Finds dead links in posts. Each pass sends a HEAD request to every
+//! external and image URL set on a post and records what came back, for
+//! `GET /admin/broken-links`. Passes run in the background when
+//! `LINK_CHECK_INTERVAL_HOURS` is set.
+//!
+//! Links are grouped by host: a few hosts are checked at once, but each
+//! host's links one after another with a pause between them, so no site
+//! gets a burst of requests.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use futures_util::{stream, StreamExt};
+use reqwest::StatusCode;
+use url::Url;
+
+use crate::{
+    db::{Database, DatabaseError},
+    models::link_check::{Link, LinkOutcome},
+};
+
+const USER_AGENT: &str = concat!(
+    env!("CARGO_PKG_NAME"),
+    "/",
+    env!("CARGO_PKG_VERSION"),
+    " (link checker)"
+);
+
+/// How long a URL may take to answer before it counts as broken
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How many hosts are checked at once
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Pause between two requests to the same host
+pub const DEFAULT_HOST_DELAY: Duration = Duration::from_secs(1);
+
+/// What a pass over every link found
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LinkCheckSummary {
+    pub checked: usize,
+    pub broken: usize,
+    /// Links that aren't http(s) URLs, even relative to the site
+    pub skipped: usize,
+}
+
+/// Checks posts' links and records the outcomes
+#[derive(Clone, Debug)]
+pub struct LinkChecker {
+    db: Database,
+    client: reqwest::Client,
+    /// Relative URLs, such as images under `/static`, are resolved
+    /// against this
+    site_url: Url,
+    timeout: Duration,
+    concurrency: usize,
+    host_delay: Duration,
+}
+
+impl LinkChecker {
+    /// A checker with the default timeout, concurrency and pause between
+    /// requests to a host
+    pub fn new(db: Database, site_url: Url) -> Self {
+        let client = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .build()
+            .expect("the link checker's client settings are valid");
+        Self {
+            db,
+            client,
+            site_url,
+            timeout: DEFAULT_TIMEOUT,
+            concurrency: DEFAULT_CONCURRENCY,
+            host_delay: DEFAULT_HOST_DELAY,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn with_host_delay(mut self, host_delay: Duration) -> Self {
+        self.host_delay = host_delay;
+        self
+    }
+
+    /// Checks every link once, recording each outcome. Outcomes that can't
+    /// be recorded, e.g. because the post was deleted meanwhile, are
+    /// logged and skipped.
+    pub async fn run(&self) -> Result<LinkCheckSummary, DatabaseError> {
+        let mut summary = LinkCheckSummary::default();
+        let mut by_host: BTreeMap<String, Vec<(Link, Url)>> = BTreeMap::new();
+        for link in self.db.link_checks().links().await? {
+            match self.resolve(&link.url) {
+                Some(url) => by_host
+                    .entry(url.host_str().unwrap_or_default().to_string())
+                    .or_default()
+                    .push((link, url)),
+                None => summary.skipped += 1,
+            }
+        }
+
+        let outcomes: Vec<(usize, usize)> = stream::iter(by_host.into_values())
+            .map(|links| self.check_host(links))
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await;
+        for (checked, broken) in outcomes {
+            summary.checked += checked;
+            summary.broken += broken;
+        }
+        Ok(summary)
+    }
+
+    /// Runs a pass every `period`, the first one a period after startup
+    pub fn spawn(self, period: Duration) {
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            loop {
+                interval.tick().await;
+                match self.run().await {
+                    Ok(summary) => tracing::info!(
+                        checked = summary.checked,
+                        broken = summary.broken,
+                        skipped = summary.skipped,
+                        "Checked posts' links"
+                    ),
+                    Err(e) => tracing::error!("Failed to check posts' links: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Checks one host's links in turn, returning how many were checked
+    /// and how many were broken
+    async fn check_host(&self, links: Vec<(Link, Url)>) -> (usize, usize) {
+        let (mut checked, mut broken) = (0, 0);
+        for (i, (link, url)) in links.into_iter().enumerate() {
+            if i > 0 {
+                tokio::time::sleep(self.host_delay).await;
+            }
+            let outcome = self.check(url).await;
+            if !outcome.is_ok() {
+                tracing::info!(
+                    post_id = link.post_id,
+                    url = %link.url,
+                    "Broken link: {:?}",
+                    outcome
+                );
+                broken += 1;
+            }
+            checked += 1;
+            if let Err(e) = self.db.link_checks().record(&link, &outcome).await {
+                tracing::warn!(post_id = link.post_id, "Failed to record link check: {}", e);
+            }
+        }
+        (checked, broken)
+    }
+
+    /// Asks for the URL's headers, falling back to the page itself for
+    /// servers that don't answer HEAD
+    async fn check(&self, url: Url) -> LinkOutcome {
+        let response = match self.request(reqwest::Method::HEAD, url.clone()).await {
+            Ok(response)
+                if matches!(
+                    response.status(),
+                    StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED
+                ) =>
+            {
+                self.request(reqwest::Method::GET, url).await
+            }
+            response => response,
+        };
+        match response {
+            Ok(response) => LinkOutcome::Status(response.status().as_u16()),
+            Err(e) if e.is_timeout() => {
+                LinkOutcome::Failed(format!("No answer within {}s", self.timeout.as_secs_f32()))
+            }
+            Err(e) => LinkOutcome::Failed(e.to_string()),
+        }
+    }
+
+    async fn request(
+        &self,
+        method: reqwest::Method,
+        url: Url,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        self.client
+            .request(method, url)
+            .timeout(self.timeout)
+            .send()
+            .await
+    }
+
+    /// The absolute http(s) URL a link points at
+    fn resolve(&self, url: &str) -> Option<Url> {
+        let url = self.site_url.join(url).ok()?;
+        matches!(url.scheme(), "http" | "https").then_some(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{
+        http::Method,
+        routing::{any, get},
+        Router,
+    };
+    use tokio::time::Instant;
+
+    use super::*;
+    use crate::{
+        db::test_utils::create_test_db,
+        models::link_check::{BrokenLink, LinkField},
+    };
+
+    /// Serves a site on a local port with working, missing, slow and
+    /// HEAD-refusing pages, keeping the method and user agent of every
+    /// request
+    async fn site() -> (Url, Arc<Mutex<Vec<(Method, String)>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        let app = Router::new()
+            .route("/ok", get(|| async { "ok" }))
+            .route("/static/cover.png", get(|| async { "png" }))
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    "late"
+                }),
+            )
+            .route(
+                "/get-only",
+                any(|method: Method| async move {
+                    if method == Method::HEAD {
+                        axum::http::StatusCode::METHOD_NOT_ALLOWED
+                    } else {
+                        axum::http::StatusCode::OK
+                    }
+                }),
+            )
+            .layer(axum::middleware::from_fn(
+                move |request: axum::extract::Request, next: axum::middleware::Next| {
+                    let seen = seen.clone();
+                    async move {
+                        let agent = request
+                            .headers()
+                            .get("user-agent")
+                            .and_then(|value| value.to_str().ok())
+                            .unwrap_or_default()
+                            .to_string();
+                        seen.lock().unwrap().push((request.method().clone(), agent));
+                        next.run(request).await
+                    }
+                },
+            ));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url.parse().unwrap(), requests)
+    }
+
+    async fn insert_post(db: &Database, id: i64, external_url: &str, image_url: Option<&str>) {
+        sqlx::query(
+            "INSERT INTO posts (id, category, title, slug, content, description, external_url, image_url)
+             VALUES (?, 'reading', 'Book', ?, 'Content', 'Description', ?, ?)",
+        )
+        .bind(id)
+        .bind(format!("book-{}", id))
+        .bind(external_url)
+        .bind(image_url)
+        .execute(db.pool())
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_run_records_broken_links() {
+        let (site_url, requests) = site().await;
+        let db = create_test_db().await.unwrap();
+        insert_post(
+            &db,
+            1,
+            &format!("{}ok", site_url),
+            Some("/static/cover.png"),
+        )
+        .await;
+        insert_post(&db, 2, &format!("{}missing", site_url), None).await;
+        insert_post(&db, 3, &format!("{}slow", site_url), None).await;
+        insert_post(
+            &db,
+            4,
+            &format!("{}get-only", site_url),
+            Some("ftp://x/a.png"),
+        )
+        .await;
+
+        let summary = LinkChecker::new(db.clone(), site_url)
+            .with_timeout(Duration::from_millis(300))
+            .with_host_delay(Duration::ZERO)
+            .run()
+            .await
+            .unwrap();
+        assert_eq!(
+            summary,
+            LinkCheckSummary {
+                checked: 5,
+                broken: 2,
+                skipped: 1,
+            }
+        );
+
+        let (broken, total) = db.link_checks().list_broken(50, 0).await.unwrap();
+        assert_eq!(total, 2);
+        let summary: Vec<(i64, LinkField, Option<i64>)> = broken
+            .iter()
+            .map(|link: &BrokenLink| (link.post_id, link.field, link.status))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (2, LinkField::ExternalUrl, Some(404)),
+                (3, LinkField::ExternalUrl, None),
+            ]
+        );
+        assert!(broken[1].error.as_deref().unwrap().contains("No answer"));
+
+        let requests = requests.lock().unwrap();
+        assert!(requests
+            .iter()
+            .all(|(_, agent)| agent.starts_with("server/") && agent.ends_with("(link checker)")));
+        // HEAD first, then GET for the page that refused it
+        let methods: Vec<&Method> = requests.iter().map(|(method, _)| method).collect();
+        assert_eq!(
+            methods.iter().filter(|m| ***m == Method::GET).count(),
+            1,
+            "{:?}",
+            methods
+        );
+    }
+
+    #[tokio::test]
+    async fn test_requests_to_a_host_are_spaced_out() {
+        let (site_url, requests) = site().await;
+        let db = create_test_db().await.unwrap();
+        for id in 1..=3 {
+            insert_post(&db, id, &format!("{}ok", site_url), None).await;
+        }
+
+        let started = Instant::now();
+        let summary = LinkChecker::new(db, site_url)
+            .with_host_delay(Duration::from_millis(100))
+            .run()
+            .await
+            .unwrap();
+        assert_eq!(summary.checked, 3);
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+}
@@ -1,408 +1,94 @@
-use axum::{
-    routing::{delete, get, patch, post, put},
-    Router,
-};
-use std::net::SocketAddr;
-use tower_http::cors::{Any, CorsLayer};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-
-use crate::{
+use clap::Parser;
+use server::{
+    cli::{self, Cli, Command},
+    config::Config,
     db::Database,
-    handlers::{
-        post_handlers::{
-            create_post, delete_post, get_post_by_id, get_post_by_slug, list_posts, patch_post,
-            update_post,
-        },
-        tag_handlers::{
-            add_tag_to_post, create_tag, delete_tag, get_post_tags, get_tag_by_id, get_tag_by_name,
-            list_tags, remove_tag_from_post, update_tag,
-        },
-    },
+    startup::{self, StartupError},
+    telemetry,
 };
 
-mod db;
-mod handlers;
-mod models;
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize logging
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
-        ))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
-    // Initialize database connection
-    let db = Database::new().await?;
-
-    // Configure CORS
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
-
-    // Build routes
-    let app = Router::new()
-        // Post routes
-        .route("/posts", get(list_posts))
-        .route("/posts", post(create_post))
-        .route("/posts/by-id/{id}", get(get_post_by_id))
-        .route("/posts/by-slug/{slug}", get(get_post_by_slug))
-        .route("/posts", put(update_post))
-        .route("/posts", patch(patch_post))
-        .route("/posts/{id}", delete(delete_post))
-        // Tag routes
-        .route("/tags", get(list_tags))
-        .route("/tags", post(create_tag))
-        .route("/tags/{id}", get(get_tag_by_id))
-        .route("/tags/by-name/{name}", get(get_tag_by_name))
-        .route("/tags/{id}", put(update_tag))
-        .route("/tags/{id}", delete(delete_tag))
-        // Post-Tag relationship routes
-        .route("/posts/{post_id}/tags", get(get_post_tags))
-        .route("/posts/{post_id}/tags/{tag_id}", put(add_tag_to_post))
-        .route(
-            "/posts/{post_id}/tags/{tag_id}",
-            delete(remove_tag_from_post),
-        )
-        // Add database state and middleware
-        .with_state(db)
-        .layer(cors);
-
-    // Start the server
-    let addr = SocketAddr::from(([0, 0, 0, 0], 8080));
-    tracing::info!("Starting server on {}", addr);
-
-    let listener = tokio::net::TcpListener::bind(&addr)
-        .await
-        .unwrap_or_else(|_| panic!("Failed to bind to address {}", addr));
-
-    axum::serve(listener, app)
-        .await
-        .unwrap_or_else(|e| panic!("Server error: {}", e));
-
-    Ok(())
-}
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use axum::{
-        body::Body,
-        http::{header, Method, Request, StatusCode},
-        response::Response,
-    };
-    use serde_json::json;
-    use tower::ServiceExt;
-    // Helper function to create a test app with a database connection
-    async fn create_test_app() -> Router {
-        std::env::set_var("DATABASE_URL", "sqlite::memory:");
-        let db = Database::new().await.unwrap();
-
-        let cors = CorsLayer::new()
-            .allow_origin(Any)
-            .allow_methods(Any)
-            .allow_headers(Any);
-
-        Router::new()
-            .route("/posts", get(list_posts))
-            .route("/posts", post(create_post))
-            .route("/posts/by-id/{id}", get(get_post_by_id))
-            .route("/posts/by-slug/{slug}", get(get_post_by_slug))
-            .route("/posts", put(update_post))
-            .route("/posts", patch(patch_post))
-            .route("/posts/{id}", delete(delete_post))
-            .route("/tags", get(list_tags))
-            .route("/tags", post(create_tag))
-            .route("/tags/{id}", get(get_tag_by_id))
-            .route("/tags/by-name/{name}", get(get_tag_by_name))
-            .route("/tags/{id}", put(update_tag))
-            .route("/tags/{id}", delete(delete_tag))
-            .route("/posts/{post_id}/tags", get(get_post_tags))
-            .route("/posts/{post_id}/tags/{tag_id}", put(add_tag_to_post))
-            .route(
-                "/posts/{post_id}/tags/{tag_id}",
-                delete(remove_tag_from_post),
-            )
-            .with_state(db)
-            .layer(cors)
+    let cli = Cli::parse();
+
+    // The declarations don't depend on any settings, so they can be
+    // generated without a configured environment
+    if let Command::ClientTypes { out } = cli.command() {
+        cli::write_client_types(out)?;
+        println!("Wrote TypeScript declarations to {}", out.display());
+        return Ok(());
     }
 
-    // Helper to get response body as a Value
-    async fn response_json(response: Response) -> serde_json::Value {
-        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
-            .await
-            .unwrap();
-        serde_json::from_slice(&bytes).unwrap()
-    }
-
-    #[tokio::test]
-    async fn test_cors_configuration() {
-        let app = create_test_app().await;
-
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .method(Method::OPTIONS)
-                    .uri("/posts")
-                    .header("Origin", "http://example.com")
-                    .header("Access-Control-Request-Method", "POST")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(response.status(), StatusCode::OK);
-        assert!(response
-            .headers()
-            .get("access-control-allow-origin")
-            .is_some());
-    }
-
-    #[tokio::test]
-    async fn test_post_crud_operations() {
-        let app = create_test_app().await;
-
-        // Create post
-        let create_response = app
-            .clone()
-            .oneshot(
-                Request::builder()
-                    .method(Method::POST)
-                    .uri("/posts")
-                    .header(header::CONTENT_TYPE, "application/json")
-                    .body(Body::from(
-                        serde_json::to_string(&json!({
-                            "category": "blog",
-                            "title": "Test Post",
-                            "slug": "test-post",
-                            "content": "Test content",
-                            "description": "Test description",
-                            "published": true
-                        }))
-                        .unwrap(),
-                    ))
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(create_response.status(), StatusCode::OK);
-        let post = response_json(create_response).await;
-        let post_id = post["id"].as_i64().unwrap();
+    // Load settings, reporting every problem before giving up
+    let config = Config::load().unwrap_or_else(|e| {
+        let error = StartupError::from(e);
+        eprintln!("{}", error);
+        std::process::exit(error.exit_code());
+    });
 
-        // Read post
-        let get_response = app
-            .clone()
-            .oneshot(
-                Request::builder()
-                    .method(Method::GET)
-                    .uri(&format!("/posts/by-id/{}", post_id))
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(get_response.status(), StatusCode::OK);
-
-        // Update post
-        let update_response = app
-            .clone()
-            .oneshot(
-                Request::builder()
-                    .method(Method::PATCH)
-                    .uri("/posts")
-                    .header(header::CONTENT_TYPE, "application/json")
-                    .body(Body::from(
-                        serde_json::to_string(&json!({
-                            "id": post_id,
-                            "title": "Updated Title"
-                        }))
-                        .unwrap(),
-                    ))
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(update_response.status(), StatusCode::OK);
-
-        // Delete post
-        let delete_response = app
-            .oneshot(
-                Request::builder()
-                    .method(Method::DELETE)
-                    .uri(&format!("/posts/{}", post_id))
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(delete_response.status(), StatusCode::NO_CONTENT);
+    // Initialize logging
+    telemetry::init(&config);
+
+    match cli.command() {
+        Command::Serve => {
+            if let Err(e) = startup::run(config).await {
+                tracing::error!("{}", e);
+                std::process::exit(e.exit_code());
+            }
+            Ok(())
+        }
+        Command::Migrate => migrate(&config).await,
+        Command::Export { out } => {
+            let db = Database::new(&config).await?;
+            let dump = cli::export(&db, out).await?;
+            println!(
+                "Exported {} posts and {} tags to {}",
+                dump.posts.len(),
+                dump.tags.len(),
+                out.display()
+            );
+            Ok(())
+        }
+        Command::CreateAdmin { email, password } => {
+            let password = match password {
+                Some(password) => password.clone(),
+                None => prompt_password()?,
+            };
+            let db = Database::new(&config).await?;
+            let user = cli::create_admin(&db, email, password).await?;
+            println!("Created admin {} with id {}", user.email, user.id);
+            Ok(())
+        }
+        Command::ClientTypes { .. } => unreachable!("handled before loading the settings"),
     }
+}
 
-    #[tokio::test]
-    async fn test_tag_operations() {
-        let app = create_test_app().await;
-
-        // Create tag
-        let create_response = app
-            .clone()
-            .oneshot(
-                Request::builder()
-                    .method(Method::POST)
-                    .uri("/tags")
-                    .header(header::CONTENT_TYPE, "application/json")
-                    .body(Body::from(
-                        serde_json::to_string(&json!({
-                            "name": "test-tag"
-                        }))
-                        .unwrap(),
-                    ))
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(create_response.status(), StatusCode::OK);
-        let tag = response_json(create_response).await;
-        let tag_id = tag["id"].as_i64().unwrap();
-
-        // List tags
-        let list_response = app
-            .clone()
-            .oneshot(
-                Request::builder()
-                    .method(Method::GET)
-                    .uri("/tags")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(list_response.status(), StatusCode::OK);
-        let tags = response_json(list_response).await;
-        assert!(tags.as_array().unwrap().len() > 0);
+/// Applies pending migrations, which opening the database does, and
+/// reports what the schema is at. Any failure makes the process exit with
+/// a non-zero status.
+async fn migrate(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::new(config).await?;
+    let status = db.migration_status().await?;
+    match status.applied.last() {
+        Some(latest) => println!(
+            "Database is at migration {} ({}), {} applied",
+            latest.version,
+            latest.description,
+            status.applied.len()
+        ),
+        None => println!("No migrations applied"),
     }
-
-    #[tokio::test]
-    async fn test_post_tag_relationships() {
-        let app = create_test_app().await;
-
-        // Create post and tag
-        let post = response_json(
-            app.clone()
-                .oneshot(
-                    Request::builder()
-                        .method(Method::POST)
-                        .uri("/posts")
-                        .header(header::CONTENT_TYPE, "application/json")
-                        .body(Body::from(
-                            serde_json::to_string(&json!({
-                                "category": "blog",
-                                "title": "Test Post",
-                                "slug": "test-post",
-                                "content": "Test content",
-                                "description": "Test description",
-                                "published": true
-                            }))
-                            .unwrap(),
-                        ))
-                        .unwrap(),
-                )
-                .await
-                .unwrap(),
-        )
-        .await;
-        let post_id = post["id"].as_i64().unwrap();
-
-        let tag = response_json(
-            app.clone()
-                .oneshot(
-                    Request::builder()
-                        .method(Method::POST)
-                        .uri("/tags")
-                        .header(header::CONTENT_TYPE, "application/json")
-                        .body(Body::from(
-                            serde_json::to_string(&json!({
-                                "name": "test-tag"
-                            }))
-                            .unwrap(),
-                        ))
-                        .unwrap(),
-                )
-                .await
-                .unwrap(),
-        )
-        .await;
-        let tag_id = tag["id"].as_i64().unwrap();
-
-        // Add tag to post
-        let add_response = app
-            .clone()
-            .oneshot(
-                Request::builder()
-                    .method(Method::PUT)
-                    .uri(&format!("/posts/{}/tags/{}", post_id, tag_id))
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(add_response.status(), StatusCode::NO_CONTENT);
-
-        // Get post tags
-        let tags_response = app
-            .clone()
-            .oneshot(
-                Request::builder()
-                    .method(Method::GET)
-                    .uri(&format!("/posts/{}/tags", post_id))
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(tags_response.status(), StatusCode::OK);
-        let tags = response_json(tags_response).await;
-        assert_eq!(tags.as_array().unwrap().len(), 1);
+    if !status.is_current() {
+        return Err(format!("{} migrations are still pending", status.pending.len()).into());
     }
+    Ok(())
+}
 
-    #[tokio::test]
-    async fn test_error_handling() {
-        let app = create_test_app().await;
-
-        // Test 404
-        let not_found = app
-            .clone()
-            .oneshot(
-                Request::builder()
-                    .uri("/nonexistent")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-        assert_eq!(not_found.status(), StatusCode::NOT_FOUND);
-
-        // Test method not allowed
-        let method_not_allowed = app
-            .oneshot(
-                Request::builder()
-                    .method(Method::PUT)
-                    .uri("/posts/by-slug/test")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-        assert_eq!(method_not_allowed.status(), StatusCode::METHOD_NOT_ALLOWED);
+/// Asks for the new admin's password twice on the terminal
+fn prompt_password() -> Result<String, Box<dyn std::error::Error>> {
+    let password = rpassword::prompt_password("Password: ")?;
+    if rpassword::prompt_password("Repeat password: ")? != password {
+        return Err("Passwords don't match".into());
     }
+    Ok(password)
 }
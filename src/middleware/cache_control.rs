@@ -0,0 +1,115 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+
+/// Routes whose successful reads are the same for every client, so shared
+/// caches such as CDNs may keep them
+const PUBLIC_ROUTES: &[&str] = &[
+    "/posts",
+    "/tags",
+    "/categories",
+    "/feed.xml",
+    "/atom.xml",
+    "/feed.json",
+    "/robots.txt",
+];
+
+const NO_STORE: HeaderValue = HeaderValue::from_static("no-store");
+
+/// `Cache-Control` values for public reads
+#[derive(Clone, Debug, PartialEq)]
+pub struct CacheControlConfig {
+    /// Value for successful reads of public routes
+    pub default: HeaderValue,
+    /// Values for routes under these path prefixes, replacing `default`.
+    /// The longest matching prefix wins.
+    pub routes: Vec<(String, HeaderValue)>,
+}
+
+impl Default for CacheControlConfig {
+    fn default() -> Self {
+        Self {
+            default: HeaderValue::from_static("public, max-age=60, stale-while-revalidate=300"),
+            routes: Vec::new(),
+        }
+    }
+}
+
+impl CacheControlConfig {
+    /// The value for a successful, anonymous `GET` of `path`, or `None`
+    /// when the route isn't public
+    fn for_path(&self, path: &str) -> Option<&HeaderValue> {
+        let overridden = self
+            .routes
+            .iter()
+            .filter(|(prefix, _)| is_under(path, prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, value)| value);
+        let public = PUBLIC_ROUTES.iter().any(|prefix| is_under(path, prefix));
+        overridden.or(public.then_some(&self.default))
+    }
+}
+
+fn is_under(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    path == prefix
+        || path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Middleware adding `Cache-Control` to every response that lacks one.
+///
+/// Successful anonymous reads of public routes get the configured value.
+/// Everything else, from writes and errors to reads made with credentials,
+/// gets `no-store`, so no cache ever keeps it.
+pub async fn cache_control(
+    State(config): State<CacheControlConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let cacheable = matches!(*request.method(), Method::GET | Method::HEAD)
+        && !request.headers().contains_key(header::AUTHORIZATION);
+    let value = cacheable
+        .then(|| config.for_path(request.uri().path()).cloned())
+        .flatten();
+
+    let mut response = next.run(request).await;
+    if !response.headers().contains_key(header::CACHE_CONTROL) {
+        let value = match value {
+            Some(value) if response.status().is_success() => value,
+            _ => NO_STORE,
+        };
+        response.headers_mut().insert(header::CACHE_CONTROL, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_path() {
+        let config = CacheControlConfig {
+            routes: vec![
+                ("/feed".to_string(), HeaderValue::from_static("max-age=600")),
+                ("/posts/changes/".to_string(), NO_STORE),
+            ],
+            ..CacheControlConfig::default()
+        };
+
+        assert_eq!(config.for_path("/posts"), Some(&config.default));
+        assert_eq!(config.for_path("/tags/1/posts"), Some(&config.default));
+        assert_eq!(config.for_path("/atom.xml"), Some(&config.default));
+        assert_eq!(config.for_path("/feed"), Some(&config.routes[0].1));
+        assert_eq!(config.for_path("/feed/rust"), Some(&config.routes[0].1));
+        assert_eq!(config.for_path("/posts/changes"), Some(&NO_STORE));
+        assert_eq!(config.for_path("/postscript"), None);
+        assert_eq!(config.for_path("/users"), None);
+        assert_eq!(config.for_path("/auth/me"), None);
+    }
+}
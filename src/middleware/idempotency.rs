@@ -0,0 +1,225 @@
+use std::time::Duration;
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures_util::StreamExt;
+
+use crate::{
+    auth::{AuthUser, Principal},
+    db::{Database, DatabaseError},
+    handlers::errors::ApiError,
+    models::idempotency::{is_valid_key, request_fingerprint, StoredResponse},
+};
+
+/// Header a client sets to make a request safe to retry
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Header set on responses replayed from an earlier request
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Largest response body that is buffered for storage
+const MAX_BUFFERED_RESPONSE: usize = 2 * 1024 * 1024;
+
+/// State for `idempotent`: where responses are stored, and the server's
+/// limits on the requests it guards
+#[derive(Clone, Debug)]
+pub struct Idempotency {
+    db: Database,
+    max_body_bytes: usize,
+    request_timeout: Duration,
+}
+
+impl Idempotency {
+    pub fn new(db: Database, max_body_bytes: usize, request_timeout: Duration) -> Self {
+        Self {
+            db,
+            max_body_bytes,
+            request_timeout,
+        }
+    }
+}
+
+/// Middleware that makes a route safe to retry with an `Idempotency-Key`
+/// header.
+///
+/// The first successful response to a key is stored with a fingerprint of
+/// the request. Repeating the request with the same key within 24 hours
+/// replays that response without running the handler again; reusing the
+/// key for a different request is rejected with 422. While the first
+/// request is still running, the key is reserved and repeats get 409.
+/// Failed requests are not stored, so they can be retried with the same
+/// key. Neither is a response too large to buffer, which is passed on as
+/// it is.
+///
+/// Keys are scoped to whoever sent them, so this must run after
+/// `require_auth`.
+pub async fn idempotent(
+    State(state): State<Idempotency>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let db = &state.db;
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(next.run(request).await);
+    };
+    let key = key
+        .to_str()
+        .ok()
+        .filter(|key| is_valid_key(key))
+        .ok_or_else(|| {
+            ApiError::InvalidInput(
+                "Idempotency-Key must be 1 to 255 visible ASCII characters".to_string(),
+            )
+        })?
+        .to_string();
+
+    let scope = request
+        .extensions()
+        .get::<Principal>()
+        .map(|principal| AuthUser::from(principal).actor.to_string())
+        .unwrap_or_default();
+
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, state.max_body_bytes)
+        .await
+        .map_err(|_| ApiError::PayloadTooLarge)?;
+    let request_hash = request_fingerprint(parts.method.as_str(), parts.uri.path(), &body);
+
+    let reserved = db
+        .idempotency()
+        .reserve(&scope, &key, &request_hash, state.request_timeout)
+        .await?;
+    if !reserved {
+        return match db.idempotency().find(&scope, &key).await? {
+            Some(stored) if stored.request_hash != request_hash => Err(ApiError::validation(
+                "Idempotency-Key",
+                "This key was already used for a different request",
+            )),
+            Some(stored) => Ok(replay(stored)),
+            None => Err(ApiError::Database(DatabaseError::Conflict(
+                "A request with this Idempotency-Key is still in progress".to_string(),
+            ))),
+        };
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    if !response.status().is_success() {
+        release(db, &scope, &key).await;
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match buffer(body, MAX_BUFFERED_RESPONSE).await {
+        Ok(body) => body,
+        Err(body) => {
+            tracing::warn!(
+                "Response for idempotency key not stored, as it couldn't be buffered; \
+                 a retry will run the request again"
+            );
+            release(db, &scope, &key).await;
+            return Ok(Response::from_parts(parts, body));
+        }
+    };
+
+    let stored = StoredResponse {
+        scope,
+        key,
+        request_hash,
+        status: i64::from(parts.status.as_u16()),
+        location: parts
+            .headers
+            .get(header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+        body: body.to_vec(),
+    };
+    // The request has already succeeded, so a storage failure only costs
+    // the ability to replay it
+    if let Err(e) = db.idempotency().save(&stored).await {
+        tracing::warn!("Failed to store response for idempotency key: {}", e);
+    }
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+/// Frees a key whose request left nothing to replay
+async fn release(db: &Database, scope: &str, key: &str) {
+    if let Err(e) = db.idempotency().release(scope, key).await {
+        tracing::warn!("Failed to release idempotency key: {}", e);
+    }
+}
+
+/// Reads a body of at most `limit` bytes into memory. A larger body, or one
+/// that fails part-way, is handed back whole, with what was read put back
+/// in front of the rest.
+async fn buffer(body: Body, limit: usize) -> Result<Bytes, Body> {
+    let mut stream = body.into_data_stream();
+    let mut chunks: Vec<Bytes> = Vec::new();
+    let mut len = 0;
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(chunk) if len + chunk.len() <= limit => {
+                len += chunk.len();
+                chunks.push(chunk);
+            }
+            chunk => {
+                let read = chunks.into_iter().map(Ok).chain(std::iter::once(chunk));
+                return Err(Body::from_stream(
+                    futures_util::stream::iter(read).chain(stream),
+                ));
+            }
+        }
+    }
+    Ok(Bytes::from(chunks.concat()))
+}
+
+/// Rebuilds a stored response, marked as a replay
+fn replay(stored: StoredResponse) -> Response {
+    let status = u16::try_from(stored.status)
+        .ok()
+        .and_then(|status| StatusCode::from_u16(status).ok())
+        .unwrap_or(StatusCode::OK);
+
+    let mut response = (status, stored.body).into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(mime::APPLICATION_JSON.as_ref()),
+    );
+    headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    if let Some(location) = stored
+        .location
+        .and_then(|location| HeaderValue::from_str(&location).ok())
+    {
+        headers.insert(header::LOCATION, location);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn read(body: Body) -> Bytes {
+        to_bytes(body, usize::MAX).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_buffer() {
+        let chunks = || {
+            Body::from_stream(futures_util::stream::iter(
+                ["abc", "def", "gh"].map(Ok::<_, axum::Error>),
+            ))
+        };
+
+        assert_eq!(buffer(chunks(), 8).await.unwrap(), "abcdefgh");
+        // A body over the limit comes back unchanged
+        let body = buffer(chunks(), 4).await.unwrap_err();
+        assert_eq!(read(body).await, "abcdefgh");
+    }
+}
@@ -0,0 +1,11 @@
+//! Request middleware that isn't specific to authentication
+
+mod cache_control;
+mod idempotency;
+mod rate_limit;
+mod request_id;
+
+pub use cache_control::{cache_control, CacheControlConfig};
+pub use idempotency::{idempotent, Idempotency, IDEMPOTENT_REPLAYED_HEADER};
+pub use rate_limit::{RateLimitConfig, RateLimitLayer, RateLimiter};
+pub use request_id::{record_route, request_id, request_span, RequestId, REQUEST_ID_HEADER};
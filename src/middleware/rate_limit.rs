@@ -0,0 +1,383 @@
+use std::{
+    future::Future,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{ConnectInfo, Request},
+    http::{HeaderMap, Method},
+    response::{IntoResponse, Response},
+};
+use dashmap::DashMap;
+use tower::{Layer, Service};
+
+use crate::handlers::errors::ApiError;
+
+const DEFAULT_READS_PER_MINUTE: u32 = 300;
+const DEFAULT_WRITES_PER_MINUTE: u32 = 60;
+const DEFAULT_COMMENTS_PER_HOUR: u32 = 10;
+const DEFAULT_CONTACT_PER_HOUR: u32 = 3;
+
+/// How many requests each client may make
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimitConfig {
+    /// Limit for `GET`, `HEAD` and `OPTIONS` requests
+    pub reads_per_minute: u32,
+    /// Limit for every other request
+    pub writes_per_minute: u32,
+    /// Limit for comment submissions, on top of the write limit
+    pub comments_per_hour: u32,
+    /// Limit for contact form submissions, on top of the write limit
+    pub contact_per_hour: u32,
+    /// Whether the server sits behind a proxy that sets `X-Forwarded-For`.
+    /// Without a proxy the header is client-controlled, so it is ignored.
+    pub trust_proxy: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            reads_per_minute: DEFAULT_READS_PER_MINUTE,
+            writes_per_minute: DEFAULT_WRITES_PER_MINUTE,
+            comments_per_hour: DEFAULT_COMMENTS_PER_HOUR,
+            contact_per_hour: DEFAULT_CONTACT_PER_HOUR,
+            trust_proxy: false,
+        }
+    }
+}
+
+/// Which limit a request counts against
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum RouteClass {
+    Read,
+    Write,
+    Comment,
+    Contact,
+}
+
+impl RouteClass {
+    fn of(method: &Method) -> Self {
+        if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+            RouteClass::Read
+        } else {
+            RouteClass::Write
+        }
+    }
+
+    /// Time over which the class's limit applies; an unused bucket has
+    /// refilled completely once this much time has passed
+    fn period(self) -> Duration {
+        match self {
+            RouteClass::Read | RouteClass::Write => Duration::from_secs(60),
+            RouteClass::Comment | RouteClass::Contact => Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+/// Token bucket holding up to a period's worth of requests, refilled
+/// continuously
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// How often idle buckets are looked for
+const EVICT_EVERY: Duration = Duration::from_secs(60);
+
+/// Per-client token buckets shared by every clone of the limiter
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Arc<DashMap<(IpAddr, RouteClass), Bucket>>,
+    last_eviction: Arc<Mutex<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Arc::default(),
+            last_eviction: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Takes a token for the client, or returns how long until one is free.
+    /// Idle buckets are evicted along the way, at most once a minute.
+    fn check(&self, ip: IpAddr, class: RouteClass, now: Instant) -> Result<(), Duration> {
+        self.evict_idle_every_minute(now);
+
+        let per_period = match class {
+            RouteClass::Read => self.config.reads_per_minute,
+            RouteClass::Write => self.config.writes_per_minute,
+            RouteClass::Comment => self.config.comments_per_hour,
+            RouteClass::Contact => self.config.contact_per_hour,
+        };
+        let capacity = f64::from(per_period);
+        let per_second = capacity / class.period().as_secs_f64();
+
+        let mut bucket = self.buckets.entry((ip, class)).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_second).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else if per_second > 0.0 {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+        } else {
+            Err(class.period())
+        }
+    }
+
+    fn evict_idle_every_minute(&self, now: Instant) {
+        let Ok(mut last_eviction) = self.last_eviction.try_lock() else {
+            // Another request is already evicting
+            return;
+        };
+        if now.saturating_duration_since(*last_eviction) >= EVICT_EVERY {
+            *last_eviction = now;
+            drop(last_eviction);
+            self.evict_idle_at(now);
+        }
+    }
+
+    /// Drops buckets untouched for their class's period. Those have
+    /// refilled completely, so forgetting them changes nothing for the
+    /// client.
+    fn evict_idle_at(&self, now: Instant) {
+        self.buckets.retain(|(_, class), bucket| {
+            now.saturating_duration_since(bucket.updated) < class.period()
+        });
+    }
+
+    /// The address a request is counted against: the last `X-Forwarded-For`
+    /// entry (the one the proxy saw) when proxies are trusted, otherwise
+    /// the peer address
+    fn client_ip(&self, request: &Request) -> IpAddr {
+        if self.config.trust_proxy {
+            if let Some(ip) = forwarded_for(request.headers()) {
+                return ip;
+            }
+        }
+        request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+    }
+}
+
+fn forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .next_back()
+        .and_then(|ip| ip.trim().parse().ok())
+}
+
+/// Layer that rejects clients over their limit with 429 Too Many Requests
+#[derive(Clone, Debug)]
+pub struct RateLimitLayer {
+    limiter: RateLimiter,
+    /// Class every request counts against; by method when unset
+    class: Option<RouteClass>,
+}
+
+impl RateLimitLayer {
+    /// Limits reads and writes, told apart by the request method
+    pub fn new(limiter: RateLimiter) -> Self {
+        Self {
+            limiter,
+            class: None,
+        }
+    }
+
+    /// Limits comment submissions, for the routes that accept them
+    pub fn comments(limiter: RateLimiter) -> Self {
+        Self {
+            limiter,
+            class: Some(RouteClass::Comment),
+        }
+    }
+
+    /// Limits contact form submissions
+    pub fn contact(limiter: RateLimiter) -> Self {
+        Self {
+            limiter,
+            class: Some(RouteClass::Contact),
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimit {
+            inner,
+            limiter: self.limiter.clone(),
+            class: self.class,
+        }
+    }
+}
+
+/// Service produced by `RateLimitLayer`
+#[derive(Clone, Debug)]
+pub struct RateLimit<S> {
+    inner: S,
+    limiter: RateLimiter,
+    class: Option<RouteClass>,
+}
+
+impl<S> Service<Request> for RateLimit<S>
+where
+    S: Service<Request, Response = Response> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let ip = self.limiter.client_ip(&request);
+        let class = self
+            .class
+            .unwrap_or_else(|| RouteClass::of(request.method()));
+
+        match self.limiter.check(ip, class, Instant::now()) {
+            Ok(()) => Box::pin(self.inner.call(request)),
+            Err(wait) => {
+                // Round up, so a client that waits as told is let through
+                let retry_after = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+                let response = ApiError::RateLimited {
+                    retry_after: retry_after.max(1),
+                }
+                .into_response();
+                Box::pin(async move { Ok(response) })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(reads_per_minute: u32, writes_per_minute: u32) -> RateLimiter {
+        RateLimiter::new(RateLimitConfig {
+            reads_per_minute,
+            writes_per_minute,
+            comments_per_hour: 2,
+            contact_per_hour: 1,
+            trust_proxy: false,
+        })
+    }
+
+    const CLIENT: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+    #[test]
+    fn test_bucket_refills() {
+        let limiter = limiter(60, 2);
+        let start = Instant::now();
+
+        assert!(limiter.check(CLIENT, RouteClass::Write, start).is_ok());
+        assert!(limiter.check(CLIENT, RouteClass::Write, start).is_ok());
+        let wait = limiter.check(CLIENT, RouteClass::Write, start).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(30));
+
+        // Reads, and other clients, have buckets of their own
+        assert!(limiter.check(CLIENT, RouteClass::Read, start).is_ok());
+        let other = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2));
+        assert!(limiter.check(other, RouteClass::Write, start).is_ok());
+
+        let later = start + Duration::from_secs(30);
+        assert!(limiter.check(CLIENT, RouteClass::Write, later).is_ok());
+        assert!(limiter.check(CLIENT, RouteClass::Write, later).is_err());
+    }
+
+    #[test]
+    fn test_comment_bucket_refills_hourly() {
+        let limiter = limiter(60, 60);
+        let start = Instant::now();
+
+        assert!(limiter.check(CLIENT, RouteClass::Comment, start).is_ok());
+        assert!(limiter.check(CLIENT, RouteClass::Comment, start).is_ok());
+        let wait = limiter
+            .check(CLIENT, RouteClass::Comment, start)
+            .unwrap_err();
+        assert_eq!(wait, Duration::from_secs(30 * 60));
+
+        // Unlike minute buckets, comment buckets outlive a minute's eviction
+        limiter.evict_idle_at(start + Duration::from_secs(120));
+        assert!(limiter.buckets.contains_key(&(CLIENT, RouteClass::Comment)));
+        limiter.evict_idle_at(start + Duration::from_secs(60 * 60));
+        assert!(limiter.buckets.is_empty());
+
+        // Contact messages have a bucket of their own
+        assert!(limiter.check(CLIENT, RouteClass::Contact, start).is_ok());
+        assert!(limiter.check(CLIENT, RouteClass::Contact, start).is_err());
+    }
+
+    #[test]
+    fn test_zero_limit_blocks() {
+        let limiter = limiter(0, 0);
+        let wait = limiter
+            .check(CLIENT, RouteClass::Read, Instant::now())
+            .unwrap_err();
+        assert_eq!(wait, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_evict_idle() {
+        let limiter = limiter(60, 60);
+        let start = Instant::now();
+        limiter.check(CLIENT, RouteClass::Read, start).unwrap();
+        limiter
+            .check(CLIENT, RouteClass::Write, start + Duration::from_secs(30))
+            .unwrap();
+
+        limiter.evict_idle_at(start + Duration::from_secs(60));
+        assert_eq!(limiter.buckets.len(), 1);
+        assert!(limiter.buckets.contains_key(&(CLIENT, RouteClass::Write)));
+
+        // Checks evict on their own once a minute has passed
+        let other = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2));
+        let later = Instant::now() + Duration::from_secs(120);
+        limiter.check(other, RouteClass::Read, later).unwrap();
+        assert_eq!(limiter.buckets.len(), 1);
+        assert!(limiter.buckets.contains_key(&(other, RouteClass::Read)));
+    }
+
+    #[test]
+    fn test_forwarded_for() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(forwarded_for(&headers), None);
+
+        headers.insert(
+            "x-forwarded-for",
+            "203.0.113.9, 198.51.100.7".parse().unwrap(),
+        );
+        assert_eq!(
+            forwarded_for(&headers),
+            Some("198.51.100.7".parse().unwrap())
+        );
+
+        headers.insert("x-forwarded-for", "not an address".parse().unwrap());
+        assert_eq!(forwarded_for(&headers), None);
+    }
+}
@@ -0,0 +1,150 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Span;
+use uuid::Uuid;
+
+/// Header carrying the id a request is known by in logs and error reports
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest client-supplied request id that is kept; longer ones are replaced
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static CURRENT: RequestId;
+}
+
+/// The id of the request being handled, available from request extensions
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// The id of the request whose handler is running, if any. Lets code
+    /// without access to the request, like error responses, report it.
+    pub fn current() -> Option<RequestId> {
+        CURRENT.try_with(Clone::clone).ok()
+    }
+}
+
+/// Middleware that gives every request an id: the client's `X-Request-Id`
+/// when it is usable, otherwise a new UUID. The id is stored in request
+/// extensions, visible to the handler through `RequestId::current`, and
+/// echoed in the response's `X-Request-Id` header.
+pub async fn request_id(mut request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_usable(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let id = RequestId(id);
+    request.extensions_mut().insert(id.clone());
+
+    let mut response = CURRENT.scope(id.clone(), next.run(request)).await;
+    if let Ok(value) = HeaderValue::from_str(&id.0) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Span for `TraceLayer` tagging everything logged while handling a request
+/// with its id. `request_id` must run first, so the id is in the extensions.
+pub fn request_span(request: &Request) -> Span {
+    let id = request
+        .extensions()
+        .get::<RequestId>()
+        .map(|id| id.0.as_str())
+        .unwrap_or_default();
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        request_id = %id,
+        route = tracing::field::Empty,
+    )
+}
+
+/// Middleware adding the matched route, like `/posts/{id}`, to the request
+/// span. Only routes know it, so this is a route layer.
+pub async fn record_route(path: MatchedPath, request: Request, next: Next) -> Response {
+    Span::current().record("route", path.as_str());
+    next.run(request).await
+}
+
+/// Client ids end up in logs and headers, so only short, printable ones
+/// are kept
+fn is_usable(id: &str) -> bool {
+    (1..=MAX_REQUEST_ID_LEN).contains(&id.len()) && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, middleware::from_fn, routing::get, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{db::DatabaseError, handlers::errors::ApiError};
+
+    #[test]
+    fn test_is_usable() {
+        assert!(is_usable("3f2b9c1e-7a4d-4c8e-9b1a-2d5e6f708192"));
+        assert!(is_usable("req_42"));
+        assert!(!is_usable(""));
+        assert!(!is_usable("two words"));
+        assert!(!is_usable(&"x".repeat(MAX_REQUEST_ID_LEN + 1)));
+    }
+
+    #[tokio::test]
+    async fn test_current() {
+        assert_eq!(RequestId::current(), None);
+
+        let id = RequestId("abc".to_string());
+        let current = CURRENT
+            .scope(id.clone(), async { RequestId::current() })
+            .await;
+        assert_eq!(current, Some(id));
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_report_request_id() {
+        let app = Router::new()
+            .route(
+                "/fail",
+                get(|| async { ApiError::Database(DatabaseError::Sqlx(sqlx::Error::RowNotFound)) }),
+            )
+            .route(
+                "/missing",
+                get(|| async { ApiError::Database(DatabaseError::not_found("Post", "1")) }),
+            )
+            .layer(from_fn(request_id));
+
+        let request = |uri: &str| {
+            Request::builder()
+                .uri(uri)
+                .header(&REQUEST_ID_HEADER, "trace-me")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(request("/fail")).await.unwrap();
+        assert_eq!(response.headers()[&REQUEST_ID_HEADER], "trace-me");
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["request_id"], "trace-me");
+
+        // Client errors don't need it
+        let response = app.oneshot(request("/missing")).await.unwrap();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert!(body.get("request_id").is_none());
+    }
+}
@@ -0,0 +1,101 @@
+use serde::Serialize;
+use serde_json::Value;
+use time::OffsetDateTime;
+
+use super::audit::{AuditAction, AuditEntity};
+
+/// What happened to a post or tag, as shown in the activity feed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivityAction {
+    Created,
+    Updated,
+    /// A post went live
+    Published,
+    /// A tag got a new name
+    Renamed,
+    Deleted,
+}
+
+impl ActivityAction {
+    /// Describes an audit entry about an `entity` from its action and the
+    /// changes it recorded
+    pub fn of(entity: AuditEntity, action: AuditAction, changes: &Value) -> Self {
+        match action {
+            AuditAction::Create => Self::Created,
+            AuditAction::Delete => Self::Deleted,
+            AuditAction::Update | AuditAction::Patch => match entity {
+                AuditEntity::Post if changes["published"]["new"] == true => Self::Published,
+                AuditEntity::Tag if changes.get("name").is_some() => Self::Renamed,
+                _ => Self::Updated,
+            },
+        }
+    }
+}
+
+/// A recent change to a post or tag
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityEntry {
+    /// ID of the audit log entry the change was read from
+    pub id: i64,
+    pub entity_type: AuditEntity,
+    pub entity_id: i64,
+    /// The post's title or the tag's name
+    pub title: Option<String>,
+    pub action: ActivityAction,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_actions() {
+        let of = |entity, action, changes: Value| ActivityAction::of(entity, action, &changes);
+
+        assert_eq!(
+            of(
+                AuditEntity::Post,
+                AuditAction::Create,
+                json!({ "published": true })
+            ),
+            ActivityAction::Created
+        );
+        assert_eq!(
+            of(
+                AuditEntity::Post,
+                AuditAction::Patch,
+                json!({ "published": { "old": false, "new": true } })
+            ),
+            ActivityAction::Published
+        );
+        assert_eq!(
+            of(
+                AuditEntity::Post,
+                AuditAction::Update,
+                json!({ "published": { "old": true, "new": false } })
+            ),
+            ActivityAction::Updated
+        );
+        assert_eq!(
+            of(
+                AuditEntity::Tag,
+                AuditAction::Patch,
+                json!({ "name": { "old": "rust", "new": "Rust" } })
+            ),
+            ActivityAction::Renamed
+        );
+        assert_eq!(
+            of(
+                AuditEntity::Tag,
+                AuditAction::Delete,
+                json!({ "name": "rust" })
+            ),
+            ActivityAction::Deleted
+        );
+    }
+}
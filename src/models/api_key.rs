@@ -0,0 +1,108 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use time::OffsetDateTime;
+
+use super::{errors::ApiKeyError, sha256_hex, to_hex};
+
+/// Prefix of every generated key, so keys are recognizable in configuration
+/// files and secret scanners, and told apart from access tokens
+pub const API_KEY_PREFIX: &str = "bk_";
+
+/// Maximum length, in characters, of an API key's name
+pub const MAX_API_KEY_NAME_LEN: usize = 100;
+
+/// Number of random bytes in a generated key
+const API_KEY_BYTES: usize = 32;
+
+/// An API key as stored in the database, without its hash
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ApiKey {
+    pub id: i64,
+    pub name: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_used_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub revoked_at: Option<OffsetDateTime>,
+}
+
+/// Request body for creating an API key
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateApiKey {
+    /// What the key is for, e.g. "CI publishing"
+    pub name: String,
+}
+
+impl CreateApiKey {
+    pub fn validate(&self) -> Result<(), ApiKeyError> {
+        let len = self.name.trim().chars().count();
+        if len == 0 || len > MAX_API_KEY_NAME_LEN {
+            return Err(ApiKeyError::InvalidName);
+        }
+        Ok(())
+    }
+}
+
+/// A newly created API key together with its plaintext, which is only
+/// ever returned by the request that created it
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+/// Generates a new random key
+pub fn generate_api_key() -> String {
+    let mut bytes = [0u8; API_KEY_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    format!("{}{}", API_KEY_PREFIX, to_hex(&bytes))
+}
+
+/// Hashes a key for storage and lookup.
+///
+/// Keys are long and random, so a fast unsalted hash is enough: it can be
+/// looked up directly, unlike a password hash.
+pub fn hash_api_key(key: &str) -> String {
+    sha256_hex(key.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_api_key() {
+        let key = generate_api_key();
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_eq!(key.len(), API_KEY_PREFIX.len() + API_KEY_BYTES * 2);
+        assert_ne!(key, generate_api_key());
+    }
+
+    #[test]
+    fn test_hash_api_key() {
+        assert_eq!(
+            hash_api_key("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_ne!(hash_api_key("bk_one"), hash_api_key("bk_two"));
+    }
+
+    #[test]
+    fn test_validate_name() {
+        let valid = CreateApiKey {
+            name: "CI publishing".to_string(),
+        };
+        assert!(valid.validate().is_ok());
+
+        for name in ["", "   ", &"x".repeat(MAX_API_KEY_NAME_LEN + 1)] {
+            let invalid = CreateApiKey {
+                name: name.to_string(),
+            };
+            assert!(matches!(invalid.validate(), Err(ApiKeyError::InvalidName)));
+        }
+    }
+}
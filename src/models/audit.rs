@@ -0,0 +1,338 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sqlx::{prelude::FromRow, types::Json};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use super::{post::Post, tag::Tag};
+
+/// Who made a change, as recorded in the audit log
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Actor {
+    /// The server itself, or code that isn't acting for a caller
+    #[default]
+    System,
+    User(i64),
+    /// The configured API key when `None`, otherwise a key from `/api-keys`
+    ApiKey(Option<i64>),
+    /// Any caller while authentication is disabled
+    Development,
+}
+
+impl std::fmt::Display for Actor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Actor::System => f.write_str("system"),
+            Actor::User(id) => write!(f, "user:{}", id),
+            Actor::ApiKey(None) => f.write_str("api_key"),
+            Actor::ApiKey(Some(id)) => write!(f, "api_key:{}", id),
+            Actor::Development => f.write_str("development"),
+        }
+    }
+}
+
+/// The kind of change an audit entry records
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Patch,
+    Delete,
+}
+
+/// The type of entity an audit entry refers to
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AuditEntity {
+    Post,
+    Tag,
+}
+
+/// An entry of the audit log as stored in the database
+#[derive(Debug, FromRow, Serialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub actor: String,
+    pub action: AuditAction,
+    pub entity_type: AuditEntity,
+    pub entity_id: i64,
+    /// A snapshot of the entity for creates and deletes, and the changed
+    /// fields with their old and new values for updates and patches
+    pub changes: Json<Value>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Filters for listing the audit log
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    pub entity_type: Option<AuditEntity>,
+    pub entity_id: Option<i64>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// An entity whose changes are recorded in the audit log
+pub trait Audited {
+    const ENTITY: AuditEntity;
+
+    fn audit_id(&self) -> i64;
+
+    /// The entity's stored fields, leaving out bookkeeping such as timestamps
+    fn snapshot(&self) -> Value;
+}
+
+impl Audited for Post {
+    const ENTITY: AuditEntity = AuditEntity::Post;
+
+    fn audit_id(&self) -> i64 {
+        self.id
+    }
+
+    fn snapshot(&self) -> Value {
+        // Destructure exhaustively so new fields can't be silently left out.
+        // Unlike the API representation, SEO fields are recorded as stored,
+        // without falling back to the title and description.
+        let Post {
+            id: _,
+            category,
+            title,
+            slug,
+            content,
+            description,
+            image_url,
+            external_url,
+            published,
+            meta_title,
+            meta_description,
+            og_image_url,
+            book_author,
+            isbn,
+            rating,
+            author_id,
+            // The entry's actor already says who made the change
+            created_by: _,
+            updated_by: _,
+            language,
+            translation_of,
+            published_at,
+            created_at: _,
+            updated_at: _,
+        } = self;
+
+        json!({
+            "category": category,
+            "title": title,
+            "slug": slug,
+            "content": content,
+            "description": description,
+            "image_url": image_url,
+            "external_url": external_url,
+            "published": published,
+            "meta_title": meta_title,
+            "meta_description": meta_description,
+            "og_image_url": og_image_url,
+            "book_author": book_author,
+            "isbn": isbn,
+            "rating": rating,
+            "author_id": author_id,
+            "language": language,
+            "translation_of": translation_of,
+            "published_at": published_at.and_then(|at| at.format(&Rfc3339).ok()),
+        })
+    }
+}
+
+impl Audited for Tag {
+    const ENTITY: AuditEntity = AuditEntity::Tag;
+
+    fn audit_id(&self) -> i64 {
+        self.id
+    }
+
+    fn snapshot(&self) -> Value {
+        let Tag {
+            id: _,
+            name,
+            slug,
+            created_at: _,
+            description,
+            color,
+        } = self;
+
+        json!({
+            "name": name,
+            "slug": slug,
+            "description": description,
+            "color": color,
+        })
+    }
+}
+
+/// A change about to be written to the audit log
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEvent {
+    pub action: AuditAction,
+    pub entity_type: AuditEntity,
+    pub entity_id: i64,
+    pub changes: Value,
+}
+
+impl AuditEvent {
+    /// Records a new entity with a snapshot of its fields
+    pub fn created<T: Audited>(entity: &T) -> Self {
+        Self {
+            action: AuditAction::Create,
+            entity_type: T::ENTITY,
+            entity_id: entity.audit_id(),
+            changes: entity.snapshot(),
+        }
+    }
+
+    /// Records an update or patch with only the fields that changed, given
+    /// the entity's snapshot from before the change
+    pub fn changed<T: Audited>(action: AuditAction, before: &Value, after: &T) -> Self {
+        Self {
+            action,
+            entity_type: T::ENTITY,
+            entity_id: after.audit_id(),
+            changes: diff(before, &after.snapshot()),
+        }
+    }
+
+    /// Records a deleted entity with a snapshot of its last fields
+    pub fn deleted<T: Audited>(entity: &T) -> Self {
+        Self {
+            action: AuditAction::Delete,
+            entity_type: T::ENTITY,
+            entity_id: entity.audit_id(),
+            changes: entity.snapshot(),
+        }
+    }
+}
+
+/// Compares two snapshots field by field.
+///
+/// Returns an object with an `{ "old": ..., "new": ... }` pair for every
+/// field whose value differs; a field missing on one side counts as null.
+/// Values that aren't objects are compared as a whole under the empty key.
+pub fn diff(old: &Value, new: &Value) -> Value {
+    let (Value::Object(old_fields), Value::Object(new_fields)) = (old, new) else {
+        let mut changes = Map::new();
+        if old != new {
+            changes.insert(String::new(), json!({ "old": old, "new": new }));
+        }
+        return Value::Object(changes);
+    };
+
+    let mut changes = Map::new();
+    let keys = old_fields.keys().chain(
+        new_fields
+            .keys()
+            .filter(|key| !old_fields.contains_key(*key)),
+    );
+    for key in keys {
+        let old_value = old_fields.get(key).unwrap_or(&Value::Null);
+        let new_value = new_fields.get(key).unwrap_or(&Value::Null);
+        if old_value != new_value {
+            changes.insert(key.clone(), json!({ "old": old_value, "new": new_value }));
+        }
+    }
+    Value::Object(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::post::PostCategory;
+
+    fn post() -> Post {
+        Post {
+            id: 5,
+            category: PostCategory::Blog,
+            title: "Old title".to_string(),
+            slug: "old-title".to_string(),
+            content: "Content".to_string(),
+            description: "Description".to_string(),
+            image_url: None,
+            external_url: None,
+            published: true,
+            meta_title: None,
+            meta_description: None,
+            og_image_url: None,
+            book_author: None,
+            isbn: None,
+            rating: None,
+            author_id: Some(1),
+            created_by: None,
+            updated_by: None,
+            language: "en".to_string(),
+            translation_of: None,
+            published_at: None,
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn test_title_change_diff() {
+        let before = post();
+        let after = Post {
+            title: "New title".to_string(),
+            updated_at: OffsetDateTime::now_utc(),
+            ..post()
+        };
+
+        let event = AuditEvent::changed(AuditAction::Update, &before.snapshot(), &after);
+        assert_eq!(event.entity_type, AuditEntity::Post);
+        assert_eq!(event.entity_id, 5);
+        // The computed meta_title fallback and the timestamp are not changes
+        assert_eq!(
+            event.changes,
+            json!({ "title": { "old": "Old title", "new": "New title" } })
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = json!({ "a": 1, "b": "x", "c": null });
+        let new = json!({ "a": 1, "b": "y", "d": true });
+        assert_eq!(
+            diff(&old, &new),
+            json!({
+                "b": { "old": "x", "new": "y" },
+                "d": { "old": null, "new": true },
+            })
+        );
+
+        assert_eq!(diff(&old, &old), json!({}));
+        assert_eq!(
+            diff(&json!(1), &json!(2)),
+            json!({ "": { "old": 1, "new": 2 } })
+        );
+    }
+
+    #[test]
+    fn test_snapshots() {
+        let snapshot = post().snapshot();
+        assert_eq!(snapshot["category"], "blog");
+        assert!(snapshot["meta_title"].is_null());
+        assert!(snapshot.get("updated_at").is_none());
+
+        let deleted = AuditEvent::deleted(&post());
+        assert_eq!(deleted.action, AuditAction::Delete);
+        assert_eq!(deleted.changes, snapshot);
+    }
+
+    #[test]
+    fn test_actor_labels() {
+        assert_eq!(Actor::User(3).to_string(), "user:3");
+        assert_eq!(Actor::ApiKey(None).to_string(), "api_key");
+        assert_eq!(Actor::ApiKey(Some(2)).to_string(), "api_key:2");
+        assert_eq!(Actor::default().to_string(), "system");
+    }
+}
@@ -0,0 +1,38 @@
+use serde::Serialize;
+
+/// Longest referer stored with a click, in characters; longer ones are cut
+pub const MAX_REFERER_LEN: usize = 255;
+
+/// Clicks on a post's external URL on one day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailyClicks {
+    /// UTC date, as `YYYY-MM-DD`
+    pub date: String,
+    pub clicks: i64,
+}
+
+/// Cuts a referer down to `MAX_REFERER_LEN` characters
+pub fn truncate_referer(referer: &str) -> &str {
+    match referer.char_indices().nth(MAX_REFERER_LEN) {
+        Some((end, _)) => &referer[..end],
+        None => referer,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_referer() {
+        assert_eq!(
+            truncate_referer("https://example.com/"),
+            "https://example.com/"
+        );
+
+        let long = "é".repeat(MAX_REFERER_LEN + 10);
+        let truncated = truncate_referer(&long);
+        assert_eq!(truncated.chars().count(), MAX_REFERER_LEN);
+        assert!(long.starts_with(truncated));
+    }
+}
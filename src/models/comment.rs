@@ -0,0 +1,227 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use time::OffsetDateTime;
+
+use super::{errors::CommentError, sha256_hex, user::is_valid_email};
+
+/// Maximum length, in characters, of a commenter's name
+pub const MAX_COMMENT_NAME_LEN: usize = 100;
+
+/// Maximum length, in characters, of a comment's body
+pub const MAX_COMMENT_BODY_LEN: usize = 5000;
+
+/// A comment on a post as stored in the database
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct Comment {
+    pub id: i64,
+    pub post_id: i64,
+    pub author_name: String,
+    /// SHA-256 of the normalized email; never sent to clients
+    #[serde(skip_serializing)]
+    pub author_email_hash: String,
+    pub body: String,
+    pub status: CommentStatus,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Where a comment stands in moderation. Only approved comments are shown
+/// to readers.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum CommentStatus {
+    #[default]
+    Pending,
+    Approved,
+    Spam,
+}
+
+impl FromStr for CommentStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pending" => Ok(CommentStatus::Pending),
+            "approved" => Ok(CommentStatus::Approved),
+            "spam" => Ok(CommentStatus::Spam),
+            _ => Err(format!("Invalid comment status: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for CommentStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            CommentStatus::Pending => "pending",
+            CommentStatus::Approved => "approved",
+            CommentStatus::Spam => "spam",
+        };
+        f.write_str(s)
+    }
+}
+
+impl TryFrom<String> for CommentStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::from_str(&value)
+    }
+}
+
+impl From<CommentStatus> for String {
+    fn from(status: CommentStatus) -> String {
+        status.to_string()
+    }
+}
+
+/// Request body for submitting a comment. New comments are always pending,
+/// so there is no status to choose.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateComment {
+    pub author_name: String,
+    /// Only its hash is stored
+    pub author_email: String,
+    pub body: String,
+    /// Token from `GET /posts/{post_id}/comments/token`, recording when the
+    /// form was served
+    pub form_token: String,
+    /// Honeypot: a field hidden from people, which only bots fill in
+    #[serde(default)]
+    pub website: Option<String>,
+}
+
+impl CreateComment {
+    /// Whether the hidden honeypot field was filled in
+    pub fn fills_honeypot(&self) -> bool {
+        self.website
+            .as_deref()
+            .is_some_and(|website| !website.trim().is_empty())
+    }
+
+    pub fn validate(&self) -> Result<(), CommentError> {
+        let name_len = self.author_name.trim().chars().count();
+        if name_len == 0 || name_len > MAX_COMMENT_NAME_LEN {
+            return Err(CommentError::InvalidName);
+        }
+        if !is_valid_email(&self.author_email) {
+            return Err(CommentError::InvalidEmail);
+        }
+        let body_len = self.body.trim().chars().count();
+        if body_len == 0 {
+            return Err(CommentError::EmptyBody);
+        }
+        if body_len > MAX_COMMENT_BODY_LEN {
+            return Err(CommentError::BodyTooLong);
+        }
+        Ok(())
+    }
+}
+
+/// Hashes a commenter's email for storage, after trimming and lowercasing
+/// it so the same address always hashes alike
+pub fn hash_email(email: &str) -> String {
+    sha256_hex(email.trim().to_lowercase().as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_comment() -> CreateComment {
+        CreateComment {
+            author_name: "Reader".to_string(),
+            author_email: "reader@example.com".to_string(),
+            body: "Nice post".to_string(),
+            form_token: String::new(),
+            website: None,
+        }
+    }
+
+    #[test]
+    fn test_comment_status_conversion() {
+        assert_eq!(
+            CommentStatus::from_str("Approved").unwrap(),
+            CommentStatus::Approved
+        );
+        assert_eq!(CommentStatus::Spam.to_string(), "spam");
+        assert!(CommentStatus::from_str("deleted").is_err());
+        assert_eq!(CommentStatus::default(), CommentStatus::Pending);
+    }
+
+    #[test]
+    fn test_create_comment_validation() {
+        assert!(create_comment().validate().is_ok());
+
+        let at_limit = CreateComment {
+            body: "x".repeat(MAX_COMMENT_BODY_LEN),
+            ..create_comment()
+        };
+        assert!(at_limit.validate().is_ok());
+
+        let cases = [
+            (
+                CreateComment {
+                    author_name: " ".to_string(),
+                    ..create_comment()
+                },
+                "author_name",
+            ),
+            (
+                CreateComment {
+                    author_email: "reader".to_string(),
+                    ..create_comment()
+                },
+                "author_email",
+            ),
+            (
+                CreateComment {
+                    body: "\n".to_string(),
+                    ..create_comment()
+                },
+                "body",
+            ),
+            (
+                CreateComment {
+                    body: "x".repeat(MAX_COMMENT_BODY_LEN + 1),
+                    ..create_comment()
+                },
+                "body",
+            ),
+        ];
+        for (comment, field) in cases {
+            let error = comment.validate().unwrap_err();
+            assert_eq!(error.field(), Some(field));
+        }
+    }
+
+    #[test]
+    fn test_honeypot() {
+        assert!(!create_comment().fills_honeypot());
+        for website in ["", "  "] {
+            let comment = CreateComment {
+                website: Some(website.to_string()),
+                ..create_comment()
+            };
+            assert!(!comment.fills_honeypot());
+        }
+        let comment = CreateComment {
+            website: Some("https://spam.example".to_string()),
+            ..create_comment()
+        };
+        assert!(comment.fills_honeypot());
+    }
+
+    #[test]
+    fn test_hash_email() {
+        assert_eq!(
+            hash_email(" Reader@Example.com "),
+            hash_email("reader@example.com")
+        );
+        assert_ne!(hash_email("a@example.com"), hash_email("b@example.com"));
+    }
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use time::OffsetDateTime;
+
+use super::{errors::ContactError, user::is_valid_email};
+
+/// Maximum length, in characters, of a sender's name
+pub const MAX_CONTACT_NAME_LEN: usize = 100;
+
+/// Maximum length, in characters, of an email address
+pub const MAX_CONTACT_EMAIL_LEN: usize = 254;
+
+/// Maximum length, in characters, of a message
+pub const MAX_CONTACT_MESSAGE_LEN: usize = 5000;
+
+/// A message sent through the contact form
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ContactMessage {
+    pub id: i64,
+    pub name: String,
+    pub email: String,
+    pub message: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    /// When an admin marked the message as read; unread while unset
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub read_at: Option<OffsetDateTime>,
+}
+
+/// Request body for sending a message through the contact form
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateContactMessage {
+    pub name: String,
+    /// Where to reply
+    pub email: String,
+    pub message: String,
+}
+
+impl CreateContactMessage {
+    pub fn validate(&self) -> Result<(), ContactError> {
+        let name_len = self.name.trim().chars().count();
+        if name_len == 0 || name_len > MAX_CONTACT_NAME_LEN {
+            return Err(ContactError::InvalidName);
+        }
+        if self.email.trim().chars().count() > MAX_CONTACT_EMAIL_LEN || !is_valid_email(&self.email)
+        {
+            return Err(ContactError::InvalidEmail);
+        }
+        let message_len = self.message.trim().chars().count();
+        if message_len == 0 {
+            return Err(ContactError::EmptyMessage);
+        }
+        if message_len > MAX_CONTACT_MESSAGE_LEN {
+            return Err(ContactError::MessageTooLong);
+        }
+        Ok(())
+    }
+}
+
+/// Request body for marking a contact message read or unread
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateContactMessage {
+    pub read: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_message() -> CreateContactMessage {
+        CreateContactMessage {
+            name: "Reader".to_string(),
+            email: "reader@example.com".to_string(),
+            message: "Hello there".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_create_contact_message_validation() {
+        assert!(create_message().validate().is_ok());
+
+        let cases = [
+            (
+                CreateContactMessage {
+                    name: "x".repeat(MAX_CONTACT_NAME_LEN + 1),
+                    ..create_message()
+                },
+                "name",
+            ),
+            (
+                CreateContactMessage {
+                    email: "not an email".to_string(),
+                    ..create_message()
+                },
+                "email",
+            ),
+            (
+                CreateContactMessage {
+                    email: format!("{}@example.com", "x".repeat(MAX_CONTACT_EMAIL_LEN)),
+                    ..create_message()
+                },
+                "email",
+            ),
+            (
+                CreateContactMessage {
+                    message: "  ".to_string(),
+                    ..create_message()
+                },
+                "message",
+            ),
+            (
+                CreateContactMessage {
+                    message: "x".repeat(MAX_CONTACT_MESSAGE_LEN + 1),
+                    ..create_message()
+                },
+                "message",
+            ),
+        ];
+        for (message, field) in cases {
+            let error = message.validate().unwrap_err();
+            assert_eq!(error.field(), Some(field));
+        }
+    }
+}
@@ -1,5 +1,73 @@
+use std::{fmt, ops::Deref};
+
+use serde::Serialize;
 use thiserror::Error;
 
+use super::{
+    api_key::MAX_API_KEY_NAME_LEN,
+    comment::{MAX_COMMENT_BODY_LEN, MAX_COMMENT_NAME_LEN},
+    contact::{MAX_CONTACT_MESSAGE_LEN, MAX_CONTACT_NAME_LEN},
+    post::{LANGUAGES, MAX_META_DESCRIPTION_LEN, MAX_META_TITLE_LEN},
+    tag::MAX_TAG_DESCRIPTION_LEN,
+    user::MIN_PASSWORD_LEN,
+};
+
+/// One rule a request broke: the field it concerns, if any, and what is
+/// wrong with it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: Option<&'static str>,
+    pub message: String,
+}
+
+/// Every rule a value broke, in the order they are checked, so that a form
+/// can be fixed in one round trip. Never empty.
+#[derive(Debug)]
+pub struct ValidationErrors<E>(Vec<E>);
+
+impl<E> ValidationErrors<E> {
+    /// Ok when no rule was broken, otherwise the broken rules
+    pub fn check(errors: Vec<E>) -> Result<(), Self> {
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Self(errors))
+        }
+    }
+
+    pub fn into_vec(self) -> Vec<E> {
+        self.0
+    }
+}
+
+impl<E> Deref for ValidationErrors<E> {
+    type Target = [E];
+
+    fn deref(&self) -> &[E] {
+        &self.0
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for ValidationErrors<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for ValidationErrors<E> {}
+
+impl<E> From<E> for ValidationErrors<E> {
+    fn from(error: E) -> Self {
+        Self(vec![error])
+    }
+}
+
 /// Represents all possible errors that can occur when working with posts.
 /// Using thiserror to automatically derive Error implementations makes our error
 /// handling more maintainable and provides better error messages.
@@ -14,9 +82,216 @@ pub enum PostError {
     #[error("Post content cannot be empty")]
     EmptyContent,
 
-    #[error("Invalid slug format")]
+    #[error("Post content is {actual} bytes, more than the limit of {limit}")]
+    ContentTooLarge { limit: usize, actual: usize },
+
+    #[error("Invalid slug format: use lowercase letters, digits and hyphens")]
     InvalidSlug,
 
+    #[error("Meta title cannot exceed {} characters", MAX_META_TITLE_LEN)]
+    MetaTitleTooLong,
+
+    #[error(
+        "Meta description cannot exceed {} characters",
+        MAX_META_DESCRIPTION_LEN
+    )]
+    MetaDescriptionTooLong,
+
+    #[error("Open Graph image URL must be an absolute http(s) URL")]
+    InvalidOgImageUrl,
+
+    #[error("Rating must be between 1 and 5")]
+    InvalidRating,
+
+    #[error("ISBN must be a valid ISBN-10 or ISBN-13")]
+    InvalidIsbn,
+
+    #[error("book_author, isbn and rating are only allowed for reading posts, not {0}")]
+    ReadingFieldsNotAllowed(String),
+
+    #[error("Reading posts must have an external_url linking to what they are about")]
+    MissingExternalUrl,
+
+    #[error("External URL must be an absolute http(s) URL")]
+    InvalidExternalUrl,
+
+    #[error("Invalid tag name: {0:?}")]
+    InvalidTagName(String),
+
+    #[error("Unsupported language {0:?}, expected one of: {supported}", supported = LANGUAGES.join(", "))]
+    UnsupportedLanguage(String),
+
+    #[error("Invalid translation: {0}")]
+    InvalidTranslation(String),
+
+    #[error("Invalid patch: {0}")]
+    InvalidPatch(String),
+
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
 }
+
+impl PostError {
+    /// Name of the request field this error refers to, if any
+    pub fn field(&self) -> Option<&'static str> {
+        match self {
+            PostError::InvalidId => Some("id"),
+            PostError::EmptyTitle => Some("title"),
+            PostError::EmptyContent | PostError::ContentTooLarge { .. } => Some("content"),
+            PostError::InvalidSlug => Some("slug"),
+            PostError::MetaTitleTooLong => Some("meta_title"),
+            PostError::MetaDescriptionTooLong => Some("meta_description"),
+            PostError::InvalidOgImageUrl => Some("og_image_url"),
+            PostError::InvalidRating => Some("rating"),
+            PostError::InvalidIsbn => Some("isbn"),
+            PostError::ReadingFieldsNotAllowed(_) => Some("category"),
+            PostError::MissingExternalUrl | PostError::InvalidExternalUrl => Some("external_url"),
+            PostError::InvalidTagName(_) => Some("tags"),
+            PostError::UnsupportedLanguage(_) => Some("language"),
+            PostError::InvalidTranslation(_) => Some("translation_of"),
+            PostError::InvalidPatch(_) | PostError::Database(_) => None,
+        }
+    }
+}
+
+impl From<&PostError> for FieldError {
+    fn from(error: &PostError) -> Self {
+        Self {
+            field: error.field(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Errors that can occur when validating tags
+#[derive(Debug, Error)]
+pub enum TagError {
+    #[error("Invalid tag name format")]
+    InvalidName,
+
+    #[error("Tag description cannot exceed {} characters", MAX_TAG_DESCRIPTION_LEN)]
+    DescriptionTooLong,
+
+    #[error("Color must be a six digit hex color like #1a2b3c")]
+    InvalidColor,
+}
+
+impl TagError {
+    /// Name of the request field this error refers to
+    pub fn field(&self) -> Option<&'static str> {
+        match self {
+            TagError::InvalidName => Some("name"),
+            TagError::DescriptionTooLong => Some("description"),
+            TagError::InvalidColor => Some("color"),
+        }
+    }
+}
+
+impl From<&TagError> for FieldError {
+    fn from(error: &TagError) -> Self {
+        Self {
+            field: error.field(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Errors that can occur when validating users
+#[derive(Debug, Error)]
+pub enum UserError {
+    #[error("Invalid email address")]
+    InvalidEmail,
+
+    #[error("Password must be at least {} characters", MIN_PASSWORD_LEN)]
+    PasswordTooShort,
+
+    #[error("Password hashing failed: {0}")]
+    PasswordHash(String),
+}
+
+impl UserError {
+    /// Name of the request field this error refers to, if any
+    pub fn field(&self) -> Option<&'static str> {
+        match self {
+            UserError::InvalidEmail => Some("email"),
+            UserError::PasswordTooShort => Some("password"),
+            UserError::PasswordHash(_) => None,
+        }
+    }
+}
+
+/// Errors that can occur when validating API keys
+#[derive(Debug, Error)]
+pub enum ApiKeyError {
+    #[error(
+        "API key name must be between 1 and {} characters",
+        MAX_API_KEY_NAME_LEN
+    )]
+    InvalidName,
+}
+
+impl ApiKeyError {
+    /// Name of the request field this error refers to, if any
+    pub fn field(&self) -> Option<&'static str> {
+        match self {
+            ApiKeyError::InvalidName => Some("name"),
+        }
+    }
+}
+
+/// Errors that can occur when validating comments
+#[derive(Debug, Error)]
+pub enum CommentError {
+    #[error(
+        "Author name must be between 1 and {} characters",
+        MAX_COMMENT_NAME_LEN
+    )]
+    InvalidName,
+
+    #[error("Invalid email address")]
+    InvalidEmail,
+
+    #[error("Comment body cannot be empty")]
+    EmptyBody,
+
+    #[error("Comment body cannot exceed {} characters", MAX_COMMENT_BODY_LEN)]
+    BodyTooLong,
+}
+
+impl CommentError {
+    /// Name of the request field this error refers to
+    pub fn field(&self) -> Option<&'static str> {
+        match self {
+            CommentError::InvalidName => Some("author_name"),
+            CommentError::InvalidEmail => Some("author_email"),
+            CommentError::EmptyBody | CommentError::BodyTooLong => Some("body"),
+        }
+    }
+}
+
+/// Errors that can occur when validating contact messages
+#[derive(Debug, Error)]
+pub enum ContactError {
+    #[error("Name must be between 1 and {} characters", MAX_CONTACT_NAME_LEN)]
+    InvalidName,
+
+    #[error("Invalid email address")]
+    InvalidEmail,
+
+    #[error("Message cannot be empty")]
+    EmptyMessage,
+
+    #[error("Message cannot exceed {} characters", MAX_CONTACT_MESSAGE_LEN)]
+    MessageTooLong,
+}
+
+impl ContactError {
+    /// Name of the request field this error refers to
+    pub fn field(&self) -> Option<&'static str> {
+        match self {
+            ContactError::InvalidName => Some("name"),
+            ContactError::InvalidEmail => Some("email"),
+            ContactError::EmptyMessage | ContactError::MessageTooLong => Some("message"),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use sqlx::prelude::FromRow;
+use time::Duration;
+
+use super::sha256_hex;
+
+/// How long a stored response can be replayed
+pub const IDEMPOTENCY_KEY_TTL: Duration = Duration::hours(24);
+
+/// Maximum length of an `Idempotency-Key` header value
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// A successful response stored so a retried request can be answered
+/// without running it again
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct StoredResponse {
+    /// Who sent the request, so clients can't replay each other's keys
+    pub scope: String,
+    pub key: String,
+    /// Fingerprint of the method, path and body of the original request
+    pub request_hash: String,
+    pub status: i64,
+    pub location: Option<String>,
+    pub body: Vec<u8>,
+}
+
+/// Whether `key` is acceptable as an idempotency key: 1 to 255 visible
+/// ASCII characters
+pub fn is_valid_key(key: &str) -> bool {
+    (1..=MAX_IDEMPOTENCY_KEY_LEN).contains(&key.len()) && key.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// Fingerprints a request, so a key reused for a different request can be
+/// told apart from a retry
+pub fn request_fingerprint(method: &str, path: &str, body: &[u8]) -> String {
+    let mut data = Vec::with_capacity(method.len() + path.len() + body.len() + 2);
+    data.extend_from_slice(method.as_bytes());
+    data.push(b' ');
+    data.extend_from_slice(path.as_bytes());
+    data.push(b'\n');
+    data.extend_from_slice(body);
+    sha256_hex(&data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_key() {
+        assert!(is_valid_key("retry-2024-05-01T12:00:00Z"));
+        assert!(is_valid_key(&"k".repeat(MAX_IDEMPOTENCY_KEY_LEN)));
+        assert!(!is_valid_key(""));
+        assert!(!is_valid_key("has space"));
+        assert!(!is_valid_key("émoji"));
+        assert!(!is_valid_key(&"k".repeat(MAX_IDEMPOTENCY_KEY_LEN + 1)));
+    }
+
+    #[test]
+    fn test_request_fingerprint() {
+        let fingerprint = request_fingerprint("POST", "/posts", b"{}");
+        assert_eq!(fingerprint, request_fingerprint("POST", "/posts", b"{}"));
+        assert_ne!(fingerprint, request_fingerprint("POST", "/tags", b"{}"));
+        assert_ne!(fingerprint, request_fingerprint("POST", "/posts", b"{ }"));
+    }
+}
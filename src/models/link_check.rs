@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// Which of a post's URLs a link is
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum LinkField {
+    ExternalUrl,
+    ImageUrl,
+}
+
+/// A URL a post links to, as stored on the post; image URLs may be
+/// relative to the site
+#[derive(Clone, Debug, PartialEq)]
+pub struct Link {
+    pub post_id: i64,
+    pub field: LinkField,
+    pub url: String,
+}
+
+/// What checking a link found
+#[derive(Clone, Debug, PartialEq)]
+pub enum LinkOutcome {
+    /// The URL answered with this HTTP status
+    Status(u16),
+    /// No answer came, for this reason
+    Failed(String),
+}
+
+impl LinkOutcome {
+    /// Whether the link works: it answered with a status below 400
+    pub fn is_ok(&self) -> bool {
+        matches!(self, LinkOutcome::Status(status) if *status < 400)
+    }
+}
+
+/// A link whose latest check failed, with the post it belongs to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BrokenLink {
+    pub post_id: i64,
+    pub post_slug: String,
+    pub post_title: String,
+    pub field: LinkField,
+    pub url: String,
+    /// HTTP status the URL answered with; null when no answer came
+    pub status: Option<i64>,
+    /// Why no answer came, such as a timeout
+    pub error: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub checked_at: OffsetDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome_is_ok() {
+        assert!(LinkOutcome::Status(200).is_ok());
+        assert!(LinkOutcome::Status(301).is_ok());
+        assert!(!LinkOutcome::Status(404).is_ok());
+        assert!(!LinkOutcome::Status(503).is_ok());
+        assert!(!LinkOutcome::Failed("timed out".to_string()).is_ok());
+    }
+}
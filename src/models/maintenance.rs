@@ -0,0 +1,92 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+
+/// A copy of the database written by `POST /admin/backup`
+#[derive(Debug, Serialize)]
+pub struct Backup {
+    /// Where the copy was written on the server
+    pub path: String,
+    pub size_bytes: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Database size around a `POST /admin/vacuum`
+#[derive(Debug, Serialize)]
+pub struct Vacuum {
+    pub size_bytes_before: i64,
+    pub size_bytes_after: i64,
+}
+
+/// What `POST /admin/render-cache/rebuild` did
+#[derive(Debug, Serialize)]
+pub struct RenderCacheRebuild {
+    /// Posts rendered, drafts included
+    pub rendered: usize,
+    pub renderer_version: u32,
+}
+
+/// What `POST /admin/integrity-check` found
+#[derive(Debug, Serialize)]
+pub struct IntegrityCheck {
+    /// True when neither check found a problem
+    pub ok: bool,
+    /// Corruption reported by `PRAGMA integrity_check`
+    pub problems: Vec<String>,
+    /// Rows pointing at rows that don't exist, from `PRAGMA foreign_key_check`
+    pub foreign_key_violations: Vec<ForeignKeyViolation>,
+}
+
+impl IntegrityCheck {
+    pub fn new(problems: Vec<String>, foreign_key_violations: Vec<ForeignKeyViolation>) -> Self {
+        Self {
+            ok: problems.is_empty() && foreign_key_violations.is_empty(),
+            problems,
+            foreign_key_violations,
+        }
+    }
+}
+
+/// A row whose foreign key refers to a missing row
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct ForeignKeyViolation {
+    /// Table holding the row
+    pub table: String,
+    /// The row's rowid, null for tables without one
+    pub rowid: Option<i64>,
+    /// Table the key refers to
+    pub parent: String,
+    /// Which of the table's foreign keys is broken, as numbered by
+    /// `PRAGMA foreign_key_list`
+    pub fkid: i64,
+}
+
+/// A migration recorded as applied in the database
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub description: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub applied_at: OffsetDateTime,
+}
+
+/// A migration embedded in the server that the database hasn't applied
+#[derive(Debug, Serialize)]
+pub struct PendingMigration {
+    pub version: i64,
+    pub description: String,
+}
+
+/// How the database schema compares to the migrations the server embeds
+#[derive(Debug, Serialize)]
+pub struct MigrationStatus {
+    /// Oldest first
+    pub applied: Vec<AppliedMigration>,
+    pub pending: Vec<PendingMigration>,
+}
+
+impl MigrationStatus {
+    pub fn is_current(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
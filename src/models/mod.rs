@@ -1,4 +1,35 @@
+pub mod activity;
+pub mod api_key;
+pub mod audit;
+pub mod click;
+pub mod comment;
+pub mod contact;
 pub mod errors;
+pub mod idempotency;
+pub mod link_check;
+pub mod maintenance;
 pub mod post;
+pub mod post_builder;
+pub mod stats;
 pub mod tag;
+pub mod upload;
 pub mod user;
+
+use std::fmt::Write;
+
+use sha2::{Digest, Sha256};
+
+/// Lowercase hex encoding of `bytes`
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut hex, byte| {
+            let _ = write!(hex, "{:02x}", byte);
+            hex
+        })
+}
+
+/// Hex-encoded SHA-256 digest of `data`
+pub(crate) fn sha256_hex(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
@@ -1,10 +1,44 @@
 use std::str::FromStr;
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sqlx::prelude::FromRow;
-use time::OffsetDateTime;
+use time::{Date, Month, OffsetDateTime};
 
-use super::errors::PostError;
+use super::{
+    errors::{PostError, ValidationErrors},
+    tag::Tag,
+};
+
+/// Which posts a listing includes, by whether they are published
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    Published,
+    Draft,
+    #[default]
+    All,
+}
+
+impl Visibility {
+    /// The visibility the older `published_only` flag stands for
+    pub fn from_published_only(published_only: bool) -> Self {
+        if published_only {
+            Visibility::Published
+        } else {
+            Visibility::All
+        }
+    }
+}
+
+impl std::fmt::Display for Visibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Visibility::Published => "published",
+            Visibility::Draft => "draft",
+            Visibility::All => "all",
+        })
+    }
+}
 
 /// Represents the different categories a post can belong to
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize, sqlx::Type)]
@@ -17,6 +51,30 @@ pub enum PostCategory {
     Reading,
 }
 
+impl PostCategory {
+    /// Every category, in the order they are listed
+    pub fn all() -> [PostCategory; 3] {
+        [PostCategory::Blog, PostCategory::Art, PostCategory::Reading]
+    }
+
+    /// Name to show readers, e.g. `Blog`
+    pub fn label(&self) -> &'static str {
+        match self {
+            PostCategory::Blog => "Blog",
+            PostCategory::Art => "Art",
+            PostCategory::Reading => "Reading",
+        }
+    }
+}
+
+/// A category with its label and number of published posts
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CategorySummary {
+    pub category: PostCategory,
+    pub label: &'static str,
+    pub published_posts: i64,
+}
+
 // This lets us convert strings into PostCategory values
 impl FromStr for PostCategory {
     type Err = String;
@@ -32,13 +90,14 @@ impl FromStr for PostCategory {
 }
 
 // This lets us convert PostCategory values into strings
-impl ToString for PostCategory {
-    fn to_string(&self) -> String {
-        match self {
-            PostCategory::Blog => "blog".to_string(),
-            PostCategory::Art => "art".to_string(),
-            PostCategory::Reading => "reading".to_string(),
-        }
+impl std::fmt::Display for PostCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            PostCategory::Blog => "blog",
+            PostCategory::Art => "art",
+            PostCategory::Reading => "reading",
+        };
+        f.write_str(s)
     }
 }
 
@@ -58,7 +117,26 @@ impl From<PostCategory> for String {
     }
 }
 
-#[derive(Debug, FromRow, Serialize)]
+/// Maximum length, in characters, of a post's SEO title
+pub const MAX_META_TITLE_LEN: usize = 70;
+
+/// Maximum length, in characters, of a post's SEO description
+pub const MAX_META_DESCRIPTION_LEN: usize = 160;
+
+/// Languages a post may be written in, as ISO 639-1 codes
+pub const LANGUAGES: [&str; 6] = ["en", "de", "fr", "es", "it", "nl"];
+
+/// Language of posts that don't say otherwise
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Largest post content accepted, in bytes, unless configured otherwise
+pub const DEFAULT_MAX_CONTENT_BYTES: usize = 1024 * 1024;
+
+/// A post as stored in the database.
+///
+/// `meta_title` and `meta_description` hold the stored overrides; when they are
+/// unset, serialized responses fall back to `title` and `description`.
+#[derive(Clone, Debug, FromRow)]
 pub struct Post {
     pub id: i64,
     pub category: PostCategory,
@@ -69,11 +147,264 @@ pub struct Post {
     pub image_url: Option<String>,
     pub external_url: Option<String>,
     pub published: bool,
+    pub meta_title: Option<String>,
+    pub meta_description: Option<String>,
+    pub og_image_url: Option<String>,
+    pub book_author: Option<String>,
+    pub isbn: Option<String>,
+    pub rating: Option<i64>,
+    /// The user who wrote the post, if it was created by a user account
+    pub author_id: Option<i64>,
+    /// The user whose change created the post. Left out of public
+    /// responses; see `Attributed`.
+    pub created_by: Option<i64>,
+    /// The user who last changed the post
+    pub updated_by: Option<i64>,
+    /// One of `LANGUAGES`
+    pub language: String,
+    /// The original post this one translates, if it is a translation
+    pub translation_of: Option<i64>,
+    /// When the post was first published; unset for posts never published
+    pub published_at: Option<OffsetDateTime>,
     pub created_at: OffsetDateTime,
     pub updated_at: OffsetDateTime,
 }
 
+/// Wire representation of a post with computed SEO fallbacks
+#[derive(Serialize)]
+struct PostResponse<'a> {
+    id: i64,
+    category: &'a PostCategory,
+    title: &'a str,
+    slug: &'a str,
+    content: &'a str,
+    description: &'a str,
+    image_url: Option<&'a str>,
+    external_url: Option<&'a str>,
+    published: bool,
+    meta_title: &'a str,
+    meta_description: &'a str,
+    og_image_url: Option<&'a str>,
+    book_author: Option<&'a str>,
+    isbn: Option<&'a str>,
+    rating: Option<i64>,
+    author_id: Option<i64>,
+    language: &'a str,
+    translation_of: Option<i64>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    published_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    updated_at: OffsetDateTime,
+}
+
+impl Serialize for Post {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Destructure exhaustively so new fields can't be silently left out
+        let Post {
+            id,
+            category,
+            title,
+            slug,
+            content,
+            description,
+            image_url,
+            external_url,
+            published,
+            meta_title,
+            meta_description,
+            og_image_url,
+            book_author,
+            isbn,
+            rating,
+            author_id,
+            // Only shown to callers who can write posts
+            created_by: _,
+            updated_by: _,
+            language,
+            translation_of,
+            published_at,
+            created_at,
+            updated_at,
+        } = self;
+
+        PostResponse {
+            id: *id,
+            category,
+            title,
+            slug,
+            content,
+            description,
+            image_url: image_url.as_deref(),
+            external_url: external_url.as_deref(),
+            published: *published,
+            meta_title: non_blank(meta_title).unwrap_or(title),
+            meta_description: non_blank(meta_description).unwrap_or(description),
+            og_image_url: og_image_url.as_deref(),
+            book_author: book_author.as_deref(),
+            isbn: isbn.as_deref(),
+            rating: *rating,
+            author_id: *author_id,
+            language,
+            translation_of: *translation_of,
+            published_at: *published_at,
+            created_at: *created_at,
+            updated_at: *updated_at,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Returns the value unless it is missing or only whitespace
+pub(crate) fn non_blank(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|v| !v.trim().is_empty())
+}
+
+/// Returns the value unless it is missing or empty
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|v| !v.is_empty())
+}
+
+impl Post {
+    /// Whether `other` holds the same editable fields as this post. Ids,
+    /// the author, editors and timestamps are ignored, and an empty optional text,
+    /// such as an `image_url` of `""`, counts as unset.
+    pub fn same_fields(&self, other: &Post) -> bool {
+        // Destructure exhaustively so new fields can't be silently left out
+        let Post {
+            id: _,
+            category,
+            title,
+            slug,
+            content,
+            description,
+            image_url,
+            external_url,
+            published,
+            meta_title,
+            meta_description,
+            og_image_url,
+            book_author,
+            isbn,
+            rating,
+            author_id: _,
+            created_by: _,
+            updated_by: _,
+            language,
+            translation_of,
+            published_at,
+            created_at: _,
+            updated_at: _,
+        } = self;
+
+        *category == other.category
+            && *title == other.title
+            && *slug == other.slug
+            && *content == other.content
+            && *description == other.description
+            && non_empty(image_url) == non_empty(&other.image_url)
+            && non_empty(external_url) == non_empty(&other.external_url)
+            && *published == other.published
+            && non_empty(meta_title) == non_empty(&other.meta_title)
+            && non_empty(meta_description) == non_empty(&other.meta_description)
+            && non_empty(og_image_url) == non_empty(&other.og_image_url)
+            && non_empty(book_author) == non_empty(&other.book_author)
+            && non_empty(isbn) == non_empty(&other.isbn)
+            && *rating == other.rating
+            && *language == other.language
+            && *translation_of == other.translation_of
+            && *published_at == other.published_at
+    }
+}
+
+/// Outcome of looking up a post by a slug that may have been renamed
+#[derive(Debug)]
+pub enum SlugLookup {
+    /// The slug is the post's current slug
+    Found(Box<Post>),
+    /// The slug belonged to a post that has since been renamed to this slug
+    Moved(String),
+}
+
+/// What the `{id_or_slug}` segment of `GET /posts/{id_or_slug}` names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostRef<'a> {
+    Id(i64),
+    Slug(&'a str),
+}
+
+impl<'a> PostRef<'a> {
+    /// A segment of ASCII digits that fits in an `i64` is an ID; anything
+    /// else, including signed or overflowing numbers, is a slug. Posts
+    /// whose slug is all digits are only reachable through
+    /// `/posts/by-slug/{slug}`.
+    pub fn parse(segment: &'a str) -> Self {
+        if !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()) {
+            if let Ok(id) = segment.parse() {
+                return PostRef::Id(id);
+            }
+        }
+        PostRef::Slug(segment)
+    }
+}
+
+/// A post together with its tags
+#[derive(Debug, Serialize)]
+pub struct PostWithTags {
+    #[serde(flatten)]
+    pub post: Post,
+    pub tags: Vec<Tag>,
+}
+
+/// A post, or a post with its tags, along with who created and last
+/// changed it. Public responses leave these out; responses to callers
+/// writing posts include them.
+#[derive(Debug, Serialize)]
+pub struct Attributed<T> {
+    #[serde(flatten)]
+    pub inner: T,
+    pub created_by: Option<i64>,
+    pub updated_by: Option<i64>,
+}
+
+impl Post {
+    pub fn attributed(self) -> Attributed<Post> {
+        Attributed {
+            created_by: self.created_by,
+            updated_by: self.updated_by,
+            inner: self,
+        }
+    }
+}
+
+impl PostWithTags {
+    pub fn attributed(self) -> Attributed<PostWithTags> {
+        Attributed {
+            created_by: self.post.created_by,
+            updated_by: self.post.updated_by,
+            inner: self,
+        }
+    }
+}
+
+/// Another language version of a post
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct Translation {
+    pub lang: String,
+    pub slug: String,
+}
+
+/// A post together with its published translations
+#[derive(Debug, Serialize)]
+pub struct PostWithTranslations {
+    #[serde(flatten)]
+    pub post: Post,
+    pub translations: Vec<Translation>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct CreatePost {
     pub category: PostCategory,
     pub title: String,
@@ -83,24 +414,63 @@ pub struct CreatePost {
     pub image_url: Option<String>,
     pub external_url: Option<String>,
     pub published: bool,
+    pub meta_title: Option<String>,
+    pub meta_description: Option<String>,
+    pub og_image_url: Option<String>,
+    pub book_author: Option<String>,
+    pub isbn: Option<String>,
+    pub rating: Option<i64>,
+    #[serde(default = "default_language")]
+    pub language: String,
+    pub translation_of: Option<i64>,
+    /// When the post was first published; defaults to now for a post
+    /// created published
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub published_at: Option<OffsetDateTime>,
+    /// Names of tags to attach; missing tags are created
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 impl CreatePost {
-    pub fn validate(&self) -> Result<(), PostError> {
+    pub fn validate(&self) -> Result<(), ValidationErrors<PostError>> {
+        let mut errors = Vec::new();
         if self.title.trim().is_empty() {
-            return Err(PostError::EmptyTitle);
+            errors.push(PostError::EmptyTitle);
         }
         if self.content.trim().is_empty() {
-            return Err(PostError::EmptyContent);
+            errors.push(PostError::EmptyContent);
         }
         if !is_valid_slug(&self.slug) {
-            return Err(PostError::InvalidSlug);
+            errors.push(PostError::InvalidSlug);
         }
-        Ok(())
+        errors.extend(
+            self.tags
+                .iter()
+                .filter(|name| !Tag::is_valid_name(name))
+                .map(|name| PostError::InvalidTagName(name.clone())),
+        );
+        errors.extend(validate_language(&self.language).err());
+        validate_seo(
+            self.meta_title.as_deref(),
+            self.meta_description.as_deref(),
+            self.og_image_url.as_deref(),
+            &mut errors,
+        );
+        validate_reading_fields(
+            &self.category,
+            self.book_author.as_deref(),
+            self.isbn.as_deref(),
+            self.rating,
+            &mut errors,
+        );
+        errors.extend(validate_external_url(&self.category, self.external_url.as_deref()).err());
+        ValidationErrors::check(errors)
     }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct UpdatePost {
     pub id: i64,
     pub category: PostCategory,
@@ -111,27 +481,68 @@ pub struct UpdatePost {
     pub image_url: Option<String>,
     pub external_url: Option<String>,
     pub published: bool,
+    pub meta_title: Option<String>,
+    pub meta_description: Option<String>,
+    pub og_image_url: Option<String>,
+    pub book_author: Option<String>,
+    pub isbn: Option<String>,
+    pub rating: Option<i64>,
+    #[serde(default = "default_language")]
+    pub language: String,
+    pub translation_of: Option<i64>,
+    /// Replaces the time the post was first published. When left out, it
+    /// is kept, or set to now if the post is being published for the
+    /// first time.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub published_at: Option<OffsetDateTime>,
 }
 
 impl UpdatePost {
-    pub fn validate(&self) -> Result<(), PostError> {
+    pub fn validate(&self) -> Result<(), ValidationErrors<PostError>> {
+        let mut errors = Vec::new();
         if self.id <= 0 {
-            return Err(PostError::InvalidId);
+            errors.push(PostError::InvalidId);
         }
         if self.title.trim().is_empty() {
-            return Err(PostError::EmptyTitle);
+            errors.push(PostError::EmptyTitle);
         }
         if self.content.trim().is_empty() {
-            return Err(PostError::EmptyContent);
+            errors.push(PostError::EmptyContent);
         }
         if !is_valid_slug(&self.slug) {
-            return Err(PostError::InvalidSlug);
+            errors.push(PostError::InvalidSlug);
+        }
+        if self.translation_of == Some(self.id) {
+            errors.push(PostError::InvalidTranslation(
+                "a post can't be a translation of itself".to_string(),
+            ));
         }
-        Ok(())
+        errors.extend(validate_language(&self.language).err());
+        validate_seo(
+            self.meta_title.as_deref(),
+            self.meta_description.as_deref(),
+            self.og_image_url.as_deref(),
+            &mut errors,
+        );
+        validate_reading_fields(
+            &self.category,
+            self.book_author.as_deref(),
+            self.isbn.as_deref(),
+            self.rating,
+            &mut errors,
+        );
+        errors.extend(validate_external_url(&self.category, self.external_url.as_deref()).err());
+        ValidationErrors::check(errors)
     }
 }
 
+/// Partial update of a post.
+///
+/// Nullable columns use a double `Option`: `None` leaves the value unchanged,
+/// while `Some(None)` clears it. Plain JSON patches treat explicit nulls as
+/// unchanged (see `ignoring_nulls`); JSON merge patches clear the value.
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct PatchPost {
     pub id: i64,
     pub category: Option<PostCategory>,
@@ -139,19 +550,370 @@ pub struct PatchPost {
     pub slug: Option<String>,
     pub content: Option<String>,
     pub description: Option<String>,
-    pub image_url: Option<String>,
-    pub external_url: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub image_url: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub external_url: Option<Option<String>>,
     pub published: Option<bool>,
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub meta_title: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub meta_description: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub og_image_url: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub book_author: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub isbn: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub rating: Option<Option<i64>>,
+    pub language: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub translation_of: Option<Option<i64>>,
+    /// Replaces the time the post was first published, like
+    /// `UpdatePost::published_at`; it can't be cleared
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub published_at: Option<OffsetDateTime>,
+}
+
+impl PatchPost {
+    /// Fields that may not be set to null in a merge patch because the
+    /// underlying columns are NOT NULL, or, for `published_at`, because
+    /// it can only be replaced
+    const REQUIRED_FIELDS: [&'static str; 9] = [
+        "id",
+        "category",
+        "title",
+        "slug",
+        "content",
+        "description",
+        "published",
+        "language",
+        "published_at",
+    ];
+
+    /// Builds a patch from an RFC 7396 JSON merge patch document.
+    /// Absent keys leave fields unchanged and explicit nulls clear them,
+    /// which is only allowed for nullable fields.
+    pub fn from_merge_patch(document: serde_json::Value) -> Result<Self, PostError> {
+        let Some(fields) = document.as_object() else {
+            return Err(PostError::InvalidPatch(
+                "merge patch must be a JSON object".to_string(),
+            ));
+        };
+
+        if let Some(field) = Self::REQUIRED_FIELDS
+            .iter()
+            .find(|field| fields.get(**field).is_some_and(|v| v.is_null()))
+        {
+            return Err(PostError::InvalidPatch(format!("{} cannot be null", field)));
+        }
+
+        serde_json::from_value(document).map_err(|e| PostError::InvalidPatch(e.to_string()))
+    }
+
+    /// Validates the fields being set by this patch
+    pub fn validate(&self) -> Result<(), ValidationErrors<PostError>> {
+        let mut errors = Vec::new();
+        if self
+            .slug
+            .as_deref()
+            .is_some_and(|slug| !is_valid_slug(slug))
+        {
+            errors.push(PostError::InvalidSlug);
+        }
+        if self.translation_of == Some(Some(self.id)) {
+            errors.push(PostError::InvalidTranslation(
+                "a post can't be a translation of itself".to_string(),
+            ));
+        }
+        if let Some(language) = &self.language {
+            errors.extend(validate_language(language).err());
+        }
+        validate_seo(
+            self.meta_title.as_ref().and_then(Option::as_deref),
+            self.meta_description.as_ref().and_then(Option::as_deref),
+            self.og_image_url.as_ref().and_then(Option::as_deref),
+            &mut errors,
+        );
+        ValidationErrors::check(errors)
+    }
+
+    /// Treats explicit nulls as "unchanged", the historical behavior of
+    /// plain `application/json` patches
+    pub fn ignoring_nulls(mut self) -> Self {
+        for field in [
+            &mut self.image_url,
+            &mut self.external_url,
+            &mut self.meta_title,
+            &mut self.meta_description,
+            &mut self.og_image_url,
+            &mut self.book_author,
+            &mut self.isbn,
+        ] {
+            if *field == Some(None) {
+                *field = None;
+            }
+        }
+        if self.rating == Some(None) {
+            self.rating = None;
+        }
+        if self.translation_of == Some(None) {
+            self.translation_of = None;
+        }
+        self
+    }
+
+    /// Whether this patch sets any of the Reading-only fields to a value
+    pub fn sets_reading_fields(&self) -> bool {
+        matches!(self.book_author, Some(Some(_)))
+            || matches!(self.isbn, Some(Some(_)))
+            || matches!(self.rating, Some(Some(_)))
+    }
+}
+
+/// Autosaved text of a draft, written by `PUT /posts/{id}/draft-content`.
+///
+/// Unlike `PatchPost` the fields may be empty or half written; only the
+/// content size limit applies. Fields left out are unchanged.
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DraftContent {
+    pub title: Option<String>,
+    pub content: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Deserializes a present field as `Some`, so that an explicit null becomes
+/// `Some(None)` while a missing field falls back to the `None` default
+pub(crate) fn double_option<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+fn default_language() -> String {
+    DEFAULT_LANGUAGE.to_string()
+}
+
+/// Whether posts may be written in `language`
+pub fn is_supported_language(language: &str) -> bool {
+    LANGUAGES.contains(&language)
+}
+
+fn validate_language(language: &str) -> Result<(), PostError> {
+    if !is_supported_language(language) {
+        return Err(PostError::UnsupportedLanguage(language.to_string()));
+    }
+    Ok(())
+}
+
+/// Validates the optional SEO / Open Graph fields shared by all post
+/// models, adding the broken rules to `errors`
+fn validate_seo(
+    meta_title: Option<&str>,
+    meta_description: Option<&str>,
+    og_image_url: Option<&str>,
+    errors: &mut Vec<PostError>,
+) {
+    if meta_title.is_some_and(|t| t.chars().count() > MAX_META_TITLE_LEN) {
+        errors.push(PostError::MetaTitleTooLong);
+    }
+    if meta_description.is_some_and(|d| d.chars().count() > MAX_META_DESCRIPTION_LEN) {
+        errors.push(PostError::MetaDescriptionTooLong);
+    }
+    if og_image_url.is_some_and(|u| !is_valid_http_url(u)) {
+        errors.push(PostError::InvalidOgImageUrl);
+    }
+}
+
+/// Validates the structured book fields, which only apply to Reading posts,
+/// adding the broken rules to `errors`. Supplying any of them for another
+/// category is an error.
+pub fn validate_reading_fields(
+    category: &PostCategory,
+    book_author: Option<&str>,
+    isbn: Option<&str>,
+    rating: Option<i64>,
+    errors: &mut Vec<PostError>,
+) {
+    if *category != PostCategory::Reading {
+        if book_author.is_some() || isbn.is_some() || rating.is_some() {
+            errors.push(PostError::ReadingFieldsNotAllowed(category.to_string()));
+        }
+        return;
+    }
+
+    if rating.is_some_and(|r| !(1..=5).contains(&r)) {
+        errors.push(PostError::InvalidRating);
+    }
+    if isbn.is_some_and(|i| !is_valid_isbn(i)) {
+        errors.push(PostError::InvalidIsbn);
+    }
+}
+
+/// Reading posts are about something elsewhere, so they must link to it
+/// with an absolute http(s) `external_url`. Other categories may leave it out.
+pub fn validate_external_url(
+    category: &PostCategory,
+    external_url: Option<&str>,
+) -> Result<(), PostError> {
+    if *category != PostCategory::Reading {
+        return Ok(());
+    }
+    match external_url {
+        Some(url) if !url.trim().is_empty() => {
+            if !is_valid_http_url(url) {
+                return Err(PostError::InvalidExternalUrl);
+            }
+            Ok(())
+        }
+        _ => Err(PostError::MissingExternalUrl),
+    }
+}
+
+/// Rejects content longer than `limit` bytes. The limit is configurable, so
+/// unlike the other rules this one isn't part of `validate`.
+pub fn validate_content_size(content: &str, limit: usize) -> Result<(), PostError> {
+    if content.len() > limit {
+        return Err(PostError::ContentTooLarge {
+            limit,
+            actual: content.len(),
+        });
+    }
+    Ok(())
+}
+
+/// The first instant of the month and of the month after it, in UTC, for
+/// listing what happened in between. None when `month` isn't 1-12 or the
+/// dates are out of range.
+pub fn month_bounds(year: i32, month: u8) -> Option<(OffsetDateTime, OffsetDateTime)> {
+    let month = Month::try_from(month).ok()?;
+    let start = Date::from_calendar_date(year, month, 1).ok()?;
+    let end = match month {
+        Month::December => Date::from_calendar_date(year.checked_add(1)?, Month::January, 1),
+        month => Date::from_calendar_date(year, month.next(), 1),
+    }
+    .ok()?;
+    Some((start.midnight().assume_utc(), end.midnight().assume_utc()))
+}
+
+/// Checks an ISBN-10 or ISBN-13 checksum, ignoring hyphens and spaces
+fn is_valid_isbn(isbn: &str) -> bool {
+    let chars: Vec<char> = isbn.chars().filter(|c| *c != '-' && *c != ' ').collect();
+
+    match chars.len() {
+        10 => {
+            let mut sum = 0;
+            for (i, c) in chars.iter().enumerate() {
+                let value = match c {
+                    'X' | 'x' if i == 9 => 10,
+                    c => match c.to_digit(10) {
+                        Some(d) => d,
+                        None => return false,
+                    },
+                };
+                sum += value * (10 - i as u32);
+            }
+            sum % 11 == 0
+        }
+        13 => {
+            let mut sum = 0;
+            for (i, c) in chars.iter().enumerate() {
+                let Some(d) = c.to_digit(10) else {
+                    return false;
+                };
+                sum += if i % 2 == 0 { d } else { d * 3 };
+            }
+            sum % 10 == 0
+        }
+        _ => false,
+    }
+}
+
+/// Checks that a URL is absolute and uses http or https
+fn is_valid_http_url(value: &str) -> bool {
+    url::Url::parse(value).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+/// Slugs are stored and looked up in lowercase so URLs are case-insensitive
+pub fn normalize_slug(slug: &str) -> String {
+    slug.to_ascii_lowercase()
 }
 
 fn is_valid_slug(slug: &str) -> bool {
     !slug.is_empty()
         && slug
             .chars()
-            .all(|c| (c.is_ascii_alphanumeric() || c == '-'))
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
         && !slug.starts_with('-')
         && !slug.ends_with('-')
 }
+
+/// Levenshtein distance between two slugs: the number of characters to
+/// insert, delete or replace to turn one into the other
+pub fn slug_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let replace = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = replace.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Whether `candidate` is close enough to the `requested` slug to suggest
+/// it: at most a third of the longer slug's characters may differ
+pub fn is_similar_slug(requested: &str, candidate: &str) -> bool {
+    let longer = requested.chars().count().max(candidate.chars().count());
+    slug_distance(requested, candidate) * 3 <= longer
+}
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -159,16 +921,13 @@ mod tests {
 
     // Helper function to create a valid CreatePost instance
     fn create_valid_post() -> CreatePost {
-        CreatePost {
-            category: PostCategory::Blog,
-            title: "Test Post".to_string(),
-            slug: "test-post".to_string(),
-            content: "Test content".to_string(),
-            description: "Test description".to_string(),
-            image_url: None,
-            external_url: None,
-            published: false,
-        }
+        CreatePost::builder()
+            .title("Test Post")
+            .slug("test-post")
+            .content("Test content")
+            .description("Test description")
+            .build()
+            .unwrap()
     }
 
     #[test]
@@ -206,72 +965,110 @@ mod tests {
         let mut invalid_post = create_valid_post();
         invalid_post.title = "".to_string();
         assert!(matches!(
-            invalid_post.validate(),
-            Err(PostError::EmptyTitle)
+            invalid_post.validate().unwrap_err()[..],
+            [PostError::EmptyTitle]
         ));
 
         // Test whitespace title
         let mut whitespace_post = create_valid_post();
         whitespace_post.title = "    ".to_string();
         assert!(matches!(
-            whitespace_post.validate(),
-            Err(PostError::EmptyTitle)
+            whitespace_post.validate().unwrap_err()[..],
+            [PostError::EmptyTitle]
         ));
 
         // Test empty content
         let mut no_content_post = create_valid_post();
         no_content_post.content = "".to_string();
         assert!(matches!(
-            no_content_post.validate(),
-            Err(PostError::EmptyContent)
+            no_content_post.validate().unwrap_err()[..],
+            [PostError::EmptyContent]
         ));
 
         // Test invalid slug
         let mut invalid_slug_post = create_valid_post();
         invalid_slug_post.slug = "invalid slug!".to_string();
         assert!(matches!(
-            invalid_slug_post.validate(),
-            Err(PostError::InvalidSlug)
+            invalid_slug_post.validate().unwrap_err()[..],
+            [PostError::InvalidSlug]
         ));
+
+        // Every broken rule is reported, in the order they are checked
+        let mut broken = create_valid_post();
+        broken.title = "".to_string();
+        broken.slug = "Not A Slug".to_string();
+        broken.tags = vec![
+            "ok".to_string(),
+            "bad!".to_string(),
+            "also bad?".to_string(),
+        ];
+        broken.meta_title = Some("t".repeat(MAX_META_TITLE_LEN + 1));
+        broken.rating = Some(5);
+        let errors = broken.validate().unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [
+                PostError::EmptyTitle,
+                PostError::InvalidSlug,
+                PostError::InvalidTagName(_),
+                PostError::InvalidTagName(_),
+                PostError::MetaTitleTooLong,
+                PostError::ReadingFieldsNotAllowed(_),
+            ]
+        ));
+        assert!(errors
+            .to_string()
+            .starts_with("Post title cannot be empty; Invalid slug format"));
     }
 
     #[test]
     fn test_update_post_validation() {
         // Test valid update
-        let valid_update = UpdatePost {
-            id: 1,
-            category: PostCategory::Blog,
-            title: "Updated Post".to_string(),
-            slug: "updated-post".to_string(),
-            content: "Updated content".to_string(),
-            description: "Updated description".to_string(),
-            image_url: None,
-            external_url: None,
-            published: true,
-        };
+        let valid_update = UpdatePost::builder(1)
+            .title("Updated Post")
+            .slug("updated-post")
+            .content("Updated content")
+            .description("Updated description")
+            .published(true)
+            .build()
+            .unwrap();
         assert!(valid_update.validate().is_ok());
 
+        // Switching to Reading needs an external_url
+        let mut reading = valid_update.clone();
+        reading.category = PostCategory::Reading;
+        assert!(matches!(
+            reading.validate().unwrap_err()[..],
+            [PostError::MissingExternalUrl]
+        ));
+
         // Test invalid ID
         let mut invalid_id = valid_update.clone();
         invalid_id.id = 0;
-        assert!(matches!(invalid_id.validate(), Err(PostError::InvalidId)));
+        assert!(matches!(
+            invalid_id.validate().unwrap_err()[..],
+            [PostError::InvalidId]
+        ));
 
         invalid_id.id = -1;
-        assert!(matches!(invalid_id.validate(), Err(PostError::InvalidId)));
+        assert!(matches!(
+            invalid_id.validate().unwrap_err()[..],
+            [PostError::InvalidId]
+        ));
 
         // Test empty fields
         let mut empty_fields = valid_update.clone();
         empty_fields.title = "".to_string();
         assert!(matches!(
-            empty_fields.validate(),
-            Err(PostError::EmptyTitle)
+            empty_fields.validate().unwrap_err()[..],
+            [PostError::EmptyTitle]
         ));
 
         empty_fields = valid_update.clone();
         empty_fields.content = "".to_string();
         assert!(matches!(
-            empty_fields.validate(),
-            Err(PostError::EmptyContent)
+            empty_fields.validate().unwrap_err()[..],
+            [PostError::EmptyContent]
         ));
     }
 
@@ -289,6 +1086,29 @@ mod tests {
         assert!(!is_valid_slug("ends-with-hyphen-")); // Ending hyphen
         assert!(!is_valid_slug("special!chars")); // Special characters
         assert!(!is_valid_slug("spaces not allowed")); // Spaces
+        assert!(!is_valid_slug("Mixed-Case")); // Uppercase, normalize first
+
+        assert!(is_valid_slug(&normalize_slug("Mixed-Case")));
+    }
+
+    #[test]
+    fn test_post_ref_parse() {
+        assert_eq!(PostRef::parse("42"), PostRef::Id(42));
+        assert_eq!(PostRef::parse("007"), PostRef::Id(7));
+        assert_eq!(PostRef::parse("9223372036854775807"), PostRef::Id(i64::MAX));
+
+        for slug in [
+            "my-first-post",
+            "2024-review",
+            "42a",
+            "-1",
+            "+5",
+            "9223372036854775808",
+            "\u{0664}\u{0662}", // Arabic-Indic digits
+            "",
+        ] {
+            assert_eq!(PostRef::parse(slug), PostRef::Slug(slug), "{:?}", slug);
+        }
     }
 
     #[test]
@@ -306,6 +1126,44 @@ mod tests {
         assert!(patch.published.is_none());
     }
 
+    #[test]
+    fn test_patch_post_null_handling() {
+        // Missing fields stay unchanged, explicit nulls become "clear"
+        let patch: PatchPost =
+            serde_json::from_value(serde_json::json!({ "id": 1, "image_url": null })).unwrap();
+        assert_eq!(patch.image_url, Some(None));
+        assert!(patch.external_url.is_none());
+
+        // Plain JSON semantics ignore the explicit null
+        let patch = patch.ignoring_nulls();
+        assert!(patch.image_url.is_none());
+    }
+
+    #[test]
+    fn test_patch_post_from_merge_patch() {
+        let patch = PatchPost::from_merge_patch(serde_json::json!({
+            "id": 1,
+            "title": "New Title",
+            "external_url": null
+        }))
+        .unwrap();
+        assert_eq!(patch.title.as_deref(), Some("New Title"));
+        assert_eq!(patch.external_url, Some(None));
+        assert!(patch.image_url.is_none());
+
+        // Required fields cannot be cleared
+        assert!(matches!(
+            PatchPost::from_merge_patch(serde_json::json!({ "id": 1, "title": null })),
+            Err(PostError::InvalidPatch(_))
+        ));
+
+        // The document must be an object
+        assert!(matches!(
+            PatchPost::from_merge_patch(serde_json::json!(["id", 1])),
+            Err(PostError::InvalidPatch(_))
+        ));
+    }
+
     #[test]
     fn test_post_urls() {
         // Test URL validation (if implemented)
@@ -322,6 +1180,179 @@ mod tests {
         // assert!(post.validate().is_err());
     }
 
+    #[test]
+    fn test_seo_validation() {
+        let mut post = create_valid_post();
+        post.meta_title = Some("a".repeat(MAX_META_TITLE_LEN));
+        post.meta_description = Some("a".repeat(MAX_META_DESCRIPTION_LEN));
+        post.og_image_url = Some("https://example.com/og.png".to_string());
+        assert!(post.validate().is_ok());
+
+        let mut long_title = post.clone();
+        long_title.meta_title = Some("a".repeat(MAX_META_TITLE_LEN + 1));
+        assert!(matches!(
+            long_title.validate().unwrap_err()[..],
+            [PostError::MetaTitleTooLong]
+        ));
+
+        let mut long_description = post.clone();
+        long_description.meta_description = Some("a".repeat(MAX_META_DESCRIPTION_LEN + 1));
+        assert!(matches!(
+            long_description.validate().unwrap_err()[..],
+            [PostError::MetaDescriptionTooLong]
+        ));
+
+        for invalid_url in ["not-a-url", "ftp://example.com/og.png", "/relative.png"] {
+            let mut bad_url = post.clone();
+            bad_url.og_image_url = Some(invalid_url.to_string());
+            assert!(matches!(
+                bad_url.validate().unwrap_err()[..],
+                [PostError::InvalidOgImageUrl]
+            ));
+        }
+
+        // Patches only validate the fields they set
+        let patch = PatchPost {
+            id: 1,
+            og_image_url: Some(Some("not-a-url".to_string())),
+            ..Default::default()
+        };
+        assert!(matches!(
+            patch.validate().unwrap_err()[..],
+            [PostError::InvalidOgImageUrl]
+        ));
+        let clearing = PatchPost {
+            id: 1,
+            og_image_url: Some(None),
+            ..Default::default()
+        };
+        assert!(clearing.validate().is_ok());
+    }
+
+    #[test]
+    fn test_seo_fallbacks() {
+        let now = OffsetDateTime::now_utc();
+        let mut post = Post {
+            id: 1,
+            category: PostCategory::Blog,
+            title: "Title".to_string(),
+            slug: "title".to_string(),
+            content: "Content".to_string(),
+            description: "Description".to_string(),
+            image_url: None,
+            external_url: None,
+            published: true,
+            meta_title: None,
+            meta_description: None,
+            og_image_url: None,
+            book_author: None,
+            isbn: None,
+            rating: None,
+            author_id: None,
+            created_by: None,
+            updated_by: None,
+            language: "en".to_string(),
+            translation_of: None,
+            published_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        // Missing values fall back to title and description
+        let json = serde_json::to_value(&post).unwrap();
+        assert_eq!(json["meta_title"], "Title");
+        assert_eq!(json["meta_description"], "Description");
+        assert!(json["og_image_url"].is_null());
+
+        // Explicit values win
+        post.meta_title = Some("SEO Title".to_string());
+        post.meta_description = Some("SEO Description".to_string());
+        post.og_image_url = Some("https://example.com/og.png".to_string());
+        let json = serde_json::to_value(&post).unwrap();
+        assert_eq!(json["meta_title"], "SEO Title");
+        assert_eq!(json["meta_description"], "SEO Description");
+        assert_eq!(json["og_image_url"], "https://example.com/og.png");
+    }
+
+    #[test]
+    fn test_isbn_validation() {
+        // Valid ISBN-10 and ISBN-13, with and without separators
+        assert!(is_valid_isbn("0306406152"));
+        assert!(is_valid_isbn("0-8044-2957-X"));
+        assert!(is_valid_isbn("9780306406157"));
+        assert!(is_valid_isbn("978-0-306-40615-7"));
+
+        // Bad checksums, lengths and characters
+        assert!(!is_valid_isbn("0306406153"));
+        assert!(!is_valid_isbn("9780306406158"));
+        assert!(!is_valid_isbn("12345"));
+        assert!(!is_valid_isbn("X306406152"));
+        assert!(!is_valid_isbn("978030640615a"));
+    }
+
+    #[test]
+    fn test_reading_fields_validation() {
+        let mut reading = create_valid_post();
+        reading.category = PostCategory::Reading;
+        reading.book_author = Some("Ursula K. Le Guin".to_string());
+        reading.isbn = Some("978-0-306-40615-7".to_string());
+        reading.rating = Some(5);
+        reading.external_url = Some("https://example.com/books/earthsea".to_string());
+        assert!(reading.validate().is_ok());
+
+        for rating in [0, 6] {
+            let mut bad_rating = reading.clone();
+            bad_rating.rating = Some(rating);
+            assert!(matches!(
+                bad_rating.validate().unwrap_err()[..],
+                [PostError::InvalidRating]
+            ));
+        }
+
+        let mut bad_isbn = reading.clone();
+        bad_isbn.isbn = Some("0306406153".to_string());
+        assert!(matches!(
+            bad_isbn.validate().unwrap_err()[..],
+            [PostError::InvalidIsbn]
+        ));
+
+        // Book fields are rejected outside the Reading category
+        let mut blog = reading.clone();
+        blog.category = PostCategory::Blog;
+        assert!(matches!(
+            blog.validate().unwrap_err()[..],
+            [PostError::ReadingFieldsNotAllowed(_)]
+        ));
+    }
+
+    #[test]
+    fn test_external_url_by_category() {
+        // Blog and Art posts may leave external_url out
+        for category in [PostCategory::Blog, PostCategory::Art] {
+            let mut post = create_valid_post();
+            post.category = category;
+            post.external_url = None;
+            assert!(post.validate().is_ok());
+        }
+
+        let mut reading = create_valid_post();
+        reading.category = PostCategory::Reading;
+        for missing in [None, Some(String::new()), Some("  ".to_string())] {
+            reading.external_url = missing;
+            assert!(matches!(
+                reading.validate().unwrap_err()[..],
+                [PostError::MissingExternalUrl]
+            ));
+        }
+        reading.external_url = Some("ftp://example.com/book".to_string());
+        assert!(matches!(
+            reading.validate().unwrap_err()[..],
+            [PostError::InvalidExternalUrl]
+        ));
+        reading.external_url = Some("https://example.com/book".to_string());
+        assert!(reading.validate().is_ok());
+    }
+
     #[test]
     fn test_post_timestamps() {
         let now = OffsetDateTime::now_utc();
@@ -335,6 +1366,18 @@ mod tests {
             image_url: None,
             external_url: None,
             published: false,
+            meta_title: None,
+            meta_description: None,
+            og_image_url: None,
+            book_author: None,
+            isbn: None,
+            rating: None,
+            author_id: None,
+            created_by: None,
+            updated_by: None,
+            language: "en".to_string(),
+            translation_of: None,
+            published_at: None,
             created_at: now,
             updated_at: now,
         };
@@ -342,4 +1385,129 @@ mod tests {
         assert_eq!(post.created_at, now);
         assert_eq!(post.updated_at, now);
     }
+
+    #[test]
+    fn test_timestamps_serialize_as_rfc3339() {
+        let post = Post {
+            id: 1,
+            category: PostCategory::Blog,
+            title: "Test".to_string(),
+            slug: "test".to_string(),
+            content: "Content".to_string(),
+            description: "Description".to_string(),
+            image_url: None,
+            external_url: None,
+            published: true,
+            meta_title: None,
+            meta_description: None,
+            og_image_url: None,
+            book_author: None,
+            isbn: None,
+            rating: None,
+            author_id: None,
+            created_by: None,
+            updated_by: None,
+            language: "en".to_string(),
+            translation_of: None,
+            published_at: None,
+            created_at: time::macros::datetime!(2024-01-11 10:00 UTC),
+            updated_at: time::macros::datetime!(2024-01-11 10:30:15.5 +02:00),
+        };
+
+        let json = serde_json::to_value(&post).unwrap();
+        assert_eq!(json["created_at"], "2024-01-11T10:00:00Z");
+        assert_eq!(json["updated_at"], "2024-01-11T10:30:15.5+02:00");
+    }
+
+    #[test]
+    fn test_similar_slugs() {
+        assert_eq!(slug_distance("rust-async-intro", "rust-async-intro"), 0);
+        assert_eq!(slug_distance("rust-asyn-intro", "rust-async-intro"), 1);
+        assert_eq!(slug_distance("kitten", "sitting"), 3);
+        assert_eq!(slug_distance("", "abc"), 3);
+
+        assert!(is_similar_slug("rust-asnyc-intro", "rust-async-intro"));
+        assert!(is_similar_slug("async-intro", "rust-async-intro"));
+        assert!(!is_similar_slug("cooking", "rust-async-intro"));
+        assert!(!is_similar_slug("ab", "xy"));
+    }
+
+    #[test]
+    fn test_month_bounds() {
+        use time::macros::datetime;
+
+        assert_eq!(
+            month_bounds(2024, 3),
+            Some((
+                datetime!(2024-03-01 0:00 UTC),
+                datetime!(2024-04-01 0:00 UTC)
+            ))
+        );
+        assert_eq!(
+            month_bounds(2024, 12),
+            Some((
+                datetime!(2024-12-01 0:00 UTC),
+                datetime!(2025-01-01 0:00 UTC)
+            ))
+        );
+        assert_eq!(
+            month_bounds(2024, 2).map(|(start, end)| (end - start).whole_days()),
+            Some(29)
+        );
+        assert_eq!(
+            month_bounds(2023, 2).map(|(start, end)| (end - start).whole_days()),
+            Some(28)
+        );
+        assert_eq!(month_bounds(2024, 0), None);
+        assert_eq!(month_bounds(2024, 13), None);
+        assert_eq!(month_bounds(9999, 12), None);
+    }
+
+    #[test]
+    fn test_same_fields() {
+        let post = Post {
+            id: 1,
+            category: PostCategory::Blog,
+            title: "Test".to_string(),
+            slug: "test".to_string(),
+            content: "Content".to_string(),
+            description: "Description".to_string(),
+            image_url: None,
+            external_url: Some("https://example.com".to_string()),
+            published: true,
+            meta_title: None,
+            meta_description: None,
+            og_image_url: None,
+            book_author: None,
+            isbn: None,
+            rating: None,
+            author_id: None,
+            created_by: None,
+            updated_by: None,
+            language: "en".to_string(),
+            translation_of: None,
+            published_at: None,
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+        };
+
+        let touched = Post {
+            id: 2,
+            image_url: Some(String::new()),
+            updated_at: OffsetDateTime::now_utc(),
+            ..post.clone()
+        };
+        assert!(post.same_fields(&touched));
+
+        let retitled = Post {
+            title: "Other".to_string(),
+            ..post.clone()
+        };
+        assert!(!post.same_fields(&retitled));
+        let unlinked = Post {
+            external_url: Some(String::new()),
+            ..post.clone()
+        };
+        assert!(!post.same_fields(&unlinked));
+    }
 }
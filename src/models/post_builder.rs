@@ -0,0 +1,629 @@
+//! Fluent builders for the post request models, so callers name only the
+//! fields they care about. Like the repository, `build` lowercases the slug
+//! and then validates, apart from the configurable content size limit.
+
+use time::OffsetDateTime;
+
+use super::{
+    errors::{PostError, ValidationErrors},
+    post::{normalize_slug, CreatePost, PatchPost, PostCategory, UpdatePost, DEFAULT_LANGUAGE},
+};
+
+/// Builds a `CreatePost`; see `CreatePost::builder`
+#[derive(Debug, Clone)]
+pub struct CreatePostBuilder {
+    post: CreatePost,
+}
+
+impl CreatePost {
+    /// Starts an unpublished Blog post in the default language with every
+    /// optional field unset. The title, slug, content and description have
+    /// no defaults and must be set before `build` succeeds.
+    ///
+    /// ```
+    /// use server::models::post::{CreatePost, PostCategory};
+    ///
+    /// let post = CreatePost::builder()
+    ///     .category(PostCategory::Art)
+    ///     .title("Sketches")
+    ///     .slug("sketches")
+    ///     .content("Some sketches from the weekend")
+    ///     .description("Weekend sketches")
+    ///     .published(true)
+    ///     .tag("drawing")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(post.tags, ["drawing"]);
+    ///
+    /// // Validation runs on build
+    /// assert!(CreatePost::builder().title("No slug").build().is_err());
+    /// ```
+    pub fn builder() -> CreatePostBuilder {
+        CreatePostBuilder {
+            post: CreatePost {
+                category: PostCategory::Blog,
+                title: String::new(),
+                slug: String::new(),
+                content: String::new(),
+                description: String::new(),
+                image_url: None,
+                external_url: None,
+                published: false,
+                meta_title: None,
+                meta_description: None,
+                og_image_url: None,
+                book_author: None,
+                isbn: None,
+                rating: None,
+                language: DEFAULT_LANGUAGE.to_string(),
+                translation_of: None,
+                published_at: None,
+                tags: Vec::new(),
+            },
+        }
+    }
+}
+
+impl CreatePostBuilder {
+    pub fn category(mut self, category: PostCategory) -> Self {
+        self.post.category = category;
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.post.title = title.into();
+        self
+    }
+
+    pub fn slug(mut self, slug: impl Into<String>) -> Self {
+        self.post.slug = slug.into();
+        self
+    }
+
+    pub fn content(mut self, content: impl Into<String>) -> Self {
+        self.post.content = content.into();
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.post.description = description.into();
+        self
+    }
+
+    pub fn image_url(mut self, image_url: impl Into<String>) -> Self {
+        self.post.image_url = Some(image_url.into());
+        self
+    }
+
+    pub fn external_url(mut self, external_url: impl Into<String>) -> Self {
+        self.post.external_url = Some(external_url.into());
+        self
+    }
+
+    pub fn meta_title(mut self, meta_title: impl Into<String>) -> Self {
+        self.post.meta_title = Some(meta_title.into());
+        self
+    }
+
+    pub fn meta_description(mut self, meta_description: impl Into<String>) -> Self {
+        self.post.meta_description = Some(meta_description.into());
+        self
+    }
+
+    pub fn og_image_url(mut self, og_image_url: impl Into<String>) -> Self {
+        self.post.og_image_url = Some(og_image_url.into());
+        self
+    }
+
+    pub fn book_author(mut self, book_author: impl Into<String>) -> Self {
+        self.post.book_author = Some(book_author.into());
+        self
+    }
+
+    pub fn isbn(mut self, isbn: impl Into<String>) -> Self {
+        self.post.isbn = Some(isbn.into());
+        self
+    }
+
+    pub fn published(mut self, published: bool) -> Self {
+        self.post.published = published;
+        self
+    }
+
+    pub fn rating(mut self, rating: i64) -> Self {
+        self.post.rating = Some(rating);
+        self
+    }
+
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.post.language = language.into();
+        self
+    }
+
+    pub fn translation_of(mut self, original: i64) -> Self {
+        self.post.translation_of = Some(original);
+        self
+    }
+
+    pub fn published_at(mut self, published_at: OffsetDateTime) -> Self {
+        self.post.published_at = Some(published_at);
+        self
+    }
+
+    /// Adds a tag to attach by name
+    pub fn tag(mut self, name: impl Into<String>) -> Self {
+        self.post.tags.push(name.into());
+        self
+    }
+
+    /// Replaces the tags to attach
+    pub fn tags(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.post.tags = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Lowercases the slug, validates the post and returns it
+    pub fn build(mut self) -> Result<CreatePost, ValidationErrors<PostError>> {
+        self.post.slug = normalize_slug(&self.post.slug);
+        self.post.validate()?;
+        Ok(self.post)
+    }
+}
+
+/// Builds an `UpdatePost`; see `UpdatePost::builder`
+#[derive(Debug, Clone)]
+pub struct UpdatePostBuilder {
+    update: UpdatePost,
+}
+
+impl UpdatePost {
+    /// Starts a full update of post `id`, with the same defaults as
+    /// `CreatePost::builder`. Fields left unset are cleared by the update.
+    ///
+    /// ```
+    /// use server::models::post::UpdatePost;
+    ///
+    /// let update = UpdatePost::builder(1)
+    ///     .title("Hello again")
+    ///     .slug("hello-again")
+    ///     .content("Updated content")
+    ///     .description("Updated description")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(update.language, "en");
+    /// assert!(UpdatePost::builder(0).build().is_err());
+    /// ```
+    pub fn builder(id: i64) -> UpdatePostBuilder {
+        let CreatePost {
+            category,
+            title,
+            slug,
+            content,
+            description,
+            image_url,
+            external_url,
+            published,
+            meta_title,
+            meta_description,
+            og_image_url,
+            book_author,
+            isbn,
+            rating,
+            language,
+            translation_of,
+            published_at,
+            tags: _,
+        } = CreatePost::builder().post;
+        UpdatePostBuilder {
+            update: UpdatePost {
+                id,
+                category,
+                title,
+                slug,
+                content,
+                description,
+                image_url,
+                external_url,
+                published,
+                meta_title,
+                meta_description,
+                og_image_url,
+                book_author,
+                isbn,
+                rating,
+                language,
+                translation_of,
+                published_at,
+            },
+        }
+    }
+}
+
+impl UpdatePostBuilder {
+    pub fn category(mut self, category: PostCategory) -> Self {
+        self.update.category = category;
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.update.title = title.into();
+        self
+    }
+
+    pub fn slug(mut self, slug: impl Into<String>) -> Self {
+        self.update.slug = slug.into();
+        self
+    }
+
+    pub fn content(mut self, content: impl Into<String>) -> Self {
+        self.update.content = content.into();
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.update.description = description.into();
+        self
+    }
+
+    pub fn image_url(mut self, image_url: impl Into<String>) -> Self {
+        self.update.image_url = Some(image_url.into());
+        self
+    }
+
+    pub fn external_url(mut self, external_url: impl Into<String>) -> Self {
+        self.update.external_url = Some(external_url.into());
+        self
+    }
+
+    pub fn meta_title(mut self, meta_title: impl Into<String>) -> Self {
+        self.update.meta_title = Some(meta_title.into());
+        self
+    }
+
+    pub fn meta_description(mut self, meta_description: impl Into<String>) -> Self {
+        self.update.meta_description = Some(meta_description.into());
+        self
+    }
+
+    pub fn og_image_url(mut self, og_image_url: impl Into<String>) -> Self {
+        self.update.og_image_url = Some(og_image_url.into());
+        self
+    }
+
+    pub fn book_author(mut self, book_author: impl Into<String>) -> Self {
+        self.update.book_author = Some(book_author.into());
+        self
+    }
+
+    pub fn isbn(mut self, isbn: impl Into<String>) -> Self {
+        self.update.isbn = Some(isbn.into());
+        self
+    }
+
+    pub fn published(mut self, published: bool) -> Self {
+        self.update.published = published;
+        self
+    }
+
+    pub fn rating(mut self, rating: i64) -> Self {
+        self.update.rating = Some(rating);
+        self
+    }
+
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.update.language = language.into();
+        self
+    }
+
+    pub fn translation_of(mut self, original: i64) -> Self {
+        self.update.translation_of = Some(original);
+        self
+    }
+
+    pub fn published_at(mut self, published_at: OffsetDateTime) -> Self {
+        self.update.published_at = Some(published_at);
+        self
+    }
+
+    /// Lowercases the slug, validates the update and returns it
+    pub fn build(mut self) -> Result<UpdatePost, ValidationErrors<PostError>> {
+        self.update.slug = normalize_slug(&self.update.slug);
+        self.update.validate()?;
+        Ok(self.update)
+    }
+}
+
+/// Builds a `PatchPost`; see `PatchPost::builder`
+#[derive(Debug, Clone)]
+pub struct PatchPostBuilder {
+    patch: PatchPost,
+}
+
+impl PatchPost {
+    /// Starts a patch of post `id` that changes nothing. Setters change a
+    /// field, and the `clear_` ones unset an optional field.
+    ///
+    /// ```
+    /// use server::models::post::PatchPost;
+    ///
+    /// let patch = PatchPost::builder(1)
+    ///     .title("New title")
+    ///     .clear_image_url()
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(patch.title.as_deref(), Some("New title"));
+    /// assert_eq!(patch.image_url, Some(None));
+    /// assert!(patch.slug.is_none());
+    /// ```
+    pub fn builder(id: i64) -> PatchPostBuilder {
+        PatchPostBuilder {
+            patch: PatchPost {
+                id,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl PatchPostBuilder {
+    pub fn category(mut self, category: PostCategory) -> Self {
+        self.patch.category = Some(category);
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.patch.title = Some(title.into());
+        self
+    }
+
+    pub fn slug(mut self, slug: impl Into<String>) -> Self {
+        self.patch.slug = Some(slug.into());
+        self
+    }
+
+    pub fn content(mut self, content: impl Into<String>) -> Self {
+        self.patch.content = Some(content.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.patch.description = Some(description.into());
+        self
+    }
+
+    pub fn image_url(mut self, image_url: impl Into<String>) -> Self {
+        self.patch.image_url = Some(Some(image_url.into()));
+        self
+    }
+
+    pub fn clear_image_url(mut self) -> Self {
+        self.patch.image_url = Some(None);
+        self
+    }
+
+    pub fn external_url(mut self, external_url: impl Into<String>) -> Self {
+        self.patch.external_url = Some(Some(external_url.into()));
+        self
+    }
+
+    pub fn clear_external_url(mut self) -> Self {
+        self.patch.external_url = Some(None);
+        self
+    }
+
+    pub fn meta_title(mut self, meta_title: impl Into<String>) -> Self {
+        self.patch.meta_title = Some(Some(meta_title.into()));
+        self
+    }
+
+    pub fn clear_meta_title(mut self) -> Self {
+        self.patch.meta_title = Some(None);
+        self
+    }
+
+    pub fn meta_description(mut self, meta_description: impl Into<String>) -> Self {
+        self.patch.meta_description = Some(Some(meta_description.into()));
+        self
+    }
+
+    pub fn clear_meta_description(mut self) -> Self {
+        self.patch.meta_description = Some(None);
+        self
+    }
+
+    pub fn og_image_url(mut self, og_image_url: impl Into<String>) -> Self {
+        self.patch.og_image_url = Some(Some(og_image_url.into()));
+        self
+    }
+
+    pub fn clear_og_image_url(mut self) -> Self {
+        self.patch.og_image_url = Some(None);
+        self
+    }
+
+    pub fn book_author(mut self, book_author: impl Into<String>) -> Self {
+        self.patch.book_author = Some(Some(book_author.into()));
+        self
+    }
+
+    pub fn clear_book_author(mut self) -> Self {
+        self.patch.book_author = Some(None);
+        self
+    }
+
+    pub fn isbn(mut self, isbn: impl Into<String>) -> Self {
+        self.patch.isbn = Some(Some(isbn.into()));
+        self
+    }
+
+    pub fn clear_isbn(mut self) -> Self {
+        self.patch.isbn = Some(None);
+        self
+    }
+
+    pub fn published(mut self, published: bool) -> Self {
+        self.patch.published = Some(published);
+        self
+    }
+
+    pub fn rating(mut self, rating: i64) -> Self {
+        self.patch.rating = Some(Some(rating));
+        self
+    }
+
+    pub fn clear_rating(mut self) -> Self {
+        self.patch.rating = Some(None);
+        self
+    }
+
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.patch.language = Some(language.into());
+        self
+    }
+
+    pub fn translation_of(mut self, original: i64) -> Self {
+        self.patch.translation_of = Some(Some(original));
+        self
+    }
+
+    pub fn clear_translation_of(mut self) -> Self {
+        self.patch.translation_of = Some(None);
+        self
+    }
+
+    pub fn published_at(mut self, published_at: OffsetDateTime) -> Self {
+        self.patch.published_at = Some(published_at);
+        self
+    }
+
+    /// Lowercases the slug, validates the fields being changed and returns
+    /// the patch
+    pub fn build(mut self) -> Result<PatchPost, ValidationErrors<PostError>> {
+        self.patch.slug = self.patch.slug.as_deref().map(normalize_slug);
+        self.patch.validate()?;
+        Ok(self.patch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valid_post() -> CreatePostBuilder {
+        CreatePost::builder()
+            .title("Test Post")
+            .slug("test-post")
+            .content("Test content")
+            .description("Test description")
+    }
+
+    #[test]
+    fn test_create_builder() {
+        let post = valid_post()
+            .category(PostCategory::Reading)
+            .external_url("https://example.com/book")
+            .book_author("Ursula K. Le Guin")
+            .rating(5)
+            .tags(["books", "fantasy"])
+            .build()
+            .unwrap();
+        assert_eq!(post.category, PostCategory::Reading);
+        assert_eq!(post.book_author.as_deref(), Some("Ursula K. Le Guin"));
+        assert_eq!(post.rating, Some(5));
+        assert_eq!(post.tags, ["books", "fantasy"]);
+        assert_eq!(post.language, DEFAULT_LANGUAGE);
+        assert!(!post.published);
+        assert!(post.image_url.is_none());
+    }
+
+    #[test]
+    fn test_create_builder_validates() {
+        assert!(matches!(
+            CreatePost::builder().build().unwrap_err()[..],
+            [PostError::EmptyTitle, ..]
+        ));
+        assert!(matches!(
+            valid_post().slug("not a slug").build().unwrap_err()[..],
+            [PostError::InvalidSlug]
+        ));
+        // Slugs are lowercased first, as the repository does
+        assert_eq!(
+            valid_post().slug("Test-Post").build().unwrap().slug,
+            "test-post"
+        );
+        assert!(matches!(
+            valid_post().rating(3).build().unwrap_err()[..],
+            [PostError::ReadingFieldsNotAllowed(_)]
+        ));
+        assert!(matches!(
+            valid_post()
+                .category(PostCategory::Reading)
+                .build()
+                .unwrap_err()[..],
+            [PostError::MissingExternalUrl]
+        ));
+        assert!(matches!(
+            valid_post().tag("").build().unwrap_err()[..],
+            [PostError::InvalidTagName(_)]
+        ));
+    }
+
+    #[test]
+    fn test_update_builder_validates() {
+        let update = UpdatePost::builder(3)
+            .title("Updated")
+            .slug("updated")
+            .content("Updated content")
+            .description("Updated description")
+            .published(true)
+            .build()
+            .unwrap();
+        assert_eq!(update.id, 3);
+        assert!(update.published);
+
+        assert!(matches!(
+            UpdatePost::builder(0).build().unwrap_err()[..],
+            [PostError::InvalidId, ..]
+        ));
+        assert!(matches!(
+            UpdatePost::builder(3).build().unwrap_err()[..],
+            [PostError::EmptyTitle, ..]
+        ));
+    }
+
+    #[test]
+    fn test_patch_builder() {
+        let patch = PatchPost::builder(3)
+            .content("New content")
+            .meta_title("SEO title")
+            .clear_og_image_url()
+            .clear_rating()
+            .build()
+            .unwrap();
+        assert_eq!(patch.content.as_deref(), Some("New content"));
+        assert_eq!(patch.meta_title, Some(Some("SEO title".to_string())));
+        assert_eq!(patch.og_image_url, Some(None));
+        assert_eq!(patch.rating, Some(None));
+        assert!(patch.title.is_none());
+        assert!(patch.image_url.is_none());
+
+        assert!(matches!(
+            PatchPost::builder(3)
+                .slug("not a slug")
+                .build()
+                .unwrap_err()[..],
+            [PostError::InvalidSlug]
+        ));
+        assert!(matches!(
+            PatchPost::builder(3).translation_of(3).build().unwrap_err()[..],
+            [PostError::InvalidTranslation(_)]
+        ));
+        assert!(matches!(
+            PatchPost::builder(3)
+                .og_image_url("not-a-url")
+                .build()
+                .unwrap_err()[..],
+            [PostError::InvalidOgImageUrl]
+        ));
+    }
+}
@@ -0,0 +1,101 @@
+use serde::Serialize;
+use time::{Date, Month, OffsetDateTime};
+
+use super::post::PostCategory;
+
+/// Most tags listed in a year's statistics
+pub const TOP_TAGS: i64 = 10;
+
+/// Figures for a year in review, counting the posts published that year
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct YearStats {
+    pub year: i32,
+    pub posts: i64,
+    /// Every category, including those without posts
+    pub by_category: Vec<CategoryCount>,
+    /// Words across all of the year's posts
+    pub words: i64,
+    /// The year's most used tags, most posts first and then by name
+    pub top_tags: Vec<TagCount>,
+    /// The post with the most words, or null in a year without posts
+    pub longest_post: Option<LongestPost>,
+}
+
+/// Number of a year's posts in one category
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CategoryCount {
+    pub category: PostCategory,
+    pub posts: i64,
+}
+
+/// Number of a year's posts carrying a tag
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TagCount {
+    pub name: String,
+    pub posts: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LongestPost {
+    pub id: i64,
+    pub title: String,
+    pub slug: String,
+    pub words: i64,
+}
+
+impl YearStats {
+    /// Statistics for a year without posts: every figure is zero
+    pub fn empty(year: i32) -> Self {
+        Self {
+            year,
+            posts: 0,
+            by_category: PostCategory::all()
+                .into_iter()
+                .map(|category| CategoryCount { category, posts: 0 })
+                .collect(),
+            words: 0,
+            top_tags: Vec::new(),
+            longest_post: None,
+        }
+    }
+}
+
+/// Number of whitespace-separated words in `text`
+pub fn word_count(text: &str) -> i64 {
+    text.split_whitespace().count() as i64
+}
+
+/// The first instant of the year and of the year after it, in UTC. None
+/// when the dates are out of range.
+pub fn year_bounds(year: i32) -> Option<(OffsetDateTime, OffsetDateTime)> {
+    let start = Date::from_calendar_date(year, Month::January, 1).ok()?;
+    let end = Date::from_calendar_date(year.checked_add(1)?, Month::January, 1).ok()?;
+    Some((start.midnight().assume_utc(), end.midnight().assume_utc()))
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    #[test]
+    fn test_word_count() {
+        assert_eq!(word_count(""), 0);
+        assert_eq!(word_count("  \n "), 0);
+        assert_eq!(word_count("one"), 1);
+        assert_eq!(word_count("Hello,  world!\nSecond\tline"), 4);
+    }
+
+    #[test]
+    fn test_year_bounds() {
+        assert_eq!(
+            year_bounds(2024),
+            Some((
+                datetime!(2024-01-01 0:00 UTC),
+                datetime!(2025-01-01 0:00 UTC)
+            ))
+        );
+        assert_eq!(year_bounds(9999), None);
+    }
+}
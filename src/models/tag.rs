@@ -2,12 +2,35 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use time::OffsetDateTime;
 
+use super::{
+    errors::{TagError, ValidationErrors},
+    post::double_option,
+};
+
+/// Maximum length of a tag description, in characters
+pub const MAX_TAG_DESCRIPTION_LEN: usize = 300;
+
+/// Outcome of looking up a tag by a slug that may have been renamed
+#[derive(Debug)]
+pub enum TagSlugLookup {
+    /// The slug is the tag's current slug
+    Found(Tag),
+    /// The slug belonged to a tag that has since been renamed to this slug
+    Moved(String),
+}
+
 /// Represents a tag in the database
-#[derive(Debug, FromRow, Serialize, Deserialize)]
+#[derive(Clone, Debug, FromRow, Serialize, Deserialize)]
 pub struct Tag {
     pub id: i64,
     pub name: String,
+    /// URL-safe form of the name, unique across tags. See `tag_slug`.
+    pub slug: String,
+    #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
+    pub description: Option<String>,
+    /// Hex color such as `#1a2b3c`, always lowercase
+    pub color: Option<String>,
 }
 
 /// Extended tag information including the count of associated posts
@@ -16,10 +39,160 @@ pub struct Tag {
 pub struct TagWithPostCount {
     pub id: i64,
     pub name: String,
+    pub slug: String,
+    #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
+    pub description: Option<String>,
+    pub color: Option<String>,
     pub post_count: i64,
 }
 
+/// A tag that shares posts with another tag, with the number of shared posts
+#[derive(Debug, FromRow, Serialize)]
+pub struct RelatedTag {
+    pub id: i64,
+    pub name: String,
+    pub slug: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub description: Option<String>,
+    pub color: Option<String>,
+    pub shared_post_count: i64,
+}
+
+/// Request body for creating or replacing a tag
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TagRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+impl TagRequest {
+    /// Request for a tag with only a name
+    pub fn named(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// The request with its name normalized by `normalize_tag_name`
+    pub fn normalized(&self, lowercase: bool) -> Self {
+        Self {
+            name: normalize_tag_name(&self.name, lowercase),
+            ..self.clone()
+        }
+    }
+
+    /// Validates all fields of the request, reporting every broken rule
+    pub fn validate(&self) -> Result<(), ValidationErrors<TagError>> {
+        let mut errors = Vec::new();
+        if !Tag::is_valid_name(&self.name) {
+            errors.push(TagError::InvalidName);
+        }
+        validate_details(
+            self.description.as_deref(),
+            self.color.as_deref(),
+            &mut errors,
+        );
+        ValidationErrors::check(errors)
+    }
+}
+
+/// Sort orders for tag listings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagSort {
+    /// Alphabetical by name
+    #[default]
+    Name,
+    /// Most used first, then by name
+    PostCount,
+    /// Newest first, then by name
+    CreatedAt,
+}
+
+impl TagSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TagSort::Name => "name",
+            TagSort::PostCount => "post_count",
+            TagSort::CreatedAt => "created_at",
+        }
+    }
+}
+
+/// Filtering, sorting and pagination options for listing tags
+#[derive(Debug, Clone)]
+pub struct TagListOptions {
+    /// When false, `post_count` is reported as 0
+    pub include_post_count: bool,
+    /// Only tags whose name contains this substring, ignoring case
+    pub search: Option<String>,
+    pub sort: TagSort,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl Default for TagListOptions {
+    fn default() -> Self {
+        Self {
+            include_post_count: false,
+            search: None,
+            sort: TagSort::default(),
+            limit: 50,
+            offset: 0,
+        }
+    }
+}
+
+/// Partial update of a tag.
+///
+/// Fields left as `None` are unchanged; for the nullable fields an explicit
+/// null (`Some(None)`) clears the value.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatchTag {
+    pub name: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub description: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub color: Option<Option<String>>,
+}
+
+impl PatchTag {
+    /// Validates the fields being set by this patch, reporting every
+    /// broken rule
+    pub fn validate(&self) -> Result<(), ValidationErrors<TagError>> {
+        let mut errors = Vec::new();
+        if self
+            .name
+            .as_deref()
+            .is_some_and(|name| !Tag::is_valid_name(name))
+        {
+            errors.push(TagError::InvalidName);
+        }
+        validate_details(
+            self.description.as_ref().and_then(Option::as_deref),
+            self.color.as_ref().and_then(Option::as_deref),
+            &mut errors,
+        );
+        ValidationErrors::check(errors)
+    }
+}
+
 impl Tag {
     /// Validates a tag name
     /// Returns true if the name is valid, false otherwise
@@ -31,7 +204,71 @@ impl Tag {
                 c.is_ascii_alphanumeric() || c.is_whitespace() || c == '-' || c == '_' || c == '+'
             })
     }
+
+    /// Checks for a six digit hex color such as `#1a2b3c`, in either case.
+    /// Shorthand like `#abc` is not accepted.
+    pub fn is_valid_color(color: &str) -> bool {
+        color.len() == 7
+            && color.starts_with('#')
+            && color[1..].chars().all(|c| c.is_ascii_hexdigit())
+    }
+}
+
+/// Puts a tag name in the form it is stored and looked up in: trimmed, with
+/// every run of whitespace inside it collapsed to a single space, and in
+/// lowercase when `lowercase` is set. `Front  End` and `front end` are
+/// then the same tag.
+pub fn normalize_tag_name(name: &str, lowercase: bool) -> String {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if lowercase {
+        name.to_ascii_lowercase()
+    } else {
+        name
+    }
+}
+
+/// URL-safe form of a tag name: lowercase ASCII letters and digits, with
+/// `+` spelled out as `plus` and every other run of characters turned into a
+/// single hyphen. `C++` becomes `c-plus-plus` and `Front End` becomes
+/// `front-end`. The `tag_slugs` migration applies the same rules in SQL.
+pub fn tag_slug(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            'a'..='z' | '0'..='9' => slug.push(c),
+            'A'..='Z' => slug.push(c.to_ascii_lowercase()),
+            '+' => slug.push_str("-plus-"),
+            _ => slug.push('-'),
+        }
+    }
+    let slug = slug
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        "tag".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Lowercases a color so equal colors are stored the same way
+pub fn normalize_color(color: &str) -> String {
+    color.to_ascii_lowercase()
 }
+
+/// Validates the optional presentation fields shared by tag models,
+/// adding the broken rules to `errors`
+fn validate_details(description: Option<&str>, color: Option<&str>, errors: &mut Vec<TagError>) {
+    if description.is_some_and(|d| d.chars().count() > MAX_TAG_DESCRIPTION_LEN) {
+        errors.push(TagError::DescriptionTooLong);
+    }
+    if color.is_some_and(|c| !Tag::is_valid_color(c)) {
+        errors.push(TagError::InvalidColor);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,7 +361,10 @@ mod tests {
         let tag_with_count = TagWithPostCount {
             id: 1,
             name: "test".to_string(),
+            slug: "test".to_string(),
             created_at: now,
+            description: None,
+            color: None,
             post_count: 5,
         };
 
@@ -147,6 +387,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_normalize_tag_name() {
+        assert_eq!(normalize_tag_name("Front  End", false), "Front End");
+        assert_eq!(normalize_tag_name(" front\t\nend ", false), "front end");
+        assert_eq!(normalize_tag_name("rust", false), "rust");
+        assert_eq!(normalize_tag_name("   ", false), "");
+        assert_eq!(normalize_tag_name("Front  End", true), "front end");
+        assert_eq!(normalize_tag_name("C++", true), "c++");
+
+        let request = TagRequest {
+            color: Some("#1a2b3c".to_string()),
+            ..TagRequest::named("  Web   Dev ")
+        };
+        let normalized = request.normalized(false);
+        assert_eq!(normalized.name, "Web Dev");
+        assert_eq!(normalized.color, request.color);
+    }
+
+    #[test]
+    fn test_tag_slug() {
+        assert_eq!(tag_slug("rust"), "rust");
+        assert_eq!(tag_slug("C++"), "c-plus-plus");
+        assert_eq!(tag_slug("Front End"), "front-end");
+        assert_eq!(tag_slug("ruby_on_rails"), "ruby-on-rails");
+        assert_eq!(tag_slug("  web -- dev  "), "web-dev");
+        assert_eq!(tag_slug("Vue3"), "vue3");
+        assert_eq!(tag_slug("+"), "plus");
+        assert_eq!(tag_slug("日本語"), "tag");
+        assert_eq!(tag_slug("---"), "tag");
+    }
+
     #[test]
     fn test_tag_name_unicode() {
         // Test Unicode character handling
@@ -160,4 +431,62 @@ mod tests {
         );
         assert!(!Tag::is_valid_name("🏷️"), "Emoji should be invalid");
     }
+
+    #[test]
+    fn test_tag_color_validation() {
+        assert!(Tag::is_valid_color("#1a2b3c"));
+        assert!(Tag::is_valid_color("#1A2B3C"));
+
+        for invalid in ["#abc", "1a2b3c", "#1a2b3g", "#1a2b3c4", "", "#", "#１a2b3c"] {
+            assert!(
+                !Tag::is_valid_color(invalid),
+                "{:?} should be invalid",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_tag_request_validation() {
+        assert!(TagRequest::named("rust").validate().is_ok());
+
+        let long_description = TagRequest {
+            description: Some("a".repeat(MAX_TAG_DESCRIPTION_LEN + 1)),
+            ..TagRequest::named("rust")
+        };
+        assert!(matches!(
+            long_description.validate().unwrap_err()[..],
+            [TagError::DescriptionTooLong]
+        ));
+
+        let shorthand = TagRequest {
+            color: Some("#abc".to_string()),
+            ..TagRequest::named("rust")
+        };
+        assert!(matches!(
+            shorthand.validate().unwrap_err()[..],
+            [TagError::InvalidColor]
+        ));
+
+        let everything_wrong = TagRequest {
+            description: Some("a".repeat(MAX_TAG_DESCRIPTION_LEN + 1)),
+            color: Some("red".to_string()),
+            ..TagRequest::named("no!")
+        };
+        assert!(matches!(
+            everything_wrong.validate().unwrap_err()[..],
+            [
+                TagError::InvalidName,
+                TagError::DescriptionTooLong,
+                TagError::InvalidColor
+            ]
+        ));
+
+        // Clearing a color in a patch needs no validation
+        let clearing = PatchTag {
+            color: Some(None),
+            ..Default::default()
+        };
+        assert!(clearing.validate().is_ok());
+    }
 }
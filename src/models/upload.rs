@@ -0,0 +1,8 @@
+use serde::Serialize;
+
+/// Where an uploaded image can be fetched, ready to use as a post's
+/// `image_url`
+#[derive(Debug, Serialize)]
+pub struct Upload {
+    pub url: String,
+}
@@ -1,12 +1,271 @@
+use std::str::FromStr;
+
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use time::OffsetDateTime;
+
+use super::errors::UserError;
+
+/// Minimum length of a password, in characters
+pub const MIN_PASSWORD_LEN: usize = 8;
+
+/// A user account as stored in the database
+#[derive(Debug, Clone, FromRow, Serialize)]
 pub struct User {
     pub id: i64,
     pub email: String,
+    /// Argon2 PHC string; never sent to clients
+    #[serde(skip_serializing)]
+    pub password_hash: String,
     pub role: UserRole,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
 }
 
+/// What a user is allowed to do
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
 pub enum UserRole {
     Admin,
+    #[default]
     User,
 }
 
-impl User {}
+// This lets us convert strings into UserRole values
+impl FromStr for UserRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "admin" => Ok(UserRole::Admin),
+            "user" => Ok(UserRole::User),
+            _ => Err(format!("Invalid user role: {}", s)),
+        }
+    }
+}
+
+// This lets us convert UserRole values into strings
+impl std::fmt::Display for UserRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            UserRole::Admin => "admin",
+            UserRole::User => "user",
+        };
+        f.write_str(s)
+    }
+}
+
+// This handles converting from String to UserRole for database operations
+impl TryFrom<String> for UserRole {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::from_str(&value)
+    }
+}
+
+// This lets SQLx convert from UserRole to String for database storage
+impl From<UserRole> for String {
+    fn from(role: UserRole) -> String {
+        role.to_string()
+    }
+}
+
+/// Request body for creating a user
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateUser {
+    pub email: String,
+    pub password: String,
+    #[serde(default)]
+    pub role: UserRole,
+}
+
+impl CreateUser {
+    pub fn validate(&self) -> Result<(), UserError> {
+        validate_email(&self.email)?;
+        validate_password(&self.password)
+    }
+
+    /// Hashes the password with Argon2 and a random salt
+    pub async fn password_hash(&self) -> Result<String, UserError> {
+        hash_password(&self.password).await
+    }
+}
+
+/// Partial update of a user; fields left as `None` are unchanged
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateUser {
+    pub email: Option<String>,
+    pub password: Option<String>,
+    pub role: Option<UserRole>,
+}
+
+impl UpdateUser {
+    /// Validates the fields being set by this update
+    pub fn validate(&self) -> Result<(), UserError> {
+        if let Some(email) = &self.email {
+            validate_email(email)?;
+        }
+        if let Some(password) = &self.password {
+            validate_password(password)?;
+        }
+        Ok(())
+    }
+}
+
+/// Trims and lowercases an email address so lookups are case-insensitive
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Hashes a password into an Argon2 PHC string with a random salt, on the
+/// blocking thread pool like `verify_password`
+pub async fn hash_password(password: &str) -> Result<String, UserError> {
+    let password = password.to_owned();
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| UserError::PasswordHash(e.to_string()))
+    })
+    .await
+    .map_err(|e| UserError::PasswordHash(e.to_string()))?
+}
+
+/// Checks a password against an Argon2 PHC string. Malformed hashes never match.
+///
+/// Argon2 is slow on purpose, so it runs on the blocking thread pool rather
+/// than stalling the async worker and every request scheduled on it.
+pub async fn verify_password(password: &str, password_hash: &str) -> bool {
+    let (password, password_hash) = (password.to_owned(), password_hash.to_owned());
+    tokio::task::spawn_blocking(move || {
+        PasswordHash::new(&password_hash).is_ok_and(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+    })
+    .await
+    .unwrap_or(false)
+}
+
+fn validate_email(email: &str) -> Result<(), UserError> {
+    if is_valid_email(email) {
+        Ok(())
+    } else {
+        Err(UserError::InvalidEmail)
+    }
+}
+
+/// Loose structural check: something before and after a single `@`, and
+/// a dot in the domain. Deliverability is not our concern here.
+pub(crate) fn is_valid_email(email: &str) -> bool {
+    let email = email.trim();
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !email.chars().any(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+fn validate_password(password: &str) -> Result<(), UserError> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(UserError::PasswordTooShort);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_user() -> CreateUser {
+        CreateUser {
+            email: "writer@example.com".to_string(),
+            password: "correct horse".to_string(),
+            role: UserRole::User,
+        }
+    }
+
+    #[test]
+    fn test_user_role_conversion() {
+        assert_eq!(UserRole::from_str("Admin").unwrap(), UserRole::Admin);
+        assert_eq!(UserRole::User.to_string(), "user");
+        assert!(UserRole::from_str("owner").is_err());
+        assert_eq!(UserRole::default(), UserRole::User);
+    }
+
+    #[test]
+    fn test_create_user_validation() {
+        assert!(create_user().validate().is_ok());
+
+        for email in [
+            "",
+            "writer",
+            "@example.com",
+            "writer@example",
+            "a b@example.com",
+        ] {
+            let user = CreateUser {
+                email: email.to_string(),
+                ..create_user()
+            };
+            assert!(
+                matches!(user.validate(), Err(UserError::InvalidEmail)),
+                "{:?} should be rejected",
+                email
+            );
+        }
+
+        let short = CreateUser {
+            password: "short".to_string(),
+            ..create_user()
+        };
+        assert!(matches!(short.validate(), Err(UserError::PasswordTooShort)));
+    }
+
+    #[tokio::test]
+    async fn test_password_hashing() {
+        let user = create_user();
+        let hash = user.password_hash().await.unwrap();
+        assert!(hash.starts_with("$argon2"));
+
+        // Salts are random, so the same password never hashes the same way
+        assert_ne!(hash, user.password_hash().await.unwrap());
+
+        assert!(verify_password(&user.password, &hash).await);
+        assert!(!verify_password("wrong password", &hash).await);
+        assert!(!verify_password(&user.password, "not a hash").await);
+    }
+
+    #[test]
+    fn test_user_serialization_omits_hash() {
+        let user = User {
+            id: 1,
+            email: "writer@example.com".to_string(),
+            password_hash: "$argon2id$secret".to_string(),
+            role: UserRole::Admin,
+            created_at: OffsetDateTime::now_utc(),
+        };
+
+        let json = serde_json::to_value(&user).unwrap();
+        assert!(json.get("password_hash").is_none());
+        assert!(!json.to_string().contains("secret"));
+        assert_eq!(json["role"], "admin");
+    }
+}
@@ -0,0 +1,178 @@
+//! Notifications for the site's owner, such as new contact messages. They
+//! are logged, and posted as JSON to a webhook when one is configured.
+
+use std::time::Duration;
+
+use serde::Serialize;
+use url::Url;
+
+use crate::models::contact::ContactMessage;
+
+/// How long a webhook may take to answer before delivery counts as failed
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Body posted to the webhook
+#[derive(Debug, Serialize)]
+pub struct Notification<T> {
+    /// What happened, e.g. `contact_message`
+    pub event: &'static str,
+    /// One-line summary, which chat webhooks show as the message
+    pub text: String,
+    pub data: T,
+}
+
+/// Sends notifications to the configured webhook
+#[derive(Clone)]
+pub struct Notifier {
+    webhook: Option<Url>,
+    client: reqwest::Client,
+}
+
+// Webhook URLs often embed a secret, so it is left out of debug output
+impl std::fmt::Debug for Notifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Notifier")
+            .field("webhook", &self.webhook.as_ref().map(|_| "<redacted>"))
+            .finish_non_exhaustive()
+    }
+}
+
+impl Notifier {
+    /// A notifier posting to `webhook`, or only logging when it is `None`
+    pub fn new(webhook: Option<Url>) -> Self {
+        let client = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .expect("the webhook client's settings are valid");
+        Self { webhook, client }
+    }
+
+    /// Announces a new contact message. Delivery happens in the background,
+    /// so a slow or failing webhook never holds up the sender; failures
+    /// are logged.
+    pub fn contact_message(&self, message: &ContactMessage) {
+        tracing::info!(id = message.id, "New contact message");
+        let notification = Notification {
+            event: "contact_message",
+            text: format!(
+                "New contact message from {} <{}>",
+                message.name, message.email
+            ),
+            data: message.clone(),
+        };
+        let notifier = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = notifier.send(&notification).await {
+                tracing::warn!(
+                    event = notification.event,
+                    "Failed to notify webhook: {}",
+                    e
+                );
+            }
+        });
+    }
+
+    /// Posts the notification to the webhook, if there is one, and waits
+    /// for a successful answer
+    pub async fn send<T: Serialize>(
+        &self,
+        notification: &Notification<T>,
+    ) -> Result<(), reqwest::Error> {
+        let Some(webhook) = &self.webhook else {
+            return Ok(());
+        };
+        self.client
+            .post(webhook.clone())
+            .json(notification)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    /// Serves a webhook on a local port that answers with `status` and
+    /// keeps the bodies it receives
+    async fn webhook(status: StatusCode) -> (Url, Arc<Mutex<Vec<Value>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(
+                    move |State(received): State<Arc<Mutex<Vec<Value>>>>,
+                          Json(body): Json<Value>| async move {
+                        received.lock().unwrap().push(body);
+                        status
+                    },
+                ),
+            )
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url.parse().unwrap(), received)
+    }
+
+    fn notification() -> Notification<Value> {
+        Notification {
+            event: "test",
+            text: "Something happened".to_string(),
+            data: json!({ "id": 1 }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_posts_to_webhook() {
+        let (url, received) = webhook(StatusCode::NO_CONTENT).await;
+
+        Notifier::new(Some(url))
+            .send(&notification())
+            .await
+            .unwrap();
+        assert_eq!(
+            *received.lock().unwrap(),
+            [json!({
+                "event": "test",
+                "text": "Something happened",
+                "data": { "id": 1 },
+            })]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_reports_failures() {
+        let (url, received) = webhook(StatusCode::INTERNAL_SERVER_ERROR).await;
+
+        let error = Notifier::new(Some(url))
+            .send(&notification())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.status(),
+            Some(reqwest::StatusCode::INTERNAL_SERVER_ERROR)
+        );
+        assert_eq!(received.lock().unwrap().len(), 1);
+
+        // Without a webhook there is nothing to fail
+        assert!(Notifier::new(None).send(&notification()).await.is_ok());
+    }
+
+    #[test]
+    fn test_debug_hides_webhook() {
+        let url = "https://hooks.example.com/secret-token".parse().unwrap();
+        let debug = format!("{:?}", Notifier::new(Some(url)));
+        assert!(!debug.contains("secret-token"));
+    }
+}
@@ -0,0 +1,67 @@
+//! Rendering posts' Markdown to HTML. Rendered HTML is cached in the
+//! database under a hash of the content and `RENDERER_VERSION`, see
+//! `db::RenderCacheRepository`.
+
+use pulldown_cmark::{html, Event, Options, Parser};
+
+use crate::models::sha256_hex;
+
+/// Bump whenever `render_markdown`'s output changes for the same input,
+/// such as after upgrading pulldown-cmark or enabling an extension, so
+/// that HTML cached by the old renderer is no longer served. Then call
+/// `POST /admin/render-cache/rebuild` to render every post again.
+///
+/// Version 2 escapes raw HTML, which version 1 passed through.
+pub const RENDERER_VERSION: u32 = 2;
+
+/// Renders Markdown to an HTML fragment. Tables, strikethrough and
+/// footnotes are supported. Raw HTML in the content is escaped and shown
+/// as text: any author can write posts, and the result is served from the
+/// API's origin, so passing it through would let them run scripts as
+/// whoever reads the post.
+pub fn render_markdown(content: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_FOOTNOTES;
+    let events = Parser::new_ext(content, options).map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        event => event,
+    });
+    let mut out = String::with_capacity(content.len() * 3 / 2);
+    html::push_html(&mut out, events);
+    out
+}
+
+/// Key of a rendering of `content` by version `version` of the renderer
+pub fn content_hash(content: &str, version: u32) -> String {
+    sha256_hex(format!("{}\n{}", version, content).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown() {
+        assert_eq!(
+            render_markdown("# Title\n\nSome *emphasis* and ~~less~~."),
+            "<h1>Title</h1>\n<p>Some <em>emphasis</em> and <del>less</del>.</p>\n"
+        );
+        assert!(render_markdown("| a |\n|---|\n| 1 |").contains("<table>"));
+    }
+
+    #[test]
+    fn test_render_markdown_escapes_raw_html() {
+        let html =
+            render_markdown("<script>alert(1)</script>\n\nText <img src=x onerror=alert(1)>");
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("<img"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+    }
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(content_hash("text", 1), content_hash("text", 1));
+        assert_ne!(content_hash("text", 1), content_hash("text!", 1));
+        assert_ne!(content_hash("text", 1), content_hash("text", 2));
+    }
+}
@@ -0,0 +1,344 @@
+//! Starting the server: checking and opening the database, binding the
+//! listener and serving. Every way this can fail is a `StartupError`
+//! naming the setting or file at fault, with an exit code of its own so
+//! that supervisors and deploy scripts can tell the failures apart.
+
+use std::{
+    io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use sqlx::{migrate::MigrateError, sqlite::SqliteConnectOptions};
+use tokio::net::TcpListener;
+
+use crate::{
+    app,
+    config::{Config, ConfigError},
+    db::{Database, DatabaseError},
+    handlers::admin_handlers::log_integrity_check,
+    link_checker::LinkChecker,
+};
+
+/// Why the server could not start, or stopped
+#[derive(thiserror::Error, Debug)]
+pub enum StartupError {
+    /// Settings are missing or invalid, such as an unset `DATABASE_URL`
+    #[error("{0}")]
+    Config(#[from] ConfigError),
+
+    #[error("DATABASE_URL {url:?} is not a SQLite URL: {reason}")]
+    DatabaseUrl { url: String, reason: String },
+
+    /// The database file exists, but this user can't read and write it
+    #[error(
+        "The database file {} can't be opened for reading and writing: {source}; check its owner and permissions",
+        path.display()
+    )]
+    DatabaseFile {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    /// A migration failed, or the applied ones don't match the binary's
+    #[error("Migrating the database failed at version {version}: {source}")]
+    Migration {
+        version: i64,
+        #[source]
+        source: MigrateError,
+    },
+
+    /// Opening or checking the database failed for another reason
+    #[error("Could not open the database at {url}: {source}")]
+    Database {
+        url: String,
+        #[source]
+        source: DatabaseError,
+    },
+
+    #[error(
+        "Could not bind to {addr}: port {} is already in use; stop the other process or change BIND_ADDRESS",
+        addr.port()
+    )]
+    AddressInUse { addr: SocketAddr },
+
+    #[error("Could not bind to {addr}: {source}")]
+    Bind {
+        addr: SocketAddr,
+        #[source]
+        source: io::Error,
+    },
+
+    /// The server stopped with an error after starting
+    #[error("Server error: {0}")]
+    Serve(#[source] io::Error),
+}
+
+impl StartupError {
+    /// Status the process exits with, distinct for each kind of problem:
+    /// 3 for settings, 4 for the database file, 5 for migrations, 6 for the
+    /// listening address and 1 for anything else
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            StartupError::Config(_) | StartupError::DatabaseUrl { .. } => 3,
+            StartupError::DatabaseFile { .. } | StartupError::Database { .. } => 4,
+            StartupError::Migration { .. } => 5,
+            StartupError::AddressInUse { .. } | StartupError::Bind { .. } => 6,
+            StartupError::Serve(_) => 1,
+        }
+    }
+}
+
+/// Opens and checks the database, binds `config.bind_address` and serves
+/// the API until the server fails. Nothing is listening until the
+/// database has been migrated and answered a query.
+pub async fn run(config: Config) -> Result<(), StartupError> {
+    if config.auth_disabled {
+        tracing::warn!(
+            "Authentication is disabled: every request is treated as an admin; never use this in production"
+        );
+    }
+
+    let db = open_database(&config).await?;
+    spawn_background_tasks(&db, &config);
+
+    let app = app(db, &config);
+
+    let addr = config.bind_address;
+    let listener = TcpListener::bind(addr).await.map_err(|source| {
+        if source.kind() == io::ErrorKind::AddrInUse {
+            StartupError::AddressInUse { addr }
+        } else {
+            StartupError::Bind { addr, source }
+        }
+    })?;
+    tracing::info!("Starting server on {}", addr);
+
+    // The rate limiter keys clients by their address
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .map_err(StartupError::Serve)
+}
+
+/// Checks the database URL and file, then opens the database, applies
+/// pending migrations and reads back which one it is at
+async fn open_database(config: &Config) -> Result<Database, StartupError> {
+    let url = &config.database_url;
+    let options = parse_database_url(url)?;
+    check_database_file(options.get_filename())?;
+
+    let database_error = |source| match source {
+        DatabaseError::Migration(source) => match migration_version(&source) {
+            Some(version) => StartupError::Migration { version, source },
+            None => StartupError::Database {
+                url: url.clone(),
+                source: DatabaseError::Migration(source),
+            },
+        },
+        source => StartupError::Database {
+            url: url.clone(),
+            source,
+        },
+    };
+    let db = Database::new(config).await.map_err(database_error)?;
+
+    // A query against the schema, so a database that opens but can't be
+    // read fails here rather than on the first request
+    let status = db.migration_status().await.map_err(database_error)?;
+    match status.applied.last() {
+        Some(latest) => tracing::info!(
+            "Database is at migration {} ({})",
+            latest.version,
+            latest.description
+        ),
+        None => tracing::info!("Database has no migrations applied"),
+    }
+    Ok(db)
+}
+
+/// Parses `DATABASE_URL`, which sqlx would otherwise take as a file name
+/// when it lacks the `sqlite:` scheme
+fn parse_database_url(url: &str) -> Result<SqliteConnectOptions, StartupError> {
+    let invalid = |reason: String| StartupError::DatabaseUrl {
+        url: url.to_string(),
+        reason,
+    };
+    if !url.starts_with("sqlite:") {
+        return Err(invalid(
+            "it must start with `sqlite:`, as in `sqlite://blog.db`".to_string(),
+        ));
+    }
+    SqliteConnectOptions::from_str(url).map_err(|e| invalid(e.to_string()))
+}
+
+/// Checks that an existing database file can be read and written, before
+/// the pool spends its connection retries on it. A missing file is left
+/// to SQLite, which creates it or is retried while a volume is mounted.
+fn check_database_file(path: &Path) -> Result<(), StartupError> {
+    if !path.is_file() {
+        return Ok(());
+    }
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map(drop)
+        .map_err(|source| StartupError::DatabaseFile {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+/// The migration a migration error is about, if it names one
+fn migration_version(error: &MigrateError) -> Option<i64> {
+    match error {
+        MigrateError::ExecuteMigration(_, version)
+        | MigrateError::VersionMissing(version)
+        | MigrateError::VersionMismatch(version)
+        | MigrateError::VersionNotPresent(version)
+        | MigrateError::VersionTooOld(version, _)
+        | MigrateError::VersionTooNew(version, _)
+        | MigrateError::Dirty(version) => Some(*version),
+        _ => None,
+    }
+}
+
+/// Housekeeping that runs for as long as the server does
+fn spawn_background_tasks(db: &Database, config: &Config) {
+    // Responses stored under idempotency keys are only replayed for a day;
+    // clear out the expired ones once an hour
+    let idempotency_db = db.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60 * 60));
+        loop {
+            interval.tick().await;
+            match idempotency_db.idempotency().purge_expired().await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("Purged {} expired idempotency keys", purged),
+                Err(e) => tracing::warn!("Failed to purge idempotency keys: {}", e),
+            }
+        }
+    });
+
+    // Look for corruption every so often, when configured; the first check
+    // runs one interval after startup
+    if let Some(period) = config.integrity_check_interval {
+        let integrity_db = db.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            loop {
+                interval.tick().await;
+                match integrity_db.integrity_check().await {
+                    Ok(check) => log_integrity_check(&check),
+                    Err(e) => tracing::error!("Failed to check database integrity: {}", e),
+                }
+            }
+        });
+    }
+
+    // Look for dead links in posts, when configured
+    if let Some(period) = config.link_check_interval {
+        LinkChecker::new(db.clone(), config.feed.site_url.clone()).spawn(period);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_address_in_use() {
+        let taken = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = taken.local_addr().unwrap();
+        let config = Config {
+            bind_address: addr,
+            ..Config::default_for_tests()
+        };
+
+        let error = run(config).await.unwrap_err();
+        assert!(
+            matches!(error, StartupError::AddressInUse { addr: a } if a == addr),
+            "{:?}",
+            error
+        );
+        assert_eq!(error.exit_code(), 6);
+        assert!(error.to_string().contains(&addr.port().to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_bad_database_url() {
+        for url in ["postgres://localhost/blog", "sqlite://blog.db?mode=bogus"] {
+            let config = Config {
+                database_url: url.to_string(),
+                ..Config::default_for_tests()
+            };
+
+            let error = run(config).await.unwrap_err();
+            assert!(
+                matches!(&error, StartupError::DatabaseUrl { url: u, .. } if u == url),
+                "{:?}",
+                error
+            );
+            assert_eq!(error.exit_code(), 3);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unopenable_database() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // A directory where the file should be
+        let path = dir.path().join("blog.db");
+        std::fs::create_dir(&path).unwrap();
+        let mut config = Config::default_for_tests();
+        config.database_url = format!("sqlite://{}", path.display());
+        config.pool.connect_retry = std::time::Duration::ZERO;
+        let error = run(config).await.unwrap_err();
+        assert!(
+            matches!(error, StartupError::Database { .. }),
+            "{:?}",
+            error
+        );
+        assert_eq!(error.exit_code(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_failed_migration() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("blog.db").display());
+        let db = Database::connect(&url).await.unwrap();
+        // Applied by a newer binary, so this one doesn't know it
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+             VALUES (99990101000000, 'from the future', TRUE, x'00', 0)",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        drop(db);
+
+        let config = Config {
+            database_url: url,
+            ..Config::default_for_tests()
+        };
+        let error = run(config).await.unwrap_err();
+        assert!(
+            matches!(
+                error,
+                StartupError::Migration {
+                    version: 99990101000000,
+                    ..
+                }
+            ),
+            "{:?}",
+            error
+        );
+        assert_eq!(error.exit_code(), 5);
+    }
+}
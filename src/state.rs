@@ -0,0 +1,20 @@
+use axum::extract::FromRef;
+
+use crate::{
+    auth::{AuthPolicy, FormTokens, JwtAuth},
+    db::Database,
+    feeds::FeedConfig,
+    notify::Notifier,
+};
+
+/// Shared state handed to every handler. Handlers extract only the parts
+/// they need, e.g. `State<Database>`.
+#[derive(Clone, Debug, FromRef)]
+pub struct AppState {
+    pub db: Database,
+    pub auth: JwtAuth,
+    pub auth_policy: AuthPolicy,
+    pub form_tokens: FormTokens,
+    pub feed: FeedConfig,
+    pub notifier: Notifier,
+}
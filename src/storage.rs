@@ -0,0 +1,157 @@
+//! Uploaded images, stored in the static directory under names derived
+//! from their content
+
+use std::{io, path::PathBuf};
+
+use crate::models::sha256_hex;
+
+/// Image formats accepted for upload
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageType {
+    Jpeg,
+    Png,
+    Webp,
+    Gif,
+}
+
+impl ImageType {
+    /// Identifies an image by its leading bytes. The declared content type
+    /// and file name are up to the client, so they are never trusted.
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(ImageType::Jpeg)
+        } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(ImageType::Png)
+        } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+            Some(ImageType::Webp)
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            Some(ImageType::Gif)
+        } else {
+            None
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageType::Jpeg => "jpg",
+            ImageType::Png => "png",
+            ImageType::Webp => "webp",
+            ImageType::Gif => "gif",
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum StorageError {
+    #[error("File is not a JPEG, PNG, WebP or GIF image")]
+    UnsupportedType,
+
+    #[error("Failed to store file: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// A stored image
+#[derive(Debug, PartialEq)]
+pub struct StoredImage {
+    /// File name within the store's directory
+    pub file_name: String,
+    /// Whether the same image had been stored before
+    pub existed: bool,
+}
+
+/// Stores images in a directory, named by the SHA-256 of their content so
+/// that uploading the same image twice keeps a single copy
+#[derive(Clone, Debug)]
+pub struct ImageStore {
+    dir: PathBuf,
+}
+
+impl ImageStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Stores `data` if it is a supported image, returning its file name
+    pub async fn store(&self, data: &[u8]) -> Result<StoredImage, StorageError> {
+        let image_type = ImageType::detect(data).ok_or(StorageError::UnsupportedType)?;
+        let file_name = format!("{}.{}", sha256_hex(data), image_type.extension());
+        let path = self.dir.join(&file_name);
+
+        if tokio::fs::try_exists(&path).await? {
+            return Ok(StoredImage {
+                file_name,
+                existed: true,
+            });
+        }
+
+        // Write under a temporary name first, so the file is never served
+        // half-written
+        let partial = self.dir.join(format!(".{}.partial", file_name));
+        tokio::fs::write(&partial, data).await?;
+        if let Err(e) = tokio::fs::rename(&partial, &path).await {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(e.into());
+        }
+
+        Ok(StoredImage {
+            file_name,
+            existed: false,
+        })
+    }
+}
+
+/// A valid 1x1 transparent PNG
+#[cfg(test)]
+pub(crate) const TINY_PNG: &[u8] = &[
+    0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1F, 0x15, 0xC4,
+    0x89, 0x00, 0x00, 0x00, 0x0B, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9C, 0x63, 0x60, 0x00, 0x02, 0x00,
+    0x00, 0x05, 0x00, 0x01, 0x7A, 0x5E, 0xAB, 0x3F, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44,
+    0xAE, 0x42, 0x60, 0x82,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(ImageType::detect(TINY_PNG), Some(ImageType::Png));
+        assert_eq!(
+            ImageType::detect(&[0xFF, 0xD8, 0xFF, 0xE0]),
+            Some(ImageType::Jpeg)
+        );
+        assert_eq!(ImageType::detect(b"GIF89a..."), Some(ImageType::Gif));
+        assert_eq!(
+            ImageType::detect(b"RIFF\x00\x00\x00\x00WEBPVP8 "),
+            Some(ImageType::Webp)
+        );
+        assert_eq!(ImageType::detect(b"RIFF\x00\x00\x00\x00WAVE"), None);
+        assert_eq!(ImageType::detect(b"<svg></svg>"), None);
+        assert_eq!(ImageType::detect(b""), None);
+    }
+
+    #[tokio::test]
+    async fn test_store_dedupes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ImageStore::new(dir.path());
+
+        let first = store.store(TINY_PNG).await.unwrap();
+        assert!(!first.existed);
+        assert_eq!(first.file_name, format!("{}.png", sha256_hex(TINY_PNG)));
+        assert_eq!(
+            std::fs::read(dir.path().join(&first.file_name)).unwrap(),
+            TINY_PNG
+        );
+
+        let second = store.store(TINY_PNG).await.unwrap();
+        assert!(second.existed);
+        assert_eq!(second.file_name, first.file_name);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        assert!(matches!(
+            store.store(b"not an image").await,
+            Err(StorageError::UnsupportedType)
+        ));
+    }
+}
@@ -0,0 +1,167 @@
+//! Log output: human-readable lines by default, or one JSON object per
+//! line for log aggregators
+
+use std::{fmt, str::FromStr};
+
+use tracing::{Event, Subscriber};
+use tracing_subscriber::{
+    fmt::{
+        format::{Format, Json, Writer},
+        FmtContext, FormatEvent, MakeWriter,
+    },
+    layer::SubscriberExt,
+    registry::LookupSpan,
+    util::SubscriberInitExt,
+    EnvFilter, Layer,
+};
+
+use crate::config::Config;
+
+/// How log lines are written
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Pretty,
+    /// One JSON object per line, with the event's fields at the top level
+    /// and the request's under `span`
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Installs the global subscriber, writing to stdout in the configured
+/// format at the configured level
+pub fn init(config: &Config) {
+    tracing_subscriber::registry()
+        .with(EnvFilter::new(&config.log_level))
+        .with(layer(config.log_format, std::io::stdout))
+        .init();
+}
+
+/// The formatting layer for `format`, writing to `writer`
+pub fn layer<S, W>(format: LogFormat, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer().with_writer(writer);
+    match format {
+        LogFormat::Pretty => layer.boxed(),
+        LogFormat::Json => layer
+            .json()
+            .event_format(Versioned(
+                tracing_subscriber::fmt::format()
+                    .json()
+                    .flatten_event(true)
+                    .with_current_span(true)
+                    .with_span_list(false),
+            ))
+            .boxed(),
+    }
+}
+
+/// JSON event format that adds `service.version` to every line
+struct Versioned(Format<Json>);
+
+impl<S, N> FormatEvent<S, N> for Versioned
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> tracing_subscriber::fmt::FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let mut line = String::new();
+        self.0.format_event(ctx, Writer::new(&mut line), event)?;
+
+        // Every line is a JSON object, so the field goes right after its
+        // opening brace
+        let Some(fields) = line.strip_prefix('{') else {
+            return writer.write_str(&line);
+        };
+        write!(
+            writer,
+            "{{\"service.version\":\"{}\",{}",
+            env!("CARGO_PKG_VERSION"),
+            fields
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    use serde_json::Value;
+    use tracing_subscriber::Registry;
+
+    use super::*;
+
+    /// Collects everything written to it
+    #[derive(Clone, Default)]
+    struct Sink(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Sink {
+        type Writer = Sink;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let sink = Sink::default();
+        let subscriber = Registry::default().with(layer(LogFormat::Json, sink.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", request_id = "abc", route = "/posts");
+            let _entered = span.enter();
+            tracing::info!(post_id = 5, "Created post");
+        });
+
+        let output = String::from_utf8(sink.0.lock().unwrap().clone()).unwrap();
+        let line: Value = serde_json::from_str(output.trim_end()).unwrap();
+        assert_eq!(line["message"], "Created post");
+        assert_eq!(line["post_id"], 5);
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["service.version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(line["span"]["request_id"], "abc");
+        assert_eq!(line["span"]["route"], "/posts");
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!("json".parse(), Ok(LogFormat::Json));
+        assert_eq!("Pretty".parse(), Ok(LogFormat::Pretty));
+        assert!("yaml".parse::<LogFormat>().is_err());
+    }
+}
@@ -0,0 +1,76 @@
+//! Exercises the public router the way an embedding application would
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use server::{app, config::Config, db::Database, middleware::RateLimitConfig};
+use tower::ServiceExt;
+
+fn config() -> Config {
+    Config {
+        database_url: "sqlite::memory:".to_string(),
+        pool: Default::default(),
+        sqlite: Default::default(),
+        cache_ttl: None,
+        slow_query_threshold: server::db::DEFAULT_SLOW_QUERY_THRESHOLD,
+        bind_address: "127.0.0.1:0".parse().unwrap(),
+        cors: Default::default(),
+        request_timeout: std::time::Duration::from_secs(30),
+        max_body_bytes: 2 * 1024 * 1024,
+        max_upload_bytes: 10 * 1024 * 1024,
+        max_content_bytes: 1024 * 1024,
+        lowercase_tag_names: false,
+        compression: Default::default(),
+        cache_control: Default::default(),
+        feed: Default::default(),
+        robots_disallow: false,
+        static_dir: None,
+        backup_dir: None,
+        integrity_check_interval: None,
+        link_check_interval: None,
+        log_level: "info".to_string(),
+        log_format: Default::default(),
+        jwt_secret: "integration-secret".to_string(),
+        jwt_expiry: time::Duration::hours(1),
+        api_key: None,
+        auth_disabled: false,
+        system_user_id: None,
+        rate_limit: RateLimitConfig::default(),
+        notify_webhook_url: None,
+    }
+}
+
+#[tokio::test]
+async fn test_app_can_be_nested() {
+    let config = config();
+    let db = Database::connect(&config.database_url).await.unwrap();
+    let router = Router::new().nest("/api", app(db, &config));
+
+    let response = router
+        .clone()
+        .oneshot(Request::get("/api/posts").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Trailing slashes are trimmed inside the nested router too
+    let response = router
+        .clone()
+        .oneshot(Request::get("/api/posts/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Writes still need credentials
+    let response = router
+        .oneshot(
+            Request::delete("/api/tags/unused")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}